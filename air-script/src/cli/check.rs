@@ -0,0 +1,43 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::Args;
+
+/// The interval at which the input file is polled for changes in watch mode.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Args)]
+pub struct Check {
+    /// Path to input file
+    input: PathBuf,
+
+    #[arg(
+        short,
+        long,
+        help = "Keeps running, checking the input file again each time it changes"
+    )]
+    watch: bool,
+}
+
+impl Check {
+    pub fn execute(&self) -> Result<(), String> {
+        if self.watch {
+            println!("Watching {} for changes...", self.input.display());
+            air_script::Watcher::new(&self.input).run(WATCH_INTERVAL, |path, result| {
+                if let Ok(air) = result {
+                    println!("{} has no errors", path.display());
+                    println!("{}", air.trace_length_constraints());
+                }
+                println!("============================================================");
+            });
+        }
+
+        match air_script::check(&self.input) {
+            Ok(air) => {
+                println!("{} has no errors", self.input.display());
+                println!("{}", air.trace_length_constraints());
+                Ok(())
+            },
+            Err(_) => Err("compilation failed".into()),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+#[derive(Args)]
+pub struct Diff {
+    /// Path to the old version of the input file
+    old: PathBuf,
+
+    /// Path to the new version of the input file
+    new: PathBuf,
+}
+
+impl Diff {
+    pub fn execute(&self) -> Result<(), String> {
+        let old = air_script::check(&self.old)
+            .map_err(|_| format!("compilation of {} failed", self.old.display()))?;
+        let new = air_script::check(&self.new)
+            .map_err(|_| format!("compilation of {} failed", self.new.display()))?;
+
+        let diff = air_ir::diff(&old, &new);
+        print!("{diff}");
+        if !diff.is_empty() && diff.is_equivalent() {
+            println!("The constraints are equivalent");
+        }
+
+        Ok(())
+    }
+}
@@ -1,2 +1,8 @@
+mod check;
+mod diff;
+mod symbols;
 mod transpile;
+pub use check::Check;
+pub use diff::Diff;
+pub use symbols::Symbols;
 pub use transpile::Transpile;
@@ -0,0 +1,42 @@
+use std::{path::PathBuf, sync::Arc};
+
+use air_parser::analysis::symbol_table_from_file;
+use clap::Args;
+use miden_diagnostics::{
+    CodeMap, DefaultEmitter, DiagnosticsHandler, Spanned, term::termcolor::ColorChoice,
+};
+
+#[derive(Args)]
+pub struct Symbols {
+    /// Path to input file
+    input: PathBuf,
+
+    #[arg(long, help = "Prints the symbols as JSON, for use by editor tooling")]
+    json: bool,
+}
+
+impl Symbols {
+    pub fn execute(&self) -> Result<(), String> {
+        let codemap = Arc::new(CodeMap::new());
+        let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+        let diagnostics = DiagnosticsHandler::new(Default::default(), codemap.clone(), emitter);
+
+        // Diagnostics are reported on stderr, the symbols are printed even if there are errors
+        let symbols = symbol_table_from_file(&diagnostics, codemap.clone(), &self.input)
+            .map_err(|_| "parsing failed".to_string())?;
+
+        if self.json {
+            println!("{}", symbols.to_json(&codemap));
+        } else {
+            for symbol in symbols.symbols.iter() {
+                let location = codemap
+                    .location(&symbol.name.span())
+                    .map(|loc| format!("{}:{}", loc.line.to_usize() + 1, loc.column.to_usize() + 1))
+                    .unwrap_or_default();
+                println!("{} {}::{} {}", symbol.kind.name(), symbol.module, symbol.name, location);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use std::{fs, path::PathBuf, sync::Arc};
 
-use air_ir::{CodeGenerator, CompileError};
+use air_ir::{CodeGenerator, CompileError, PassConfig, UnrollLimits};
 use air_pass::Pass;
 use clap::{Args, ValueEnum};
 use miden_diagnostics::{
@@ -10,11 +10,14 @@ use miden_diagnostics::{
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum Target {
     Winterfell,
+    Plonky3,
+    Markdown,
 }
 impl Target {
     pub fn extension(&self) -> &'static str {
         match self {
-            Self::Winterfell => "rs",
+            Self::Winterfell | Self::Plonky3 => "rs",
+            Self::Markdown => "md",
         }
     }
 }
@@ -24,6 +27,12 @@ pub enum Pipeline {
     WithoutMIR,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Lint {
+    CopyColumns,
+    RecurrenceBoundaries,
+}
+
 #[derive(Args)]
 pub struct Transpile {
     /// Path to input file
@@ -32,11 +41,15 @@ pub struct Transpile {
     #[arg(
         short,
         long,
-        help = "Output filename, defaults to the input file with the .rs extension for Winterfell"
+        help = "Output filename, defaults to the input file with the .rs extension for Winterfell and Plonky3, or the .md extension for Markdown"
     )]
     output: Option<PathBuf>,
 
-    #[arg(short, long, help = "Defines the target language, defaults to Winterfell")]
+    #[arg(
+        short,
+        long,
+        help = "Defines the target language, defaults to Winterfell. Markdown emits a human-readable specification of the constraints"
+    )]
     target: Option<Target>,
 
     #[arg(
@@ -45,9 +58,103 @@ pub struct Transpile {
         help = "Defines the compilation pipeline (WithMIR or WithoutMIR), defaults to WithMIR"
     )]
     pipeline: Option<Pipeline>,
+
+    #[arg(
+        long,
+        help = "Maximum number of iterations of a single comprehension unrolled by the MIR pipeline, defaults to 10000"
+    )]
+    max_unroll: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Maximum number of operations produced by unrolling all comprehensions in the MIR pipeline, defaults to 1000000"
+    )]
+    max_unrolled_ops: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Maximum exponent of an exponentiation expanded by the MIR pipeline, defaults to 64"
+    )]
+    max_exponent: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Canonicalizes the constraint graph and removes duplicate integrity constraints in the MIR pipeline"
+    )]
+    canonicalize: bool,
+
+    #[arg(
+        long,
+        help = "Removes the random values which no constraint depends on, and renumbers the others densely, in the MIR pipeline"
+    )]
+    dead_random_values: bool,
+
+    #[arg(
+        long,
+        help = "Keeps all the random values, even with --dead-random-values, as they are coordinated with another party, e.g. a verifier shared with other programs"
+    )]
+    export_random_values: bool,
+
+    #[arg(
+        long,
+        help = "Writes a reproduction of internal compiler errors in the MIR pipeline to this file, to attach to a bug report"
+    )]
+    repro: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Only keeps the constraints with a label matching this glob, e.g. 'stack_*', where the labels of a constraint are the evaluators it was inlined from and the bus it enforces"
+    )]
+    only_label: Option<String>,
+
+    #[arg(
+        long,
+        help = "Enables an opt-in lint of the MIR pipeline, may be repeated. copy-columns warns about the columns which are copies of an expression of the previous row, recurrence-boundaries warns about the columns defined by a recurrence whose first row is not constrained"
+    )]
+    lint: Vec<Lint>,
 }
 
 impl Transpile {
+    /// The options affecting the generated code, recorded in its provenance header.
+    fn compile_options(&self, target: Target, pipeline: Pipeline) -> Vec<String> {
+        let target = match target {
+            Target::Winterfell => "winterfell",
+            Target::Plonky3 => "plonky3",
+            Target::Markdown => "markdown",
+        };
+        let pipeline_name = match pipeline {
+            Pipeline::WithMIR => "WithMIR",
+            Pipeline::WithoutMIR => "WithoutMIR",
+        };
+        let mut options = vec![format!("target={target}"), format!("pipeline={pipeline_name}")];
+        if pipeline == Pipeline::WithMIR {
+            options.extend(self.pass_config().options());
+        }
+        if let Some(pattern) = &self.only_label {
+            options.push(format!("only-label={pattern}"));
+        }
+        options
+    }
+
+    /// The configuration of the passes of the MIR pipeline.
+    fn pass_config(&self) -> PassConfig {
+        let defaults = UnrollLimits::default();
+        let unroll_limits = UnrollLimits {
+            max_iterations: self.max_unroll.unwrap_or(defaults.max_iterations),
+            max_ops: self.max_unrolled_ops.unwrap_or(defaults.max_ops),
+        };
+        PassConfig {
+            canonicalize: self.canonicalize,
+            dead_random_values: self.dead_random_values,
+            export_random_values: self.export_random_values,
+            unroll_limits,
+            max_exponent: self.max_exponent.unwrap_or(air_ir::passes::DEFAULT_MAX_EXPONENT),
+            recurrence_boundaries: self.lint.contains(&Lint::RecurrenceBoundaries),
+            copy_columns: self.lint.contains(&Lint::CopyColumns),
+            ..Default::default()
+        }
+    }
+
     pub fn execute(&self) -> Result<(), String> {
         println!("============================================================");
 
@@ -58,43 +165,53 @@ impl Transpile {
         let diagnostics = DiagnosticsHandler::new(Default::default(), codemap.clone(), emitter);
 
         let pipeline = self.pipeline.unwrap_or(Pipeline::WithMIR);
+        let target = self.target.unwrap_or(Target::Winterfell);
         // Parse from file to internal representation
-        let air = match pipeline {
+        let program =
+            air_parser::parse_file(&diagnostics, codemap, input_path).map_err(CompileError::Parse);
+        let provenance = program
+            .as_ref()
+            .ok()
+            .map(|program| air_script::provenance(program, self.compile_options(target, pipeline)));
+        let air = program.and_then(|ast| match pipeline {
             Pipeline::WithMIR => {
                 println!("Transpiling with Mir pipeline...");
-                air_parser::parse_file(&diagnostics, codemap, input_path)
-                    .map_err(CompileError::Parse)
-                    .and_then(|ast| {
-                        let mut pipeline =
-                            air_parser::transforms::ConstantPropagation::new(&diagnostics)
-                                .chain(mir::passes::AstToMir::new(&diagnostics))
-                                .chain(mir::passes::Inlining::new(&diagnostics))
-                                .chain(mir::passes::Unrolling::new(&diagnostics))
-                                .chain(air_ir::passes::MirToAir::new(&diagnostics))
-                                .chain(air_ir::passes::BusOpExpand::new(&diagnostics));
-                        pipeline.run(ast)
-                    })
+                match &self.repro {
+                    Some(repro) => {
+                        air_ir::compile_with_repro(&diagnostics, ast, self.pass_config(), repro)
+                    },
+                    None => air_ir::compile(&diagnostics, ast, self.pass_config()),
+                }
             },
             Pipeline::WithoutMIR => {
                 println!("Transpiling without Mir pipeline...");
-                air_parser::parse_file(&diagnostics, codemap, input_path)
-                    .map_err(CompileError::Parse)
-                    .and_then(|ast| {
-                        let mut pipeline =
-                            air_parser::transforms::ConstantPropagation::new(&diagnostics)
-                                .chain(air_parser::transforms::Inlining::new(&diagnostics))
-                                .chain(air_ir::passes::AstToAir::new(&diagnostics));
-                        pipeline.run(ast)
-                    })
+                let mut pipeline = air_parser::transforms::ConstantPropagation::new(&diagnostics)
+                    .chain(air_parser::transforms::Inlining::new(&diagnostics))
+                    .chain(air_ir::passes::AstToAir::new(&diagnostics))
+                    .chain(air_ir::passes::Simplify::new(&diagnostics));
+                pipeline.run(ast)
             },
-        };
+        });
+
+        let air = air.map(|air| match &self.only_label {
+            Some(pattern) => air.filter(|constraint| constraint.matches_label(pattern)),
+            None => air,
+        });
 
         match air {
             Ok(air) => {
-                // generate Rust code targeting Winterfell
-                let target = self.target.unwrap_or(Target::Winterfell);
+                // generate Rust code for the target, recording the provenance of the sources
+                let provenance = provenance.expect("compiled programs have a provenance");
                 let backend: Box<dyn CodeGenerator<Output = String>> = match target {
-                    Target::Winterfell => Box::new(air_codegen_winter::CodeGenerator),
+                    Target::Winterfell => Box::new(
+                        air_codegen_winter::CodeGenerator::default().with_provenance(provenance),
+                    ),
+                    Target::Plonky3 => Box::new(
+                        air_codegen_plonky3::CodeGenerator::default().with_provenance(provenance),
+                    ),
+                    Target::Markdown => Box::new(
+                        air_codegen_markdown::CodeGenerator::default().with_provenance(provenance),
+                    ),
                 };
 
                 // write transpiled output to the output path
@@ -0,0 +1,143 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use air_ir::{Air, CompileError};
+use miden_diagnostics::{
+    Emitter, NullEmitter, Severity,
+    term::termcolor::{Buffer, ColorChoice},
+};
+
+use crate::check_with_emitter;
+
+/// A diagnostic emitted while compiling, as collected by a [CollectingEmitter].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectedDiagnostic {
+    /// The severity of the diagnostic, after `warnings_as_errors` was applied by the handler
+    pub severity: Severity,
+    /// The code of the diagnostic, e.g. `E0001`, if it has one
+    pub code: Option<String>,
+    /// The message of the diagnostic, without its labels and notes
+    pub message: String,
+    /// The diagnostic as it is displayed, without colors
+    pub rendered: String,
+}
+
+/// An [Emitter] which collects the diagnostics it is given, before passing them on to another
+/// [Emitter], so that tools can inspect the diagnostics of a compilation without scraping its
+/// output.
+///
+/// A [DiagnosticsHandler](miden_diagnostics::DiagnosticsHandler) only gives its emitter the
+/// rendering of each diagnostic, so the severity, code and message are read back from it.
+pub struct CollectingEmitter {
+    inner: Arc<dyn Emitter>,
+    collected: Mutex<Vec<CollectedDiagnostic>>,
+}
+impl CollectingEmitter {
+    /// Creates a [CollectingEmitter] passing the diagnostics on to `inner`.
+    pub fn new(inner: Arc<dyn Emitter>) -> Self {
+        Self { inner, collected: Mutex::new(vec![]) }
+    }
+
+    /// Creates a [CollectingEmitter] which only collects the diagnostics, without displaying them.
+    pub fn silent() -> Self {
+        Self::new(Arc::new(NullEmitter::new(ColorChoice::Never)))
+    }
+
+    /// Returns the diagnostics collected so far, in the order in which they were emitted, and
+    /// clears them.
+    pub fn take(&self) -> Vec<CollectedDiagnostic> {
+        std::mem::take(&mut *self.collected.lock().unwrap())
+    }
+}
+impl Emitter for CollectingEmitter {
+    fn buffer(&self) -> Buffer {
+        self.inner.buffer()
+    }
+
+    fn print(&self, buffer: Buffer) -> std::io::Result<()> {
+        let rendered = strip_ansi(&String::from_utf8_lossy(buffer.as_slice()));
+        // Messages which are not diagnostics, e.g. `DiagnosticsHandler::info`, are not collected
+        if let Some(diagnostic) = parse_rendered(rendered) {
+            self.collected.lock().unwrap().push(diagnostic);
+        }
+        self.inner.print(buffer)
+    }
+}
+
+/// The result of [check_collecting], with the diagnostics emitted while compiling.
+#[derive(Debug)]
+pub struct CheckOutput {
+    pub result: Result<Air, CompileError>,
+    /// The diagnostics, in the order in which they were emitted, including the errors which made
+    /// the compilation fail
+    pub diagnostics: Vec<CollectedDiagnostic>,
+}
+impl CheckOutput {
+    /// Returns the number of diagnostics of the given severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    }
+
+    /// Returns the diagnostics of the given severity.
+    pub fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &CollectedDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(move |diagnostic| diagnostic.severity == severity)
+    }
+}
+
+/// Same as [check](crate::check), collecting the diagnostics instead of emitting them to stderr.
+pub fn check_collecting(path: &Path) -> CheckOutput {
+    let emitter = Arc::new(CollectingEmitter::silent());
+    let result = check_with_emitter(path, emitter.clone());
+    CheckOutput { result, diagnostics: emitter.take() }
+}
+
+/// Reads a diagnostic back from its rendering, whose first line is e.g. `warning: message` or
+/// `error[E0001]: message`.
+fn parse_rendered(rendered: String) -> Option<CollectedDiagnostic> {
+    let header = rendered.lines().next()?;
+    let (prefix, message) = header.split_once(": ")?;
+    let (severity, code) = match prefix.split_once('[') {
+        Some((severity, code)) => (severity, Some(code.strip_suffix(']')?.to_string())),
+        None => (prefix, None),
+    };
+    let severity = match severity {
+        "bug" => Severity::Bug,
+        "error" => Severity::Error,
+        "warning" => Severity::Warning,
+        "note" => Severity::Note,
+        "help" => Severity::Help,
+        _ => return None,
+    };
+    Some(CollectedDiagnostic {
+        severity,
+        code,
+        message: message.to_string(),
+        rendered,
+    })
+}
+
+/// Removes the ANSI escape sequences setting the colors of a rendering, e.g. `\x1b[1;31m`.
+fn strip_ansi(rendered: &str) -> String {
+    let mut stripped = String::with_capacity(rendered.len());
+    let mut chars = rendered.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip the parameters of the sequence up to its final character, e.g. `m`
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
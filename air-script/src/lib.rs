@@ -2,3 +2,11 @@ pub use air_codegen_winter::CodeGenerator as WinterfellCodeGenerator;
 pub use air_ir::{Air, CompileError, passes};
 pub use air_parser::{parse, parse_file, transforms};
 pub use air_pass::Pass;
+pub use diagnostics::{CheckOutput, CollectedDiagnostic, CollectingEmitter, check_collecting};
+pub use provenance::{COMPILER_VERSION, provenance, source_hash, verify_provenance};
+
+mod diagnostics;
+mod provenance;
+pub use watch::{Watcher, check, check_with_emitter};
+
+mod watch;
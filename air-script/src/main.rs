@@ -7,15 +7,26 @@ mod cli;
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[command(arg_required_else_help = true)]
 pub struct Cli {
     #[command(subcommand)]
-    command: Command,
+    command: Option<Command>,
+
+    /// Print a detailed explanation of the diagnostic with the given error code, e.g. E0001
+    #[arg(long, value_name = "CODE", exclusive = true)]
+    explain: Option<String>,
 }
 
 #[derive(Subcommand)]
 pub enum Command {
     /// Transpile AirScript source code to Rust targeting Winterfell
     Transpile(cli::Transpile),
+    /// Check AirScript source code for errors, without generating any code
+    Check(cli::Check),
+    /// Compare the constraints of two versions of AirScript source code
+    Diff(cli::Diff),
+    /// List the symbols declared in AirScript source code, for use by editor tooling
+    Symbols(cli::Symbols),
 }
 
 pub fn main() {
@@ -26,11 +37,27 @@ pub fn main() {
 
     let cli = Cli::parse();
 
-    let res = match cli.command {
-        Command::Transpile(transpile) => transpile.execute(),
+    let res = match (cli.command, cli.explain) {
+        (_, Some(code)) => explain(&code),
+        (Some(Command::Transpile(transpile)), _) => transpile.execute(),
+        (Some(Command::Check(check)), _) => check.execute(),
+        (Some(Command::Diff(diff)), _) => diff.execute(),
+        (Some(Command::Symbols(symbols)), _) => symbols.execute(),
+        (None, None) => unreachable!("clap requires a subcommand or --explain"),
     };
 
     if let Err(error) = res {
         println!("{error}");
     }
 }
+
+/// Prints the explanation registered for the error code `code`.
+fn explain(code: &str) -> Result<(), String> {
+    match air_parser::error_codes::explain(code) {
+        Some(explanation) => {
+            print!("{explanation}");
+            Ok(())
+        },
+        None => Err(format!("error: no explanation is registered for error code '{code}'")),
+    }
+}
@@ -0,0 +1,63 @@
+use std::{fs, path::Path, sync::Arc};
+
+use air_ir::Provenance;
+use air_parser::ast::Program;
+use miden_diagnostics::{
+    CodeMap, DiagnosticsHandler, FileName, NullEmitter, term::termcolor::ColorChoice,
+};
+
+/// The version of the AirScript compiler recorded in the [Provenance] of generated artifacts.
+pub const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Computes the hex-encoded blake3 hash of the canonicalized sources of `program`.
+///
+/// The canonical form is the pretty-printed [Program] after import resolution, so the hash only
+/// depends on the items the program uses, and not on how they are split across files or formatted.
+pub fn source_hash(program: &Program) -> String {
+    blake3::hash(program.to_string().as_bytes()).to_hex().to_string()
+}
+
+/// Builds the [Provenance] of artifacts generated from `program` with the given compile options.
+pub fn provenance(program: &Program, options: Vec<String>) -> Provenance {
+    Provenance {
+        compiler_version: COMPILER_VERSION.to_string(),
+        source_hash: source_hash(program),
+        options,
+    }
+}
+
+/// Checks that an artifact was generated from the given sources, by recomputing the hash of the
+/// sources and comparing it to the one recorded in the provenance header of the artifact.
+///
+/// The first of `source_files` is the root module of the program, the remaining ones are the
+/// modules it imports. Returns `false` if the header has no provenance, or if the sources fail to
+/// parse.
+pub fn verify_provenance<P: AsRef<Path>>(artifact_header: &str, source_files: &[P]) -> bool {
+    let Some(provenance) = Provenance::from_header(artifact_header) else {
+        return false;
+    };
+    let Some((root, imports)) = source_files.split_first() else {
+        return false;
+    };
+
+    let codemap = Arc::new(CodeMap::new());
+    let emitter = Arc::new(NullEmitter::new(ColorChoice::Never));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), codemap.clone(), emitter);
+
+    // Imports are resolved next to the root module, looking into the codemap before the disk, so
+    // register the provided modules there, wherever they are actually stored
+    let root = root.as_ref();
+    let source_dir = root.parent().unwrap_or_else(|| Path::new("."));
+    for import in imports {
+        let import = import.as_ref();
+        let (Some(file_name), Ok(source)) = (import.file_name(), fs::read_to_string(import)) else {
+            return false;
+        };
+        codemap.add(FileName::Real(source_dir.join(file_name)), source);
+    }
+
+    match air_parser::parse_file(&diagnostics, codemap, root) {
+        Ok(program) => source_hash(&program) == provenance.source_hash,
+        Err(_) => false,
+    }
+}
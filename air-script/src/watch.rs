@@ -0,0 +1,88 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use air_ir::{Air, CompileError, PassConfig};
+use miden_diagnostics::{
+    CodeMap, DefaultEmitter, DiagnosticsHandler, Emitter, term::termcolor::ColorChoice,
+};
+
+/// Compiles the AirScript file at `path` with the default pipeline, without generating any code,
+/// emitting diagnostics to stderr. On failure, the errors have been reported as diagnostics.
+pub fn check(path: &Path) -> Result<Air, CompileError> {
+    check_with_emitter(path, Arc::new(DefaultEmitter::new(ColorChoice::Auto)))
+}
+
+/// Same as [check], emitting diagnostics with the given [Emitter].
+///
+/// A fresh [CodeMap] and [DiagnosticsHandler] are created for each check, so that the sources and
+/// diagnostics of a previous check do not leak into the next one.
+pub fn check_with_emitter(path: &Path, emitter: Arc<dyn Emitter>) -> Result<Air, CompileError> {
+    let codemap = Arc::new(CodeMap::new());
+    let diagnostics = DiagnosticsHandler::new(Default::default(), codemap.clone(), emitter);
+
+    let air = air_parser::parse_file(&diagnostics, codemap, path)
+        .map_err(CompileError::Parse)
+        .and_then(|ast| air_ir::compile(&diagnostics, ast, PassConfig::default()));
+    air.map_err(|err| {
+        diagnostics.emit(err);
+        CompileError::Failed
+    })
+}
+
+/// Watches an AirScript file, checking it again each time its contents change.
+///
+/// Changes are detected by polling the contents of the file, so that saving a file without
+/// modifying it does not trigger a new check. A file which is missing is skipped until it exists
+/// again, and a file which does not compile, e.g. because it is only partially written, only
+/// results in a failed check: the watcher keeps going.
+pub struct Watcher {
+    path: PathBuf,
+    emitter: Arc<dyn Emitter>,
+    last_source: Option<String>,
+}
+impl Watcher {
+    /// Creates a [Watcher] for the file at `path`, emitting diagnostics to stderr.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self::with_emitter(path, Arc::new(DefaultEmitter::new(ColorChoice::Auto)))
+    }
+
+    /// Creates a [Watcher] for the file at `path`, emitting diagnostics with the given [Emitter].
+    pub fn with_emitter(path: impl Into<PathBuf>, emitter: Arc<dyn Emitter>) -> Self {
+        Self {
+            path: path.into(),
+            emitter,
+            last_source: None,
+        }
+    }
+
+    /// Checks the file if its contents changed since the last check, or if it was never checked.
+    ///
+    /// Returns `None` if the file did not change, or could not be read.
+    pub fn poll(&mut self) -> Option<Result<Air, CompileError>> {
+        let source = fs::read_to_string(&self.path).ok()?;
+        if self.last_source.as_ref() == Some(&source) {
+            return None;
+        }
+        self.last_source = Some(source);
+        Some(check_with_emitter(&self.path, self.emitter.clone()))
+    }
+
+    /// Polls the file every `interval` forever, calling `on_check` with the result of each check.
+    pub fn run(
+        mut self,
+        interval: Duration,
+        mut on_check: impl FnMut(&Path, Result<Air, CompileError>),
+    ) -> ! {
+        loop {
+            if let Some(result) = self.poll() {
+                on_check(&self.path, result);
+            }
+            thread::sleep(interval);
+        }
+    }
+}
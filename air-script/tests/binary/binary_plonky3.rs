@@ -0,0 +1,30 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, BaseAirWithPublicValues};
+use p3_field::PrimeCharacteristicRing;
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+
+pub struct BinaryAir;
+
+impl<F> BaseAir<F> for BinaryAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for BinaryAir {
+    fn num_public_values(&self) -> usize {
+        16
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues<F = Goldilocks>> Air<AB> for BinaryAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let main_current: Vec<AB::Expr> = main.row_slice(0).unwrap().iter().map(|&v| v.into()).collect();
+        let public_values: Vec<AB::Expr> = builder.public_values().iter().map(|&v| v.into()).collect();
+        let stack_inputs = &public_values[0..16];
+        builder.when_first_row().assert_zero(main_current[0].clone() - AB::Expr::ZERO);
+        builder.assert_zero(main_current[0].clone() * main_current[0].clone() - main_current[0].clone() - AB::Expr::ZERO);
+        builder.assert_zero(main_current[1].clone() * main_current[1].clone() - main_current[1].clone() - AB::Expr::ZERO);
+    }
+}
\ No newline at end of file
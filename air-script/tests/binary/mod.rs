@@ -1,4 +1,11 @@
 #[rustfmt::skip]
 #[allow(clippy::all)]
 mod binary;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+#[allow(unused_imports)]
+mod binary_plonky3 {
+    use crate::helpers::plonky3::*;
+    include!("binary_plonky3.rs");
+}
 mod test_air;
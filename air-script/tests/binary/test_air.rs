@@ -1,5 +1,5 @@
 use winter_air::Air;
-use winter_math::fields::f64::BaseElement as Felt;
+use winter_math::{ToElements, fields::f64::BaseElement as Felt};
 use winterfell::{Trace, TraceTable};
 
 use crate::{
@@ -36,6 +36,10 @@ impl AirTester for BinaryAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/binary/binary.air")
+    }
 }
 
 #[test]
@@ -51,4 +55,23 @@ fn test_binary_air() {
 
     let air = BinaryAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<BinaryAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+#[test]
+fn test_binary_plonky3_air() {
+    use crate::{
+        binary::binary_plonky3::BinaryAir,
+        helpers::plonky3::{check_constraints, main_rows},
+    };
+
+    let air_tester = Box::new(BinaryAirTester {});
+    let mut trace = main_rows(&air_tester.build_main_trace(1024));
+    let public_values = air_tester.public_inputs().to_elements();
+    assert_eq!(check_constraints(&BinaryAir, &trace, &public_values), Vec::<usize>::new());
+
+    // `b` is no longer binary on row 7, and `a` no longer starts at 0
+    trace[7][1] = Felt::new(2);
+    trace[0][0] = Felt::new(1);
+    assert_eq!(check_constraints(&BinaryAir, &trace, &public_values), [0, 7]);
 }
@@ -47,6 +47,10 @@ impl AirTester for BitwiseAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/bitwise/bitwise.air")
+    }
 }
 
 #[test]
@@ -62,4 +66,5 @@ fn test_bitwise_air() {
 
     let air = BitwiseAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<BitwiseAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -3,6 +3,11 @@ use winter_math::fields::f64::BaseElement as Felt;
 use winter_math::{ExtensionOf, FieldElement, ToElements};
 use winter_utils::{ByteWriter, Serializable};
 
+/// The public inputs of the AIR.
+///
+/// They are serialized and flattened by `to_elements` in the canonical order, sorted by name,
+/// which is the order in which every verifier absorbs them into the transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PublicInputs {
     inputs: [Felt; 2],
 }
@@ -3,11 +3,19 @@ use winter_math::fields::f64::BaseElement as Felt;
 use winter_math::{ExtensionOf, FieldElement, ToElements};
 use winter_utils::{ByteWriter, Serializable};
 
+/// The public inputs of the AIR.
+///
+/// They are serialized and flattened by `to_elements` in the canonical order, sorted by name,
+/// which is the order in which every verifier absorbs them into the transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PublicInputs {
     inputs: Vec<[Felt; 2]>,
 }
 
 impl PublicInputs {
+    /// Each row of a table is one tuple of the bus initialized or finalized by it. The order of the
+    /// rows does not affect the bus boundaries, but `to_elements` flattens them in order, so the
+    /// prover and the verifier must be given the rows in the same order.
     pub fn new(inputs: Vec<[Felt; 2]>) -> Self {
         Self { inputs }
     }
@@ -0,0 +1,116 @@
+use winter_air::{Air, AirContext, Assertion, AuxRandElements, EvaluationFrame, ProofOptions as WinterProofOptions, TransitionConstraintDegree, TraceInfo};
+use winter_math::fields::f64::BaseElement as Felt;
+use winter_math::{ExtensionOf, FieldElement, ToElements};
+use winter_utils::{ByteWriter, Serializable};
+
+/// The public inputs of the AIR.
+///
+/// They are serialized and flattened by `to_elements` in the canonical order, sorted by name,
+/// which is the order in which every verifier absorbs them into the transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicInputs {
+    inputs: [Felt; 2],
+    left_inputs: [Felt; 1],
+    right_inputs: [Felt; 1],
+}
+
+impl PublicInputs {
+    pub fn new(inputs: [Felt; 2], left_inputs: [Felt; 1], right_inputs: [Felt; 1]) -> Self {
+        Self { inputs, left_inputs, right_inputs }
+    }
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.inputs.write_into(target);
+        self.left_inputs.write_into(target);
+        self.right_inputs.write_into(target);
+    }
+}
+
+impl ToElements<Felt> for PublicInputs {
+    fn to_elements(&self) -> Vec<Felt> {
+        let mut elements = Vec::new();
+        elements.extend_from_slice(&self.inputs);
+        elements.extend_from_slice(&self.left_inputs);
+        elements.extend_from_slice(&self.right_inputs);
+        elements
+    }
+}
+
+pub struct LinkedBusesAir {
+    context: AirContext<Felt>,
+    inputs: [Felt; 2],
+    left_inputs: [Felt; 1],
+    right_inputs: [Felt; 1],
+}
+
+impl LinkedBusesAir {
+    pub fn last_step(&self) -> usize {
+        self.trace_length() - self.context().num_transition_exemptions()
+    }
+}
+
+impl Air for LinkedBusesAir {
+    type BaseField = Felt;
+    type PublicInputs = PublicInputs;
+
+    fn context(&self) -> &AirContext<Felt> {
+        &self.context
+    }
+
+    fn new(trace_info: TraceInfo, public_inputs: PublicInputs, options: WinterProofOptions) -> Self {
+        let main_degrees = vec![TransitionConstraintDegree::new(2), TransitionConstraintDegree::new(2), TransitionConstraintDegree::new(2)];
+        let aux_degrees = vec![TransitionConstraintDegree::new(7)];
+        let num_main_assertions = 2;
+        let num_aux_assertions = 2;
+
+        let context = AirContext::new_multi_segment(
+            trace_info,
+            main_degrees,
+            aux_degrees,
+            num_main_assertions,
+            num_aux_assertions,
+            options,
+        )
+        .set_num_transition_exemptions(2);
+        Self { context, inputs: public_inputs.inputs, left_inputs: public_inputs.left_inputs, right_inputs: public_inputs.right_inputs }
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Felt>> {
+        vec![]
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Felt>> {
+        let mut result = Vec::new();
+        result.push(Assertion::single(2, 0, Felt::ONE));
+        result.push(Assertion::single(4, 0, Felt::ONE));
+        result
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = Felt>>(&self, aux_rand_elements: &AuxRandElements<E>) -> Vec<Assertion<E>> {
+        let mut result = Vec::new();
+        result.push(Assertion::single(0, 0, E::ONE));
+        result.push(Assertion::single(0, self.last_step(), E::ONE));
+        result
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = Felt>>(&self, frame: &EvaluationFrame<E>, periodic_values: &[E], result: &mut [E]) {
+        let main_current = frame.current();
+        let main_next = frame.next();
+        result[0] = main_current[0] * main_current[0] - main_current[0];
+        result[1] = main_current[2] * main_current[2] - main_current[2];
+        result[2] = main_current[4] * main_current[4] - main_current[4];
+    }
+
+    fn evaluate_aux_transition<F, E>(&self, main_frame: &EvaluationFrame<F>, aux_frame: &EvaluationFrame<E>, _periodic_values: &[F], aux_rand_elements: &AuxRandElements<E>, result: &mut [E])
+    where F: FieldElement<BaseField = Felt>,
+          E: FieldElement<BaseField = Felt> + ExtensionOf<F>,
+    {
+        let main_current = main_frame.current();
+        let main_next = main_frame.next();
+        let aux_current = aux_frame.current();
+        let aux_next = aux_frame.next();
+        result[0] = ((aux_rand_elements.rand_elements()[0] + E::from(main_current[1]) * aux_rand_elements.rand_elements()[1]) * E::from(main_current[0]) + E::ONE - E::from(main_current[0])) * ((aux_rand_elements.rand_elements()[0] + E::from(main_current[3]) * aux_rand_elements.rand_elements()[1]) * (E::ONE - E::from(main_current[2])) + E::ONE - (E::ONE - E::from(main_current[2]))) * ((aux_rand_elements.rand_elements()[0] + E::from(main_current[5]) * aux_rand_elements.rand_elements()[1]) * E::from(main_current[4]) + E::ONE - E::from(main_current[4])) * aux_current[0] - ((aux_rand_elements.rand_elements()[0] + E::from(main_current[1]) * aux_rand_elements.rand_elements()[1]) * (E::ONE - E::from(main_current[0])) + E::ONE - (E::ONE - E::from(main_current[0]))) * ((aux_rand_elements.rand_elements()[0] + E::from(main_current[3]) * aux_rand_elements.rand_elements()[1]) * E::from(main_current[2]) + E::ONE - E::from(main_current[2])) * ((aux_rand_elements.rand_elements()[0] + E::from(main_current[6]) * aux_rand_elements.rand_elements()[1]) * E::from(main_current[4]) + E::ONE - E::from(main_current[4])) * aux_next[0];
+    }
+}
\ No newline at end of file
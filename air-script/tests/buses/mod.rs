@@ -3,14 +3,38 @@
 mod buses_complex;
 #[rustfmt::skip]
 #[allow(clippy::all)]
+mod buses_complex_canonical;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod buses_complex_split;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod buses_next_row;
+#[rustfmt::skip]
+#[allow(clippy::all)]
 mod buses_simple;
 #[rustfmt::skip]
 #[allow(clippy::all)]
+mod buses_varlen_boundary_binary;
+#[rustfmt::skip]
+#[allow(clippy::all)]
 mod buses_varlen_boundary_both;
 #[rustfmt::skip]
 #[allow(clippy::all)]
+mod buses_varlen_boundary_commitment;
+#[rustfmt::skip]
+#[allow(clippy::all)]
 mod buses_varlen_boundary_first;
 #[rustfmt::skip]
 #[allow(clippy::all)]
 mod buses_varlen_boundary_last;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod buses_varlen_boundary_named;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod buses_when_with;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod linked_buses;
 mod test_air;
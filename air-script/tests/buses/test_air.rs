@@ -1,10 +1,10 @@
-use winter_air::{Air, AuxRandElements};
-use winter_math::fields::f64::BaseElement as Felt;
+use winter_air::{Air, AuxRandElements, TraceInfo};
+use winter_math::{FieldElement, ToElements, fields::f64::BaseElement as Felt};
 use winterfell::{AuxTraceWithMetadata, Trace, TraceTable, matrix::ColMatrix};
 
 use crate::{
     buses::buses_complex::{BusesAir, PublicInputs},
-    helpers::{AirTester, MyTraceTable},
+    helpers::{AirTester, MyTraceTable, assert_backends_agree},
 };
 
 #[derive(Clone)]
@@ -54,6 +54,10 @@ impl AirTester for BusesAirTester {
         let aux_trace_with_meta = AuxTraceWithMetadata { aux_trace, aux_rand_elements };
         Some(aux_trace_with_meta)
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/buses/buses_complex.air")
+    }
 }
 
 #[test]
@@ -69,4 +73,331 @@ fn test_buses_air() {
 
     let air = BusesAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<BusesAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+#[test]
+fn test_buses_air_canonical() {
+    use crate::buses::buses_complex_canonical::{BusesAir, PublicInputs};
+
+    let air_tester = Box::new(BusesAirTester {});
+    let length = 1024;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    let pub_inputs = PublicInputs::new([Felt::new(0); 2]);
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
+    let air = BusesAir::new(trace_info, pub_inputs, options);
+    main_trace.validate::<BusesAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+#[test]
+fn test_buses_air_split() {
+    use crate::buses::buses_complex_split::{BusesAir, PublicInputs};
+
+    let air_tester = Box::new(BusesAirTester {});
+    let length = 1024;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    let pub_inputs = PublicInputs::new([Felt::new(0); 2]);
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
+    let air = BusesAir::new(trace_info, pub_inputs.clone(), options);
+    main_trace.validate::<BusesAir, Felt>(&air, aux_trace.as_ref());
+    assert_backends_agree(&air, "tests/buses/buses_complex.air", &pub_inputs);
+}
+
+#[test]
+fn test_buses_varlen_boundary_air_agrees_with_ace() {
+    use crate::buses::buses_varlen_boundary_binary::{BusesAir, PublicInputs};
+
+    let tester = BusesAirTester {};
+    let trace_info = TraceInfo::new_multi_segment(1, 2, 3, 1024, vec![]);
+    let inputs = vec![[1, 2].map(Felt::new), [3, 4].map(Felt::new)];
+    let pub_inputs = PublicInputs::new(vec![[Felt::new(9), Felt::new(10)]], inputs);
+
+    // The boundaries of both buses are bound to tables, so they are skipped
+    let air = BusesAir::new(trace_info, pub_inputs.clone(), tester.build_proof_options());
+    assert_eq!(air.get_aux_assertions(&AuxRandElements::new(vec![Felt::ONE; 3])).len(), 4);
+    assert_backends_agree(&air, "tests/buses/buses_varlen_boundary_binary.air", &pub_inputs);
+}
+
+#[test]
+fn test_buses_varlen_boundary_commitment_prefixes_rows() {
+    use crate::buses::buses_varlen_boundary_commitment::{BusesAir, PublicInputs};
+
+    let tester = BusesAirTester {};
+    let trace_info = TraceInfo::new_multi_segment(1, 2, 3, 1024, vec![]);
+    let inputs = vec![[1, 2].map(Felt::new), [3, 4].map(Felt::new)];
+    let pub_inputs = PublicInputs::new(vec![[Felt::new(9), Felt::new(10)]], inputs);
+
+    // Only the rows of `inputs`, which is annotated with `@commitment`, are prefixed with their
+    // number, while the table itself is serialized as a `Vec` either way
+    let expected: Vec<Felt> = [2, 1, 2, 3, 4, 9, 10].into_iter().map(Felt::new).collect();
+    assert_eq!(pub_inputs.to_elements(), expected);
+
+    let air = BusesAir::new(trace_info, pub_inputs.clone(), tester.build_proof_options());
+    assert_eq!(air.get_aux_assertions(&AuxRandElements::new(vec![Felt::ONE; 3])).len(), 4);
+    assert_backends_agree(&air, "tests/buses/buses_varlen_boundary_commitment.air", &pub_inputs);
+}
+
+#[derive(Clone)]
+struct BusesNextRowAirTester {}
+
+impl AirTester for BusesNextRowAirTester {
+    type PubInputs = crate::buses::buses_next_row::PublicInputs;
+
+    fn build_main_trace(&self, length: usize) -> MyTraceTable {
+        let trace_width = 2;
+        let mut trace = TraceTable::new(trace_width, length);
+
+        // the selector is never set, such that `a'` is read but no value is moved on the bus
+        trace.fill(
+            |state| {
+                state[0] = Felt::new(0);
+                state[1] = Felt::new(0);
+            },
+            |_, state| {
+                state[1] += Felt::new(1);
+            },
+        );
+
+        MyTraceTable::new(trace, 1)
+    }
+
+    fn public_inputs(&self) -> Self::PubInputs {
+        let zero = Felt::new(0);
+        crate::buses::buses_next_row::PublicInputs::new([zero; 2])
+    }
+
+    fn build_aux_trace(&self, length: usize) -> Option<AuxTraceWithMetadata<Felt>> {
+        let num_rand_values = 2;
+        let aux_trace = ColMatrix::new(vec![vec![Felt::new(1); length]]);
+        let aux_rand_elements = AuxRandElements::new(vec![Felt::new(0); num_rand_values]);
+
+        let aux_trace_with_meta = AuxTraceWithMetadata { aux_trace, aux_rand_elements };
+        Some(aux_trace_with_meta)
+    }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/buses/buses_next_row.air")
+    }
+}
+
+#[test]
+fn test_buses_next_row_air() {
+    use crate::buses::buses_next_row::BusesNextRowAir;
+
+    let air_tester = Box::new(BusesNextRowAirTester {});
+    let length = 1024;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    let pub_inputs = air_tester.public_inputs();
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
+    let air = BusesNextRowAir::new(trace_info, pub_inputs, options);
+    main_trace.validate::<BusesNextRowAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+/// Returns the running sum of a LogUp bus over `rows`, where each row inserts and removes the
+/// tuple `(value)` with the effective multiplicities `inserted` and `removed` of that row, i.e.
+/// the product of the selector and the multiplicity of each operation.
+///
+/// The bus column starts at zero, and its value on the next row adds `inserted / v` and removes
+/// `removed / v` from its value on the current row, where `v = alpha_0 + alpha_1 * value`.
+fn logup_column(
+    rows: impl Iterator<Item = (Felt, Felt, Felt)>,
+    [alpha_0, alpha_1]: [Felt; 2],
+) -> Vec<Felt> {
+    let mut column = vec![Felt::ZERO];
+    for (value, inserted, removed) in rows {
+        let v = alpha_0 + alpha_1 * value;
+        column.push(*column.last().unwrap() + (inserted - removed) / v);
+    }
+    column
+}
+
+#[derive(Clone)]
+struct BusesWhenWithAirTester {
+    rand_elements: [Felt; 2],
+}
+
+impl BusesWhenWithAirTester {
+    /// Returns the rows `[s, m, a, n]` of the main trace.
+    ///
+    /// On even rows, the selector `s` is set, and `a` is inserted `m` times. On the following odd
+    /// row, the same value is removed `n` times with `n` equal to the previous `m`, while the
+    /// multiplicity `m` of the insertion is not zero but is cancelled by the selector. So the bus
+    /// is only balanced if the selector and the multiplicity of the insertion are combined.
+    fn rows(&self, length: usize) -> Vec<[Felt; 4]> {
+        (0..length as u64)
+            .map(|i| {
+                if i % 2 == 0 {
+                    [Felt::ONE, Felt::new(i + 1), Felt::new(3 * i + 1), Felt::ZERO]
+                } else {
+                    [Felt::ZERO, Felt::new(5), Felt::new(3 * i - 2), Felt::new(i)]
+                }
+            })
+            .collect()
+    }
+}
+
+impl AirTester for BusesWhenWithAirTester {
+    type PubInputs = crate::buses::buses_when_with::PublicInputs;
+
+    fn build_main_trace(&self, length: usize) -> MyTraceTable {
+        let rows = self.rows(length);
+        let columns = (0..4).map(|column| rows.iter().map(|row| row[column]).collect()).collect();
+        MyTraceTable::new(TraceTable::init(columns), 1)
+    }
+
+    fn public_inputs(&self) -> Self::PubInputs {
+        let zero = Felt::new(0);
+        crate::buses::buses_when_with::PublicInputs::new([zero; 2])
+    }
+
+    fn build_aux_trace(&self, length: usize) -> Option<AuxTraceWithMetadata<Felt>> {
+        let rows = self.rows(length);
+        let column = logup_column(
+            rows.iter().take(length - 1).map(|&[s, m, a, n]| (a, s * m, n)),
+            self.rand_elements,
+        );
+
+        let aux_trace = ColMatrix::new(vec![column]);
+        let aux_rand_elements = AuxRandElements::new(self.rand_elements.to_vec());
+        Some(AuxTraceWithMetadata { aux_trace, aux_rand_elements })
+    }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/buses/buses_when_with.air")
+    }
+}
+
+#[test]
+fn test_buses_when_with_air() {
+    use crate::buses::buses_when_with::BusesWhenWithAir;
+
+    let air_tester = Box::new(BusesWhenWithAirTester {
+        rand_elements: [Felt::new(7), Felt::new(11)],
+    });
+    let length = 1024;
+
+    let trace_info = air_tester.build_trace_info(length);
+    let pub_inputs = air_tester.public_inputs();
+    let options = air_tester.build_proof_options();
+
+    let air = BusesWhenWithAir::new(trace_info, pub_inputs, options);
+    air_tester.validate(&air, length);
+    air_tester.check_backends(&air);
+}
+
+/// The operations on the bus `p` of [crate::buses::linked_buses], in the order in which they are
+/// merged by linking: whether the operation is an insertion, the column of the inserted or removed
+/// value, and the selector of the operation, given a row of the main trace.
+#[allow(clippy::type_complexity)]
+fn linked_bus_ops() -> [(bool, usize, fn(&[Felt]) -> Felt); 6] {
+    [
+        // LinkedBusesAir
+        (true, 1, |row| row[0]),
+        (false, 1, |row| Felt::ONE - row[0]),
+        // LinkedBusesLeft
+        (true, 3, |row| Felt::ONE - row[2]),
+        (false, 3, |row| row[2]),
+        // LinkedBusesRight
+        (true, 5, |row| row[4]),
+        (false, 6, |row| row[4]),
+    ]
+}
+
+#[derive(Clone)]
+struct LinkedBusesAirTester {
+    rand_elements: [Felt; 2],
+}
+
+impl LinkedBusesAirTester {
+    /// Returns the rows of the main trace, with the columns of the linked programs side by side:
+    /// `[s, a]`, `[t, b]` and `[u, c, d]`.
+    ///
+    /// The selectors alternate between 1 and 0. On even rows, `a` and `c` are inserted while `b`
+    /// and `d` are removed, with `a = d` and `b = c`; on odd rows, `a` is removed and `b` inserted,
+    /// with `a = b`. So each row only balances once the operations of all programs are merged.
+    fn rows(&self, length: usize) -> Vec<[Felt; 7]> {
+        (0..length as u64)
+            .map(|i| {
+                if i % 2 == 0 {
+                    let (x, y) = (Felt::new(i + 1), Felt::new(2 * i + 3));
+                    [Felt::ONE, x, Felt::ONE, y, Felt::ONE, y, x]
+                } else {
+                    let z = Felt::new(i + 5);
+                    [Felt::ZERO, z, Felt::ZERO, z, Felt::ZERO, Felt::ZERO, Felt::ZERO]
+                }
+            })
+            .collect()
+    }
+}
+
+impl AirTester for LinkedBusesAirTester {
+    type PubInputs = crate::buses::linked_buses::PublicInputs;
+
+    fn build_main_trace(&self, length: usize) -> MyTraceTable {
+        let rows = self.rows(length);
+        let columns = (0..7).map(|column| rows.iter().map(|row| row[column]).collect()).collect();
+        MyTraceTable::new(TraceTable::init(columns), 1)
+    }
+
+    fn public_inputs(&self) -> Self::PubInputs {
+        let zero = Felt::new(0);
+        crate::buses::linked_buses::PublicInputs::new([zero; 2], [zero], [zero])
+    }
+
+    /// Builds the running product of the bus over the merged list of operations
+    fn build_aux_trace(&self, length: usize) -> Option<AuxTraceWithMetadata<Felt>> {
+        let [alpha_0, alpha_1] = self.rand_elements;
+        let mut column = vec![Felt::ONE];
+        for row in self.rows(length).iter().take(length - 1) {
+            let mut inserted = Felt::ONE;
+            let mut removed = Felt::ONE;
+            for (is_insert, value, selector) in linked_bus_ops() {
+                let selector = selector(row);
+                let factor = (alpha_0 + alpha_1 * row[value]) * selector + Felt::ONE - selector;
+                if is_insert {
+                    inserted *= factor;
+                } else {
+                    removed *= factor;
+                }
+            }
+            column.push(*column.last().unwrap() * inserted / removed);
+        }
+
+        let aux_trace = ColMatrix::new(vec![column]);
+        let aux_rand_elements = AuxRandElements::new(self.rand_elements.to_vec());
+        Some(AuxTraceWithMetadata { aux_trace, aux_rand_elements })
+    }
+}
+
+#[test]
+fn test_linked_buses_air() {
+    use crate::buses::linked_buses::LinkedBusesAir;
+
+    let air_tester = Box::new(LinkedBusesAirTester {
+        rand_elements: [Felt::new(7), Felt::new(11)],
+    });
+    let length = 1024;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    let pub_inputs = air_tester.public_inputs();
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
+    let air = LinkedBusesAir::new(trace_info, pub_inputs, options);
+    main_trace.validate::<LinkedBusesAir, Felt>(&air, aux_trace.as_ref());
 }
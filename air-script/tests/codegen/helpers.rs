@@ -8,6 +8,8 @@ use miden_diagnostics::{
 
 pub enum Target {
     Winterfell,
+    Plonky3,
+    Markdown,
 }
 pub enum Pipeline {
     WithMIR,
@@ -16,47 +18,165 @@ pub enum Pipeline {
 
 pub struct Test {
     input_path: String,
+    linked_paths: Vec<String>,
+    canonicalize: bool,
 }
 impl Test {
     pub fn new(input_path: String) -> Self {
-        Test { input_path }
+        Test {
+            input_path,
+            linked_paths: vec![],
+            canonicalize: false,
+        }
+    }
+
+    /// Links the program at `input_path` after those of this test, see [air_ir::Air::link].
+    ///
+    /// Only supported with [Pipeline::WithMIR].
+    pub fn link_with(mut self, input_path: String) -> Self {
+        self.linked_paths.push(input_path);
+        self
+    }
+
+    /// Canonicalizes the compiled Air and removes its duplicate integrity constraints, see
+    /// [air_ir::PassConfig::canonicalize].
+    ///
+    /// Only supported with [Pipeline::WithMIR].
+    pub fn canonicalize(mut self) -> Self {
+        self.canonicalize = true;
+        self
     }
 
     pub fn transpile(&self, target: Target, pipeline: Pipeline) -> Result<String, CompileError> {
+        let backend: Box<dyn CodeGenerator<Output = String>> = match target {
+            Target::Winterfell => Box::new(air_codegen_winter::CodeGenerator::default()),
+            Target::Plonky3 => Box::new(air_codegen_plonky3::CodeGenerator::default()),
+            Target::Markdown => Box::new(air_codegen_markdown::CodeGenerator::default()),
+        };
+        self.transpile_with(backend.as_ref(), pipeline)
+    }
+
+    pub fn transpile_with(
+        &self,
+        backend: &dyn CodeGenerator<Output = String>,
+        pipeline: Pipeline,
+    ) -> Result<String, CompileError> {
         let codemap = Arc::new(CodeMap::new());
         let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
         let diagnostics = DiagnosticsHandler::new(Default::default(), codemap.clone(), emitter);
 
         // Parse from file to internal representation
         let air = match pipeline {
-            Pipeline::WithMIR => air_parser::parse_file(&diagnostics, codemap, &self.input_path)
-                .map_err(CompileError::Parse)
-                .and_then(|ast| {
-                    let mut pipeline =
-                        air_parser::transforms::ConstantPropagation::new(&diagnostics)
-                            .chain(mir::passes::AstToMir::new(&diagnostics))
-                            .chain(mir::passes::Inlining::new(&diagnostics))
-                            .chain(mir::passes::Unrolling::new(&diagnostics))
-                            .chain(air_ir::passes::MirToAir::new(&diagnostics))
-                            .chain(air_ir::passes::BusOpExpand::new(&diagnostics));
-                    pipeline.run(ast)
-                })?,
-            Pipeline::WithoutMIR => air_parser::parse_file(&diagnostics, codemap, &self.input_path)
-                .map_err(CompileError::Parse)
-                .and_then(|ast| {
-                    let mut pipeline =
-                        air_parser::transforms::ConstantPropagation::new(&diagnostics)
-                            .chain(air_parser::transforms::Inlining::new(&diagnostics))
-                            .chain(air_ir::passes::AstToAir::new(&diagnostics));
-                    pipeline.run(ast)
-                })?,
-        };
-
-        let backend: Box<dyn CodeGenerator<Output = String>> = match target {
-            Target::Winterfell => Box::new(air_codegen_winter::CodeGenerator),
+            Pipeline::WithMIR => {
+                let mut airs = vec![];
+                for input_path in std::iter::once(&self.input_path).chain(&self.linked_paths) {
+                    let air = air_parser::parse_file(&diagnostics, codemap.clone(), input_path)
+                        .map_err(CompileError::Parse)
+                        .and_then(|ast| {
+                            let mut pipeline =
+                                air_parser::transforms::ConstantPropagation::new(&diagnostics)
+                                    .chain(mir::passes::AstToMir::new(&diagnostics))
+                                    .chain(mir::passes::Inlining::new(&diagnostics))
+                                    .chain(mir::passes::Unrolling::new(&diagnostics))
+                                    .chain(air_ir::passes::MirToAir::new(&diagnostics))
+                                    .chain(air_ir::passes::TrivialConstraints::new(&diagnostics));
+                            pipeline.run(ast)
+                        })?;
+                    airs.push(air);
+                }
+                // Buses are expanded once the programs are linked, as extern buses cannot be
+                // expanded on their own
+                let air = air_ir::Air::link(airs).expect("linking failed");
+                let air = air_ir::passes::BusOpExpand::new(&diagnostics).run(air)?;
+                if self.canonicalize {
+                    let mut pipeline = air_ir::passes::Canonicalize::new(&diagnostics)
+                        .chain(air_ir::passes::DuplicateConstraints::new(&diagnostics));
+                    pipeline.run(air)?
+                } else {
+                    air
+                }
+            },
+            Pipeline::WithoutMIR => {
+                assert!(self.linked_paths.is_empty(), "linking requires the MIR pipeline");
+                assert!(!self.canonicalize, "canonicalization requires the MIR pipeline");
+                air_parser::parse_file(&diagnostics, codemap, &self.input_path)
+                    .map_err(CompileError::Parse)
+                    .and_then(|ast| {
+                        let mut pipeline =
+                            air_parser::transforms::ConstantPropagation::new(&diagnostics)
+                                .chain(air_parser::transforms::Inlining::new(&diagnostics))
+                                .chain(air_ir::passes::AstToAir::new(&diagnostics));
+                        pipeline.run(ast)
+                    })?
+            },
         };
 
         // generate Rust code targeting Winterfell
         Ok(backend.generate(&air).expect("code generation failed"))
     }
 }
+
+/// A Winterfell code generation hook adding an implementation of `From<&MyTraceTable>` for the
+/// generated `PublicInputs`, reading each public input from the first row of the main trace.
+pub fn add_public_inputs_from_trace(scope: &mut air_codegen_winter::Scope, ir: &air_ir::Air) {
+    let main_width = ir.trace_segment_widths[0];
+    scope.import("crate::helpers", "MyTraceTable");
+    let from_fn = scope
+        .new_impl("PublicInputs")
+        .impl_trait("From<&MyTraceTable>")
+        .new_fn("from")
+        .arg("trace", "&MyTraceTable")
+        .ret("Self");
+    let args: Vec<String> = ir
+        .public_inputs()
+        .map(|_| format!("core::array::from_fn(|i| trace.trace.get(i % {main_width}, 0))"))
+        .collect();
+    from_fn.line(format!("Self::new({})", args.join(", ")));
+}
+
+/// A Winterfell preamble importing the items used by the generated code from
+/// [crate::helpers::winter] rather than from the Winterfell crates.
+pub fn winter_preamble(scope: &mut air_codegen_winter::Scope, _ir: &air_ir::Air) {
+    for item in [
+        "Air",
+        "AirContext",
+        "Assertion",
+        "AuxRandElements",
+        "EvaluationFrame",
+        "ProofOptions as WinterProofOptions",
+        "TransitionConstraintDegree",
+        "TraceInfo",
+        "Felt",
+        "ExtensionOf",
+        "FieldElement",
+        "ToElements",
+        "ByteWriter",
+        "Serializable",
+    ] {
+        scope.import("crate::helpers::winter", item);
+    }
+}
+
+/// A Winterfell code generation hook adding the names of the main trace columns to the generated
+/// Air struct.
+pub fn add_main_column_names(scope: &mut air_codegen_winter::Scope, ir: &air_ir::Air) {
+    let names: Vec<String> = (0..ir.trace_segment_widths[0] as usize)
+        .map(|column| format!("\"{}\"", ir.trace_column_name(0, column)))
+        .collect();
+    scope.raw(format!(
+        "impl {} {{\n    pub const MAIN_COLUMNS: [&str; {}] = [{}];\n}}",
+        ir.name(),
+        names.len(),
+        names.join(", ")
+    ));
+}
+
+/// A Winterfell code generation hook adding the number of integrity constraints of the main trace
+/// to the generated Air struct.
+pub fn add_num_integrity_constraints(scope: &mut air_codegen_winter::Scope, ir: &air_ir::Air) {
+    scope.raw(format!(
+        "impl {} {{\n    pub const NUM_MAIN_INTEGRITY_CONSTRAINTS: usize = {};\n}}",
+        ir.name(),
+        ir.integrity_constraints(0).len()
+    ));
+}
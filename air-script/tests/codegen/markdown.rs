@@ -0,0 +1,38 @@
+use expect_test::expect_file;
+
+use super::helpers::{Pipeline, Target, Test};
+
+#[test]
+fn buses_complex() {
+    let generated_spec = Test::new("tests/buses/buses_complex.air".to_string())
+        .transpile(Target::Markdown, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/buses_complex.md"];
+    expected.assert_eq(&generated_spec);
+}
+
+#[test]
+fn periodic_columns() {
+    let generated_spec = Test::new("tests/periodic_columns/periodic_columns.air".to_string())
+        .transpile(Target::Markdown, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../periodic_columns/periodic_columns.md"];
+    expected.assert_eq(&generated_spec);
+}
+
+#[test]
+fn doc_comments() {
+    let generated_spec = Test::new("tests/docs/docs.air".to_string())
+        .transpile(Target::Markdown, Pipeline::WithMIR)
+        .unwrap();
+
+    // The paragraphs of the doc comments are joined in a description column
+    assert!(generated_spec.contains("| Constraint | Description |"));
+    assert!(generated_spec.contains(
+        "| The clock is incremented by one, at every step.<br>It never wraps around. |\n"
+    ));
+    assert!(generated_spec.contains("| `s` is a selector |\n"));
+    assert!(generated_spec.contains("| The clock starts at zero |\n"));
+}
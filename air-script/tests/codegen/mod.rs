@@ -1,3 +1,5 @@
 mod helpers;
+mod markdown;
+mod plonky3;
 mod winterfell_with_mir;
 mod winterfell_wo_mir;
@@ -0,0 +1,23 @@
+use expect_test::expect_file;
+
+use super::helpers::{Pipeline, Target, Test};
+
+#[test]
+fn binary() {
+    let generated_air = Test::new("tests/binary/binary.air".to_string())
+        .transpile(Target::Plonky3, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../binary/binary_plonky3.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn fibonacci() {
+    let generated_air = Test::new("tests/fibonacci/fibonacci.air".to_string())
+        .transpile(Target::Plonky3, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../fibonacci/fibonacci_plonky3.rs"];
+    expected.assert_eq(&generated_air);
+}
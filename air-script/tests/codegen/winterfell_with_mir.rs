@@ -1,6 +1,9 @@
 use expect_test::expect_file;
 
-use super::helpers::{Pipeline, Target, Test};
+use super::helpers::{
+    Pipeline, Target, Test, add_main_column_names, add_num_integrity_constraints,
+    add_public_inputs_from_trace, winter_preamble,
+};
 
 // tests_wo_mir
 // ================================================================================================
@@ -35,6 +38,60 @@ fn buses_complex() {
     expected.assert_eq(&generated_air);
 }
 
+#[test]
+fn buses_complex_canonical() {
+    let generated_air = Test::new("tests/buses/buses_complex.air".to_string())
+        .canonicalize()
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/buses_complex_canonical.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn buses_complex_split() {
+    let backend = air_codegen_winter::CodeGenerator::new().with_split_buses();
+    let generated_air = Test::new("tests/buses/buses_complex.air".to_string())
+        .transpile_with(&backend, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/buses_complex_split.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn buses_when_with() {
+    let generated_air = Test::new("tests/buses/buses_when_with.air".to_string())
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/buses_when_with.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn linked_buses() {
+    let generated_air = Test::new("tests/buses/linked_buses.air".to_string())
+        .link_with("tests/buses/linked_buses_left.air".to_string())
+        .link_with("tests/buses/linked_buses_right.air".to_string())
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/linked_buses.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn buses_next_row() {
+    let generated_air = Test::new("tests/buses/buses_next_row.air".to_string())
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/buses_next_row.rs"];
+    expected.assert_eq(&generated_air);
+}
+
 #[test]
 fn buses_varlen_boundary_first() {
     let generated_air = Test::new("tests/buses/buses_varlen_boundary_first.air".to_string())
@@ -55,6 +112,16 @@ fn buses_varlen_boundary_last() {
     expected.assert_eq(&generated_air);
 }
 
+#[test]
+fn buses_varlen_boundary_named() {
+    let generated_air = Test::new("tests/buses/buses_varlen_boundary_named.air".to_string())
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/buses_varlen_boundary_named.rs"];
+    expected.assert_eq(&generated_air);
+}
+
 #[test]
 fn buses_varlen_boundary_both() {
     let generated_air = Test::new("tests/buses/buses_varlen_boundary_both.air".to_string())
@@ -65,6 +132,26 @@ fn buses_varlen_boundary_both() {
     expected.assert_eq(&generated_air);
 }
 
+#[test]
+fn buses_varlen_boundary_binary() {
+    let generated_air = Test::new("tests/buses/buses_varlen_boundary_binary.air".to_string())
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/buses_varlen_boundary_binary.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn buses_varlen_boundary_commitment() {
+    let generated_air = Test::new("tests/buses/buses_varlen_boundary_commitment.air".to_string())
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../buses/buses_varlen_boundary_commitment.rs"];
+    expected.assert_eq(&generated_air);
+}
+
 #[test]
 fn periodic_columns() {
     let generated_air = Test::new("tests/periodic_columns/periodic_columns.air".to_string())
@@ -85,6 +172,32 @@ fn pub_inputs() {
     expected.assert_eq(&generated_air);
 }
 
+#[test]
+fn pub_inputs_with_hooks() {
+    let backend =
+        air_codegen_winter::CodeGenerator::new().after_public_inputs(add_public_inputs_from_trace);
+    let generated_air = Test::new("tests/pub_inputs/pub_inputs.air".to_string())
+        .transpile_with(&backend, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../pub_inputs/pub_inputs_hooks.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn pub_inputs_with_preamble() {
+    let backend = air_codegen_winter::CodeGenerator::new()
+        .with_preamble(winter_preamble)
+        .after_air_struct(add_main_column_names)
+        .after_air_trait(add_num_integrity_constraints);
+    let generated_air = Test::new("tests/pub_inputs/pub_inputs.air".to_string())
+        .transpile_with(&backend, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../pub_inputs/pub_inputs_preamble.rs"];
+    expected.assert_eq(&generated_air);
+}
+
 #[test]
 fn system() {
     let generated_air = Test::new("tests/system/system.air".to_string())
@@ -145,6 +258,39 @@ fn fibonacci() {
     expected.assert_eq(&generated_air);
 }
 
+#[test]
+fn fibonacci_canonical() {
+    let generated_air = Test::new("tests/fibonacci/fibonacci.air".to_string())
+        .canonicalize()
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../fibonacci/fibonacci_canonical.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn fibonacci_base_field() {
+    let backend = air_codegen_winter::CodeGenerator::new().with_base_field("Goldilocks");
+    let generated_air = Test::new("tests/fibonacci/fibonacci.air".to_string())
+        .transpile_with(&backend, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../fibonacci/fibonacci_base_field.rs"];
+    expected.assert_eq(&generated_air);
+}
+
+#[test]
+fn fibonacci_generic_base_field() {
+    let backend = air_codegen_winter::CodeGenerator::new().with_generic_base_field();
+    let generated_air = Test::new("tests/fibonacci/fibonacci.air".to_string())
+        .transpile_with(&backend, Pipeline::WithMIR)
+        .unwrap();
+
+    let expected = expect_file!["../fibonacci/fibonacci_generic.rs"];
+    expected.assert_eq(&generated_air);
+}
+
 #[test]
 fn functions_simple() {
     let generated_air = Test::new("tests/functions/functions_simple.air".to_string())
@@ -274,3 +420,18 @@ fn constraint_comprehension() {
     let expected = expect_file!["../constraint_comprehension/constraint_comprehension.rs"];
     expected.assert_eq(&generated_air);
 }
+
+#[test]
+fn doc_comments() {
+    let generated_air = Test::new("tests/docs/docs.air".to_string())
+        .transpile(Target::Winterfell, Pipeline::WithMIR)
+        .unwrap();
+
+    // The doc comments of a constraint precede its evaluation
+    assert!(generated_air.contains(
+        "// The clock is incremented by one,\n        // at every step.\n        //\n        // It never wraps around.\n        result[0] = "
+    ));
+    assert!(generated_air.contains("// `s` is a selector\n        result[1] = "));
+    assert!(generated_air.contains("// The clock starts at zero\n"));
+    assert!(!generated_air.contains("// Enforces that the column is binary."));
+}
@@ -43,6 +43,10 @@ impl AirTester for ConstantInRangeAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/constant_in_range/constant_in_range.air")
+    }
 }
 
 #[test]
@@ -58,4 +62,5 @@ fn test_constant_in_range_air() {
 
     let air = ConstantInRangeAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<ConstantInRangeAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -39,7 +39,11 @@ impl AirTester for ConstantsAirTester {
 
     fn public_inputs(&self) -> PublicInputs {
         let zero = Felt::new(0);
-        PublicInputs::new([zero; 4], [zero; 4], [zero; 4], [zero; 20])
+        PublicInputs::new([zero; 4], [zero; 4], [zero; 20], [zero; 4])
+    }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/constants/constants.air")
     }
 }
 
@@ -56,4 +60,5 @@ fn test_constants_air() {
 
     let air = ConstantsAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<ConstantsAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -47,6 +47,10 @@ impl AirTester for ConstraintComprehensionAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/constraint_comprehension/constraint_comprehension.air")
+    }
 }
 
 #[test]
@@ -62,4 +66,5 @@ fn test_constraint_comprehension_air() {
 
     let air = ConstraintComprehensionAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<ConstraintComprehensionAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -0,0 +1,51 @@
+use std::fs;
+
+use air_script::check_collecting;
+use miden_diagnostics::Severity;
+
+const SOURCE: &str = "
+def DiagnosticsAir
+
+trace_columns {
+    main: [a, b],
+}
+
+public_inputs {
+    stack_inputs: [16],
+}
+
+boundary_constraints {
+    enf a.first = 0;
+}
+
+integrity_constraints {
+    enf a' = a + b;
+    enf b = b;
+}";
+
+#[test]
+fn check_collects_diagnostics() {
+    let dir = std::env::temp_dir().join("air-script-diagnostics");
+    fs::create_dir_all(&dir).unwrap();
+
+    // The trivial constraint is reported as a warning, without failing the compilation
+    let path = dir.join("warning.air");
+    fs::write(&path, SOURCE).unwrap();
+    let output = check_collecting(&path);
+    assert!(output.result.is_ok());
+    assert_eq!(output.count(Severity::Error), 0);
+    let warnings: Vec<_> = output.with_severity(Severity::Warning).collect();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message,
+        "integrity constraint #1 against the main trace is identically zero"
+    );
+    assert!(warnings[0].rendered.starts_with("warning: integrity constraint #1"));
+
+    // The errors which made the compilation fail are collected as well
+    let path = dir.join("error.air");
+    fs::write(&path, SOURCE.replace("a + b", "a + c")).unwrap();
+    let output = check_collecting(&path);
+    assert!(output.result.is_err());
+    assert!(output.count(Severity::Error) > 0);
+}
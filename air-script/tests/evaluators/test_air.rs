@@ -37,6 +37,10 @@ impl AirTester for EvaluatorsAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/evaluators/evaluators.air")
+    }
 }
 
 #[test]
@@ -52,4 +56,5 @@ fn test_evaluators_air() {
 
     let air = EvaluatorsAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<EvaluatorsAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
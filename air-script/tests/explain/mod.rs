@@ -0,0 +1,26 @@
+use std::process::Command;
+
+use air_parser::error_codes::{ERROR_CODES, OVERLAPPING_BOUNDARY_CONSTRAINTS};
+
+/// Runs `airc --explain <code>` and returns its standard output.
+fn explain(code: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_airc"))
+        .args(["--explain", code])
+        .output()
+        .expect("failed to run airc");
+    String::from_utf8(output.stdout).expect("output is not valid utf-8")
+}
+
+#[test]
+fn explain_prints_registered_text() {
+    assert_eq!(explain("E0001"), OVERLAPPING_BOUNDARY_CONSTRAINTS.explanation);
+    assert_eq!(explain("e0001"), OVERLAPPING_BOUNDARY_CONSTRAINTS.explanation);
+    for error in ERROR_CODES {
+        assert_eq!(explain(error.code), error.explanation);
+    }
+}
+
+#[test]
+fn explain_reports_unknown_codes() {
+    assert!(explain("E9999").contains("no explanation is registered for error code 'E9999'"));
+}
@@ -0,0 +1,109 @@
+use winter_air::{Air, AirContext, Assertion, AuxRandElements, EvaluationFrame, ProofOptions as WinterProofOptions, TransitionConstraintDegree, TraceInfo};
+use winter_math::fields::f64::BaseElement as Goldilocks;
+use winter_math::{ExtensionOf, FieldElement, ToElements};
+use winter_utils::{ByteWriter, Serializable};
+
+/// The public inputs of the AIR.
+///
+/// They are serialized and flattened by `to_elements` in the canonical order, sorted by name,
+/// which is the order in which every verifier absorbs them into the transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicInputs {
+    stack_inputs: [Goldilocks; 2],
+    stack_output: [Goldilocks; 1],
+}
+
+impl PublicInputs {
+    pub fn new(stack_inputs: [Goldilocks; 2], stack_output: [Goldilocks; 1]) -> Self {
+        Self { stack_inputs, stack_output }
+    }
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.stack_inputs.write_into(target);
+        self.stack_output.write_into(target);
+    }
+}
+
+impl ToElements<Goldilocks> for PublicInputs {
+    fn to_elements(&self) -> Vec<Goldilocks> {
+        let mut elements = Vec::new();
+        elements.extend_from_slice(&self.stack_inputs);
+        elements.extend_from_slice(&self.stack_output);
+        elements
+    }
+}
+
+pub struct FibonacciAir {
+    context: AirContext<Goldilocks>,
+    stack_inputs: [Goldilocks; 2],
+    stack_output: [Goldilocks; 1],
+}
+
+impl FibonacciAir {
+    pub fn last_step(&self) -> usize {
+        self.trace_length() - self.context().num_transition_exemptions()
+    }
+}
+
+impl Air for FibonacciAir {
+    type BaseField = Goldilocks;
+    type PublicInputs = PublicInputs;
+
+    fn context(&self) -> &AirContext<Goldilocks> {
+        &self.context
+    }
+
+    fn new(trace_info: TraceInfo, public_inputs: PublicInputs, options: WinterProofOptions) -> Self {
+        let main_degrees = vec![TransitionConstraintDegree::new(1), TransitionConstraintDegree::new(1)];
+        let aux_degrees = vec![];
+        let num_main_assertions = 3;
+        let num_aux_assertions = 0;
+
+        let context = AirContext::new_multi_segment(
+            trace_info,
+            main_degrees,
+            aux_degrees,
+            num_main_assertions,
+            num_aux_assertions,
+            options,
+        )
+        .set_num_transition_exemptions(2);
+        Self { context, stack_inputs: public_inputs.stack_inputs, stack_output: public_inputs.stack_output }
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Goldilocks>> {
+        vec![]
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Goldilocks>> {
+        let mut result = Vec::new();
+        result.push(Assertion::single(0, 0, self.stack_inputs[0]));
+        result.push(Assertion::single(1, 0, self.stack_inputs[1]));
+        result.push(Assertion::single(1, self.last_step(), self.stack_output[0]));
+        result
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = Goldilocks>>(&self, aux_rand_elements: &AuxRandElements<E>) -> Vec<Assertion<E>> {
+        let mut result = Vec::new();
+        result
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = Goldilocks>>(&self, frame: &EvaluationFrame<E>, periodic_values: &[E], result: &mut [E]) {
+        let main_current = frame.current();
+        let main_next = frame.next();
+        result[0] = main_next[1] - (main_current[0] + main_current[1]);
+        result[1] = main_next[0] - main_current[1];
+    }
+
+    fn evaluate_aux_transition<F, E>(&self, main_frame: &EvaluationFrame<F>, aux_frame: &EvaluationFrame<E>, _periodic_values: &[F], aux_rand_elements: &AuxRandElements<E>, result: &mut [E])
+    where F: FieldElement<BaseField = Goldilocks>,
+          E: FieldElement<BaseField = Goldilocks> + ExtensionOf<F>,
+    {
+        let main_current = main_frame.current();
+        let main_next = main_frame.next();
+        let aux_current = aux_frame.current();
+        let aux_next = aux_frame.next();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,139 @@
+use winter_air::{Air, AirContext, Assertion, AuxRandElements, EvaluationFrame, ProofOptions as WinterProofOptions, TransitionConstraintDegree, TraceInfo};
+use winter_math::{ExtensibleField, ExtensionOf, FieldElement, StarkField, ToElements};
+use winter_utils::{ByteWriter, Serializable};
+
+/// Converts a constant of the AirScript program to an element of the base field.
+///
+/// The constants are not reduced modulo the base field, which is unknown when the code is generated,
+/// so this panics if the constant is not smaller than its modulus.
+#[allow(dead_code)]
+fn base_constant<B: StarkField>(value: u64) -> B {
+    let modulus = B::get_modulus_le_bytes();
+    let (low_bytes, high_bytes) = modulus.split_at(modulus.len().min(8));
+    let mut bytes = [0_u8; 8];
+    bytes[..low_bytes.len()].copy_from_slice(low_bytes);
+    assert!(
+        high_bytes.iter().any(|byte| *byte != 0) || value < u64::from_le_bytes(bytes),
+        "the constant {value} is not an element of the base field"
+    );
+    B::from((value >> 32) as u32) * (B::from(u32::MAX) + B::ONE) + B::from(value as u32)
+}
+
+/// The public inputs of the AIR.
+///
+/// They are serialized and flattened by `to_elements` in the canonical order, sorted by name,
+/// which is the order in which every verifier absorbs them into the transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicInputs<B>
+where B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    stack_inputs: [B; 2],
+    stack_output: [B; 1],
+}
+
+impl<B> PublicInputs<B>
+where B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    pub fn new(stack_inputs: [B; 2], stack_output: [B; 1]) -> Self {
+        Self { stack_inputs, stack_output }
+    }
+}
+
+impl<B> Serializable for PublicInputs<B>
+where B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.stack_inputs.write_into(target);
+        self.stack_output.write_into(target);
+    }
+}
+
+impl<B> ToElements<B> for PublicInputs<B>
+where B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    fn to_elements(&self) -> Vec<B> {
+        let mut elements = Vec::new();
+        elements.extend_from_slice(&self.stack_inputs);
+        elements.extend_from_slice(&self.stack_output);
+        elements
+    }
+}
+
+pub struct FibonacciAir<B>
+where B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    context: AirContext<B>,
+    stack_inputs: [B; 2],
+    stack_output: [B; 1],
+}
+
+impl<B> FibonacciAir<B>
+where B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    pub fn last_step(&self) -> usize {
+        self.trace_length() - self.context().num_transition_exemptions()
+    }
+}
+
+impl<B> Air for FibonacciAir<B>
+where B: StarkField + ExtensibleField<2> + ExtensibleField<3>,
+{
+    type BaseField = B;
+    type PublicInputs = PublicInputs<B>;
+
+    fn context(&self) -> &AirContext<B> {
+        &self.context
+    }
+
+    fn new(trace_info: TraceInfo, public_inputs: PublicInputs<B>, options: WinterProofOptions) -> Self {
+        let main_degrees = vec![TransitionConstraintDegree::new(1), TransitionConstraintDegree::new(1)];
+        let aux_degrees = vec![];
+        let num_main_assertions = 3;
+        let num_aux_assertions = 0;
+
+        let context = AirContext::new_multi_segment(
+            trace_info,
+            main_degrees,
+            aux_degrees,
+            num_main_assertions,
+            num_aux_assertions,
+            options,
+        )
+        .set_num_transition_exemptions(2);
+        Self { context, stack_inputs: public_inputs.stack_inputs, stack_output: public_inputs.stack_output }
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<B>> {
+        vec![]
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<B>> {
+        let mut result = Vec::new();
+        result.push(Assertion::single(0, 0, self.stack_inputs[0]));
+        result.push(Assertion::single(1, 0, self.stack_inputs[1]));
+        result.push(Assertion::single(1, self.last_step(), self.stack_output[0]));
+        result
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = B>>(&self, aux_rand_elements: &AuxRandElements<E>) -> Vec<Assertion<E>> {
+        let mut result = Vec::new();
+        result
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = B>>(&self, frame: &EvaluationFrame<E>, periodic_values: &[E], result: &mut [E]) {
+        let main_current = frame.current();
+        let main_next = frame.next();
+        result[0] = main_next[1] - (main_current[0] + main_current[1]);
+        result[1] = main_next[0] - main_current[1];
+    }
+
+    fn evaluate_aux_transition<F, E>(&self, main_frame: &EvaluationFrame<F>, aux_frame: &EvaluationFrame<E>, _periodic_values: &[F], aux_rand_elements: &AuxRandElements<E>, result: &mut [E])
+    where F: FieldElement<BaseField = B>,
+          E: FieldElement<BaseField = B> + ExtensionOf<F>,
+    {
+        let main_current = main_frame.current();
+        let main_next = main_frame.next();
+        let aux_current = aux_frame.current();
+        let aux_next = aux_frame.next();
+    }
+}
\ No newline at end of file
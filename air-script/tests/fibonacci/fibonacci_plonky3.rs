@@ -0,0 +1,34 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, BaseAirWithPublicValues};
+use p3_field::PrimeCharacteristicRing;
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+
+pub struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for FibonacciAir {
+    fn num_public_values(&self) -> usize {
+        3
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues<F = Goldilocks>> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let main_current: Vec<AB::Expr> = main.row_slice(0).unwrap().iter().map(|&v| v.into()).collect();
+        let main_next: Vec<AB::Expr> = main.row_slice(1).unwrap().iter().map(|&v| v.into()).collect();
+        let public_values: Vec<AB::Expr> = builder.public_values().iter().map(|&v| v.into()).collect();
+        let stack_inputs = &public_values[0..2];
+        let stack_output = &public_values[2..3];
+        builder.when_first_row().assert_zero(main_current[0].clone() - stack_inputs[0].clone());
+        builder.when_first_row().assert_zero(main_current[1].clone() - stack_inputs[1].clone());
+        builder.when_last_row().assert_zero(main_current[1].clone() - stack_output[0].clone());
+        builder.when_transition().assert_zero(main_next[1].clone() - (main_current[0].clone() + main_current[1].clone()));
+        builder.when_transition().assert_zero(main_next[0].clone() - main_current[1].clone());
+    }
+}
\ No newline at end of file
@@ -1,4 +1,20 @@
 #[rustfmt::skip]
 #[allow(clippy::all)]
 mod fibonacci;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod fibonacci_base_field;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod fibonacci_canonical;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod fibonacci_generic;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+#[allow(unused_imports)]
+mod fibonacci_plonky3 {
+    use crate::helpers::plonky3::*;
+    include!("fibonacci_plonky3.rs");
+}
 mod test_air;
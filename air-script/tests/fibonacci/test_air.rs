@@ -1,14 +1,18 @@
+use std::path::Path;
+
 #[allow(unused_imports)]
 use winter_air::{Air, AuxRandElements};
-use winter_math::fields::f64::BaseElement as Felt;
+use winter_math::{ToElements, fields::f64::BaseElement as Felt};
 #[allow(unused_imports)]
 use winterfell::{AuxTraceWithMetadata, Trace, TraceTable, matrix::ColMatrix};
 
 use crate::{
     fibonacci::fibonacci::{FibonacciAir, PublicInputs},
-    helpers::{AirTester, MyTraceTable},
+    helpers::{AirTester, MyTraceTable, debug_trace},
 };
 
+const SOURCE_PATH: &str = "tests/fibonacci/fibonacci.air";
+
 #[derive(Clone)]
 struct FibonacciAirTester {}
 
@@ -42,6 +46,10 @@ impl AirTester for FibonacciAirTester {
         let last = Felt::new(2178309); // 32nd Fibonacci number
         PublicInputs::new([one, one], [last])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some(SOURCE_PATH)
+    }
 }
 
 #[test]
@@ -50,11 +58,129 @@ fn test_fibonacci_air() {
     let length = 32;
 
     let main_trace = air_tester.build_main_trace(length);
-    let aux_trace = air_tester.build_aux_trace(length);
     let pub_inputs = air_tester.public_inputs();
     let trace_info = air_tester.build_trace_info(length);
     let options = air_tester.build_proof_options();
 
+    let air = FibonacciAir::new(trace_info, pub_inputs.clone(), options);
+    air_tester.validate(&air, length);
+    air_tester.check_backends(&air);
+
+    let source = air_script::check(Path::new(SOURCE_PATH)).expect("compilation failed");
+    assert_eq!(debug_trace(&source, &main_trace, None, &pub_inputs, usize::MAX), vec![]);
+}
+
+#[test]
+fn test_fibonacci_plonky3_air() {
+    use crate::{
+        fibonacci::fibonacci_plonky3::FibonacciAir,
+        helpers::plonky3::{check_constraints, main_rows},
+    };
+
+    let air_tester = Box::new(FibonacciAirTester {});
+    let mut trace = main_rows(&air_tester.build_main_trace(32));
+    // The last row of Plonky3 is the last row of the trace, which holds the 33rd Fibonacci number
+    let one = Felt::new(1);
+    let public_values = PublicInputs::new([one, one], [Felt::new(3524578)]).to_elements();
+    assert_eq!(check_constraints(&FibonacciAir, &trace, &public_values), Vec::<usize>::new());
+
+    // `b' = a + b` is violated on the row before the corrupted cell, and both transition
+    // constraints on its row
+    trace[5][1] += Felt::new(1);
+    assert_eq!(check_constraints(&FibonacciAir, &trace, &public_values), [4, 5]);
+
+    // The output is only checked on the last row
+    trace[5][1] -= Felt::new(1);
+    trace[31][1] += Felt::new(1);
+    assert_eq!(check_constraints(&FibonacciAir, &trace, &public_values), [30, 31]);
+}
+
+#[test]
+fn test_fibonacci_air_with_corrupted_trace() {
+    let air_tester = Box::new(FibonacciAirTester {});
+    let length = 32;
+
+    // The 6th value of `b` is off by one
+    let mut main_trace = air_tester.build_main_trace(length);
+    let b = main_trace.trace.get(1, 5);
+    main_trace.trace.set(1, 5, b + Felt::new(1));
+    let pub_inputs = air_tester.public_inputs();
+
+    let source = air_script::check(Path::new(SOURCE_PATH)).expect("compilation failed");
+    let violations = debug_trace(&source, &main_trace, None, &pub_inputs, 1);
+    assert_eq!(violations.len(), 1);
+    // `b' = a + b` is violated on the row before the corrupted cell
+    assert_eq!(violations[0].kind, air_ir::RootKind::Integrity(0));
+    assert_eq!(violations[0].row, 4);
+    let next_b = air_ir::TraceAccess::new(0, 1, 1);
+    assert!(
+        violations[0]
+            .reads
+            .contains(&(next_b, air_ir::QuadFelt::from(b + Felt::new(1))))
+    );
+
+    // `a' = b` and `b' = a + b` are then both violated on the row of the corrupted cell
+    let violations = debug_trace(&source, &main_trace, None, &pub_inputs, usize::MAX);
+    let rows: Vec<_> = violations.iter().map(|violation| violation.row).collect();
+    assert_eq!(rows, [4, 5, 5]);
+    let table = air_ir::violation_table(&source, &violations);
+    assert!(table.contains("row 4: integrity constraint #"), "{table}");
+    assert!(table.contains("b' = 14"), "{table}");
+}
+
+#[test]
+fn test_fibonacci_air_with_base_field() {
+    use crate::fibonacci::fibonacci_base_field::{FibonacciAir, PublicInputs};
+
+    let air_tester = Box::new(FibonacciAirTester {});
+    let length = 32;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    let one = Felt::new(1);
+    let pub_inputs = PublicInputs::new([one, one], [Felt::new(2178309)]);
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
+    let air = FibonacciAir::new(trace_info, pub_inputs, options);
+    main_trace.validate::<FibonacciAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+#[test]
+fn test_fibonacci_air_with_generic_base_field() {
+    use crate::fibonacci::fibonacci_generic::{FibonacciAir, PublicInputs};
+
+    let air_tester = Box::new(FibonacciAirTester {});
+    let length = 32;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    let one = Felt::new(1);
+    let pub_inputs = PublicInputs::new([one, one], [Felt::new(2178309)]);
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
+    let air = FibonacciAir::<Felt>::new(trace_info, pub_inputs, options);
+    main_trace.validate::<FibonacciAir<Felt>, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+#[test]
+fn test_fibonacci_air_canonical() {
+    use crate::fibonacci::fibonacci_canonical::{FibonacciAir, PublicInputs};
+
+    let air_tester = Box::new(FibonacciAirTester {});
+    let length = 32;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    let one = Felt::new(1);
+    let pub_inputs = PublicInputs::new([one, one], [Felt::new(2178309)]);
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
     let air = FibonacciAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<FibonacciAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -65,6 +65,10 @@ impl AirTester for FunctionsAirTester {
             BatchingMethod::Linear, // method of batching used in computing DEEP polynomial
         )
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/functions/functions_complex.air")
+    }
 }
 
 #[test]
@@ -80,4 +84,5 @@ fn test_functions_complex_air() {
 
     let air = FunctionsAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<FunctionsAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
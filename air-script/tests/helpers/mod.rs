@@ -1,7 +1,34 @@
-use winter_air::{BatchingMethod, EvaluationFrame, FieldExtension, ProofOptions, TraceInfo};
-use winter_math::fields::f64::BaseElement as Felt;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+use air_codegen_ace::AceEvaluator;
+use air_ir::{
+    BusBoundary, ConstraintEvaluator, EvalContext, PublicInput, QuadFelt, RootKind, TraceAccess,
+    Violation,
+};
+use winter_air::{
+    AuxRandElements, BatchingMethod, EvaluationFrame, FieldExtension, ProofOptions, TraceInfo,
+};
+use winter_math::{FieldElement, ToElements, fields::f64::BaseElement as Felt, polynom};
 use winterfell::{AuxTraceWithMetadata, Trace, TraceTable, matrix::ColMatrix};
 
+pub mod plonky3;
+
+/// The Winterfell items imported by the generated code, re-exported to check that a custom preamble
+/// can import them through the crate instead.
+pub mod winter {
+    pub use winter_air::{
+        Air, AirContext, Assertion, AuxRandElements, EvaluationFrame, ProofOptions, TraceInfo,
+        TransitionConstraintDegree,
+    };
+    pub use winter_math::{
+        ExtensionOf, FieldElement, ToElements, fields::f64::BaseElement as Felt,
+    };
+    pub use winter_utils::{ByteWriter, Serializable};
+}
+
 /// We need to encapsulate the trace table in a struct to manually implement the `aux_trace_width`
 /// method of the `Table` trait. Otherwise, using only a TraceTable<Felt> will return an
 /// `aux_trace_width` of 0 even if we provide a non-empty aux trace in `Trace::validate`,
@@ -37,8 +64,180 @@ impl Trace for MyTraceTable {
     }
 }
 
+/// Returns the first `max_violations` constraints of `air` violated by `main_trace` and
+/// `aux_trace`, given the public inputs of the AIR generated from it, see [air_ir::debug_trace].
+pub fn debug_trace(
+    air: &air_ir::Air,
+    main_trace: &MyTraceTable,
+    aux_trace: Option<&AuxTraceWithMetadata<Felt>>,
+    pub_inputs: &impl ToElements<Felt>,
+    max_violations: usize,
+) -> Vec<Violation> {
+    let columns = |matrix: &ColMatrix<Felt>| -> Vec<Vec<Felt>> {
+        (0..matrix.num_cols())
+            .map(|column| matrix.get_column(column).to_vec())
+            .collect()
+    };
+    let main = columns(main_trace.main_segment());
+    let (aux, rand) = match aux_trace {
+        None => (vec![], vec![]),
+        Some(aux_trace) => {
+            let aux = columns(&aux_trace.aux_trace)
+                .into_iter()
+                .map(|column| column.into_iter().map(QuadFelt::from).collect())
+                .collect();
+            let rand = aux_trace.aux_rand_elements.rand_elements();
+            (aux, rand.iter().copied().map(QuadFelt::from).collect())
+        },
+    };
+
+    let public = public_values(air, pub_inputs);
+    air_ir::debug_trace(air, &main, &aux, &public, &rand, max_violations)
+}
+
+/// Returns the values of the public inputs of `air`, given those of the AIR generated from it.
+fn public_values(air: &air_ir::Air, pub_inputs: &impl ToElements<Felt>) -> Vec<Vec<Felt>> {
+    // The public inputs are flattened in the order of `air`, i.e. sorted by name. Tables are only
+    // read by bus boundaries, which are not part of the constraint graph, so their regions are
+    // left zeroed.
+    let mut elements = pub_inputs.to_elements().into_iter();
+    air.public_inputs()
+        .map(|input| match input {
+            PublicInput::Vector { size, .. } => elements.by_ref().take(*size).collect(),
+            PublicInput::Table { .. } => vec![Felt::ZERO; input.size()],
+        })
+        .collect()
+}
+
+/// Checks that the Winterfell `air` generated from the AirScript source at `source_path` agrees
+/// with the ACE circuit of the source on the evaluation of every constraint, in several random
+/// frames, given the public inputs of `air`.
+///
+/// The generated code is compiled with the tests, so each constraint is evaluated by calling the
+/// `evaluate_transition` and `evaluate_aux_transition` methods of `air`, and by reading its
+/// assertions. The assertions of buses bounded by a variable-length public input table are not
+/// constraints of the source AIR, and are skipped.
+pub fn assert_backends_agree<A: winter_air::Air<BaseField = Felt>>(
+    air: &A,
+    source_path: &str,
+    pub_inputs: &impl ToElements<Felt>,
+) {
+    let source = air_script::check(Path::new(source_path)).expect("compilation failed");
+    let ace = AceEvaluator::new(&source).expect("unable to build the ACE circuit");
+    let public: Vec<Vec<QuadFelt>> = public_values(&source, pub_inputs)
+        .into_iter()
+        .map(|values| values.into_iter().map(QuadFelt::from).collect())
+        .collect();
+    let [main_width, aux_width] =
+        [0, 1].map(|segment| source.trace_segment_widths.get(segment).copied().unwrap_or(0));
+
+    for _ in 0..3 {
+        let ctx = EvalContext {
+            log_trace_len: air.trace_length().ilog2(),
+            main: [0, 1].map(|_| rand_quad_vec(main_width as usize)),
+            aux: [0, 1].map(|_| rand_quad_vec(aux_width as usize)),
+            public: public.clone(),
+            rand: rand_quad_vec(source.num_random_values as usize),
+            periodic: Default::default(),
+            z: rand_quad(),
+        }
+        .with_periodic_evals(&source);
+        for (kind, evals) in winterfell_evaluations(air, &source, &ctx) {
+            assert_eq!(evals.len(), ace.num_roots(kind), "{source_path}: {kind:?}");
+            for (i, eval) in evals.into_iter().enumerate() {
+                assert_eq!(
+                    eval,
+                    ace.evaluate_root(kind, i, &ctx),
+                    "{source_path}: root {i} of {kind:?}"
+                );
+            }
+        }
+    }
+}
+
+/// Evaluates the constraints of the Winterfell `air` generated from `source` in the frame of
+/// `ctx`, grouped as the constraint roots of `source`.
+fn winterfell_evaluations<A: winter_air::Air<BaseField = Felt>>(
+    air: &A,
+    source: &air_ir::Air,
+    ctx: &EvalContext,
+) -> Vec<(RootKind, Vec<QuadFelt>)> {
+    // The periodic columns are evaluated at `z` as by the Winterfell verifier
+    let trace_len = air.trace_length();
+    let periodic: Vec<QuadFelt> = air
+        .get_periodic_column_polys()
+        .iter()
+        .map(|poly| polynom::eval(poly, ctx.z.exp_vartime((trace_len / poly.len()) as u64)))
+        .collect();
+    let main = EvaluationFrame::from_rows(ctx.main[0].clone(), ctx.main[1].clone());
+    let mut main_evals = vec![QuadFelt::ZERO; air.context().num_main_transition_constraints()];
+    air.evaluate_transition(&main, &periodic, &mut main_evals);
+
+    // Each assertion is the constraint `column - value` on its row
+    let mut assertions: Vec<_> = air
+        .get_assertions()
+        .into_iter()
+        .map(|assertion| {
+            (0, assertion.column(), assertion.first_step(), assertion.values()[0].into())
+        })
+        .collect();
+    let mut aux_evals = vec![QuadFelt::ZERO; air.context().num_aux_transition_constraints()];
+    if source.has_aux_segment() {
+        let aux = EvaluationFrame::from_rows(ctx.aux[0].clone(), ctx.aux[1].clone());
+        let rand = AuxRandElements::new(ctx.rand.clone());
+        air.evaluate_aux_transition(&main, &aux, &periodic, &rand, &mut aux_evals);
+        assertions.extend(air.get_aux_assertions(&rand).into_iter().map(|assertion| {
+            (1, assertion.column(), assertion.first_step(), assertion.values()[0])
+        }));
+    }
+
+    let bus_columns = source.bus_column_layout();
+    let is_table_boundary = |column: usize, boundary: &BusBoundary| {
+        matches!(boundary, BusBoundary::PublicInputTable(access)
+            if bus_columns[&access.bus_name] == column)
+    };
+    let last_step = trace_len - air.context().num_transition_exemptions();
+    let mut boundary_first = vec![];
+    let mut boundary_last = vec![];
+    for (segment, column, step, value) in assertions {
+        let is_first = step == 0;
+        assert!(is_first || step == last_step, "unexpected step {step} of an assertion");
+        if segment == 1
+            && source
+                .buses
+                .values()
+                .any(|bus| is_table_boundary(column, if is_first { &bus.first } else { &bus.last }))
+        {
+            continue;
+        }
+        let eval = ctx.trace_value(&TraceAccess::new(segment, column, 0)) - value;
+        if is_first {
+            boundary_first.push(eval);
+        } else {
+            boundary_last.push(eval);
+        }
+    }
+
+    vec![
+        (RootKind::Integrity(0), main_evals),
+        (RootKind::Integrity(1), aux_evals),
+        (RootKind::BoundaryFirst, boundary_first),
+        (RootKind::BoundaryLast, boundary_last),
+    ]
+}
+
+/// Generates a random extension field element.
+fn rand_quad() -> QuadFelt {
+    QuadFelt::new(Felt::new(rand::random()), Felt::new(rand::random()))
+}
+
+/// Generates a vector of length `len` of random extension field elements.
+fn rand_quad_vec(len: usize) -> Vec<QuadFelt> {
+    (0..len).map(|_| rand_quad()).collect()
+}
+
 pub trait AirTester {
-    type PubInputs;
+    type PubInputs: ToElements<Felt>;
 
     fn build_main_trace(&self, length: usize) -> MyTraceTable;
     fn public_inputs(&self) -> Self::PubInputs;
@@ -70,4 +269,37 @@ pub trait AirTester {
             BatchingMethod::Linear, // method of batching used in computing DEEP polynomial
         )
     }
+
+    /// Returns the path of the AirScript source of the tested AIR, from which the constraints
+    /// violated by an invalid trace are reported by [AirTester::validate].
+    fn source_path(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Checks that `air` agrees with the ACE circuit of the source AIR on the evaluation of every
+    /// constraint, see [assert_backends_agree].
+    fn check_backends<A: winter_air::Air<BaseField = Felt>>(&self, air: &A) {
+        let path = self.source_path().expect("the source of the tested AIR is unknown");
+        assert_backends_agree(air, path, &self.public_inputs());
+    }
+
+    /// Validates the trace of `length` rows built by this tester against `air`. If the trace is
+    /// invalid, the constraints of the source AIR it violates are printed before panicking.
+    fn validate<A: winter_air::Air<BaseField = Felt>>(&self, air: &A, length: usize) {
+        let main_trace = self.build_main_trace(length);
+        let aux_trace = self.build_aux_trace(length);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            main_trace.validate::<A, Felt>(air, aux_trace.as_ref())
+        }));
+        let Err(err) = result else {
+            return;
+        };
+        if let Some(path) = self.source_path() {
+            let source = air_script::check(Path::new(path)).expect("compilation failed");
+            let pub_inputs = self.public_inputs();
+            let violations = debug_trace(&source, &main_trace, aux_trace.as_ref(), &pub_inputs, 10);
+            eprintln!("{}", air_ir::violation_table(&source, &violations));
+        }
+        panic::resume_unwind(err);
+    }
 }
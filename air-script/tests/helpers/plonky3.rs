@@ -0,0 +1,209 @@
+//! The subset of the Plonky3 0.3 API used by the code of the Plonky3 backend, to compile it with
+//! the tests and check traces against it.
+//!
+//! The Plonky3 crates are not dependencies of this workspace, so each module below mirrors the
+//! signatures of the items the generated code imports from the crate of the same name. The
+//! generated code is included in a module which glob-imports this one, such that its imports
+//! resolve to these modules, see [check_constraints].
+
+use winter_math::{FieldElement, fields::f64::BaseElement as Felt};
+use winterfell::Trace;
+
+use super::MyTraceTable;
+
+pub mod p3_air {
+    use std::ops::{Add, Mul, Sub};
+
+    use super::{p3_field::PrimeCharacteristicRing, p3_matrix::Matrix};
+
+    pub trait BaseAir<F> {
+        fn width(&self) -> usize;
+    }
+
+    pub trait BaseAirWithPublicValues<F>: BaseAir<F> {
+        fn num_public_values(&self) -> usize {
+            0
+        }
+    }
+
+    pub trait Air<AB: AirBuilder>: BaseAir<AB::F> {
+        fn eval(&self, builder: &mut AB);
+    }
+
+    pub trait AirBuilder: Sized {
+        type F;
+        type Expr: PrimeCharacteristicRing
+            + Clone
+            + Add<Output = Self::Expr>
+            + Sub<Output = Self::Expr>
+            + Mul<Output = Self::Expr>;
+        type Var: Into<Self::Expr> + Copy;
+        type M: Matrix<Self::Var>;
+
+        fn main(&self) -> Self::M;
+
+        fn is_first_row(&self) -> Self::Expr;
+
+        fn is_last_row(&self) -> Self::Expr;
+
+        fn is_transition(&self) -> Self::Expr;
+
+        fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I);
+
+        fn when_first_row(&mut self) -> FilteredAirBuilder<'_, Self> {
+            let condition = self.is_first_row();
+            FilteredAirBuilder { inner: self, condition }
+        }
+
+        fn when_last_row(&mut self) -> FilteredAirBuilder<'_, Self> {
+            let condition = self.is_last_row();
+            FilteredAirBuilder { inner: self, condition }
+        }
+
+        fn when_transition(&mut self) -> FilteredAirBuilder<'_, Self> {
+            let condition = self.is_transition();
+            FilteredAirBuilder { inner: self, condition }
+        }
+    }
+
+    pub trait AirBuilderWithPublicValues: AirBuilder {
+        type PublicVar: Into<Self::Expr> + Copy;
+
+        fn public_values(&self) -> &[Self::PublicVar];
+    }
+
+    /// An [AirBuilder] whose constraints are only enforced where `condition` is non-zero.
+    pub struct FilteredAirBuilder<'a, AB: AirBuilder> {
+        inner: &'a mut AB,
+        condition: AB::Expr,
+    }
+
+    impl<AB: AirBuilder> FilteredAirBuilder<'_, AB> {
+        pub fn assert_zero<I: Into<AB::Expr>>(&mut self, x: I) {
+            self.inner.assert_zero(self.condition.clone() * x.into());
+        }
+    }
+}
+
+pub mod p3_field {
+    pub trait PrimeCharacteristicRing: Sized {
+        const ZERO: Self;
+        const ONE: Self;
+
+        fn from_u64(value: u64) -> Self;
+    }
+}
+
+pub mod p3_goldilocks {
+    /// The Goldilocks field of Plonky3 has the same modulus as the base field of Winterfell.
+    pub type Goldilocks = super::Felt;
+}
+
+pub mod p3_matrix {
+    use std::ops::Deref;
+
+    pub trait Matrix<T> {
+        fn row_slice(&self, r: usize) -> Option<impl Deref<Target = [T]>>;
+    }
+}
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAirWithPublicValues};
+use p3_field::PrimeCharacteristicRing;
+use p3_matrix::Matrix;
+
+impl PrimeCharacteristicRing for Felt {
+    const ZERO: Self = <Felt as FieldElement>::ZERO;
+    const ONE: Self = <Felt as FieldElement>::ONE;
+
+    fn from_u64(value: u64) -> Self {
+        Felt::new(value)
+    }
+}
+
+/// The current and next rows of the main trace.
+pub struct Window(Vec<Felt>, Vec<Felt>);
+
+impl Matrix<Felt> for Window {
+    fn row_slice(&self, r: usize) -> Option<impl std::ops::Deref<Target = [Felt]>> {
+        match r {
+            0 => Some(self.0.as_slice()),
+            1 => Some(self.1.as_slice()),
+            _ => None,
+        }
+    }
+}
+
+/// Evaluates the constraints of an AIR on a single row of the trace, recording whether any of
+/// them is non-zero.
+pub struct CheckConstraintsBuilder<'a> {
+    row: usize,
+    trace: &'a [Vec<Felt>],
+    public_values: &'a [Felt],
+    failed: bool,
+}
+
+impl AirBuilder for CheckConstraintsBuilder<'_> {
+    type F = Felt;
+    type Expr = Felt;
+    type Var = Felt;
+    type M = Window;
+
+    fn main(&self) -> Window {
+        // The next row of the last one wraps around, as in the Plonky3 prover
+        let next = (self.row + 1) % self.trace.len();
+        Window(self.trace[self.row].clone(), self.trace[next].clone())
+    }
+
+    fn is_first_row(&self) -> Felt {
+        Felt::from_u64((self.row == 0) as u64)
+    }
+
+    fn is_last_row(&self) -> Felt {
+        Felt::from_u64((self.row == self.trace.len() - 1) as u64)
+    }
+
+    fn is_transition(&self) -> Felt {
+        Felt::from_u64((self.row != self.trace.len() - 1) as u64)
+    }
+
+    fn assert_zero<I: Into<Felt>>(&mut self, x: I) {
+        self.failed |= x.into() != <Felt as FieldElement>::ZERO;
+    }
+}
+
+impl AirBuilderWithPublicValues for CheckConstraintsBuilder<'_> {
+    type PublicVar = Felt;
+
+    fn public_values(&self) -> &[Felt] {
+        self.public_values
+    }
+}
+
+/// Evaluates the constraints of the Plonky3 `air` on each row of `trace`, given as a list of rows,
+/// and returns the rows on which any of them is violated.
+///
+/// # Panics
+///
+/// Panics if the width of `trace` or the number of `public_values` differ from those of `air`.
+pub fn check_constraints<A>(air: &A, trace: &[Vec<Felt>], public_values: &[Felt]) -> Vec<usize>
+where
+    A: BaseAirWithPublicValues<Felt> + for<'a> Air<CheckConstraintsBuilder<'a>>,
+{
+    assert!(trace.iter().all(|row| row.len() == air.width()));
+    assert_eq!(public_values.len(), air.num_public_values());
+    (0..trace.len())
+        .filter(|&row| {
+            let mut builder = CheckConstraintsBuilder { row, trace, public_values, failed: false };
+            air.eval(&mut builder);
+            builder.failed
+        })
+        .collect()
+}
+
+/// Returns the rows of the main segment of `trace`.
+pub fn main_rows(trace: &MyTraceTable) -> Vec<Vec<Felt>> {
+    let main = trace.main_segment();
+    (0..main.num_rows())
+        .map(|row| (0..main.num_cols()).map(|col| main.get(col, row)).collect())
+        .collect()
+}
@@ -37,6 +37,10 @@ impl AirTester for TraceAccessAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/indexed_trace_access/indexed_trace_access.air")
+    }
 }
 
 #[test]
@@ -52,4 +56,5 @@ fn test_indexed_trace_access_air() {
 
     let air = TraceAccessAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<TraceAccessAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -49,6 +49,10 @@ impl AirTester for ListComprehensionAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/list_comprehension/list_comprehension.air")
+    }
 }
 
 #[test]
@@ -64,4 +68,5 @@ fn test_list_comprehension_air() {
 
     let air = ListComprehensionAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<ListComprehensionAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -50,6 +50,10 @@ impl AirTester for ListFoldingAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/list_folding/list_folding.air")
+    }
 }
 
 #[test]
@@ -65,4 +69,5 @@ fn test_list_folding_air() {
 
     let air = ListFoldingAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<ListFoldingAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -14,8 +14,10 @@ mod constant_in_range;
 mod constants;
 #[allow(unused_variables, dead_code, unused_mut)]
 mod constraint_comprehension;
+mod diagnostics;
 #[allow(unused_variables, dead_code, unused_mut)]
 mod evaluators;
+mod explain;
 #[allow(unused_variables, dead_code, unused_mut)]
 mod fibonacci;
 #[allow(unused_variables, dead_code, unused_mut)]
@@ -28,6 +30,7 @@ mod list_comprehension;
 mod list_folding;
 #[allow(unused_variables, dead_code, unused_mut)]
 mod periodic_columns;
+mod provenance;
 #[allow(unused_variables, dead_code, unused_mut)]
 mod pub_inputs;
 #[allow(unused_variables, dead_code, unused_mut)]
@@ -36,5 +39,7 @@ mod selectors;
 mod system;
 #[allow(unused_variables, dead_code, unused_mut)]
 mod trace_col_groups;
+mod transpile;
 #[allow(unused_variables, dead_code, unused_mut)]
 mod variables;
+mod watch;
@@ -34,6 +34,10 @@ impl AirTester for PeriodicColumnsAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/periodic_columns/periodic_columns.air")
+    }
 }
 
 #[test]
@@ -49,4 +53,5 @@ fn test_periodic_columns_air() {
 
     let air = PeriodicColumnsAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<PeriodicColumnsAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
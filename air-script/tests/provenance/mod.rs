@@ -0,0 +1,102 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use air_ir::{CodeGenerator, PassConfig, Provenance};
+use air_pass::Pass;
+use air_script::{provenance, verify_provenance};
+use miden_diagnostics::{
+    CodeMap, DefaultEmitter, DiagnosticsHandler, term::termcolor::ColorChoice,
+};
+
+const ROOT: &str = "tests/provenance/provenance.air";
+const LIB: &str = "tests/provenance/provenance_lib.air";
+
+/// Transpiles the given file to Winterfell, with its provenance as header.
+fn transpile(path: &str) -> String {
+    let codemap = Arc::new(CodeMap::new());
+    let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), codemap.clone(), emitter);
+
+    let program = air_parser::parse_file(&diagnostics, codemap, path).expect("parsing failed");
+    let mut options = vec!["pipeline=WithMIR".to_string()];
+    options.extend(PassConfig::default().options());
+    let provenance = provenance(&program, options);
+    let air = air_parser::transforms::ConstantPropagation::new(&diagnostics)
+        .chain(mir::passes::AstToMir::new(&diagnostics))
+        .chain(mir::passes::Inlining::new(&diagnostics))
+        .chain(mir::passes::Unrolling::new(&diagnostics))
+        .chain(air_ir::passes::MirToAir::new(&diagnostics))
+        .chain(air_ir::passes::TrivialConstraints::new(&diagnostics))
+        .chain(air_ir::passes::BusOpExpand::new(&diagnostics))
+        .run(program)
+        .expect("lowering failed");
+
+    air_codegen_winter::CodeGenerator::default()
+        .with_provenance(provenance)
+        .generate(&air)
+        .expect("code generation failed")
+}
+
+/// Copies the test sources to a fresh directory, replacing `from` by `to` in the imported module.
+fn copy_sources(name: &str, from: &str, to: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("air-script-provenance-{name}"));
+    fs::create_dir_all(&dir).unwrap();
+    let root = dir.join("provenance.air");
+    let lib = dir.join("provenance_lib.air");
+    fs::copy(ROOT, &root).unwrap();
+    fs::write(&lib, fs::read_to_string(LIB).unwrap().replace(from, to)).unwrap();
+    (root, lib)
+}
+
+#[test]
+fn identical_sources_have_identical_headers() {
+    let code = transpile(ROOT);
+    let provenance = Provenance::from_header(&code).expect("missing provenance header");
+    assert_eq!(provenance.compiler_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        provenance.options,
+        [
+            "pipeline=WithMIR",
+            "max-unroll=10000",
+            "max-unrolled-ops=1000000",
+            "max-exponent=64"
+        ]
+    );
+
+    let (root, _) = copy_sources("identical", "", "");
+    assert_eq!(Provenance::from_header(&transpile(root.to_str().unwrap())), Some(provenance));
+}
+
+#[test]
+fn changing_imported_module_changes_hash() {
+    let code = transpile(ROOT);
+    let provenance = Provenance::from_header(&code).unwrap();
+
+    let (root, _) = copy_sources("changed", "STEP = 2", "STEP = 3");
+    let changed = Provenance::from_header(&transpile(root.to_str().unwrap())).unwrap();
+    assert_ne!(changed.source_hash, provenance.source_hash);
+}
+
+#[test]
+fn verify_provenance_of_sources() {
+    let code = transpile(ROOT);
+    assert!(verify_provenance(&code, &[ROOT, LIB]));
+
+    let (root, lib) = copy_sources("verify", "STEP = 2", "STEP = 3");
+    assert!(!verify_provenance(&code, &[&root, &lib]));
+    // The provided imported module is used, rather than the one next to the root module
+    assert!(!verify_provenance(&code, &[ROOT, lib.to_str().unwrap()]));
+    assert!(!verify_provenance("// no provenance", &[ROOT, LIB]));
+}
+
+#[test]
+fn header_records_compiler_version_and_pass_config() {
+    let code = transpile(ROOT);
+    let header: Vec<_> = code.lines().take_while(|line| line.starts_with("//")).collect();
+    assert_eq!(header[0], format!("// airscript-version: {}", env!("CARGO_PKG_VERSION")));
+    assert!(
+        header
+            .iter()
+            .any(|line| line.starts_with("// compile-options: pipeline=WithMIR")
+                && line.contains("max-exponent=64"))
+    );
+}
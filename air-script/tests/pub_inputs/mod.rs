@@ -1,4 +1,10 @@
 #[rustfmt::skip]
 #[allow(clippy::all)]
 mod pub_inputs;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod pub_inputs_hooks;
+#[rustfmt::skip]
+#[allow(clippy::all)]
+mod pub_inputs_preamble;
 mod test_air;
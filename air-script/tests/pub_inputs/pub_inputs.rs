@@ -3,16 +3,21 @@ use winter_math::fields::f64::BaseElement as Felt;
 use winter_math::{ExtensionOf, FieldElement, ToElements};
 use winter_utils::{ByteWriter, Serializable};
 
+/// The public inputs of the AIR.
+///
+/// They are serialized and flattened by `to_elements` in the canonical order, sorted by name,
+/// which is the order in which every verifier absorbs them into the transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PublicInputs {
-    overflow_addrs: [Felt; 4],
     program_hash: [Felt; 4],
     stack_inputs: [Felt; 4],
     stack_outputs: [Felt; 20],
+    overflow_addrs: [Felt; 4],
 }
 
 impl PublicInputs {
-    pub fn new(overflow_addrs: [Felt; 4], program_hash: [Felt; 4], stack_inputs: [Felt; 4], stack_outputs: [Felt; 20]) -> Self {
-        Self { overflow_addrs, program_hash, stack_inputs, stack_outputs }
+    pub fn new(program_hash: [Felt; 4], stack_inputs: [Felt; 4], stack_outputs: [Felt; 20], overflow_addrs: [Felt; 4]) -> Self {
+        Self { program_hash, stack_inputs, stack_outputs, overflow_addrs }
     }
 }
 
@@ -1,5 +1,10 @@
+use std::sync::Arc;
+
+use miden_diagnostics::{
+    CodeMap, DefaultEmitter, DiagnosticsHandler, term::termcolor::ColorChoice,
+};
 use winter_air::Air;
-use winter_math::fields::f64::BaseElement as Felt;
+use winter_math::{ToElements, fields::f64::BaseElement as Felt};
 use winterfell::{Trace, TraceTable};
 
 use crate::{
@@ -33,7 +38,11 @@ impl AirTester for PubInputsAirTester {
 
     fn public_inputs(&self) -> PublicInputs {
         let zero = Felt::new(0);
-        PublicInputs::new([zero; 4], [zero; 4], [zero; 4], [zero; 20])
+        PublicInputs::new([zero; 4], [zero; 4], [zero; 20], [zero; 4])
+    }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/pub_inputs/pub_inputs.air")
     }
 }
 
@@ -50,4 +59,82 @@ fn test_pub_inputs_air() {
 
     let air = PubInputsAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<PubInputsAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+#[test]
+fn test_pub_inputs_air_with_hooks() {
+    use crate::pub_inputs::pub_inputs_hooks::{PubInputsAir, PublicInputs};
+
+    let air_tester = Box::new(PubInputsAirTester {});
+    let length = 1024;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    // the public inputs are derived from the first row of the trace by the generated code
+    let pub_inputs = PublicInputs::from(&main_trace);
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
+    let air = PubInputsAir::new(trace_info, pub_inputs, options);
+    main_trace.validate::<PubInputsAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+#[test]
+fn test_pub_inputs_air_with_preamble() {
+    use crate::pub_inputs::pub_inputs_preamble::{PubInputsAir, PublicInputs};
+
+    // the items appended by the hooks read the metadata of the Air
+    assert_eq!(PubInputsAir::MAIN_COLUMNS, ["a", "b", "c", "d"]);
+    assert_eq!(PubInputsAir::NUM_MAIN_INTEGRITY_CONSTRAINTS, 1);
+
+    let air_tester = Box::new(PubInputsAirTester {});
+    let length = 1024;
+
+    let main_trace = air_tester.build_main_trace(length);
+    let aux_trace = air_tester.build_aux_trace(length);
+    let zero = Felt::new(0);
+    let pub_inputs = PublicInputs::new([zero; 4], [zero; 4], [zero; 20], [zero; 4]);
+    let trace_info = air_tester.build_trace_info(length);
+    let options = air_tester.build_proof_options();
+
+    let air = PubInputsAir::new(trace_info, pub_inputs, options);
+    main_trace.validate::<PubInputsAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
+}
+
+#[test]
+fn test_pub_inputs_are_flattened_in_canonical_order() {
+    // the constructor takes the public inputs in declaration order
+    let declared = [
+        ("program_hash", 4),
+        ("stack_inputs", 4),
+        ("stack_outputs", 20),
+        ("overflow_addrs", 4),
+    ];
+    let values = |i: u64, size: usize| (0..size as u64).map(move |j| Felt::new(100 * i + j));
+    let pub_inputs = PublicInputs::new(
+        core::array::from_fn(|j| Felt::new(j as u64)),
+        core::array::from_fn(|j| Felt::new(100 + j as u64)),
+        core::array::from_fn(|j| Felt::new(200 + j as u64)),
+        core::array::from_fn(|j| Felt::new(300 + j as u64)),
+    );
+    assert_eq!(pub_inputs.clone(), pub_inputs);
+
+    // they are flattened in the canonical order of the Air, which is shared by all backends
+    let codemap = Arc::new(CodeMap::new());
+    let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), codemap.clone(), emitter);
+    let ast = air_parser::parse_file(&diagnostics, codemap, "tests/pub_inputs/pub_inputs.air")
+        .expect("parsing failed");
+    let air = air_ir::compile(&diagnostics, ast, Default::default()).expect("compilation failed");
+
+    let mut expected = Vec::new();
+    for input in air.public_inputs() {
+        let i = declared.iter().position(|(name, _)| input.name() == *name).unwrap();
+        assert_eq!(declared[i].1, input.size());
+        expected.extend(values(i as u64, input.size()));
+    }
+    assert_eq!(pub_inputs.to_elements(), expected);
 }
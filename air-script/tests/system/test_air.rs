@@ -36,6 +36,10 @@ impl AirTester for SystemAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/system/system.air")
+    }
 }
 
 #[test]
@@ -51,4 +55,5 @@ fn test_system_air() {
 
     let air = SystemAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<SystemAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -43,6 +43,10 @@ impl AirTester for TraceColGroupAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/trace_col_groups/trace_col_groups.air")
+    }
 }
 
 #[test]
@@ -58,4 +62,5 @@ fn test_trace_col_groups_air() {
 
     let air = TraceColGroupAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<TraceColGroupAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
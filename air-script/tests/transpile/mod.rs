@@ -0,0 +1,46 @@
+use std::{fs, process::Command};
+
+/// Runs `airc transpile` on the file at `path` with the given extra arguments, and returns the
+/// generated code without its provenance header.
+fn transpile(path: &str, args: &[&str]) -> String {
+    let dir = std::env::temp_dir().join("air-script-transpile");
+    fs::create_dir_all(&dir).unwrap();
+    let name = path.rsplit('/').next().unwrap().replace(".air", ".rs");
+    let output_path = dir.join(format!("{}-{name}", args.join("").replace('-', "")));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_airc"))
+        .args(["transpile", path, "-o", output_path.to_str().unwrap()])
+        .args(args)
+        .output()
+        .expect("failed to run airc");
+    assert!(
+        output.status.success(),
+        "transpiling {path} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let code = fs::read_to_string(output_path).expect("missing generated code");
+    let (_, code) = code.split_once("\n\n").expect("missing provenance header");
+    code.to_string()
+}
+
+#[test]
+fn transpile_canonical() {
+    let generated_air = transpile("tests/fibonacci/fibonacci.air", &["--canonicalize"]);
+    let expected = fs::read_to_string("tests/fibonacci/fibonacci_canonical.rs").unwrap();
+    assert_eq!(generated_air, expected);
+}
+
+#[test]
+fn transpile_canonical_buses() {
+    for path in [
+        "tests/buses/buses_simple.air",
+        "tests/buses/buses_complex.air",
+        "tests/buses/buses_varlen_boundary_both.air",
+        "tests/buses/buses_when_with.air",
+    ] {
+        for target in ["winterfell", "markdown"] {
+            transpile(path, &["--canonicalize", "--target", target]);
+        }
+    }
+}
@@ -37,6 +37,10 @@ impl AirTester for VariablesAirTester {
         let zero = Felt::new(0);
         PublicInputs::new([zero; 16], [zero; 16])
     }
+
+    fn source_path(&self) -> Option<&'static str> {
+        Some("tests/variables/variables.air")
+    }
 }
 
 #[test]
@@ -52,4 +56,5 @@ fn test_variables_air() {
 
     let air = VariablesAir::new(trace_info, pub_inputs, options);
     main_trace.validate::<VariablesAir, Felt>(&air, aux_trace.as_ref());
+    air_tester.check_backends(&air);
 }
@@ -0,0 +1,56 @@
+use std::{fs, sync::Arc};
+
+use air_script::Watcher;
+use miden_diagnostics::{NullEmitter, term::termcolor::ColorChoice};
+
+const SOURCE: &str = "
+def WatchAir
+
+trace_columns {
+    main: [a, b],
+}
+
+public_inputs {
+    stack_inputs: [16],
+}
+
+boundary_constraints {
+    enf a.first = 0;
+}
+
+integrity_constraints {
+    enf a' = a + b;
+}";
+
+#[test]
+fn watcher_recompiles_on_change() {
+    let dir = std::env::temp_dir().join("air-script-watch");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("watch.air");
+    fs::write(&path, SOURCE).unwrap();
+
+    let emitter = Arc::new(NullEmitter::new(ColorChoice::Never));
+    let mut watcher = Watcher::with_emitter(&path, emitter);
+
+    // The file is checked when first polled, and not again until it changes
+    let air = watcher.poll().expect("file not checked").expect("check failed");
+    assert_eq!(air.name(), "WatchAir");
+    assert!(watcher.poll().is_none());
+    fs::write(&path, SOURCE).unwrap();
+    assert!(watcher.poll().is_none());
+
+    // A partially written file fails to compile, without stopping the watcher
+    fs::write(&path, &SOURCE[..SOURCE.len() / 2]).unwrap();
+    assert!(watcher.poll().expect("file not checked").is_err());
+
+    let renamed = SOURCE.replace("WatchAir", "RenamedAir");
+    fs::write(&path, &renamed).unwrap();
+    let air = watcher.poll().expect("file not checked").expect("check failed");
+    assert_eq!(air.name(), "RenamedAir");
+
+    // A missing file is skipped until it exists again
+    fs::remove_file(&path).unwrap();
+    assert!(watcher.poll().is_none());
+    fs::write(&path, SOURCE).unwrap();
+    assert!(watcher.poll().expect("file not checked").is_ok());
+}
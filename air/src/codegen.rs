@@ -6,3 +6,62 @@ pub trait CodeGenerator {
     /// Generates code using this generator, consuming it in the process
     fn generate(&self, ir: &crate::Air) -> anyhow::Result<Self::Output>;
 }
+
+/// Describes which compiler and which sources produced a generated artifact, so that the artifact
+/// can be audited against the AirScript it claims to implement.
+///
+/// Backends embed it in their output as a header, one `key: value` field per line, which can be
+/// read back with [Provenance::from_header].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// The version of the AirScript compiler which produced the artifact
+    pub compiler_version: String,
+    /// The hex-encoded hash of the canonicalized AirScript sources
+    pub source_hash: String,
+    /// The compile options the artifact was produced with, e.g. `pipeline=WithMIR`
+    pub options: Vec<String>,
+}
+impl Provenance {
+    const COMPILER_VERSION: &'static str = "airscript-version";
+    const SOURCE_HASH: &'static str = "source-hash";
+    const OPTIONS: &'static str = "compile-options";
+
+    /// Returns the lines of the provenance header, without any comment syntax
+    pub fn header_lines(&self) -> [String; 3] {
+        [
+            format!("{}: {}", Self::COMPILER_VERSION, self.compiler_version),
+            format!("{}: {}", Self::SOURCE_HASH, self.source_hash),
+            format!("{}: {}", Self::OPTIONS, self.options.join(" ")),
+        ]
+    }
+
+    /// Recovers the [Provenance] from the header of a generated artifact.
+    ///
+    /// Leading comment markers (`//` or `#`) are ignored, as are lines which are not part of the
+    /// header. Returns `None` if any of the fields is missing.
+    pub fn from_header(header: &str) -> Option<Self> {
+        let mut compiler_version = None;
+        let mut source_hash = None;
+        let mut options = None;
+        for line in header.lines() {
+            let line = line.trim_start().trim_start_matches(['/', '#']).trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                Self::COMPILER_VERSION => compiler_version = Some(value.to_string()),
+                Self::SOURCE_HASH => source_hash = Some(value.to_string()),
+                Self::OPTIONS => {
+                    options = Some(value.split_whitespace().map(str::to_string).collect())
+                },
+                _ => (),
+            }
+        }
+        Some(Self {
+            compiler_version: compiler_version?,
+            source_hash: source_hash?,
+            options: options?,
+        })
+    }
+}
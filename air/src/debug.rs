@@ -0,0 +1,193 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use air_parser::Symbol;
+use winter_math::{FieldElement, fields::f64::BaseElement as Felt};
+
+use crate::{
+    Air, ConstraintEvaluator, ConstraintRoot, DivisorSpec, EvalContext, NUM_TRANSITION_EXEMPTIONS,
+    QuadFelt, QualifiedIdentifier, RootKind, TraceAccess,
+};
+
+/// A constraint which does not evaluate to zero on a row of a trace, as reported by [debug_trace].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The group of the violated constraint, see [RootKind::roots]
+    pub kind: RootKind,
+    /// The index of the violated constraint among the roots of its group
+    pub index: usize,
+    /// The labels of the violated constraint, see [crate::ConstraintMeta::labels]
+    pub labels: Vec<Symbol>,
+    /// The row on which the constraint is violated
+    pub row: usize,
+    /// The value of the constraint on that row, which is not zero
+    pub value: QuadFelt,
+    /// The value of each trace cell read by the constraint, relative to that row
+    pub reads: Vec<(TraceAccess, QuadFelt)>,
+}
+
+/// Checks the constraints of `air` against a trace, and returns the first `max_violations`
+/// constraints which do not evaluate to zero, in row order.
+///
+/// The trace is given as its columns, i.e. `main[c][r]` is the value of the `c`-th column of the
+/// main trace on row `r`, and `aux` is empty if `air` has no aux trace. Public inputs are given in
+/// the same order as [Air::public_inputs], and `rand` are the random values from which the aux
+/// trace was built.
+///
+/// The constraints are checked on the rows on which the Winterfell backend enforces them: the last
+/// `e` rows are exempted from integrity constraints, where `e` is the largest exemption of their
+/// divisors, see [Air::divisor_for], and boundary constraints on the last row are checked on the
+/// last row which is not exempted, i.e. row `trace_len - e`. Periodic columns are read from their
+/// cycle.
+pub fn debug_trace(
+    air: &Air,
+    main: &[Vec<Felt>],
+    aux: &[Vec<QuadFelt>],
+    public: &[Vec<Felt>],
+    rand: &[QuadFelt],
+    max_violations: usize,
+) -> Vec<Violation> {
+    let trace_len = main.first().map(Vec::len).unwrap_or_default();
+    let last_step = trace_len.saturating_sub(num_transition_exemptions(air));
+    let metas = air.constraint_metas();
+    let labels = |root: &ConstraintRoot| {
+        metas
+            .iter()
+            .find(|meta| std::ptr::eq(meta.root, root))
+            .map(|meta| meta.labels())
+            .unwrap_or_default()
+    };
+    let integrity = (0..air.trace_segment_widths.len()).map(RootKind::Integrity);
+    let kinds: Vec<_> = std::iter::once(RootKind::BoundaryFirst)
+        .chain(integrity)
+        .chain([RootKind::BoundaryLast])
+        .collect();
+
+    let mut ctx = EvalContext {
+        public: public
+            .iter()
+            .map(|input| input.iter().copied().map(QuadFelt::from).collect())
+            .collect(),
+        rand: rand.to_vec(),
+        ..Default::default()
+    };
+    let mut violations = vec![];
+    for row in 0..trace_len {
+        let next = (row + 1) % trace_len;
+        ctx.main =
+            [row, next].map(|r| main.iter().map(|column| QuadFelt::from(column[r])).collect());
+        ctx.aux = [row, next].map(|r| aux.iter().map(|column| column[r]).collect());
+        ctx.periodic = periodic_values(air, row);
+
+        let kinds = kinds.iter().copied().filter(|kind| match kind {
+            RootKind::BoundaryFirst => row == 0,
+            RootKind::Integrity(_) => row < last_step,
+            RootKind::BoundaryLast => row == last_step,
+        });
+        for kind in kinds {
+            for (index, root) in kind.roots(air).iter().enumerate() {
+                let value = air.evaluate_root(kind, index, &ctx);
+                if value == QuadFelt::ZERO {
+                    continue;
+                }
+                if violations.len() == max_violations {
+                    return violations;
+                }
+                let reads = air
+                    .constraint_graph()
+                    .trace_accesses(root.node_index())
+                    .into_iter()
+                    .map(|access| (access, ctx.trace_value(&access)))
+                    .collect();
+                violations.push(Violation {
+                    kind,
+                    index,
+                    labels: labels(root),
+                    row,
+                    value,
+                    reads,
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Returns the number of rows at the end of the trace which are exempted from the integrity
+/// constraints of `air`, which is shared by all of them as in the Winterfell backend.
+fn num_transition_exemptions(air: &Air) -> usize {
+    (0..air.trace_segment_widths.len())
+        .flat_map(|segment| air.integrity_constraints(segment))
+        .map(|root| match air.divisor_for(root.domain()) {
+            DivisorSpec::TransitionWithExemptions { exemptions } => exemptions,
+            DivisorSpec::FirstRow | DivisorSpec::LastRow => 0,
+        })
+        .fold(NUM_TRANSITION_EXEMPTIONS, usize::max)
+}
+
+/// Returns the values of the periodic columns of `air` on `row`, and on the rows they are read
+/// ahead of it, see [Air::shifted_periodic_columns].
+fn periodic_values(air: &Air, row: usize) -> BTreeMap<(QualifiedIdentifier, usize), QuadFelt> {
+    let offsets = air.periodic_columns.keys().map(|ident| (*ident, 0)).chain(
+        air.shifted_periodic_columns()
+            .into_iter()
+            .map(|access| (access.name, access.row_offset)),
+    );
+    offsets
+        .map(|(ident, row_offset)| {
+            let values = &air.periodic_columns[&ident].values;
+            let value = values[(row + row_offset) % values.len()];
+            ((ident, row_offset), QuadFelt::from(Felt::new(value)))
+        })
+        .collect()
+}
+
+/// Formats `violations` as a table, one line per violation followed by the trace cells read by the
+/// constraint, e.g. to print them when a test fails.
+pub fn violation_table(air: &Air, violations: &[Violation]) -> String {
+    let mut table = String::new();
+    for violation in violations {
+        let constraint = match violation.kind {
+            RootKind::Integrity(segment) => format!(
+                "integrity constraint #{} of the {} trace",
+                violation.index,
+                air.trace_segment_name(segment).as_str().trim_start_matches('$')
+            ),
+            RootKind::BoundaryFirst => format!("first row constraint #{}", violation.index),
+            RootKind::BoundaryLast => format!("last row constraint #{}", violation.index),
+        };
+        let labels: Vec<_> = violation.labels.iter().map(|label| label.as_str()).collect();
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", labels.join(", "))
+        };
+        writeln!(
+            table,
+            "row {}: {constraint}{labels} = {}",
+            violation.row,
+            format_value(violation.value)
+        )
+        .unwrap();
+        let reads: Vec<_> = violation
+            .reads
+            .iter()
+            .map(|(access, value)| {
+                let next = if access.row_offset == 0 { "" } else { "'" };
+                let name = air.trace_column_name(access.segment, access.column);
+                format!("{name}{next} = {}", format_value(*value))
+            })
+            .collect();
+        if !reads.is_empty() {
+            writeln!(table, "    {}", reads.join(", ")).unwrap();
+        }
+    }
+    table
+}
+
+/// Formats `value` as a base field element if it is one, or as a pair of coordinates otherwise.
+fn format_value(value: QuadFelt) -> String {
+    match value.to_base_elements() {
+        [value, imaginary] if imaginary == Felt::ZERO => value.to_string(),
+        [real, imaginary] => format!("({real}, {imaginary})"),
+    }
+}
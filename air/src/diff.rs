@@ -0,0 +1,425 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use winter_math::fields::f64::BaseElement as Felt;
+
+use crate::{
+    Air, BusBoundary, BusType, ConstraintEvaluator, EvalContext, IntegrityConstraintDegree,
+    NodeIndex, Operation, PublicInputSchema, QuadFelt, RootKind, Value,
+};
+
+/// The number of random points at which two constraints are evaluated to decide whether they are
+/// equivalent.
+const NUM_EVAL_POINTS: u64 = 4;
+
+/// The log of the trace length over which periodic columns are evaluated.
+const LOG_TRACE_LEN: u32 = 16;
+
+/// A value which differs between two [Air]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+impl<T: PartialEq> Change<T> {
+    fn compare(old: T, new: T) -> Option<Self> {
+        (old != new).then_some(Self { old, new })
+    }
+}
+
+/// How a constraint differs between two [Air]s.
+///
+/// Constraints are identified by their [RootKind] and their index among the roots of that kind,
+/// see [RootKind::roots]. Constraints which are structurally identical in both [Air]s, even if
+/// they were reordered, are not reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintChange {
+    /// The constraint only exists in the new [Air]
+    Added { kind: RootKind, new: usize },
+    /// The constraint only exists in the old [Air]
+    Removed { kind: RootKind, old: usize },
+    /// The constraint is structurally different, but evaluates to the same value
+    Refactored {
+        kind: RootKind,
+        old: usize,
+        new: usize,
+        degree: Option<Change<IntegrityConstraintDegree>>,
+    },
+    /// The constraint was matched to a constraint of the new [Air] which evaluates differently
+    Changed {
+        kind: RootKind,
+        old: usize,
+        new: usize,
+        degree: Option<Change<IntegrityConstraintDegree>>,
+    },
+}
+impl ConstraintChange {
+    /// Returns true if this change does not affect the semantics of the constraints
+    pub fn is_equivalent(&self) -> bool {
+        matches!(self, Self::Refactored { .. })
+    }
+}
+
+/// The summary of a bus which is compared by [diff].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusSummary {
+    pub bus_type: BusType,
+    pub first: BusBoundary,
+    pub last: BusBoundary,
+}
+
+/// The differences between two [Air]s, as computed by [diff].
+///
+/// Each of the optional fields is only set if the corresponding item differs.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AirDiff {
+    pub constraints: Vec<ConstraintChange>,
+    pub trace_segment_widths: Option<Change<Vec<u16>>>,
+    pub public_inputs: Option<Change<Vec<PublicInputSchema>>>,
+    pub periodic_columns: Option<Change<BTreeMap<String, Vec<u64>>>>,
+    pub buses: Option<Change<BTreeMap<String, BusSummary>>>,
+    pub num_random_values: Option<Change<u16>>,
+}
+impl AirDiff {
+    /// Returns true if no difference was found
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns true if both [Air]s enforce the same constraints over the same inputs, i.e. all the
+    /// differences found are refactorings
+    pub fn is_equivalent(&self) -> bool {
+        self.constraints.iter().all(ConstraintChange::is_equivalent)
+            && Self { constraints: vec![], ..self.clone() }.is_empty()
+    }
+}
+
+/// Computes the differences between the `old` and `new` [Air]s, e.g. to review the effect of a
+/// compiler upgrade or of a refactoring of AirScript sources.
+///
+/// Constraints are matched in three steps, among the roots of each [RootKind]:
+///
+/// 1. Constraints with the same structure are considered unchanged.
+/// 2. Constraints which evaluate to the same values at several random points are considered
+///    refactored.
+/// 3. The remaining constraints are paired by the number of subexpressions they share, and are
+///    considered changed. Constraints which share no subexpression with any other are considered
+///    added or removed.
+///
+/// The random points are derived deterministically from the names of the public inputs and the
+/// positions of the trace columns, so that both [Air]s are evaluated at the same points.
+pub fn diff(old: &Air, new: &Air) -> AirDiff {
+    let mut kinds = vec![RootKind::BoundaryFirst, RootKind::BoundaryLast];
+    let num_segments = old.trace_segment_widths.len().max(new.trace_segment_widths.len());
+    kinds.extend((0..num_segments).map(RootKind::Integrity));
+
+    let (old_contexts, new_contexts) = (eval_contexts(old), eval_contexts(new));
+    let mut constraints = vec![];
+    for kind in kinds {
+        let old_roots = RootSummary::collect(old, kind, &old_contexts);
+        let new_roots = RootSummary::collect(new, kind, &new_contexts);
+        diff_roots(kind, &old_roots, &new_roots, &mut constraints);
+    }
+
+    AirDiff {
+        constraints,
+        trace_segment_widths: Change::compare(
+            old.trace_segment_widths.clone(),
+            new.trace_segment_widths.clone(),
+        ),
+        public_inputs: Change::compare(old.public_input_schema(), new.public_input_schema()),
+        periodic_columns: Change::compare(periodic_columns(old), periodic_columns(new)),
+        buses: Change::compare(buses(old), buses(new)),
+        num_random_values: Change::compare(old.num_random_values, new.num_random_values),
+    }
+}
+
+/// The information about a constraint root used to match it with the roots of another [Air].
+struct RootSummary {
+    /// The structural hash of the root
+    hash: u64,
+    /// The structural hashes of all the subexpressions of the root
+    subexpressions: BTreeSet<u64>,
+    /// The evaluations of the root at each of the random points
+    evals: Vec<QuadFelt>,
+    /// The degree of the root, for integrity constraints
+    degree: Option<IntegrityConstraintDegree>,
+}
+impl RootSummary {
+    fn collect(air: &Air, kind: RootKind, contexts: &[EvalContext]) -> Vec<Self> {
+        let mut hashes = BTreeMap::default();
+        kind.roots(air)
+            .iter()
+            .enumerate()
+            .map(|(i, root)| {
+                let index = root.node_index();
+                let hash = structural_hash(air, index, &mut hashes);
+                let mut subexpressions = BTreeSet::default();
+                collect_subexpressions(air, index, &hashes, &mut subexpressions);
+                Self {
+                    hash,
+                    subexpressions,
+                    evals: contexts.iter().map(|ctx| air.evaluate_root(kind, i, ctx)).collect(),
+                    degree: matches!(kind, RootKind::Integrity(_))
+                        .then(|| air.constraint_graph().degree(index)),
+                }
+            })
+            .collect()
+    }
+}
+
+fn diff_roots(
+    kind: RootKind,
+    old: &[RootSummary],
+    new: &[RootSummary],
+    changes: &mut Vec<ConstraintChange>,
+) {
+    let mut old_left: Vec<usize> = (0..old.len()).collect();
+    let mut new_left: Vec<usize> = (0..new.len()).collect();
+
+    // Pairs each remaining old root with the first remaining new root satisfying `matches`
+    let mut pair = |matches: &dyn Fn(&RootSummary, &RootSummary) -> bool| {
+        let mut pairs = vec![];
+        old_left.retain(|&i| match new_left.iter().position(|&j| matches(&old[i], &new[j])) {
+            Some(position) => {
+                pairs.push((i, new_left.remove(position)));
+                false
+            },
+            None => true,
+        });
+        pairs
+    };
+
+    pair(&|old, new| old.hash == new.hash);
+    for (i, j) in pair(&|old, new| old.evals == new.evals) {
+        let degree = Change::compare(old[i].degree.clone(), new[j].degree.clone())
+            .and_then(|Change { old, new }| Some(Change { old: old?, new: new? }));
+        changes.push(ConstraintChange::Refactored { kind, old: i, new: j, degree });
+    }
+
+    // Greedily pair the remaining roots sharing the most subexpressions
+    let mut candidates: Vec<(usize, usize, usize)> = old_left
+        .iter()
+        .flat_map(|&i| new_left.iter().map(move |&j| (i, j)))
+        .map(|(i, j)| (old[i].subexpressions.intersection(&new[j].subexpressions).count(), i, j))
+        .filter(|(shared, ..)| *shared > 0)
+        .collect();
+    candidates.sort_by_key(|&(shared, i, j)| (core::cmp::Reverse(shared), i.abs_diff(j), i));
+    for (_, i, j) in candidates {
+        if old_left.contains(&i) && new_left.contains(&j) {
+            old_left.retain(|&k| k != i);
+            new_left.retain(|&k| k != j);
+            let degree = Change::compare(old[i].degree.clone(), new[j].degree.clone())
+                .and_then(|Change { old, new }| Some(Change { old: old?, new: new? }));
+            changes.push(ConstraintChange::Changed { kind, old: i, new: j, degree });
+        }
+    }
+
+    changes.extend(old_left.into_iter().map(|old| ConstraintChange::Removed { kind, old }));
+    changes.extend(new_left.into_iter().map(|new| ConstraintChange::Added { kind, new }));
+}
+
+/// Computes a hash of the subgraph rooted at `index` which only depends on its structure, and not
+/// on the indices of its nodes or on source locations.
+fn structural_hash(air: &Air, index: &NodeIndex, hashes: &mut BTreeMap<NodeIndex, u64>) -> u64 {
+    if let Some(hash) = hashes.get(index) {
+        return *hash;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    match air.constraint_graph().node(index).op() {
+        Operation::Value(value) => {
+            core::mem::discriminant(value).hash(&mut hasher);
+            match value {
+                Value::Constant(c) => c.hash(&mut hasher),
+                Value::TraceAccess(access) => {
+                    (access.segment, access.column, access.row_offset).hash(&mut hasher)
+                },
+                Value::PeriodicColumn(access) => {
+                    (access.name.to_string(), access.cycle, access.row_offset).hash(&mut hasher)
+                },
+                Value::PublicInput(access) => {
+                    (access.name.as_str(), access.index).hash(&mut hasher)
+                },
+                Value::RandomValue(index) => index.hash(&mut hasher),
+            }
+        },
+        op @ (Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs)) => {
+            core::mem::discriminant(op).hash(&mut hasher);
+            structural_hash(air, lhs, hashes).hash(&mut hasher);
+            structural_hash(air, rhs, hashes).hash(&mut hasher);
+        },
+    }
+    let hash = hasher.finish();
+    hashes.insert(*index, hash);
+    hash
+}
+
+fn collect_subexpressions(
+    air: &Air,
+    index: &NodeIndex,
+    hashes: &BTreeMap<NodeIndex, u64>,
+    subexpressions: &mut BTreeSet<u64>,
+) {
+    if !subexpressions.insert(hashes[index]) {
+        return;
+    }
+    if let Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) =
+        air.constraint_graph().node(index).op()
+    {
+        collect_subexpressions(air, lhs, hashes, subexpressions);
+        collect_subexpressions(air, rhs, hashes, subexpressions);
+    }
+}
+
+/// Builds the contexts at which the constraints of `air` are evaluated.
+fn eval_contexts(air: &Air) -> Vec<EvalContext> {
+    (0..NUM_EVAL_POINTS)
+        .map(|seed| {
+            let width = |segment| air.trace_segment_widths.get(segment).copied().unwrap_or(0);
+            let trace = |segment: usize| {
+                [0, 1].map(|row: usize| {
+                    (0..width(segment))
+                        .map(|column| random_value(&(seed, "trace", segment, row, column)))
+                        .collect()
+                })
+            };
+            EvalContext {
+                log_trace_len: LOG_TRACE_LEN,
+                main: trace(0),
+                aux: trace(1),
+                public: air
+                    .public_inputs()
+                    .map(|input| {
+                        (0..input.size())
+                            .map(|i| random_value(&(seed, "public", input.name().as_str(), i)))
+                            .collect()
+                    })
+                    .collect(),
+                rand: (0..air.num_random_values)
+                    .map(|i| random_value(&(seed, "rand", i)))
+                    .collect(),
+                periodic: Default::default(),
+                z: random_value(&(seed, "z")),
+            }
+            .with_periodic_evals(air)
+        })
+        .collect()
+}
+
+/// Derives a pseudo-random extension field element from `key`.
+fn random_value(key: &impl Hash) -> QuadFelt {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    let low = hasher.finish();
+    low.hash(&mut hasher);
+    let high = hasher.finish();
+    QuadFelt::new(Felt::new(low), Felt::new(high))
+}
+
+fn periodic_columns(air: &Air) -> BTreeMap<String, Vec<u64>> {
+    air.periodic_columns
+        .iter()
+        .map(|(name, column)| (name.to_string(), column.values.clone()))
+        .collect()
+}
+
+fn buses(air: &Air) -> BTreeMap<String, BusSummary> {
+    air.buses
+        .iter()
+        .map(|(name, bus)| {
+            let summary = BusSummary {
+                bus_type: bus.bus_type,
+                first: bus.first.clone(),
+                last: bus.last.clone(),
+            };
+            (name.to_string(), summary)
+        })
+        .collect()
+}
+
+impl fmt::Display for AirDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+
+        for change in self.constraints.iter() {
+            match change {
+                ConstraintChange::Added { kind, new } => {
+                    writeln!(f, "+ {}", ConstraintName(*kind, *new))?;
+                },
+                ConstraintChange::Removed { kind, old } => {
+                    writeln!(f, "- {}", ConstraintName(*kind, *old))?;
+                },
+                ConstraintChange::Refactored { kind, old, new, degree } => {
+                    write!(
+                        f,
+                        "= {} -> #{new}: refactored (equivalent)",
+                        ConstraintName(*kind, *old)
+                    )?;
+                    write_degree(f, degree)?;
+                },
+                ConstraintChange::Changed { kind, old, new, degree } => {
+                    write!(f, "~ {} -> #{new}: changed", ConstraintName(*kind, *old))?;
+                    write_degree(f, degree)?;
+                },
+            }
+        }
+        if let Some(Change { old, new }) = &self.trace_segment_widths {
+            writeln!(f, "trace segment widths: {old:?} -> {new:?}")?;
+        }
+        if let Some(Change { old, new }) = &self.public_inputs {
+            let names = |inputs: &[PublicInputSchema]| {
+                inputs
+                    .iter()
+                    .map(|input| format!("{}[{}]", input.name, input.size))
+                    .collect::<Vec<_>>()
+            };
+            writeln!(f, "public inputs: {:?} -> {:?}", names(old), names(new))?;
+        }
+        if let Some(Change { old, new }) = &self.periodic_columns {
+            writeln!(f, "periodic columns: {old:?} -> {new:?}")?;
+        }
+        if let Some(Change { old, new }) = &self.buses {
+            let names = |buses: &BTreeMap<String, BusSummary>| {
+                buses
+                    .iter()
+                    .map(|(name, bus)| format!("{name}: {:?}", bus.bus_type))
+                    .collect::<Vec<_>>()
+            };
+            writeln!(f, "buses: {:?} -> {:?}", names(old), names(new))?;
+        }
+        if let Some(Change { old, new }) = &self.num_random_values {
+            writeln!(f, "random values: {old} -> {new}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_degree(
+    f: &mut fmt::Formatter<'_>,
+    degree: &Option<Change<IntegrityConstraintDegree>>,
+) -> fmt::Result {
+    match degree {
+        Some(Change { old, new }) => writeln!(f, ", degree {} -> {}", old.base(), new.base()),
+        None => writeln!(f),
+    }
+}
+
+/// Formats a reference to the `i`-th constraint of the given [RootKind]
+struct ConstraintName(RootKind, usize);
+impl fmt::Display for ConstraintName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            RootKind::Integrity(0) => write!(f, "main integrity constraint #{}", self.1),
+            RootKind::Integrity(_) => write!(f, "aux integrity constraint #{}", self.1),
+            RootKind::BoundaryFirst => write!(f, "first row boundary constraint #{}", self.1),
+            RootKind::BoundaryLast => write!(f, "last row boundary constraint #{}", self.1),
+        }
+    }
+}
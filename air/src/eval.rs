@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use winter_math::{FieldElement, StarkField, fields::f64::BaseElement as Felt};
+
+use crate::{
+    Air, ConstraintRoot, NodeIndex, Operation, QualifiedIdentifier, TraceAccess, TraceSegmentId,
+    Value,
+};
+
+/// The quadratic extension of the base field, over which constraints are evaluated out of domain.
+pub type QuadFelt = winter_math::fields::QuadExtension<Felt>;
+
+/// Identifies one of the groups of constraint roots produced by a backend.
+///
+/// Boundary roots are grouped across all trace segments, in segment order, matching the way they
+/// are combined by the ACE backend.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RootKind {
+    /// The integrity constraints enforced against the given trace segment
+    Integrity(TraceSegmentId),
+    /// The boundary constraints enforced against the first row, across all segments
+    BoundaryFirst,
+    /// The boundary constraints enforced against the last row, across all segments
+    BoundaryLast,
+}
+impl RootKind {
+    /// Returns the [ConstraintRoot]s of `air` belonging to this group, in evaluation order.
+    ///
+    /// The groups are indexed as the constraints of `air` are inserted, so the roots of each group
+    /// are looked up rather than collected. Every backend enumerates its roots from these groups,
+    /// such that the `i`-th root of a group is the same for all of them.
+    pub fn roots(self, air: &Air) -> &[ConstraintRoot] {
+        air.constraints.roots(self)
+    }
+}
+
+/// The set of values against which constraint roots are evaluated, shared by all
+/// [ConstraintEvaluator] implementations.
+///
+/// Trace values are given as `[current_row, next_row]` pairs, public inputs are given in the same
+/// order as [Air::public_inputs], and periodic columns are given as their evaluation at the
+/// appropriate power of `z`, or of `z·gᵒ` when read `o` rows ahead (see
+/// [EvalContext::with_periodic_evals]).
+#[derive(Clone, Debug, Default)]
+pub struct EvalContext {
+    /// Log of the trace length.
+    pub log_trace_len: u32,
+    /// Evaluations of the *main* trace, in the current and next rows.
+    pub main: [Vec<QuadFelt>; 2],
+    /// Evaluations of the *aux* trace, in the current and next rows.
+    pub aux: [Vec<QuadFelt>; 2],
+    /// Public inputs in the same order as [Air::public_inputs].
+    pub public: Vec<Vec<QuadFelt>>,
+    /// Random values used to derive the *aux* trace.
+    pub rand: Vec<QuadFelt>,
+    /// Out-of-domain evaluations of each periodic column, keyed by column and row offset.
+    pub periodic: BTreeMap<(QualifiedIdentifier, usize), QuadFelt>,
+    /// The point at which the constraints are evaluated.
+    pub z: QuadFelt,
+}
+impl EvalContext {
+    /// Returns the value of the trace cell referenced by `access`.
+    ///
+    /// # Panics
+    /// Panics if the access is out of bounds of the trace frame held by this context.
+    pub fn trace_value(&self, access: &TraceAccess) -> QuadFelt {
+        let segment = match access.segment {
+            0 => &self.main,
+            1 => &self.aux,
+            segment => panic!("invalid trace segment {segment}"),
+        };
+        segment[access.row_offset][access.column]
+    }
+
+    /// Populates the periodic column evaluations of this context from the columns of `air`.
+    ///
+    /// Each column with a cycle of length `k` is interpolated, and evaluated at `zⁿᐟᵏ` where `n` is
+    /// the trace length. Columns read `o` rows ahead (see [Air::shifted_periodic_columns]) are
+    /// also evaluated at `(z·gᵒ)ⁿᐟᵏ`, where `g` generates the trace domain.
+    pub fn with_periodic_evals(mut self, air: &Air) -> Self {
+        let trace_len = 1u64 << self.log_trace_len;
+        let g = QuadFelt::from(Felt::get_root_of_unity(self.log_trace_len));
+        let offsets = air.periodic_columns.keys().map(|ident| (*ident, 0)).chain(
+            air.shifted_periodic_columns()
+                .into_iter()
+                .map(|access| (access.name, access.row_offset)),
+        );
+        self.periodic = offsets
+            .map(|(ident, row_offset)| {
+                let column = &air.periodic_columns[&ident];
+                let z = self.z * g.exp_vartime(row_offset as u64);
+                let z_col = z.exp_vartime(trace_len / column.values.len() as u64);
+
+                let poly = air.periodic_column_polynomial(&ident).expect("undefined column");
+
+                let eval = poly.iter().rev().fold(QuadFelt::ZERO, |acc, coeff| {
+                    acc * z_col + QuadFelt::from(Felt::new(*coeff))
+                });
+                ((ident, row_offset), eval)
+            })
+            .collect();
+        self
+    }
+}
+
+/// This trait should be implemented on representations of an [Air]'s constraints which are able
+/// to evaluate each constraint root against an [EvalContext].
+///
+/// Implementations must agree on the number and order of the roots in each [RootKind], so that
+/// results from different backends can be compared one-to-one.
+pub trait ConstraintEvaluator {
+    /// Returns the number of constraint roots of the given kind.
+    fn num_roots(&self, kind: RootKind) -> usize;
+
+    /// Evaluates the `i`-th constraint root of the given kind against `ctx`.
+    ///
+    /// # Panics
+    /// Implementations may panic if `i` is out of bounds, or if `ctx` does not provide a value
+    /// referenced by the constraint.
+    fn evaluate_root(&self, kind: RootKind, i: usize, ctx: &EvalContext) -> QuadFelt;
+}
+
+/// Evaluates the constraints by interpreting the [crate::AlgebraicGraph] directly.
+impl ConstraintEvaluator for Air {
+    fn num_roots(&self, kind: RootKind) -> usize {
+        kind.roots(self).len()
+    }
+
+    fn evaluate_root(&self, kind: RootKind, i: usize, ctx: &EvalContext) -> QuadFelt {
+        let mut evals = BTreeMap::default();
+        eval_node(self, kind.roots(self)[i].node_index(), ctx, &mut evals)
+    }
+}
+
+/// Recursively evaluates the subgraph rooted at `index`, caching the evaluation of shared nodes.
+fn eval_node(
+    air: &Air,
+    index: &NodeIndex,
+    ctx: &EvalContext,
+    evals: &mut BTreeMap<NodeIndex, QuadFelt>,
+) -> QuadFelt {
+    if let Some(eval) = evals.get(index) {
+        return *eval;
+    }
+
+    let eval = match air.constraint_graph().node(index).op() {
+        Operation::Value(value) => match value {
+            Value::Constant(c) => QuadFelt::from(Felt::new(*c)),
+            Value::TraceAccess(access) => ctx.trace_value(access),
+            Value::PeriodicColumn(access) => ctx.periodic[&(access.name, access.row_offset)],
+            Value::PublicInput(access) => {
+                let position = air
+                    .public_inputs
+                    .keys()
+                    .position(|name| *name == access.name)
+                    .expect("invalid public input access");
+                ctx.public[position][access.index]
+            },
+            Value::RandomValue(idx) => ctx.rand[*idx],
+        },
+        Operation::Add(lhs, rhs) => {
+            eval_node(air, lhs, ctx, evals) + eval_node(air, rhs, ctx, evals)
+        },
+        Operation::Sub(lhs, rhs) => {
+            eval_node(air, lhs, ctx, evals) - eval_node(air, rhs, ctx, evals)
+        },
+        Operation::Mul(lhs, rhs) => {
+            eval_node(air, lhs, ctx, evals) * eval_node(air, rhs, ctx, evals)
+        },
+    };
+    evals.insert(*index, eval);
+    eval
+}
@@ -0,0 +1,77 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
+
+use super::{AlgebraicGraph, NodeIndex};
+use crate::ir::*;
+
+impl AlgebraicGraph {
+    /// Serialization to Graphviz Dot format for debugging purposes. Display on
+    /// <https://dreampuf.github.io/GraphvizOnline> or using `dot -Tsvg graph.dot > graph.svg`
+    ///
+    /// Each node is labeled with its index and the operation it represents. The tips of the
+    /// `roots` constraints, given with the trace segment they apply to, are drawn as boxes and
+    /// labeled with the domain of each constraint they represent.
+    pub fn to_dot<'a, I>(&self, roots: I) -> Result<String, fmt::Error>
+    where
+        I: IntoIterator<Item = (TraceSegmentId, &'a ConstraintRoot)>,
+    {
+        let mut root_labels = BTreeMap::<NodeIndex, Vec<String>>::new();
+        for (segment, root) in roots {
+            root_labels
+                .entry(*root.node_index())
+                .or_default()
+                .push(format!("segment {segment}, {}", root.domain()));
+        }
+
+        let mut f = String::new();
+        writeln!(f, "digraph G {{")?;
+        for (i, node) in self.nodes.iter().enumerate() {
+            let index = NodeIndex(i);
+            let mut label = format!("n{i}\\n");
+            match node.op() {
+                Operation::Value(value) => write_value(&mut label, value)?,
+                Operation::Add(lhs, rhs) => write!(label, "n{} + n{}", lhs.0, rhs.0)?,
+                Operation::Sub(lhs, rhs) => write!(label, "n{} - n{}", lhs.0, rhs.0)?,
+                Operation::Mul(lhs, rhs) => write!(label, "n{} × n{}", lhs.0, rhs.0)?,
+            }
+            match root_labels.get(&index) {
+                Some(domains) => {
+                    for domain in domains {
+                        write!(label, "\\n{domain}")?;
+                    }
+                    writeln!(f, "n{i} [shape=box, label=\"{label}\"]")?;
+                },
+                None => writeln!(f, "n{i} [label=\"{label}\"]")?,
+            }
+            if let Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) =
+                node.op()
+            {
+                writeln!(f, "n{} -> n{i}", lhs.0)?;
+                writeln!(f, "n{} -> n{i}", rhs.0)?;
+            }
+        }
+        writeln!(f, "}}")?;
+        Ok(f)
+    }
+}
+
+/// Writes the label of a leaf node, using `M` and `A` for the columns of the main and aux trace
+/// segments, and one `'` per row of offset.
+pub(super) fn write_value(f: &mut String, value: &Value) -> fmt::Result {
+    match value {
+        Value::Constant(value) => write!(f, "{value}"),
+        Value::TraceAccess(access) => {
+            match access.segment {
+                DEFAULT_SEGMENT => write!(f, "M[{}]", access.column)?,
+                AUX_SEGMENT => write!(f, "A[{}]", access.column)?,
+                segment => write!(f, "S{segment}[{}]", access.column)?,
+            }
+            write!(f, "{}", "'".repeat(access.row_offset))
+        },
+        Value::PeriodicColumn(pc) => write!(f, "{}{}", pc.name, "'".repeat(pc.row_offset)),
+        Value::PublicInput(pi) => write!(f, "PI[{}][{}]", pi.name, pi.index),
+        Value::RandomValue(index) => write!(f, "R[{index}]"),
+    }
+}
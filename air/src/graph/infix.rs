@@ -0,0 +1,55 @@
+use std::fmt::{self, Write};
+
+use super::{AlgebraicGraph, NodeIndex, dot::write_value};
+use crate::ir::*;
+
+impl AlgebraicGraph {
+    /// Renders the subgraph which has the specified node as its tip as an infix expression, e.g.
+    /// `M[0]' - (M[0] + M[1])`, for documenting constraints.
+    ///
+    /// Shared subexpressions are written out at each use, and parentheses are only added where
+    /// the precedence of the operators requires them. Leaves are labeled as in
+    /// [AlgebraicGraph::to_dot].
+    pub fn to_infix(&self, index: &NodeIndex) -> Result<String, fmt::Error> {
+        let mut f = String::new();
+        self.write_infix(&mut f, index, Precedence::Sum)?;
+        Ok(f)
+    }
+
+    /// Writes the expression at `index`, wrapped in parentheses if it binds less tightly than
+    /// `min` requires.
+    fn write_infix(&self, f: &mut String, index: &NodeIndex, min: Precedence) -> fmt::Result {
+        let (lhs, op, rhs) = match self.node(index).op() {
+            Operation::Value(value) => return write_value(f, value),
+            Operation::Add(lhs, rhs) => (lhs, "+", rhs),
+            Operation::Sub(lhs, rhs) => (lhs, "-", rhs),
+            Operation::Mul(lhs, rhs) => (lhs, "*", rhs),
+        };
+        let precedence = if op == "*" {
+            Precedence::Product
+        } else {
+            Precedence::Sum
+        };
+        // Subtraction is not associative, so a sum or difference on its right needs parentheses
+        let rhs_min = if op == "-" { Precedence::Product } else { precedence };
+
+        let parenthesize = precedence < min;
+        if parenthesize {
+            f.write_char('(')?;
+        }
+        self.write_infix(f, lhs, precedence)?;
+        write!(f, " {op} ")?;
+        self.write_infix(f, rhs, rhs_min)?;
+        if parenthesize {
+            f.write_char(')')?;
+        }
+        Ok(())
+    }
+}
+
+/// How tightly an expression binds, from the loosest to the tightest
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Sum,
+    Product,
+}
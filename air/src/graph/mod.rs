@@ -1,4 +1,8 @@
-use std::collections::BTreeMap;
+mod dot;
+mod infix;
+mod ssa;
+
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::ir::*;
 
@@ -35,6 +39,11 @@ impl From<NodeIndex> for usize {
     }
 }
 
+/// Computes the degree of a trace access for [AlgebraicGraph::degree_with], recording the cycle
+/// lengths of the periodic columns it depends on, by name.
+pub(crate) type TraceAccessDegree<'a> =
+    dyn Fn(&mut BTreeMap<QualifiedIdentifier, usize>, &TraceAccess) -> usize + 'a;
+
 /// A node in the [AlgebraicGraph]
 #[derive(Debug, Clone)]
 pub struct Node {
@@ -84,8 +93,19 @@ impl AlgebraicGraph {
 
     /// Returns the degree of the subgraph which has the specified node as its tip.
     pub fn degree(&self, index: &NodeIndex) -> IntegrityConstraintDegree {
+        self.degree_with(index, &|_, _| 1)
+    }
+
+    /// Returns the degree of the subgraph which has the specified node as its tip, where the degree
+    /// of each trace access is given by `trace_access_degree`, which may record the cycle lengths
+    /// of the periodic columns it depends on.
+    pub(crate) fn degree_with(
+        &self,
+        index: &NodeIndex,
+        trace_access_degree: &TraceAccessDegree<'_>,
+    ) -> IntegrityConstraintDegree {
         let mut cycles = BTreeMap::default();
-        let base = self.accumulate_degree(&mut cycles, index);
+        let base = self.accumulate_degree_with(&mut cycles, index, trace_access_degree);
 
         if cycles.is_empty() {
             IntegrityConstraintDegree::new(base)
@@ -110,7 +130,8 @@ impl AlgebraicGraph {
                         !default_domain.is_boundary(),
                         "unexpected access to periodic column in boundary constraint"
                     );
-                    // the default domain for [IntegrityConstraints] is `EveryRow`
+                    // the default domain for [IntegrityConstraints] is `EveryRow`, even when the
+                    // column is read in a later row, since periodic columns wrap around
                     Ok((DEFAULT_SEGMENT, ConstraintDomain::EveryRow))
                 },
                 Value::PublicInput(_) => {
@@ -146,6 +167,17 @@ impl AlgebraicGraph {
         }
     }
 
+    /// Replaces the value of each leaf node by its image under `f`.
+    ///
+    /// `f` must be injective, so that the nodes of the graph remain unique.
+    pub(crate) fn map_values(&mut self, f: impl Fn(Value) -> Value) {
+        for node in self.nodes.iter_mut() {
+            if let Operation::Value(value) = node.op {
+                node.op = Operation::Value(f(value));
+            }
+        }
+    }
+
     /// Insert the operation and return its node index. If an identical node already exists, return
     /// that index instead.
     pub(crate) fn insert_node(&mut self, op: Operation) -> NodeIndex {
@@ -168,32 +200,106 @@ impl AlgebraicGraph {
         &self,
         cycles: &mut BTreeMap<QualifiedIdentifier, usize>,
         index: &NodeIndex,
+    ) -> usize {
+        self.accumulate_degree_with(cycles, index, &|_, _| 1)
+    }
+
+    /// Recursively accumulates the base degree and the cycle lengths of the periodic columns, where
+    /// the degree of each trace access is given by `trace_access_degree`.
+    pub(crate) fn accumulate_degree_with(
+        &self,
+        cycles: &mut BTreeMap<QualifiedIdentifier, usize>,
+        index: &NodeIndex,
+        trace_access_degree: &TraceAccessDegree<'_>,
     ) -> usize {
         // recursively walk the subgraph and compute the degree from the operation and child nodes
         match self.node(index).op() {
             Operation::Value(value) => match value {
                 Value::Constant(_) | Value::PublicInput(_) | Value::RandomValue(_) => 0,
-                Value::TraceAccess(_) => 1,
+                Value::TraceAccess(access) => trace_access_degree(cycles, access),
                 Value::PeriodicColumn(pc) => {
                     cycles.insert(pc.name, pc.cycle);
                     0
                 },
             },
             Operation::Add(lhs, rhs) => {
-                let lhs_base = self.accumulate_degree(cycles, lhs);
-                let rhs_base = self.accumulate_degree(cycles, rhs);
+                let lhs_base = self.accumulate_degree_with(cycles, lhs, trace_access_degree);
+                let rhs_base = self.accumulate_degree_with(cycles, rhs, trace_access_degree);
                 lhs_base.max(rhs_base)
             },
             Operation::Sub(lhs, rhs) => {
-                let lhs_base = self.accumulate_degree(cycles, lhs);
-                let rhs_base = self.accumulate_degree(cycles, rhs);
+                let lhs_base = self.accumulate_degree_with(cycles, lhs, trace_access_degree);
+                let rhs_base = self.accumulate_degree_with(cycles, rhs, trace_access_degree);
                 lhs_base.max(rhs_base)
             },
             Operation::Mul(lhs, rhs) => {
-                let lhs_base = self.accumulate_degree(cycles, lhs);
-                let rhs_base = self.accumulate_degree(cycles, rhs);
+                let lhs_base = self.accumulate_degree_with(cycles, lhs, trace_access_degree);
+                let rhs_base = self.accumulate_degree_with(cycles, rhs, trace_access_degree);
                 lhs_base + rhs_base
             },
         }
     }
+
+    /// Returns the indices of all nodes in the graph, ordered such that every node appears after
+    /// its children.
+    ///
+    /// Evaluating the nodes in this order guarantees that the operands of an operation are always
+    /// available when the operation is evaluated, so each node needs to be computed only once. The
+    /// traversal is iterative, so deep graphs cannot overflow the stack.
+    pub fn topological_order(&self) -> Vec<NodeIndex> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut visited = vec![false; self.nodes.len()];
+        for root in 0..self.nodes.len() {
+            self.extend_topological_order(NodeIndex(root), &mut visited, &mut order);
+        }
+        order
+    }
+
+    /// Returns the trace cells read by the subgraph rooted at `root`, i.e. the trace accesses it
+    /// depends on.
+    pub fn trace_accesses(&self, root: &NodeIndex) -> BTreeSet<TraceAccess> {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut order = vec![];
+        self.extend_topological_order(*root, &mut visited, &mut order);
+        order
+            .iter()
+            .filter_map(|index| match self.node(index).op() {
+                Operation::Value(Value::TraceAccess(access)) => Some(*access),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Appends to `order` the nodes of the subgraph rooted at `root` which are not yet `visited`,
+    /// such that every node appears after its children, and marks them as visited.
+    pub(crate) fn extend_topological_order(
+        &self,
+        root: NodeIndex,
+        visited: &mut [bool],
+        order: &mut Vec<NodeIndex>,
+    ) {
+        // Each entry is a node, and whether its children have already been pushed on the stack
+        let mut stack = vec![(root, false)];
+        while let Some((index, expanded)) = stack.pop() {
+            if expanded {
+                order.push(index);
+                continue;
+            }
+            if visited[index.0] {
+                continue;
+            }
+            visited[index.0] = true;
+            stack.push((index, true));
+            match self.node(&index).op() {
+                Operation::Value(_) => (),
+                Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) => {
+                    for child in [rhs, lhs] {
+                        if !visited[child.0] {
+                            stack.push((*child, false));
+                        }
+                    }
+                },
+            }
+        }
+    }
 }
@@ -0,0 +1,80 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
+
+use super::{AlgebraicGraph, NodeIndex, dot::write_value};
+use crate::ir::*;
+
+impl AlgebraicGraph {
+    /// Linearization of the `roots` constraints, given with the trace segment they apply to, to a
+    /// single-static-assignment text form, for reviewing constraints line by line.
+    ///
+    /// Each constraint starts with a comment giving its segment and domain, followed by the
+    /// operations it depends on which no previous constraint assigned, in topological order, so
+    /// shared subexpressions are only named once. The operation at the tip of a constraint is
+    /// named after it, e.g. `constraint0 = t1 - t2`. Leaves are written inline, labeled as in
+    /// [AlgebraicGraph::to_dot].
+    pub fn to_ssa_text<'a, I>(&self, roots: I) -> Result<String, fmt::Error>
+    where
+        I: IntoIterator<Item = (TraceSegmentId, &'a ConstraintRoot)>,
+    {
+        let mut names = BTreeMap::<NodeIndex, String>::new();
+        let mut visited = vec![false; self.nodes.len()];
+        let mut num_temporaries = 0;
+
+        let mut f = String::new();
+        for (i, (segment, root)) in roots.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "# constraint{i}: segment {segment}, {}", root.domain())?;
+
+            let tip = *root.node_index();
+            let mut tip_assigned = false;
+            let mut order = vec![];
+            self.extend_topological_order(tip, &mut visited, &mut order);
+            for index in order {
+                let (lhs, op, rhs) = match self.node(&index).op() {
+                    Operation::Value(_) => continue,
+                    Operation::Add(lhs, rhs) => (lhs, "+", rhs),
+                    Operation::Sub(lhs, rhs) => (lhs, "-", rhs),
+                    Operation::Mul(lhs, rhs) => (lhs, "*", rhs),
+                };
+                let name = if index == tip {
+                    tip_assigned = true;
+                    format!("constraint{i}")
+                } else {
+                    num_temporaries += 1;
+                    format!("t{}", num_temporaries - 1)
+                };
+                let lhs = self.ssa_operand(&names, lhs)?;
+                let rhs = self.ssa_operand(&names, rhs)?;
+                writeln!(f, "{name} = {lhs} {op} {rhs}")?;
+                names.insert(index, name);
+            }
+
+            // The tip was assigned by a previous constraint, or is a leaf
+            if !tip_assigned {
+                writeln!(f, "constraint{i} = {}", self.ssa_operand(&names, &tip)?)?;
+            }
+        }
+        Ok(f)
+    }
+
+    /// Returns the name assigned to the operation at `index`, or the label of the leaf at `index`.
+    fn ssa_operand(
+        &self,
+        names: &BTreeMap<NodeIndex, String>,
+        index: &NodeIndex,
+    ) -> Result<String, fmt::Error> {
+        match self.node(index).op() {
+            Operation::Value(value) => {
+                let mut label = String::new();
+                write_value(&mut label, value)?;
+                Ok(label)
+            },
+            _ => Ok(names[index].clone()),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 pub use air_parser::ast::BusType;
-use air_parser::ast::Identifier;
+use air_parser::ast::{Identifier, TraceColumnIndex};
 pub use mir::ir::BusOpKind;
 
 use crate::NodeIndex;
@@ -17,6 +17,26 @@ pub struct Bus {
     pub last: BusBoundary,
     /// The operations (insertions and removals) of this bus
     pub bus_ops: Vec<BusOp>,
+    /// The range check on the multiplicity of one of the operations of this bus, if any
+    pub multiplicity_range: Option<MultiplicityRange>,
+    /// Whether the bus is declared `extern`, i.e. defined by another program, in which case its
+    /// boundaries are [BusBoundary::Unconstrained] until it is linked with that program, see
+    /// [crate::Air::link].
+    pub is_extern: bool,
+}
+
+/// A range check on the effective multiplicity of an operation of a [Bus], declared with e.g.
+/// `logup q with bits`.
+///
+/// The multiplicity is constrained to equal `sum(2^i * bits[i])`, where each of the `bits` is
+/// constrained to be binary, so that it lies in `[0, 2^n)` for `n` columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiplicityRange {
+    /// The main trace columns into which the multiplicity is decomposed, from the least
+    /// significant bit
+    pub bits: Vec<TraceColumnIndex>,
+    /// The index in [Bus::bus_ops] of the operation whose multiplicity is range checked
+    pub bus_op: usize,
 }
 
 /// Represents the boundaries of a bus, which can be either a public input table or an empty bus.
@@ -73,6 +93,50 @@ impl Bus {
         last: BusBoundary,
         bus_ops: Vec<BusOp>,
     ) -> Self {
-        Self { name, bus_type, first, last, bus_ops }
+        Self {
+            name,
+            bus_type,
+            first,
+            last,
+            bus_ops,
+            multiplicity_range: None,
+            is_extern: false,
+        }
     }
 }
+
+/// The integrity constraint of a [Bus], as expanded by [crate::passes::BusOpExpand], kept in a
+/// structured form alongside the monolithic constraint rooted at [ExpandedBus::root].
+///
+/// Backends can use it to evaluate the constraint one operation at a time, rather than as a
+/// single expression whose size grows with the number of operations of the bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedBus {
+    /// The [Identifier] of the bus
+    pub name: Identifier,
+    /// The type of bus
+    pub bus_type: BusType,
+    /// The root of the monolithic integrity constraint of the bus, in the aux trace segment
+    pub root: NodeIndex,
+    /// The access to the bus column in the current row
+    pub column: NodeIndex,
+    /// The access to the bus column in the next row
+    pub column_next: NodeIndex,
+    /// The operations of the bus, in declaration order
+    pub ops: Vec<ExpandedBusOp>,
+}
+
+/// An operation of an [ExpandedBus].
+///
+/// For a multiset bus, the operation contributes the factor `args_combined * latch + 1 - latch`
+/// to the product of its kind. For a logup bus, it contributes `latch / args_combined` to the sum
+/// of its kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpandedBusOp {
+    /// The tuple of the operation, combined with the random values
+    pub args_combined: NodeIndex,
+    /// The selector (or multiplicity) of the operation
+    pub latch: NodeIndex,
+    /// The kind of operation (insert or remove)
+    pub kind: BusOpKind,
+}
@@ -1,7 +1,12 @@
 use core::fmt;
 
+use miden_diagnostics::InFlightDiagnostic;
+
 use super::*;
-use crate::graph::{AlgebraicGraph, NodeIndex};
+use crate::{
+    RootKind,
+    graph::{AlgebraicGraph, NodeIndex},
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ConstraintError {
@@ -21,7 +26,7 @@ pub enum ConstraintError {
 /// For example, integrity constraints for the main execution trace, which has a trace segment id of
 /// 0, will be specified by the vector of constraint roots found at index 0 of the
 /// `integrity_constraints` matrix.
-#[derive(Default, Debug)]
+#[derive(Default)]
 pub struct Constraints {
     /// Constraint roots for all boundary constraints against the execution trace, by trace
     /// segment, where boundary constraints are any constraints that apply to either the first
@@ -31,21 +36,61 @@ pub struct Constraints {
     /// segment, where integrity constraints are any constraints that apply to every row or
     /// every frame.
     integrity_constraints: Vec<Vec<ConstraintRoot>>,
+    /// The boundary constraints applied to the first row, and those applied to the last row,
+    /// across all segments in segment order, see [RootKind::roots]. They are indexed as the
+    /// constraints are inserted, rather than collected whenever a root is looked up.
+    boundary_roots: [Vec<ConstraintRoot>; 2],
+    /// The provenance of the constraints inlined from evaluators, by entry node.
+    provenance: BTreeMap<NodeIndex, ConstraintProvenance>,
+    /// The selectors under which integrity constraints are enforced, outermost first, by entry
+    /// node.
+    selectors: BTreeMap<NodeIndex, Vec<NodeIndex>>,
+    /// The doc comments of the statements in which constraints are written, by entry node.
+    docs: BTreeMap<NodeIndex, Vec<String>>,
+    /// The spans of the statements in which constraints are written, by entry node.
+    spans: BTreeMap<NodeIndex, SourceSpan>,
+    /// The structured form of the bus integrity constraints, see [ExpandedBus].
+    expanded_buses: Vec<ExpandedBus>,
     /// A directed acyclic graph which represents all of the constraints and their subexpressions.
     graph: AlgebraicGraph,
 }
+/// The spans of the constraints are omitted, so that the constraints of programs which only differ
+/// in their layout are formatted identically.
+impl fmt::Debug for Constraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Constraints")
+            .field("boundary_constraints", &self.boundary_constraints)
+            .field("integrity_constraints", &self.integrity_constraints)
+            .field("provenance", &self.provenance)
+            .field("selectors", &self.selectors)
+            .field("docs", &self.docs)
+            .field("expanded_buses", &self.expanded_buses)
+            .field("graph", &self.graph)
+            .finish_non_exhaustive()
+    }
+}
 impl Constraints {
     /// Constructs a new [Constraints] graph from the given parts
-    pub const fn new(
+    ///
+    /// Each root is inserted with [Constraints::insert_constraint], so a root given as an integrity
+    /// constraint, but whose domain is the first or last row, becomes a boundary constraint.
+    pub fn new(
         graph: AlgebraicGraph,
         boundary_constraints: Vec<Vec<ConstraintRoot>>,
         integrity_constraints: Vec<Vec<ConstraintRoot>>,
     ) -> Self {
-        Self {
-            graph,
-            boundary_constraints,
-            integrity_constraints,
+        let mut constraints = Self { graph, ..Default::default() };
+        for (segment, roots) in boundary_constraints.iter().enumerate() {
+            for root in roots {
+                constraints.insert_constraint(segment, root.index, root.domain);
+            }
+        }
+        for (segment, roots) in integrity_constraints.iter().enumerate() {
+            for root in roots {
+                constraints.insert_constraint(segment, root.index, root.domain);
+            }
         }
+        constraints
     }
 
     /// Returns the number of boundary constraints applied against the specified trace segment.
@@ -97,6 +142,23 @@ impl Constraints {
         &self.integrity_constraints[trace_segment]
     }
 
+    /// Returns an iterator over every constraint, boundary constraints first, each in segment
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = Constraint> + '_ {
+        let boundary = self.boundary_constraints.iter().enumerate().flat_map(|(segment, roots)| {
+            roots
+                .iter()
+                .map(move |root| Constraint::new(segment, ConstraintKind::Boundary, root))
+        });
+        let integrity =
+            self.integrity_constraints.iter().enumerate().flat_map(|(segment, roots)| {
+                roots
+                    .iter()
+                    .map(move |root| Constraint::new(segment, ConstraintKind::Integrity, root))
+            });
+        boundary.chain(integrity)
+    }
+
     /// Inserts a new constraint against `trace_segment`, using the provided `root` and `domain`
     pub fn insert_constraint(
         &mut self,
@@ -109,7 +171,16 @@ impl Constraints {
             if self.boundary_constraints.len() <= trace_segment {
                 self.boundary_constraints.resize(trace_segment + 1, vec![]);
             }
-            self.boundary_constraints[trace_segment].push(root);
+            self.boundary_constraints[trace_segment].push(root.clone());
+            // Keep the roots in segment order, which only requires a scan of the later segments,
+            // usually empty, when a root is added to an earlier one
+            let later = self.boundary_constraints[trace_segment + 1..]
+                .iter()
+                .flatten()
+                .filter(|root| root.domain() == domain)
+                .count();
+            let roots = &mut self.boundary_roots[(domain == ConstraintDomain::LastRow) as usize];
+            roots.insert(roots.len() - later, root);
         } else {
             if self.integrity_constraints.len() <= trace_segment {
                 self.integrity_constraints.resize(trace_segment + 1, vec![]);
@@ -118,6 +189,178 @@ impl Constraints {
         }
     }
 
+    /// Returns the [ConstraintRoot]s of the given group, in evaluation order, see [RootKind::roots].
+    pub fn roots(&self, kind: RootKind) -> &[ConstraintRoot] {
+        match kind {
+            RootKind::Integrity(segment) => self.integrity_constraints(segment),
+            RootKind::BoundaryFirst => &self.boundary_roots[0],
+            RootKind::BoundaryLast => &self.boundary_roots[1],
+        }
+    }
+
+    /// Groups the boundary constraints of all segments by the row they are applied to.
+    fn index_boundary_roots(&mut self) {
+        self.boundary_roots =
+            [ConstraintDomain::FirstRow, ConstraintDomain::LastRow].map(|domain| {
+                self.boundary_constraints
+                    .iter()
+                    .flatten()
+                    .filter(|root| root.domain() == domain)
+                    .cloned()
+                    .collect()
+            });
+    }
+
+    /// Returns where the given constraint was written, if it was inlined from an evaluator.
+    pub fn provenance(&self, root: &ConstraintRoot) -> Option<&ConstraintProvenance> {
+        self.provenance.get(root.node_index())
+    }
+
+    /// Records where the constraint whose entry node is `root` was written.
+    pub fn set_provenance(&mut self, root: NodeIndex, provenance: ConstraintProvenance) {
+        self.provenance.insert(root, provenance);
+    }
+
+    /// Returns the selectors under which the given constraint is enforced, outermost first, which
+    /// is empty if it is not enforced under a `when` or `match` selector.
+    pub fn selectors(&self, root: &ConstraintRoot) -> &[NodeIndex] {
+        self.selectors.get(root.node_index()).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Records the selectors under which the constraint whose entry node is `root` is enforced.
+    pub fn set_selectors(&mut self, root: NodeIndex, selectors: Vec<NodeIndex>) {
+        self.selectors.insert(root, selectors);
+    }
+
+    /// Returns the doc comments of the statement in which the given constraint is written, which
+    /// is empty if it is not documented.
+    pub fn docs(&self, root: &ConstraintRoot) -> &[String] {
+        self.docs.get(root.node_index()).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Records the doc comments of the constraint whose entry node is `root`.
+    pub fn set_docs(&mut self, root: NodeIndex, docs: Vec<String>) {
+        self.docs.insert(root, docs);
+    }
+
+    /// Returns the span of the statement in which the given constraint is written, if known.
+    pub fn span(&self, root: &ConstraintRoot) -> Option<SourceSpan> {
+        self.spans.get(root.node_index()).copied()
+    }
+
+    /// Records the span of the statement in which the constraint whose entry node is `root` is
+    /// written.
+    pub fn set_span(&mut self, root: NodeIndex, span: SourceSpan) {
+        self.spans.insert(root, span);
+    }
+
+    /// Returns the structured form of the integrity constraints of the expanded buses, in bus
+    /// order.
+    pub fn expanded_buses(&self) -> &[ExpandedBus] {
+        &self.expanded_buses
+    }
+
+    /// Returns the expanded bus whose monolithic integrity constraint is rooted at `root`, if any.
+    pub fn expanded_bus(&self, root: &ConstraintRoot) -> Option<&ExpandedBus> {
+        self.expanded_buses.iter().find(|bus| bus.root == *root.node_index())
+    }
+
+    /// Records the structured form of the integrity constraint of a bus.
+    pub fn insert_expanded_bus(&mut self, bus: ExpandedBus) {
+        self.expanded_buses.push(bus);
+    }
+
+    /// Retains only the integrity constraints against `trace_segment` for which `f` returns true,
+    /// preserving their order.
+    pub fn retain_integrity_constraints<F>(&mut self, trace_segment: TraceSegmentId, f: F)
+    where
+        F: FnMut(&ConstraintRoot) -> bool,
+    {
+        if let Some(constraints) = self.integrity_constraints.get_mut(trace_segment) {
+            constraints.retain(f);
+        }
+    }
+
+    /// Replaces the entry node of every constraint root with the node returned by `f`, visiting
+    /// boundary constraints first, then integrity constraints, in segment order.
+    ///
+    /// The provenance, the selectors, the doc comments and the span of each constraint follow its
+    /// entry node. The selectors and the nodes of the expanded buses are rewritten last, as if they
+    /// were roots of integrity constraints.
+    pub fn rewrite_roots<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&ConstraintRoot) -> NodeIndex,
+    {
+        let mut provenance = BTreeMap::new();
+        let mut selectors = BTreeMap::new();
+        let mut docs = BTreeMap::new();
+        let mut spans = BTreeMap::new();
+        for root in self
+            .boundary_constraints
+            .iter_mut()
+            .chain(self.integrity_constraints.iter_mut())
+            .flatten()
+        {
+            let index = f(root);
+            if let Some(entry) = self.provenance.get(&root.index) {
+                provenance.insert(index, entry.clone());
+            }
+            if let Some(entry) = self.selectors.get(&root.index) {
+                selectors.insert(index, entry.clone());
+            }
+            if let Some(entry) = self.docs.get(&root.index) {
+                docs.insert(index, entry.clone());
+            }
+            if let Some(entry) = self.spans.get(&root.index) {
+                spans.insert(index, *entry);
+            }
+            root.index = index;
+        }
+        self.provenance = provenance;
+        self.docs = docs;
+        self.spans = spans;
+        self.index_boundary_roots();
+
+        let mut rewrite = |index: &mut NodeIndex| {
+            *index = f(&ConstraintRoot::new(*index, ConstraintDomain::EveryRow));
+        };
+        for selector in selectors.values_mut().flatten() {
+            rewrite(selector);
+        }
+        self.selectors = selectors;
+        for bus in self.expanded_buses.iter_mut() {
+            rewrite(&mut bus.root);
+            rewrite(&mut bus.column);
+            rewrite(&mut bus.column_next);
+            for op in bus.ops.iter_mut() {
+                rewrite(&mut op.args_combined);
+                rewrite(&mut op.latch);
+            }
+        }
+    }
+
+    /// Serialization of the constraint graph to Graphviz Dot format, with the tips of all
+    /// boundary and integrity constraints labeled with their domain. See [AlgebraicGraph::to_dot].
+    pub fn to_dot(&self) -> Result<String, fmt::Error> {
+        let boundary = self.boundary_constraints.iter().enumerate();
+        let integrity = self.integrity_constraints.iter().enumerate();
+        let roots = boundary
+            .chain(integrity)
+            .flat_map(|(segment, roots)| roots.iter().map(move |root| (segment, root)));
+        self.graph.to_dot(roots)
+    }
+
+    /// Linearization of all boundary and integrity constraints to a single-static-assignment text
+    /// form. See [AlgebraicGraph::to_ssa_text].
+    pub fn to_ssa_text(&self) -> Result<String, fmt::Error> {
+        let boundary = self.boundary_constraints.iter().enumerate();
+        let integrity = self.integrity_constraints.iter().enumerate();
+        let roots = boundary
+            .chain(integrity)
+            .flat_map(|(segment, roots)| roots.iter().map(move |root| (segment, root)));
+        self.graph.to_ssa_text(roots)
+    }
+
     /// Returns the underlying [AlgebraicGraph] representing all constraints and their
     /// sub-expressions.
     #[inline]
@@ -133,6 +376,69 @@ impl Constraints {
     }
 }
 
+/// Where a constraint inlined from an evaluator was written, which is recorded when lowering the
+/// MIR to the [Air], so that diagnostics about the constraint can point into the body of the
+/// evaluator rather than at the call site only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintProvenance {
+    /// The span of the constraint in the body of the evaluator
+    pub span: SourceSpan,
+    /// The calls through which the constraint was inlined, innermost first
+    pub call_sites: Vec<CallSiteInfo>,
+}
+impl ConstraintProvenance {
+    /// Labels `diagnostic` with the span of the constraint, and with each call through which it
+    /// was inlined.
+    pub fn label<'a>(
+        &self,
+        diagnostic: InFlightDiagnostic<'a>,
+        message: impl ToString,
+    ) -> InFlightDiagnostic<'a> {
+        self.call_sites.iter().fold(
+            diagnostic.with_primary_label(self.span, message),
+            |diagnostic, call_site| {
+                diagnostic.with_secondary_label(
+                    call_site.call_span,
+                    format!("inlined from `{}` called here", call_site.callee.name()),
+                )
+            },
+        )
+    }
+}
+
+/// Whether a constraint is a boundary constraint, or an integrity constraint
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstraintKind {
+    /// A constraint applied to the first or last row of the trace
+    Boundary,
+    /// A constraint applied to every row, or every frame of the trace
+    Integrity,
+}
+
+/// A constraint of an [Air], along with everything needed to process it on its own, as yielded by
+/// [Air::all_constraints].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    /// The entry node of the subgraph representing the constraint
+    pub root: NodeIndex,
+    /// The rows against which the constraint is applied
+    pub domain: ConstraintDomain,
+    /// The trace segment against which the constraint is enforced
+    pub segment: TraceSegmentId,
+    /// Whether this is a boundary or an integrity constraint
+    pub kind: ConstraintKind,
+}
+impl Constraint {
+    fn new(segment: TraceSegmentId, kind: ConstraintKind, root: &ConstraintRoot) -> Self {
+        Self {
+            root: *root.node_index(),
+            domain: root.domain(),
+            segment,
+            kind,
+        }
+    }
+}
+
 /// A [ConstraintRoot] represents the entry node of a subgraph within the [AlgebraicGraph]
 /// representing a constraint. It also contains the [ConstraintDomain] for the constraint, which is
 /// the domain against which the constraint should be applied.
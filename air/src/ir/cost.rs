@@ -0,0 +1,125 @@
+use super::*;
+
+/// The parameters of a STARK proof which influence its size, mirroring the options of the
+/// Winterfell prover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofOptions {
+    /// The ratio between the size of the low-degree extension domain and the trace length
+    pub blowup_factor: usize,
+    /// The number of positions of the low-degree extension domain queried by the verifier
+    pub num_queries: usize,
+    /// The factor by which the domain is reduced in each FRI layer
+    pub fri_folding_factor: usize,
+    /// The maximum degree of the polynomial which is sent in the clear after the last FRI layer
+    pub fri_remainder_max_degree: usize,
+}
+impl Default for ProofOptions {
+    /// Returns the options used by the Miden VM to target 96 bits of security.
+    fn default() -> Self {
+        Self {
+            blowup_factor: 8,
+            num_queries: 27,
+            fri_folding_factor: 4,
+            fri_remainder_max_degree: 127,
+        }
+    }
+}
+
+/// A rough estimate of the cost of proving an [Air], see [Air::cost_estimate].
+///
+/// The figures which depend on the trace length are computed by the methods of this struct, so
+/// that a single estimate can be used to compare several trace lengths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// The options from which this estimate was computed
+    pub options: ProofOptions,
+    /// The width of each segment of the trace, in segment order
+    pub trace_widths: Vec<u16>,
+    /// The number of boundary constraints, across all segments
+    pub num_boundary_constraints: usize,
+    /// The number of integrity constraints, across all segments
+    pub num_integrity_constraints: usize,
+    /// The number of periodic columns, which are evaluated by the verifier
+    pub num_periodic_columns: usize,
+    /// The highest degree of the integrity constraints, as a multiple of the trace length, where
+    /// each periodic column a constraint depends on increases its degree by one
+    pub max_constraint_degree: usize,
+    /// The degree of the quotient polynomial as a multiple of the trace length, which is also
+    /// the number of columns it is split into when committed to
+    pub quotient_degree: usize,
+    /// The number of commitments to the trace segments and to the quotient polynomial, excluding
+    /// those to the FRI layers
+    pub num_trace_commitments: usize,
+    /// The number of values opened at each queried position of the trace segments and of the
+    /// quotient polynomial
+    pub num_queried_values: usize,
+}
+impl CostEstimate {
+    /// Returns the size of the low-degree extension domain for a trace of `2^log_trace_len` rows.
+    pub fn lde_domain_size(&self, log_trace_len: u32) -> usize {
+        (1 << log_trace_len) * self.options.blowup_factor
+    }
+
+    /// Returns the number of FRI layers committed to for a trace of `2^log_trace_len` rows, i.e.
+    /// the number of times the domain is folded before the remainder is small enough.
+    pub fn num_fri_layers(&self, log_trace_len: u32) -> usize {
+        let max_remainder_size =
+            (self.options.fri_remainder_max_degree + 1) * self.options.blowup_factor;
+        let mut domain_size = self.lde_domain_size(log_trace_len);
+        let mut num_layers = 0;
+        while domain_size > max_remainder_size {
+            domain_size /= self.options.fri_folding_factor;
+            num_layers += 1;
+        }
+        num_layers
+    }
+
+    /// Returns the total number of commitments in a proof for a trace of `2^log_trace_len` rows.
+    pub fn num_commitments(&self, log_trace_len: u32) -> usize {
+        self.num_trace_commitments + self.num_fri_layers(log_trace_len)
+    }
+
+    /// Returns true if the blowup factor is large enough for the quotient polynomial to be
+    /// committed to over the low-degree extension domain.
+    pub fn blowup_is_sufficient(&self) -> bool {
+        self.options.blowup_factor >= self.quotient_degree.next_power_of_two()
+    }
+}
+
+impl Air {
+    /// Estimates the cost of proving this program with the given [ProofOptions], from its trace
+    /// widths, constraints, and periodic columns.
+    ///
+    /// This is only meant to compare programs and parameters with one another, the figures are
+    /// rounded up and do not account for the details of any particular prover.
+    pub fn cost_estimate(&self, options: ProofOptions) -> CostEstimate {
+        let segments = 0..self.trace_segment_widths.len();
+        let num_boundary_constraints =
+            segments.clone().map(|segment| self.num_boundary_constraints(segment)).sum();
+        let num_integrity_constraints =
+            segments.clone().map(|segment| self.integrity_constraints(segment).len()).sum();
+        let max_constraint_degree = segments
+            .flat_map(|segment| self.integrity_constraint_degrees(segment))
+            .map(|degree| degree.base() + degree.cycles().len())
+            .max()
+            .unwrap_or(1);
+        // Dividing the composition polynomial by the vanishing polynomial of the trace domain
+        // lowers its degree by one multiple of the trace length
+        let quotient_degree = max_constraint_degree.saturating_sub(1).max(1);
+        let num_trace_segments =
+            self.trace_segment_widths.iter().filter(|width| **width > 0).count();
+        let trace_width: usize = self.trace_segment_widths.iter().map(|w| *w as usize).sum();
+
+        CostEstimate {
+            options,
+            trace_widths: self.trace_segment_widths.clone(),
+            num_boundary_constraints,
+            num_integrity_constraints,
+            num_periodic_columns: self.periodic_columns.len(),
+            max_constraint_degree,
+            quotient_degree,
+            num_trace_commitments: num_trace_segments + 1,
+            num_queried_values: trace_width + quotient_degree,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use super::*;
+
+/// The number of rows at the end of the trace over which integrity constraints are not enforced.
+///
+/// All integrity constraints share this number, regardless of the size of their frame.
+pub const NUM_TRANSITION_EXEMPTIONS: usize = 2;
+
+/// A factor of the polynomial by which a group of constraints is divided, in terms of a point `z`
+/// of the extension field, the trace length `n` and the generator `g` of the trace domain.
+///
+/// The variants are ordered such that the factors of a product can be listed canonically.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VanishingFactor {
+    /// `z - 1`, which vanishes over the first row
+    FirstRow,
+    /// `z - g⁻ⁱ`, which vanishes over the `i`-th row counting from the end of the trace, starting
+    /// at 1 for the last row
+    FromEnd(usize),
+    /// `zⁿ - 1`, which vanishes over every row
+    AllRows,
+}
+
+/// [DivisorSpec] describes the vanishing polynomial by which the constraints over a given
+/// [ConstraintDomain] are divided when composing them into the quotient polynomial.
+///
+/// See [Air::divisor_for].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DivisorSpec {
+    /// `(zⁿ - 1) / ∏ᵢ (z - g⁻ⁱ)` for `i` from 1 to `exemptions`, i.e. every row except for the
+    /// last `exemptions` ones
+    TransitionWithExemptions { exemptions: usize },
+    /// `z - 1`, i.e. the first row
+    FirstRow,
+    /// `z - g⁻²`, i.e. the last row over which the trace is constrained, as the last row of the
+    /// trace is exempted from every constraint
+    LastRow,
+}
+impl DivisorSpec {
+    /// Returns the factors of the numerator of this divisor.
+    pub fn numerator(&self) -> Vec<VanishingFactor> {
+        match self {
+            Self::TransitionWithExemptions { .. } => vec![VanishingFactor::AllRows],
+            Self::FirstRow => vec![VanishingFactor::FirstRow],
+            Self::LastRow => vec![VanishingFactor::FromEnd(NUM_TRANSITION_EXEMPTIONS)],
+        }
+    }
+
+    /// Returns the factors of the denominator of this divisor, which is empty if the divisor is a
+    /// polynomial.
+    pub fn denominator(&self) -> Vec<VanishingFactor> {
+        match self {
+            Self::TransitionWithExemptions { exemptions } => {
+                (1..=*exemptions).map(VanishingFactor::FromEnd).collect()
+            },
+            Self::FirstRow | Self::LastRow => vec![],
+        }
+    }
+}
+
+impl Air {
+    /// Returns the [DivisorSpec] of the constraints over `domain`.
+    ///
+    /// Validity and transition constraints are exempted from the last [NUM_TRANSITION_EXEMPTIONS]
+    /// rows, or more if their frame does not fit in the remaining rows.
+    pub fn divisor_for(&self, domain: ConstraintDomain) -> DivisorSpec {
+        match domain {
+            ConstraintDomain::FirstRow => DivisorSpec::FirstRow,
+            ConstraintDomain::LastRow => DivisorSpec::LastRow,
+            // TODO(Issue #392): Technically validity constraints only need to be divided by
+            //                   `zⁿ - 1`
+            ConstraintDomain::EveryRow => {
+                DivisorSpec::TransitionWithExemptions { exemptions: NUM_TRANSITION_EXEMPTIONS }
+            },
+            ConstraintDomain::EveryFrame(size) => DivisorSpec::TransitionWithExemptions {
+                exemptions: NUM_TRANSITION_EXEMPTIONS.max(size - 1),
+            },
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::*;
+use crate::graph::{AlgebraicGraph, NodeIndex};
+
+/// A constraint of an [Air], along with what is known about where it comes from, on which the
+/// constraints kept by [Air::filter] are selected.
+#[derive(Debug, Clone, Copy)]
+pub struct ConstraintMeta<'a> {
+    /// The trace segment against which the constraint is enforced
+    pub segment: TraceSegmentId,
+    /// The index of the constraint among the boundary or integrity constraints of its segment
+    pub index: usize,
+    /// The root of the constraint
+    pub root: &'a ConstraintRoot,
+    /// Where the constraint was written, if it was inlined from an evaluator
+    pub provenance: Option<&'a ConstraintProvenance>,
+    /// The bus enforced by the constraint, if it was generated by the expansion of a bus
+    pub bus: Option<Identifier>,
+}
+impl ConstraintMeta<'_> {
+    /// Returns true if this is a boundary constraint
+    pub fn is_boundary(&self) -> bool {
+        self.root.domain().is_boundary()
+    }
+
+    /// Returns true if this is an integrity constraint
+    pub fn is_integrity(&self) -> bool {
+        self.root.domain().is_integrity()
+    }
+
+    /// Returns the labels of this constraint, i.e. the names of the evaluators through which it
+    /// was inlined, outermost first, or the name of the bus it enforces.
+    ///
+    /// AirScript has no syntax to label individual constraints, so the evaluators of a constraint
+    /// stand for the component it belongs to, e.g. `stack_push`. A constraint written directly in
+    /// the `boundary_constraints` or `integrity_constraints` section has no label.
+    pub fn labels(&self) -> Vec<Symbol> {
+        let evaluators = self
+            .provenance
+            .into_iter()
+            .flat_map(|provenance| provenance.call_sites.iter().rev())
+            .map(|call_site| call_site.callee.name());
+        evaluators.chain(self.bus.map(|bus| bus.name())).collect()
+    }
+
+    /// Returns true if any label of this constraint matches the glob `pattern`, in which `*`
+    /// matches any sequence of characters, and `?` any single character.
+    pub fn matches_label(&self, pattern: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        self.labels().iter().any(|label| {
+            let label: Vec<char> = label.as_str().chars().collect();
+            glob_matches(&pattern, &label)
+        })
+    }
+}
+
+impl Air {
+    /// Returns the [ConstraintMeta] of every constraint of this [Air], boundary constraints first,
+    /// in segment order.
+    pub fn constraint_metas(&self) -> Vec<ConstraintMeta<'_>> {
+        let bus_columns: BTreeMap<usize, Identifier> =
+            self.bus_columns.iter().map(|(name, column)| (*column, *name)).collect();
+        let mut metas = vec![];
+        for segment in 0..self.trace_segment_widths.len() {
+            for (index, root) in self.boundary_constraints(segment).iter().enumerate() {
+                // The boundaries of a bus are enforced on its column, e.g. `p - 1` for `p.first`
+                let bus = match self.constraint_graph().node(root.node_index()).op() {
+                    Operation::Sub(lhs, _) => match self.constraint_graph().node(lhs).op() {
+                        Operation::Value(Value::TraceAccess(access))
+                            if access.segment == AUX_SEGMENT =>
+                        {
+                            bus_columns.get(&access.column).copied()
+                        },
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                metas.push(ConstraintMeta {
+                    segment,
+                    index,
+                    root,
+                    provenance: None,
+                    bus,
+                });
+            }
+        }
+        for segment in 0..self.trace_segment_widths.len() {
+            for (index, root) in self.integrity_constraints(segment).iter().enumerate() {
+                metas.push(ConstraintMeta {
+                    segment,
+                    index,
+                    root,
+                    provenance: self.constraint_provenance(root),
+                    bus: self.expanded_bus(root).map(|bus| bus.name),
+                });
+            }
+        }
+        metas
+    }
+
+    /// Returns a new [Air] enforcing only the constraints of this one for which `predicate`
+    /// returns true, e.g. to prove a partial trace against the constraints of a single component.
+    ///
+    /// The constraints of a bus are either kept or excluded together: a bus is kept only if all of
+    /// its expanded constraints are selected. Otherwise, its column is left unconstrained, without
+    /// any operation or boundary. Buses which have not been expanded yet are always kept.
+    ///
+    /// The trace segments, public inputs, periodic columns and random values are preserved, so
+    /// that the same trace fits both programs. The constraint graph is pruned to the nodes
+    /// reachable from the remaining constraints.
+    pub fn filter(&self, predicate: impl Fn(&ConstraintMeta) -> bool) -> Air {
+        let metas = self.constraint_metas();
+        let selected: Vec<bool> = metas.iter().map(&predicate).collect();
+        let excluded_buses: BTreeSet<Identifier> = metas
+            .iter()
+            .zip(selected.iter())
+            .filter(|(_, selected)| !**selected)
+            .filter_map(|(meta, _)| meta.bus)
+            .collect();
+
+        let mut air = Air::new(self.name);
+        air.trace_segment_widths = self.trace_segment_widths.clone();
+        air.trace_segment_names = self.trace_segment_names.clone();
+        air.trace_column_names = self.trace_column_names.clone();
+        air.periodic_columns = self.periodic_columns.clone();
+        air.public_inputs = self.public_inputs.clone();
+        air.num_random_values = self.num_random_values;
+        air.buses_span = self.buses_span;
+        air.bus_columns = self.bus_columns.clone();
+        air.virtual_columns = self.virtual_columns.clone();
+
+        let mut pruner = Pruner {
+            source: self.constraint_graph(),
+            graph: AlgebraicGraph::default(),
+            rewrites: BTreeMap::new(),
+        };
+        let mut roots = vec![];
+        for (meta, _) in metas.iter().zip(selected).filter(|(meta, selected)| {
+            *selected && !meta.bus.is_some_and(|bus| excluded_buses.contains(&bus))
+        }) {
+            let root = pruner.node(*meta.root.node_index());
+            roots.push((meta.segment, root, meta.root.domain()));
+            if let Some(provenance) = meta.provenance {
+                air.constraints.set_provenance(root, provenance.clone());
+            }
+            let selectors = self.constraint_selectors(meta.root);
+            if !selectors.is_empty() {
+                let selectors = selectors.iter().map(|selector| pruner.node(*selector)).collect();
+                air.constraints.set_selectors(root, selectors);
+            }
+            let docs = self.constraint_docs(meta.root);
+            if !docs.is_empty() {
+                air.constraints.set_docs(root, docs.to_vec());
+            }
+            if let Some(span) = self.constraint_span(meta.root) {
+                air.constraints.set_span(root, span);
+            }
+        }
+
+        for (name, bus) in self.buses.iter() {
+            let bus = if excluded_buses.contains(name) {
+                Bus {
+                    first: BusBoundary::Unconstrained,
+                    last: BusBoundary::Unconstrained,
+                    bus_ops: vec![],
+                    multiplicity_range: None,
+                    ..bus.clone()
+                }
+            } else {
+                Bus {
+                    bus_ops: bus
+                        .bus_ops
+                        .iter()
+                        .map(|op| BusOp {
+                            columns: op.columns.iter().map(|col| pruner.node(*col)).collect(),
+                            latch: pruner.node(op.latch),
+                            op_kind: op.op_kind,
+                        })
+                        .collect(),
+                    ..bus.clone()
+                }
+            };
+            air.buses.insert(*name, bus);
+        }
+        for bus in self.expanded_buses().iter().filter(|bus| !excluded_buses.contains(&bus.name)) {
+            air.constraints.insert_expanded_bus(ExpandedBus {
+                root: pruner.node(bus.root),
+                column: pruner.node(bus.column),
+                column_next: pruner.node(bus.column_next),
+                ops: bus
+                    .ops
+                    .iter()
+                    .map(|op| ExpandedBusOp {
+                        args_combined: pruner.node(op.args_combined),
+                        latch: pruner.node(op.latch),
+                        kind: op.kind,
+                    })
+                    .collect(),
+                ..bus.clone()
+            });
+        }
+
+        *air.constraint_graph_mut() = pruner.graph;
+        for (segment, root, domain) in roots {
+            air.constraints.insert_constraint(segment, root, domain);
+        }
+        air
+    }
+}
+
+/// Copies the nodes of a constraint graph reachable from the nodes it is asked for to a new graph.
+struct Pruner<'a> {
+    source: &'a AlgebraicGraph,
+    graph: AlgebraicGraph,
+    /// Maps each node of the source graph copied so far to its copy
+    rewrites: BTreeMap<NodeIndex, NodeIndex>,
+}
+impl Pruner<'_> {
+    /// Returns the copy of the source node at `index`, copying it along with its operands if
+    /// needed
+    fn node(&mut self, index: NodeIndex) -> NodeIndex {
+        if let Some(copy) = self.rewrites.get(&index) {
+            return *copy;
+        }
+        let op = match *self.source.node(&index).op() {
+            Operation::Value(value) => Operation::Value(value),
+            Operation::Add(lhs, rhs) => Operation::Add(self.node(lhs), self.node(rhs)),
+            Operation::Sub(lhs, rhs) => Operation::Sub(self.node(lhs), self.node(rhs)),
+            Operation::Mul(lhs, rhs) => Operation::Mul(self.node(lhs), self.node(rhs)),
+        };
+        let copy = self.graph.insert_node(op);
+        self.rewrites.insert(index, copy);
+        copy
+    }
+}
+
+/// Returns true if `text` matches the glob `pattern`, see [ConstraintMeta::matches_label].
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        Some(('?', rest)) => !text.is_empty() && glob_matches(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && glob_matches(rest, &text[1..]),
+    }
+}
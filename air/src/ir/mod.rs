@@ -1,9 +1,19 @@
 mod bus;
 mod constraints;
+mod cost;
 mod degree;
+mod divisor;
+mod filter;
 mod operation;
+mod product;
+mod public_inputs;
+mod rename;
 mod trace;
+mod trace_layout;
+mod trace_length;
 mod value;
+mod virtual_columns;
+mod witness;
 
 pub use air_parser::{
     Symbol,
@@ -12,14 +22,31 @@ pub use air_parser::{
         TraceSegmentId,
     },
 };
+pub use mir::ir::CallSiteInfo;
 
 pub use self::{
-    bus::{Bus, BusBoundary, BusOp, BusOpKind, BusType, PublicInputTableAccess},
-    constraints::{ConstraintDomain, ConstraintError, ConstraintRoot, Constraints},
+    bus::{
+        Bus, BusBoundary, BusOp, BusOpKind, BusType, ExpandedBus, ExpandedBusOp, MultiplicityRange,
+        PublicInputTableAccess,
+    },
+    constraints::{
+        Constraint, ConstraintDomain, ConstraintError, ConstraintKind, ConstraintProvenance,
+        ConstraintRoot, Constraints,
+    },
+    cost::{CostEstimate, ProofOptions},
     degree::IntegrityConstraintDegree,
+    divisor::{DivisorSpec, NUM_TRANSITION_EXEMPTIONS, VanishingFactor},
+    filter::ConstraintMeta,
     operation::Operation,
+    product::ProductError,
+    public_inputs::{PublicInputKind, PublicInputSchema},
+    rename::RenameError,
     trace::TraceAccess,
+    trace_layout::{TraceLayoutRegistry, TraceLayoutSpec},
+    trace_length::TraceLengthBounds,
     value::{PeriodicColumnAccess, PublicInputAccess, Value},
+    virtual_columns::VirtualColumns,
+    witness::{WitnessInput, WitnessLayout, WitnessRegion},
 };
 
 /// The default segment against which a constraint is applied is the main trace segment.
@@ -31,11 +58,12 @@ pub const CURRENT_ROW: usize = 0;
 /// The minimum cycle length of a periodic column
 pub const MIN_CYCLE_LENGTH: usize = 2;
 
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fmt};
 
 use miden_diagnostics::{SourceSpan, Spanned};
+use winter_math::fields::f64::BaseElement as Felt;
 
-use crate::graph::AlgebraicGraph;
+use crate::graph::{AlgebraicGraph, NodeIndex};
 
 /// The intermediate representation of a complete AirScript program
 ///
@@ -51,7 +79,17 @@ pub struct Air {
     pub name: Identifier,
     /// The widths (number of columns) of each segment of the trace, in segment order (i.e. the
     /// index in this vector matches the index of the segment in the program).
+    ///
+    /// An aux segment without any column is not materialized, i.e. this vector only holds the
+    /// width of the main segment in that case.
     pub trace_segment_widths: Vec<u16>,
+    /// The names of each segment of the trace, in segment order.
+    ///
+    /// The main segment is named after its declaration in the program, while the name of the aux
+    /// segment, which holds the bus columns, can be configured when translating to [Air].
+    pub trace_segment_names: Vec<Identifier>,
+    /// The name of each column of the trace, by segment, see [Air::trace_column_name].
+    pub trace_column_names: Vec<Vec<String>>,
     /// The periodic columns referenced by this program.
     ///
     /// These are taken straight from the [air_parser::ast::Program] without modification.
@@ -62,13 +100,35 @@ pub struct Air {
     pub public_inputs: BTreeMap<Identifier, PublicInput>,
     /// The total number of elements in the random values array
     pub num_random_values: u16,
+    /// The span of the `buses` section of the program, if any. It is where the random values are
+    /// declared, as they are drawn for the buses.
+    pub buses_span: Option<SourceSpan>,
     /// The constraints enforced by this program, in their algebraic graph representation.
     pub constraints: Constraints,
     /// The buses referenced by this program.
     ///
     /// Only their name, type, and the first and last boundary constraints are stored here.
     pub buses: BTreeMap<Identifier, Bus>,
+    /// The column of the aux segment allocated to each bus during lowering, see
+    /// [Air::bus_column_layout].
+    pub(crate) bus_columns: BTreeMap<Identifier, usize>,
+    /// The columns of the main trace declared virtual, with their definition.
+    ///
+    /// These are not enforced, see [VirtualColumns].
+    pub virtual_columns: VirtualColumns,
+}
+/// The reasons why the periodic columns of an [Air] cannot be evaluated over a trace, see
+/// [Air::z_exponent_for_periodic].
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PeriodicColumnError {
+    #[error("the trace length {0} is not a power of two")]
+    TraceLengthNotPowerOfTwo(u64),
+    #[error("the cycle length {1} of periodic column '{0}' is not a power of two")]
+    CycleLengthNotPowerOfTwo(Identifier, u64),
+    #[error("the cycle length {1} of periodic column '{0}' exceeds the trace length {2}")]
+    CycleLongerThanTrace(Identifier, u64, u64),
 }
+
 impl Default for Air {
     fn default() -> Self {
         Self::new(Identifier::new(SourceSpan::UNKNOWN, Symbol::intern("unnamed")))
@@ -85,11 +145,16 @@ impl Air {
         Self {
             name,
             trace_segment_widths: vec![],
+            trace_segment_names: vec![],
+            trace_column_names: vec![],
             periodic_columns: Default::default(),
             public_inputs: Default::default(),
             num_random_values: 0,
+            buses_span: None,
             constraints: Default::default(),
             buses: Default::default(),
+            bus_columns: Default::default(),
+            virtual_columns: Default::default(),
         }
     }
 
@@ -99,14 +164,147 @@ impl Air {
         self.name.as_str()
     }
 
+    /// Returns the name of the given trace segment, falling back to the default name of the
+    /// segment if it was not named during translation.
+    pub fn trace_segment_name(&self, trace_segment: TraceSegmentId) -> Symbol {
+        match self.trace_segment_names.get(trace_segment) {
+            Some(name) => name.name(),
+            None if trace_segment == DEFAULT_SEGMENT => air_parser::symbols::Main,
+            None => air_parser::symbols::Aux,
+        }
+    }
+
+    /// Returns the name of the given column, e.g. `s[1]` for the second column of a binding
+    /// `s[2]`, falling back to its index in the segment if it was not named during translation,
+    /// e.g. `main[3]`.
+    pub fn trace_column_name(&self, trace_segment: TraceSegmentId, column: usize) -> String {
+        let name = self.trace_column_names.get(trace_segment).and_then(|names| names.get(column));
+        match name.filter(|name| !name.is_empty()) {
+            Some(name) => name.clone(),
+            None => {
+                let segment = self.trace_segment_name(trace_segment);
+                format!("{}[{column}]", segment.as_str().trim_start_matches('$'))
+            },
+        }
+    }
+
+    /// Returns true if this program has a non-empty auxiliary trace segment, e.g. because it uses
+    /// buses, whose columns are allocated in the aux segment during translation to [Air].
+    pub fn has_aux_segment(&self) -> bool {
+        self.trace_segment_widths.get(AUX_SEGMENT).is_some_and(|width| *width > 0)
+    }
+
+    /// Returns true if this program uses random values, which are drawn after the main trace is
+    /// committed to, in order to build the auxiliary trace.
+    pub fn uses_random_values(&self) -> bool {
+        self.num_random_values > 0
+    }
+
+    /// Returns the public inputs of this program sorted by name.
+    ///
+    /// This is the canonical order in which the public inputs are absorbed into the transcript,
+    /// e.g. by the `ToElements` implementation generated for Winterfell and by the layout of the
+    /// inputs of an ACE circuit, which must agree for the verifier to accept a proof.
     pub fn public_inputs(&self) -> impl Iterator<Item = &PublicInput> + '_ {
         self.public_inputs.values()
     }
 
+    /// Returns the public inputs of this program in the order in which they were declared, rather
+    /// than the canonical order of [Air::public_inputs], which sorts them by name.
+    pub fn declared_public_inputs(&self) -> Vec<&PublicInput> {
+        let mut inputs: Vec<_> = self.public_inputs().collect();
+        inputs.sort_by_key(|input| input.span().start());
+        inputs
+    }
+
+    /// Returns the [PublicInputSchema] of each public input of this program, in the same order as
+    /// [Air::public_inputs], which is the order in which they are expected by the backends.
+    pub fn public_input_schema(&self) -> Vec<PublicInputSchema> {
+        self.public_inputs().map(PublicInputSchema::from).collect()
+    }
+
+    /// Returns the column of the aux segment which holds each bus, i.e. the column a prover must
+    /// fill with the running product or sum of the bus when building the aux trace.
+    pub fn bus_column_layout(&self) -> BTreeMap<Identifier, usize> {
+        self.bus_columns.clone()
+    }
+
     pub fn periodic_columns(&self) -> impl Iterator<Item = &PeriodicColumn> + '_ {
         self.periodic_columns.values()
     }
 
+    /// Returns the coefficients of the polynomial interpolating the periodic column `name` over
+    /// its cycle, from the constant term up, or `None` if there is no such column.
+    ///
+    /// The `i`-th value of a column with a cycle of length `k` is the evaluation of this polynomial
+    /// at `ωⁱ`, where `ω` is the primitive `k`-th root of unity. On a trace of length `n`, the
+    /// column is thus evaluated at `zⁿᐟᵏ` out of domain.
+    pub fn periodic_column_polynomial(&self, name: &QualifiedIdentifier) -> Option<Vec<u64>> {
+        let column = self.periodic_columns.get(name)?;
+        let mut poly: Vec<_> = column.values.iter().copied().map(Felt::new).collect();
+        let inv_twiddles = winter_math::fft::get_inv_twiddles::<Felt>(poly.len());
+        winter_math::fft::interpolate_poly(&mut poly, &inv_twiddles);
+        Some(poly.into_iter().map(|coeff| coeff.as_int()).collect())
+    }
+
+    /// Returns the coefficients of the polynomial interpolating each periodic column, see
+    /// [Air::periodic_column_polynomial].
+    pub fn periodic_column_polynomials(&self) -> BTreeMap<QualifiedIdentifier, Vec<u64>> {
+        self.periodic_columns
+            .keys()
+            .filter_map(|name| Some((*name, self.periodic_column_polynomial(name)?)))
+            .collect()
+    }
+
+    /// Returns the distinct accesses of periodic columns in a later row, e.g. `k'`, sorted by
+    /// column and row offset.
+    ///
+    /// Such accesses evaluate the column polynomial at `z·gᵒ` instead of `z`, where `o` is the row
+    /// offset, which the Winterfell backend supports by declaring a copy of the column whose
+    /// values are rotated by `o`.
+    pub fn shifted_periodic_columns(&self) -> Vec<PeriodicColumnAccess> {
+        let graph = self.constraint_graph();
+        let accesses = (0..graph.num_nodes())
+            .filter_map(|index| match graph.node(&index.into()).op() {
+                Operation::Value(Value::PeriodicColumn(access)) if access.row_offset > 0 => {
+                    Some(*access)
+                },
+                _ => None,
+            })
+            .collect::<std::collections::BTreeSet<_>>();
+        accesses.into_iter().collect()
+    }
+
+    /// Returns the exponent `k = trace_len / max_cycle_len`, such that the longest periodic column
+    /// is evaluated at `z^k`, or 0 if there are no periodic columns.
+    ///
+    /// A column of cycle length `cycle_len` is evaluated at `z^(trace_len / cycle_len)`, which the
+    /// verifiers derive from `z^k` by squaring it `log2(max_cycle_len / cycle_len)` times. This is
+    /// only exact when the trace length and all the cycle lengths are powers of two, with no cycle
+    /// longer than the trace, so this is validated here. Backends must use this function rather
+    /// than computing `k` themselves, so that they all agree on its definition.
+    pub fn z_exponent_for_periodic(&self, trace_len: u64) -> Result<u64, PeriodicColumnError> {
+        if !trace_len.is_power_of_two() {
+            return Err(PeriodicColumnError::TraceLengthNotPowerOfTwo(trace_len));
+        }
+        let mut max_cycle_len = None;
+        for column in self.periodic_columns() {
+            let cycle_len = column.period() as u64;
+            if !cycle_len.is_power_of_two() {
+                return Err(PeriodicColumnError::CycleLengthNotPowerOfTwo(column.name, cycle_len));
+            }
+            if cycle_len > trace_len {
+                return Err(PeriodicColumnError::CycleLongerThanTrace(
+                    column.name,
+                    cycle_len,
+                    trace_len,
+                ));
+            }
+            max_cycle_len = max_cycle_len.max(Some(cycle_len));
+        }
+        Ok(max_cycle_len.map(|max_cycle_len| trace_len / max_cycle_len).unwrap_or(0))
+    }
+
     /// Return the number of boundary constraints
     pub fn num_boundary_constraints(&self, trace_segment: TraceSegmentId) -> usize {
         self.constraints.num_boundary_constraints(trace_segment)
@@ -122,6 +320,52 @@ impl Air {
         self.constraints.integrity_constraints(trace_segment)
     }
 
+    /// Returns an iterator over every [Constraint] of this program, boundary constraints first,
+    /// each in segment order.
+    pub fn all_constraints(&self) -> impl Iterator<Item = Constraint> + '_ {
+        self.constraints.iter()
+    }
+
+    /// Returns where the given constraint was written, if it was inlined from an evaluator
+    pub fn constraint_provenance(&self, root: &ConstraintRoot) -> Option<&ConstraintProvenance> {
+        self.constraints.provenance(root)
+    }
+
+    /// Returns the selectors under which the given constraint is enforced, outermost first, e.g.
+    /// `s` for `enf x' = x + 1 when s`
+    pub fn constraint_selectors(&self, root: &ConstraintRoot) -> &[NodeIndex] {
+        self.constraints.selectors(root)
+    }
+
+    /// Returns the doc comments of the statement in which the given constraint is written, e.g.
+    /// the `##` lines above `enf x' = x + 1`, which is empty if it is not documented.
+    ///
+    /// The constraints inlined from an evaluator are documented by the statement of the evaluator
+    /// they are written in, or else by the nearest documented call through which they were
+    /// inlined. The doc comments of the evaluators themselves are recorded in the call sites of
+    /// their [ConstraintProvenance].
+    pub fn constraint_docs(&self, root: &ConstraintRoot) -> &[String] {
+        self.constraints.docs(root)
+    }
+
+    /// Returns the span of the statement in which the given constraint is written, e.g.
+    /// `enf x' = x + 1`, if it was translated from the MIR. The span of a constraint inlined from
+    /// an evaluator is the one of its [ConstraintProvenance].
+    pub fn constraint_span(&self, root: &ConstraintRoot) -> Option<SourceSpan> {
+        self.constraints.span(root)
+    }
+
+    /// Return the structured form of the integrity constraints of the buses expanded by
+    /// [crate::passes::BusOpExpand]
+    pub fn expanded_buses(&self) -> &[ExpandedBus] {
+        self.constraints.expanded_buses()
+    }
+
+    /// Returns the expanded bus whose monolithic integrity constraint is rooted at `root`, if any
+    pub fn expanded_bus(&self, root: &ConstraintRoot) -> Option<&ExpandedBus> {
+        self.constraints.expanded_bus(root)
+    }
+
     /// Return the set of [IntegrityConstraintDegree] corresponding to each integrity constraint
     pub fn integrity_constraint_degrees(
         &self,
@@ -130,6 +374,22 @@ impl Air {
         self.constraints.integrity_constraint_degrees(trace_segment)
     }
 
+    /// Return the set of [IntegrityConstraintDegree] corresponding to each integrity constraint,
+    /// with each access to a virtual column replaced by its definition.
+    ///
+    /// These are the degrees the constraints would have if the virtual columns were not committed
+    /// to, while [Air::integrity_constraint_degrees] reports the degrees of the constraints as
+    /// they are enforced.
+    pub fn integrity_constraint_degrees_with_virtual_columns(
+        &self,
+        trace_segment: TraceSegmentId,
+    ) -> Vec<IntegrityConstraintDegree> {
+        self.integrity_constraints(trace_segment)
+            .iter()
+            .map(|root| self.virtual_columns.degree(self.constraint_graph(), root.node_index()))
+            .collect()
+    }
+
     /// Return an [Iterator] over the validity constraints for the given trace segment
     pub fn validity_constraints(
         &self,
@@ -163,4 +423,13 @@ impl Air {
     pub fn constraint_graph_mut(&mut self) -> &mut AlgebraicGraph {
         self.constraints.graph_mut()
     }
+
+    /// Returns the constraints of this program as a flat list of single-static-assignment
+    /// statements, in which every shared subexpression is named once, for auditing.
+    ///
+    /// Boundary constraints are listed before integrity constraints, and the columns of the main
+    /// and aux trace segments are written `M[i]` and `A[i]`. See [AlgebraicGraph::to_ssa_text].
+    pub fn to_ssa_text(&self) -> Result<String, fmt::Error> {
+        self.constraints.to_ssa_text()
+    }
 }
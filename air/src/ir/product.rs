@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::*;
+use crate::graph::{AlgebraicGraph, NodeIndex};
+
+/// The reasons why [Air]s cannot be composed into a product [Air], see [Air::product] and
+/// [Air::link].
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ProductError {
+    #[error("public input '{0}' is declared by both programs, even after namespacing it")]
+    PublicInputConflict(Identifier),
+    #[error("periodic column '{0}' is declared by both programs")]
+    PeriodicColumnConflict(QualifiedIdentifier),
+    #[error("bus '{0}' is defined by both programs")]
+    BusConflict(Identifier),
+    #[error("bus '{0}' is declared with a different type by each program")]
+    BusTypeMismatch(Identifier),
+    #[error("bus '{0}' carries tuples of {1} values in one program, but {2} in the other")]
+    BusArityMismatch(Identifier, usize, usize),
+    /// An extern bus which is not defined by any of the linked programs, along with each of its
+    /// declarations.
+    #[error("extern bus '{}' is not defined by any of the linked programs", .0[0])]
+    UnresolvedExternBus(Vec<Identifier>),
+}
+
+impl Air {
+    /// Composes `a` and `b` into a single [Air] enforcing the constraints of both, so that two
+    /// independent computations can be verified with a single proof.
+    ///
+    /// The trace columns of `b` are placed after those of `a`, and the constraints of `b` are
+    /// enforced after those of `a`, in each trace segment. The columns of the aux segment are
+    /// allocated to buses in the order of [Air::buses], so the bus columns of both programs are
+    /// reordered to match the merged set of buses. Random values are shared by both programs.
+    ///
+    /// Public inputs declared by only one of the programs keep their name, while those declared by
+    /// both are namespaced with the name of their program, e.g. `inputs` becomes `a_inputs` and
+    /// `b_inputs`. Any conflict which remains after that, as well as periodic columns or buses
+    /// declared by both programs, is reported as a [ProductError].
+    ///
+    /// A bus declared `extern` by one of the programs is instead unified with the bus of the same
+    /// name of the other, provided they have the same type and carry tuples of the same size: the
+    /// operations of `b` are then enforced after those of `a`, on a single aux column.
+    ///
+    /// The virtual columns of both programs remain virtual in the product.
+    pub fn product(a: &Air, b: &Air) -> Result<Air, ProductError> {
+        let public_inputs_a = namespaced_public_inputs(a, b);
+        let public_inputs_b = namespaced_public_inputs(b, a);
+        let mut public_inputs = BTreeMap::default();
+        for input in public_inputs_a.values().chain(public_inputs_b.values()) {
+            let name = input.name();
+            if public_inputs.insert(name, input.clone()).is_some() {
+                return Err(ProductError::PublicInputConflict(name));
+            }
+        }
+
+        let mut periodic_columns = a.periodic_columns.clone();
+        for (name, column) in b.periodic_columns.iter() {
+            if periodic_columns.insert(*name, column.clone()).is_some() {
+                return Err(ProductError::PeriodicColumnConflict(*name));
+            }
+        }
+
+        for (name, bus_b) in b.buses.iter() {
+            let Some(bus_a) = a.buses.get(name) else {
+                continue;
+            };
+            if !bus_a.is_extern && !bus_b.is_extern {
+                return Err(ProductError::BusConflict(*name));
+            }
+            if bus_a.bus_type != bus_b.bus_type {
+                return Err(ProductError::BusTypeMismatch(*name));
+            }
+            if let (Some(arity_a), Some(arity_b)) = (tuple_arity(bus_a), tuple_arity(bus_b)) {
+                if arity_a != arity_b {
+                    return Err(ProductError::BusArityMismatch(*name, arity_a, arity_b));
+                }
+            }
+        }
+        let bus_names = a.buses.keys().chain(b.buses.keys()).copied().collect::<BTreeSet<_>>();
+
+        let name = Identifier::new(a.name.span(), Symbol::intern(format!("{}_{}", a.name, b.name)));
+        let mut air = Air::new(name);
+        let num_segments = a.trace_segment_widths.len().max(b.trace_segment_widths.len());
+        air.trace_segment_widths = (0..num_segments)
+            .map(|segment| {
+                let width = |air: &Air| air.trace_segment_widths.get(segment).copied().unwrap_or(0);
+                // Buses shared by both programs are allocated a single column
+                if segment == AUX_SEGMENT {
+                    bus_names.len() as u16
+                } else {
+                    width(a) + width(b)
+                }
+            })
+            .collect();
+        air.trace_segment_names = if a.trace_segment_names.len() >= b.trace_segment_names.len() {
+            a.trace_segment_names.clone()
+        } else {
+            b.trace_segment_names.clone()
+        };
+        // The main columns of `b` are named after those of `a`, while the aux segment only holds
+        // the columns of the buses
+        let main_column_names = |air: &Air| {
+            let width = air.trace_segment_widths.get(DEFAULT_SEGMENT).copied().unwrap_or(0);
+            (0..width as usize)
+                .map(|column| air.trace_column_name(DEFAULT_SEGMENT, column))
+                .collect::<Vec<_>>()
+        };
+        air.trace_column_names = vec![[main_column_names(a), main_column_names(b)].concat()];
+        if num_segments > AUX_SEGMENT {
+            air.trace_column_names.push(bus_names.iter().map(ToString::to_string).collect());
+        }
+        air.periodic_columns = periodic_columns;
+        air.public_inputs = public_inputs;
+        air.num_random_values = a.num_random_values.max(b.num_random_values);
+        air.buses_span = a.buses_span.or(b.buses_span);
+        air.bus_columns =
+            bus_names.iter().enumerate().map(|(column, name)| (*name, column)).collect();
+
+        let main_offset = a.trace_segment_widths.get(DEFAULT_SEGMENT).copied().unwrap_or(0);
+        let mut graph = AlgebraicGraph::default();
+        let mut boundary_constraints = vec![];
+        let mut integrity_constraints = vec![];
+        let mut provenance = vec![];
+        let mut selectors = vec![];
+        let mut docs = vec![];
+        let mut spans = vec![];
+        let mut virtual_graph = AlgebraicGraph::default();
+        let mut virtual_definitions = BTreeMap::new();
+        for (factor, public_inputs, main_offset) in
+            [(a, &public_inputs_a, 0), (b, &public_inputs_b, main_offset as usize)]
+        {
+            let mut copier = FactorCopier {
+                public_inputs,
+                main_offset,
+                bus_columns: factor_bus_columns(factor, &bus_names),
+                rewrites: Vec::with_capacity(factor.constraint_graph().num_nodes()),
+            };
+            copier.copy_graph(factor.constraint_graph(), &mut graph);
+
+            for segment in 0..factor.trace_segment_widths.len() {
+                for root in factor.boundary_constraints(segment) {
+                    boundary_constraints.push((segment, copier.rewrite(root)));
+                    let entry = factor.constraint_docs(root);
+                    if !entry.is_empty() {
+                        docs.push((copier.node(*root.node_index()), entry.to_vec()));
+                    }
+                    if let Some(span) = factor.constraint_span(root) {
+                        spans.push((copier.node(*root.node_index()), span));
+                    }
+                }
+                for root in factor.integrity_constraints(segment) {
+                    integrity_constraints.push((segment, copier.rewrite(root)));
+                    if let Some(entry) = factor.constraint_provenance(root) {
+                        provenance.push((copier.node(*root.node_index()), entry.clone()));
+                    }
+                    let entry = factor.constraint_selectors(root);
+                    if !entry.is_empty() {
+                        let entry = entry.iter().map(|selector| copier.node(*selector)).collect();
+                        selectors.push((copier.node(*root.node_index()), entry));
+                    }
+                    let entry = factor.constraint_docs(root);
+                    if !entry.is_empty() {
+                        docs.push((copier.node(*root.node_index()), entry.to_vec()));
+                    }
+                    if let Some(span) = factor.constraint_span(root) {
+                        spans.push((copier.node(*root.node_index()), span));
+                    }
+                }
+            }
+
+            for (name, bus) in factor.buses.iter() {
+                let bus = Bus {
+                    first: copier.rewrite_bus_boundary(&bus.first),
+                    last: copier.rewrite_bus_boundary(&bus.last),
+                    bus_ops: bus
+                        .bus_ops
+                        .iter()
+                        .map(|op| BusOp {
+                            columns: op.columns.iter().map(|col| copier.node(*col)).collect(),
+                            latch: copier.node(op.latch),
+                            op_kind: op.op_kind,
+                        })
+                        .collect(),
+                    multiplicity_range: bus.multiplicity_range.as_ref().map(|range| {
+                        MultiplicityRange {
+                            bits: range.bits.iter().map(|bit| bit + copier.main_offset).collect(),
+                            ..range.clone()
+                        }
+                    }),
+                    ..bus.clone()
+                };
+                match air.buses.get_mut(name) {
+                    Some(unified) => unify_buses(unified, bus),
+                    None => {
+                        air.buses.insert(*name, bus);
+                    },
+                }
+            }
+
+            copier.copy_graph(factor.virtual_columns.graph(), &mut virtual_graph);
+            for (column, name, definition) in factor.virtual_columns.iter() {
+                virtual_definitions
+                    .insert(column + copier.main_offset, (name, copier.node(definition)));
+            }
+        }
+
+        *air.constraint_graph_mut() = graph;
+        air.virtual_columns = VirtualColumns::new(virtual_graph, virtual_definitions);
+        // The constraints of `a` are inserted before those of `b`, in each segment
+        boundary_constraints.sort_by_key(|(segment, _)| *segment);
+        integrity_constraints.sort_by_key(|(segment, _)| *segment);
+        for (segment, root) in boundary_constraints.into_iter().chain(integrity_constraints) {
+            air.constraints.insert_constraint(segment, *root.node_index(), root.domain());
+        }
+        for (root, entry) in provenance {
+            air.constraints.set_provenance(root, entry);
+        }
+        for (root, entry) in selectors {
+            air.constraints.set_selectors(root, entry);
+        }
+        for (root, entry) in docs {
+            air.constraints.set_docs(root, entry);
+        }
+        for (root, span) in spans {
+            air.constraints.set_span(root, span);
+        }
+
+        Ok(air)
+    }
+
+    /// Links `airs` into a single [Air], in which each bus declared `extern` by some of the
+    /// programs is unified with the bus of the same name defined by exactly one of them.
+    ///
+    /// The programs are composed in order with [Air::product], so the operations of a bus shared
+    /// by several programs are enforced on a single aux column, in the order of `airs`. Since the
+    /// buses of an [Air] can no longer be unified once they are expanded into constraints, the
+    /// programs must be compiled without [crate::passes::BusOpExpand], which should instead be run
+    /// on the linked [Air]. The linked [Air] is named after the first program.
+    ///
+    /// An extern bus which is not defined by any of the programs is reported as
+    /// [ProductError::UnresolvedExternBus].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `airs` is empty.
+    pub fn link(airs: Vec<Air>) -> Result<Air, ProductError> {
+        let mut declarations = BTreeMap::<Identifier, Vec<Identifier>>::default();
+        for bus in airs.iter().flat_map(|air| air.buses.values()) {
+            declarations.entry(bus.name).or_default().push(bus.name);
+        }
+
+        let mut airs = airs.into_iter();
+        let mut linked = airs.next().expect("expected at least one program to link");
+        let name = linked.name;
+        for air in airs {
+            linked = Air::product(&linked, &air)?;
+        }
+        linked.name = name;
+
+        if let Some(bus) = linked.buses.values().find(|bus| bus.is_extern) {
+            return Err(ProductError::UnresolvedExternBus(declarations.remove(&bus.name).unwrap()));
+        }
+        Ok(linked)
+    }
+}
+
+/// Returns the number of values in the tuples carried by `bus`, if all of its operations agree
+/// on it.
+fn tuple_arity(bus: &Bus) -> Option<usize> {
+    let arity = bus.bus_ops.first()?.columns.len();
+    bus.bus_ops.iter().all(|op| op.columns.len() == arity).then_some(arity)
+}
+
+/// Unifies `bus` with the bus of the same name of a previous factor, at least one of which is
+/// declared `extern`, by appending its operations to those of `unified`.
+fn unify_buses(unified: &mut Bus, bus: Bus) {
+    let offset = unified.bus_ops.len();
+    if unified.is_extern {
+        // The definition of the bus determines its boundaries
+        unified.name = bus.name;
+        unified.first = bus.first;
+        unified.last = bus.last;
+        unified.is_extern = bus.is_extern;
+    }
+    if let Some(range) = bus.multiplicity_range {
+        unified.multiplicity_range =
+            Some(MultiplicityRange { bus_op: range.bus_op + offset, ..range });
+    }
+    unified.bus_ops.extend(bus.bus_ops);
+}
+
+/// Returns the column of the product holding each column of the aux segment of `factor`, given
+/// the merged set of buses of both programs, which are allocated one column each in order.
+fn factor_bus_columns(factor: &Air, bus_names: &BTreeSet<Identifier>) -> Vec<usize> {
+    let mut columns = vec![0; factor.bus_columns.len()];
+    for (name, column) in factor.bus_columns.iter() {
+        columns[*column] = bus_names.iter().position(|n| n == name).unwrap();
+    }
+    columns
+}
+
+/// Returns the public inputs of `air`, where those which are also declared by `other` are
+/// prefixed with the name of `air`.
+fn namespaced_public_inputs(air: &Air, other: &Air) -> BTreeMap<Identifier, PublicInput> {
+    air.public_inputs
+        .iter()
+        .map(|(name, input)| {
+            if !other.public_inputs.contains_key(name) {
+                return (*name, input.clone());
+            }
+            let namespaced =
+                Identifier::new(name.span(), Symbol::intern(format!("{}_{}", air.name, name)));
+            (*name, rename::with_name(input, namespaced))
+        })
+        .collect()
+}
+
+/// Copies the graphs of one of the factors of a product [Air] into the graphs of the product,
+/// rewriting the values which refer to the trace and public inputs along the way.
+struct FactorCopier<'a> {
+    /// Maps the original name of each public input of the factor to its declaration in the
+    /// product
+    public_inputs: &'a BTreeMap<Identifier, PublicInput>,
+    /// The index in the product of the first main trace column of the factor
+    main_offset: usize,
+    /// The index in the product of the aux trace column of each bus of the factor
+    bus_columns: Vec<usize>,
+    /// Maps each node of the factor graph, by index, to its copy in the product graph
+    rewrites: Vec<NodeIndex>,
+}
+impl FactorCopier<'_> {
+    /// Copies the nodes of `source`, which is the constraint graph of the factor or the graph of
+    /// its virtual columns, to `graph`, replacing the rewrites of any previous copy.
+    fn copy_graph(&mut self, source: &AlgebraicGraph, graph: &mut AlgebraicGraph) {
+        self.rewrites.clear();
+        // Nodes are always inserted after their operands, so visiting them in index order ensures
+        // the operands of a node have been copied before the node itself
+        for index in 0..source.num_nodes() {
+            let op = match *source.node(&NodeIndex::from(index)).op() {
+                Operation::Value(value) => Operation::Value(self.rewrite_value(value)),
+                Operation::Add(lhs, rhs) => Operation::Add(self.node(lhs), self.node(rhs)),
+                Operation::Sub(lhs, rhs) => Operation::Sub(self.node(lhs), self.node(rhs)),
+                Operation::Mul(lhs, rhs) => Operation::Mul(self.node(lhs), self.node(rhs)),
+            };
+            self.rewrites.push(graph.insert_node(op));
+        }
+    }
+
+    /// Returns the copy in the product graph of the factor node at `index`
+    fn node(&self, index: NodeIndex) -> NodeIndex {
+        self.rewrites[usize::from(index)]
+    }
+
+    fn rewrite(&self, root: &ConstraintRoot) -> ConstraintRoot {
+        ConstraintRoot::new(self.node(*root.node_index()), root.domain())
+    }
+
+    fn rewrite_value(&self, value: Value) -> Value {
+        match value {
+            Value::TraceAccess(access) => {
+                let column = match access.segment {
+                    DEFAULT_SEGMENT => access.column + self.main_offset,
+                    // The aux segment only holds bus columns, allocated in bus order
+                    AUX_SEGMENT => self.bus_columns[access.column],
+                    segment => unreachable!("invalid trace segment {segment}"),
+                };
+                Value::TraceAccess(TraceAccess { column, ..access })
+            },
+            Value::PublicInput(access) => Value::PublicInput(PublicInputAccess {
+                name: self.public_input(access.name),
+                ..access
+            }),
+            value @ (Value::Constant(_) | Value::PeriodicColumn(_) | Value::RandomValue(_)) => {
+                value
+            },
+        }
+    }
+
+    fn rewrite_bus_boundary(&self, boundary: &BusBoundary) -> BusBoundary {
+        match boundary {
+            BusBoundary::PublicInputTable(access) => {
+                BusBoundary::PublicInputTable(PublicInputTableAccess {
+                    table_name: self.public_input(access.table_name),
+                    ..*access
+                })
+            },
+            boundary => boundary.clone(),
+        }
+    }
+
+    /// Returns the name in the product of the public input of the factor named `name`
+    fn public_input(&self, name: Identifier) -> Identifier {
+        self.public_inputs.get(&name).map(|input| input.name()).unwrap_or(name)
+    }
+}
@@ -0,0 +1,53 @@
+use super::PublicInput;
+
+/// Describes the shape of a public input which must be supplied by a prover (and verifier) of an
+/// [super::Air].
+///
+/// Unlike [PublicInput], this type does not carry any source information, and is intended to be
+/// consumed outside of the compiler, e.g. to validate the public inputs supplied for proof
+/// construction, or to match them against the `PublicInputs` struct generated by a backend.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PublicInputSchema {
+    /// The name of the public input, as declared in the `public_inputs` section
+    pub name: String,
+    /// The kind of public input
+    pub kind: PublicInputKind,
+    /// For [PublicInputKind::Vector], the number of elements of the vector.
+    ///
+    /// For [PublicInputKind::Table], the number of elements in each row of the table.
+    pub size: usize,
+}
+impl PublicInputSchema {
+    /// Returns the number of field elements expected for this public input, given the number of
+    /// rows supplied for it.
+    ///
+    /// The number of rows is ignored for [PublicInputKind::Vector], as vectors have a fixed size.
+    pub fn num_elements(&self, num_rows: usize) -> usize {
+        match self.kind {
+            PublicInputKind::Vector => self.size,
+            PublicInputKind::Table => self.size * num_rows,
+        }
+    }
+}
+impl From<&PublicInput> for PublicInputSchema {
+    fn from(input: &PublicInput) -> Self {
+        let kind = match input {
+            PublicInput::Vector { .. } => PublicInputKind::Vector,
+            PublicInput::Table { .. } => PublicInputKind::Table,
+        };
+        Self {
+            name: input.name().to_string(),
+            kind,
+            size: input.size(),
+        }
+    }
+}
+
+/// The kind of a [PublicInputSchema]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PublicInputKind {
+    /// A vector of a fixed number of elements, e.g. `stack_inputs: [16]`
+    Vector,
+    /// A table with a fixed number of columns, and a variable number of rows, e.g. `inputs: [[4]]`
+    Table,
+}
@@ -0,0 +1,107 @@
+use super::*;
+
+/// The reasons why an item of an [Air] cannot be renamed, see [Air::rename_public_input] and
+/// [Air::rename_trace_segment].
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum RenameError {
+    #[error("public input '{0}' is not declared")]
+    UndefinedPublicInput(Identifier),
+    #[error("trace segment '{0}' is not declared")]
+    UndefinedTraceSegment(Identifier),
+    #[error("'{0}' is already the name of a public input, trace segment, or bus")]
+    NameConflict(Identifier),
+}
+
+impl Air {
+    /// Renames the public input `old` to `new`, e.g. to match the naming conventions of the
+    /// codebase into which the generated code is integrated.
+    ///
+    /// Every access to the public input is updated, so the constraints are unchanged. However,
+    /// since public inputs are ordered by name, renaming one may change the position at which it
+    /// is expected by the backends, see [Air::public_inputs].
+    pub fn rename_public_input(
+        &mut self,
+        old: Identifier,
+        new: Identifier,
+    ) -> Result<(), RenameError> {
+        if !self.public_inputs.contains_key(&old) {
+            return Err(RenameError::UndefinedPublicInput(old));
+        }
+        if old == new {
+            return Ok(());
+        }
+        self.check_unused_name(new)?;
+
+        let input = self.public_inputs.remove(&old).unwrap();
+        self.public_inputs.insert(new, with_name(&input, new));
+        let rename = |value| match value {
+            Value::PublicInput(access) if access.name == old => {
+                Value::PublicInput(PublicInputAccess { name: new, ..access })
+            },
+            value => value,
+        };
+        self.constraint_graph_mut().map_values(rename);
+        self.virtual_columns.graph.map_values(rename);
+        for bus in self.buses.values_mut() {
+            for boundary in [&mut bus.first, &mut bus.last] {
+                if let BusBoundary::PublicInputTable(access) = boundary
+                    && access.table_name == old
+                {
+                    access.table_name = new;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renames the trace segment `old` to `new`, e.g. `$main`, which is used to refer to the
+    /// segment in diagnostics.
+    ///
+    /// Trace columns are accessed by their index in a segment, so the constraints are unchanged.
+    pub fn rename_trace_segment(
+        &mut self,
+        old: Identifier,
+        new: Identifier,
+    ) -> Result<(), RenameError> {
+        let Some(segment) = self.trace_segment_names.iter().position(|name| *name == old) else {
+            return Err(RenameError::UndefinedTraceSegment(old));
+        };
+        if old == new {
+            return Ok(());
+        }
+        self.check_unused_name(new)?;
+
+        self.trace_segment_names[segment] = new;
+
+        Ok(())
+    }
+
+    /// Returns an error if `name` is already bound to a public input, trace segment, or bus, which
+    /// share the same namespace in AirScript.
+    fn check_unused_name(&self, name: Identifier) -> Result<(), RenameError> {
+        if self.public_inputs.contains_key(&name)
+            || self.trace_segment_names.contains(&name)
+            || self.buses.contains_key(&name)
+        {
+            return Err(RenameError::NameConflict(name));
+        }
+        Ok(())
+    }
+}
+
+/// Returns a copy of `input` named `name`.
+pub(super) fn with_name(input: &PublicInput, name: Identifier) -> PublicInput {
+    match input {
+        PublicInput::Vector { span, size, .. } => {
+            PublicInput::Vector { span: *span, name, size: *size }
+        },
+        PublicInput::Table { span, size, columns, commitment, .. } => PublicInput::Table {
+            span: *span,
+            name,
+            size: *size,
+            columns: columns.clone(),
+            commitment: *commitment,
+        },
+    }
+}
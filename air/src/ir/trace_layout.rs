@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+/// Describes the trace layout imposed by a target, which an [super::Air] must fit into.
+///
+/// See [crate::passes::ValidateTraceLayout] for how an [super::Air] is validated against it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TraceLayoutSpec {
+    /// The number of columns of the main trace of the target
+    pub main_width: usize,
+    /// The maximum number of columns of the aux trace, i.e. the maximum number of buses
+    pub aux_width_max: usize,
+    /// The maximum number of random values which can be drawn to build the aux trace
+    pub num_random_max: usize,
+    /// The number of columns of the main trace which are reserved by the target, and therefore
+    /// cannot be declared by the program
+    pub reserved_columns: usize,
+}
+impl TraceLayoutSpec {
+    /// Returns the number of columns of the main trace which can be declared by the program
+    pub fn available_main_width(&self) -> usize {
+        self.main_width.saturating_sub(self.reserved_columns)
+    }
+}
+
+/// A registry of named [TraceLayoutSpec] presets.
+///
+/// The default registry is empty, downstream users register the layouts of their targets with
+/// [TraceLayoutRegistry::register], and select one by name as [crate::PassConfig::target].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TraceLayoutRegistry {
+    presets: BTreeMap<String, TraceLayoutSpec>,
+}
+impl TraceLayoutRegistry {
+    /// Registers `spec` under `name`, returning the preset previously registered under that name,
+    /// if any.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        spec: TraceLayoutSpec,
+    ) -> Option<TraceLayoutSpec> {
+        self.presets.insert(name.into(), spec)
+    }
+
+    /// Returns the preset registered under `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&TraceLayoutSpec> {
+        self.presets.get(name)
+    }
+
+    /// Returns an iterator over the names and specs of all registered presets, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TraceLayoutSpec)> {
+        self.presets.iter().map(|(name, spec)| (name.as_str(), spec))
+    }
+}
@@ -0,0 +1,74 @@
+use core::fmt;
+
+use super::*;
+
+/// The number of rows at the end of the trace on which integrity constraints are not enforced,
+/// matching the exemptions declared by the backends.
+const NUM_TRANSITION_EXEMPTIONS: u32 = 2;
+
+/// The requirements on the length of the trace over which an [Air] can be proven, see
+/// [Air::trace_length_constraints].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceLengthBounds {
+    /// The base-2 logarithm of the minimum trace length
+    pub min_log2: u32,
+    /// Whether the trace length must be a power of two
+    pub must_be_power_of_two: bool,
+    /// The features of the program from which these bounds derive, in a human-readable form
+    pub reasons: Vec<String>,
+}
+impl TraceLengthBounds {
+    /// Returns the minimum trace length, i.e. `2^min_log2`
+    pub fn min_len(&self) -> u64 {
+        1 << self.min_log2
+    }
+}
+impl fmt::Display for TraceLengthBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the trace length must be ")?;
+        if self.must_be_power_of_two {
+            write!(f, "a power of two, ")?;
+        }
+        write!(f, "at least 2^{} ({} rows)", self.min_log2, self.min_len())?;
+        for reason in self.reasons.iter() {
+            write!(f, "\n  - {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Air {
+    /// Returns the bounds on the trace length implied by this program, which all backends must
+    /// enforce before proving or verifying it.
+    ///
+    /// The trace length is always a power of two, since the trace is interpolated over a
+    /// multiplicative subgroup of the field. Integrity constraints are not enforced on the last
+    /// rows of the trace, which must leave the first row constrained, and each periodic column
+    /// must repeat a whole number of times over the trace.
+    pub fn trace_length_constraints(&self) -> TraceLengthBounds {
+        // The smallest power of two greater than the number of exempted rows
+        let mut min_log2 = NUM_TRANSITION_EXEMPTIONS.ilog2() + 1;
+        let mut reasons = vec![
+            "the trace is interpolated over a multiplicative subgroup, whose size is a power of two"
+                .to_string(),
+            format!(
+                "integrity constraints are not enforced on the last {NUM_TRANSITION_EXEMPTIONS} \
+                 rows of the trace"
+            ),
+        ];
+        for column in self.periodic_columns() {
+            let cycle_len = column.period();
+            reasons.push(format!(
+                "periodic column '{}' has a cycle of length {cycle_len}",
+                column.name
+            ));
+            min_log2 = min_log2.max(cycle_len.next_power_of_two().ilog2());
+        }
+
+        TraceLengthBounds {
+            min_log2,
+            must_be_power_of_two: true,
+            reasons,
+        }
+    }
+}
@@ -25,10 +25,12 @@ pub enum Value {
 pub struct PeriodicColumnAccess {
     pub name: QualifiedIdentifier,
     pub cycle: usize,
+    /// The number of rows after the current row at which the column is read, e.g. 1 for `k'`
+    pub row_offset: usize,
 }
 impl PeriodicColumnAccess {
-    pub const fn new(name: QualifiedIdentifier, cycle: usize) -> Self {
-        Self { name, cycle }
+    pub const fn new(name: QualifiedIdentifier, cycle: usize, row_offset: usize) -> Self {
+        Self { name, cycle, row_offset }
     }
 }
 
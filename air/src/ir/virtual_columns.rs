@@ -0,0 +1,74 @@
+use super::*;
+use crate::graph::NodeIndex;
+
+/// The definitions of the columns of the main trace declared virtual, e.g. `@virtual(a * b) c`,
+/// whose value in every row is a pure function of other columns.
+///
+/// The definitions are not constraints, so they are kept in their own [AlgebraicGraph]. They are
+/// only used to report the degrees the constraints would have if each virtual column was replaced
+/// by its definition, see [Air::integrity_constraint_degrees_with_virtual_columns].
+#[derive(Debug, Default, Clone)]
+pub struct VirtualColumns {
+    /// The graph of the definitions
+    pub(crate) graph: AlgebraicGraph,
+    /// The name and the definition of each virtual column, by index in the main trace
+    definitions: BTreeMap<usize, (Identifier, NodeIndex)>,
+}
+impl VirtualColumns {
+    /// Creates a new set of virtual columns from the graph of their definitions, and the name and
+    /// the definition of each of them, by index in the main trace.
+    ///
+    /// A virtual column may only depend on the virtual columns declared before it, so that
+    /// substituting the definitions terminates.
+    pub fn new(
+        graph: AlgebraicGraph,
+        definitions: BTreeMap<usize, (Identifier, NodeIndex)>,
+    ) -> Self {
+        Self { graph, definitions }
+    }
+
+    /// Returns true if there are no virtual columns
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// Returns the graph of the definitions
+    pub fn graph(&self) -> &AlgebraicGraph {
+        &self.graph
+    }
+
+    /// Returns an iterator over the index in the main trace, the name, and the definition of each
+    /// virtual column, in column order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Identifier, NodeIndex)> + '_ {
+        self.definitions
+            .iter()
+            .map(|(column, (name, definition))| (*column, *name, *definition))
+    }
+
+    /// Returns the name and the definition of the virtual column at index `column` of the main
+    /// trace, if any.
+    pub fn get(&self, column: usize) -> Option<(Identifier, NodeIndex)> {
+        self.definitions.get(&column).copied()
+    }
+
+    /// Returns the degree of the subgraph of `graph` which has the specified node as its tip, with
+    /// each access to a virtual column replaced by its definition.
+    pub fn degree(&self, graph: &AlgebraicGraph, index: &NodeIndex) -> IntegrityConstraintDegree {
+        graph.degree_with(index, &|cycles, access| self.trace_access_degree(cycles, access))
+    }
+
+    fn trace_access_degree(
+        &self,
+        cycles: &mut BTreeMap<QualifiedIdentifier, usize>,
+        access: &TraceAccess,
+    ) -> usize {
+        match self.get(access.column) {
+            Some((_, definition)) if access.segment == DEFAULT_SEGMENT => {
+                self.graph.accumulate_degree_with(cycles, &definition, &|cycles, access| {
+                    self.trace_access_degree(cycles, access)
+                })
+            },
+            _ => 1,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use super::*;
+
+/// What an input of the constraint system of an [Air] stands for, see [WitnessLayout].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WitnessInput {
+    /// An element of a public input
+    PublicInput(PublicInputAccess),
+    /// The random value drawn by the verifier at this index, from which the aux trace is built
+    RandomValue(usize),
+    /// A cell of the main or aux trace, in the current or next row
+    Trace(TraceAccess),
+}
+
+/// A contiguous group of inputs of a [WitnessLayout]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WitnessRegion {
+    /// The elements of the public input `name`, or of a single row of it for a table
+    PublicInput { name: Identifier, size: usize },
+    /// The random values drawn by the verifier
+    RandomValues { count: usize },
+    /// The cells of a trace segment in the row at `row_offset`
+    Trace {
+        segment: TraceSegmentId,
+        row_offset: usize,
+        width: usize,
+    },
+}
+impl WitnessRegion {
+    /// Returns the number of inputs in this region
+    pub fn width(&self) -> usize {
+        match *self {
+            Self::PublicInput { size, .. } => size,
+            Self::RandomValues { count } => count,
+            Self::Trace { width, .. } => width,
+        }
+    }
+
+    /// Returns the input at `index` in this region, if it is within bounds.
+    pub fn input(&self, index: usize) -> Option<WitnessInput> {
+        if index >= self.width() {
+            return None;
+        }
+        Some(match *self {
+            Self::PublicInput { name, .. } => {
+                WitnessInput::PublicInput(PublicInputAccess::new(name, index))
+            },
+            Self::RandomValues { .. } => WitnessInput::RandomValue(index),
+            Self::Trace { segment, row_offset, .. } => {
+                WitnessInput::Trace(TraceAccess::new(segment, index, row_offset))
+            },
+        })
+    }
+
+    /// Returns an iterator over the inputs of this region, in order.
+    pub fn inputs(&self) -> impl Iterator<Item = WitnessInput> + '_ {
+        (0..self.width()).filter_map(|index| self.input(index))
+    }
+}
+
+/// Describes every input of the constraint system of an [Air], i.e. the values a witness generator
+/// must supply to evaluate its constraints in a single row, and their order.
+///
+/// The inputs are grouped in [WitnessRegion]s, in the following order:
+/// - the public inputs, sorted by name as in [Air::public_inputs],
+/// - the random values,
+/// - the main and aux segments of the current row,
+/// - the main and aux segments of the next row.
+///
+/// The aux segment is always present, and is empty if the [Air] has none. This is the order in
+/// which the ACE backend lays out the inputs of its circuits, which additionally aligns each region
+/// and inserts the evaluations of the quotient and the STARK variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessLayout {
+    regions: Vec<WitnessRegion>,
+}
+impl WitnessLayout {
+    /// Returns the regions of this layout, in order
+    pub fn regions(&self) -> &[WitnessRegion] {
+        &self.regions
+    }
+
+    /// Returns an iterator over all the inputs of this layout, in order.
+    pub fn inputs(&self) -> impl Iterator<Item = WitnessInput> + '_ {
+        self.regions.iter().flat_map(|region| region.inputs())
+    }
+
+    /// Returns the total number of inputs of this layout
+    pub fn num_inputs(&self) -> usize {
+        self.regions.iter().map(WitnessRegion::width).sum()
+    }
+
+    /// Returns the index of `input` among all the inputs of this layout, or `None` if the
+    /// constraint system has no such input.
+    pub fn position(&self, input: &WitnessInput) -> Option<usize> {
+        let mut offset = 0;
+        for region in self.regions.iter() {
+            let index = match (*region, *input) {
+                (WitnessRegion::PublicInput { name, .. }, WitnessInput::PublicInput(access))
+                    if access.name == name =>
+                {
+                    Some(access.index)
+                },
+                (WitnessRegion::RandomValues { .. }, WitnessInput::RandomValue(index)) => {
+                    Some(index)
+                },
+                (WitnessRegion::Trace { segment, row_offset, .. }, WitnessInput::Trace(access))
+                    if access.segment == segment && access.row_offset == row_offset =>
+                {
+                    Some(access.column)
+                },
+                _ => None,
+            };
+            if let Some(index) = index {
+                return (index < region.width()).then_some(offset + index);
+            }
+            offset += region.width();
+        }
+        None
+    }
+}
+
+impl Air {
+    /// Returns the [WitnessLayout] of this program, which enumerates every input of its constraint
+    /// system along with its meaning, independently of any backend.
+    pub fn witness_layout(&self) -> WitnessLayout {
+        let mut regions: Vec<_> = self
+            .public_inputs()
+            .map(|input| WitnessRegion::PublicInput { name: input.name(), size: input.size() })
+            .collect();
+        regions.push(WitnessRegion::RandomValues { count: self.num_random_values as usize });
+        for row_offset in [CURRENT_ROW, CURRENT_ROW + 1] {
+            for segment in [DEFAULT_SEGMENT, AUX_SEGMENT] {
+                let width = self.trace_segment_widths.get(segment).copied().unwrap_or(0);
+                regions.push(WitnessRegion::Trace {
+                    segment,
+                    row_offset,
+                    width: width as usize,
+                });
+            }
+        }
+        WitnessLayout { regions }
+    }
+}
@@ -1,16 +1,24 @@
 mod codegen;
+mod debug;
+mod diff;
+mod eval;
 mod graph;
 mod ir;
 pub mod passes;
+mod pipeline;
 #[cfg(test)]
 mod tests;
 
 use miden_diagnostics::{Diagnostic, ToDiagnostic};
 
 pub use self::{
-    codegen::CodeGenerator,
+    codegen::{CodeGenerator, Provenance},
+    debug::{Violation, debug_trace, violation_table},
+    diff::{AirDiff, BusSummary, Change, ConstraintChange, diff},
+    eval::{ConstraintEvaluator, EvalContext, QuadFelt, RootKind},
     graph::{AlgebraicGraph, Node, NodeIndex},
     ir::*,
+    pipeline::{PassConfig, UnrollLimits, compile, compile_with_repro},
 };
 
 #[derive(Debug, thiserror::Error)]
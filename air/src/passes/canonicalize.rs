@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use air_parser::ast::{FIELD_MODULUS, field_neg};
+use air_pass::Pass;
+use miden_diagnostics::DiagnosticsHandler;
+
+use crate::{Air, AlgebraicGraph, CompileError, ConstraintRoot, NodeIndex, Operation, Value};
+
+/// The constant `-1` in the field
+const NEG_ONE: u64 = FIELD_MODULUS - 1;
+
+/// This pass rewrites the constraint graph of an [Air] into a canonical form, so that equivalent
+/// expressions written differently are more likely to be represented by the same nodes.
+///
+/// The following rewrites are performed:
+///
+/// * `a - b` is rewritten as `a + (-1 * b)`, so that the graph only contains additions and
+///   multiplications. The negation of a constant is folded into a constant, and the negation of a
+///   negation is simplified away.
+/// * `a + 0` and `0 + a` are simplified to `a`.
+/// * The operands of additions and multiplications, which are commutative, are ordered by node
+///   index, so that e.g. `a + b` and `b + a` share the same node.
+///
+/// The top-level operation of a boundary constraint root is preserved, since backends expect those
+/// to be of the form `column - value`: only its operands are canonicalized.
+///
+/// The graph is rebuilt from the constraint roots and bus operations, so nodes which are not
+/// reachable from any of them are dropped. This pass is semantics-preserving: every constraint root evaluates to the same
+/// value before and after it is run.
+pub struct Canonicalize<'a> {
+    #[allow(unused)]
+    diagnostics: &'a DiagnosticsHandler,
+}
+impl<'a> Canonicalize<'a> {
+    /// Create a new instance of this pass
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+        Self { diagnostics }
+    }
+}
+impl Pass for Canonicalize<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, mut ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        let source = core::mem::take(ir.constraint_graph_mut());
+        let mut builder = CanonicalGraphBuilder::new(&source);
+        ir.constraints.rewrite_roots(|root| builder.canonicalize_root(root));
+        for bus_op in ir.buses.values_mut().flat_map(|bus| bus.bus_ops.iter_mut()) {
+            for column in bus_op.columns.iter_mut() {
+                *column = builder.canonicalize(column);
+            }
+            bus_op.latch = builder.canonicalize(&bus_op.latch);
+        }
+        *ir.constraint_graph_mut() = builder.finish();
+
+        Ok(ir)
+    }
+}
+
+/// Builds the canonical form of a source [AlgebraicGraph] into a new graph, one subgraph at a time.
+struct CanonicalGraphBuilder<'a> {
+    source: &'a AlgebraicGraph,
+    graph: AlgebraicGraph,
+    /// Maps nodes of the source graph to their canonical form in the new graph
+    rewrites: BTreeMap<NodeIndex, NodeIndex>,
+}
+impl<'a> CanonicalGraphBuilder<'a> {
+    fn new(source: &'a AlgebraicGraph) -> Self {
+        Self {
+            source,
+            graph: AlgebraicGraph::default(),
+            rewrites: BTreeMap::default(),
+        }
+    }
+
+    fn finish(self) -> AlgebraicGraph {
+        self.graph
+    }
+
+    /// Returns the index in the new graph of the canonical form of the constraint `root`.
+    fn canonicalize_root(&mut self, root: &ConstraintRoot) -> NodeIndex {
+        let index = root.node_index();
+        if !root.domain().is_boundary() {
+            return self.canonicalize(index);
+        }
+
+        // Only canonicalize the operands of the root
+        match *self.source.node(index).op() {
+            Operation::Value(_) => self.canonicalize(index),
+            Operation::Add(lhs, rhs) => {
+                let (lhs, rhs) = (self.canonicalize(&lhs), self.canonicalize(&rhs));
+                self.graph.insert_node(Operation::Add(lhs, rhs))
+            },
+            Operation::Sub(lhs, rhs) => {
+                let (lhs, rhs) = (self.canonicalize(&lhs), self.canonicalize(&rhs));
+                self.graph.insert_node(Operation::Sub(lhs, rhs))
+            },
+            Operation::Mul(lhs, rhs) => {
+                let (lhs, rhs) = (self.canonicalize(&lhs), self.canonicalize(&rhs));
+                self.graph.insert_node(Operation::Mul(lhs, rhs))
+            },
+        }
+    }
+
+    /// Returns the index in the new graph of the canonical form of the source node at `index`.
+    fn canonicalize(&mut self, index: &NodeIndex) -> NodeIndex {
+        if let Some(rewritten) = self.rewrites.get(index) {
+            return *rewritten;
+        }
+
+        let rewritten = match *self.source.node(index).op() {
+            Operation::Value(value) => self.graph.insert_node(Operation::Value(value)),
+            Operation::Add(lhs, rhs) => {
+                let lhs = self.canonicalize(&lhs);
+                let rhs = self.canonicalize(&rhs);
+                self.insert_add(lhs, rhs)
+            },
+            Operation::Sub(lhs, rhs) => {
+                let lhs = self.canonicalize(&lhs);
+                let rhs = self.canonicalize(&rhs);
+                let neg_rhs = self.negate(rhs);
+                self.insert_add(lhs, neg_rhs)
+            },
+            Operation::Mul(lhs, rhs) => {
+                let lhs = self.canonicalize(&lhs);
+                let rhs = self.canonicalize(&rhs);
+                self.insert_commutative(Operation::Mul, lhs, rhs)
+            },
+        };
+        self.rewrites.insert(*index, rewritten);
+        rewritten
+    }
+
+    /// Inserts the negation of the node at `index` of the new graph.
+    fn negate(&mut self, index: NodeIndex) -> NodeIndex {
+        match *self.graph.node(&index).op() {
+            // -c is folded into a constant
+            Operation::Value(Value::Constant(c)) => {
+                self.graph.insert_node(Operation::Value(Value::Constant(field_neg(c))))
+            },
+            // -(-1 * x) is simplified to x
+            Operation::Mul(lhs, rhs) if self.is_neg_one(&lhs) => rhs,
+            Operation::Mul(lhs, rhs) if self.is_neg_one(&rhs) => lhs,
+            _ => {
+                let neg_one = self.graph.insert_node(Operation::Value(Value::Constant(NEG_ONE)));
+                self.insert_commutative(Operation::Mul, neg_one, index)
+            },
+        }
+    }
+
+    /// Inserts the sum of the nodes at `lhs` and `rhs` of the new graph, omitting zero operands.
+    fn insert_add(&mut self, lhs: NodeIndex, rhs: NodeIndex) -> NodeIndex {
+        if self.is_constant(&lhs, 0) {
+            rhs
+        } else if self.is_constant(&rhs, 0) {
+            lhs
+        } else {
+            self.insert_commutative(Operation::Add, lhs, rhs)
+        }
+    }
+
+    /// Inserts a commutative operation, with its operands ordered by node index.
+    fn insert_commutative(
+        &mut self,
+        op: fn(NodeIndex, NodeIndex) -> Operation,
+        lhs: NodeIndex,
+        rhs: NodeIndex,
+    ) -> NodeIndex {
+        let (lhs, rhs) = if lhs <= rhs { (lhs, rhs) } else { (rhs, lhs) };
+        self.graph.insert_node(op(lhs, rhs))
+    }
+
+    fn is_neg_one(&self, index: &NodeIndex) -> bool {
+        self.is_constant(index, NEG_ONE)
+    }
+
+    fn is_constant(&self, index: &NodeIndex, value: u64) -> bool {
+        matches!(self.graph.node(index).op(), Operation::Value(Value::Constant(c)) if *c == value)
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsHandler, Severity};
+
+use crate::{Air, CompileError, NodeIndex, Operation, TraceSegmentId, Value};
+
+/// A trace column, by segment and index within the segment
+type Column = (TraceSegmentId, usize);
+
+/// This lint warns about the trace columns which are copies of an expression of the previous row,
+/// and could be eliminated by substituting that expression.
+///
+/// A column `b` is a copy column if an integrity constraint is exactly of the form `b' = e`, where
+/// `e` does not read `b`, and is enforced without selector. Then every read of `b'` can be
+/// replaced by `e`, which saves one column and one constraint. However, reads of `b` in the
+/// current row cannot be rewritten in terms of the previous row, and neither can boundary
+/// constraints on `b`, so such a column is reported as only partially eliminable.
+///
+/// This lint is opt-in, as copy columns are sometimes introduced on purpose, e.g. to lower the
+/// degree of the constraints reading them.
+pub struct CopyColumns<'a> {
+    diagnostics: &'a DiagnosticsHandler,
+}
+impl<'a> CopyColumns<'a> {
+    /// Create a new instance of this pass
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+        Self { diagnostics }
+    }
+}
+impl Pass for CopyColumns<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        // The first copy constraint defining each column
+        let mut copies = BTreeMap::<Column, (TraceSegmentId, usize, NodeIndex)>::new();
+        for segment in 0..ir.trace_segment_widths.len() {
+            for (i, root) in ir.integrity_constraints(segment).iter().enumerate() {
+                if let Some((column, expr)) = copy_column(&ir, root.node_index()) {
+                    copies.entry(column).or_insert((segment, i, expr));
+                }
+            }
+        }
+
+        for (column, (segment, index, expr)) in copies {
+            let root = *ir.integrity_constraints(segment)[index].node_index();
+            let current_row_reads = ir
+                .all_constraints()
+                .filter(|constraint| constraint.root != root)
+                .filter(|constraint| {
+                    ir.constraint_graph().trace_accesses(&constraint.root).iter().any(|access| {
+                        (access.segment, access.column) == column && access.row_offset == 0
+                    })
+                })
+                .count();
+            self.warn(&ir, column, segment, index, expr, current_row_reads);
+        }
+
+        Ok(ir)
+    }
+}
+impl CopyColumns<'_> {
+    fn warn(
+        &self,
+        ir: &Air,
+        (column_segment, column): Column,
+        segment: TraceSegmentId,
+        index: usize,
+        expr: NodeIndex,
+        current_row_reads: usize,
+    ) {
+        let name = ir.trace_column_name(column_segment, column);
+        let segment_name = match segment {
+            0 => "main",
+            _ => "aux",
+        };
+        let expr = ir.constraint_graph().to_infix(&expr).expect("failed to render expression");
+        let diagnostic = self.diagnostics.diagnostic(Severity::Warning);
+        let mut diagnostic = if current_row_reads == 0 {
+            diagnostic
+                .with_message(format!(
+                    "column `{name}` is a copy of `{expr}` from the previous row, and could be eliminated"
+                ))
+                .with_note(format!(
+                    "Integrity constraint #{index} against the {segment_name} trace only enforces `{name}' = {expr}`, so `{name}` is never read in the current row, and its reads in the next row can be replaced by `{expr}`."
+                ))
+                .with_note("Eliminating the column would save one column and one constraint.")
+        } else {
+            diagnostic
+                .with_message(format!(
+                    "column `{name}` is a copy of `{expr}` from the previous row, and is partially eliminable"
+                ))
+                .with_note(format!(
+                    "Integrity constraint #{index} against the {segment_name} trace only enforces `{name}' = {expr}`, so the reads of `{name}` in the next row can be replaced by `{expr}`."
+                ))
+                .with_note(format!(
+                    "However, {current_row_reads} other constraint(s) read `{name}` in the current row, which cannot be rewritten in terms of the previous row. Eliminating the column, to save one column and one constraint, requires rewriting them first."
+                ))
+        };
+        let root = &ir.integrity_constraints(segment)[index];
+        if let Some(provenance) = ir.constraint_provenance(root) {
+            diagnostic = provenance.label(diagnostic, "this constraint copies the column");
+        }
+        diagnostic.emit();
+    }
+}
+
+/// Returns the column copied by the integrity constraint rooted at `index`, and the expression it
+/// is a copy of, if the constraint is of the form `b' - e` or `e - b'`, where `e` does not read
+/// `b`.
+fn copy_column(ir: &Air, index: &NodeIndex) -> Option<(Column, NodeIndex)> {
+    let graph = ir.constraint_graph();
+    let Operation::Sub(lhs, rhs) = graph.node(index).op() else {
+        return None;
+    };
+    [(lhs, rhs), (rhs, lhs)]
+        .into_iter()
+        .find_map(|(copy, expr)| match graph.node(copy).op() {
+            Operation::Value(Value::TraceAccess(access))
+                if access.row_offset == 1
+                    && !graph.trace_accesses(expr).iter().any(|read| {
+                        read.segment == access.segment && read.column == access.column
+                    }) =>
+            {
+                Some(((access.segment, access.column), *expr))
+            },
+            _ => None,
+        })
+}
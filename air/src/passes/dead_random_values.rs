@@ -0,0 +1,132 @@
+use std::collections::BTreeSet;
+
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsHandler, Severity};
+
+use crate::{Air, BusBoundary, CompileError, Operation, Value};
+
+/// This pass removes the random values which no constraint depends on, and renumbers the others
+/// densely, so that the verifier draws no more random values than the constraints need.
+///
+/// The random values are drawn for the buses, one more than the widest tuple of their operations.
+/// Some of them may end up unused once the graph is simplified, e.g. `p.insert(a, 0) when s` only
+/// depends on the first two. The used random values keep their relative order, and
+/// [Air::num_random_values] is lowered to their count, which in turn shrinks the layouts derived
+/// from it, see [Air::witness_layout]. A note lists the removed indices.
+///
+/// The random values combining the rows of a public input table bound to a bus boundary are
+/// always kept, as backends combine the table with them outside of the constraint graph. Since the
+/// random values are only drawn once the buses are expanded, programs sharing buses must be linked
+/// before this pass is run, see [Air::link].
+///
+/// This pass is disabled by default, as the prover must build the aux trace from the renumbered
+/// random values. It is never run on programs exporting their random values, see
+/// [crate::PassConfig::export_random_values].
+pub struct DeadRandomValues<'a> {
+    diagnostics: &'a DiagnosticsHandler,
+}
+impl<'a> DeadRandomValues<'a> {
+    /// Create a new instance of this pass
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+        Self { diagnostics }
+    }
+}
+impl Pass for DeadRandomValues<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, mut ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        let num_random_values = ir.num_random_values as usize;
+        let used = used_random_values(&ir);
+        let (mut order, removed): (Vec<_>, Vec<_>) =
+            (0..num_random_values).partition(|index| used.contains(index));
+        if removed.is_empty() {
+            return Ok(ir);
+        }
+
+        // The used random values come first, in order, so that the renumbering is injective
+        let num_used = order.len();
+        order.extend(removed.iter().copied());
+        let renumber = |value| match value {
+            Value::RandomValue(index) if index < num_random_values => {
+                Value::RandomValue(order.iter().position(|i| *i == index).unwrap())
+            },
+            value => value,
+        };
+        ir.constraint_graph_mut().map_values(renumber);
+        ir.virtual_columns.graph.map_values(renumber);
+        ir.num_random_values = num_used as u16;
+
+        self.note(&ir, &removed, num_random_values);
+
+        Ok(ir)
+    }
+}
+impl DeadRandomValues<'_> {
+    fn note(&self, ir: &Air, removed: &[usize], num_random_values: usize) {
+        let unused = match removed {
+            [index] => format!("The random value at index {index} is"),
+            _ => {
+                let indices = removed.iter().map(usize::to_string).collect::<Vec<_>>();
+                format!("The random values at indices {} are", indices.join(", "))
+            },
+        };
+        let diagnostic = self
+            .diagnostics
+            .diagnostic(Severity::Note)
+            .with_message(format!(
+                "{} of the {num_random_values} random values are unused, and were removed",
+                removed.len()
+            ))
+            .with_note(format!(
+                "{unused} not read by any constraint. The others are renumbered in order, so that \
+                 only {} are drawn.",
+                ir.num_random_values
+            ));
+        // The random values are declared by the buses section, as they are drawn for its buses
+        match ir.buses_span {
+            Some(span) => diagnostic
+                .with_primary_label(
+                    span,
+                    format!("{num_random_values} random values are declared for these buses"),
+                )
+                .emit(),
+            None => diagnostic.emit(),
+        }
+    }
+}
+
+/// Returns the indices of the random values read by the constraints of `ir`, by the operations of
+/// its expanded buses, or by the public input tables bound to the boundaries of its buses.
+fn used_random_values(ir: &Air) -> BTreeSet<usize> {
+    let graph = ir.constraint_graph();
+    let mut visited = vec![false; graph.num_nodes()];
+    let mut order = vec![];
+    let roots = ir.all_constraints().map(|constraint| constraint.root);
+    let bus_ops = ir
+        .expanded_buses()
+        .iter()
+        .flat_map(|bus| bus.ops.iter().flat_map(|op| [op.args_combined, op.latch]));
+    for root in roots.chain(bus_ops) {
+        graph.extend_topological_order(root, &mut visited, &mut order);
+    }
+    let mut used: BTreeSet<_> = order
+        .iter()
+        .filter_map(|index| match graph.node(index).op() {
+            Operation::Value(Value::RandomValue(index)) => Some(*index),
+            _ => None,
+        })
+        .collect();
+
+    // A table is combined with the first random values, as the tuples of the bus operations
+    for bus in ir.buses.values() {
+        for boundary in [&bus.first, &bus.last] {
+            if let BusBoundary::PublicInputTable(access) = boundary {
+                used.extend(0..=access.num_cols);
+            }
+        }
+    }
+    used
+}
@@ -0,0 +1,44 @@
+use std::collections::BTreeSet;
+
+use air_pass::Pass;
+use miden_diagnostics::DiagnosticsHandler;
+
+use crate::{Air, CompileError};
+
+/// This pass removes integrity constraints which are identical to an earlier integrity constraint
+/// against the same trace segment, i.e. which share both its root node and its domain.
+///
+/// Such duplicates typically arise from unrolling comprehensions over symmetric iterables, e.g.
+/// `enf a[i] * a[j] = 0 for (i, j) in ...` enumerating both `(0, 1)` and `(1, 0)`. Duplicates only
+/// share a root once their commutative operands have been ordered, so this pass is most effective
+/// after [super::Canonicalize].
+///
+/// The first occurrence of each constraint is kept, so the relative order of the remaining
+/// constraints is preserved.
+pub struct DuplicateConstraints<'a> {
+    #[allow(unused)]
+    diagnostics: &'a DiagnosticsHandler,
+}
+impl<'a> DuplicateConstraints<'a> {
+    /// Create a new instance of this pass
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+        Self { diagnostics }
+    }
+}
+impl Pass for DuplicateConstraints<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, mut ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        for segment in 0..ir.trace_segment_widths.len() {
+            let mut seen = BTreeSet::new();
+            ir.constraints.retain_integrity_constraints(segment, |root| {
+                seen.insert((*root.node_index(), root.domain()))
+            });
+        }
+
+        Ok(ir)
+    }
+}
@@ -1,15 +1,14 @@
 use air_parser::ast::{Boundary, BusType};
 use air_pass::Pass;
-use miden_diagnostics::DiagnosticsHandler;
+use miden_diagnostics::{DiagnosticsHandler, Severity, Spanned};
 use mir::ir::BusOpKind;
 
 use crate::{
-    AUX_SEGMENT, Air, BusBoundary, BusOp, CompileError, ConstraintDomain, NodeIndex, Operation,
-    TraceAccess,
+    AUX_SEGMENT, Air, BusBoundary, BusOp, CompileError, ConstraintDomain, DEFAULT_SEGMENT,
+    ExpandedBus, ExpandedBusOp, MultiplicityRange, NodeIndex, Operation, TraceAccess,
 };
 
 pub struct BusOpExpand<'a> {
-    #[allow(unused)]
     diagnostics: &'a DiagnosticsHandler,
 }
 
@@ -21,6 +20,25 @@ impl Pass for BusOpExpand<'_> {
     fn run<'a>(&mut self, mut ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
         let buses = ir.buses.clone();
 
+        let mut unresolved = false;
+        for bus in buses.values().filter(|bus| bus.is_extern) {
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("unresolved extern bus")
+                .with_primary_label(
+                    bus.name.span(),
+                    "this bus is declared extern, but is not defined by any linked program",
+                )
+                .with_note(
+                    "Link this program with the one defining the bus, with `Air::link`, before expanding its buses.",
+                )
+                .emit();
+            unresolved = true;
+        }
+        if unresolved {
+            return Err(CompileError::Failed);
+        }
+
         for (bus_index, (_ident, bus)) in buses.iter().enumerate() {
             let bus_type = bus.bus_type;
 
@@ -54,32 +72,63 @@ impl Pass for BusOpExpand<'_> {
                         bus_trace_access_with_offset,
                     )));
 
-            // Then, depending on the bus type, expand the integrity constraint
-            match bus_type {
-                BusType::Multiset => {
-                    self.expand_multiset_constraint(
-                        &mut ir,
-                        bus_ops,
-                        bus_access,
-                        bus_access_with_offset,
-                    );
-                },
-                BusType::Logup => {
-                    self.expand_logup_constraint(
-                        &mut ir,
-                        bus_ops,
-                        bus_access,
-                        bus_access_with_offset,
-                    );
-                },
+            if let Some(range) = bus.multiplicity_range.as_ref() {
+                self.expand_multiplicity_range(&mut ir, range, bus_ops[range.bus_op].latch)?;
             }
+
+            // Then, depending on the bus type, expand the integrity constraint
+            let (root, ops) = match bus_type {
+                BusType::Multiset => self.expand_multiset_constraint(
+                    &mut ir,
+                    bus_ops,
+                    bus_access,
+                    bus_access_with_offset,
+                ),
+                BusType::Logup => self.expand_logup_constraint(
+                    &mut ir,
+                    bus_ops,
+                    bus_access,
+                    bus_access_with_offset,
+                ),
+            };
+
+            // Keep the operations of the bus apart, so that backends need not evaluate the
+            // monolithic constraint
+            ir.constraints.insert_expanded_bus(ExpandedBus {
+                name: bus.name,
+                bus_type,
+                root,
+                column: bus_access,
+                column_next: bus_access_with_offset,
+                ops,
+            });
         }
 
-        ir.num_random_values = buses
+        let widest_bus = buses
             .values()
-            .map(|bus| bus.bus_ops.iter().map(|a| a.columns.len() + 1).max().unwrap_or_default())
-            .max()
-            .unwrap_or_default() as u16;
+            .map(|bus| {
+                let num_random_values =
+                    bus.bus_ops.iter().map(|a| a.columns.len() + 1).max().unwrap_or_default();
+                (bus, num_random_values)
+            })
+            .max_by_key(|(_, num_random_values)| *num_random_values);
+        let num_random_values = widest_bus.map_or(0, |(_, num_random_values)| num_random_values);
+        ir.num_random_values = u16::try_from(num_random_values).map_err(|_| {
+            // Only a bus can require more random values than fit in a u16
+            let (bus, _) = widest_bus.unwrap();
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("bus operation is too wide")
+                .with_primary_label(
+                    bus.name.span(),
+                    format!(
+                        "this bus requires {num_random_values} random values, but at most {} are supported",
+                        u16::MAX
+                    ),
+                )
+                .emit();
+            CompileError::Failed
+        })?;
 
         Ok(ir)
     }
@@ -137,16 +186,60 @@ impl<'a> BusOpExpand<'a> {
         }
     }
 
-    /// Helper function to expand the integrity constraint of a multiset bus
+    /// Helper function to enforce the range check on the multiplicity of a bus operation, whose
+    /// effective multiplicity is `latch`
+    ///
+    /// Example:
+    /// logup q with bits
+    /// q.insert(a) when s with m
+    /// => bits[i] * bits[i] - bits[i] = 0 for each i, and s * m - sum(2^i * bits[i]) = 0
+    fn expand_multiplicity_range(
+        &self,
+        ir: &mut Air,
+        range: &MultiplicityRange,
+        latch: NodeIndex,
+    ) -> Result<(), CompileError> {
+        let graph = ir.constraint_graph_mut();
+        let mut bit_roots = Vec::with_capacity(range.bits.len());
+        let mut decomposition = None;
+        for (i, column) in range.bits.iter().enumerate() {
+            let bit = graph.insert_node(Operation::Value(crate::Value::TraceAccess(
+                TraceAccess::new(DEFAULT_SEGMENT, *column, 0),
+            )));
+            let bit_squared = graph.insert_node(Operation::Mul(bit, bit));
+            bit_roots.push(graph.insert_node(Operation::Sub(bit_squared, bit)));
+
+            let weight = graph.insert_node(Operation::Value(crate::Value::Constant(1 << i)));
+            let term = graph.insert_node(Operation::Mul(weight, bit));
+            decomposition = Some(match decomposition {
+                Some(sum) => graph.insert_node(Operation::Add(sum, term)),
+                None => term,
+            });
+        }
+        let decomposition = decomposition.expect("the multiplicity must have at least one bit");
+        let root = graph.insert_node(Operation::Sub(latch, decomposition));
+        let (trace_segment, domain) = graph.node_details(&root, ConstraintDomain::EveryRow)?;
+
+        for bit_root in bit_roots {
+            ir.constraints
+                .insert_constraint(DEFAULT_SEGMENT, bit_root, ConstraintDomain::EveryRow);
+        }
+        ir.constraints.insert_constraint(trace_segment, root, domain);
+        Ok(())
+    }
+
+    /// Helper function to expand the integrity constraint of a multiset bus, returning its root
+    /// and the expanded operations
     fn expand_multiset_constraint(
         &self,
         ir: &mut Air,
         bus_ops: Vec<BusOp>,
         bus_access: NodeIndex,
         bus_access_with_offset: NodeIndex,
-    ) {
+    ) -> (NodeIndex, Vec<ExpandedBusOp>) {
         let graph = ir.constraint_graph_mut();
 
+        let mut ops = Vec::with_capacity(bus_ops.len());
         let mut p_factor = None;
         let mut p_prime_factor = None;
 
@@ -181,6 +274,8 @@ impl<'a> BusOpExpand<'a> {
                 args_combined = graph.insert_node(Operation::Add(args_combined, arg_times_alpha));
             }
 
+            ops.push(ExpandedBusOp { args_combined, latch, kind: bus_op_kind });
+
             // 2. Multiply by latch
             let args_combined_with_latch = graph.insert_node(Operation::Mul(args_combined, latch));
 
@@ -231,16 +326,18 @@ impl<'a> BusOpExpand<'a> {
         let root = graph.insert_node(Operation::Sub(p_prod, p_prime_prod));
 
         ir.constraints.insert_constraint(AUX_SEGMENT, root, ConstraintDomain::EveryRow);
+        (root, ops)
     }
 
-    /// Helper function to expand the integrity constraint of a logup bus
+    /// Helper function to expand the integrity constraint of a logup bus, returning its root and
+    /// the expanded operations
     fn expand_logup_constraint(
         &self,
         ir: &mut Air,
         bus_ops: Vec<BusOp>,
         bus_access: NodeIndex,
         bus_access_with_offset: NodeIndex,
-    ) {
+    ) -> (NodeIndex, Vec<ExpandedBusOp>) {
         let graph = ir.constraint_graph_mut();
         // Example:
         // q.insert(a, b, c) with d
@@ -368,5 +465,16 @@ impl<'a> BusOpExpand<'a> {
         // 5. Create the resulting constraint
         let root = graph.insert_node(Operation::Sub(q_term, q_prime_term));
         ir.constraints.insert_constraint(AUX_SEGMENT, root, ConstraintDomain::EveryRow);
+
+        let ops = bus_ops
+            .iter()
+            .zip(factors)
+            .map(|(bus_op, args_combined)| ExpandedBusOp {
+                args_combined,
+                latch: bus_op.latch,
+                kind: bus_op.op_kind,
+            })
+            .collect();
+        (root, ops)
     }
 }
@@ -1,7 +1,27 @@
+mod canonicalize;
+mod copy_columns;
+mod dead_random_values;
+mod duplicate_constraints;
 mod expand_buses;
+mod recurrence_boundaries;
+mod selector_booleanity;
+mod simplify;
 mod translate_from_ast;
 mod translate_from_mir;
+mod trivial_constraints;
+mod validate_trace_layout;
 
 pub use self::{
-    expand_buses::BusOpExpand, translate_from_ast::AstToAir, translate_from_mir::MirToAir,
+    canonicalize::Canonicalize,
+    copy_columns::CopyColumns,
+    dead_random_values::DeadRandomValues,
+    duplicate_constraints::DuplicateConstraints,
+    expand_buses::BusOpExpand,
+    recurrence_boundaries::RecurrenceBoundaries,
+    selector_booleanity::SelectorBooleanity,
+    simplify::Simplify,
+    translate_from_ast::AstToAir,
+    translate_from_mir::{DEFAULT_MAX_EXPONENT, MirToAir},
+    trivial_constraints::TrivialConstraints,
+    validate_trace_layout::ValidateTraceLayout,
 };
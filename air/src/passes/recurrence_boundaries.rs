@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsHandler, Severity};
+
+use crate::{Air, CompileError, ConstraintDomain, NodeIndex, Operation, TraceSegmentId, Value};
+
+/// A trace column, by segment and index within the segment
+type Column = (TraceSegmentId, usize);
+
+/// This pass warns about the trace columns which are defined by a recurrence, but whose value in
+/// the first row is not pinned by a boundary constraint.
+///
+/// An integrity constraint such as `enf a' = a + 1` only relates the value of `a` in each row to
+/// its value in the previous one, so any initial value yields a valid trace. This is usually a
+/// soundness gap, which is closed by a boundary constraint such as `enf a.first = 0`.
+///
+/// A column is considered to be defined by a recurrence if an integrity constraint of the form
+/// `a' = f(..)` reads `a` in the current row of `f`, possibly under a selector. A warning is
+/// emitted for each such column which is not the target of any boundary constraint on the first
+/// row. This is a heuristic: the initial value may legitimately be free, or be pinned in another
+/// way, e.g. by a constraint relating it to another pinned column.
+///
+/// This pass is disabled by default, as its heuristic can report columns which are correctly
+/// constrained, see [crate::PassConfig::recurrence_boundaries].
+pub struct RecurrenceBoundaries<'a> {
+    diagnostics: &'a DiagnosticsHandler,
+}
+impl<'a> RecurrenceBoundaries<'a> {
+    /// Create a new instance of this pass
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+        Self { diagnostics }
+    }
+}
+impl Pass for RecurrenceBoundaries<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        let mut pinned = BTreeSet::new();
+        // The first recurrence defining each column
+        let mut recurrences = BTreeMap::<Column, (TraceSegmentId, usize)>::new();
+        for segment in 0..ir.trace_segment_widths.len() {
+            for root in ir.boundary_constraints(segment) {
+                if root.domain() == ConstraintDomain::FirstRow {
+                    pinned.extend(boundary_column(&ir, root.node_index()));
+                }
+            }
+
+            for (i, root) in ir.integrity_constraints(segment).iter().enumerate() {
+                let selectors = ir.constraint_selectors(root);
+                if let Some(column) = recurrence_column(&ir, root.node_index(), selectors) {
+                    recurrences.entry(column).or_insert((segment, i));
+                }
+            }
+        }
+
+        for (column, (segment, index)) in recurrences {
+            if !pinned.contains(&column) {
+                self.warn(&ir, column, segment, index);
+            }
+        }
+
+        Ok(ir)
+    }
+}
+impl RecurrenceBoundaries<'_> {
+    fn warn(
+        &self,
+        ir: &Air,
+        (column_segment, column): Column,
+        segment: TraceSegmentId,
+        index: usize,
+    ) {
+        let name = ir.trace_column_name(column_segment, column);
+        let segment_name = match segment {
+            0 => "main",
+            _ => "aux",
+        };
+        let mut diagnostic = self
+            .diagnostics
+            .diagnostic(Severity::Warning)
+            .with_message(format!(
+                "column `{name}` is defined by a recurrence, but its value in the first row is not constrained"
+            ))
+            .with_note(format!(
+                "Integrity constraint #{index} against the {segment_name} trace defines `{name}'` from `{name}`, so the trace is valid for any initial value of `{name}`."
+            ))
+            .with_note(format!(
+                "Consider pinning its initial value with a boundary constraint, e.g. `enf {name}.first = 0;`."
+            ));
+        let root = &ir.integrity_constraints(segment)[index];
+        if let Some(provenance) = ir.constraint_provenance(root) {
+            diagnostic = provenance.label(diagnostic, "this constraint defines the column");
+        }
+        diagnostic.emit();
+    }
+}
+
+/// Returns the column constrained by the boundary constraint rooted at `index`, which is lowered
+/// from `enf a.first = e` to `a - e`.
+fn boundary_column(ir: &Air, index: &NodeIndex) -> Option<Column> {
+    match ir.constraint_graph().node(index).op() {
+        Operation::Sub(lhs, _) => match ir.constraint_graph().node(lhs).op() {
+            Operation::Value(Value::TraceAccess(access)) => Some((access.segment, access.column)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the column defined by the integrity constraint rooted at `index`, if it is of the form
+/// `a' - f(a)`, possibly multiplied by some of its `selectors`.
+fn recurrence_column(ir: &Air, index: &NodeIndex, selectors: &[NodeIndex]) -> Option<Column> {
+    match ir.constraint_graph().node(index).op() {
+        // A constraint enforced under a selector `s` is lowered to `s * e - 0`
+        Operation::Sub(lhs, rhs) if is_zero(ir, rhs) => recurrence_column(ir, lhs, selectors),
+        Operation::Mul(lhs, rhs) if selectors.contains(lhs) => {
+            recurrence_column(ir, rhs, selectors)
+        },
+        Operation::Mul(lhs, rhs) if selectors.contains(rhs) => {
+            recurrence_column(ir, lhs, selectors)
+        },
+        Operation::Sub(lhs, rhs) => match ir.constraint_graph().node(lhs).op() {
+            Operation::Value(Value::TraceAccess(access))
+                if access.row_offset == 1 && reads(ir, rhs, access.segment, access.column) =>
+            {
+                Some((access.segment, access.column))
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns true if the subgraph rooted at `index` reads `column` of `segment` in the current row
+fn reads(ir: &Air, index: &NodeIndex, segment: TraceSegmentId, column: usize) -> bool {
+    match ir.constraint_graph().node(index).op() {
+        Operation::Value(Value::TraceAccess(access)) => {
+            access.segment == segment && access.column == column && access.row_offset == 0
+        },
+        Operation::Value(_) => false,
+        Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) => {
+            reads(ir, lhs, segment, column) || reads(ir, rhs, segment, column)
+        },
+    }
+}
+
+fn is_zero(ir: &Air, index: &NodeIndex) -> bool {
+    matches!(ir.constraint_graph().node(index).op(), Operation::Value(Value::Constant(0)))
+}
@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsHandler, Severity};
+
+use crate::{Air, CompileError, NodeIndex, Operation, TraceSegmentId, Value};
+
+/// A trace column, by segment and index within the segment
+type Column = (TraceSegmentId, usize);
+
+/// This pass checks that the selectors under which integrity constraints are enforced, e.g. `s` in
+/// `enf x' = x + 1 when s`, are constrained to be binary.
+///
+/// A constraint enforced under a selector is multiplied by it, so that it only holds in the rows
+/// in which the selector is one. This is only sound if the selector is either zero or one in every
+/// row, which must be enforced by another constraint. A warning is emitted for each trace column
+/// read by a selector, for which the [Air] contains no integrity constraint of the form
+/// `s * s - s`, `s - s * s`, `s * (1 - s)` or `s * (s - 1)`, up to the order of the operands of the
+/// product. For compound selectors, e.g. `s1 * (1 - s2)`, each column they read is checked.
+///
+/// Columns declared `@binary` come with such a constraint, and are therefore never reported.
+///
+/// This is a structural check, so equivalent constraints written in another form are not
+/// recognized.
+pub struct SelectorBooleanity<'a> {
+    diagnostics: &'a DiagnosticsHandler,
+}
+impl<'a> SelectorBooleanity<'a> {
+    /// Create a new instance of this pass
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+        Self { diagnostics }
+    }
+}
+impl Pass for SelectorBooleanity<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        let mut binary = BTreeSet::new();
+        // The first constraint gated by each selector column, and the number of such constraints
+        let mut gated = BTreeMap::<Column, ((TraceSegmentId, usize), usize)>::new();
+        for segment in 0..ir.trace_segment_widths.len() {
+            for (i, root) in ir.integrity_constraints(segment).iter().enumerate() {
+                binary.extend(binary_column(&ir, root.node_index()));
+
+                let mut columns = BTreeSet::new();
+                for selector in ir.constraint_selectors(root) {
+                    collect_columns(&ir, selector, &mut columns);
+                }
+                for column in columns {
+                    gated.entry(column).or_insert(((segment, i), 0)).1 += 1;
+                }
+            }
+        }
+
+        for (column, ((segment, index), count)) in gated {
+            if !binary.contains(&column) {
+                self.warn(&ir, column, segment, index, count);
+            }
+        }
+
+        Ok(ir)
+    }
+}
+impl SelectorBooleanity<'_> {
+    fn warn(
+        &self,
+        ir: &Air,
+        (column_segment, column): Column,
+        segment: TraceSegmentId,
+        index: usize,
+        count: usize,
+    ) {
+        let name = ir.trace_column_name(column_segment, column);
+        let segment_name = match segment {
+            0 => "main",
+            _ => "aux",
+        };
+        let constraints = match count {
+            1 => format!("integrity constraint #{index} against the {segment_name} trace"),
+            _ => format!(
+                "{count} integrity constraints, starting with #{index} against the {segment_name} trace"
+            ),
+        };
+        let mut diagnostic = self
+            .diagnostics
+            .diagnostic(Severity::Warning)
+            .with_message(format!("selector column `{name}` is not constrained to be binary"))
+            .with_note(format!(
+                "It selects {constraints}, which only hold as intended if `{name}` is either 0 or 1 in every row."
+            ))
+            .with_note(format!(
+                "Consider enforcing `{name} * {name} = {name}`, or declaring the column as `@binary`."
+            ));
+        let root = &ir.integrity_constraints(segment)[index];
+        if let Some(provenance) = ir.constraint_provenance(root) {
+            diagnostic =
+                provenance.label(diagnostic, "this constraint is enforced under the selector");
+        }
+        diagnostic.emit();
+    }
+}
+
+/// Adds the trace columns read by the subgraph rooted at `index` to `columns`.
+fn collect_columns(ir: &Air, index: &NodeIndex, columns: &mut BTreeSet<Column>) {
+    match ir.constraint_graph().node(index).op() {
+        Operation::Value(Value::TraceAccess(access)) => {
+            columns.insert((access.segment, access.column));
+        },
+        Operation::Value(_) => {},
+        Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) => {
+            collect_columns(ir, lhs, columns);
+            collect_columns(ir, rhs, columns);
+        },
+    }
+}
+
+/// Returns the column constrained to be binary by the constraint rooted at `index`, if any.
+///
+/// The row at which the column is read does not matter, as an integrity constraint on `s'` also
+/// applies to `s` in all rows but the first one.
+fn binary_column(ir: &Air, index: &NodeIndex) -> Option<Column> {
+    match ir.constraint_graph().node(index).op() {
+        // `enf e = 0` is lowered to `e - 0`
+        Operation::Sub(lhs, rhs) if is_constant(ir, rhs, 0) => binary_column(ir, lhs),
+        // `s * s - s` or `s - s * s`
+        Operation::Sub(lhs, rhs) => square_of(ir, lhs, rhs).or_else(|| square_of(ir, rhs, lhs)),
+        // `s * (1 - s)` or `s * (s - 1)`
+        Operation::Mul(lhs, rhs) => {
+            complement_of(ir, lhs, rhs).or_else(|| complement_of(ir, rhs, lhs))
+        },
+        _ => None,
+    }
+}
+
+/// Returns the column read by `column` if `square` is its square.
+fn square_of(ir: &Air, square: &NodeIndex, column: &NodeIndex) -> Option<Column> {
+    match ir.constraint_graph().node(square).op() {
+        Operation::Mul(lhs, rhs) if lhs == column && rhs == column => column_of(ir, column),
+        _ => None,
+    }
+}
+
+/// Returns the column read by `column` if `complement` is `1 - column` or `column - 1`.
+fn complement_of(ir: &Air, column: &NodeIndex, complement: &NodeIndex) -> Option<Column> {
+    match ir.constraint_graph().node(complement).op() {
+        Operation::Sub(lhs, rhs) if lhs == column && is_constant(ir, rhs, 1) => {
+            column_of(ir, column)
+        },
+        Operation::Sub(lhs, rhs) if rhs == column && is_constant(ir, lhs, 1) => {
+            column_of(ir, column)
+        },
+        _ => None,
+    }
+}
+
+fn column_of(ir: &Air, index: &NodeIndex) -> Option<Column> {
+    match ir.constraint_graph().node(index).op() {
+        Operation::Value(Value::TraceAccess(access)) => Some((access.segment, access.column)),
+        _ => None,
+    }
+}
+
+fn is_constant(ir: &Air, index: &NodeIndex, value: u64) -> bool {
+    matches!(ir.constraint_graph().node(index).op(), Operation::Value(Value::Constant(c)) if *c == value)
+}
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use air_pass::Pass;
+use miden_diagnostics::DiagnosticsHandler;
+
+use crate::{Air, AlgebraicGraph, CompileError, ConstraintRoot, NodeIndex, Operation, Value};
+
+/// This pass simplifies the constraint graph of an [Air] by applying the following identities:
+///
+/// * `x * 1` and `1 * x` are simplified to `x`
+/// * `x * 0` and `0 * x` are simplified to `0`
+/// * `x + 0` and `0 + x` are simplified to `x`
+/// * `x - 0` is simplified to `x`
+/// * `x - x` is simplified to `0`
+///
+/// These are the same identities the ACE circuit builder applies, so that backends generating code
+/// directly from the graph, e.g. Winterfell, do not emit operations such as `x * E::ONE`.
+///
+/// The top-level operation of a constraint root is preserved when the root is a boundary
+/// constraint, since backends expect those to be of the form `column - value`, or when simplifying
+/// it would turn the constraint into a constant, which has no degree. Such constraints are reported
+/// by the [super::TrivialConstraints] pass instead.
+///
+/// The graph is rebuilt from the constraint roots and bus operations, so nodes which are not
+/// reachable from any of them are dropped. This pass is semantics-preserving: every constraint
+/// root evaluates to the same value before and after it is run.
+pub struct Simplify<'a> {
+    #[allow(unused)]
+    diagnostics: &'a DiagnosticsHandler,
+}
+impl<'a> Simplify<'a> {
+    /// Create a new instance of this pass
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+        Self { diagnostics }
+    }
+}
+impl Pass for Simplify<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, mut ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        let source = core::mem::take(ir.constraint_graph_mut());
+        let mut builder = SimplifiedGraphBuilder::new(&source);
+        ir.constraints.rewrite_roots(|root| builder.simplify_root(root));
+        for bus_op in ir.buses.values_mut().flat_map(|bus| bus.bus_ops.iter_mut()) {
+            for column in bus_op.columns.iter_mut() {
+                *column = builder.simplify(column);
+            }
+            bus_op.latch = builder.simplify(&bus_op.latch);
+        }
+        *ir.constraint_graph_mut() = builder.finish();
+
+        Ok(ir)
+    }
+}
+
+/// Builds the simplified form of a source [AlgebraicGraph] into a new graph, one subgraph at a
+/// time.
+struct SimplifiedGraphBuilder<'a> {
+    source: &'a AlgebraicGraph,
+    graph: AlgebraicGraph,
+    /// Maps nodes of the source graph to their simplified form in the new graph
+    rewrites: BTreeMap<NodeIndex, NodeIndex>,
+}
+impl<'a> SimplifiedGraphBuilder<'a> {
+    fn new(source: &'a AlgebraicGraph) -> Self {
+        Self {
+            source,
+            graph: AlgebraicGraph::default(),
+            rewrites: BTreeMap::default(),
+        }
+    }
+
+    fn finish(self) -> AlgebraicGraph {
+        self.graph
+    }
+
+    /// Returns the index in the new graph of the simplified form of the constraint `root`.
+    fn simplify_root(&mut self, root: &ConstraintRoot) -> NodeIndex {
+        let index = root.node_index();
+        let simplified = self.simplify(index);
+        if !root.domain().is_boundary() && !self.is_constant(&simplified) {
+            return simplified;
+        }
+
+        // Only simplify the operands of the root
+        match *self.source.node(index).op() {
+            Operation::Value(_) => simplified,
+            Operation::Add(lhs, rhs) => {
+                let (lhs, rhs) = (self.simplify(&lhs), self.simplify(&rhs));
+                self.graph.insert_node(Operation::Add(lhs, rhs))
+            },
+            Operation::Sub(lhs, rhs) => {
+                let (lhs, rhs) = (self.simplify(&lhs), self.simplify(&rhs));
+                self.graph.insert_node(Operation::Sub(lhs, rhs))
+            },
+            Operation::Mul(lhs, rhs) => {
+                let (lhs, rhs) = (self.simplify(&lhs), self.simplify(&rhs));
+                self.graph.insert_node(Operation::Mul(lhs, rhs))
+            },
+        }
+    }
+
+    /// Returns the index in the new graph of the simplified form of the source node at `index`.
+    fn simplify(&mut self, index: &NodeIndex) -> NodeIndex {
+        if let Some(rewritten) = self.rewrites.get(index) {
+            return *rewritten;
+        }
+
+        let rewritten = match *self.source.node(index).op() {
+            Operation::Value(value) => self.graph.insert_node(Operation::Value(value)),
+            Operation::Add(lhs, rhs) => {
+                let (lhs, rhs) = (self.simplify(&lhs), self.simplify(&rhs));
+                if self.is_constant_value(&lhs, 0) {
+                    rhs
+                } else if self.is_constant_value(&rhs, 0) {
+                    lhs
+                } else {
+                    self.graph.insert_node(Operation::Add(lhs, rhs))
+                }
+            },
+            Operation::Sub(lhs, rhs) => {
+                let (lhs, rhs) = (self.simplify(&lhs), self.simplify(&rhs));
+                if self.is_constant_value(&rhs, 0) {
+                    lhs
+                } else if lhs == rhs {
+                    self.graph.insert_node(Operation::Value(Value::Constant(0)))
+                } else {
+                    self.graph.insert_node(Operation::Sub(lhs, rhs))
+                }
+            },
+            Operation::Mul(lhs, rhs) => {
+                let (lhs, rhs) = (self.simplify(&lhs), self.simplify(&rhs));
+                if self.is_constant_value(&lhs, 0) || self.is_constant_value(&rhs, 1) {
+                    lhs
+                } else if self.is_constant_value(&rhs, 0) || self.is_constant_value(&lhs, 1) {
+                    rhs
+                } else {
+                    self.graph.insert_node(Operation::Mul(lhs, rhs))
+                }
+            },
+        };
+        self.rewrites.insert(*index, rewritten);
+        rewritten
+    }
+
+    fn is_constant(&self, index: &NodeIndex) -> bool {
+        matches!(self.graph.node(index).op(), Operation::Value(Value::Constant(_)))
+    }
+
+    fn is_constant_value(&self, index: &NodeIndex, value: u64) -> bool {
+        matches!(self.graph.node(index).op(), Operation::Value(Value::Constant(c)) if *c == value)
+    }
+}
@@ -1,4 +1,11 @@
-use air_parser::{LexicalScope, ast};
+use std::collections::BTreeMap;
+
+use air_parser::{
+    LexicalScope, ast,
+    error_codes::{
+        BOUNDARY_CONSTRAINT_DOMAIN_MISMATCH, OVERLAPPING_BOUNDARY_CONSTRAINTS, WithErrorCode,
+    },
+};
 use air_pass::Pass;
 use miden_diagnostics::{DiagnosticsHandler, Severity, Span, Spanned};
 
@@ -29,9 +36,17 @@ impl Pass for AstToAir<'_> {
         let boundary_constraints = program.boundary_constraints;
         let integrity_constraints = program.integrity_constraints;
 
-        air.trace_segment_widths = trace_columns.iter().map(|ts| ts.size as u16).collect();
+        air.trace_segment_widths = trace_segment_widths(self.diagnostics, &trace_columns)?;
+        air.trace_segment_names = trace_columns.iter().map(|ts| ts.name).collect();
+        air.trace_column_names = trace_column_names(&trace_columns);
         air.periodic_columns = program.periodic_columns;
         air.public_inputs = program.public_inputs;
+        air.virtual_columns = build_virtual_columns(
+            self.diagnostics,
+            &air,
+            &trace_columns,
+            &program.virtual_columns,
+        )?;
 
         let mut builder = AirBuilder {
             diagnostics: self.diagnostics,
@@ -62,6 +77,137 @@ enum MemoizedBinding {
     Matrix(Vec<Vec<NodeIndex>>),
 }
 
+/// Returns the name of each column of the given trace segments, see [Air::trace_column_name].
+pub(super) fn trace_column_names(trace_columns: &[ast::TraceSegment]) -> Vec<Vec<String>> {
+    trace_columns
+        .iter()
+        .map(|segment| {
+            let mut names = vec![String::new(); segment.size];
+            for binding in segment.bindings.iter() {
+                let Some(name) = binding.name else {
+                    continue;
+                };
+                for index in 0..binding.size {
+                    names[binding.offset + index] = if binding.is_scalar() {
+                        name.to_string()
+                    } else {
+                        format!("{name}[{index}]")
+                    };
+                }
+            }
+            names
+        })
+        .collect()
+}
+
+/// Returns the width of each of the given trace segments, emitting an error for each segment
+/// wider than the `u16` widths stored in the [Air].
+pub(super) fn trace_segment_widths(
+    diagnostics: &DiagnosticsHandler,
+    trace_columns: &[ast::TraceSegment],
+) -> Result<Vec<u16>, CompileError> {
+    let mut widths = Vec::with_capacity(trace_columns.len());
+    for segment in trace_columns {
+        match u16::try_from(segment.size) {
+            Ok(width) => widths.push(width),
+            Err(_) => {
+                diagnostics
+                    .diagnostic(Severity::Error)
+                    .with_message("trace segment is too wide")
+                    .with_primary_label(
+                        segment.span(),
+                        format!(
+                            "trace segment width {} exceeds the maximum supported {}",
+                            segment.size,
+                            u16::MAX
+                        ),
+                    )
+                    .emit();
+            },
+        }
+    }
+    if widths.len() < trace_columns.len() {
+        return Err(CompileError::Failed);
+    }
+    Ok(widths)
+}
+
+/// Lowers the definitions of the virtual columns of the main trace to their own graph, see
+/// [VirtualColumns].
+///
+/// Semantic analysis guarantees that the definitions only consist of arithmetic on scalar values,
+/// so they are lowered like the constraints of this pipeline, which requires no inlining.
+pub(super) fn build_virtual_columns(
+    diagnostics: &DiagnosticsHandler,
+    air: &Air,
+    trace_columns: &[ast::TraceSegment],
+    virtual_columns: &[ast::VirtualColumn],
+) -> Result<VirtualColumns, CompileError> {
+    let column_of = |name: Identifier| {
+        trace_columns[DEFAULT_SEGMENT]
+            .bindings
+            .iter()
+            .find(|binding| binding.name == Some(name))
+            .map(|binding| binding.offset)
+            .expect("virtual columns are declared in the main trace")
+    };
+    let declarations = virtual_columns
+        .iter()
+        .map(|column| (column_of(column.name), column))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut definitions_air = Air::new(air.name);
+    definitions_air.periodic_columns = air.periodic_columns.clone();
+    definitions_air.public_inputs = air.public_inputs.clone();
+    let mut builder = AirBuilder {
+        diagnostics,
+        air: &mut definitions_air,
+        trace_columns: trace_columns.to_vec(),
+        bindings: Default::default(),
+    };
+
+    let mut definitions = BTreeMap::new();
+    for column in virtual_columns {
+        let definition = builder.insert_scalar_expr(&column.definition)?;
+
+        // Substituting the definitions must terminate, so they can only depend on the virtual
+        // columns which are already defined
+        let graph = builder.air.constraint_graph();
+        let mut order = vec![];
+        graph.extend_topological_order(definition, &mut vec![false; graph.num_nodes()], &mut order);
+        for index in order {
+            let Operation::Value(Value::TraceAccess(access)) = graph.node(&index).op() else {
+                continue;
+            };
+            if access.segment != DEFAULT_SEGMENT || definitions.contains_key(&access.column) {
+                continue;
+            }
+            if let Some(dependency) = declarations.get(&access.column) {
+                diagnostics
+                    .diagnostic(Severity::Error)
+                    .with_message("invalid virtual column definition")
+                    .with_primary_label(
+                        column.definition.span(),
+                        format!(
+                            "`{}` may only depend on the virtual columns declared before it",
+                            column.name
+                        ),
+                    )
+                    .with_secondary_label(
+                        dependency.span(),
+                        format!("but it depends on `{}`, declared here", dependency.name),
+                    )
+                    .emit();
+                return Err(CompileError::Failed);
+            }
+        }
+
+        definitions.insert(column_of(column.name), (column.name, definition));
+    }
+
+    Ok(VirtualColumns::new(definitions_air.constraint_graph().clone(), definitions))
+}
+
 struct AirBuilder<'a> {
     diagnostics: &'a DiagnosticsHandler,
     air: &'a mut Air,
@@ -174,7 +320,8 @@ impl AirBuilder<'_> {
             trace_access.column,
             access.boundary,
         ) {
-            self.diagnostics
+            let diagnostic = self
+                .diagnostics
                 .diagnostic(Severity::Error)
                 .with_message("overlapping boundary constraints")
                 .with_primary_label(
@@ -182,7 +329,8 @@ impl AirBuilder<'_> {
                     "this constrains a column and boundary that has already been constrained",
                 )
                 .with_secondary_label(prev, "previous constraint occurs here")
-                .emit();
+                .with_code(OVERLAPPING_BOUNDARY_CONSTRAINTS);
+            self.diagnostics.emit(diagnostic);
             return Err(CompileError::Failed);
         }
 
@@ -209,12 +357,13 @@ impl AirBuilder<'_> {
                 return Err(CompileError::Failed);
             }
             if lhs_domain != rhs_domain {
-                self.diagnostics.diagnostic(Severity::Error)
+                let diagnostic = self.diagnostics.diagnostic(Severity::Error)
                     .with_message("invalid boundary constraint")
                     .with_primary_label(lhs_span, format!("this has a constraint domain of {lhs_domain}"))
                     .with_secondary_label(rhs_span, format!("this has a constraint domain of {rhs_domain}"))
                     .with_note("Boundary constraints require both sides of the constraint to be in the same domain.")
-                    .emit();
+                    .with_code(BOUNDARY_CONSTRAINT_DOMAIN_MISMATCH);
+                self.diagnostics.emit(diagnostic);
                 return Err(CompileError::Failed);
             }
         }
@@ -391,7 +540,7 @@ impl AirBuilder<'_> {
                             AccessType::Slice(range) => {
                                 MemoizedBinding::Vector(nodes[range.to_slice_range()].to_vec())
                             },
-                            AccessType::Matrix(..) => unreachable!(),
+                            AccessType::Matrix(..) | AccessType::Bound(_) => unreachable!(),
                         };
                         Ok(value)
                     },
@@ -405,6 +554,7 @@ impl AirBuilder<'_> {
                             AccessType::Matrix(row, col) => {
                                 MemoizedBinding::Scalar(nodes[*row][*col])
                             },
+                            AccessType::Bound(_) => unreachable!(),
                         };
                         Ok(value)
                     },
@@ -440,7 +590,8 @@ impl AirBuilder<'_> {
             | ast::ScalarExpr::BoundedSymbolAccess(_)
             | ast::ScalarExpr::BusOperation(_)
             | ast::ScalarExpr::Null(_)
-            | ast::ScalarExpr::Unconstrained(_) => unreachable!(),
+            | ast::ScalarExpr::Unconstrained(_)
+            | ast::ScalarExpr::RowComprehension(_) => unreachable!(),
         }
     }
 
@@ -488,7 +639,7 @@ impl AirBuilder<'_> {
             ResolvableIdentifier::Resolved(ref qid) => {
                 if let Some(pc) = self.air.periodic_columns.get(qid) {
                     self.insert_op(Operation::Value(Value::PeriodicColumn(
-                        PeriodicColumnAccess::new(*qid, pc.period()),
+                        PeriodicColumnAccess::new(*qid, pc.period(), access.offset),
                     )))
                 } else {
                     // This is a qualified reference that should have been eliminated
@@ -524,24 +675,22 @@ impl AirBuilder<'_> {
                 }
 
                 // If we reach here, this must be a let-bound variable
-                match self.bindings.get(access.name.as_ref()).expect("undefined variable") {
-                    MemoizedBinding::Scalar(node) => {
-                        assert_eq!(access.access_type, AccessType::Default);
-                        *node
-                    },
-                    MemoizedBinding::Vector(nodes) => {
-                        if let AccessType::Index(idx) = &access.access_type {
-                            return nodes[*idx];
-                        }
-                        unreachable!("impossible vector access: {:?}", access)
-                    },
-                    MemoizedBinding::Matrix(nodes) => {
-                        if let AccessType::Matrix(row, col) = &access.access_type {
-                            return nodes[*row][*col];
-                        }
-                        unreachable!("impossible matrix access: {:?}", access)
-                    },
-                }
+                let node =
+                    match self.bindings.get(access.name.as_ref()).expect("undefined variable") {
+                        MemoizedBinding::Scalar(node) => {
+                            assert_eq!(access.access_type, AccessType::Default);
+                            *node
+                        },
+                        MemoizedBinding::Vector(nodes) => match &access.access_type {
+                            AccessType::Index(idx) => nodes[*idx],
+                            _ => unreachable!("impossible vector access: {:?}", access),
+                        },
+                        MemoizedBinding::Matrix(nodes) => match &access.access_type {
+                            AccessType::Matrix(row, col) => nodes[*row][*col],
+                            _ => unreachable!("impossible matrix access: {:?}", access),
+                        },
+                    };
+                self.offset_node(node, access.offset)
             },
             // These should have been eliminated by previous compiler passes
             ResolvableIdentifier::Unresolved(_) => {
@@ -605,6 +754,39 @@ impl AirBuilder<'_> {
 
     /// Adds the specified operation to the graph and returns the index of its node.
     #[inline]
+    /// Returns the node representing the value at `node`, with all of the trace accesses it
+    /// contains shifted down by `offset` rows.
+    ///
+    /// This is used to apply the row offset of an access to a let-bound variable, e.g. `m[0][1]'`
+    /// where `m` is a matrix of trace columns.
+    fn offset_node(&mut self, node: NodeIndex, offset: usize) -> NodeIndex {
+        if offset == 0 {
+            return node;
+        }
+        match *self.air.constraint_graph().node(&node).op() {
+            Operation::Value(Value::TraceAccess(ta)) => {
+                let ta = TraceAccess::new(ta.segment, ta.column, ta.row_offset + offset);
+                self.insert_op(Operation::Value(Value::TraceAccess(ta)))
+            },
+            Operation::Value(_) => node,
+            Operation::Add(lhs, rhs) => {
+                let lhs = self.offset_node(lhs, offset);
+                let rhs = self.offset_node(rhs, offset);
+                self.insert_op(Operation::Add(lhs, rhs))
+            },
+            Operation::Sub(lhs, rhs) => {
+                let lhs = self.offset_node(lhs, offset);
+                let rhs = self.offset_node(rhs, offset);
+                self.insert_op(Operation::Sub(lhs, rhs))
+            },
+            Operation::Mul(lhs, rhs) => {
+                let lhs = self.offset_node(lhs, offset);
+                let rhs = self.offset_node(rhs, offset);
+                self.insert_op(Operation::Mul(lhs, rhs))
+            },
+        }
+    }
+
     fn insert_op(&mut self, op: Operation) -> NodeIndex {
         self.air.constraint_graph_mut().insert_node(op)
     }
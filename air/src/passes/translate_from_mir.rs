@@ -2,26 +2,59 @@ use std::{collections::BTreeMap, ops::Deref};
 
 use air_parser::{
     SemanticAnalysisError,
-    ast::{self, TraceSegment},
+    ast::{self, DocComments, TraceSegment},
+    error_codes::{
+        BOUNDARY_CONSTRAINT_DOMAIN_MISMATCH, OVERLAPPING_BOUNDARY_CONSTRAINTS, WithErrorCode,
+    },
 };
 use air_pass::Pass;
 use miden_diagnostics::{DiagnosticsHandler, Severity, SourceSpan, Span, Spanned};
-use mir::ir::{ConstantValue, Link, Mir, MirValue, Op, Parent, SpannedMirValue};
+use mir::ir::{ConstantValue, Graph, Link, Mir, MirValue, Op, Parent, SpannedMirValue};
 
+use super::translate_from_ast::{build_virtual_columns, trace_column_names, trace_segment_widths};
 use crate::{CompileError, graph::NodeIndex, ir::*};
 
+/// The maximum exponent of an exponentiation accepted by [MirToAir] by default.
+pub const DEFAULT_MAX_EXPONENT: u64 = 64;
+
 /// This pass creates the [Air] from the [Mir].
 ///  
 /// We mainly directly transform Mir operations to Air operations,
 /// as after the Inlining and Unrolling the nodes correspond 1 to 1.
+///
+/// Exponentiations are expanded into multiplications, and the degree of the resulting
+/// constraint grows with the exponent, so exponents larger than a configurable maximum are
+/// rejected, as they are most likely the result of a miscomputed constant.
 pub struct MirToAir<'a> {
     diagnostics: &'a DiagnosticsHandler,
+    aux_segment_name: Option<Identifier>,
+    max_exponent: u64,
 }
 impl<'a> MirToAir<'a> {
     /// Create a new instance of this pass
     #[inline]
     pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
-        Self { diagnostics }
+        Self {
+            diagnostics,
+            aux_segment_name: None,
+            max_exponent: DEFAULT_MAX_EXPONENT,
+        }
+    }
+
+    /// Sets the maximum exponent of an exponentiation, which is [DEFAULT_MAX_EXPONENT] unless
+    /// overridden.
+    #[inline]
+    pub fn with_max_exponent(mut self, max_exponent: u64) -> Self {
+        self.max_exponent = max_exponent;
+        self
+    }
+
+    /// Sets the name of the aux trace segment synthesized to hold the bus columns, which is used
+    /// in diagnostics and carried into the [Air]. Defaults to `$aux`.
+    #[inline]
+    pub fn with_aux_segment_name(mut self, name: Identifier) -> Self {
+        self.aux_segment_name = Some(name);
+        self
     }
 }
 impl Pass for MirToAir<'_> {
@@ -38,42 +71,106 @@ impl Pass for MirToAir<'_> {
 
         let mut bus_bindings_map = BTreeMap::new();
         if !buses.is_empty() {
-            let bus_raw_bindings: Vec<_> = buses
-                .keys()
-                .map(|k| Span::new(k.span(), (Identifier::new(k.span(), k.name()), AUX_SEGMENT)))
-                .collect();
+            // The buses are added as `aux` trace columns, after the declared aux columns, if any
+            let declared_aux = trace_columns.get(AUX_SEGMENT);
+            let mut raw_bindings: Vec<_> = declared_aux
+                .map(|segment| {
+                    segment
+                        .bindings
+                        .iter()
+                        .filter_map(|binding| {
+                            binding.name.map(|name| Span::new(binding.span(), (name, binding.size)))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let num_declared_columns = declared_aux.map_or(0, |segment| segment.size);
+            let num_declared_bindings = raw_bindings.len();
+
+            let mut collision = false;
+            for bus in buses.keys() {
+                let name = Identifier::new(bus.span(), bus.name());
+                if let Some(column) = raw_bindings.iter().find(|binding| binding.0 == name) {
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid bus declaration")
+                        .with_primary_label(
+                            bus.span(),
+                            "this bus conflicts with a column of the aux trace segment",
+                        )
+                        .with_secondary_label(column.span(), "previously defined here")
+                        .emit();
+                    collision = true;
+                }
+            }
+            if collision {
+                return Err(CompileError::Failed);
+            }
+            raw_bindings.extend(
+                buses
+                    .keys()
+                    .map(|k| Span::new(k.span(), (Identifier::new(k.span(), k.name()), 1))),
+            );
 
-            // Add buses as `aux` trace columns
             let aux_trace_segment = TraceSegment::new(
-                SourceSpan::default(),
+                declared_aux.map_or(SourceSpan::default(), |segment| segment.span),
                 AUX_SEGMENT,
-                Identifier::new(SourceSpan::default(), Symbol::new(AUX_SEGMENT as u32)),
-                bus_raw_bindings,
+                declared_aux.map(|segment| segment.name).unwrap_or_else(|| {
+                    self.aux_segment_name.unwrap_or_else(|| {
+                        Identifier::new(SourceSpan::default(), air_parser::symbols::Aux)
+                    })
+                }),
+                raw_bindings,
             );
-            for binding in aux_trace_segment.bindings.iter() {
+            // Every bus is bound to a single column, after all of the declared aux columns
+            assert_eq!(aux_trace_segment.size, num_declared_columns + buses.len());
+            for binding in aux_trace_segment.bindings.iter().skip(num_declared_bindings) {
                 bus_bindings_map.insert(binding.name.unwrap(), binding.offset);
             }
-            if trace_columns.len() == 1 {
-                trace_columns.push(aux_trace_segment);
-            } else {
-                panic!("Expected only one trace segment, but found multiple: {trace_columns:?}",);
+            match trace_columns.len() {
+                1 => trace_columns.push(aux_trace_segment),
+                2 => trace_columns[AUX_SEGMENT] = aux_trace_segment,
+                _ => panic!("Expected at most two trace segments, but found: {trace_columns:?}"),
             }
         }
 
-        air.trace_segment_widths = trace_columns.iter().map(|ts| ts.size as u16).collect();
+        // An empty aux segment is never materialized, so that the widths of the segments, from
+        // which e.g. the layout of the inputs of a recursive verifier is derived, are the same
+        // whether or not the MIR declares one
+        if trace_columns.get(AUX_SEGMENT).is_some_and(|segment| segment.size == 0) {
+            trace_columns.truncate(AUX_SEGMENT);
+        }
+
+        air.trace_segment_widths = trace_segment_widths(self.diagnostics, &trace_columns)?;
+        air.trace_segment_names = trace_columns.iter().map(|ts| ts.name).collect();
+        air.trace_column_names = trace_column_names(&trace_columns);
+        air.bus_columns = bus_bindings_map.clone();
         air.num_random_values = mir.num_random_values;
+        air.buses_span = mir.buses_span;
         air.periodic_columns = mir.periodic_columns.clone();
         air.public_inputs = mir.public_inputs.clone();
+        air.virtual_columns = build_virtual_columns(
+            self.diagnostics,
+            &air,
+            &mir.trace_columns,
+            &mir.virtual_columns,
+        )?;
+
+        let graph = mir.constraint_graph();
 
         let mut builder = AirBuilder {
             diagnostics: self.diagnostics,
             air: &mut air,
+            graph,
             trace_columns: trace_columns.clone(),
             bus_bindings_map,
+            provenance: None,
+            max_exponent: self.max_exponent,
+            constraint_span: None,
+            docs: &mir.docs,
+            constraint_docs: &[],
         };
 
-        let graph = mir.constraint_graph();
-
         for bus in buses.values() {
             builder.build_bus(bus)?;
         }
@@ -92,8 +189,19 @@ impl Pass for MirToAir<'_> {
 struct AirBuilder<'a> {
     diagnostics: &'a DiagnosticsHandler,
     air: &'a mut Air,
+    graph: &'a Graph,
     trace_columns: Vec<TraceSegment>,
     bus_bindings_map: BTreeMap<Identifier, usize>,
+    /// The provenance of the integrity constraint being built, if it was inlined from an evaluator
+    provenance: Option<ConstraintProvenance>,
+    /// The maximum exponent of an exponentiation
+    max_exponent: u64,
+    /// The span of the constraint being built, if any, reported along with oversized exponents
+    constraint_span: Option<SourceSpan>,
+    /// The doc comments of the program
+    docs: &'a DocComments,
+    /// The doc comments of the nearest documented statement enclosing the constraint being built
+    constraint_docs: &'a [String],
 }
 
 /// In case of nested list comprehension, we may not have entirely unrolled outer loops iterators
@@ -108,7 +216,18 @@ fn indexed_accessor(mir_node: &Link<Op>) -> Link<Op> {
                         "Index out of bounds during indexed accessor translation from MIR to AIR: {index}",
                     );
                 }
-                children[index].clone()
+                let child = children[index].clone();
+                // Keep the row offset of the access, if any, for the accessed element
+                if accessor.offset > 0 && child.as_value().is_some() {
+                    mir::ir::Accessor::create(
+                        child,
+                        AccessType::Default,
+                        accessor.offset,
+                        accessor.span(),
+                    )
+                } else {
+                    child
+                }
             } else {
                 mir_node.clone()
             }
@@ -149,7 +268,7 @@ fn enf_to_scalar(mir_node: &Link<Op>) -> Link<Op> {
     }
 }
 
-impl AirBuilder<'_> {
+impl<'a> AirBuilder<'a> {
     // Uses square and multiply algorithm to expand the exp into a series of multiplications
     fn expand_exp(&mut self, lhs: NodeIndex, rhs: u64) -> NodeIndex {
         match rhs {
@@ -234,6 +353,31 @@ impl AirBuilder<'_> {
                     ));
                 };
 
+                if rhs_value > self.max_exponent {
+                    let mut diagnostic = self
+                        .diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("exponent is too large")
+                        .with_primary_label(
+                            rhs.span(),
+                            format!(
+                                "this exponent is {rhs_value}, which exceeds the limit of {}",
+                                self.max_exponent
+                            ),
+                        );
+                    if let Some(span) = self.constraint_span {
+                        diagnostic = diagnostic
+                            .with_secondary_label(span, "in the expansion of this constraint");
+                    }
+                    diagnostic
+                        .with_note(
+                            "The limit can be raised with `PassConfig::max_exponent`, or the \
+                             `--max-exponent` option of `airc transpile`.",
+                        )
+                        .emit();
+                    return Err(CompileError::Failed);
+                }
+
                 Ok(self.expand_exp(lhs_node_index, rhs_value))
             },
             Op::Value(value) => {
@@ -267,6 +411,7 @@ impl AirBuilder<'_> {
                         crate::ir::Value::PeriodicColumn(crate::ir::PeriodicColumnAccess {
                             name: periodic_column_access.name,
                             cycle: periodic_column_access.cycle,
+                            row_offset: periodic_column_access.row_offset,
                         })
                     },
                     MirValue::PublicInput(public_input_access) => {
@@ -289,6 +434,12 @@ impl AirBuilder<'_> {
                 let child = accessor.indexable.clone();
                 let child = indexed_accessor(&child);
 
+                // Accessing an expression without offset, e.g. an element of a vector returned by
+                // a function, is equivalent to the expression itself
+                if offset == 0 && child.as_value().is_none() {
+                    return self.insert_mir_operation(&child);
+                }
+
                 let Some(value) = child.as_value() else {
                     unreachable!("Expected value in accessor, found: {:?}", child);
                 };
@@ -307,7 +458,7 @@ impl AirBuilder<'_> {
                         crate::ir::Value::TraceAccess(crate::ir::TraceAccess {
                             segment: trace_access.segment,
                             column: trace_access.column,
-                            row_offset: offset,
+                            row_offset: trace_access.row_offset + offset,
                         })
                     },
                     MirValue::BusAccess(bus_access) => {
@@ -323,6 +474,7 @@ impl AirBuilder<'_> {
                         crate::ir::Value::PeriodicColumn(crate::ir::PeriodicColumnAccess {
                             name: periodic_column_access.name,
                             cycle: periodic_column_access.cycle,
+                            row_offset: periodic_column_access.row_offset,
                         })
                     },
                     MirValue::PublicInput(public_input_access) => {
@@ -340,6 +492,30 @@ impl AirBuilder<'_> {
         }
     }
 
+    /// Documents the constraints built from now on with the doc comments of the statement at
+    /// `span`, if it has any, and returns the doc comments to restore once they are built.
+    fn enter_docs(&mut self, span: SourceSpan) -> &'a [String] {
+        let docs: &'a [String] = self.docs.get(span);
+        if docs.is_empty() {
+            self.constraint_docs
+        } else {
+            core::mem::replace(&mut self.constraint_docs, docs)
+        }
+    }
+
+    /// Records the doc comments of the constraint being built, whose entry node is `root`
+    fn record_docs(&mut self, root: NodeIndex) {
+        if !self.constraint_docs.is_empty() {
+            self.air.constraints.set_docs(root, self.constraint_docs.to_vec());
+        }
+    }
+
+    fn record_span(&mut self, root: NodeIndex) {
+        if let Some(span) = self.constraint_span {
+            self.air.constraints.set_span(root, span);
+        }
+    }
+
     fn build_boundary_constraint(&mut self, bc: &Link<Op>) -> Result<(), CompileError> {
         match bc.borrow().deref() {
             Op::Vector(vector) => {
@@ -362,10 +538,20 @@ impl AirBuilder<'_> {
             Op::Enf(enf) => {
                 let child_op = enf.expr.clone();
                 let child_op = indexed_accessor(&child_op);
+                // An evaluator enforcing several constraints, some of them in comprehensions, is
+                // inlined as nested vectors of constraints, which are all enforced
+                let child_op = enf_to_scalar(&child_op);
+                if child_op.as_vector().is_some_and(|vector| vector.size != 1) {
+                    return self.build_integrity_constraint(&child_op);
+                }
                 let child_op = vec_to_scalar(&child_op);
 
-                self.build_boundary_constraint(&child_op)?;
-                Ok(())
+                let enclosing_span = self.constraint_span.replace(enf.span());
+                let enclosing_docs = self.enter_docs(enf.span());
+                let result = self.build_boundary_constraint(&child_op);
+                self.constraint_span = enclosing_span;
+                self.constraint_docs = enclosing_docs;
+                result
             },
             Op::Sub(sub) => {
                 // Check that lhs is a Bounded trace access
@@ -431,7 +617,8 @@ impl AirBuilder<'_> {
                     trace_access.column,
                     boundary.kind,
                 ) {
-                    self.diagnostics
+                    let diagnostic = self
+                        .diagnostics
                         .diagnostic(Severity::Error)
                         .with_message("overlapping boundary constraints")
                         .with_primary_label(
@@ -439,7 +626,8 @@ impl AirBuilder<'_> {
                             "this constrains a column and boundary that has already been constrained",
                         )
                         .with_secondary_label(prev, "previous constraint occurs here")
-                        .emit();
+                        .with_code(OVERLAPPING_BOUNDARY_CONSTRAINTS);
+                    self.diagnostics.emit(diagnostic);
                     return Err(CompileError::Failed);
                 }
 
@@ -462,8 +650,8 @@ impl AirBuilder<'_> {
                         // trace segment inference defaults to the lowest segment (the main trace)
                         // and is adjusted according to the use of random
                         // values and trace columns.
-                        let lhs_segment_name = self.trace_columns[lhs_segment].name;
-                        let rhs_segment_name = self.trace_columns[rhs_segment].name;
+                        let lhs_segment_name = self.air.trace_segment_name(lhs_segment);
+                        let rhs_segment_name = self.air.trace_segment_name(rhs_segment);
                         self.diagnostics.diagnostic(Severity::Error)
                                     .with_message("invalid boundary constraint")
                                     .with_primary_label(lhs_span, format!("this constrains a column in the '{lhs_segment_name}' trace segment"))
@@ -473,12 +661,13 @@ impl AirBuilder<'_> {
                         return Err(CompileError::Failed);
                     }
                     if lhs_domain != rhs_domain {
-                        self.diagnostics.diagnostic(Severity::Error)
+                        let diagnostic = self.diagnostics.diagnostic(Severity::Error)
                                     .with_message("invalid boundary constraint")
                                     .with_primary_label(lhs_span, format!("this has a constraint domain of {lhs_domain}"))
                                     .with_secondary_label(rhs_span, format!("this has a constraint domain of {rhs_domain}"))
                                     .with_note("Boundary constraints require both sides of the constraint to be in the same domain.")
-                                    .emit();
+                                    .with_code(BOUNDARY_CONSTRAINT_DOMAIN_MISMATCH);
+                        self.diagnostics.emit(diagnostic);
                         return Err(CompileError::Failed);
                     }
                 }
@@ -488,6 +677,8 @@ impl AirBuilder<'_> {
 
                 // Store the generated constraint
                 self.air.constraints.insert_constraint(trace_access.segment, root, domain);
+                self.record_docs(root);
+                self.record_span(root);
                 Ok(())
             },
             _ => unreachable!(),
@@ -512,16 +703,36 @@ impl AirBuilder<'_> {
                 }
             },
             Op::Enf(enf) => {
+                let call_sites = self.graph.get_provenance(ic);
+                let enclosing = if call_sites.is_empty() {
+                    self.provenance.clone()
+                } else {
+                    self.provenance.replace(ConstraintProvenance {
+                        span: enf.span(),
+                        call_sites: call_sites.to_vec(),
+                    })
+                };
                 let child_op = enf.expr.clone();
                 let child_op = indexed_accessor(&child_op);
-                let child_op = vec_to_scalar(&child_op);
-                let child_op = enf_to_scalar(&child_op);
+                // An evaluator enforcing several constraints, some of them in comprehensions, is
+                // inlined as nested vectors of constraints, which are all enforced. Nested
+                // constraints are visited in turn, as each of them may have its own provenance.
+                let child_op = if child_op.as_vector().is_some_and(|vector| vector.size == 1) {
+                    vec_to_scalar(&child_op)
+                } else {
+                    child_op
+                };
+                let enclosing_span = self.constraint_span.replace(enf.span());
+                let enclosing_docs = self.enter_docs(enf.span());
                 match child_op.clone().borrow().deref() {
-                    Op::Sub(_sub) => {
+                    Op::Sub(_) | Op::Enf(_) | Op::Vector(_) => {
                         self.build_integrity_constraint(&child_op)?;
                     },
                     _ => unreachable!("Enforced with unexpected operation: {:?}", child_op),
                 }
+                self.constraint_span = enclosing_span;
+                self.constraint_docs = enclosing_docs;
+                self.provenance = enclosing;
             },
             Op::Sub(sub) => {
                 let lhs = sub.lhs.clone();
@@ -532,6 +743,19 @@ impl AirBuilder<'_> {
                 let (trace_segment, domain) =
                     self.air.constraint_graph().node_details(&root, ConstraintDomain::EveryRow)?;
                 self.air.constraints.insert_constraint(trace_segment, root, domain);
+                if let Some(provenance) = self.provenance.clone() {
+                    self.air.constraints.set_provenance(root, provenance);
+                }
+                self.record_docs(root);
+                self.record_span(root);
+                let selectors = self.graph.get_selectors(ic).to_vec();
+                if !selectors.is_empty() {
+                    let selectors = selectors
+                        .iter()
+                        .map(|selector| self.insert_mir_operation(selector))
+                        .collect::<Result<_, _>>()?;
+                    self.air.constraints.set_selectors(root, selectors);
+                }
             },
             _ => unreachable!(),
         }
@@ -542,8 +766,15 @@ impl AirBuilder<'_> {
     fn build_bus(&mut self, mir_bus: &Link<mir::ir::Bus>) -> Result<(), CompileError> {
         let mir_bus = mir_bus.borrow();
 
-        let first = build_bus_boundary(self.diagnostics, mir_bus.span(), &mir_bus.get_first())?;
-        let last = build_bus_boundary(self.diagnostics, mir_bus.span(), &mir_bus.get_last())?;
+        // The boundaries of an extern bus are constrained by the program defining it
+        let (first, last) = if mir_bus.is_extern {
+            (BusBoundary::Unconstrained, BusBoundary::Unconstrained)
+        } else {
+            (
+                build_bus_boundary(self.diagnostics, mir_bus.span(), &mir_bus.get_first())?,
+                build_bus_boundary(self.diagnostics, mir_bus.span(), &mir_bus.get_last())?,
+            )
+        };
 
         let mut bus_ops = vec![];
         for (mir_column, mir_latch) in mir_bus.columns.iter().zip(mir_bus.latches.iter()) {
@@ -561,10 +792,13 @@ impl AirBuilder<'_> {
             let bus_op = BusOp::new(column, latch, mir_bus_op.kind);
             bus_ops.push(bus_op);
         }
-        self.air.buses.insert(
-            mir_bus.name(),
-            Bus::new(mir_bus.name(), mir_bus.bus_type, first, last, bus_ops),
-        );
+        let mut bus = Bus::new(mir_bus.name(), mir_bus.bus_type, first, last, bus_ops);
+        bus.multiplicity_range = mir_bus.range_checked_op.map(|bus_op| MultiplicityRange {
+            bits: mir_bus.multiplicity_bits.clone(),
+            bus_op,
+        });
+        bus.is_extern = mir_bus.is_extern;
+        self.air.buses.insert(mir_bus.name(), bus);
         Ok(())
     }
 
@@ -0,0 +1,167 @@
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsHandler, InFlightDiagnostic, Severity};
+
+use crate::{Air, CompileError, ConstraintRoot, NodeIndex, Operation, TraceSegmentId, Value};
+
+/// This pass detects integrity constraints which are structurally zero, i.e. which evaluate to
+/// zero regardless of the values in the trace, such as `a - a`.
+///
+/// Such constraints typically arise after inlining, e.g. when an evaluator enforcing `x = y` is
+/// called with the same column for both `x` and `y`. They enforce nothing, but still consume a
+/// random coefficient and contribute to the quotient, so a warning is emitted for each of them.
+///
+/// Optionally, these constraints can also be removed from the [Air].
+///
+/// A warning is also emitted for each integrity constraint which does not reference any trace
+/// column, such as `enf A = B` where `A` and `B` are constants. Such a constraint holds either for
+/// every trace or for none, so it is better expressed as a compile-time check, if it is not a
+/// mistake altogether. It is never removed, as it may not hold.
+pub struct TrivialConstraints<'a> {
+    diagnostics: &'a DiagnosticsHandler,
+    remove: bool,
+}
+impl<'a> TrivialConstraints<'a> {
+    /// Create a new instance of this pass, which only emits warnings
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+        Self { diagnostics, remove: false }
+    }
+
+    /// Sets whether the trivial constraints should also be removed from the [Air]
+    #[inline]
+    pub fn with_removal(mut self, remove: bool) -> Self {
+        self.remove = remove;
+        self
+    }
+}
+impl Pass for TrivialConstraints<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, mut ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        for segment in 0..ir.trace_segment_widths.len() {
+            let trivial: Vec<usize> = ir
+                .integrity_constraints(segment)
+                .iter()
+                .enumerate()
+                .filter(|(_, root)| is_zero(&ir, root.node_index()))
+                .map(|(i, _)| i)
+                .collect();
+
+            let roots = ir.integrity_constraints(segment);
+            for &i in trivial.iter() {
+                self.warn(segment, i, &ir, &roots[i]);
+            }
+
+            for (i, root) in roots.iter().enumerate() {
+                if !trivial.contains(&i) && !references_trace(&ir, root.node_index()) {
+                    self.warn_trace_free(segment, i, &ir, root);
+                }
+            }
+
+            if self.remove && !trivial.is_empty() {
+                let mut i = 0;
+                ir.constraints.retain_integrity_constraints(segment, |_| {
+                    let keep = !trivial.contains(&i);
+                    i += 1;
+                    keep
+                });
+            }
+        }
+
+        Ok(ir)
+    }
+}
+impl TrivialConstraints<'_> {
+    fn warn(&self, segment: TraceSegmentId, index: usize, ir: &Air, root: &ConstraintRoot) {
+        let segment_name = match segment {
+            0 => "main",
+            _ => "aux",
+        };
+        let note = if self.remove {
+            "This constraint has been removed."
+        } else {
+            "This can happen after inlining, e.g. when an evaluator is called with the same column for different parameters."
+        };
+        let diagnostic = self.diagnostics.diagnostic(Severity::Warning).with_message(format!(
+            "integrity constraint #{index} against the {segment_name} trace is identically zero"
+        ));
+        with_source(diagnostic, ir, root, "this constraint is identically zero")
+            .with_note("This constraint holds for any trace, and therefore enforces nothing.")
+            .with_note(note)
+            .emit();
+    }
+
+    fn warn_trace_free(
+        &self,
+        segment: TraceSegmentId,
+        index: usize,
+        ir: &Air,
+        root: &ConstraintRoot,
+    ) {
+        let segment_name = match segment {
+            0 => "main",
+            _ => "aux",
+        };
+        let diagnostic = self.diagnostics.diagnostic(Severity::Warning).with_message(format!(
+            "integrity constraint #{index} against the {segment_name} trace does not reference any trace column"
+        ));
+        with_source(diagnostic, ir, root, "this constraint does not reference any trace column")
+            .with_note("This constraint holds either for any trace, or for none.")
+            .with_note(
+                "Consider checking it at compile time instead, e.g. by computing its value with constants.",
+            )
+            .emit();
+    }
+}
+
+/// Labels `diagnostic` with the provenance of the constraint it is about, if the constraint was
+/// inlined from an evaluator, or else with the span of the statement in which it is written.
+fn with_source<'a>(
+    diagnostic: InFlightDiagnostic<'a>,
+    ir: &Air,
+    root: &ConstraintRoot,
+    message: &str,
+) -> InFlightDiagnostic<'a> {
+    match (ir.constraint_provenance(root), ir.constraint_span(root)) {
+        (Some(provenance), _) => provenance.label(diagnostic, message),
+        (None, Some(span)) => diagnostic.with_primary_label(span, message),
+        (None, None) => diagnostic,
+    }
+}
+
+/// Returns true if the subgraph rooted at `index` reads a trace column.
+fn references_trace(ir: &Air, index: &NodeIndex) -> bool {
+    let graph = ir.constraint_graph();
+    // The subgraph is a DAG, so each node is only visited once
+    let mut visited = vec![false; graph.num_nodes()];
+    let mut stack = vec![*index];
+    while let Some(index) = stack.pop() {
+        if core::mem::replace(&mut visited[usize::from(index)], true) {
+            continue;
+        }
+        match graph.node(&index).op() {
+            Operation::Value(Value::TraceAccess(_)) => return true,
+            Operation::Value(_) => {},
+            Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) => {
+                stack.extend([*lhs, *rhs]);
+            },
+        }
+    }
+    false
+}
+
+/// Returns true if the subgraph rooted at `index` evaluates to zero for any input.
+///
+/// This is a structural check: it recognizes the constant zero, differences of identical nodes,
+/// and sums, differences and products thereof.
+fn is_zero(ir: &Air, index: &NodeIndex) -> bool {
+    match ir.constraint_graph().node(index).op() {
+        Operation::Value(Value::Constant(0)) => true,
+        Operation::Value(_) => false,
+        Operation::Sub(lhs, rhs) if lhs == rhs => true,
+        Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) => is_zero(ir, lhs) && is_zero(ir, rhs),
+        Operation::Mul(lhs, rhs) => is_zero(ir, lhs) || is_zero(ir, rhs),
+    }
+}
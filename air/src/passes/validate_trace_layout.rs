@@ -0,0 +1,91 @@
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsHandler, Severity, Spanned};
+
+use crate::{AUX_SEGMENT, Air, CompileError, DEFAULT_SEGMENT, TraceLayoutSpec};
+
+/// This pass validates that an [Air] fits into the trace layout of a target, described by a
+/// [TraceLayoutSpec].
+///
+/// The following budgets are checked, and an error is emitted for each one which is exceeded:
+///
+/// * The width of the main trace, excluding the columns reserved by the target
+/// * The width of the aux trace, i.e. the number of buses
+/// * The number of random values
+///
+/// As the number of random values is only known once buses have been expanded, this pass is
+/// expected to run after [super::BusOpExpand]. The [Air] is returned unchanged if it fits.
+pub struct ValidateTraceLayout<'a> {
+    diagnostics: &'a DiagnosticsHandler,
+    spec: TraceLayoutSpec,
+}
+impl<'a> ValidateTraceLayout<'a> {
+    /// Create a new instance of this pass, validating against `spec`
+    #[inline]
+    pub fn new(diagnostics: &'a DiagnosticsHandler, spec: TraceLayoutSpec) -> Self {
+        Self { diagnostics, spec }
+    }
+}
+impl Pass for ValidateTraceLayout<'_> {
+    type Input<'a> = Air;
+    type Output<'a> = Air;
+    type Error = CompileError;
+
+    fn run<'a>(&mut self, ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
+        let width = |segment| ir.trace_segment_widths.get(segment).copied().unwrap_or(0) as usize;
+        let mut valid = true;
+
+        let main_width = width(DEFAULT_SEGMENT);
+        let available = self.spec.available_main_width();
+        if main_width > available {
+            let mut diagnostic = self
+                .diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("main trace width exceeds the budget of the target layout");
+            if let Some(name) = ir.trace_segment_names.get(DEFAULT_SEGMENT) {
+                diagnostic = diagnostic.with_primary_label(
+                    name.span(),
+                    format!(
+                        "this segment declares {main_width} columns, but at most {available} are available"
+                    ),
+                );
+            }
+            if self.spec.reserved_columns > 0 {
+                diagnostic = diagnostic.with_note(format!(
+                    "The target main trace has {} columns, {} of which are reserved.",
+                    self.spec.main_width, self.spec.reserved_columns
+                ));
+            }
+            diagnostic.emit();
+            valid = false;
+        }
+
+        let aux_width = width(AUX_SEGMENT);
+        if aux_width > self.spec.aux_width_max {
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("aux trace width exceeds the budget of the target layout")
+                .with_note(format!(
+                    "This program declares {aux_width} buses, but the target layout allows at most {} aux columns.",
+                    self.spec.aux_width_max
+                ))
+                .emit();
+            valid = false;
+        }
+
+        let num_random_values = ir.num_random_values as usize;
+        if num_random_values > self.spec.num_random_max {
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("number of random values exceeds the budget of the target layout")
+                .with_note(format!(
+                    "This program requires {num_random_values} random values, but the target layout provides at most {}.",
+                    self.spec.num_random_max
+                ))
+                .with_note("The number of random values is determined by the largest tuple inserted into or removed from a bus.")
+                .emit();
+            valid = false;
+        }
+
+        if valid { Ok(ir) } else { Err(CompileError::Failed) }
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    any::Any,
+    fmt::Write,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+use air_parser::ast;
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsHandler, Severity};
+pub use mir::passes::UnrollLimits;
+use mir::{ir::Mir, passes::FailurePoint};
+
+use crate::{Air, CompileError, TraceLayoutSpec, passes, passes::DEFAULT_MAX_EXPONENT};
+
+/// Controls which passes of the canonical pipeline are run by [compile], e.g. to bisect the pass
+/// introducing a miscompilation.
+///
+/// The translations from the AST to MIR and from MIR to [Air] always run. Every other pass is
+/// enabled by default, except [PassConfig::canonicalize], [PassConfig::dead_random_values] and
+/// the opt-in lints. Some of them are required for the resulting [Air] to be correct, so a
+/// warning is emitted when they are disabled, see [PassConfig::skipped_mandatory_passes].
+///
+/// It also holds the limits enforced by the passes, e.g. on the size of unrolled comprehensions
+/// or on the exponents of exponentiations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PassConfig {
+    /// Folds constants and constant expressions in the AST, required by the translation to MIR
+    pub constant_propagation: bool,
+    /// Inlines function and evaluator calls in the MIR, required by the translation to [Air]
+    pub inlining: bool,
+    /// Unrolls comprehensions and folds in the MIR, required by the translation to [Air]
+    pub unrolling: bool,
+    /// Warns about integrity constraints which are structurally zero
+    pub trivial_constraints: bool,
+    /// Warns about the columns of selectors which are not constrained to be binary
+    pub selector_booleanity: bool,
+    /// Warns about the columns defined by a recurrence without a boundary constraint on their
+    /// first row. Disabled by default, as this is an opt-in lint.
+    pub recurrence_boundaries: bool,
+    /// Expands bus operations into constraints on the aux trace, required to enforce buses
+    pub bus_op_expand: bool,
+    /// Removes identity operations from the constraint graph
+    pub simplify: bool,
+    /// Rewrites the constraint graph into a canonical form, then removes the integrity constraints
+    /// which have become identical to another one. Disabled by default, as it changes the order of
+    /// the nodes, and therefore of the expressions in the generated code.
+    pub canonicalize: bool,
+    /// Removes the random values which no constraint depends on, and renumbers the others densely.
+    /// Disabled by default, as the prover must then build the aux trace from the renumbered random
+    /// values.
+    pub dead_random_values: bool,
+    /// Keeps all the random values, even when [PassConfig::dead_random_values] is enabled, for
+    /// programs whose random values are coordinated with another party, e.g. drawn by a verifier
+    /// shared with other programs. Disabled by default.
+    pub export_random_values: bool,
+    /// Warns about the columns which are copies of an expression of the previous row, and could
+    /// be eliminated. Disabled by default, as this is an opt-in lint.
+    pub copy_columns: bool,
+    /// The limits on the number of operations produced by the Unrolling pass
+    pub unroll_limits: UnrollLimits,
+    /// The maximum exponent of an exponentiation, which is expanded into multiplications when
+    /// translating the MIR to [Air]
+    pub max_exponent: u64,
+    /// The trace layout of the target which the resulting [Air] is validated against, if any
+    pub target: Option<TraceLayoutSpec>,
+}
+impl Default for PassConfig {
+    fn default() -> Self {
+        Self {
+            constant_propagation: true,
+            inlining: true,
+            unrolling: true,
+            trivial_constraints: true,
+            selector_booleanity: true,
+            recurrence_boundaries: false,
+            bus_op_expand: true,
+            simplify: true,
+            canonicalize: false,
+            dead_random_values: false,
+            export_random_values: false,
+            copy_columns: false,
+            unroll_limits: UnrollLimits::default(),
+            max_exponent: DEFAULT_MAX_EXPONENT,
+            target: None,
+        }
+    }
+}
+impl PassConfig {
+    /// Returns the names of the passes required for correctness which are disabled.
+    pub fn skipped_mandatory_passes(&self) -> Vec<&'static str> {
+        [
+            ("ConstantPropagation", self.constant_propagation),
+            ("Inlining", self.inlining),
+            ("Unrolling", self.unrolling),
+            ("BusOpExpand", self.bus_op_expand),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| (!enabled).then_some(name))
+        .collect()
+    }
+
+    /// Describes this configuration as a list of options, e.g. `max-exponent=64`, which are
+    /// recorded in the provenance header of generated artifacts.
+    ///
+    /// The passes which are enabled by default are only listed when disabled, as `skip=<pass>`,
+    /// and `canonicalize`, the options on random values and the lints are listed when enabled, e.g.
+    /// `lint=copy-columns`. The limits are always listed, so that the configuration can be
+    /// reproduced even if their defaults change.
+    pub fn options(&self) -> Vec<String> {
+        let mut options: Vec<String> = [
+            ("ConstantPropagation", self.constant_propagation),
+            ("Inlining", self.inlining),
+            ("Unrolling", self.unrolling),
+            ("TrivialConstraints", self.trivial_constraints),
+            ("SelectorBooleanity", self.selector_booleanity),
+            ("BusOpExpand", self.bus_op_expand),
+            ("Simplify", self.simplify),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| !enabled)
+        .map(|(name, _)| format!("skip={name}"))
+        .collect();
+        if self.canonicalize {
+            options.push("canonicalize".to_string());
+        }
+        if self.dead_random_values {
+            options.push("dead-random-values".to_string());
+        }
+        if self.export_random_values {
+            options.push("export-random-values".to_string());
+        }
+        if self.recurrence_boundaries {
+            options.push("lint=recurrence-boundaries".to_string());
+        }
+        if self.copy_columns {
+            options.push("lint=copy-columns".to_string());
+        }
+        options.push(format!("max-unroll={}", self.unroll_limits.max_iterations));
+        options.push(format!("max-unrolled-ops={}", self.unroll_limits.max_ops));
+        options.push(format!("max-exponent={}", self.max_exponent));
+        options
+    }
+}
+
+/// Compiles `program` to an [Air] through the MIR, running the passes enabled by `config`.
+pub fn compile(
+    diagnostics: &DiagnosticsHandler,
+    program: ast::Program,
+    config: PassConfig,
+) -> Result<Air, CompileError> {
+    Stages { diagnostics, repro: None }.compile(program, config)
+}
+
+/// Compiles `program` like [compile], but writes a reproduction of internal errors to `repro`,
+/// which can be attached to a bug report.
+///
+/// An internal error is either a panic of a pass, which is reported as a [Severity::Bug]
+/// diagnostic rather than unwinding through the caller, or an error reported by a pass with
+/// such a diagnostic. The reproduction names the failing pass, and holds the root of the MIR
+/// graph it was visiting when it failed, formatted with [mir::ir::Link::debug], or else the IR
+/// it was run on.
+pub fn compile_with_repro(
+    diagnostics: &DiagnosticsHandler,
+    program: ast::Program,
+    config: PassConfig,
+    repro: &Path,
+) -> Result<Air, CompileError> {
+    Stages { diagnostics, repro: Some(repro) }.compile(program, config)
+}
+
+/// Runs the passes of the pipeline, see [compile_with_repro]
+pub(crate) struct Stages<'a> {
+    pub diagnostics: &'a DiagnosticsHandler,
+    pub repro: Option<&'a Path>,
+}
+impl Stages<'_> {
+    fn compile(&self, program: ast::Program, config: PassConfig) -> Result<Air, CompileError> {
+        let diagnostics = self.diagnostics;
+        for pass in config.skipped_mandatory_passes() {
+            diagnostics
+                .diagnostic(Severity::Warning)
+                .with_message(format!("the {pass} pass is disabled"))
+                .with_note(
+                    "This pass is required for correctness, the resulting AIR may be invalid.",
+                )
+                .emit();
+        }
+
+        let program = if config.constant_propagation {
+            self.run("ConstantPropagation", program, |program| {
+                Ok(air_parser::transforms::ConstantPropagation::new(diagnostics).run(program)?)
+            })?
+        } else {
+            program
+        };
+        let mut mir = self.run("AstToMir", program, |program| {
+            Ok(mir::passes::AstToMir::new(diagnostics).run(program)?)
+        })?;
+        if config.inlining {
+            mir = self.run("Inlining", mir, |mir| {
+                Ok(mir::passes::Inlining::new(diagnostics).run(mir)?)
+            })?;
+        }
+        if config.unrolling {
+            mir = self.run("Unrolling", mir, |mir| {
+                Ok(mir::passes::Unrolling::new(diagnostics)
+                    .with_limits(config.unroll_limits)
+                    .run(mir)?)
+            })?;
+        }
+
+        let mut air = self.run("MirToAir", mir, |mir| {
+            passes::MirToAir::new(diagnostics)
+                .with_max_exponent(config.max_exponent)
+                .run(mir)
+        })?;
+        if config.trivial_constraints {
+            air = self.run("TrivialConstraints", air, |air| {
+                passes::TrivialConstraints::new(diagnostics).run(air)
+            })?;
+        }
+        if config.selector_booleanity {
+            air = self.run("SelectorBooleanity", air, |air| {
+                passes::SelectorBooleanity::new(diagnostics).run(air)
+            })?;
+        }
+        if config.recurrence_boundaries {
+            air = self.run("RecurrenceBoundaries", air, |air| {
+                passes::RecurrenceBoundaries::new(diagnostics).run(air)
+            })?;
+        }
+        if config.bus_op_expand {
+            air =
+                self.run("BusOpExpand", air, |air| passes::BusOpExpand::new(diagnostics).run(air))?;
+        }
+        if config.simplify {
+            air = self.run("Simplify", air, |air| passes::Simplify::new(diagnostics).run(air))?;
+        }
+        if config.dead_random_values && !config.export_random_values {
+            air = self.run("DeadRandomValues", air, |air| {
+                passes::DeadRandomValues::new(diagnostics).run(air)
+            })?;
+        }
+        if config.copy_columns {
+            air =
+                self.run("CopyColumns", air, |air| passes::CopyColumns::new(diagnostics).run(air))?;
+        }
+        if config.canonicalize {
+            air = self.run("Canonicalize", air, |air| {
+                passes::Canonicalize::new(diagnostics)
+                    .chain(passes::DuplicateConstraints::new(diagnostics))
+                    .run(air)
+            })?;
+        }
+        if let Some(spec) = config.target {
+            air = self.run("ValidateTraceLayout", air, |air| {
+                passes::ValidateTraceLayout::new(diagnostics, spec).run(air)
+            })?;
+        }
+        Ok(air)
+    }
+
+    /// Runs the pass named `name` on `input`, writing a reproduction of its internal errors if
+    /// requested
+    pub fn run<I: Repro, O>(
+        &self,
+        name: &str,
+        input: I,
+        pass: impl FnOnce(I) -> Result<O, CompileError>,
+    ) -> Result<O, CompileError> {
+        let Some(path) = self.repro else {
+            return pass(input);
+        };
+
+        // The input is formatted upfront, as the pass consumes it
+        let snapshot = input.repro();
+        mir::passes::take_failure_point();
+        let (message, failure_point) = match panic::catch_unwind(AssertUnwindSafe(|| pass(input))) {
+            Ok(Ok(output)) => return Ok(output),
+            Ok(Err(err)) => match mir::passes::take_failure_point() {
+                Some(failure_point) if failure_point.internal => (None, failure_point),
+                _ => return Err(err),
+            },
+            Err(payload) => {
+                let message = panic_message(payload.as_ref());
+                self.diagnostics
+                    .diagnostic(Severity::Bug)
+                    .with_message(format!("internal error in the {name} pass: {message}"))
+                    .emit();
+                (Some(message), mir::passes::take_failure_point().unwrap_or_default())
+            },
+        };
+
+        let report = repro_report(name, message.as_deref(), &failure_point, &snapshot);
+        match fs::write(path, report) {
+            Ok(()) => self
+                .diagnostics
+                .diagnostic(Severity::Note)
+                .with_message(format!(
+                    "a reproduction of this internal error was written to {}",
+                    path.display()
+                ))
+                .with_note("Please attach it to a bug report.")
+                .emit(),
+            Err(err) => self
+                .diagnostics
+                .diagnostic(Severity::Warning)
+                .with_message(format!(
+                    "unable to write a reproduction of this internal error to {}: {err}",
+                    path.display()
+                ))
+                .emit(),
+        }
+        Err(CompileError::Failed)
+    }
+}
+
+/// The IR a pass is run on, as written in the reproduction of its internal errors
+pub(crate) trait Repro {
+    fn repro(&self) -> String;
+}
+impl Repro for ast::Program {
+    fn repro(&self) -> String {
+        self.to_string()
+    }
+}
+impl Repro for Mir {
+    fn repro(&self) -> String {
+        let graph = self.constraint_graph();
+        let roots = graph.get_function_nodes().into_iter().chain(graph.get_evaluator_nodes());
+        let constraints = graph
+            .boundary_constraints_roots
+            .borrow()
+            .iter()
+            .chain(graph.integrity_constraints_roots.borrow().iter())
+            .map(|root| root.debug())
+            .collect::<Vec<_>>();
+        roots.map(|root| root.debug()).chain(constraints).collect::<Vec<_>>().join("\n")
+    }
+}
+impl Repro for Air {
+    fn repro(&self) -> String {
+        format!("{self:#?}")
+    }
+}
+
+fn repro_report(
+    pass: &str,
+    message: Option<&str>,
+    failure_point: &FailurePoint,
+    snapshot: &str,
+) -> String {
+    let mut report = format!("internal error in the {pass} pass");
+    if let Some(message) = message {
+        write!(report, ": {message}").unwrap();
+    }
+    match failure_point.root.as_ref() {
+        Some(root) => write!(report, "\n\nfailure point:\n{root}\n").unwrap(),
+        None => write!(report, "\n\ninput of the pass:\n{snapshot}\n").unwrap(),
+    }
+    report
+}
+
+/// Returns the message of a panic, if it was raised with one
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "unknown panic".to_string(),
+        },
+    }
+}
@@ -1,4 +1,16 @@
-use super::{Pipeline, compile, expect_diagnostic};
+use air_parser::ast::Boundary as BoundaryKind;
+use air_pass::Pass;
+use miden_diagnostics::{SourceSpan, Spanned};
+use mir::ir::{Boundary, Enf, MirValue, SpannedMirValue, Sub, TraceAccess, Value};
+
+use super::{
+    Compiler, Pipeline, assert_equivalent_boundary_constraints,
+    assert_equivalent_integrity_constraints, compile, expect_diagnostic,
+};
+use crate::{
+    Identifier, Symbol,
+    passes::{MirToAir, RecurrenceBoundaries},
+};
 
 #[test]
 fn boundary_constraints() {
@@ -44,6 +56,129 @@ fn err_bc_duplicate_first() {
     expect_diagnostic(source, "overlapping boundary constraints", Pipeline::WithMIR);
 }
 
+#[test]
+fn trace_column_initial_values() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk = 0, a = stack_inputs[1] + 1, b[2]],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf b[0].first = 1;
+        enf clk.last = 1;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [clk, a, b[2]],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+        enf a.first = stack_inputs[1] + 1;
+        enf b[0].first = 1;
+        enf clk.last = 1;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_boundary_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn boundary_constraints_indexed_trace_access() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk, a, b[2]],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf $main[0].first = 0;
+        enf $main[1].first = stack_inputs[1] + 1;
+        enf $main[3].last = 1;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [clk, a, b[2]],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+        enf a.first = stack_inputs[1] + 1;
+        enf b[1].last = 1;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_boundary_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn err_bc_duplicate_indexed_trace_access() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk, a, b[2]],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf b[1].first = 0;
+        enf $main[3].first = 1;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+
+    expect_diagnostic(source, "overlapping boundary constraints", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "overlapping boundary constraints", Pipeline::WithMIR);
+}
+
+#[test]
+fn err_bc_duplicate_initial_value() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk = 0],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 1;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+
+    expect_diagnostic(source, "overlapping boundary constraints", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "overlapping boundary constraints", Pipeline::WithMIR);
+}
+
 #[test]
 fn err_bc_duplicate_last() {
     let source = "
@@ -65,3 +200,350 @@ fn err_bc_duplicate_last() {
     expect_diagnostic(source, "overlapping boundary constraints", Pipeline::WithoutMIR);
     expect_diagnostic(source, "overlapping boundary constraints", Pipeline::WithMIR);
 }
+
+#[test]
+fn err_bc_segment_mismatch_uses_segment_names() {
+    // The main and aux segments are named in diagnostics, using the configured name for the aux
+    // segment. As AirScript has no syntax to reference the aux segment directly, the mismatched
+    // constraint is injected in the MIR.
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    buses {
+        multiset p,
+    }
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = null;
+        enf a.last = 0;
+    }
+    integrity_constraints {
+        enf a = 0;
+    }";
+
+    let compiler = Compiler::default();
+    let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), source)
+        .expect("parsing failed");
+    let mut mir = air_parser::transforms::ConstantPropagation::new(&compiler.diagnostics)
+        .chain(mir::passes::AstToMir::new(&compiler.diagnostics))
+        .chain(mir::passes::Inlining::new(&compiler.diagnostics))
+        .chain(mir::passes::Unrolling::new(&compiler.diagnostics))
+        .run(ast)
+        .expect("translation to MIR failed");
+
+    // enf a.first = p, where `p` is the bus column in the aux segment
+    let span = mir.constraint_graph().boundary_constraints_roots.borrow()[0].span();
+    let value = |value| Value::create(SpannedMirValue { span, value });
+    let lhs = Boundary::create(
+        value(MirValue::TraceAccess(TraceAccess::new(0, 0, 0))),
+        BoundaryKind::First,
+        span,
+    );
+    let rhs = value(MirValue::TraceAccess(TraceAccess::new(1, 0, 0)));
+    let root = Enf::create(Sub::create(lhs, rhs, span), span);
+    mir.constraint_graph_mut().boundary_constraints_roots.borrow_mut().push(root);
+
+    let aux_name = Identifier::new(SourceSpan::UNKNOWN, Symbol::intern("bus_trace"));
+    let result = MirToAir::new(&compiler.diagnostics).with_aux_segment_name(aux_name).run(mir);
+    assert!(result.is_err());
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("invalid boundary constraint"));
+    assert!(captured.contains("this constrains a column in the '$main' trace segment"));
+    assert!(captured.contains("but this expression implies the 'bus_trace' trace segment"));
+}
+
+#[test]
+fn boundary_constraints_from_row_selectors() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk, a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+        enf $first * (a - 5) = 0;
+        enf (a - stack_inputs[0]) * $last = 0;
+        enf $last * clk = 0;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [clk, a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+        enf a.first = 5;
+        enf a.last = stack_inputs[0];
+        enf clk.last = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_boundary_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn boundary_constraints_from_normalised_row_selectors() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk, a, b, c],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+        enf $last * (5 - a) = 0;
+        enf (stack_inputs[0] - b) * $first = 0;
+        enf $first * (c + 3) = 0;
+        enf (7 + a) * $first = 0;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [clk, a, b, c],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+        enf a.last = 5;
+        enf b.first = stack_inputs[0];
+        enf c.first = 0 - 3;
+        enf a.first = 0 - 7;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_boundary_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn err_row_selector_on_expression_of_columns() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk, a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+        enf $last * (a * b - 1) = 0;
+    }";
+
+    expect_diagnostic(source, "invalid use of builtin row selector", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "invalid use of builtin row selector", Pipeline::WithMIR);
+}
+
+#[test]
+fn running_product_constraints() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk, p, q[2], a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+        enf running_product(p, a + 7);
+        enf running_product(q[1], a * clk);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [clk, p, q[2], a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+        enf p.first = 1;
+        enf q[1].first = 1;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+        enf p' = p * (a + 7);
+        enf q[1]' = q[1] * (a * clk);
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_boundary_constraints(source, expected, pipeline);
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn err_running_product_of_invalid_column() {
+    let source = |column: &str| {
+        format!(
+            "
+    def test
+    trace_columns {{
+        main: [clk, q[2], a],
+    }}
+    buses {{
+        multiset b,
+    }}
+    public_inputs {{
+        stack_inputs: [16],
+    }}
+    boundary_constraints {{
+        enf clk.first = 0;
+        enf b.first = null;
+    }}
+    integrity_constraints {{
+        enf clk' = clk + 1;
+        enf running_product({column}, a);
+    }}"
+        )
+    };
+
+    for column in ["q", "q[2]", "clk'", "stack_inputs[0]", "b"] {
+        for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+            expect_diagnostic(&source(column), "expected a single trace column", pipeline);
+        }
+    }
+    expect_diagnostic(
+        &source("b"),
+        "Buses maintain their running product themselves",
+        Pipeline::WithMIR,
+    );
+    expect_diagnostic(&source("clk, a"), "expected exactly two arguments", Pipeline::WithMIR);
+}
+
+#[test]
+fn boundary_diagnostics_carry_error_codes() {
+    let overlapping = "
+    def test
+    trace_columns {
+        main: [clk],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.last = 0;
+        enf clk.last = 1;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        expect_diagnostic(overlapping, "error[E0001]: overlapping boundary constraints", pipeline);
+        expect_diagnostic(overlapping, "try `airc --explain E0001`", pipeline);
+    }
+
+    let domain_mismatch = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = b';
+    }
+    integrity_constraints {
+        enf a' = b;
+    }";
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        expect_diagnostic(domain_mismatch, "error[E0002]: invalid expression", pipeline);
+    }
+}
+
+#[test]
+fn warn_recurrence_without_initial_boundary() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, s],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+        enf s.last = 0;
+    }
+    integrity_constraints {
+        enf a' = a + 1;
+        enf b' = b + a;
+        enf s' = s * b when a;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    RecurrenceBoundaries::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains(
+        "column `a` is defined by a recurrence, but its value in the first row is not constrained"
+    ));
+    assert!(
+        captured.contains("Integrity constraint #0 against the main trace defines `a'` from `a`")
+    );
+    assert!(captured.contains("e.g. `enf a.first = 0;`"));
+    // A boundary on the last row does not pin the initial value
+    assert!(captured.contains("column `s` is defined by a recurrence"));
+    assert!(!captured.contains("column `b`"));
+}
+
+#[test]
+fn recurrence_with_initial_boundary() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b[2]],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = stack_inputs[0];
+        enf b[1].first = 1;
+    }
+    integrity_constraints {
+        enf a' = a + 1;
+        enf b[1]' = b[1] * a when b[0];
+        enf b[0]' = a;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    RecurrenceBoundaries::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(!captured.contains("is defined by a recurrence"), "{captured}");
+}
@@ -1,4 +1,16 @@
-use super::{Pipeline, compile, expect_diagnostic};
+use air_parser::ast::TraceSegment;
+use air_pass::Pass;
+use miden_diagnostics::{SourceSpan, Span};
+use winter_math::fields::f64::BaseElement as Felt;
+
+use super::{
+    Compiler, Pipeline, assert_equivalent_integrity_constraints, compile, expect_diagnostic,
+};
+use crate::{
+    AUX_SEGMENT, Air, BusBoundary, BusOpKind, BusType, CompileError, ConstraintEvaluator,
+    DEFAULT_SEGMENT, EvalContext, Identifier, QuadFelt, RootKind, Symbol,
+    passes::{Canonicalize, MirToAir, Simplify},
+};
 
 #[test]
 fn buses_in_boundary_constraints() {
@@ -74,6 +86,87 @@ fn buses_in_integrity_constraints() {
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
 
+#[test]
+fn buses_with_selector_and_multiplicity() {
+    let source = "
+        def test
+
+    trace_columns {
+        main: [a, s, m],
+    }
+
+    buses {
+        logup q,
+    }
+
+    public_inputs {
+        inputs: [[2]],
+    }
+
+    boundary_constraints {
+        enf q.first = null;
+        enf q.last = null;
+    }
+
+    integrity_constraints {
+        q.insert(a) when s with m;
+        q.remove(a) with m;
+    }";
+
+    let combined = compile(source, Pipeline::WithMIR).expect("compilation failed");
+
+    // The effective multiplicity of a bus operation with both a selector and a multiplicity is
+    // their product
+    let product = source.replace("when s with m", "with s * m");
+    let expected = compile(&product, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(format!("{:?}", combined.constraints), format!("{:?}", expected.constraints));
+}
+
+#[test]
+fn bus_multiplicity_range() {
+    let source = "
+        def test
+
+    trace_columns {
+        main: [a, s, m, bits[4]],
+    }
+
+    buses {
+        logup q with bits,
+    }
+
+    public_inputs {
+        inputs: [[2]],
+    }
+
+    boundary_constraints {
+        enf q.first = null;
+        enf q.last = null;
+    }
+
+    integrity_constraints {
+        q.insert(a) when s with m;
+        q.remove(a) when s;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let range = air.buses.values().next().unwrap().multiplicity_range.as_ref().unwrap();
+    assert_eq!(range.bits, [3, 4, 5, 6]);
+    assert_eq!(range.bus_op, 0);
+
+    // Each of the bits is binary, and the effective multiplicity is their weighted sum
+    let expected = source.replace("logup q with bits,", "logup q,").replace(
+        "q.insert(a) when s with m;",
+        "q.insert(a) when s with m;
+        enf bits[0]^2 = bits[0];
+        enf bits[1]^2 = bits[1];
+        enf bits[2]^2 = bits[2];
+        enf bits[3]^2 = bits[3];
+        enf s * m = bits[0] + 2 * bits[1] + 4 * bits[2] + 8 * bits[3];",
+    );
+    assert_equivalent_integrity_constraints(source, &expected, Pipeline::WithMIR);
+}
+
 // Tests that should return errors
 #[test]
 fn err_buses_boundaries_to_const() {
@@ -171,3 +264,648 @@ fn err_buses_unconstrained() {
     );
     expect_diagnostic(source, "error: invalid bus boundary", Pipeline::WithMIR);
 }
+
+#[test]
+fn err_multiset_bus_with_selector_and_multiplicity() {
+    let source = "
+        def test
+
+    trace_columns {
+        main: [a, s, m],
+    }
+
+    buses {
+        multiset p,
+    }
+
+    public_inputs {
+        inputs: [[2]],
+    }
+
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = null;
+    }
+
+    integrity_constraints {
+        p.insert(a) when s with m;
+        p.remove(a) when s;
+    }";
+
+    expect_diagnostic(
+        source,
+        "a multiplicity cannot be combined with a selector on a multiset bus",
+        Pipeline::WithMIR,
+    );
+}
+
+#[test]
+fn buses_use_aux_segment() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    buses {
+        multiset p,
+    }
+    public_inputs {
+        inputs: [[2]],
+    }
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = inputs;
+    }
+    integrity_constraints {
+        p.insert(a) when a;
+        enf a^2 = a;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    assert!(air.has_aux_segment());
+    assert!(air.uses_random_values());
+}
+
+#[test]
+fn err_bus_multiplicity_range_on_multiset() {
+    let source = "
+        def test
+
+    trace_columns {
+        main: [a, s, bits[4]],
+    }
+
+    buses {
+        multiset p with bits,
+    }
+
+    public_inputs {
+        inputs: [[2]],
+    }
+
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = null;
+    }
+
+    integrity_constraints {
+        p.insert(a) when s;
+    }";
+
+    expect_diagnostic(
+        source,
+        "the multiplicity of a multiset bus cannot be range checked",
+        Pipeline::WithMIR,
+    );
+}
+
+#[test]
+fn err_bus_multiplicity_range_shared_by_operations() {
+    let source = "
+        def test
+
+    trace_columns {
+        main: [a, m, n, bits[4]],
+    }
+
+    buses {
+        logup q with bits,
+    }
+
+    public_inputs {
+        inputs: [[2]],
+    }
+
+    boundary_constraints {
+        enf q.first = null;
+        enf q.last = null;
+    }
+
+    integrity_constraints {
+        q.insert(a) with m;
+        q.remove(a) with n;
+    }";
+
+    expect_diagnostic(
+        source,
+        "the multiplicity of another operation of this bus is already range checked",
+        Pipeline::WithMIR,
+    );
+
+    let source = source
+        .replace("q.remove(a) with n;", "")
+        .replace("q.insert(a) with m;", "q.insert(a) when m;");
+    expect_diagnostic(&source, "none of its operations has a multiplicity", Pipeline::WithMIR);
+}
+
+#[test]
+fn bus_column_layout() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    buses {
+        multiset q,
+        logup p,
+    }
+    public_inputs {
+        inputs: [2],
+    }
+    boundary_constraints {
+        enf q.first = null;
+        enf q.last = null;
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        q.insert(a) when a;
+        q.remove(a) when 1 - a;
+        p.insert(b, 2) when b;
+        p.remove(b, 2) with 3;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    // Buses are allocated aux columns in name order, rather than in declaration order
+    let layout = air.bus_column_layout();
+    let layout: Vec<_> = layout.iter().map(|(name, column)| (name.as_str(), *column)).collect();
+    assert_eq!(layout, [("p", 0), ("q", 1)]);
+    assert_eq!(air.trace_segment_widths[AUX_SEGMENT], 2);
+}
+
+#[test]
+fn bus_column_layout_is_empty_without_buses() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    public_inputs {
+        inputs: [2],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a^2 = a;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    assert!(air.bus_column_layout().is_empty());
+}
+
+#[test]
+fn named_bus_selector() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a, b, s, m],
+    }
+
+    buses {
+        multiset p,
+        logup q,
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = null;
+        enf q.first = null;
+        enf q.last = null;
+    }
+
+    integrity_constraints {
+        p.insert(a) when s * b as g;
+        enf a' = a + 1 when g;
+        q.insert(b) when 1 - s as h with m;
+        enf b' = b when h;
+        q.remove(b) with m;
+        p.remove(a) when g;
+    }";
+    let named = compile(source, Pipeline::WithMIR).expect("compilation failed");
+
+    // The selector of a bus operation can be shared with the following constraints
+    let expected = source
+        .replace(" as g", "")
+        .replace("when g", "when s * b")
+        .replace(" as h", "")
+        .replace("when h", "when 1 - s");
+    let expected = compile(&expected, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(format!("{:?}", named.constraints), format!("{:?}", expected.constraints));
+}
+
+#[test]
+fn err_named_bus_selector_out_of_scope() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a, s],
+    }
+
+    buses {
+        multiset p,
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = null;
+    }
+
+    integrity_constraints {
+        enf a' = a when g;
+        p.insert(a) when s as g;
+        p.remove(a) when g;
+    }";
+
+    expect_diagnostic(source, "undefined variable", Pipeline::WithMIR);
+}
+
+#[test]
+fn table_row_destructuring() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a],
+    }
+
+    buses {
+        multiset p,
+        logup q,
+    }
+
+    public_inputs {
+        overflow: [[clk, value, prev]],
+    }
+
+    boundary_constraints {
+        enf p.first = [(clk, value, prev) for (clk, value, prev) in overflow];
+        enf p.last = null;
+        enf q.first = [(row[0], row[1], row[2]) for row in overflow];
+        enf q.last = null;
+    }
+
+    integrity_constraints {
+        p.insert(a, a, a) when a;
+        q.remove(a, a, a) when a;
+    }";
+    let named = compile(source, Pipeline::WithMIR).expect("compilation failed");
+
+    // Destructuring the rows of a table is the same as using the table directly
+    let expected = source
+        .replace("[[clk, value, prev]]", "[[3]]")
+        .replace("[(clk, value, prev) for (clk, value, prev) in overflow]", "overflow")
+        .replace("[(row[0], row[1], row[2]) for row in overflow]", "overflow");
+    let expected = compile(&expected, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(format!("{:?}", named.buses), format!("{:?}", expected.buses));
+    assert_eq!(format!("{:?}", named.constraints), format!("{:?}", expected.constraints));
+}
+
+#[test]
+fn err_table_row_destructuring_arity() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a],
+    }
+
+    buses {
+        multiset p,
+    }
+
+    public_inputs {
+        overflow: [[clk, value, prev]],
+    }
+
+    boundary_constraints {
+        enf p.first = [(clk, value) for (clk, value) in overflow];
+        enf p.last = null;
+    }
+
+    integrity_constraints {
+        p.insert(a, a, a) when a;
+    }";
+
+    expect_diagnostic(source, "this destructures rows into 2 columns", Pipeline::WithMIR);
+    expect_diagnostic(source, "but `overflow` is declared with 3 columns here", Pipeline::WithMIR);
+}
+
+#[test]
+fn err_table_row_destructuring_out_of_order() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a],
+    }
+
+    buses {
+        multiset p,
+    }
+
+    public_inputs {
+        overflow: [[clk, value]],
+    }
+
+    boundary_constraints {
+        enf p.first = [(value, clk) for (clk, value) in overflow];
+        enf p.last = null;
+    }
+
+    integrity_constraints {
+        p.insert(a, a) when a;
+    }";
+
+    expect_diagnostic(source, "expected the columns of `overflow` in order", Pipeline::WithMIR);
+}
+
+const BUSES_SOURCE: &str = "
+    def test
+
+    trace_columns {
+        main: [a, s],
+    }
+
+    buses {
+        multiset p,
+        logup q,
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf p.first = null;
+        enf q.first = null;
+        enf p.last = null;
+        enf q.last = null;
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        p.insert(a) when s;
+        q.remove(a) with s;
+    }";
+
+/// Lowers [BUSES_SOURCE] to MIR, in which an aux trace segment is declared with the given
+/// columns, and translates it to [Air].
+fn compile_with_aux_columns(compiler: &Compiler, columns: &[&str]) -> Result<Air, CompileError> {
+    let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), BUSES_SOURCE)
+        .expect("parsing failed");
+    let mut mir = air_parser::transforms::ConstantPropagation::new(&compiler.diagnostics)
+        .chain(mir::passes::AstToMir::new(&compiler.diagnostics))
+        .chain(mir::passes::Inlining::new(&compiler.diagnostics))
+        .chain(mir::passes::Unrolling::new(&compiler.diagnostics))
+        .run(ast)
+        .expect("translation to MIR failed");
+
+    // The aux columns are not part of the source, so they borrow the span of the main segment,
+    // which diagnostics can point at
+    let span = mir.trace_columns[0].span;
+    let bindings = columns
+        .iter()
+        .map(|name| Span::new(span, (Identifier::new(span, Symbol::intern(*name)), 1)))
+        .collect();
+    let name = Identifier::new(span, Symbol::intern("aux"));
+    mir.trace_columns.push(TraceSegment::new(span, AUX_SEGMENT, name, bindings));
+
+    MirToAir::new(&compiler.diagnostics).run(mir)
+}
+
+#[test]
+fn buses_are_bound_after_declared_aux_columns() {
+    let compiler = Compiler::default();
+    let air = compile_with_aux_columns(&compiler, &["x", "y"]).expect("compilation failed");
+
+    assert_eq!(air.trace_segment_widths, [2, 4]);
+    let layout = air.bus_column_layout();
+    let offset = |bus| layout[&Identifier::new(SourceSpan::UNKNOWN, Symbol::intern(bus))];
+    assert_eq!(offset("p"), 2);
+    assert_eq!(offset("q"), 3);
+}
+
+#[test]
+fn err_bus_conflicts_with_declared_aux_column() {
+    let compiler = Compiler::default();
+    assert!(compile_with_aux_columns(&compiler, &["x", "q"]).is_err());
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("invalid bus declaration"));
+    assert!(captured.contains("this bus conflicts with a column of the aux trace segment"));
+}
+
+#[test]
+fn expanded_buses_follow_graph_rewrites() {
+    let source = "
+        def test
+
+    trace_columns {
+        main: [a, s],
+    }
+
+    buses {
+        multiset p,
+        logup q,
+    }
+
+    public_inputs {
+        inputs: [[2]],
+    }
+
+    boundary_constraints {
+        enf p.first = null;
+        enf q.first = null;
+        enf p.last = null;
+        enf q.last = null;
+    }
+
+    integrity_constraints {
+        p.insert(a) when s;
+        p.remove(a + 0) when 1;
+        q.insert(a, 2) when s;
+        q.remove(a * 1, 2) with 2;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let air = Simplify::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let air = Canonicalize::new(&compiler.diagnostics).run(air).expect("pass failed");
+
+    let ops: Vec<_> = air.expanded_buses().iter().map(|bus| bus.ops.len()).collect();
+    assert_eq!(ops, [2, 2]);
+    for (bus, root) in air.expanded_buses().iter().zip(air.integrity_constraints(AUX_SEGMENT)) {
+        assert_eq!(bus.root, *root.node_index());
+        assert_eq!(air.expanded_bus(root), Some(bus));
+        for op in bus.ops.iter() {
+            // The rewritten nodes belong to the rewritten graph
+            air.constraint_graph().node(&op.args_combined);
+            air.constraint_graph().node(&op.latch);
+        }
+    }
+}
+
+#[test]
+fn combine_matches_multiset_bus_expansion() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, s, t],
+    }
+    buses {
+        multiset p,
+    }
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        enf s^2 = s;
+        p.insert(a, b) when s;
+        p.remove(a, b) when t;
+    }";
+    // The same permutation argument, written by hand on a main column `p`, with the random values
+    // given as the columns `alpha`
+    let manual = "
+    def test
+    trace_columns {
+        main: [a, b, s, t, p, alpha[3]],
+    }
+    boundary_constraints {
+        enf p.first = 1;
+    }
+    integrity_constraints {
+        enf s^2 = s;
+        enf p * (combine([a, b], alpha) * s + 1 - s) = p' * (combine([a, b], alpha) * t + 1 - t);
+    }";
+    let bus = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let manual = compile(manual, Pipeline::WithMIR).expect("compilation failed");
+
+    let felt = |value: u64| QuadFelt::from(Felt::new(value));
+    let main = [[3, 5, 7, 11], [13, 17, 19, 23]];
+    let p = [29, 31];
+    let alpha = [37, 41, 43];
+    let bus_ctx = EvalContext {
+        main: main.map(|row| row.map(felt).to_vec()),
+        aux: p.map(|p| vec![felt(p)]),
+        rand: alpha.map(felt).to_vec(),
+        ..Default::default()
+    };
+    let manual_ctx = EvalContext {
+        main: [0, 1]
+            .map(|row| main[row].iter().chain([&p[row]]).chain(&alpha).map(|v| felt(*v)).collect()),
+        ..Default::default()
+    };
+
+    let bus_kind = RootKind::Integrity(AUX_SEGMENT);
+    let manual_kind = RootKind::Integrity(DEFAULT_SEGMENT);
+    assert_eq!(bus.num_roots(bus_kind), 1);
+    assert_eq!(manual.num_roots(manual_kind), 2);
+    assert_eq!(
+        bus.evaluate_root(bus_kind, 0, &bus_ctx),
+        manual.evaluate_root(manual_kind, 1, &manual_ctx)
+    );
+}
+
+#[test]
+fn permutation_declares_a_bus() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf permutation(a, b);
+        enf permutation([a, c], [b, d]);
+    }";
+    // The same permutation arguments, written with explicit buses
+    let manual = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    buses {
+        multiset p,
+        multiset q,
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf p.first = null;
+        enf p.last = null;
+        enf q.first = null;
+        enf q.last = null;
+    }
+    integrity_constraints {
+        p.insert(a) when 1;
+        p.remove(b) when 1;
+        q.insert(a, c) when 1;
+        q.remove(b, d) when 1;
+    }";
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let manual = compile(manual, Pipeline::WithMIR).expect("compilation failed");
+
+    // Each permutation is a bus of its own, inserting the values of `a` and removing those of `b`
+    let names: Vec<_> = air.buses.keys().map(|name| name.as_str()).collect();
+    assert_eq!(names, ["%permutation0", "%permutation1"]);
+    for (bus, width) in air.buses.values().zip([1, 2]) {
+        assert_eq!(bus.bus_type, BusType::Multiset);
+        assert_eq!(bus.first, BusBoundary::Null);
+        assert_eq!(bus.last, BusBoundary::Null);
+        let ops: Vec<_> = bus.bus_ops.iter().map(|op| (op.op_kind, op.columns.len())).collect();
+        assert_eq!(ops, [(BusOpKind::Insert, width), (BusOpKind::Remove, width)]);
+    }
+    assert_eq!(air.num_random_values, manual.num_random_values);
+    assert_eq!(air.trace_segment_widths, manual.trace_segment_widths);
+
+    let felt = |value: u64| QuadFelt::from(Felt::new(value));
+    let ctx = EvalContext {
+        main: [[3, 5, 7, 11], [13, 17, 19, 23]].map(|row| row.map(felt).to_vec()),
+        aux: [[29, 31], [37, 41]].map(|row| row.map(felt).to_vec()),
+        rand: [43, 47, 53].map(felt).to_vec(),
+        ..Default::default()
+    };
+    let kind = RootKind::Integrity(AUX_SEGMENT);
+    assert_eq!(air.num_roots(kind), 2);
+    assert_eq!(air.num_boundary_constraints(AUX_SEGMENT), 4);
+    for i in 0..air.num_roots(kind) {
+        assert_eq!(air.evaluate_root(kind, i, &ctx), manual.evaluate_root(kind, i, &ctx));
+    }
+}
+
+#[test]
+fn err_permutation_of_different_widths() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf permutation([a, b], c);
+    }";
+    expect_diagnostic(source, "expected 2 values", Pipeline::WithMIR);
+}
@@ -0,0 +1,46 @@
+use air_pass::Pass;
+
+use super::{Compiler, Pipeline};
+use crate::{NodeIndex, Operation, passes::Canonicalize};
+
+#[test]
+fn canonicalize_collapses_equivalent_constraints() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a * b = c;
+        enf b * a = c;
+        enf c' = a - b;
+        enf c' = a + (0 - b);
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let compiler = Compiler::default();
+        let air = compiler.compile(source, pipeline).expect("compilation failed");
+        let roots: Vec<_> =
+            air.integrity_constraints(0).iter().map(|root| *root.node_index()).collect();
+        assert_ne!(roots[0], roots[1]);
+        assert_ne!(roots[2], roots[3]);
+
+        let air = Canonicalize::new(&compiler.diagnostics).run(air).expect("pass failed");
+        let roots: Vec<_> =
+            air.integrity_constraints(0).iter().map(|root| *root.node_index()).collect();
+        assert_eq!(roots[0], roots[1]);
+        assert_eq!(roots[2], roots[3]);
+
+        // Only the boundary constraint keeps its subtraction, as backends expect `column - value`
+        let graph = air.constraint_graph();
+        let boundary = *air.boundary_constraints(0)[0].node_index();
+        assert!(matches!(graph.node(&boundary).op(), Operation::Sub(..)));
+        assert!((0..graph.num_nodes()).all(|i| {
+            NodeIndex::from(i) == boundary
+                || !matches!(graph.node(&i.into()).op(), Operation::Sub(..))
+        }));
+    }
+}
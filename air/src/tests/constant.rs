@@ -1,4 +1,4 @@
-use super::{Pipeline, compile, expect_diagnostic};
+use super::{Pipeline, assert_equivalent_integrity_constraints, compile, expect_diagnostic};
 
 #[test]
 fn boundary_constraint_with_constants() {
@@ -75,3 +75,73 @@ fn invalid_matrix_constant() {
     );
     expect_diagnostic(source, "invalid matrix literal: mismatched dimensions", Pipeline::WithMIR);
 }
+
+#[test]
+fn integrity_constraint_with_computed_constants() {
+    let source = "
+    def test
+    const fn rc(i: felt) -> felt {
+        return (i * 7 + 3)^5;
+    }
+    const RCS = [rc(i) for i in 0..8];
+    trace_columns {
+        main: [clk, a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + RCS[1];
+        enf a = sum(RCS[6..8]);
+    }";
+    let expected = "
+    def test
+    const RCS = [243, 100000, 1419857, 7962624, 28629151, 79235168, 184528125, 380204032];
+    trace_columns {
+        main: [clk, a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + RCS[1];
+        enf a = sum(RCS[6..8]);
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn invalid_computed_constant() {
+    let source = "
+    def test
+    fn rc(i: felt) -> felt {
+        return i * clk;
+    }
+    const RCS = [rc(i) for i in 0..8];
+    trace_columns {
+        main: [clk, a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + RCS[1];
+        enf a = sum(RCS[6..8]);
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        expect_diagnostic(source, "this value is not known at compile time", pipeline);
+    }
+}
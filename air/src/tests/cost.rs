@@ -0,0 +1,48 @@
+use super::{Pipeline, compile};
+use crate::ProofOptions;
+
+#[test]
+fn cost_estimate_with_default_options() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    periodic_columns {
+        k: [1, 0, 0, 0],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf b.last = 1;
+    }
+    integrity_constraints {
+        enf a' = a * b * k;
+        enf c' = c + 1;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+        let estimate = air.cost_estimate(ProofOptions::default());
+
+        assert_eq!(estimate.trace_widths, [3]);
+        assert_eq!(estimate.num_boundary_constraints, 2);
+        assert_eq!(estimate.num_integrity_constraints, 2);
+        assert_eq!(estimate.num_periodic_columns, 1);
+        // `a * b` is of degree 2, and multiplying by `k` increases it by one
+        assert_eq!(estimate.max_constraint_degree, 3);
+        assert_eq!(estimate.quotient_degree, 2);
+        assert!(estimate.blowup_is_sufficient());
+        // One commitment to the main trace and one to the quotient
+        assert_eq!(estimate.num_trace_commitments, 2);
+        assert_eq!(estimate.num_queried_values, 5);
+
+        // The domain of 2^13 elements is folded twice, until it is at most 8 * 128
+        assert_eq!(estimate.lde_domain_size(10), 8192);
+        assert_eq!(estimate.num_fri_layers(10), 2);
+        assert_eq!(estimate.num_commitments(10), 4);
+        assert_eq!(estimate.num_fri_layers(7), 0);
+    }
+}
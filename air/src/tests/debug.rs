@@ -0,0 +1,95 @@
+use winter_math::{FieldElement, fields::f64::BaseElement as Felt};
+
+use super::{Pipeline, compile};
+use crate::{
+    DEFAULT_SEGMENT, NUM_TRANSITION_EXEMPTIONS, QuadFelt, RootKind, TraceAccess, debug_trace,
+    violation_table,
+};
+
+const SOURCE: &str = "
+    def test
+
+    ev fibonacci([a, b]) {
+        enf b' = a + b;
+        enf a' = b;
+    }
+
+    trace_columns {
+        main: [a, b],
+    }
+
+    public_inputs {
+        stack_inputs: [2],
+        stack_output: [1],
+    }
+
+    boundary_constraints {
+        enf a.first = stack_inputs[0];
+        enf b.first = stack_inputs[1];
+        enf b.last = stack_output[0];
+    }
+
+    integrity_constraints {
+        enf fibonacci([a, b]);
+    }";
+
+/// Returns the columns of the Fibonacci trace of `length` rows starting at 1, 1, and the public
+/// inputs it satisfies, whose output is read on the last row which is not exempted.
+fn fibonacci_trace(length: usize) -> (Vec<Vec<Felt>>, Vec<Vec<Felt>>) {
+    let (mut a, mut b) = (vec![Felt::ONE], vec![Felt::ONE]);
+    for row in 1..length {
+        a.push(b[row - 1]);
+        b.push(a[row - 1] + b[row - 1]);
+    }
+    let public = vec![vec![Felt::ONE, Felt::ONE], vec![b[length - NUM_TRANSITION_EXEMPTIONS]]];
+    (vec![a, b], public)
+}
+
+#[test]
+fn valid_trace_has_no_violations() {
+    let air = compile(SOURCE, Pipeline::WithMIR).expect("compilation failed");
+    let (main, public) = fibonacci_trace(32);
+    assert_eq!(debug_trace(&air, &main, &[], &public, &[], usize::MAX), vec![]);
+}
+
+#[test]
+fn corrupted_trace_reports_first_violation() {
+    let air = compile(SOURCE, Pipeline::WithMIR).expect("compilation failed");
+    let (mut main, public) = fibonacci_trace(32);
+    main[1][5] += Felt::ONE;
+
+    let violations = debug_trace(&air, &main, &[], &public, &[], 1);
+    assert_eq!(violations.len(), 1);
+    let violation = &violations[0];
+    // `b' = a + b` is the first constraint violated, on the row before the corrupted cell
+    assert_eq!(violation.kind, RootKind::Integrity(DEFAULT_SEGMENT));
+    assert_eq!(violation.row, 4);
+    assert!([QuadFelt::ONE, -QuadFelt::ONE].contains(&violation.value));
+    assert_eq!(violation.labels.len(), 1);
+    assert_eq!(violation.labels[0].as_str(), "fibonacci");
+    let next_b = TraceAccess::new(DEFAULT_SEGMENT, 1, 1);
+    assert!(violation.reads.contains(&(next_b, QuadFelt::from(main[1][5]))));
+
+    // Both constraints are also violated on the row of the corrupted cell, but not on the next
+    // rows, which are computed from the original value
+    let violations = debug_trace(&air, &main, &[], &public, &[], usize::MAX);
+    let rows: Vec<_> = violations.iter().map(|violation| (violation.kind, violation.row)).collect();
+    let integrity = RootKind::Integrity(DEFAULT_SEGMENT);
+    assert_eq!(rows, [(integrity, 4), (integrity, 5), (integrity, 5)]);
+    let table = violation_table(&air, &violations);
+    assert!(table.contains("row 4: integrity constraint #"));
+    assert!(table.contains("[fibonacci] = "));
+    assert!(table.contains("b' = 14"));
+}
+
+#[test]
+fn last_boundary_is_checked_on_last_step() {
+    let air = compile(SOURCE, Pipeline::WithMIR).expect("compilation failed");
+    let (main, mut public) = fibonacci_trace(32);
+    // The output is read on the last row, rather than on the last row which is not exempted
+    public[1][0] = main[1][31];
+
+    let violations = debug_trace(&air, &main, &[], &public, &[], usize::MAX);
+    let rows: Vec<_> = violations.iter().map(|violation| (violation.kind, violation.row)).collect();
+    assert_eq!(rows, [(RootKind::BoundaryLast, 32 - NUM_TRANSITION_EXEMPTIONS)]);
+}
@@ -0,0 +1,124 @@
+use super::{Pipeline, compile};
+use crate::{ConstraintChange, RootKind, diff};
+
+const SOURCE: &str = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = stack_inputs[0];
+    }
+    integrity_constraints {
+        let x = a + b;
+        enf a' = x * 2;
+        enf b' = a * (b + c);
+        enf c' = c + 1;
+    }";
+
+#[test]
+fn diff_identical() {
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let old = compile(SOURCE, pipeline).expect("compilation failed");
+        let new = compile(SOURCE, pipeline).expect("compilation failed");
+
+        let diff = diff(&old, &new);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "no changes\n");
+    }
+}
+
+#[test]
+fn diff_renamed_let_binding_is_equivalent() {
+    let renamed = SOURCE.replace("let x = a + b;", "let sum = a + b;").replace("x * 2", "sum * 2");
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let old = compile(SOURCE, pipeline).expect("compilation failed");
+        let new = compile(&renamed, pipeline).expect("compilation failed");
+
+        assert!(diff(&old, &new).is_equivalent());
+    }
+}
+
+#[test]
+fn diff_rewritten_constraint_is_refactored() {
+    let rewritten = SOURCE.replace("enf b' = a * (b + c);", "enf b' = a * c + b * a;");
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let old = compile(SOURCE, pipeline).expect("compilation failed");
+        let new = compile(&rewritten, pipeline).expect("compilation failed");
+
+        let diff = diff(&old, &new);
+        assert!(diff.is_equivalent());
+        assert_eq!(
+            diff.constraints,
+            [ConstraintChange::Refactored {
+                kind: RootKind::Integrity(0),
+                old: 1,
+                new: 1,
+                degree: None
+            }]
+        );
+    }
+}
+
+#[test]
+fn diff_changed_coefficient() {
+    let changed = SOURCE.replace("x * 2", "x * 3");
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let old = compile(SOURCE, pipeline).expect("compilation failed");
+        let new = compile(&changed, pipeline).expect("compilation failed");
+
+        let diff = diff(&old, &new);
+        assert!(!diff.is_equivalent());
+        assert_eq!(
+            diff.constraints,
+            [ConstraintChange::Changed {
+                kind: RootKind::Integrity(0),
+                old: 0,
+                new: 0,
+                degree: None
+            }]
+        );
+        assert_eq!(diff.to_string(), "~ main integrity constraint #0 -> #0: changed\n");
+    }
+}
+
+#[test]
+fn diff_added_constraint() {
+    let added = SOURCE.replace("enf c' = c + 1;", "enf c' = c + 1;\n        enf a * b = 0;");
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let old = compile(SOURCE, pipeline).expect("compilation failed");
+        let new = compile(&added, pipeline).expect("compilation failed");
+
+        let diff = diff(&old, &new);
+        assert_eq!(
+            diff.constraints,
+            [ConstraintChange::Added { kind: RootKind::Integrity(0), new: 3 }]
+        );
+        assert_eq!(diff.to_string(), "+ main integrity constraint #3\n");
+    }
+}
+
+#[test]
+fn diff_trace_and_public_inputs() {
+    let changed = SOURCE
+        .replace("main: [a, b, c]", "main: [a, b, c, d]")
+        .replace("stack_inputs: [16]", "stack_inputs: [8]");
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let old = compile(SOURCE, pipeline).expect("compilation failed");
+        let new = compile(&changed, pipeline).expect("compilation failed");
+
+        let diff = diff(&old, &new);
+        assert!(diff.constraints.is_empty());
+        assert!(!diff.is_equivalent());
+        let widths = diff.trace_segment_widths.as_ref().expect("widths changed");
+        assert_eq!((widths.old[0], widths.new[0]), (3, 4));
+        let inputs = diff.public_inputs.as_ref().expect("public inputs changed");
+        assert_eq!((inputs.old[0].size, inputs.new[0].size), (16, 8));
+        assert!(diff.periodic_columns.is_none());
+        assert!(diff.buses.is_none());
+        assert!(diff.num_random_values.is_none());
+    }
+}
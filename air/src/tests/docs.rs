@@ -0,0 +1,77 @@
+use super::{Pipeline, compile};
+use crate::DEFAULT_SEGMENT;
+
+const SOURCE: &str = "
+    def test
+
+    ## Enforces that the column is binary.
+    ##
+    ## Used by every selector.
+    ev is_binary([c]) {
+        ## `c` is either 0 or 1
+        enf c^2 = c;
+    }
+
+    ev stack_push([a, b]) {
+        enf a' = b;
+        enf b' = b + 1;
+    }
+
+    trace_columns {
+        main: [a, b, c, s],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        ## `a` starts at zero
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        # An ordinary comment, which is not a doc comment
+        enf is_binary([s]);
+        ## Pushes `b` onto the stack
+        ### A section header, which is not a doc comment
+        enf stack_push([a, b]);
+
+        ## `c` is a boolean too
+        enf c^2 = c;
+    }";
+
+#[test]
+fn doc_comments_document_constraints() {
+    let air = compile(SOURCE, Pipeline::WithMIR).expect("compilation failed");
+    let boundary = air.boundary_constraints(DEFAULT_SEGMENT);
+    assert_eq!(boundary.len(), 1);
+    assert_eq!(air.constraint_docs(&boundary[0]), ["`a` starts at zero"]);
+
+    let integrity = air.integrity_constraints(DEFAULT_SEGMENT);
+    assert_eq!(integrity.len(), 4);
+    let docs: Vec<_> = integrity.iter().map(|root| air.constraint_docs(root)).collect();
+    // The statement of an evaluator documents the constraints inlined from it
+    assert_eq!(docs[0], ["`c` is either 0 or 1"]);
+    // Otherwise, the constraints are documented by the call, as for any other statement
+    assert_eq!(docs[1], ["Pushes `b` onto the stack"]);
+    assert_eq!(docs[2], docs[1]);
+    assert_eq!(docs[3], ["`c` is a boolean too"]);
+}
+
+#[test]
+fn doc_comments_of_evaluators_are_kept_in_provenance() {
+    let air = compile(SOURCE, Pipeline::WithMIR).expect("compilation failed");
+    let integrity = air.integrity_constraints(DEFAULT_SEGMENT);
+
+    let provenance = air.constraint_provenance(&integrity[0]).expect("missing provenance");
+    assert_eq!(provenance.call_sites.len(), 1);
+    assert_eq!(provenance.call_sites[0].callee.name().as_str(), "is_binary");
+    assert_eq!(
+        provenance.call_sites[0].docs,
+        ["Enforces that the column is binary.", "", "Used by every selector."]
+    );
+    let provenance = air.constraint_provenance(&integrity[1]).expect("missing provenance");
+    assert!(provenance.call_sites[0].docs.is_empty());
+    assert!(air.constraint_provenance(&integrity[3]).is_none());
+}
@@ -1,4 +1,7 @@
-use super::{Pipeline, compile};
+use air_pass::Pass;
+
+use super::{Compiler, Pipeline, assert_equivalent_integrity_constraints, compile};
+use crate::passes::TrivialConstraints;
 
 #[test]
 fn simple_evaluator() {
@@ -116,3 +119,288 @@ fn ev_fn_call_with_column_group() {
     assert!(compile(source, Pipeline::WithoutMIR).is_ok());
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
+
+#[test]
+fn warn_trivial_constraint() {
+    let source = "
+    def test
+    ev equal([x, y]) {
+        enf x = y;
+    }
+
+    trace_columns {
+        main: [a, b],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a' = a + 1;
+        enf equal([a, a]);
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(air.integrity_constraints(0).len(), 2);
+    assert!(
+        compiler
+            .emitter
+            .captured()
+            .contains("integrity constraint #1 against the main trace is identically zero")
+    );
+
+    let compiler = Compiler::default();
+    let source = "
+    def test
+    ev equal([x, y]) {
+        enf x = y;
+    }
+
+    trace_columns {
+        main: [a, b],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a' = a + 1;
+        enf equal([a, b]);
+    }";
+    compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    assert!(!compiler.emitter.captured().contains("identically zero"));
+}
+
+#[test]
+fn remove_trivial_constraint() {
+    let source = "
+    def test
+    ev equal([x, y]) {
+        enf x = y;
+    }
+
+    trace_columns {
+        main: [a, b],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a' = a + 1;
+        enf equal([a, a]);
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let air = TrivialConstraints::new(&compiler.diagnostics)
+        .with_removal(true)
+        .run(air)
+        .expect("pass failed");
+    assert_eq!(air.integrity_constraints(0).len(), 1);
+}
+
+#[test]
+fn trivial_constraint_provenance() {
+    let source = "
+    def test
+    ev equal([x, y]) {
+        enf x = y;
+    }
+
+    ev copy([x, y]) {
+        enf x' = y;
+        enf equal([x, y]);
+    }
+
+    trace_columns {
+        main: [a, b],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf copy([a, a]);
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+
+    // the trivial constraint is reported where it is written, along with the two calls which
+    // inlined it
+    let root = &air.integrity_constraints(0)[1];
+    let provenance = air.constraint_provenance(root).expect("missing provenance");
+    assert_eq!(compiler.codemap.source_slice(&provenance.span).unwrap(), "x = y");
+    let call_sites: Vec<_> = provenance
+        .call_sites
+        .iter()
+        .map(|call_site| (call_site.callee.name().as_str(), call_site.depth))
+        .collect();
+    assert_eq!(call_sites, [("equal", 0), ("copy", 1)]);
+
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("this constraint is identically zero"), "{captured}");
+    assert!(captured.contains("inlined from `equal` called here"), "{captured}");
+    assert!(captured.contains("inlined from `copy` called here"), "{captured}");
+
+    // constraints written directly in the integrity constraints section have no provenance
+    let source = "
+    def test
+    ev equal([x, y]) {
+        enf x = y;
+    }
+
+    trace_columns {
+        main: [a, b],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a' = a + 1;
+        enf equal([a, b]);
+    }";
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    assert!(air.constraint_provenance(&air.integrity_constraints(0)[0]).is_none());
+    assert!(air.constraint_provenance(&air.integrity_constraints(0)[1]).is_some());
+}
+
+#[test]
+fn trivial_constraint_span() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+
+    periodic_columns {
+        k: [0, 1],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a' = a + 1;
+        enf b = b;
+        enf k^2 = k;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+
+    // constraints written directly in the integrity constraints section are reported where they
+    // are written
+    let roots = air.integrity_constraints(0);
+    assert!(air.constraint_provenance(&roots[1]).is_none());
+    let span = air.constraint_span(&roots[1]).expect("missing span");
+    assert_eq!(compiler.codemap.source_slice(&span).unwrap(), "b = b");
+    let span = air.constraint_span(&roots[2]).expect("missing span");
+    assert_eq!(compiler.codemap.source_slice(&span).unwrap(), "k^2 = k");
+
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("enf b = b;"), "{captured}");
+    assert!(captured.contains("this constraint is identically zero"), "{captured}");
+    assert!(captured.contains("enf k^2 = k;"), "{captured}");
+    assert!(
+        captured.contains("this constraint does not reference any trace column"),
+        "{captured}"
+    );
+}
+
+#[test]
+fn warn_trace_free_constraint() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+
+    periodic_columns {
+        k: [0, 1],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a' = a + k;
+        enf k^2 = k;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(air.integrity_constraints(0).len(), 2);
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains(
+        "integrity constraint #1 against the main trace does not reference any trace column"
+    ));
+    assert!(!captured.contains("integrity constraint #0"));
+}
+
+#[test]
+fn repeated_evaluator_calls() {
+    let source = |constraints: &str| {
+        format!(
+            "
+    def test
+    ev advance([x[2]]) {{
+        enf x[0]' = x[1] when x[0];
+    }}
+    trace_columns {{
+        main: [a, b, c[2]],
+    }}
+    boundary_constraints {{
+        enf a.first = 0;
+    }}
+    integrity_constraints {{
+        {constraints}
+    }}"
+        )
+    };
+    let calls = source("enf advance([a, b]);\n enf advance([c]);\n enf advance([a, b]);");
+    let expected = source("enf a' = b when a;\n enf c[0]' = c[1] when c[0];\n enf a' = b when a;");
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(&calls, &expected, pipeline);
+    }
+}
+
+#[test]
+fn evaluator_in_comprehension_shares_expressions() {
+    let source = "
+    def test
+    ev constraints([x, y]) {
+        enf x^2 * 3 = x + y * 4;
+        enf x' * (2 + 5) = y^3 * 6;
+    }
+    trace_columns {
+        main: [c[4], d[4]],
+    }
+    boundary_constraints {
+        enf c[0].first = 0;
+    }
+    integrity_constraints {
+        enf constraints([x, y]) for (x, y) in (c, d);
+    }";
+
+    // The constants of the body are shared by all iterations rather than duplicated in each one,
+    // which must not change the resulting Air, down to the order of its nodes
+    let [shared, duplicated] = [true, false].map(|share_expressions| {
+        Compiler::default()
+            .compile_with_mir(source, share_expressions)
+            .expect("compilation failed")
+    });
+    assert_eq!(format!("{shared:?}"), format!("{duplicated:?}"));
+}
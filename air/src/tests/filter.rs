@@ -0,0 +1,158 @@
+use air_pass::Pass;
+
+use super::{Compiler, Pipeline, compile};
+use crate::{
+    AUX_SEGMENT, BusBoundary, ConstraintKind, DEFAULT_SEGMENT, Operation, Value,
+    passes::Canonicalize,
+};
+
+const COMPONENTS_SOURCE: &str = "
+    def test
+
+    ev stack_push([a, b]) {
+        enf a' = b;
+        enf b' = b + 1;
+    }
+
+    ev stack_pop([a, b]) {
+        enf a' = a - b;
+    }
+
+    ev stack_ops([a, b, s]) {
+        enf stack_push([a, b]) when s;
+        enf stack_pop([a, b]) when !s;
+    }
+
+    ev range_check([c]) {
+        enf c^2 = c;
+    }
+
+    trace_columns {
+        main: [a, b, c, s],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf stack_ops([a, b, s]);
+        enf range_check([c]);
+        enf s^2 = s;
+    }";
+
+#[test]
+fn filter_constraints_by_label() {
+    let air = compile(COMPONENTS_SOURCE, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(air.integrity_constraints(DEFAULT_SEGMENT).len(), 5);
+
+    let stack = air.filter(|constraint| constraint.matches_label("stack_*"));
+    assert_eq!(stack.num_boundary_constraints(DEFAULT_SEGMENT), 0);
+    assert_eq!(stack.integrity_constraints(DEFAULT_SEGMENT).len(), 3);
+    let push = air.filter(|constraint| constraint.matches_label("stack_pu?h"));
+    assert_eq!(push.integrity_constraints(DEFAULT_SEGMENT).len(), 2);
+    let range = air.filter(|constraint| constraint.matches_label("range_check"));
+    assert_eq!(range.integrity_constraints(DEFAULT_SEGMENT).len(), 1);
+    let none = air.filter(|constraint| constraint.matches_label("stack"));
+    assert_eq!(none.integrity_constraints(DEFAULT_SEGMENT).len(), 0);
+
+    // The constraints keep their provenance and selectors, and the trace is preserved
+    for root in stack.integrity_constraints(DEFAULT_SEGMENT) {
+        assert!(stack.constraint_provenance(root).is_some());
+        assert_eq!(stack.constraint_selectors(root).len(), 1);
+    }
+    assert_eq!(stack.trace_segment_widths, air.trace_segment_widths);
+    assert_eq!(stack.public_inputs, air.public_inputs);
+    assert!(stack.constraint_graph().num_nodes() < air.constraint_graph().num_nodes());
+}
+
+#[test]
+fn filter_constraints_excludes_buses_wholesale() {
+    let source = include_str!("../../../air-script/tests/buses/buses_complex.air");
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+
+    let boundary = air.filter(|constraint| constraint.is_boundary());
+    assert_eq!(boundary.trace_segment_widths, air.trace_segment_widths);
+    assert_eq!(boundary.num_random_values, air.num_random_values);
+    assert_eq!(boundary.bus_column_layout(), air.bus_column_layout());
+    for segment in [DEFAULT_SEGMENT, AUX_SEGMENT] {
+        assert!(boundary.integrity_constraints(segment).is_empty());
+    }
+    // Only `a.first = 0` remains, as the boundaries of the buses are excluded with them
+    assert_eq!(boundary.num_boundary_constraints(DEFAULT_SEGMENT), 1);
+    assert_eq!(boundary.num_boundary_constraints(AUX_SEGMENT), 0);
+    assert!(boundary.expanded_buses().is_empty());
+    for bus in boundary.buses.values() {
+        assert!(bus.bus_ops.is_empty());
+        assert_eq!(bus.first, BusBoundary::Unconstrained);
+    }
+
+    // The buses are kept when all of their constraints are selected
+    let buses = air.filter(|constraint| constraint.bus.is_some());
+    assert_eq!(buses.expanded_buses().len(), 2);
+    assert_eq!(buses.integrity_constraints(AUX_SEGMENT).len(), 2);
+    assert_eq!(buses.num_boundary_constraints(AUX_SEGMENT), 4);
+    assert!(buses.integrity_constraints(DEFAULT_SEGMENT).is_empty());
+    let p = buses.filter(|constraint| constraint.matches_label("p"));
+    assert_eq!(p.expanded_buses().len(), 1);
+    assert_eq!(p.integrity_constraints(AUX_SEGMENT).len(), 1);
+}
+
+#[test]
+fn filter_canonical_constraints() {
+    let source = include_str!("../../../air-script/tests/buses/buses_complex.air");
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let air = Canonicalize::new(&compiler.diagnostics).run(air).expect("pass failed");
+
+    // The boundaries of the buses are still attributed to them
+    let boundary = air.filter(|constraint| constraint.is_boundary());
+    assert_eq!(boundary.num_boundary_constraints(DEFAULT_SEGMENT), 1);
+    assert_eq!(boundary.num_boundary_constraints(AUX_SEGMENT), 0);
+    let buses = air.filter(|constraint| constraint.bus.is_some());
+    assert_eq!(buses.expanded_buses().len(), 2);
+    assert_eq!(buses.num_boundary_constraints(AUX_SEGMENT), 4);
+
+    // The operations of the buses refer to the nodes of the canonical graph, e.g. the first
+    // operation of `p` is `p.insert(1, a) when s1`
+    let p = buses.buses.values().next().unwrap();
+    let op = &p.bus_ops[0];
+    let graph = buses.constraint_graph();
+    assert!(matches!(
+        graph.node(&op.columns[1]).op(),
+        Operation::Value(Value::TraceAccess(access)) if access.column == 0
+    ));
+    assert!(matches!(
+        graph.node(&op.latch).op(),
+        Operation::Value(Value::TraceAccess(access)) if access.column == 2
+    ));
+}
+
+#[test]
+fn all_constraints_covers_every_segment() {
+    let source = include_str!("../../../air-script/tests/buses/buses_complex.air");
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let constraints: Vec<_> = air.all_constraints().collect();
+
+    let segments = 0..air.trace_segment_widths.len();
+    let num_boundary: usize = segments.clone().map(|s| air.num_boundary_constraints(s)).sum();
+    let num_integrity: usize = segments.map(|s| air.integrity_constraints(s).len()).sum();
+    assert!(num_boundary > 0 && num_integrity > 0);
+    assert_eq!(constraints.len(), num_boundary + num_integrity);
+    assert_eq!(constraints.len(), air.constraint_metas().len());
+
+    // Boundary constraints come first, and each constraint matches its root in its segment
+    let boundary = constraints.iter().filter(|c| c.kind == ConstraintKind::Boundary).count();
+    assert_eq!(boundary, num_boundary);
+    for (constraint, meta) in constraints.iter().zip(air.constraint_metas()) {
+        assert_eq!(constraint.kind == ConstraintKind::Boundary, meta.is_boundary());
+        assert_eq!(constraint.segment, meta.segment);
+        assert_eq!(constraint.root, *meta.root.node_index());
+        assert_eq!(constraint.domain, meta.root.domain());
+    }
+    assert!(constraints.iter().any(|c| c.segment == AUX_SEGMENT));
+}
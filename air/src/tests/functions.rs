@@ -0,0 +1,216 @@
+use super::{Pipeline, assert_equivalent_integrity_constraints, expect_diagnostic};
+
+#[test]
+fn enforce_felt_function_call() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    fn is_binary(x: felt) -> felt {
+        return x^2 - x;
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf is_binary(a);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a^2 - a = 0;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn enforce_felt_function_call_with_selector() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    fn is_binary(x: felt) -> felt {
+        return x^2 - x;
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf is_binary(a) when b;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf b * (a^2 - a) = 0;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn enforce_vector_function_call() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    fn are_binary(x: felt[2]) -> felt[2] {
+        return [y^2 - y for y in x];
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf are_binary([a, b]);
+        enf c = 0;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a^2 - a = 0;
+        enf b^2 - b = 0;
+        enf c = 0;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn enforce_function_call_in_comprehension() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    fn is_binary(x: felt) -> felt {
+        return x^2 - x;
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf is_binary(x) for x in s;
+    }";
+    let explicit = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    fn is_binary(x: felt) -> felt {
+        return x^2 - x;
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf is_binary(x) = 0 for x in s;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf s[0]^2 - s[0] = 0;
+        enf s[1]^2 - s[1] = 0;
+        enf s[2]^2 - s[2] = 0;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+        assert_equivalent_integrity_constraints(explicit, expected, pipeline);
+    }
+}
+
+#[test]
+fn err_enforce_vector_function_call_in_comprehension() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    fn are_binary(x: felt[2]) -> felt[2] {
+        return [y^2 - y for y in x];
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf are_binary([x, a]) for x in s;
+    }";
+
+    expect_diagnostic(source, "this function returns a vector", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "this function returns a vector", Pipeline::WithMIR);
+}
+
+#[test]
+fn err_enforce_matrix_function_call() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    fn outer(x: felt[2]) -> felt[2, 2] {
+        return [[x[0], x[1]], [x[1], x[0]]];
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf outer([a, b]);
+    }";
+
+    expect_diagnostic(source, "this function returns a matrix", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "this function returns a matrix", Pipeline::WithMIR);
+}
+
+#[test]
+fn err_function_call_with_empty_vector_argument() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, s[3]],
+    }
+    fn are_binary(x: felt[2]) -> felt[2] {
+        return [y^2 - y for y in x];
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf are_binary([]);
+    }";
+
+    expect_diagnostic(source, "this argument has type felt[0]", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "this argument has type felt[0]", Pipeline::WithMIR);
+}
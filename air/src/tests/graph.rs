@@ -0,0 +1,200 @@
+use super::{Pipeline, compile};
+use crate::{AlgebraicGraph, Operation, TraceAccess, Value};
+
+#[test]
+fn topological_order_children_precede_parents() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    periodic_columns {
+        k: [1, 0],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let x = a * b + c;
+        enf d' = x * x - k * a;
+        enf c' = (x - d) * (b + 1);
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+        let graph = air.constraint_graph();
+        let order = graph.topological_order();
+
+        // Every node appears exactly once
+        assert_eq!(order.len(), graph.num_nodes());
+        let mut position = vec![None; graph.num_nodes()];
+        for (i, index) in order.iter().enumerate() {
+            let index = usize::from(*index);
+            assert_eq!(position[index], None, "node {index} appears more than once");
+            position[index] = Some(i);
+        }
+
+        // Every node appears after its children
+        for index in order.iter() {
+            if let Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) =
+                graph.node(index).op()
+            {
+                let parent = position[usize::from(*index)];
+                assert!(position[usize::from(*lhs)] < parent);
+                assert!(position[usize::from(*rhs)] < parent);
+            }
+        }
+    }
+}
+
+#[test]
+fn topological_order_deep_graph() {
+    // A long chain of operations must not overflow the stack
+    let mut graph = AlgebraicGraph::default();
+    let a = graph.insert_node(Operation::Value(Value::TraceAccess(TraceAccess::new(0, 0, 0))));
+    let mut tip = a;
+    for i in 0..5_000 {
+        let constant = graph.insert_node(Operation::Value(Value::Constant(i)));
+        let product = graph.insert_node(Operation::Mul(tip, a));
+        tip = graph.insert_node(Operation::Add(product, constant));
+    }
+
+    let order = graph.topological_order();
+    assert_eq!(order.len(), graph.num_nodes());
+    assert_eq!(order.last(), Some(&tip));
+}
+
+#[test]
+fn to_dot_labels_constraint_roots() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [2],
+    }
+    periodic_columns {
+        k: [1, 0],
+    }
+    boundary_constraints {
+        enf a.first = stack_inputs[0];
+        enf b.last = 1;
+    }
+    integrity_constraints {
+        enf a' = a + k * b;
+        enf b^2 = b;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let expected = r#"digraph G {
+n0 [label="n0\nM[0]"]
+n1 [label="n1\nPI[stack_inputs][0]"]
+n2 [shape=box, label="n2\nn0 - n1\nsegment 0, the first row"]
+n0 -> n2
+n1 -> n2
+n3 [label="n3\nM[1]"]
+n4 [label="n4\n1"]
+n5 [shape=box, label="n5\nn3 - n4\nsegment 0, the last row"]
+n3 -> n5
+n4 -> n5
+n6 [label="n6\nM[0]'"]
+n7 [label="n7\ntest::k"]
+n8 [label="n8\nn7 × n3"]
+n7 -> n8
+n3 -> n8
+n9 [label="n9\nn0 + n8"]
+n0 -> n9
+n8 -> n9
+n10 [shape=box, label="n10\nn6 - n9\nsegment 0, every frame of 2 consecutive rows"]
+n6 -> n10
+n9 -> n10
+n11 [label="n11\nn3 × n3"]
+n3 -> n11
+n3 -> n11
+n12 [shape=box, label="n12\nn11 - n3\nsegment 0, every row"]
+n11 -> n12
+n3 -> n12
+}
+"#;
+    assert_eq!(air.constraints.to_dot().unwrap(), expected);
+}
+
+#[test]
+fn to_ssa_text_names_shared_subexpressions_once() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    public_inputs {
+        stack_inputs: [2],
+    }
+    periodic_columns {
+        k: [1, 0],
+    }
+    boundary_constraints {
+        enf a.first = stack_inputs[0];
+        enf c.last = 1;
+    }
+    integrity_constraints {
+        enf a' = a + k * b;
+        enf b' = k * b - c;
+        enf c^2 = c;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    // `k * b` is shared by two constraints, and only assigned by the first one
+    let expected = "\
+# constraint0: segment 0, the first row
+constraint0 = M[0] - PI[stack_inputs][0]
+
+# constraint1: segment 0, the last row
+constraint1 = M[2] - 1
+
+# constraint2: segment 0, every frame of 2 consecutive rows
+t0 = test::k * M[1]
+t1 = M[0] + t0
+constraint2 = M[0]' - t1
+
+# constraint3: segment 0, every frame of 2 consecutive rows
+t2 = t0 - M[2]
+constraint3 = M[1]' - t2
+
+# constraint4: segment 0, every row
+t3 = M[2] * M[2]
+constraint4 = t3 - M[2]
+";
+    assert_eq!(air.to_ssa_text().unwrap(), expected);
+}
+
+#[test]
+fn to_infix_parenthesizes_by_precedence() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    public_inputs {
+        stack_inputs: [2],
+    }
+    boundary_constraints {
+        enf a.first = stack_inputs[0];
+    }
+    integrity_constraints {
+        enf a' = a - (b - c);
+        enf b' = (a + b) * c - a * b;
+    }";
+
+    let air = compile(source, Pipeline::WithoutMIR).expect("compilation failed");
+    let graph = air.constraint_graph();
+    let rendered: Vec<_> = air
+        .integrity_constraints(0)
+        .iter()
+        .map(|root| graph.to_infix(root.node_index()).unwrap())
+        .collect();
+    assert_eq!(
+        rendered,
+        ["M[0]' - (M[0] - (M[1] - M[2]))", "M[1]' - ((M[0] + M[1]) * M[2] - M[0] * M[1])"]
+    );
+}
@@ -1,4 +1,7 @@
+use miden_diagnostics::{DiagnosticsConfig, Verbosity};
+
 use super::super::{Pipeline, compile};
+use crate::tests::Compiler;
 
 #[test]
 fn constraint_comprehension() {
@@ -41,3 +44,32 @@ fn ic_comprehension_with_selectors() {
     assert!(compile(source, Pipeline::WithoutMIR).is_ok());
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
+
+#[test]
+fn constraint_comprehension_over_empty_slice() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf x = 0 for x in b[0..0];
+        enf a' = a + 1;
+    }";
+
+    // The comprehension enforces nothing, which is reported with a note
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let compiler = Compiler::new(DiagnosticsConfig {
+            verbosity: Verbosity::Info,
+            warnings_as_errors: true,
+            no_warn: false,
+            display: Default::default(),
+        });
+        let air = compiler.compile(source, pipeline).expect("compilation failed");
+        assert_eq!(air.integrity_constraints(0).len(), 1);
+        assert!(compiler.emitter.captured().contains("empty constraint comprehension"));
+    }
+}
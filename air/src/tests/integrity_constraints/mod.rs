@@ -1,4 +1,10 @@
-use super::{Pipeline, compile, expect_diagnostic};
+use air_pass::Pass;
+use winter_math::{FieldElement, fields::f64::BaseElement as Felt};
+
+use super::{
+    Compiler, Pipeline, assert_equivalent_integrity_constraints, compile, expect_diagnostic,
+};
+use crate::{Operation, Value, passes::CopyColumns};
 
 mod comprehension;
 
@@ -107,3 +113,341 @@ fn err_non_const_exp_outside_lc() {
     expect_diagnostic(source, "expected exponent to be a constant", Pipeline::WithoutMIR);
     expect_diagnostic(source, "expected exponent to be a constant", Pipeline::WithMIR);
 }
+
+#[test]
+fn rational_coefficients() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a = b / 2;
+        enf (1/3) * a = b;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+        let graph = air.constraint_graph();
+
+        // Returns the constant coefficient of the `Mul` node on the given side of the constraint
+        let coefficient = |root: usize, lhs: bool| {
+            let Operation::Sub(l, r) =
+                graph.node(air.integrity_constraints(0)[root].node_index()).op()
+            else {
+                panic!("expected constraint to be a subtraction");
+            };
+            let mul = if lhs { l } else { r };
+            let Operation::Mul(x, y) = graph.node(mul).op() else {
+                panic!("expected a multiplication");
+            };
+            [x, y]
+                .into_iter()
+                .find_map(|index| match graph.node(index).op() {
+                    Operation::Value(Value::Constant(c)) => Some(*c),
+                    _ => None,
+                })
+                .expect("expected a constant coefficient")
+        };
+
+        assert_eq!(coefficient(0, false), Felt::new(2).inv().as_int());
+        assert_eq!(coefficient(1, true), Felt::new(3).inv().as_int());
+    }
+}
+
+#[test]
+fn err_non_const_denominator() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a = 1 / b;
+    }";
+
+    expect_diagnostic(source, "expected denominator to be a constant", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "expected denominator to be a constant", Pipeline::WithMIR);
+}
+
+#[test]
+fn err_division_by_zero() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a = b / 0;
+    }";
+
+    expect_diagnostic(source, "division by zero", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "division by zero", Pipeline::WithMIR);
+}
+
+#[test]
+fn ic_gated_by_last_row_exemption() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf (1 - $last) * (a' - a - 1) = 0;
+        enf (b' - a) * (1 - $last) = 0;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' - a - 1 = 0;
+        enf b' - a = 0;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn err_ic_row_selector_in_expression() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + $first;
+    }";
+
+    expect_diagnostic(source, "invalid use of builtin row selector", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "invalid use of builtin row selector", Pipeline::WithMIR);
+}
+
+#[test]
+fn err_ic_row_selector_on_transition() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + 1;
+        enf $first * (a' - a) = 0;
+    }";
+
+    expect_diagnostic(source, "invalid expression", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "invalid expression", Pipeline::WithMIR);
+}
+
+#[test]
+fn ic_in_set() {
+    let source = "
+    def test
+    const OPCODES = [0, 1, 2];
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf in_set(a, OPCODES);
+        enf in_set(a + b, [3, 5, 7]);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a * (a - 1) * (a - 2) = 0;
+        enf (a + b - 3) * (a + b - 5) * (a + b - 7) = 0;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+
+        // The degree of the constraints is the size of the set
+        let air = compile(source, pipeline).expect("compilation failed");
+        let graph = air.constraint_graph();
+        for root in air.integrity_constraints(0) {
+            assert_eq!(graph.degree(root.node_index()).base(), 3);
+        }
+    }
+}
+
+#[test]
+fn err_in_set_of_non_constants() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf in_set(a, [0, b]);
+    }";
+
+    expect_diagnostic(source, "must be a vector of constants", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "must be a vector of constants", Pipeline::WithMIR);
+}
+
+#[test]
+fn err_in_set_of_large_set() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf in_set(a, [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    }";
+
+    expect_diagnostic(source, "this constraint has degree 9", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "this constraint has degree 9", Pipeline::WithMIR);
+}
+
+#[test]
+fn warn_copy_column() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + b';
+        enf b' = a * c;
+        enf c^2 = c;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    CopyColumns::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains(
+        "column `b` is a copy of `M[0] * M[2]` from the previous row, and could be eliminated"
+    ));
+    assert!(captured.contains("Integrity constraint #1 against the main trace only enforces"));
+    assert!(captured.contains("save one column and one constraint"));
+    assert!(!captured.contains("column `a`"));
+    assert!(!captured.contains("column `c`"));
+}
+
+#[test]
+fn warn_partially_eliminable_copy_column() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + b;
+        enf b' = a * c;
+        enf c^2 = c;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    CopyColumns::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains(
+        "column `b` is a copy of `M[0] * M[2]` from the previous row, and is partially eliminable"
+    ));
+    assert!(captured.contains("1 other constraint(s) read `b` in the current row"));
+}
+
+#[test]
+fn copy_columns_ignores_other_constraints() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + 1;
+        enf b' = b * a when c;
+        enf c' * a = c;
+        enf c^2 = c;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    CopyColumns::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(!captured.contains("copy of"));
+}
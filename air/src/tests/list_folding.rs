@@ -1,4 +1,4 @@
-use super::{Pipeline, compile};
+use super::{Pipeline, assert_equivalent_integrity_constraints, compile, expect_diagnostic};
 
 #[test]
 fn list_folding_on_const() {
@@ -117,3 +117,137 @@ fn list_folding_in_lc() {
     assert!(compile(source, Pipeline::WithoutMIR).is_ok());
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
+
+#[test]
+fn list_folding_on_empty_slice() {
+    let source = "
+    def test
+    const N = 0;
+    trace_columns {
+        main: [a[2], b],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        enf b' = b + sum(a[0..N]) + prod(a[0..N]);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a[2], b],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        enf b' = b + 1;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn list_folding_on_empty_vector() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        let v = [];
+        enf b' = b + a * prod(v) + sum(v);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        enf b' = b + a;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn combine_tuples() {
+    let source = "
+    def test
+    const ALPHAS = [2, 3, 5, 7];
+    trace_columns {
+        main: [a, b, c[2], alpha[3]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let t = [a, c[1]];
+        enf a' = combine([a, b], alpha) + combine(c, ALPHAS);
+        enf b' = combine(t, alpha[0..3]) * combine([], [b]);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c[2], alpha[3]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = alpha[0] + a * alpha[1] + b * alpha[2] + 2 + c[0] * 3 + c[1] * 5;
+        enf b' = (alpha[0] + a * alpha[1] + c[1] * alpha[2]) * b;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+}
+
+#[test]
+fn combine_with_too_few_coefficients() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, alpha[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = combine([a, b], alpha);
+    }";
+
+    expect_diagnostic(source, "expected at least 3 coefficients, but got 2", Pipeline::WithMIR);
+}
+
+#[test]
+fn combine_on_scalar() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, alpha[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = combine(a, alpha);
+    }";
+
+    expect_diagnostic(
+        source,
+        "this function expects vectors of field elements as arguments",
+        Pipeline::WithMIR,
+    );
+}
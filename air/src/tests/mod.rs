@@ -1,23 +1,42 @@
 mod access;
 mod boundary_constraints;
 mod buses;
+mod canonicalize;
 mod constant;
+mod cost;
+mod debug;
+mod diff;
+mod docs;
 mod evaluators;
+mod filter;
+mod functions;
+mod graph;
 mod integrity_constraints;
 mod list_folding;
+mod periodic_columns;
+mod pipeline;
+mod product;
 mod pub_inputs;
 mod random_values;
+mod rename;
 mod selectors;
+mod serialization;
+mod simplify;
 mod source_sections;
 mod trace;
+mod trace_layout;
 mod variables;
+mod virtual_columns;
+mod witness;
 
 use std::sync::Arc;
 
 use air_pass::Pass;
 use miden_diagnostics::{CodeMap, DiagnosticsConfig, DiagnosticsHandler, Verbosity};
+use winter_math::fields::f64::BaseElement as Felt;
 
 pub use crate::CompileError;
+use crate::{ConstraintEvaluator, EvalContext, QuadFelt, RootKind};
 
 #[derive(Clone, Copy, Debug)]
 pub enum Pipeline {
@@ -57,6 +76,70 @@ pub fn expect_diagnostic(source: &str, expected: &str, pipeline: Pipeline) {
     );
 }
 
+/// Asserts that the integrity constraints of `source` and `expected` are the same, by comparing
+/// their count and their evaluation over a frame with distinct values in every cell.
+#[track_caller]
+pub fn assert_equivalent_integrity_constraints(source: &str, expected: &str, pipeline: Pipeline) {
+    let air = compile(source, pipeline).expect("compilation failed");
+    let expected = compile(expected, pipeline).expect("compilation failed");
+
+    let kind = RootKind::Integrity(0);
+    assert_eq!(air.num_roots(kind), expected.num_roots(kind));
+
+    let width = air.trace_segment_widths[0] as u64;
+    let ctx = EvalContext {
+        main: [0, 1].map(|row| {
+            (0..width)
+                .map(|col| QuadFelt::from(Felt::new(3 + 7 * col + 101 * row)))
+                .collect()
+        }),
+        ..Default::default()
+    };
+    for i in 0..air.num_roots(kind) {
+        assert_eq!(
+            air.evaluate_root(kind, i, &ctx),
+            expected.evaluate_root(kind, i, &ctx),
+            "root {i}"
+        );
+    }
+}
+
+/// Asserts that the boundary constraints of `source` and `expected` evaluate to the same values,
+/// given the same trace and public inputs.
+pub fn assert_equivalent_boundary_constraints(source: &str, expected: &str, pipeline: Pipeline) {
+    let air = compile(source, pipeline).expect("compilation failed");
+    let expected = compile(expected, pipeline).expect("compilation failed");
+
+    let width = air.trace_segment_widths[0] as u64;
+    let ctx = EvalContext {
+        main: [0, 1].map(|row| {
+            (0..width)
+                .map(|col| QuadFelt::from(Felt::new(3 + 7 * col + 101 * row)))
+                .collect()
+        }),
+        public: air
+            .public_inputs()
+            .enumerate()
+            .map(|(i, input)| {
+                (0..input.size() as u64)
+                    .map(|j| QuadFelt::from(Felt::new(5 + 11 * i as u64 + 13 * j)))
+                    .collect()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    for kind in [RootKind::BoundaryFirst, RootKind::BoundaryLast] {
+        assert_eq!(air.num_roots(kind), expected.num_roots(kind));
+        for i in 0..air.num_roots(kind) {
+            assert_eq!(
+                air.evaluate_root(kind, i, &ctx),
+                expected.evaluate_root(kind, i, &ctx),
+                "{kind:?} root {i}"
+            );
+        }
+    }
+}
+
 struct Compiler {
     codemap: Arc<CodeMap>,
     emitter: Arc<SplitEmitter>,
@@ -84,18 +167,7 @@ impl Compiler {
 
     pub fn compile(&self, source: &str, pipeline: Pipeline) -> Result<crate::Air, CompileError> {
         match pipeline {
-            Pipeline::WithMIR => air_parser::parse(&self.diagnostics, self.codemap.clone(), source)
-                .map_err(CompileError::Parse)
-                .and_then(|ast| {
-                    let mut pipeline =
-                        air_parser::transforms::ConstantPropagation::new(&self.diagnostics)
-                            .chain(mir::passes::AstToMir::new(&self.diagnostics))
-                            .chain(mir::passes::Inlining::new(&self.diagnostics))
-                            .chain(mir::passes::Unrolling::new(&self.diagnostics))
-                            .chain(crate::passes::MirToAir::new(&self.diagnostics))
-                            .chain(crate::passes::BusOpExpand::new(&self.diagnostics));
-                    pipeline.run(ast)
-                }),
+            Pipeline::WithMIR => self.compile_with_mir(source, true),
             Pipeline::WithoutMIR => {
                 air_parser::parse(&self.diagnostics, self.codemap.clone(), source)
                     .map_err(CompileError::Parse)
@@ -111,6 +183,33 @@ impl Compiler {
     }
 }
 
+impl Compiler {
+    /// Compiles `source` with the MIR pipeline, where `share_expressions` is passed to
+    /// [mir::passes::Unrolling::with_shared_expressions].
+    pub fn compile_with_mir(
+        &self,
+        source: &str,
+        share_expressions: bool,
+    ) -> Result<crate::Air, CompileError> {
+        air_parser::parse(&self.diagnostics, self.codemap.clone(), source)
+            .map_err(CompileError::Parse)
+            .and_then(|ast| {
+                let mut pipeline =
+                    air_parser::transforms::ConstantPropagation::new(&self.diagnostics)
+                        .chain(mir::passes::AstToMir::new(&self.diagnostics))
+                        .chain(mir::passes::Inlining::new(&self.diagnostics))
+                        .chain(
+                            mir::passes::Unrolling::new(&self.diagnostics)
+                                .with_shared_expressions(share_expressions),
+                        )
+                        .chain(crate::passes::MirToAir::new(&self.diagnostics))
+                        .chain(crate::passes::TrivialConstraints::new(&self.diagnostics))
+                        .chain(crate::passes::BusOpExpand::new(&self.diagnostics));
+                pipeline.run(ast)
+            })
+    }
+}
+
 struct SplitEmitter {
     capture: miden_diagnostics::CaptureEmitter,
     default: miden_diagnostics::DefaultEmitter,
@@ -0,0 +1,280 @@
+use winter_math::{FieldElement, StarkField, fields::f64::BaseElement as Felt};
+
+use super::{Pipeline, compile, expect_diagnostic};
+use crate::{ConstraintEvaluator, EvalContext, PeriodicColumnError, QuadFelt, RootKind};
+
+#[test]
+fn z_exponent_for_periodic() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    periodic_columns {
+        k0: [1, 0],
+        k1: [1, 0, 0, 0, 0, 0, 0, 0],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + k0 * k1;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+        assert_eq!(air.z_exponent_for_periodic(8), Ok(1));
+        assert_eq!(air.z_exponent_for_periodic(1024), Ok(128));
+
+        let err = air.z_exponent_for_periodic(1000).unwrap_err();
+        assert_eq!(err, PeriodicColumnError::TraceLengthNotPowerOfTwo(1000));
+
+        let err = air.z_exponent_for_periodic(4).unwrap_err();
+        assert!(
+            matches!(err, PeriodicColumnError::CycleLongerThanTrace(name, 8, 4) if name.as_str() == "k1")
+        );
+    }
+}
+
+#[test]
+fn periodic_column_polynomials() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    periodic_columns {
+        k0: [1, 0],
+        k1: [3, 3, 3, 3],
+        k2: [1, 2, 3, 4, 5, 6, 7, 8],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + k0 * k1 * k2;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let polys = air.periodic_column_polynomials();
+    let poly = |name: &str| polys.iter().find(|(ident, _)| ident.name() == name).unwrap().1;
+
+    // `(1 + x) / 2` is 1 at `x = 1` and 0 at `x = -1`
+    let half = Felt::new(2).inv().as_int();
+    assert_eq!(poly("k0"), &[half, half]);
+    assert_eq!(poly("k1"), &[3, 0, 0, 0]);
+
+    // The polynomial evaluates to the `i`-th value at `ωⁱ`
+    let omega = Felt::get_root_of_unity(3);
+    for (i, value) in (1..=8).enumerate() {
+        let x = omega.exp(i as u64);
+        let eval = poly("k2").iter().rev().fold(Felt::ZERO, |acc, c| acc * x + Felt::new(*c));
+        assert_eq!(eval, Felt::new(value));
+    }
+}
+
+#[test]
+fn z_exponent_without_periodic_columns() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(air.z_exponent_for_periodic(8), Ok(0));
+}
+
+#[test]
+fn trace_length_constraints() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    periodic_columns {
+        k0: [1, 0],
+        k1: [CYCLE],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + k0 * k1;
+    }";
+    let cycle = vec!["0"; 1024].join(", ");
+    let source = source.replace("CYCLE", &cycle);
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(&source, pipeline).expect("compilation failed");
+        let bounds = air.trace_length_constraints();
+        assert_eq!(bounds.min_log2, 10);
+        assert!(bounds.must_be_power_of_two);
+        assert!(bounds.reasons.iter().any(|reason| reason.contains("'k1'")));
+
+        let report = bounds.to_string();
+        assert!(report.starts_with("the trace length must be a power of two, at least 2^10"));
+        assert!(report.contains("periodic column 'k1' has a cycle of length 1024"));
+    }
+}
+
+#[test]
+fn trace_length_constraints_without_periodic_columns() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let bounds = air.trace_length_constraints();
+    assert_eq!(bounds.min_log2, 2);
+    assert_eq!(bounds.reasons.len(), 2);
+}
+
+#[test]
+fn parity_selectors() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf on_even {
+            a' = a + 1;
+        };
+        enf on_odd {
+            a' = a * 2;
+        };
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+
+        // A period-2 column is synthesized for each parity
+        let columns: Vec<_> = air
+            .periodic_columns()
+            .map(|column| (column.name.name().as_str(), column.values.clone()))
+            .collect();
+        assert_eq!(columns, [("%even", vec![1, 0]), ("%odd", vec![0, 1])]);
+        assert_eq!(air.trace_length_constraints().min_log2, 2);
+
+        // The frame violates both constraints, which only vanish on the rows of the other parity
+        let log_trace_len = 3;
+        let g = QuadFelt::from(Felt::get_root_of_unity(log_trace_len));
+        let kind = RootKind::Integrity(0);
+        for (row, expected) in [(0, [false, true]), (1, [true, false])] {
+            let ctx = EvalContext {
+                log_trace_len,
+                main: [vec![QuadFelt::from(5u32)], vec![QuadFelt::from(7u32)]],
+                z: g.exp(row),
+                ..Default::default()
+            }
+            .with_periodic_evals(&air);
+            let holds: Vec<_> =
+                (0..2).map(|i| air.evaluate_root(kind, i, &ctx) == QuadFelt::ZERO).collect();
+            assert_eq!(holds, expected, "row {row}");
+        }
+    }
+}
+
+#[test]
+fn masked_constraints() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, s],
+    }
+    periodic_columns {
+        k: [1, 0],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + 1 unless k;
+        enf a' = a * 2 when s unless k;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+
+        // The frame violates both constraints, which only vanish on the rows where `k` is 1
+        let log_trace_len = 3;
+        let g = QuadFelt::from(Felt::get_root_of_unity(log_trace_len));
+        let kind = RootKind::Integrity(0);
+        for (row, expected) in [(0, [true, true]), (1, [false, false])] {
+            let ctx = EvalContext {
+                log_trace_len,
+                main: [
+                    vec![QuadFelt::from(5u32), QuadFelt::ONE],
+                    vec![QuadFelt::from(7u32), QuadFelt::ONE],
+                ],
+                z: g.exp(row),
+                ..Default::default()
+            }
+            .with_periodic_evals(&air);
+            let holds: Vec<_> =
+                (0..2).map(|i| air.evaluate_root(kind, i, &ctx) == QuadFelt::ZERO).collect();
+            assert_eq!(holds, expected, "row {row}");
+        }
+    }
+}
+
+#[test]
+fn err_mask_not_periodic_column() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, s],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a + 1 unless s;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        expect_diagnostic(source, "expected a periodic column here", pipeline);
+    }
+}
@@ -0,0 +1,255 @@
+use std::fs;
+
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsConfig, Verbosity};
+use mir::{
+    ir::{Graph, Link, Node, Op},
+    passes::Visitor,
+};
+use winter_math::fields::f64::BaseElement as Felt;
+
+use super::Compiler;
+use crate::{
+    Air, ConstraintEvaluator, EvalContext, PassConfig, QuadFelt, RootKind, compile,
+    compile_with_repro, pipeline::Stages,
+};
+
+const SOURCE: &str = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a * 1 + b - 0;
+        enf b' = b * b;
+    }";
+
+/// Compiles [SOURCE] with the canonical pipeline, configured by `config`
+fn compile_with(compiler: &Compiler, config: PassConfig) -> Air {
+    let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), SOURCE)
+        .expect("parsing failed");
+    compile(&compiler.diagnostics, ast, config).expect("compilation failed")
+}
+
+#[test]
+fn skipping_simplify_produces_a_larger_equivalent_air() {
+    let compiler = Compiler::default();
+    let simplified = compile_with(&compiler, PassConfig::default());
+    let unsimplified =
+        compile_with(&compiler, PassConfig { simplify: false, ..Default::default() });
+    // Optional passes are skipped without any warning
+    assert!(compiler.emitter.captured().is_empty());
+
+    let graph_size = |air: &Air| air.constraint_graph().num_nodes();
+    assert!(graph_size(&unsimplified) > graph_size(&simplified));
+
+    let ctx = EvalContext {
+        main: [[3, 5], [7, 11]].map(|row| row.map(|v| QuadFelt::from(Felt::new(v))).to_vec()),
+        ..Default::default()
+    };
+    let kind = RootKind::Integrity(0);
+    assert_eq!(simplified.num_roots(kind), unsimplified.num_roots(kind));
+    for i in 0..simplified.num_roots(kind) {
+        assert_eq!(
+            simplified.evaluate_root(kind, i, &ctx),
+            unsimplified.evaluate_root(kind, i, &ctx)
+        );
+    }
+}
+
+#[test]
+fn skipping_a_mandatory_pass_warns() {
+    let config = PassConfig {
+        bus_op_expand: false,
+        ..Default::default()
+    };
+    assert_eq!(config.skipped_mandatory_passes(), ["BusOpExpand"]);
+
+    let compiler = Compiler::default();
+    compile_with(&compiler, config);
+    assert!(compiler.emitter.captured().contains("the BusOpExpand pass is disabled"));
+}
+
+#[test]
+fn pass_config_options() {
+    assert_eq!(
+        PassConfig::default().options(),
+        ["max-unroll=10000", "max-unrolled-ops=1000000", "max-exponent=64"]
+    );
+
+    let config = PassConfig {
+        simplify: false,
+        recurrence_boundaries: true,
+        canonicalize: true,
+        max_exponent: 8,
+        ..Default::default()
+    };
+    assert_eq!(
+        config.options(),
+        [
+            "skip=Simplify",
+            "canonicalize",
+            "lint=recurrence-boundaries",
+            "max-unroll=10000",
+            "max-unrolled-ops=1000000",
+            "max-exponent=8"
+        ]
+    );
+}
+
+#[test]
+fn canonicalize_removes_symmetric_duplicates() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a[4], c],
+    }
+    boundary_constraints {
+        enf c.first = 0;
+    }
+    integrity_constraints {
+        let reversed = [a[3], a[2], a[1], a[0]];
+        enf x * y = c for (x, y) in (a, reversed);
+    }";
+    let compiler = Compiler::default();
+    let compile_source = |config| {
+        let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), source)
+            .expect("parsing failed");
+        compile(&compiler.diagnostics, ast, config).expect("compilation failed")
+    };
+    let air = compile_source(PassConfig::default());
+    let canonical = compile_source(PassConfig { canonicalize: true, ..Default::default() });
+
+    let kind = RootKind::Integrity(0);
+    assert_eq!(air.num_roots(kind), 4);
+    assert_eq!(canonical.num_roots(kind), 2);
+
+    // Every constraint of the original AIR is still enforced by the canonical one
+    let mut seed = 0x9e37_79b9_7f4a_7c15_u64;
+    let mut random = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        QuadFelt::from(Felt::new(seed))
+    };
+    for _ in 0..8 {
+        let ctx = EvalContext {
+            main: [(); 2].map(|_| (0..5).map(|_| random()).collect()),
+            ..Default::default()
+        };
+        let evaluations = |air: &Air| {
+            (0..air.num_roots(kind))
+                .map(|i| air.evaluate_root(kind, i, &ctx))
+                .collect::<Vec<_>>()
+        };
+        let (original, canonical) = (evaluations(&air), evaluations(&canonical));
+        assert_eq!(original[0], original[3]);
+        assert_eq!(original[1], original[2]);
+        assert_eq!(canonical, original[..2]);
+    }
+}
+
+#[test]
+fn exponents_are_limited() {
+    let source = SOURCE.replace("enf b' = b * b;", "enf b' = b^65;");
+    // Each compilation gets its own compiler, as parsing fails once an error has been reported
+    let compile_source = |compiler: &Compiler, config| {
+        let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), &source)
+            .expect("parsing failed");
+        compile(&compiler.diagnostics, ast, config)
+    };
+
+    let compiler = Compiler::default();
+    assert!(compile_source(&compiler, PassConfig::default()).is_err());
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("exponent is too large"));
+    assert!(captured.contains("this exponent is 65, which exceeds the limit of 64"));
+    assert!(captured.contains("in the expansion of this constraint"));
+
+    let config = PassConfig { max_exponent: 65, ..Default::default() };
+    let air = compile_source(&Compiler::default(), config).expect("compilation failed");
+    assert_eq!(air.integrity_constraint_degrees(0)[1].base(), 65);
+}
+
+/// A pass panicking on the first addition it visits, standing in for a bug of the compiler
+#[derive(Default)]
+struct PanickingPass {
+    work_stack: Vec<Link<Node>>,
+}
+impl Visitor for PanickingPass {
+    fn work_stack(&mut self) -> &mut Vec<Link<Node>> {
+        &mut self.work_stack
+    }
+    fn root_nodes_to_visit(&self, graph: &Graph) -> Vec<Link<Node>> {
+        graph
+            .integrity_constraints_roots
+            .borrow()
+            .iter()
+            .map(|root| root.as_node())
+            .collect()
+    }
+    fn visit_add(&mut self, _graph: &mut Graph, _add: Link<Op>) -> Result<(), mir::CompileError> {
+        panic!("unexpected addition")
+    }
+}
+
+#[test]
+fn internal_errors_write_a_reproduction() {
+    // The path of the reproduction is reported in a note
+    let compiler = Compiler::new(DiagnosticsConfig {
+        verbosity: Verbosity::Info,
+        warnings_as_errors: true,
+        no_warn: false,
+        display: Default::default(),
+    });
+    let path = std::env::temp_dir().join("air-ir-internal-error.repro");
+    let _ = fs::remove_file(&path);
+
+    let stages = Stages {
+        diagnostics: &compiler.diagnostics,
+        repro: Some(&path),
+    };
+    let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), SOURCE)
+        .expect("parsing failed");
+    let mir = stages
+        .run("AstToMir", ast, |ast| {
+            Ok(mir::passes::AstToMir::new(&compiler.diagnostics).run(ast)?)
+        })
+        .expect("translation failed");
+    assert!(!path.exists());
+
+    let result = stages.run("Panicking", mir, |mut mir| {
+        PanickingPass::default().run(mir.constraint_graph_mut())?;
+        Ok(mir)
+    });
+    assert!(result.is_err());
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("internal error in the Panicking pass: unexpected addition"));
+    assert!(captured.contains("a reproduction of this internal error was written to"));
+
+    // The reproduction holds the constraint being visited when the pass panicked
+    let repro = fs::read_to_string(&path).expect("expected a reproduction to be written");
+    assert!(repro.starts_with("internal error in the Panicking pass: unexpected addition"));
+    assert!(repro.contains("failure point:\nNode::Op(Op::Enf@"));
+    assert!(!repro.contains("input of the pass"));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn successful_compilations_write_no_reproduction() {
+    let compiler = Compiler::default();
+    let path = std::env::temp_dir().join("air-ir-no-internal-error.repro");
+    let _ = fs::remove_file(&path);
+
+    let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), SOURCE)
+        .expect("parsing failed");
+    compile_with_repro(&compiler.diagnostics, ast, PassConfig::default(), &path)
+        .expect("compilation failed");
+    assert!(!path.exists());
+}
@@ -0,0 +1,439 @@
+use air_pass::Pass;
+use miden_diagnostics::Spanned;
+use winter_math::fields::f64::BaseElement as Felt;
+
+use super::{Compiler, Pipeline, compile};
+use crate::{
+    AUX_SEGMENT, Air, BusBoundary, ConstraintEvaluator, EvalContext, PassConfig, ProductError,
+    QuadFelt, RootKind, passes::BusOpExpand,
+};
+
+const LEFT: &str = "
+    def left
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [2],
+    }
+    boundary_constraints {
+        enf a.first = stack_inputs[1];
+    }
+    integrity_constraints {
+        enf a' = a + b;
+        enf b' = a * b;
+    }";
+
+const RIGHT: &str = "
+    def right
+    trace_columns {
+        main: [c],
+    }
+    public_inputs {
+        program_hash: [1],
+        stack_inputs: [3],
+    }
+    boundary_constraints {
+        enf c.last = program_hash[0] + stack_inputs[2];
+    }
+    integrity_constraints {
+        enf c' = c * c + 1;
+    }";
+
+fn felts(values: &[u64]) -> Vec<QuadFelt> {
+    values.iter().map(|v| QuadFelt::from(Felt::new(*v))).collect()
+}
+
+/// Returns the evaluations of every constraint root of `air` of the given kind against `ctx`
+fn evaluate(air: &Air, kind: RootKind, ctx: &EvalContext) -> Vec<QuadFelt> {
+    (0..air.num_roots(kind)).map(|i| air.evaluate_root(kind, i, ctx)).collect()
+}
+
+#[test]
+fn product_combines_two_airs() {
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let left = compile(LEFT, pipeline).expect("compilation failed");
+        let right = compile(RIGHT, pipeline).expect("compilation failed");
+
+        let product = Air::product(&left, &right).expect("product failed");
+        assert_eq!(product.name(), "left_right");
+        assert_eq!(product.trace_segment_widths[0], 3);
+        // Only the public inputs declared by both programs are namespaced
+        let public_inputs: Vec<_> = product.public_inputs().map(|i| i.name().to_string()).collect();
+        assert_eq!(public_inputs, ["left_stack_inputs", "program_hash", "right_stack_inputs"]);
+
+        let left_ctx = EvalContext {
+            main: [felts(&[3, 5]), felts(&[7, 11])],
+            public: vec![felts(&[13, 17])],
+            ..Default::default()
+        };
+        let right_ctx = EvalContext {
+            main: [felts(&[19]), felts(&[23])],
+            public: vec![felts(&[29]), felts(&[31, 37, 41])],
+            ..Default::default()
+        };
+        let product_ctx = EvalContext {
+            main: [felts(&[3, 5, 19]), felts(&[7, 11, 23])],
+            public: vec![felts(&[13, 17]), felts(&[29]), felts(&[31, 37, 41])],
+            ..Default::default()
+        };
+
+        for kind in [RootKind::Integrity(0), RootKind::BoundaryFirst, RootKind::BoundaryLast] {
+            let mut expected = evaluate(&left, kind, &left_ctx);
+            expected.extend(evaluate(&right, kind, &right_ctx));
+            assert_eq!(evaluate(&product, kind, &product_ctx), expected, "{kind:?}");
+        }
+    }
+}
+
+#[test]
+fn product_reorders_bus_columns() {
+    let left = "
+    def left
+    trace_columns {
+        main: [a],
+    }
+    buses {
+        multiset q,
+    }
+    public_inputs {
+        inputs: [1],
+    }
+    boundary_constraints {
+        enf q.first = null;
+        enf q.last = null;
+    }
+    integrity_constraints {
+        q.insert(a) when a;
+        q.remove(a) when 1 - a;
+    }";
+    let right = "
+    def right
+    trace_columns {
+        main: [b],
+    }
+    buses {
+        logup p,
+    }
+    public_inputs {
+        inputs: [1],
+    }
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        p.insert(b, 2) when b;
+        p.remove(b, 2) with 3;
+    }";
+    let left = compile(left, Pipeline::WithMIR).expect("compilation failed");
+    let right = compile(right, Pipeline::WithMIR).expect("compilation failed");
+
+    let product = Air::product(&left, &right).expect("product failed");
+    assert_eq!(product.trace_segment_widths, [2, 2]);
+    assert_eq!(product.num_random_values, right.num_random_values);
+    // Buses are allocated aux columns in name order, so `p` comes first in the product
+    assert_eq!(
+        product.buses.keys().map(|name| name.to_string()).collect::<Vec<_>>(),
+        ["p", "q"]
+    );
+    let layout = product.bus_column_layout();
+    let layout: Vec<_> = layout.iter().map(|(name, column)| (name.as_str(), *column)).collect();
+    assert_eq!(layout, [("p", 0), ("q", 1)]);
+
+    let rand = felts(&[43, 47, 53]);
+    let left_ctx = EvalContext {
+        main: [felts(&[1]), felts(&[0])],
+        aux: [felts(&[59]), felts(&[61])],
+        rand: rand.clone(),
+        ..Default::default()
+    };
+    let right_ctx = EvalContext {
+        main: [felts(&[5]), felts(&[6])],
+        aux: [felts(&[67]), felts(&[71])],
+        rand: rand.clone(),
+        ..Default::default()
+    };
+    let product_ctx = EvalContext {
+        main: [felts(&[1, 5]), felts(&[0, 6])],
+        aux: [felts(&[67, 59]), felts(&[71, 61])],
+        rand,
+        ..Default::default()
+    };
+
+    let kind = RootKind::Integrity(AUX_SEGMENT);
+    let mut expected = evaluate(&left, kind, &left_ctx);
+    expected.extend(evaluate(&right, kind, &right_ctx));
+    assert_eq!(evaluate(&product, kind, &product_ctx), expected);
+}
+
+#[test]
+fn product_reports_conflicts() {
+    let left = compile(LEFT, Pipeline::WithMIR).expect("compilation failed");
+
+    // Namespacing `stack_inputs` in the product of an AIR with itself still results in a conflict
+    let err = Air::product(&left, &left).unwrap_err();
+    assert!(
+        matches!(err, ProductError::PublicInputConflict(name) if name.as_str() == "left_stack_inputs")
+    );
+
+    let with_bus = |name: &str| {
+        format!(
+            "
+    def {name}
+    trace_columns {{
+        main: [a],
+    }}
+    buses {{
+        multiset p,
+    }}
+    public_inputs {{
+        {name}_inputs: [1],
+    }}
+    boundary_constraints {{
+        enf p.first = null;
+        enf p.last = null;
+    }}
+    integrity_constraints {{
+        p.insert(a) when a;
+    }}"
+        )
+    };
+    let left = compile(&with_bus("left"), Pipeline::WithMIR).expect("compilation failed");
+    let right = compile(&with_bus("right"), Pipeline::WithMIR).expect("compilation failed");
+    let err = Air::product(&left, &right).unwrap_err();
+    assert!(matches!(err, ProductError::BusConflict(name) if name.as_str() == "p"));
+}
+
+/// Compiles `source` without expanding its buses, so that it can be linked with other programs
+fn compile_unexpanded(source: &str) -> Air {
+    let compiler = Compiler::default();
+    let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), source)
+        .expect("parsing failed");
+    let config = PassConfig {
+        bus_op_expand: false,
+        ..Default::default()
+    };
+    crate::compile(&compiler.diagnostics, ast, config).expect("compilation failed")
+}
+
+#[test]
+fn link_unifies_extern_buses() {
+    let defining = "
+    def defining
+    trace_columns {
+        main: [a],
+    }
+    buses {
+        multiset p,
+    }
+    public_inputs {
+        defining_inputs: [1],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        p.insert(a) when a;
+        p.remove(a) when 1 - a;
+    }";
+    let left = "
+    def left
+    trace_columns {
+        main: [b],
+    }
+    buses {
+        extern multiset p,
+    }
+    public_inputs {
+        left_inputs: [1],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        p.insert(b) when b;
+        p.remove(b) when 1 - b;
+    }";
+    let right = "
+    def right
+    trace_columns {
+        main: [c],
+    }
+    buses {
+        extern multiset p,
+    }
+    public_inputs {
+        right_inputs: [1],
+    }
+    boundary_constraints {
+        enf c.first = 0;
+    }
+    integrity_constraints {
+        p.insert(c) when c;
+        p.remove(c) when 1 - c;
+    }";
+    // Externs may come before the definition, the operations are merged in the order of the
+    // programs regardless
+    let linked = Air::link(vec![
+        compile_unexpanded(left),
+        compile_unexpanded(defining),
+        compile_unexpanded(right),
+    ])
+    .expect("linking failed");
+    assert_eq!(linked.name.as_str(), "left");
+    assert_eq!(linked.trace_segment_widths, [3, 1]);
+    let bus = &linked.buses.values().next().unwrap();
+    assert!(!bus.is_extern);
+    assert_eq!(bus.bus_ops.len(), 6);
+    assert_eq!((&bus.first, &bus.last), (&BusBoundary::Null, &BusBoundary::Null));
+
+    let linked = BusOpExpand::new(&Compiler::default().diagnostics)
+        .run(linked)
+        .expect("bus expansion failed");
+
+    // The same operations, written in a single program
+    let merged = "
+    def merged
+    trace_columns {
+        main: [b, a, c],
+    }
+    buses {
+        multiset p,
+    }
+    public_inputs {
+        inputs: [1],
+    }
+    boundary_constraints {
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        p.insert(b) when b;
+        p.remove(b) when 1 - b;
+        p.insert(a) when a;
+        p.remove(a) when 1 - a;
+        p.insert(c) when c;
+        p.remove(c) when 1 - c;
+    }";
+    let merged = compile(merged, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(linked.num_random_values, merged.num_random_values);
+
+    let ctx = EvalContext {
+        main: [felts(&[1, 0, 1]), felts(&[0, 1, 1])],
+        aux: [felts(&[59]), felts(&[61])],
+        rand: felts(&[43, 47]),
+        ..Default::default()
+    };
+    let kind = RootKind::Integrity(AUX_SEGMENT);
+    assert_eq!(linked.num_roots(kind), 1);
+    assert_eq!(evaluate(&linked, kind, &ctx), evaluate(&merged, kind, &ctx));
+}
+
+#[test]
+fn link_reports_mismatched_extern_buses() {
+    let defining = "
+    def defining
+    trace_columns {
+        main: [a],
+    }
+    buses {
+        multiset p,
+    }
+    public_inputs {
+        defining_inputs: [1],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        p.insert(a) when a;
+    }";
+    let left = "
+    def left
+    trace_columns {
+        main: [b],
+    }
+    buses {
+        extern multiset p,
+    }
+    public_inputs {
+        left_inputs: [1],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        p.insert(b) when b;
+    }";
+    let right = "
+    def right
+    trace_columns {
+        main: [b],
+    }
+    buses {
+        extern multiset p,
+    }
+    public_inputs {
+        right_inputs: [1],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        p.insert(b) when b;
+    }";
+
+    let err = Air::link(vec![compile_unexpanded(left), compile_unexpanded(right)]).unwrap_err();
+    let ProductError::UnresolvedExternBus(declarations) = err else {
+        panic!("expected an unresolved extern bus, got {err:?}");
+    };
+    // Each declaration is reported, with its own span
+    assert_eq!(declarations.len(), 2);
+    assert!(declarations.iter().all(|name| name.as_str() == "p"));
+    assert_ne!(declarations[0].span(), declarations[1].span());
+
+    let logup = "
+    def left
+    trace_columns {
+        main: [b],
+    }
+    buses {
+        extern logup p,
+    }
+    public_inputs {
+        left_inputs: [1],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        p.insert(b) when b;
+    }";
+    let err = Air::link(vec![compile_unexpanded(defining), compile_unexpanded(logup)]).unwrap_err();
+    assert!(matches!(err, ProductError::BusTypeMismatch(name) if name.as_str() == "p"));
+
+    let pairs = "
+    def left
+    trace_columns {
+        main: [b],
+    }
+    buses {
+        extern multiset p,
+    }
+    public_inputs {
+        left_inputs: [1],
+    }
+    boundary_constraints {
+        enf b.first = 0;
+    }
+    integrity_constraints {
+        p.insert(b, b) when b;
+    }";
+    let err = Air::link(vec![compile_unexpanded(defining), compile_unexpanded(pairs)]).unwrap_err();
+    assert!(matches!(err, ProductError::BusArityMismatch(name, 1, 2) if name.as_str() == "p"));
+}
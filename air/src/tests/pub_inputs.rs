@@ -1,4 +1,5 @@
-use super::{Pipeline, compile};
+use super::{Pipeline, compile, expect_diagnostic};
+use crate::{PublicInputKind, PublicInputSchema};
 
 #[test]
 fn bc_with_public_inputs() {
@@ -20,3 +21,81 @@ fn bc_with_public_inputs() {
     assert!(compile(source, Pipeline::WithoutMIR).is_ok());
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
+
+#[test]
+fn err_public_input_access_out_of_bounds() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = stack_inputs[20];
+    }
+    integrity_constraints {
+        enf clk' = clk - 1;
+    }";
+
+    expect_diagnostic(
+        source,
+        "attempted to access an index which is out of bounds",
+        Pipeline::WithoutMIR,
+    );
+    expect_diagnostic(
+        source,
+        "attempted to access an index which is out of bounds",
+        Pipeline::WithMIR,
+    );
+
+    // Slices of a public input are checked as well
+    let slice = source.replace("stack_inputs[20]", "sum(stack_inputs[10..20])");
+    expect_diagnostic(
+        &slice,
+        "attempted to access an index which is out of bounds",
+        Pipeline::WithMIR,
+    );
+}
+
+#[test]
+fn public_input_schema() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a],
+    }
+    buses {
+        multiset p,
+    }
+    public_inputs {
+        stack_inputs: [16],
+        inputs: [[2]],
+    }
+    boundary_constraints {
+        enf a.first = stack_inputs[0];
+        enf p.first = null;
+        enf p.last = inputs;
+    }
+    integrity_constraints {
+        enf a = 0;
+    }";
+
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let expected = vec![
+        PublicInputSchema {
+            name: "inputs".to_string(),
+            kind: PublicInputKind::Table,
+            size: 2,
+        },
+        PublicInputSchema {
+            name: "stack_inputs".to_string(),
+            kind: PublicInputKind::Vector,
+            size: 16,
+        },
+    ];
+    assert_eq!(air.public_input_schema(), expected);
+    assert_eq!(expected[0].num_elements(3), 6);
+    assert_eq!(expected[1].num_elements(3), 16);
+}
@@ -1 +1,131 @@
+use air_pass::Pass;
+use miden_diagnostics::{DiagnosticsConfig, Verbosity};
+use winter_math::fields::f64::BaseElement as Felt;
 
+use super::{Compiler, Pipeline};
+use crate::{
+    AUX_SEGMENT, Air, ConstraintEvaluator, EvalContext, PassConfig, QuadFelt, RootKind,
+    WitnessRegion, compile,
+    passes::{DeadRandomValues, Simplify},
+};
+
+/// A bus drawing 4 random values, of which only the first and the third are used once the
+/// constant columns of its tuples are simplified away
+const SOURCE: &str = "
+    def test
+    trace_columns {
+        main: [a, b, s],
+    }
+    buses {
+        multiset p,
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        enf s^2 = s;
+        p.insert(0, a, 0) when s;
+        p.remove(0, b, 0) when s;
+    }";
+
+/// Compiles `source`, then simplifies it, and returns it before and after removing its dead random
+/// values, along with the diagnostics captured.
+fn compile_and_eliminate(source: &str) -> (Air, Air, String) {
+    let compiler = Compiler::new(DiagnosticsConfig {
+        verbosity: Verbosity::Info,
+        warnings_as_errors: true,
+        no_warn: false,
+        display: Default::default(),
+    });
+    let compile = || {
+        let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+        Simplify::new(&compiler.diagnostics).run(air).expect("pass failed")
+    };
+    let air = compile();
+    let eliminated = DeadRandomValues::new(&compiler.diagnostics)
+        .run(compile())
+        .expect("pass failed");
+    (air, eliminated, compiler.emitter.captured())
+}
+
+#[test]
+fn dead_random_values_are_removed() {
+    let (air, eliminated, captured) = compile_and_eliminate(SOURCE);
+    assert_eq!(air.num_random_values, 4);
+    assert_eq!(eliminated.num_random_values, 2);
+    assert!(captured.contains("2 of the 4 random values are unused, and were removed"));
+    assert!(captured.contains("The random values at indices 1, 3 are not read by any constraint"));
+    assert!(captured.contains("4 random values are declared for these buses"));
+    assert!(
+        eliminated
+            .witness_layout()
+            .regions()
+            .contains(&WitnessRegion::RandomValues { count: 2 })
+    );
+
+    // The constraints are unchanged, once the random values are renumbered
+    let felt = |value: u64| QuadFelt::from(Felt::new(value));
+    let main = [[3, 5, 1], [7, 11, 1]].map(|row| row.map(felt).to_vec());
+    let aux = [[13], [17]].map(|row| row.map(felt).to_vec());
+    let rand = [19, 23, 29, 31].map(felt);
+    let ctx = EvalContext {
+        main: main.clone(),
+        aux: aux.clone(),
+        rand: rand.to_vec(),
+        ..Default::default()
+    };
+    let renumbered = EvalContext {
+        main,
+        aux,
+        rand: vec![rand[0], rand[2]],
+        ..Default::default()
+    };
+    let kind = RootKind::Integrity(AUX_SEGMENT);
+    assert_eq!(eliminated.num_roots(kind), air.num_roots(kind));
+    for i in 0..air.num_roots(kind) {
+        assert_eq!(
+            eliminated.evaluate_root(kind, i, &renumbered),
+            air.evaluate_root(kind, i, &ctx)
+        );
+    }
+}
+
+#[test]
+fn used_random_values_are_kept() {
+    let source = SOURCE.replace("p.insert(0, a, 0)", "p.insert(0, a, b)");
+    let (air, eliminated, captured) = compile_and_eliminate(&source);
+    assert_eq!(air.num_random_values, 4);
+    // Only the random value combined with the first column of the tuples is still unused
+    assert_eq!(eliminated.num_random_values, 3);
+    assert!(captured.contains("The random value at index 1 is not read by any constraint"));
+
+    let source = SOURCE.replace("p.insert(0, a, 0)", "p.insert(b, a, b)");
+    let (_, eliminated, captured) = compile_and_eliminate(&source);
+    assert_eq!(eliminated.num_random_values, 4);
+    assert!(!captured.contains("random values are unused"));
+}
+
+#[test]
+fn exported_random_values_are_kept() {
+    let compiler = Compiler::default();
+    let compile_source = |config| {
+        let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), SOURCE)
+            .expect("parsing failed");
+        compile(&compiler.diagnostics, ast, config).expect("compilation failed")
+    };
+
+    let config = PassConfig {
+        dead_random_values: true,
+        ..Default::default()
+    };
+    assert_eq!(compile_source(config).num_random_values, 2);
+
+    let config = PassConfig { export_random_values: true, ..config };
+    assert_eq!(compile_source(config).num_random_values, 4);
+    assert_eq!(config.options()[..2], ["dead-random-values", "export-random-values"]);
+}
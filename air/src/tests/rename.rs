@@ -0,0 +1,84 @@
+use miden_diagnostics::SourceSpan;
+use winter_math::fields::f64::BaseElement as Felt;
+
+use super::{Pipeline, compile};
+use crate::{
+    ConstraintEvaluator, EvalContext, Identifier, QuadFelt, RenameError, RootKind, Symbol,
+};
+
+const SOURCE: &str = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        program_hash: [1],
+        stack_inputs: [2],
+    }
+    boundary_constraints {
+        enf a.first = stack_inputs[1] + program_hash[0];
+    }
+    integrity_constraints {
+        enf a' = a + b;
+    }";
+
+fn ident(name: &str) -> Identifier {
+    Identifier::new(SourceSpan::UNKNOWN, Symbol::intern(name))
+}
+
+fn felts(values: &[u64]) -> Vec<QuadFelt> {
+    values.iter().map(|v| QuadFelt::from(Felt::new(*v))).collect()
+}
+
+#[test]
+fn rename_public_input() {
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let mut air = compile(SOURCE, pipeline).expect("compilation failed");
+        let ctx = EvalContext {
+            main: [felts(&[11, 13]), felts(&[17, 19])],
+            public: vec![felts(&[5]), felts(&[3, 7])],
+            ..Default::default()
+        };
+        let expected = air.evaluate_root(RootKind::BoundaryFirst, 0, &ctx);
+
+        air.rename_public_input(ident("stack_inputs"), ident("inputs")).unwrap();
+        let public_inputs: Vec<_> = air.public_inputs().map(|i| i.name().to_string()).collect();
+        assert_eq!(public_inputs, ["inputs", "program_hash"]);
+
+        // Public inputs are ordered by name, so the renamed input is now expected first
+        let ctx = EvalContext {
+            public: vec![felts(&[3, 7]), felts(&[5])],
+            ..ctx
+        };
+        assert_eq!(air.evaluate_root(RootKind::BoundaryFirst, 0, &ctx), expected);
+    }
+}
+
+#[test]
+fn rename_detects_collisions() {
+    let mut air = compile(SOURCE, Pipeline::WithMIR).expect("compilation failed");
+
+    assert_eq!(
+        air.rename_public_input(ident("stack_inputs"), ident("program_hash")),
+        Err(RenameError::NameConflict(ident("program_hash")))
+    );
+    assert_eq!(
+        air.rename_public_input(ident("stack_inputs"), ident("$main")),
+        Err(RenameError::NameConflict(ident("$main")))
+    );
+    assert_eq!(
+        air.rename_public_input(ident("inputs"), ident("outputs")),
+        Err(RenameError::UndefinedPublicInput(ident("inputs")))
+    );
+    assert_eq!(
+        air.rename_trace_segment(ident("$main"), ident("stack_inputs")),
+        Err(RenameError::NameConflict(ident("stack_inputs")))
+    );
+    assert_eq!(
+        air.rename_trace_segment(ident("aux"), ident("trace")),
+        Err(RenameError::UndefinedTraceSegment(ident("aux")))
+    );
+
+    air.rename_trace_segment(ident("$main"), ident("trace")).unwrap();
+    assert_eq!(air.trace_segment_name(0), Symbol::intern("trace"));
+}
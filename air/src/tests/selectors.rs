@@ -1,4 +1,7 @@
-use super::{Pipeline, compile};
+use air_pass::Pass;
+
+use super::{Compiler, Pipeline, assert_equivalent_integrity_constraints, compile};
+use crate::{DEFAULT_SEGMENT, PassConfig, passes::SelectorBooleanity};
 
 #[test]
 fn single_selector() {
@@ -29,7 +32,7 @@ fn chained_selectors() {
     trace_columns {
         main: [s[3], clk],
     }
-    
+
     public_inputs {
         stack_inputs: [16],
     }
@@ -79,7 +82,7 @@ fn selectors_in_evaluators() {
     ev evaluator_with_selector([selector, clk]) {
         enf clk' - clk = 0 when selector;
     }
-    
+
     trace_columns {
         main: [s[3], clk],
     }
@@ -107,7 +110,7 @@ fn multiple_selectors_in_evaluators() {
     ev evaluator_with_selector([s0, s1, clk]) {
         enf clk' - clk = 0 when s0 & !s1;
     }
-    
+
     trace_columns {
         main: [s[3], clk],
     }
@@ -135,7 +138,7 @@ fn selector_with_evaluator_call() {
     ev unchanged([clk]) {
         enf clk' = clk;
     }
-    
+
     trace_columns {
         main: [s[3], clk],
     }
@@ -156,6 +159,68 @@ fn selector_with_evaluator_call() {
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
 
+#[test]
+fn selector_with_multiconstraint_evaluator_call() {
+    let source = "
+    def test
+    ev push([a, b]) {
+        enf a' = b;
+        enf b' = b + 1;
+    }
+
+    trace_columns {
+        main: [a, b, s],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf push([a, b]) when s;
+        enf s^2 = s;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, s],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf s * (a' - b) = 0;
+        enf s * (b' - (b + 1)) = 0;
+        enf s^2 = s;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        assert_equivalent_integrity_constraints(source, expected, pipeline);
+    }
+
+    // Through the full pipeline, the selector is distributed over the constraints of the evaluator
+    let compiler = Compiler::default();
+    let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), source)
+        .expect("parsing failed");
+    let air = crate::compile(&compiler.diagnostics, ast, PassConfig::default())
+        .expect("compilation failed");
+    let roots = air.integrity_constraints(DEFAULT_SEGMENT);
+    assert_eq!(roots.len(), 3);
+    let num_selectors: Vec<_> =
+        roots.iter().map(|root| air.constraint_selectors(root).len()).collect();
+    assert_eq!(num_selectors, [1, 1, 0]);
+}
+
 #[test]
 fn selectors_inside_match() {
     let source = "
@@ -175,7 +240,7 @@ fn selectors_inside_match() {
     trace_columns {
         main: [s[3], clk],
     }
- 
+
     public_inputs {
         stack_inputs: [16],
     }
@@ -195,3 +260,183 @@ fn selectors_inside_match() {
     assert!(compile(source, Pipeline::WithoutMIR).is_ok());
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
+
+#[test]
+fn warn_non_binary_selector() {
+    let source = "
+    def test
+    trace_columns {
+        main: [s, clk],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk' = clk + 1 when s;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    SelectorBooleanity::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("selector column `s` is not constrained to be binary"));
+    assert!(captured.contains("Consider enforcing `s * s = s`"));
+}
+
+#[test]
+fn binary_attribute_constrains_selector() {
+    let source = "
+    def test
+    trace_columns {
+        main: [@binary s, clk],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk' = clk + 1 when s;
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    SelectorBooleanity::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(!captured.contains("not constrained to be binary"));
+
+    // The attribute generates the constraint itself
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    assert_eq!(air.integrity_constraints(0).len(), 2);
+}
+
+#[test]
+fn explicit_constraint_constrains_selector() {
+    let sources = [
+        "
+        def test
+        trace_columns {
+            main: [s, clk],
+        }
+
+        public_inputs {
+            stack_inputs: [16],
+        }
+
+        boundary_constraints {
+            enf clk.first = 0;
+        }
+
+        integrity_constraints {
+            enf s * (1 - s) = 0;
+            enf clk' = clk + 1 when s;
+        }",
+        "
+        def test
+        trace_columns {
+            main: [s, clk],
+        }
+
+        public_inputs {
+            stack_inputs: [16],
+        }
+
+        boundary_constraints {
+            enf clk.first = 0;
+        }
+
+        integrity_constraints {
+            enf s * s = s;
+            enf clk' = clk + 1 when s;
+        }",
+        "
+        def test
+        trace_columns {
+            main: [s, clk],
+        }
+
+        public_inputs {
+            stack_inputs: [16],
+        }
+
+        boundary_constraints {
+            enf clk.first = 0;
+        }
+
+        integrity_constraints {
+            enf s' * (s' - 1) = 0;
+            enf clk' = clk + 1 when s;
+        }",
+    ];
+    for source in sources {
+        let compiler = Compiler::default();
+        let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+        SelectorBooleanity::new(&compiler.diagnostics).run(air).expect("pass failed");
+        let captured = compiler.emitter.captured();
+        assert!(!captured.contains("not constrained to be binary"), "{source}:\n{captured}");
+    }
+}
+
+#[test]
+fn compound_selector_checks_each_column() {
+    let source = "
+    def test
+    trace_columns {
+        main: [s[2], clk],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk' = clk + 1 when s[0] & !s[1];
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    SelectorBooleanity::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("selector column `s[0]` is not constrained to be binary"));
+    assert!(captured.contains("selector column `s[1]` is not constrained to be binary"));
+
+    let source = "
+    def test
+    trace_columns {
+        main: [s[2], clk],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf s[1] * s[1] = s[1];
+        enf clk' = clk + 1 when s[0] & !s[1];
+    }";
+
+    let compiler = Compiler::default();
+    let air = compiler.compile(source, Pipeline::WithMIR).expect("compilation failed");
+    SelectorBooleanity::new(&compiler.diagnostics).run(air).expect("pass failed");
+    let captured = compiler.emitter.captured();
+    assert!(captured.contains("selector column `s[0]` is not constrained to be binary"));
+    assert!(!captured.contains("selector column `s[1]`"));
+}
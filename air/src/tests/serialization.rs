@@ -0,0 +1,101 @@
+use air_pass::Pass;
+use miden_diagnostics::DiagnosticsHandler;
+use mir::ir::{Mir, SerializableMir};
+
+use super::Compiler;
+use crate::{Air, diff};
+
+const FIXTURES: [(&str, &str); 7] = [
+    ("buses_simple", include_str!("../../../air-script/tests/buses/buses_simple.air")),
+    (
+        "buses_complex",
+        include_str!("../../../air-script/tests/buses/buses_complex.air"),
+    ),
+    (
+        "buses_varlen_boundary_both",
+        include_str!("../../../air-script/tests/buses/buses_varlen_boundary_both.air"),
+    ),
+    (
+        "buses_varlen_boundary_named",
+        include_str!("../../../air-script/tests/buses/buses_varlen_boundary_named.air"),
+    ),
+    (
+        "list_comprehension",
+        include_str!("../../../air-script/tests/list_comprehension/list_comprehension.air"),
+    ),
+    (
+        "list_comprehension_nested",
+        include_str!("../../../air-script/tests/list_comprehension/list_comprehension_nested.air"),
+    ),
+    (
+        "constraint_comprehension",
+        include_str!(
+            "../../../air-script/tests/constraint_comprehension/constraint_comprehension.air"
+        ),
+    ),
+];
+
+type MirPass = fn(&DiagnosticsHandler, Mir) -> Mir;
+
+/// The passes run on the MIR after its translation from the AST, in order
+const MIR_PASSES: [MirPass; 2] = [inline, unroll];
+
+fn inline(diagnostics: &DiagnosticsHandler, mir: Mir) -> Mir {
+    mir::passes::Inlining::new(diagnostics).run(mir).expect("inlining failed")
+}
+
+fn unroll(diagnostics: &DiagnosticsHandler, mir: Mir) -> Mir {
+    mir::passes::Unrolling::new(diagnostics).run(mir).expect("unrolling failed")
+}
+
+/// Translates `source` to MIR, and runs the first `num_passes` of [MIR_PASSES] on it
+fn translate(compiler: &Compiler, source: &str, num_passes: usize) -> Mir {
+    let diagnostics = &compiler.diagnostics;
+    let ast =
+        air_parser::parse(diagnostics, compiler.codemap.clone(), source).expect("parsing failed");
+    let ast = air_parser::transforms::ConstantPropagation::new(diagnostics)
+        .run(ast)
+        .expect("constant propagation failed");
+    let mir = mir::passes::AstToMir::new(diagnostics).run(ast).expect("translation failed");
+    MIR_PASSES[..num_passes].iter().fold(mir, |mir, pass| pass(diagnostics, mir))
+}
+
+/// Runs the passes following the first `num_passes` of [MIR_PASSES] on `mir`, and lowers it to
+/// [Air]
+fn lower(compiler: &Compiler, mir: Mir, num_passes: usize) -> Air {
+    let diagnostics = &compiler.diagnostics;
+    let mir = MIR_PASSES[num_passes..].iter().fold(mir, |mir, pass| pass(diagnostics, mir));
+    crate::passes::MirToAir::new(diagnostics)
+        .chain(crate::passes::BusOpExpand::new(diagnostics))
+        .run(mir)
+        .expect("lowering failed")
+}
+
+#[test]
+fn mir_round_trips_through_json_after_each_pass() {
+    for (name, source) in FIXTURES {
+        for num_passes in 0..=MIR_PASSES.len() {
+            let compiler = Compiler::default();
+            let mir = translate(&compiler, source, num_passes);
+
+            let serializable = mir.to_serializable();
+            let json = serde_json::to_string(&serializable).expect("serialization failed");
+            let deserialized: SerializableMir =
+                serde_json::from_str(&json).expect("deserialization failed");
+            assert_eq!(deserialized, serializable, "{name} after {num_passes} passes");
+
+            // Shared nodes and the owners of parameters are the same once imported
+            let imported = deserialized.into_mir();
+            assert_eq!(
+                imported.to_serializable(),
+                serializable,
+                "{name} after {num_passes} passes"
+            );
+
+            let expected = lower(&compiler, mir, num_passes);
+            let air = lower(&compiler, imported, num_passes);
+            let diff = diff(&expected, &air);
+            assert!(diff.is_equivalent(), "{name} after {num_passes} passes:\n{diff}");
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use air_pass::Pass;
+
+use super::{Compiler, Pipeline};
+use crate::{Air, NodeIndex, Operation, Value, passes::Simplify};
+
+/// Returns true if the node at `index` is an operation which one of the identities simplifies
+fn is_identity(air: &Air, index: &NodeIndex) -> bool {
+    let graph = air.constraint_graph();
+    let is_constant = |index: &NodeIndex, value: u64| matches!(graph.node(index).op(), Operation::Value(Value::Constant(c)) if *c == value);
+    match graph.node(index).op() {
+        Operation::Value(_) => false,
+        Operation::Add(lhs, rhs) => is_constant(lhs, 0) || is_constant(rhs, 0),
+        Operation::Sub(lhs, rhs) => is_constant(rhs, 0) || lhs == rhs,
+        Operation::Mul(lhs, rhs) => {
+            [0, 1].iter().any(|c| is_constant(lhs, *c) || is_constant(rhs, *c))
+        },
+    }
+}
+
+/// Returns true if any node reachable from an integrity constraint root is an identity
+fn has_identities(air: &Air) -> bool {
+    fn visit(air: &Air, index: &NodeIndex) -> bool {
+        is_identity(air, index)
+            || match air.constraint_graph().node(index).op() {
+                Operation::Value(_) => false,
+                Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) => {
+                    visit(air, lhs) || visit(air, rhs)
+                },
+            }
+    }
+    air.integrity_constraints(0).iter().any(|root| visit(air, root.node_index()))
+}
+
+/// Renders the subgraph at `index`, independently of the indices of its nodes
+fn render(air: &Air, index: &NodeIndex) -> String {
+    match air.constraint_graph().node(index).op() {
+        Operation::Value(value) => format!("{value:?}"),
+        Operation::Add(lhs, rhs) => format!("({} + {})", render(air, lhs), render(air, rhs)),
+        Operation::Sub(lhs, rhs) => format!("({} - {})", render(air, lhs), render(air, rhs)),
+        Operation::Mul(lhs, rhs) => format!("({} * {})", render(air, lhs), render(air, rhs)),
+    }
+}
+
+#[test]
+fn simplify_removes_identities() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a * 1 + 0;
+        enf b' = (b - 0) * c;
+        enf c' = c * (a - a) + b;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = a;
+        enf b' = b * c;
+        enf c' = b;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let compiler = Compiler::default();
+        let air = compiler.compile(source, pipeline).expect("compilation failed");
+        assert!(has_identities(&air));
+
+        let air = Simplify::new(&compiler.diagnostics).run(air).expect("pass failed");
+        assert!(!has_identities(&air));
+
+        let expected = compiler.compile(expected, pipeline).expect("compilation failed");
+        let roots = |air: &Air| {
+            air.integrity_constraints(0)
+                .iter()
+                .map(|root| render(air, root.node_index()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(roots(&air), roots(&expected));
+    }
+}
+
+#[test]
+fn simplify_preserves_boundary_constraint_roots() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf b.last = stack_inputs[0] * 1;
+    }
+    integrity_constraints {
+        enf a' = a + b;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let compiler = Compiler::default();
+        let air = compiler.compile(source, pipeline).expect("compilation failed");
+        let air = Simplify::new(&compiler.diagnostics).run(air).expect("pass failed");
+
+        let graph = air.constraint_graph();
+        for root in air.boundary_constraints(0) {
+            let Operation::Sub(lhs, rhs) = graph.node(root.node_index()).op() else {
+                panic!("expected boundary constraint root to be a subtraction");
+            };
+            assert!(matches!(graph.node(lhs).op(), Operation::Value(Value::TraceAccess(_))));
+            assert!(!is_identity(&air, rhs));
+        }
+    }
+}
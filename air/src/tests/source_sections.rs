@@ -1,4 +1,4 @@
-use super::{Pipeline, expect_diagnostic};
+use super::{Pipeline, compile, expect_diagnostic};
 
 #[test]
 fn err_trace_cols_empty() {
@@ -38,7 +38,8 @@ fn err_trace_cols_omitted() {
 
 #[test]
 fn err_pub_inputs_empty() {
-    // if public inputs are empty, an error should be returned at parser level.
+    // if public inputs are empty, a warning should be emitted at parser level, which is an error
+    // when warnings are treated as errors.
     let source = "
     def test
     trace_columns {
@@ -52,13 +53,13 @@ fn err_pub_inputs_empty() {
         enf clk' = clk + 1;
     }";
 
-    expect_diagnostic(source, "expected one of: 'identifier'", Pipeline::WithoutMIR);
-    expect_diagnostic(source, "expected one of: 'identifier'", Pipeline::WithMIR);
+    expect_diagnostic(source, "empty public_inputs section", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "empty public_inputs section", Pipeline::WithMIR);
 }
 
 #[test]
-fn err_pub_inputs_omitted() {
-    // if public inputs are omitted, an error should be returned at IR level.
+fn pub_inputs_omitted() {
+    // public inputs may be omitted if they are never referenced.
     let source = "
     def test
     trace_columns {
@@ -71,16 +72,71 @@ fn err_pub_inputs_omitted() {
         enf clk' = clk + 1;
     }";
 
-    expect_diagnostic(
-        source,
-        "root module must contain a public_inputs section",
-        Pipeline::WithoutMIR,
-    );
-    expect_diagnostic(
-        source,
-        "root module must contain a public_inputs section",
-        Pipeline::WithMIR,
-    );
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+        assert!(air.public_inputs.is_empty());
+    }
+}
+
+#[test]
+fn err_pub_inputs_omitted_but_referenced() {
+    // if public inputs are omitted but referenced, an error should be returned.
+    let source = "
+    def test
+    trace_columns {
+        main: [clk],
+    }
+    boundary_constraints {
+        enf clk.first = stack_inputs[0];
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+
+    expect_diagnostic(source, "reference to undefined variable / bus", Pipeline::WithoutMIR);
+    expect_diagnostic(source, "reference to undefined variable / bus", Pipeline::WithMIR);
+}
+
+#[test]
+fn reordered_sections() {
+    // the order in which sections are declared has no effect on the resulting AIR.
+    let source = include_str!("../../../air-script/tests/periodic_columns/periodic_columns.air");
+    let reordered = "
+    def PeriodicColumnsAir
+
+    integrity_constraints {
+        enf k0 * (b + c) = 0;
+        enf k1 * (a' - a) = 0;
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    periodic_columns {
+        k0: [1, 0, 0, 0],
+        k1: [1, 1, 1, 1, 1, 1, 1, 0],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    trace_columns {
+        main: [a, b, c],
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let expected = compile(source, pipeline).expect("compilation failed");
+        let air = compile(reordered, pipeline).expect("compilation failed");
+        assert_eq!(air.name, expected.name);
+        assert_eq!(air.trace_segment_widths, expected.trace_segment_widths);
+        assert_eq!(air.periodic_columns, expected.periodic_columns);
+        assert_eq!(air.public_inputs, expected.public_inputs);
+        assert_eq!(air.num_random_values, expected.num_random_values);
+        assert_eq!(air.buses, expected.buses);
+        assert_eq!(format!("{:?}", air.constraints), format!("{:?}", expected.constraints));
+    }
 }
 
 #[test]
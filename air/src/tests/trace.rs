@@ -1,4 +1,9 @@
-use super::{Pipeline, compile, expect_diagnostic};
+use air_parser::ast::TraceSegment;
+use air_pass::Pass;
+use miden_diagnostics::SourceSpan;
+
+use super::{Compiler, Pipeline, compile, expect_diagnostic};
+use crate::{AUX_SEGMENT, Identifier, Symbol, passes::MirToAir};
 
 #[test]
 fn trace_columns_index_access() {
@@ -21,6 +26,66 @@ fn trace_columns_index_access() {
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
 
+#[test]
+fn main_only_trace_has_no_aux_segment() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 1;
+    }
+    integrity_constraints {
+        enf a' = a + b;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+        assert!(!air.has_aux_segment());
+        assert!(!air.uses_random_values());
+    }
+}
+
+#[test]
+fn empty_aux_segment_is_not_materialized() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 1;
+    }
+    integrity_constraints {
+        enf a' = a + b;
+    }";
+
+    let compiler = Compiler::default();
+    let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), source)
+        .expect("parsing failed");
+    let mut mir = air_parser::transforms::ConstantPropagation::new(&compiler.diagnostics)
+        .chain(mir::passes::AstToMir::new(&compiler.diagnostics))
+        .chain(mir::passes::Inlining::new(&compiler.diagnostics))
+        .chain(mir::passes::Unrolling::new(&compiler.diagnostics))
+        .run(ast)
+        .expect("translation to MIR failed");
+    let name = Identifier::new(SourceSpan::UNKNOWN, Symbol::intern("aux"));
+    mir.trace_columns
+        .push(TraceSegment::new(SourceSpan::UNKNOWN, AUX_SEGMENT, name, vec![]));
+
+    let air = MirToAir::new(&compiler.diagnostics).run(mir).expect("lowering failed");
+    assert_eq!(air.trace_segment_widths, [2]);
+    assert_eq!(air.trace_segment_names.len(), 1);
+    assert!(!air.has_aux_segment());
+}
+
 #[test]
 fn trace_cols_groups() {
     let source = "
@@ -176,3 +241,41 @@ fn err_ic_trace_cols_group_used_as_scalar() {
     expect_diagnostic(source, "type mismatch", Pipeline::WithoutMIR);
     expect_diagnostic(source, "type mismatch", Pipeline::WithMIR);
 }
+
+#[test]
+fn wide_trace_segments() {
+    let with_width = |width: usize| {
+        format!(
+            "
+    def test
+    trace_columns {{
+        main: [a, b[{}]],
+    }}
+    public_inputs {{
+        stack_inputs: [16],
+    }}
+    boundary_constraints {{
+        enf a.first = 1;
+    }}
+    integrity_constraints {{
+        enf a' = a + b[{}];
+    }}",
+            width - 1,
+            width - 2
+        )
+    };
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        for width in [255, 256, u16::MAX as usize] {
+            let air = compile(&with_width(width), pipeline).expect("compilation failed");
+            assert_eq!(air.trace_segment_widths, [width as u16]);
+        }
+
+        // The width of a segment must fit in the `u16` widths of the Air, instead of wrapping
+        expect_diagnostic(
+            &with_width(u16::MAX as usize + 1),
+            "trace segment width 65536 exceeds the maximum supported 65535",
+            pipeline,
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use air_pass::Pass;
+
+use super::{Compiler, Pipeline};
+use crate::{
+    PassConfig, TraceLayoutRegistry, TraceLayoutSpec, compile, passes::ValidateTraceLayout,
+};
+
+const SOURCE: &str = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    buses {
+        multiset p,
+        logup q,
+    }
+    public_inputs {
+        inputs: [2],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf p.first = null;
+        enf q.first = null;
+        enf p.last = null;
+        enf q.last = null;
+    }
+    integrity_constraints {
+        enf a' = a + 1;
+        p.insert(a, b) when c;
+        p.remove(a, b) when c;
+        q.insert(a) with b;
+        q.remove(a) with b;
+    }";
+
+/// A layout into which [SOURCE] fits exactly
+const EXACT: TraceLayoutSpec = TraceLayoutSpec {
+    main_width: 4,
+    aux_width_max: 2,
+    num_random_max: 3,
+    reserved_columns: 1,
+};
+
+/// Validates [SOURCE] against `spec`, returning the validated [crate::Air] if it fits, or the
+/// captured diagnostics otherwise
+fn validate(spec: TraceLayoutSpec) -> Result<crate::Air, String> {
+    let compiler = Compiler::default();
+    let air = compiler.compile(SOURCE, Pipeline::WithMIR).expect("compilation failed");
+    let expected = format!("{air:?}");
+    match ValidateTraceLayout::new(&compiler.diagnostics, spec).run(air) {
+        Ok(air) => {
+            assert_eq!(format!("{air:?}"), expected);
+            Ok(air)
+        },
+        Err(_) => Err(compiler.emitter.captured()),
+    }
+}
+
+#[test]
+fn trace_layout_fits() {
+    assert!(validate(EXACT).is_ok());
+    assert!(validate(TraceLayoutSpec { main_width: 8, ..EXACT }).is_ok());
+}
+
+#[test]
+fn err_trace_layout_main_width() {
+    let spec = TraceLayoutSpec { reserved_columns: 2, ..EXACT };
+    let err = validate(spec).unwrap_err();
+    assert!(err.contains("main trace width exceeds the budget of the target layout"));
+    assert!(err.contains("this segment declares 3 columns, but at most 2 are available"));
+    assert!(!err.contains("aux trace width"));
+    assert!(!err.contains("number of random values"));
+}
+
+#[test]
+fn err_trace_layout_aux_width() {
+    let spec = TraceLayoutSpec { aux_width_max: 1, ..EXACT };
+    let err = validate(spec).unwrap_err();
+    assert!(err.contains("aux trace width exceeds the budget of the target layout"));
+    assert!(!err.contains("main trace width"));
+    assert!(!err.contains("number of random values"));
+}
+
+#[test]
+fn err_trace_layout_random_values() {
+    let spec = TraceLayoutSpec { num_random_max: 2, ..EXACT };
+    let err = validate(spec).unwrap_err();
+    assert!(err.contains("number of random values exceeds the budget of the target layout"));
+    assert!(!err.contains("main trace width"));
+    assert!(!err.contains("aux trace width"));
+}
+
+#[test]
+fn trace_layout_registry() {
+    let mut registry = TraceLayoutRegistry::default();
+    assert_eq!(registry.iter().count(), 0);
+
+    assert_eq!(registry.register("test", EXACT), None);
+    assert_eq!(registry.register("wide", TraceLayoutSpec { main_width: 8, ..EXACT }), None);
+    assert_eq!(registry.get("test"), Some(&EXACT));
+    assert_eq!(registry.get("unknown"), None);
+    assert_eq!(registry.iter().map(|(name, _)| name).collect::<Vec<_>>(), ["test", "wide"]);
+
+    let spec = TraceLayoutSpec { aux_width_max: 1, ..EXACT };
+    assert_eq!(registry.register("test", spec), Some(EXACT));
+    assert_eq!(registry.get("test"), Some(&spec));
+}
+
+#[test]
+fn trace_layout_target_of_the_pipeline() {
+    let mut registry = TraceLayoutRegistry::default();
+    registry.register("test", EXACT);
+    registry.register("narrow", TraceLayoutSpec { main_width: 3, ..EXACT });
+
+    let compile_for = |name: &str| {
+        let compiler = Compiler::default();
+        let ast = air_parser::parse(&compiler.diagnostics, compiler.codemap.clone(), SOURCE)
+            .expect("parsing failed");
+        let config = PassConfig {
+            target: registry.get(name).copied(),
+            ..Default::default()
+        };
+        compile(&compiler.diagnostics, ast, config).map_err(|_| compiler.emitter.captured())
+    };
+
+    assert!(compile_for("test").is_ok());
+    let err = compile_for("narrow").unwrap_err();
+    assert!(err.contains("main trace width exceeds the budget of the target layout"));
+    assert!(err.contains("this segment declares 3 columns, but at most 2 are available"));
+}
@@ -1,4 +1,4 @@
-use super::{Pipeline, compile, expect_diagnostic};
+use super::{Pipeline, assert_equivalent_integrity_constraints, compile, expect_diagnostic};
 
 #[test]
 fn let_scalar_constant_in_boundary_constraint() {
@@ -116,6 +116,37 @@ fn invalid_column_offset_in_boundary_constraint() {
     expect_diagnostic(source, "invalid access of a trace column with offset", Pipeline::WithMIR);
 }
 
+#[test]
+fn periodic_column_offset_in_integrity_constraint() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk],
+    }
+    periodic_columns {
+        k: [1, 0],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + k';
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(source, pipeline).expect("compilation failed");
+        let shifted = air.shifted_periodic_columns();
+        assert_eq!(shifted.len(), 1);
+        assert_eq!(
+            (shifted[0].name.to_string(), shifted[0].row_offset),
+            ("test::k".to_string(), 1)
+        );
+    }
+}
+
 #[test]
 fn nested_let_with_expressions_in_integrity_constraint() {
     let source = "
@@ -192,12 +223,12 @@ fn invalid_matrix_literal_with_leading_vector_binding() {
 
     expect_diagnostic(
         source,
-        "expected one of: '\"!\"', '\"(\"', '\"null\"', '\"unconstrained\"', 'decl_ident_ref', 'function_identifier', 'identifier', 'int'",
+        "expected one of: '\"!\"', '\"(\"', '\"-\"', '\"null\"', '\"unconstrained\"', 'decl_ident_ref', 'function_identifier', 'identifier', 'int'",
         Pipeline::WithoutMIR,
     );
     expect_diagnostic(
         source,
-        "expected one of: '\"!\"', '\"(\"', '\"null\"', '\"unconstrained\"', 'decl_ident_ref', 'function_identifier', 'identifier', 'int'",
+        "expected one of: '\"!\"', '\"(\"', '\"-\"', '\"null\"', '\"unconstrained\"', 'decl_ident_ref', 'function_identifier', 'identifier', 'int'",
         Pipeline::WithMIR,
     );
 }
@@ -443,3 +474,415 @@ fn trace_binding_access_in_integrity_constraint() {
     assert!(compile(source, Pipeline::WithoutMIR).is_ok());
     assert!(compile(source, Pipeline::WithMIR).is_ok());
 }
+
+#[test]
+fn let_matrix_rows_in_comprehension() {
+    // The rows of a matrix are bound per-iteration, and indexed with constants
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        enf row[0] = row[1]' for row in m;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a = b';
+        enf c = d';
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_matrix_constant_index_in_comprehension() {
+    // Both indices are constant, while the comprehension iterates over another binding
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        enf m[1][0] = row[1] for row in m;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf c = b;
+        enf c = d;
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_matrix_access_with_offset() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        enf m[0][1]' = m[1][0];
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf b' = c;
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_matrix_row_access_with_offset() {
+    // A row of the matrix is bound to a variable, which is then indexed
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        let r = m[1];
+        enf r[1]' = r[0];
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf d' = c;
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_matrix_bound_row_index_in_comprehension() {
+    // The row is bound per-iteration, and the column is constant
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        enf m[i][0] = m[i][1]' for i in 0..2;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a = b';
+        enf c = d';
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_matrix_bound_column_index_in_comprehension() {
+    // The row is constant, and the column is bound per-iteration
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        enf m[0][j]' = m[1][j] + j for j in 0..2;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = c;
+        enf b' = d + 1;
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_matrix_bound_indices_in_comprehension() {
+    // Both indices are bound per-iteration
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        enf m[i][j] = m[j][i]' + i for (i, j) in (0..2, 0..2);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a = a';
+        enf d = d' + 1;
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn err_let_matrix_bound_index_out_of_bounds() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        enf m[i][0] = 0 for i in 0..3;
+    }";
+    let expected = "at iteration 2 of this comprehension, `m[i][0]` accesses `m[2][0]`, which is out of bounds";
+
+    expect_diagnostic(source, expected, Pipeline::WithoutMIR);
+    expect_diagnostic(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn err_let_matrix_index_by_unbound_variable() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c, d],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let m = [[a, b], [c, d]];
+        enf m[k][0] = 0 for i in 0..2;
+    }";
+
+    expect_diagnostic(
+        source,
+        "indices must be constant integers, or bindings of an enclosing list comprehension",
+        Pipeline::WithMIR,
+    );
+}
+
+#[test]
+fn let_body_with_multiple_enforces() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c[2], d[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        let t = a + b;
+        enf t * c[0] = 0;
+        enf t * d[0] = 0;
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c[2], d[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf (a + b) * c[0] = 0;
+        enf (a + b) * d[0] = 0;
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_visible_in_later_sibling_constraints() {
+    // A `let` is in scope for all the statements following it in the block, including those
+    // following other constraints or other `let`s
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c[2], d[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a * b = 0;
+        let t = a + b;
+        enf t * c[0] = 0;
+        let u = t * d[0];
+        enf u = c[1];
+        enf t + u = d[1];
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c[2], d[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a * b = 0;
+        enf (a + b) * c[0] = 0;
+        enf (a + b) * d[0] = c[1];
+        enf (a + b) + (a + b) * d[0] = d[1];
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_body_with_multiple_enforces_in_evaluator() {
+    // Every constraint of the body is kept, not only the last one
+    let source = "
+    def test
+    ev foo([x, y, z[2]]) {
+        let t = x + y;
+        enf t * z[0] = 0;
+        enf t * w = 0 for w in z;
+        enf t * x = 0;
+    }
+    trace_columns {
+        main: [a, b, c[2], d[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf foo([a, b, c]);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c[2], d[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf (a + b) * c[0] = 0;
+        enf (a + b) * c[0] = 0;
+        enf (a + b) * c[1] = 0;
+        enf (a + b) * a = 0;
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn let_body_with_multiple_enforces_in_comprehension() {
+    // Each iteration of the comprehension enforces all the constraints of the inlined let body
+    let source = "
+    def test
+    ev bar([x, y]) {
+        let t = x + y;
+        enf t * x = 0;
+        enf t * y = 0;
+    }
+    trace_columns {
+        main: [a, b, c[2], d[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf bar([x, y]) for (x, y) in (c, d);
+    }";
+    let expected = "
+    def test
+    trace_columns {
+        main: [a, b, c[2], d[2]],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf (c[0] + d[0]) * c[0] = 0;
+        enf (c[0] + d[0]) * d[0] = 0;
+        enf (c[1] + d[1]) * c[1] = 0;
+        enf (c[1] + d[1]) * d[1] = 0;
+    }";
+
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithoutMIR);
+    assert_equivalent_integrity_constraints(source, expected, Pipeline::WithMIR);
+}
@@ -0,0 +1,126 @@
+use super::{Pipeline, compile, expect_diagnostic};
+use crate::{Air, IntegrityConstraintDegree};
+
+const SOURCE: &str = "
+    def test
+    trace_columns {
+        main: [a, b, @virtual(a * b * k) c, @virtual(c * c) d],
+    }
+    periodic_columns {
+        k: [1, 0, 0, 0],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = c * a;
+        enf b' = d + 1;
+        enf c = a * b * k;
+    }";
+
+#[test]
+fn virtual_column_degrees() {
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(SOURCE, pipeline).expect("compilation failed");
+
+        assert_eq!(
+            air.integrity_constraint_degrees(0),
+            [
+                IntegrityConstraintDegree::new(2),
+                IntegrityConstraintDegree::new(1),
+                IntegrityConstraintDegree::with_cycles(2, vec![4]),
+            ]
+        );
+        // `c` is of degree 2 and depends on `k`, and `d` is of degree 4, as it depends on `c`
+        assert_eq!(
+            air.integrity_constraint_degrees_with_virtual_columns(0),
+            [
+                IntegrityConstraintDegree::with_cycles(3, vec![4]),
+                IntegrityConstraintDegree::with_cycles(4, vec![4]),
+                IntegrityConstraintDegree::with_cycles(2, vec![4]),
+            ]
+        );
+    }
+}
+
+#[test]
+fn virtual_columns_are_not_enforced() {
+    let without_virtual = SOURCE.replace("@virtual(a * b * k) c, @virtual(c * c) d", "c, d");
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let air = compile(SOURCE, pipeline).expect("compilation failed");
+        let expected = compile(&without_virtual, pipeline).expect("compilation failed");
+
+        assert_eq!(air.to_ssa_text(), expected.to_ssa_text());
+        assert!(expected.virtual_columns.is_empty());
+        assert_eq!(
+            air.virtual_columns
+                .iter()
+                .map(|(column, name, _)| (column, name.to_string()))
+                .collect::<Vec<_>>(),
+            [(2, "c".to_string()), (3, "d".to_string())]
+        );
+    }
+}
+
+#[test]
+fn err_virtual_column_depends_on_later_column() {
+    let source = "
+    def test
+    trace_columns {
+        main: [a, @virtual(a * d) c, @virtual(a * a) d],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+    }
+    integrity_constraints {
+        enf a' = c + d;
+    }";
+
+    let expected = "`c` may only depend on the virtual columns declared before it";
+    expect_diagnostic(source, expected, Pipeline::WithoutMIR);
+    expect_diagnostic(source, expected, Pipeline::WithMIR);
+}
+
+#[test]
+fn product_keeps_virtual_columns() {
+    let right = "
+    def right
+    trace_columns {
+        main: [x, @virtual(x * x) y],
+    }
+    public_inputs {
+        inputs: [1],
+    }
+    boundary_constraints {
+        enf x.first = 0;
+    }
+    integrity_constraints {
+        enf x' = y * x;
+    }";
+
+    for pipeline in [Pipeline::WithoutMIR, Pipeline::WithMIR] {
+        let left = compile(SOURCE, pipeline).expect("compilation failed");
+        let right = compile(right, pipeline).expect("compilation failed");
+
+        let product = Air::product(&left, &right).expect("product failed");
+        // The columns of `right` are placed after the 4 columns of `left`
+        assert_eq!(
+            product
+                .virtual_columns
+                .iter()
+                .map(|(column, name, _)| (column, name.to_string()))
+                .collect::<Vec<_>>(),
+            [(2, "c".to_string()), (3, "d".to_string()), (5, "y".to_string())]
+        );
+        let mut expected = left.integrity_constraint_degrees_with_virtual_columns(0);
+        expected.extend(right.integrity_constraint_degrees_with_virtual_columns(0));
+        assert_eq!(product.integrity_constraint_degrees_with_virtual_columns(0), expected);
+    }
+}
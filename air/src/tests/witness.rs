@@ -0,0 +1,106 @@
+use super::{Pipeline, compile};
+use crate::{
+    AUX_SEGMENT, DEFAULT_SEGMENT, PublicInputAccess, TraceAccess, WitnessInput, WitnessRegion,
+};
+
+const SOURCE: &str = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    buses {
+        multiset p,
+    }
+    public_inputs {
+        stack_inputs: [2],
+        inputs: [1],
+    }
+    boundary_constraints {
+        enf a.first = inputs[0];
+        enf b.last = stack_inputs[1];
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        enf a' = a + 1;
+        enf c^2 = c;
+        p.insert(a, b) when c;
+        p.remove(a, b) when c;
+    }";
+
+#[test]
+fn witness_layout_enumerates_inputs() {
+    let air = compile(SOURCE, Pipeline::WithMIR).expect("compilation failed");
+    let layout = air.witness_layout();
+    let num_random_values = air.num_random_values as usize;
+    assert!(num_random_values > 0);
+
+    // The public inputs are sorted by name, and the trace is laid out row by row
+    let regions = layout.regions();
+    assert_eq!(regions.len(), 7);
+    assert!(matches!(regions[0], WitnessRegion::PublicInput { name, size: 1 } if name == "inputs"));
+    assert!(
+        matches!(regions[1], WitnessRegion::PublicInput { name, size: 2 } if name == "stack_inputs")
+    );
+    assert_eq!(regions[2], WitnessRegion::RandomValues { count: num_random_values });
+    let trace = |segment, row_offset, width| WitnessRegion::Trace { segment, row_offset, width };
+    assert_eq!(
+        &regions[3..],
+        [
+            trace(DEFAULT_SEGMENT, 0, 3),
+            trace(AUX_SEGMENT, 0, 1),
+            trace(DEFAULT_SEGMENT, 1, 3),
+            trace(AUX_SEGMENT, 1, 1)
+        ]
+    );
+    assert_eq!(layout.num_inputs(), 3 + num_random_values + 8);
+
+    // Each input is found at its position in the enumeration
+    let inputs: Vec<_> = layout.inputs().collect();
+    assert_eq!(inputs.len(), layout.num_inputs());
+    for (position, input) in inputs.iter().enumerate() {
+        assert_eq!(layout.position(input), Some(position));
+    }
+    let stack_inputs = *air.public_inputs.keys().find(|name| **name == "stack_inputs").unwrap();
+    let stack_input = WitnessInput::PublicInput(PublicInputAccess::new(stack_inputs, 1));
+    assert_eq!(layout.position(&stack_input), Some(2));
+    let c_next = WitnessInput::Trace(TraceAccess::new(DEFAULT_SEGMENT, 2, 1));
+    assert_eq!(layout.position(&c_next), Some(3 + num_random_values + 4 + 2));
+
+    // Out of bounds inputs have no position
+    let missing = WitnessInput::Trace(TraceAccess::new(DEFAULT_SEGMENT, 3, 0));
+    assert_eq!(layout.position(&missing), None);
+    let missing = WitnessInput::RandomValue(num_random_values);
+    assert_eq!(layout.position(&missing), None);
+}
+
+#[test]
+fn witness_layout_without_aux_segment() {
+    let source = "
+    def test
+    trace_columns {
+        main: [clk],
+    }
+    public_inputs {
+        stack_inputs: [16],
+    }
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+    integrity_constraints {
+        enf clk' = clk + 1;
+    }";
+    let air = compile(source, Pipeline::WithMIR).expect("compilation failed");
+    let layout = air.witness_layout();
+
+    // The aux segment is still present, but empty
+    assert_eq!(layout.num_inputs(), 16 + 2);
+    assert!(layout.regions().contains(&WitnessRegion::Trace {
+        segment: AUX_SEGMENT,
+        row_offset: 1,
+        width: 0
+    }));
+    let inputs: Vec<_> = layout.inputs().collect();
+    assert_eq!(inputs[16], WitnessInput::Trace(TraceAccess::new(DEFAULT_SEGMENT, 0, 0)));
+    assert_eq!(inputs[17], WitnessInput::Trace(TraceAccess::new(DEFAULT_SEGMENT, 0, 1)));
+}
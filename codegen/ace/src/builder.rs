@@ -1,9 +1,11 @@
 use std::collections::BTreeMap;
 
 use air_ir::{
-    Air, NodeIndex, Operation as AirOperation, PeriodicColumnAccess, QualifiedIdentifier, Value,
+    Air, BusOpKind, BusType, ConstraintRoot, ExpandedBus, NodeIndex, Operation as AirOperation,
+    PeriodicColumnAccess, QualifiedIdentifier, Value,
 };
 use miden_core::Felt;
+use winter_math::{FieldElement, StarkField};
 
 use crate::{
     circuit::{ArithmeticOp, Circuit, Node, OperationNode},
@@ -30,14 +32,25 @@ pub struct CircuitBuilder {
     ops_cache: BTreeMap<OperationNode, Node>,
     // A cache of nodes already inserted in the circuit, used to avoid duplicates.
     air_node_cache: BTreeMap<AirOperation, Node>,
-    // Cache mapping a periodic column identifier to the evaluation of a column at `z`.
-    periodic_columns_cache: BTreeMap<QualifiedIdentifier, Node>,
+    // Cache mapping a periodic column identifier and row offset to the evaluation of a column at
+    // `z·gᵒ`.
+    periodic_columns_cache: BTreeMap<(QualifiedIdentifier, usize), Node>,
+    // When set, operations are pushed as given, without caching, constant folding or algebraic
+    // simplifications. Only enabled by tests comparing both kinds of circuits.
+    disable_simplifications: bool,
+    // When set, the integrity constraints of expanded buses are built from their operations,
+    // rather than from their monolithic expansion.
+    split_buses: bool,
 }
 
 impl CircuitBuilder {
     /// Initializes a [`CircuitBuilder`] for a given [`Air`].
     pub fn new(air: &Air) -> Self {
-        let layout = Layout::new(air);
+        Self::with_layout(Layout::new(air))
+    }
+
+    /// Initializes a [`CircuitBuilder`] whose inputs are described by `layout`.
+    pub fn with_layout(layout: Layout) -> Self {
         Self {
             layout,
             constants: vec![],
@@ -46,9 +59,26 @@ impl CircuitBuilder {
             ops_cache: BTreeMap::default(),
             air_node_cache: BTreeMap::default(),
             periodic_columns_cache: BTreeMap::default(),
+            disable_simplifications: false,
+            split_buses: false,
         }
     }
 
+    /// Builds the integrity constraints of the expanded buses of the [`Air`] from their
+    /// operations, whose terms are combined in balanced trees, see [`Self::expanded_bus`].
+    pub fn with_split_buses(mut self) -> Self {
+        self.split_buses = true;
+        self
+    }
+
+    /// Disables all simplifications performed when building operations, such that each call to
+    /// [`Self::add`], [`Self::mul`] or [`Self::sub`] pushes a new operation node.
+    #[cfg(test)]
+    pub(crate) fn without_simplifications(mut self) -> Self {
+        self.disable_simplifications = true;
+        self
+    }
+
     /// Returns the built [`Circuit`].
     pub fn into_ace_circuit(self) -> Circuit {
         Circuit {
@@ -86,11 +116,7 @@ impl CircuitBuilder {
                 self.constant(c.as_int())
             },
             // Store new `Operation` node
-            _ => {
-                let index = self.operations.len();
-                self.operations.push(operation);
-                Node::Operation(index)
-            },
+            _ => self.push_operation(operation),
         };
 
         // Cache the operation node for future use.
@@ -98,6 +124,13 @@ impl CircuitBuilder {
         node
     }
 
+    /// Appends `operation` to the circuit and returns its [`Node`], without any simplification.
+    fn push_operation(&mut self, operation: OperationNode) -> Node {
+        let index = self.operations.len();
+        self.operations.push(operation);
+        Node::Operation(index)
+    }
+
     /// Returns a [`Node`] corresponding to a circuit constant. The mapping is cached to avoid
     /// duplicating constants.
     pub fn constant(&mut self, c: u64) -> Node {
@@ -162,8 +195,112 @@ impl CircuitBuilder {
         node
     }
 
+    /// Returns the [`Node`] of the constraint rooted at `root`, which is built from the operations
+    /// of the corresponding expanded bus when buses are split.
+    pub fn root_node(&mut self, air: &Air, root: &ConstraintRoot) -> Node {
+        match air.expanded_bus(root).filter(|_| self.split_buses) {
+            Some(bus) => self.expanded_bus(air, bus),
+            None => self.node_from_index(air, root.node_index()),
+        }
+    }
+
+    /// Returns a [`Node`] evaluating to the same value as the monolithic integrity constraint of
+    /// `bus`, whose depth is logarithmic rather than linear in the number of operations.
+    ///
+    /// For a multiset bus `p`, the constraint is `p⋅∏ᵢ fᵢ - p'⋅∏ⱼ fⱼ`, where `fᵢ` are the factors
+    /// of the inserted tuples and `fⱼ` those of the removed ones. For a logup bus `q`, the sum
+    /// `N/D = ∑ᵢ ±latchᵢ/argsᵢ` is built by adding fractions pairwise, and the constraint is
+    /// `D⋅(q - q') + N`.
+    fn expanded_bus(&mut self, air: &Air, bus: &ExpandedBus) -> Node {
+        let column = self.node_from_index(air, &bus.column);
+        let column_next = self.node_from_index(air, &bus.column_next);
+        match bus.bus_type {
+            BusType::Multiset => {
+                let one = self.constant(1);
+                let mut inserted = vec![];
+                let mut removed = vec![];
+                for op in bus.ops.iter() {
+                    let args = self.node_from_index(air, &op.args_combined);
+                    let latch = self.node_from_index(air, &op.latch);
+                    // args⋅latch + 1 - latch
+                    let args_with_latch = self.mul(args, latch);
+                    let latch_inverse = self.sub(one, latch);
+                    let factor = self.add(args_with_latch, latch_inverse);
+                    match op.kind {
+                        BusOpKind::Insert => inserted.push(factor),
+                        BusOpKind::Remove => removed.push(factor),
+                    }
+                }
+                let inserted = self.balanced_prod(&inserted);
+                let removed = self.balanced_prod(&removed);
+                let lhs = self.mul(inserted, column);
+                let rhs = self.mul(removed, column_next);
+                self.sub(lhs, rhs)
+            },
+            BusType::Logup => {
+                let zero = self.constant(0);
+                let fractions: Vec<_> = bus
+                    .ops
+                    .iter()
+                    .map(|op| {
+                        let args = self.node_from_index(air, &op.args_combined);
+                        let latch = self.node_from_index(air, &op.latch);
+                        let numerator = match op.kind {
+                            BusOpKind::Insert => latch,
+                            BusOpKind::Remove => self.sub(zero, latch),
+                        };
+                        (numerator, args)
+                    })
+                    .collect();
+                let (numerator, denominator) = self.balanced_fraction_sum(&fractions);
+                let diff = self.sub(column, column_next);
+                let scaled = self.mul(denominator, diff);
+                self.add(scaled, numerator)
+            },
+        }
+    }
+
+    /// Returns the product of `els`, multiplied pairwise in a balanced tree.
+    pub fn balanced_prod(&mut self, els: &[Node]) -> Node {
+        match els {
+            [] => self.constant(1),
+            [el] => *el,
+            _ => {
+                let (l, r) = els.split_at(els.len() / 2);
+                let (node_l, node_r) = (self.balanced_prod(l), self.balanced_prod(r));
+                self.mul(node_l, node_r)
+            },
+        }
+    }
+
+    /// Returns the numerator and denominator of the sum of the fractions `(n, d)`, added pairwise
+    /// in a balanced tree with `n₁/d₁ + n₂/d₂ = (n₁⋅d₂ + n₂⋅d₁)/(d₁⋅d₂)`.
+    ///
+    /// The denominator is the product of all denominators, and the numerator is the sum of each
+    /// numerator multiplied by all other denominators.
+    fn balanced_fraction_sum(&mut self, fractions: &[(Node, Node)]) -> (Node, Node) {
+        match fractions {
+            [] => (self.constant(0), self.constant(1)),
+            [fraction] => *fraction,
+            _ => {
+                let (l, r) = fractions.split_at(fractions.len() / 2);
+                let (n_l, d_l) = self.balanced_fraction_sum(l);
+                let (n_r, d_r) = self.balanced_fraction_sum(r);
+                let n_l_d_r = self.mul(n_l, d_r);
+                let n_r_d_l = self.mul(n_r, d_l);
+                let numerator = self.add(n_l_d_r, n_r_d_l);
+                let denominator = self.mul(d_l, d_r);
+                (numerator, denominator)
+            },
+        }
+    }
+
     /// Returns the [`Node`] resulting from the addition of two existing [`Node`]s.
     pub fn add(&mut self, mut node_l: Node, mut node_r: Node) -> Node {
+        if self.disable_simplifications {
+            return self.push_operation(OperationNode { op: ArithmeticOp::Add, node_l, node_r });
+        }
+
         // Since addition is commutative, sorting ensures the operation is not duplicated.
         if node_r < node_l {
             (node_l, node_r) = (node_r, node_l);
@@ -183,6 +320,10 @@ impl CircuitBuilder {
 
     /// Returns the [`Node`] resulting from the multiplication of two existing [`Node`]s.
     pub fn mul(&mut self, mut node_l: Node, mut node_r: Node) -> Node {
+        if self.disable_simplifications {
+            return self.push_operation(OperationNode { op: ArithmeticOp::Mul, node_l, node_r });
+        }
+
         // Since multiplication is commutative, sorting ensures the operation is not duplicated
         if node_r < node_l {
             (node_l, node_r) = (node_r, node_l);
@@ -208,6 +349,10 @@ impl CircuitBuilder {
 
     /// Returns the [`Node`] resulting from the subtraction of two existing [`Node`]s.
     pub fn sub(&mut self, node_l: Node, node_r: Node) -> Node {
+        if self.disable_simplifications {
+            return self.push_operation(OperationNode { op: ArithmeticOp::Sub, node_l, node_r });
+        }
+
         // Subtracting by zero is the identity.
         let zero = self.constant(0);
         if node_r == zero {
@@ -254,16 +399,18 @@ impl CircuitBuilder {
     }
 
     /// Returns a [`Node`] corresponding to the evaluation of the `periodic_column` at the
-    /// appropriate power of `z`. The evaluation is cached to avoid unnecessary computation.
+    /// appropriate power of `z`, or of `z·gᵒ` when the column is read `o` rows ahead. The
+    /// evaluation is cached to avoid unnecessary computation.
     fn periodic_column(
         &mut self,
         air: &Air,
         periodic_column: &PeriodicColumnAccess,
     ) -> Option<Node> {
         let ident = periodic_column.name;
+        let row_offset = periodic_column.row_offset;
 
         // Check if we have already computed this column's value
-        if let Some(node) = self.periodic_columns_cache.get(&ident) {
+        if let Some(node) = self.periodic_columns_cache.get(&(ident, row_offset)) {
             return Some(*node);
         }
 
@@ -285,22 +432,30 @@ impl CircuitBuilder {
         // For different columns, squares of `z_max_col` are cached, avoiding duplicate operations.
         let log_pow_col = (max_col_len / col_len).ilog2();
         let z_col = (0..log_pow_col).fold(z_max_col, |acc, _| self.mul(acc, acc));
+        // Reading the column `o` rows ahead evaluates it at `(z·gᵒ)ˡ = zˡ⋅ωᵒ`, where
+        // `ω = gˡ` is the primitive `col_len`-th root of unity, so the evaluation point is only
+        // shifted by a constant.
+        let z_col = if row_offset > 0 {
+            let omega = Felt::get_root_of_unity(col_len.ilog2());
+            let shift = self.constant(omega.exp(row_offset as u64).as_int());
+            self.mul(z_col, shift)
+        } else {
+            z_col
+        };
 
         // Interpolate the values of the column, converting the resulting coefficients
         // to constant nodes
-        let poly_nodes: Vec<_> = {
-            let mut column: Vec<_> =
-                periodic_column.values.iter().map(|val| Felt::new(*val)).collect();
-            let inv_twiddles = winter_math::fft::get_inv_twiddles::<Felt>(column.len());
-            winter_math::fft::interpolate_poly(&mut column, &inv_twiddles);
-            column.into_iter().map(|coeff| self.constant(coeff.as_int())).collect()
-        };
+        let poly_nodes: Vec<_> = air
+            .periodic_column_polynomial(&ident)?
+            .into_iter()
+            .map(|coeff| self.constant(coeff))
+            .collect();
 
         // Evaluate the polynomial at z_col
         let result = self.poly_eval(z_col, &poly_nodes);
 
         // Cache evaluation
-        self.periodic_columns_cache.insert(ident, result);
+        self.periodic_columns_cache.insert((ident, row_offset), result);
         Some(result)
     }
 }
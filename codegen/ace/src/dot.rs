@@ -49,9 +49,12 @@ impl Circuit {
         }
 
         // Quotient
-        for (idx, node) in self.layout.trace_segments[0][1].iter_nodes().enumerate() {
+        for (idx, node) in self.layout.trace_segments[0][2].iter_nodes().enumerate() {
             writeln!(f, "{node} [label=\"Q[{idx}]\"]")?;
         }
+        for (idx, node) in self.layout.trace_segments[1][2].iter_nodes().enumerate() {
+            writeln!(f, "{node} [label=\"Q'[{idx}]\"]")?;
+        }
 
         // Air vars
         for var_idx in 0..StarkVar::num_vars() {
@@ -1,3 +1,4 @@
+use air_ir::Provenance;
 use miden_core::{
     Felt,
     crypto::hash::{Rpo256, RpoDigest},
@@ -7,8 +8,43 @@ use winter_math::FieldElement;
 use crate::{
     QuadFelt,
     circuit::{ArithmeticOp, Circuit, Node, OperationNode},
+    layout::Layout,
 };
 
+/// The layout of the *Constants* region of an [`EncodedCircuit`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ConstantLayout {
+    /// Each constant `c` is stored as the extension field element `(c, 0)`, and the region is
+    /// padded with zeros to be word-aligned. This is the layout read by the ACE chiplet.
+    #[default]
+    ExtensionPadded,
+    /// Constants are stored as consecutive base field elements, and the region is padded with
+    /// zeros to be word-aligned.
+    Packed,
+}
+
+/// The order of the field elements within each word of the *Constants* region of an
+/// [`EncodedCircuit`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// The elements of a word are stored in order, e.g. `[c0, 0, c1, 0]` for the constants `c0`
+    /// and `c1` in the [`ConstantLayout::ExtensionPadded`] layout.
+    #[default]
+    Little,
+    /// The elements of a word are stored in reverse order, e.g. `[0, c1, 0, c0]` for the constants
+    /// `c0` and `c1` in the [`ConstantLayout::ExtensionPadded`] layout.
+    Big,
+}
+
+/// Options controlling how a [`Circuit`] is encoded by [`Circuit::to_ace_with_options`].
+///
+/// The default options produce the encoding expected by the ACE chiplet.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EncodingOptions {
+    pub constant_layout: ConstantLayout,
+    pub endianness: Endianness,
+}
+
 /// An encoded [`Circuit`] matching the required format for the ACE chiplet.
 /// The chiplet performs an evaluation by sequentially reading a region in memory with the following
 /// layout, where each region must be word-aligned.
@@ -17,14 +53,18 @@ use crate::{
 ///   zeros.
 ///   - *Inputs*: List of all inputs to the circuit. The internal layout is described in
 ///     [`crate::AceVars::to_memory_vec`].
-///   - *Constants*: Fixed values that can be referenced by instructions.
+///   - *Constants*: Fixed values that can be referenced by instructions, laid out according to
+///     the [`EncodingOptions`] of the circuit.
 /// - *Instructions*: List of arithmetic gates to be evaluated. Each instruction is encoded as a
 ///   single field element. It is padded with instructions which square the output, as this still
 ///   ensures the final evaluation is still evaluates to zero.
 pub struct EncodedCircuit {
     num_vars: usize,
+    num_constants: usize,
     num_ops: usize,
     instructions: Vec<Felt>,
+    options: EncodingOptions,
+    provenance: Option<Provenance>,
 }
 
 impl EncodedCircuit {
@@ -55,7 +95,7 @@ impl EncodedCircuit {
 
     /// Number of constant nodes.
     pub fn num_constants(&self) -> usize {
-        self.num_nodes() - self.num_ops
+        self.num_constants
     }
 
     /// Number of nodes (variables and operations).
@@ -72,11 +112,160 @@ impl EncodedCircuit {
     pub fn circuit_hash(&self) -> RpoDigest {
         Rpo256::hash_elements(self.instructions())
     }
+
+    /// The options the circuit was encoded with.
+    pub fn options(&self) -> EncodingOptions {
+        self.options
+    }
+
+    /// Decodes the values of the constant nodes from the *Constants* region, according to the
+    /// [`EncodingOptions`] of the circuit.
+    pub fn constants(&self) -> Vec<Felt> {
+        let len = self.options.constant_layout.region_len(self.num_constants);
+        let mut region = self.instructions[..len].to_vec();
+        self.options.endianness.reorder_words(&mut region);
+        match self.options.constant_layout {
+            ConstantLayout::ExtensionPadded => {
+                region.into_iter().step_by(2).take(self.num_constants).collect()
+            },
+            ConstantLayout::Packed => {
+                region.truncate(self.num_constants);
+                region
+            },
+        }
+    }
+
+    /// Evaluates the encoded circuit on the given `inputs`, returning the value of its last node,
+    /// i.e. the root of the circuit squared by the padding instructions.
+    ///
+    /// This reads the circuit the way the ACE chiplet does, and is used to check that an
+    /// encoding is faithful to the [`Circuit`] it was produced from.
+    pub fn eval(&self, inputs: &[QuadFelt]) -> QuadFelt {
+        const ID_MASK: u64 = (1 << 30) - 1;
+
+        assert_eq!(inputs.len(), self.num_inputs(), "invalid number of inputs");
+
+        // Nodes are identified in reverse order of their evaluation, starting from the inputs
+        let num_nodes = self.num_nodes();
+        let mut evals = Vec::with_capacity(num_nodes);
+        evals.extend_from_slice(inputs);
+        evals.extend(self.constants().into_iter().map(QuadFelt::from));
+
+        let len = self.options.constant_layout.region_len(self.num_constants);
+        for instruction in &self.instructions[len..] {
+            let instruction = instruction.as_int();
+            let eval = |id: u64| evals[num_nodes - 1 - id as usize];
+            let eval_l = eval(instruction & ID_MASK);
+            let eval_r = eval((instruction >> 30) & ID_MASK);
+            let eval = match instruction >> 60 {
+                0 => eval_l - eval_r,
+                1 => eval_l * eval_r,
+                2 => eval_l + eval_r,
+                op => panic!("invalid opcode {op}"),
+            };
+            evals.push(eval);
+        }
+
+        *evals.last().expect("the circuit has no operations")
+    }
+
+    /// Decodes the encoded circuit back into a [`Circuit`] whose inputs are described by `layout`.
+    ///
+    /// The decoded circuit contains the padding instructions squaring the root, such that its last
+    /// operation evaluates to the same value as [`Self::eval`].
+    ///
+    /// # Panics
+    /// Panics if `layout` does not have the same number of inputs as the encoded circuit, or if an
+    /// instruction is malformed.
+    pub fn decode(&self, layout: Layout) -> Circuit {
+        const ID_MASK: u64 = (1 << 30) - 1;
+
+        assert_eq!(layout.num_inputs, self.num_inputs(), "invalid number of inputs");
+
+        // Node ids are assigned in reverse order, from the first input down to the last operation.
+        let num_nodes = self.num_nodes();
+        let num_inputs = self.num_inputs();
+        let node = |id: u64| {
+            let index = num_nodes.checked_sub(id as usize + 1).expect("invalid node id");
+            if index < num_inputs {
+                Node::Input(index)
+            } else if index < self.num_vars {
+                Node::Constant(index - num_inputs)
+            } else {
+                Node::Operation(index - self.num_vars)
+            }
+        };
+
+        let len = self.options.constant_layout.region_len(self.num_constants);
+        let operations = self.instructions[len..]
+            .iter()
+            .map(|instruction| {
+                let instruction = instruction.as_int();
+                let op = match instruction >> 60 {
+                    0 => ArithmeticOp::Sub,
+                    1 => ArithmeticOp::Mul,
+                    2 => ArithmeticOp::Add,
+                    op => panic!("invalid opcode {op}"),
+                };
+                OperationNode {
+                    op,
+                    node_l: node(instruction & ID_MASK),
+                    node_r: node((instruction >> 30) & ID_MASK),
+                }
+            })
+            .collect();
+
+        Circuit {
+            layout,
+            constants: self.constants(),
+            operations,
+        }
+    }
+
+    /// Attaches the [Provenance] of the AirScript this circuit was compiled from. It does not
+    /// affect the encoding, nor the [`Self::circuit_hash`].
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// The [Provenance] of the AirScript this circuit was compiled from, if attached.
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+}
+
+impl ConstantLayout {
+    /// Length of the word-aligned region storing `num_constants` constants in this layout.
+    fn region_len(self, num_constants: usize) -> usize {
+        match self {
+            Self::ExtensionPadded => num_constants.next_multiple_of(2) * 2,
+            Self::Packed => num_constants.next_multiple_of(4),
+        }
+    }
+}
+
+impl Endianness {
+    /// Converts a word-aligned region between little-endian and this endianness, which is its
+    /// own inverse.
+    fn reorder_words(self, region: &mut [Felt]) {
+        if self == Self::Big {
+            region.chunks_exact_mut(4).for_each(<[Felt]>::reverse);
+        }
+    }
 }
 
 impl Circuit {
     /// Serializes a [`Circuit`] to a list of field elements in the format expected by the
-    /// ACE chiplet.
+    /// ACE chiplet, i.e. with the default [`EncodingOptions`].
+    ///
+    /// See [`Self::to_ace_with_options`] for a description of the format.
+    pub fn to_ace(&self) -> EncodedCircuit {
+        self.to_ace_with_options(EncodingOptions::default())
+    }
+
+    /// Serializes a [`Circuit`] to a list of field elements, laying out the constants according
+    /// to `options`.
     ///
     /// In the case of a gate, the 63 bits available in a field element are used in
     /// the following order, from least significant:
@@ -99,24 +288,26 @@ impl Circuit {
     /// ```
     ///
     /// The encoded circuit is padded according to [`Self::is_padded`].
-    pub fn to_ace(&self) -> EncodedCircuit {
+    pub fn to_ace_with_options(&self, options: EncodingOptions) -> EncodedCircuit {
         const MAX_NODE_ID: u64 = (1 << 30) - 1;
 
         assert!(self.num_nodes() as u64 <= MAX_NODE_ID, "more than 2^30 nodes");
 
-        // Constants are encoded two-by-two as extension field elements, followed by operations.
-        let num_const = self.constants.len().next_multiple_of(2);
+        // Constants are encoded according to the layout, followed by operations.
         let num_ops = self.operations.len().next_multiple_of(4);
-        let len_const = num_const * 2;
+        let len_const = options.constant_layout.region_len(self.constants.len());
         let len_circuit = len_const + num_ops;
         let mut instructions = Vec::with_capacity(len_circuit);
 
         // Add constants
-        instructions
-            .extend(self.constants.iter().flat_map(|c| QuadFelt::from(*c).to_base_elements()));
-        // Since constants are treated as extension field elements, we pad this section with zeros
-        // to ensure it is aligned in memory.
+        match options.constant_layout {
+            ConstantLayout::ExtensionPadded => instructions
+                .extend(self.constants.iter().flat_map(|c| QuadFelt::from(*c).to_base_elements())),
+            ConstantLayout::Packed => instructions.extend_from_slice(&self.constants),
+        }
+        // We pad this section with zeros to ensure it is aligned in memory.
         instructions.resize(len_const, Felt::ZERO);
+        options.endianness.reorder_words(&mut instructions);
 
         let num_inputs = self.layout.num_inputs;
         let num_constants = self.constants.len();
@@ -168,7 +359,14 @@ impl Circuit {
         }
 
         let num_vars = num_inputs + num_constants;
-        EncodedCircuit { num_vars, num_ops, instructions }
+        EncodedCircuit {
+            num_vars,
+            num_constants,
+            num_ops,
+            instructions,
+            options,
+            provenance: None,
+        }
     }
 
     /// Returns `true` when the circuit is properly padded, and each region is word-aligned. It
@@ -176,9 +374,9 @@ impl Circuit {
     /// - Inputs and constants lie two-by-two in memory, treated as extension field elements,
     /// - Operations are encoded as single field elements.
     pub fn is_padded(&self) -> bool {
-        (self.layout.num_inputs % 2 == 0)
-            && (self.constants.len() % 2 == 0)
-            && (self.operations.len() % 4 == 0)
+        self.layout.num_inputs.is_multiple_of(2)
+            && self.constants.len().is_multiple_of(2)
+            && self.operations.len().is_multiple_of(4)
     }
 }
 
@@ -223,6 +421,7 @@ mod tests {
                 ],
             ],
             stark_vars: Default::default(),
+            stark_var_layout: Default::default(),
             num_inputs: 2,
         };
 
@@ -288,4 +487,54 @@ mod tests {
             assert_eq!(op, expected, "op {i} is different");
         }
     }
+
+    /// Checks the constants region of the circuit `[(i0 + 1) * i0 - 2]^2` in each layout, and
+    /// that the instructions do not depend on it.
+    #[test]
+    fn test_constant_layouts() {
+        let layout = Layout { num_inputs: 2, ..Layout::default() };
+        let circuit = Circuit {
+            layout,
+            constants: vec![Felt::new(1), Felt::new(2)],
+            operations: vec![
+                OperationNode {
+                    op: ArithmeticOp::Add,
+                    node_l: Node::Input(0),
+                    node_r: Node::Constant(0),
+                },
+                OperationNode {
+                    op: ArithmeticOp::Mul,
+                    node_l: Node::Operation(0),
+                    node_r: Node::Input(0),
+                },
+                OperationNode {
+                    op: ArithmeticOp::Sub,
+                    node_l: Node::Operation(1),
+                    node_r: Node::Constant(1),
+                },
+            ],
+        };
+
+        let cases = [
+            (ConstantLayout::ExtensionPadded, Endianness::Little, [1, 0, 2, 0]),
+            (ConstantLayout::ExtensionPadded, Endianness::Big, [0, 2, 0, 1]),
+            (ConstantLayout::Packed, Endianness::Little, [1, 2, 0, 0]),
+            (ConstantLayout::Packed, Endianness::Big, [0, 0, 2, 1]),
+        ];
+        let default = circuit.to_ace();
+        for (constant_layout, endianness, expected) in cases {
+            let options = EncodingOptions { constant_layout, endianness };
+            let encoded = circuit.to_ace_with_options(options);
+            let (constants, operations) = encoded.instructions().split_at(4);
+            assert_eq!(constants, expected.map(Felt::new).as_slice(), "{options:?}");
+            assert_eq!(operations, &default.instructions()[4..], "{options:?}");
+            assert_eq!(encoded.constants(), circuit.constants, "{options:?}");
+            assert_eq!(encoded.num_inputs(), 2);
+            assert_eq!(encoded.num_constants(), 2);
+
+            let inputs = [QuadFelt::from(Felt::new(3)), QuadFelt::ZERO];
+            // [(3 + 1) * 3 - 2]^2 = 100
+            assert_eq!(encoded.eval(&inputs), QuadFelt::from(Felt::new(100)), "{options:?}");
+        }
+    }
 }
@@ -0,0 +1,89 @@
+use air_ir::{Air, ConstraintEvaluator, EvalContext, RootKind};
+use winter_math::FieldElement;
+
+use crate::{
+    QuadFelt, build_ace_circuit_with_roots,
+    circuit::{Circuit, Node},
+    inputs::{AceVars, StarkInputs},
+};
+
+/// The [`Node`]s of an ACE [`Circuit`] corresponding to each constraint root of the [`Air`] it was
+/// built from, grouped in the same way as [`RootKind`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AceRoots {
+    /// Integrity roots, indexed by trace segment.
+    pub integrity: [Vec<Node>; 2],
+    /// Boundary roots applied to the first row, across all segments.
+    pub boundary_first: Vec<Node>,
+    /// Boundary roots applied to the last row, across all segments.
+    pub boundary_last: Vec<Node>,
+}
+
+impl AceRoots {
+    /// Returns the nodes of all roots of the given kind.
+    pub fn get(&self, kind: RootKind) -> &[Node] {
+        match kind {
+            RootKind::Integrity(segment) => {
+                self.integrity.get(segment).map(Vec::as_slice).unwrap_or_default()
+            },
+            RootKind::BoundaryFirst => &self.boundary_first,
+            RootKind::BoundaryLast => &self.boundary_last,
+        }
+    }
+}
+
+/// A [`ConstraintEvaluator`] backed by the ACE [`Circuit`] of an [`Air`].
+///
+/// Each root is evaluated by running the circuit over the inputs derived from the [`EvalContext`],
+/// using the memory layout expected by the ACE chiplet. The quotient and `α` inputs do not affect
+/// individual roots, and are set to zero.
+pub struct AceEvaluator<'a> {
+    air: &'a Air,
+    circuit: Circuit,
+    roots: AceRoots,
+}
+
+impl<'a> AceEvaluator<'a> {
+    /// Builds the ACE circuit for `air`, keeping track of the node of each constraint root.
+    pub fn new(air: &'a Air) -> anyhow::Result<Self> {
+        let (_root, circuit, roots) = build_ace_circuit_with_roots(air)?;
+        Ok(Self { air, circuit, roots })
+    }
+
+    /// Returns the underlying circuit.
+    pub fn circuit(&self) -> &Circuit {
+        &self.circuit
+    }
+
+    /// Returns the nodes of each constraint root in the underlying circuit.
+    pub fn roots(&self) -> &AceRoots {
+        &self.roots
+    }
+
+    /// Returns the ACE variables corresponding to the values in `ctx`.
+    fn ace_vars(&self, ctx: &EvalContext) -> AceVars {
+        let stark = StarkInputs::new(self.air, ctx.log_trace_len, QuadFelt::ZERO, ctx.z);
+        let num_quotient_parts = self.circuit.layout.quotient_nodes().len();
+        let quotient = vec![QuadFelt::ZERO; num_quotient_parts];
+        let segments = [0, 1].map(|row_offset| {
+            [ctx.main[row_offset].clone(), ctx.aux[row_offset].clone(), quotient.clone()]
+        });
+        AceVars {
+            public: ctx.public.clone(),
+            segments,
+            rand: ctx.rand.clone(),
+            stark,
+        }
+    }
+}
+
+impl ConstraintEvaluator for AceEvaluator<'_> {
+    fn num_roots(&self, kind: RootKind) -> usize {
+        self.roots.get(kind).len()
+    }
+
+    fn evaluate_root(&self, kind: RootKind, i: usize, ctx: &EvalContext) -> QuadFelt {
+        let inputs = self.ace_vars(ctx).to_memory_vec(&self.circuit.layout);
+        self.circuit.eval(self.roots.get(kind)[i], &inputs)
+    }
+}
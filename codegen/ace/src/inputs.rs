@@ -6,7 +6,7 @@ use winter_math::{FieldElement, StarkField};
 
 use crate::{
     QuadFelt,
-    layout::{InputRegion, Layout},
+    layout::{HASH_ALIGNMENT, InputRegion, Layout, StarkVar},
 };
 
 /// Set of all inputs required to perform the DEEP-ALI constraint evaluations check.
@@ -87,7 +87,8 @@ impl StarkInputs {
     /// The `g` is computed as the generator of the group of roots of unity of size `n`.
     ///
     /// The [`Air`] is required to compute `zᵐᵃˣ`, the power of `z` at which we evaluate the longest
-    /// periodic column, and from which we derive the evaluation points of all other columns.
+    /// periodic column, and from which we derive the evaluation points of all other columns. See
+    /// [`Air::z_exponent_for_periodic`], whose requirements on the cycle lengths must hold.
     pub(crate) fn new(air: &Air, log_trace_len: u32, alpha: QuadFelt, z: QuadFelt) -> Self {
         let generator = Felt::get_root_of_unity(log_trace_len);
         let gen_next = generator.square();
@@ -98,8 +99,7 @@ impl StarkInputs {
         let n = 1 << log_trace_len;
         let z_pow_n = z.exp_vartime(n);
 
-        let max_cycle_len = air.periodic_columns.values().map(|col| col.values.len() as u64).max();
-        let z_max_cycle_pow = max_cycle_len.map(|cycle_len| n / cycle_len).unwrap_or(0);
+        let z_max_cycle_pow = air.z_exponent_for_periodic(n).unwrap_or_else(|err| panic!("{err}"));
         let z_max_cycle = z.exp_vartime(z_max_cycle_pow);
 
         Self {
@@ -123,39 +123,155 @@ impl StarkInputs {
             self.z_max_cycle,
         ]
     }
+
+    /// Returns the [`StarkInputs`] given by `vars`, in the same order as [`crate::StarkVar`].
+    pub(crate) fn from_vars(vars: [QuadFelt; StarkVar::num_vars()]) -> Self {
+        let [gen_penultimate, gen_last, alpha, z, z_pow_n, z_max_cycle] = vars;
+        Self {
+            gen_penultimate,
+            gen_last,
+            alpha,
+            z,
+            z_pow_n,
+            z_max_cycle,
+        }
+    }
 }
 
 impl AceVars {
+    /// Returns the set of all variables required for the evaluation of the ACE circuit with the
+    /// given `layout`, from the values of a trace frame and the verifier's challenges:
+    /// - `public_inputs`, in the same order as [`Air::public_inputs`],
+    /// - `rand`, the challenges used to derive the *aux* trace,
+    /// - the *main* and *aux* trace evaluations in the current and next rows,
+    /// - `quotients`, the evaluations of the quotient parts in the current and next rows,
+    /// - `stark_vars`, in the same order as [`StarkVar`], regardless of the order in which the
+    ///   layout stores them.
+    ///
+    /// Returns an error if the number of values in any of them does not match the `layout`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_frame(
+        layout: &Layout,
+        public_inputs: Vec<Vec<QuadFelt>>,
+        rand: Vec<QuadFelt>,
+        main_curr: Vec<QuadFelt>,
+        aux_curr: Vec<QuadFelt>,
+        main_next: Vec<QuadFelt>,
+        aux_next: Vec<QuadFelt>,
+        quotients: [Vec<QuadFelt>; 2],
+        stark_vars: [QuadFelt; StarkVar::num_vars()],
+    ) -> anyhow::Result<Self> {
+        let check = |name: &str, region: &InputRegion, vars: &[QuadFelt]| {
+            anyhow::ensure!(
+                region.width == vars.len(),
+                "expected {} values for {name}, but got {}",
+                region.width,
+                vars.len()
+            );
+            Ok(())
+        };
+
+        anyhow::ensure!(
+            layout.public_inputs.len() == public_inputs.len(),
+            "expected {} public inputs, but got {}",
+            layout.public_inputs.len(),
+            public_inputs.len()
+        );
+        for ((name, region), inputs) in zip(&layout.public_inputs, &public_inputs) {
+            check(&format!("public input `{name}`"), region, inputs)?;
+        }
+        check("the random values", &layout.random_values, &rand)?;
+
+        let [quotient_curr, quotient_next] = quotients;
+        let segments = [[main_curr, aux_curr, quotient_curr], [main_next, aux_next, quotient_next]];
+        for (row, (segment_row, regions)) in
+            ["current", "next"].into_iter().zip(zip(&segments, &layout.trace_segments))
+        {
+            for (segment, (values, region)) in ["main trace", "aux trace", "quotient"]
+                .into_iter()
+                .zip(zip(segment_row, regions))
+            {
+                check(&format!("the {segment} in the {row} row"), region, values)?;
+            }
+        }
+
+        Ok(Self {
+            public: public_inputs,
+            segments,
+            rand,
+            stark: StarkInputs::from_vars(stark_vars),
+        })
+    }
+
     /// Generates a vector containing all inputs, respecting the required memory alignment for the
     /// recursive verifier.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values in any set of variables does not match its region in the
+    /// `layout`, or if the regions of the `layout` do not span exactly [`Layout::num_inputs`]
+    /// inputs. Either would shift the inputs which follow, and make the circuit evaluate to an
+    /// unrelated value.
     pub fn to_memory_vec(&self, layout: &Layout) -> Vec<QuadFelt> {
-        let mut mem = vec![QuadFelt::ZERO; layout.num_inputs];
+        let mut mem = Vec::with_capacity(layout.num_inputs);
 
-        let store = |mem: &mut Vec<QuadFelt>, region: &InputRegion, vars: &[QuadFelt]| {
-            assert_eq!(region.width, vars.len());
-            mem[region.range()].copy_from_slice(vars);
-        };
+        // Each region starts after the padding of the previous one
+        let store =
+            |mem: &mut Vec<QuadFelt>, name: &str, region: &InputRegion, vars: &[QuadFelt]| {
+                assert_eq!(
+                    region.width,
+                    vars.len(),
+                    "expected {} values for {name}, but got {}",
+                    region.width,
+                    vars.len()
+                );
+                assert!(
+                    mem.len() <= region.offset,
+                    "the region of {name} starts at input {}, which overlaps the previous region",
+                    region.offset
+                );
+                mem.resize(region.offset, QuadFelt::ZERO);
+                mem.extend_from_slice(vars);
+            };
 
         // Public values, ordered by identifiers
         assert_eq!(layout.public_inputs.len(), self.public.len());
-        for (pi_region, inputs) in zip(layout.public_inputs.values(), &self.public) {
-            store(&mut mem, pi_region, inputs)
+        for ((name, pi_region), inputs) in zip(&layout.public_inputs, &self.public) {
+            store(&mut mem, &format!("public input `{name}`"), pi_region, inputs)
         }
 
         // Random values
-        store(&mut mem, &layout.random_values, &self.rand);
+        store(&mut mem, "the random values", &layout.random_values, &self.rand);
 
         // Trace values
-        for row_offset in [0, 1] {
-            for (segment_row, region) in
-                zip(&self.segments[row_offset], &layout.trace_segments[row_offset])
+        for (row_offset, row) in ["current", "next"].into_iter().enumerate() {
+            for (segment, (segment_row, region)) in ["main trace", "aux trace", "quotient"]
+                .into_iter()
+                .zip(zip(&self.segments[row_offset], &layout.trace_segments[row_offset]))
             {
-                store(&mut mem, region, segment_row.as_slice());
+                let name = format!("the {segment} in the {row} row");
+                store(&mut mem, &name, region, segment_row.as_slice());
             }
         }
 
-        // Stark vars
-        store(&mut mem, &layout.stark_vars, &self.stark.to_vec());
+        // Stark vars, in the order given by the layout
+        let stark_vars = self.stark.to_vec();
+        let stark_vars: Vec<_> = layout
+            .stark_var_layout
+            .order()
+            .iter()
+            .map(|var| stark_vars[usize::from(*var)])
+            .collect();
+        store(&mut mem, "the STARK variables", &layout.stark_vars, &stark_vars);
+
+        mem.resize(mem.len().next_multiple_of(HASH_ALIGNMENT), QuadFelt::ZERO);
+        assert_eq!(
+            mem.len(),
+            layout.num_inputs,
+            "the inputs span {} elements, but the layout expects {}",
+            mem.len(),
+            layout.num_inputs
+        );
         mem
     }
 }
@@ -1,15 +1,17 @@
 use std::{collections::BTreeMap, ops::Range};
 
-use air_ir::{Air, Identifier, PublicInputAccess, TraceAccess};
+use air_ir::{AUX_SEGMENT, Air, Identifier, PublicInputAccess, TraceAccess, WitnessRegion};
 
 use crate::circuit::Node;
 
 /// For each set of inputs read from the transcript, we treat them as extension field elements
 /// and pad them with zeros to the next multiple of 4. They can then be unhashed to a double-word
 /// aligned region in memory.
-const HASH_ALIGNMENT: usize = 4;
+pub(crate) const HASH_ALIGNMENT: usize = 4;
 
-const NUM_QUOTIENT_PARTS: usize = 8;
+/// Number of parts the quotient is decomposed in by default, matching the degree `9` of the VM
+/// constraints.
+pub const DEFAULT_NUM_QUOTIENT_PARTS: usize = 8;
 
 /// Describes the layout of inputs given to an ACE circuit.
 /// Each set of variables is aligned to the next multiple of 4, ensuring they can be efficiently
@@ -17,8 +19,8 @@ const NUM_QUOTIENT_PARTS: usize = 8;
 ///
 /// We assume the following about the underlying `Air` from which the layout is constructed
 /// - The proof always contains a `main` and `aux` segment, even when the latter is unused,
-/// - The maximal degree of an [`Air`] is `9`, such that the quotient can be decomposed in 8 chunks.
-///   TODO(Issue: #391): Derive the degree generically.
+/// - The quotient is decomposed in [`DEFAULT_NUM_QUOTIENT_PARTS`] chunks unless specified
+///   otherwise with [`Layout::with_num_quotient_parts`], see also [`num_quotient_parts`].
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Layout {
     /// Region for each set of public inputs, sorted by `Identifier`
@@ -41,13 +43,11 @@ pub struct Layout {
     /// - The rows must be ordered as follows: ```ignore main_curr, aux_curr, quotient_curr,
     ///   main_next, aux_next, quotient_next. ```
     /// - Each trace must be padded with zero columns such that each row is word-aligned.
-    ///
-    /// # TODO(Issue #391):
-    /// The degree of the quotient is fixed to 8 matching the degree of the VM constraints, but
-    /// the actual degree can be derived from the [`Air`].
     pub trace_segments: [[InputRegion; 3]; 2],
     /// Index of the first auxiliary input describing variables
     pub stark_vars: InputRegion,
+    /// Order of the variables within the `stark_vars` region
+    pub stark_var_layout: StarkVarLayout,
     /// Total number of inputs
     pub num_inputs: usize,
 }
@@ -56,6 +56,23 @@ impl Layout {
     /// Returns a new [`Layout`] from a description of an [`Air`]. All regions are padded according
     /// to `HASH_ALIGNMENT`, ensuring that each section starts at a word-aligned memory pointer.
     pub fn new(air: &Air) -> Self {
+        Self::with_stark_var_layout(air, StarkVarLayout::default())
+    }
+
+    /// Returns a new [`Layout`] as in [`Layout::new`], where the STARK variables are stored in the
+    /// order given by `stark_var_layout`.
+    pub fn with_stark_var_layout(air: &Air, stark_var_layout: StarkVarLayout) -> Self {
+        Self::with_num_quotient_parts(air, stark_var_layout, DEFAULT_NUM_QUOTIENT_PARTS)
+    }
+
+    /// Returns a new [`Layout`] as in [`Layout::with_stark_var_layout`], where the quotient is
+    /// decomposed in `num_quotient_parts` chunks, whose evaluations are stored as the third
+    /// segment of each row.
+    pub fn with_num_quotient_parts(
+        air: &Air,
+        stark_var_layout: StarkVarLayout,
+        num_quotient_parts: usize,
+    ) -> Self {
         let mut inputs_offset = 0;
 
         fn next_region(current_offset: &mut usize, width: usize) -> InputRegion {
@@ -64,42 +81,32 @@ impl Layout {
             InputRegion { offset, width }
         }
 
-        let public_inputs: BTreeMap<_, _> = air
-            .public_inputs
-            .iter()
-            .map(|(ident, pi)| (*ident, next_region(&mut inputs_offset, pi.size())))
-            .collect();
-
-        let random_values = next_region(&mut inputs_offset, air.num_random_values as usize);
-
-        // TODO(Issue: #391): Use the following to derive the degree generically, and maybe add it
-        // to `Air`
-        // let degree = [0, 1]
-        //     .into_iter()
-        //     .flat_map(|segment| air.integrity_constraint_degrees(segment.into()))
-        //     .map(|deg| deg.base())
-        //     .max()
-        //     .unwrap();
-        //
-        // let num_quotient_elements = (degree - 1)
-        //     .next_power_of_two()
-        //     .next_multiple_of(HASH_ALIGNMENT);
-        // let quotient_degree = Self::quotient_parts(air);
-        let num_quotient_parts = NUM_QUOTIENT_PARTS;
-
-        // For better uniformity, the proof will include the evaluations of the quotient
-        // at the shifted point. Even if these are not used, they facilitate uniform evaluation
-        // of the DEEP composition polynomial.
-        let segment_widths = [
-            // The Air always contains a main segment
-            air.trace_segment_widths[0] as usize,
-            // If there is no aux segment, we set it to 0.
-            air.trace_segment_widths.get(1).copied().unwrap_or(0) as usize,
-            // Quotient is stored as a segment
-            num_quotient_parts,
-        ];
-        let trace_segments = [0, 1]
-            .map(|_row_offset| segment_widths.map(|width| next_region(&mut inputs_offset, width)));
+        // The inputs of the Air are laid out in the order of its witness layout, which always
+        // contains a main and an aux segment in each row.
+        let mut public_inputs = BTreeMap::new();
+        let mut random_values = InputRegion::default();
+        let mut trace_segments = [[InputRegion::default(); 3]; 2];
+        for region in air.witness_layout().regions() {
+            match *region {
+                WitnessRegion::PublicInput { name, size } => {
+                    public_inputs.insert(name, next_region(&mut inputs_offset, size));
+                },
+                WitnessRegion::RandomValues { count } => {
+                    random_values = next_region(&mut inputs_offset, count);
+                },
+                WitnessRegion::Trace { segment, row_offset, width } => {
+                    trace_segments[row_offset][segment] = next_region(&mut inputs_offset, width);
+                    // The quotient is stored as a third segment of each row. For better
+                    // uniformity, the proof will include the evaluations of the quotient at the
+                    // shifted point. Even if these are not used, they facilitate uniform
+                    // evaluation of the DEEP composition polynomial.
+                    if segment == AUX_SEGMENT {
+                        trace_segments[row_offset][2] =
+                            next_region(&mut inputs_offset, num_quotient_parts);
+                    }
+                },
+            }
+        }
 
         let stark_vars = next_region(&mut inputs_offset, StarkVar::num_vars());
 
@@ -108,6 +115,7 @@ impl Layout {
             trace_segments,
             random_values,
             stark_vars,
+            stark_var_layout,
             num_inputs: inputs_offset,
         }
     }
@@ -143,10 +151,36 @@ impl Layout {
 
     /// Input node associated with an auxiliary STARK challenge/variable.
     pub fn stark_node(&self, stark_var: StarkVar) -> Node {
-        self.stark_vars.as_node(stark_var.into()).unwrap()
+        self.stark_vars.as_node(self.stark_var_layout.position(stark_var)).unwrap()
     }
 }
 
+/// Returns the number of parts in which the quotient of `air` must be decomposed, for a proof
+/// whose low-degree extension uses the given `blowup_factor`.
+///
+/// Dividing the composition of the integrity constraints of degree `d` by the polynomial vanishing
+/// over the trace yields a quotient of degree `(d - 1)⋅n`, where `n` is the length of the trace.
+/// Each periodic column in a constraint increases its degree by at most one. As in Winterfell, the
+/// number of parts of size `n` is rounded up to the next power of two.
+///
+/// Returns an error if the quotient cannot be committed to with the given `blowup_factor`, i.e. if
+/// it has more parts than the blowup factor.
+pub fn num_quotient_parts(air: &Air, blowup_factor: usize) -> anyhow::Result<usize> {
+    let degree = [0, 1]
+        .into_iter()
+        .flat_map(|segment| air.integrity_constraint_degrees(segment))
+        .map(|degree| degree.base() + degree.cycles().len())
+        .max()
+        .unwrap_or(1);
+    let num_parts = degree.saturating_sub(1).max(1).next_power_of_two();
+    anyhow::ensure!(
+        num_parts <= blowup_factor,
+        "the quotient of constraints of degree {degree} is decomposed in {num_parts} parts, which \
+         exceeds the blowup factor {blowup_factor}"
+    );
+    Ok(num_parts)
+}
+
 /// An [`InputRegion`] is a section of indices within the overall list of inputs to the
 /// [`AceCircuit`](crate::AceCircuit).
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
@@ -179,7 +213,7 @@ impl InputRegion {
 }
 
 /// List of STARK variables and challenges, derived from the public parameters and proof transcript.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum StarkVar {
     /// The variable g⁻² corresponding to the penultimate point in the subgroup over which the
     /// trace is interpolated.
@@ -225,3 +259,44 @@ impl From<StarkVar> for usize {
         value as usize
     }
 }
+
+/// Describes the order in which the [`StarkVar`]s are stored in the STARK variables region of the
+/// inputs, so that the circuit can match the memory layout expected by a given verifier.
+///
+/// The default layout stores the variables in the order of their [`StarkVar`] discriminant.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct StarkVarLayout {
+    order: [StarkVar; StarkVar::num_vars()],
+}
+
+impl StarkVarLayout {
+    /// Returns a layout storing the STARK variables in the given `order`, or `None` if `order` is
+    /// not a permutation of all the [`StarkVar`]s.
+    pub fn new(order: [StarkVar; StarkVar::num_vars()]) -> Option<Self> {
+        let mut seen = [false; StarkVar::num_vars()];
+        for var in order {
+            if core::mem::replace(&mut seen[usize::from(var)], true) {
+                return None;
+            }
+        }
+        Some(Self { order })
+    }
+
+    /// Returns the variables in the order in which they are stored.
+    pub fn order(&self) -> &[StarkVar; StarkVar::num_vars()] {
+        &self.order
+    }
+
+    /// Returns the index of `stark_var` within the STARK variables region.
+    pub fn position(&self, stark_var: StarkVar) -> usize {
+        self.order.iter().position(|var| *var == stark_var).unwrap()
+    }
+}
+
+impl Default for StarkVarLayout {
+    fn default() -> Self {
+        Self {
+            order: core::array::from_fn(|index| StarkVar::try_from(index).unwrap()),
+        }
+    }
+}
@@ -2,23 +2,27 @@ mod builder;
 mod circuit;
 mod dot;
 mod encoded;
+mod evaluator;
 mod inputs;
 mod layout;
+mod schedule;
 #[cfg(test)]
 mod tests;
 
-use air_ir::{Air, ConstraintDomain};
+use air_ir::{Air, DivisorSpec, NUM_TRANSITION_EXEMPTIONS, RootKind, VanishingFactor};
 use miden_core::{Felt, QuadExtension};
 
-use crate::{
-    builder::{CircuitBuilder, LinearCombination},
-    layout::StarkVar,
-};
+use crate::builder::{CircuitBuilder, LinearCombination};
 pub use crate::{
     circuit::{Circuit as AceCircuit, Node as AceNode},
-    encoded::EncodedCircuit as EncodedAceCircuit,
+    encoded::{ConstantLayout, EncodedCircuit as EncodedAceCircuit, EncodingOptions, Endianness},
+    evaluator::{AceEvaluator, AceRoots},
     inputs::{AceVars, AirInputs},
-    layout::Layout as AirLayout,
+    layout::{
+        DEFAULT_NUM_QUOTIENT_PARTS, Layout as AirLayout, StarkVar, StarkVarLayout,
+        num_quotient_parts,
+    },
+    schedule::{ConstraintGroup, ConstraintSchedule, ScheduledConstraint},
 };
 
 type QuadFelt = QuadExtension<Felt>;
@@ -37,66 +41,136 @@ type QuadFelt = QuadExtension<Felt>;
 ///   - `z₋₂ = z - g⁻²` is the vanishing polynomial for the penultimate row,
 ///   - `z₋₁ = z - g⁻¹` is the vanishing polynomial for the last row,
 ///   - `zₙ = zⁿ - 1` is the vanishing polynomial for all rows,
-///   - `Q(z) = Q₀(z) + Q₁(z)⋅zⁿ + ⋯ + Q₇(z)⋅z⁷ⁿ` is the reconstructed quotient,
-///   - `n` is the length of the trace.
+///   - `Q(z) = Q₀(z) + Q₁(z)⋅zⁿ + ⋯ + Qₖ₋₁(z)⋅z⁽ᵏ⁻¹⁾ⁿ` is the reconstructed quotient,
+///   - `n` is the length of the trace,
+///   - `k` is the number of parts of the quotient, [`DEFAULT_NUM_QUOTIENT_PARTS`] unless chosen
+///     with [`build_ace_circuit_with_quotient_parts`].
 ///
 /// This is equivalent to the check
 /// ```text
 ///     num₀/[(zⁿ - 1)/[(z - g⁻¹)(z - g⁻²)]] + num₁/(z - 1) + num₂/(z - g⁻²) = Q(z)
 /// ```
 ///
+/// The divisor of each group is given by [`Air::divisor_for`]. The group and the power of `α` of
+/// each root are returned in a [`ConstraintSchedule`], in combination order.
+///
 /// The ACE chiplet expects the inputs of the original AirScript, with the order defined by
 /// `AceLayout`:
 /// - the public inputs of the AirScript e.g. `public_inputs { stack_inputs[16] }`,
 /// - auxiliary randomness of the AirScript e.g. `random_values { rand: [2] }`,
 /// - the main segment of trace inputs of the AirScript e.g. `trace_columns { main: [a b] }`,
 /// - the aux segment of trace inputs of the AirScript e.g. `trace_columns { aux: [f] }`,
-/// - the segment of 8 quotient evaluations `[Q₀(z), ..., Q₇(z)]`,
+/// - the segment of `k` quotient evaluations `[Q₀(z), ..., Qₖ₋₁(z)]`,
 /// - the next row main segment of trace inputs of the AirScript e.g. `a' b'`,
 /// - the next row aux segment of trace inputs of the AirScript e.g. `f'`,
-/// - a dummy section of 8 quotient evaluation for the next row, unused by the ACE circuit.
+/// - a dummy section of `k` quotient evaluation for the next row, unused by the ACE circuit.
 ///
 /// Additionally, the ACE chiplet expects the following 5 auxiliary "STARK" inputs, whose order
-/// is defined by `StarkVar`, given by `[g⁻¹, g⁻¹, α, z, zⁿ, zᵐᵃˣ`].
-pub fn build_ace_circuit(air: &Air) -> anyhow::Result<(AceNode, AceCircuit)> {
-    // A circuit builder is instantiated with the inputs of the circuits plus the 13 needed by the
-    // ACE chiplet
-    let mut cb = CircuitBuilder::new(air);
+/// is defined by `StarkVar`, given by `[g⁻¹, g⁻¹, α, z, zⁿ, zᵐᵃˣ`]. A different order can be
+/// chosen with [`build_ace_circuit_with_layout`].
+pub fn build_ace_circuit(air: &Air) -> anyhow::Result<(AceNode, AceCircuit, ConstraintSchedule)> {
+    build_ace_circuit_with_layout(air, StarkVarLayout::default())
+}
+
+/// Builds the same circuit as [`build_ace_circuit`], where the auxiliary "STARK" inputs are
+/// stored in the order given by `stark_var_layout` rather than the default one.
+pub fn build_ace_circuit_with_layout(
+    air: &Air,
+    stark_var_layout: StarkVarLayout,
+) -> anyhow::Result<(AceNode, AceCircuit, ConstraintSchedule)> {
+    build_ace_circuit_with_quotient_parts(air, stark_var_layout, DEFAULT_NUM_QUOTIENT_PARTS)
+}
+
+/// Builds the same circuit as [`build_ace_circuit_with_layout`], where the quotient is decomposed
+/// in `num_quotient_parts` parts rather than [`DEFAULT_NUM_QUOTIENT_PARTS`], e.g. as derived from
+/// the degree of the constraints with [`num_quotient_parts`]. The segments of quotient evaluations
+/// in the inputs then contain `num_quotient_parts` values each.
+pub fn build_ace_circuit_with_quotient_parts(
+    air: &Air,
+    stark_var_layout: StarkVarLayout,
+    num_quotient_parts: usize,
+) -> anyhow::Result<(AceNode, AceCircuit, ConstraintSchedule)> {
+    anyhow::ensure!(num_quotient_parts > 0, "the quotient must have at least one part");
+    let layout = AirLayout::with_num_quotient_parts(air, stark_var_layout, num_quotient_parts);
+    let (root, circuit, _roots, schedule) =
+        build_circuit(air, CircuitBuilder::with_layout(layout))?;
+    Ok((root, circuit, schedule))
+}
 
+/// Builds a circuit equivalent to the one of [`build_ace_circuit`], in which the integrity
+/// constraint of each expanded bus is built from its operations rather than from its monolithic
+/// expansion, see [`air_ir::ExpandedBus`]. The terms of the operations are combined in balanced
+/// trees, such that the depth of the circuit does not grow linearly with the size of the buses.
+pub fn build_ace_circuit_with_split_buses(
+    air: &Air,
+) -> anyhow::Result<(AceNode, AceCircuit, ConstraintSchedule)> {
+    let (root, circuit, _roots, schedule) =
+        build_circuit(air, CircuitBuilder::new(air).with_split_buses())?;
+    Ok((root, circuit, schedule))
+}
+
+/// Builds the same circuit as [`build_ace_circuit`], additionally returning the [`AceNode`]
+/// corresponding to each individual constraint root of the [`Air`], before they are combined.
+pub fn build_ace_circuit_with_roots(air: &Air) -> anyhow::Result<(AceNode, AceCircuit, AceRoots)> {
+    let (root, circuit, roots, _schedule) = build_circuit(air, CircuitBuilder::new(air))?;
+    Ok((root, circuit, roots))
+}
+
+/// Builds the circuit described in [`build_ace_circuit`] with `cb`, a circuit builder instantiated
+/// with the inputs of the circuits plus the 13 needed by the ACE chiplet.
+fn build_circuit(
+    air: &Air,
+    mut cb: CircuitBuilder,
+) -> anyhow::Result<(AceNode, AceCircuit, AceRoots, ConstraintSchedule)> {
     let segments = [0, 1];
-    let integrity_roots: Vec<_> = segments
-        .iter()
-        .flat_map(|&seg| air.integrity_constraints(seg))
-        .filter_map(|constraint| match constraint.domain() {
-            // TODO(Issue #392): Technically we should separate the transition from
-            //                   all-row constraints
-            ConstraintDomain::EveryRow | ConstraintDomain::EveryFrame(2) => {
-                Some(cb.node_from_index(air, constraint.node_index()))
-            },
-            ConstraintDomain::FirstRow | ConstraintDomain::LastRow => None,
-            ConstraintDomain::EveryFrame(_) => {
-                panic!("invalid integrity constraint domain")
-            },
-        })
-        .collect();
-
-    let boundary_first_roots: Vec<_> = segments
-        .iter()
-        .flat_map(|&seg| air.boundary_constraints(seg))
-        .filter_map(|constraint| match constraint.domain() {
-            ConstraintDomain::FirstRow => Some(cb.node_from_index(air, constraint.node_index())),
-            _ => None,
-        })
-        .collect();
-
-    let boundary_last_roots: Vec<_> = segments
-        .iter()
-        .flat_map(|&seg| air.boundary_constraints(seg))
-        .filter_map(|constraint| match constraint.domain() {
-            ConstraintDomain::LastRow => Some(cb.node_from_index(air, constraint.node_index())),
-            _ => None,
-        })
-        .collect();
+    // The divisor of each group of roots, in the order in which they are linearly combined
+    let integrity_divisor =
+        DivisorSpec::TransitionWithExemptions { exemptions: NUM_TRANSITION_EXEMPTIONS };
+    let divisors = [integrity_divisor, DivisorSpec::FirstRow, DivisorSpec::LastRow];
+
+    // The roots of each group, in the order in which they are linearly combined
+    let groups = [
+        ConstraintGroup::Integrity,
+        ConstraintGroup::BoundaryFirst,
+        ConstraintGroup::BoundaryLast,
+    ];
+    let mut schedule = ConstraintSchedule::default();
+
+    // The roots are enumerated from the groups of `RootKind`, like those of every other backend
+    let mut integrity_roots = [vec![], vec![]];
+    for (seg, roots) in segments.into_iter().zip(integrity_roots.iter_mut()) {
+        for constraint in RootKind::Integrity(seg).roots(air) {
+            let divisor = air.divisor_for(constraint.domain());
+            anyhow::ensure!(
+                divisor == integrity_divisor,
+                "integrity constraint domain {:?} is not supported by the ACE circuit",
+                constraint.domain()
+            );
+            let node = cb.root_node(air, constraint);
+            roots.push(node);
+            schedule.push(ConstraintGroup::Integrity, *constraint.node_index(), node);
+        }
+    }
+
+    for (group, kind) in groups
+        .into_iter()
+        .skip(1)
+        .zip([RootKind::BoundaryFirst, RootKind::BoundaryLast])
+    {
+        for constraint in kind.roots(air) {
+            let node = cb.node_from_index(air, constraint.node_index());
+            schedule.push(group, *constraint.node_index(), node);
+        }
+    }
+
+    let [boundary_first_roots, boundary_last_roots] =
+        [ConstraintGroup::BoundaryFirst, ConstraintGroup::BoundaryLast]
+            .map(|group| schedule.group(group).map(|constraint| constraint.ace_node).collect());
+    let roots = AceRoots {
+        integrity: integrity_roots,
+        boundary_first: boundary_first_roots,
+        boundary_last: boundary_last_roots,
+    };
 
     let one = cb.constant(1);
 
@@ -113,38 +187,49 @@ pub fn build_ace_circuit(air: &Air) -> anyhow::Result<(AceNode, AceCircuit)> {
     let vanish_penultimate = cb.sub(z, gen_penultimate);
     let vanish_last = cb.sub(z, gen_last);
     let vanish_all = cb.sub(z_n, one);
-
-    let mut lc = LinearCombination::new(alpha);
-    let mut lhs = cb.constant(0);
-    // z₋₂²⋅z₋₁⋅z₀⋅int
-    {
-        let int = lc.next_linear_combination(&mut cb, integrity_roots);
-        let res = cb.prod([vanish_first, vanish_penultimate, vanish_last, vanish_penultimate, int]);
-        lhs = cb.add(lhs, res);
+    let vanish = |factor: VanishingFactor| match factor {
+        VanishingFactor::FirstRow => Ok(vanish_first),
+        VanishingFactor::FromEnd(1) => Ok(vanish_last),
+        VanishingFactor::FromEnd(2) => Ok(vanish_penultimate),
+        VanishingFactor::AllRows => Ok(vanish_all),
+        VanishingFactor::FromEnd(_) => Err(anyhow::anyhow!(
+            "vanishing factor {factor:?} is not supported by the ACE circuit"
+        )),
     };
 
-    // zₙ⋅z₋₂⋅bf
-    {
-        let bf = lc.next_linear_combination(&mut cb, boundary_first_roots);
-        let res = cb.prod([vanish_penultimate, vanish_all, bf]);
-        lhs = cb.add(lhs, res);
-    };
+    // Both sides of the formula are multiplied by the product of the numerators of all divisors,
+    // `zₙ⋅z₀⋅z₋₂`, so that no division is needed.
+    let mut common_factors: Vec<_> = divisors.iter().flat_map(DivisorSpec::numerator).collect();
+    common_factors.sort();
+    common_factors.dedup();
 
-    // zₙ⋅z₀⋅bl
-    {
-        let bl = lc.next_linear_combination(&mut cb, boundary_last_roots);
-        let res = cb.prod([vanish_first, vanish_all, bl]);
+    let mut lc = LinearCombination::new(alpha);
+    let mut lhs = cb.constant(0);
+    // z₋₂²⋅z₋₁⋅z₀⋅int + zₙ⋅z₋₂⋅bf + zₙ⋅z₀⋅bl
+    for (divisor, group) in divisors.iter().zip(groups) {
+        let group = schedule.group(group).map(|constraint| constraint.ace_node);
+        let combined = lc.next_linear_combination(&mut cb, group);
+        // The common factors divided by the divisor of the group
+        let mut factors = common_factors.clone();
+        for factor in divisor.numerator() {
+            let pos = factors.iter().position(|f| *f == factor).expect("missing common factor");
+            factors.remove(pos);
+        }
+        factors.extend(divisor.denominator());
+        let factors = factors.into_iter().map(vanish).collect::<anyhow::Result<Vec<_>>>()?;
+        let res = cb.prod(factors.into_iter().chain([combined]));
         lhs = cb.add(lhs, res);
-    };
+    }
 
     // Q(z)⋅zₙ⋅z₀⋅z₋₂, where Q(z) = Q₀(z) + Q₁(z)⋅zⁿ + ⋯ + Q₇(z)⋅z⁷ⁿ
     let rhs = {
         let q = cb.layout.quotient_nodes(); // [Q₀(z), ..., Q₇(z)]
         let qz = cb.poly_eval(z_n, &q); // Q(z)
-        cb.prod([vanish_first, vanish_penultimate, vanish_all, qz])
+        let factors = common_factors.into_iter().map(vanish).collect::<anyhow::Result<Vec<_>>>()?;
+        cb.prod(factors.into_iter().chain([qz]))
     };
 
     let root = cb.sub(lhs, rhs);
     let circuit = cb.into_ace_circuit();
-    Ok((root, circuit))
+    Ok((root, circuit, roots, schedule))
 }
@@ -0,0 +1,70 @@
+use air_ir::NodeIndex;
+
+use crate::circuit::Node;
+
+/// The groups in which the constraint roots of an [`air_ir::Air`] are linearly combined by the ACE
+/// circuit, each with its own divisor. See [`crate::build_ace_circuit`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum ConstraintGroup {
+    /// Integrity roots of all segments.
+    Integrity,
+    /// Boundary roots applied to the first row, across all segments.
+    BoundaryFirst,
+    /// Boundary roots applied to the last row, across all segments.
+    BoundaryLast,
+}
+
+/// A constraint root, as combined by the ACE circuit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScheduledConstraint {
+    /// The group of the root, which determines its divisor.
+    pub group: ConstraintGroup,
+    /// The root in the algebraic graph of the [`air_ir::Air`].
+    pub air_root: NodeIndex,
+    /// The node of the root in the ACE circuit.
+    pub ace_node: Node,
+    /// The power of `α` multiplying the root in the linear combination of its group.
+    pub alpha_power: usize,
+}
+
+/// The constraint roots of an [`air_ir::Air`], in the order in which they are combined by the ACE
+/// circuit.
+///
+/// Roots are ordered by group: integrity roots first, then boundary roots for the first row, then
+/// boundary roots for the last row. Within a group, roots follow the order of their trace segment,
+/// then of their declaration. The powers of `α` are contiguous across groups: the `i`-th root of
+/// the schedule is multiplied by `αⁱ`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConstraintSchedule {
+    constraints: Vec<ScheduledConstraint>,
+}
+
+impl ConstraintSchedule {
+    /// Appends a root to `group`, which must not precede the group of the last root.
+    pub(crate) fn push(&mut self, group: ConstraintGroup, air_root: NodeIndex, ace_node: Node) {
+        debug_assert!(self.constraints.last().is_none_or(|last| last.group <= group));
+        let alpha_power = self.constraints.len();
+        self.constraints
+            .push(ScheduledConstraint { group, air_root, ace_node, alpha_power });
+    }
+
+    /// Returns the number of roots in the schedule.
+    pub fn len(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Returns true if the schedule has no roots.
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
+    /// Returns an iterator over all roots, in combination order.
+    pub fn iter(&self) -> impl Iterator<Item = &ScheduledConstraint> {
+        self.constraints.iter()
+    }
+
+    /// Returns an iterator over the roots of `group`, in combination order.
+    pub fn group(&self, group: ConstraintGroup) -> impl Iterator<Item = &ScheduledConstraint> {
+        self.constraints.iter().filter(move |constraint| constraint.group == group)
+    }
+}
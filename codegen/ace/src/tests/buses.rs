@@ -0,0 +1,118 @@
+use winter_math::FieldElement;
+
+use crate::{
+    AceVars, QuadFelt, build_ace_circuit, build_ace_circuit_with_split_buses,
+    tests::{generate_circuit, lower},
+};
+
+/// Returns an AirScript program with a single bus of `bus_type`, with `num_ops` operations
+/// alternately inserting and removing distinct tuples.
+fn large_bus_source(bus_type: &str, num_ops: usize) -> String {
+    let ops: String = (0..num_ops)
+        .map(|i| match i % 2 {
+            0 => format!("p.insert(a, {i}) when s;\n"),
+            _ => format!("p.remove(b, {i}) when s;\n"),
+        })
+        .collect();
+    format!(
+        "
+    def LargeBus
+    trace_columns {{
+        main: [a, b, s],
+    }}
+    buses {{
+        {bus_type} p,
+    }}
+    public_inputs {{
+        stack_inputs: [1],
+    }}
+    boundary_constraints {{
+        enf a.first = 0;
+        enf p.first = null;
+        enf p.last = null;
+    }}
+    integrity_constraints {{
+        enf s^2 = s;
+        {ops}
+    }}"
+    )
+}
+
+/// Checks that a circuit built from the operations of a large bus evaluates to the same value as
+/// the one built from its monolithic expansion.
+#[test]
+fn test_split_buses_match_monolithic_expansion() {
+    let log_trace_len = 10u32;
+    for bus_type in ["multiset", "logup"] {
+        let (air, circuit, root) = generate_circuit(&large_bus_source(bus_type, 32));
+        assert_eq!(air.expanded_buses().len(), 1);
+        assert_eq!(air.expanded_buses()[0].ops.len(), 32);
+
+        let (split_root, split_circuit, _) =
+            build_ace_circuit_with_split_buses(&air).expect("codegen failed");
+        let (monolithic_root, ..) = build_ace_circuit(&air).expect("codegen failed");
+        assert_eq!(monolithic_root, root);
+
+        let ace_vars = AceVars::random(&air, log_trace_len);
+        let mem_inputs = ace_vars.to_memory_vec(&circuit.layout);
+        let eval = circuit.eval(root, &mem_inputs);
+        assert_eq!(split_circuit.eval(split_root, &mem_inputs), eval, "{bus_type}");
+
+        let ace_vars = AceVars::random_with_valid_quotient(&air, log_trace_len);
+        let mem_inputs = ace_vars.to_memory_vec(&split_circuit.layout);
+        assert_eq!(split_circuit.eval(split_root, &mem_inputs), QuadFelt::ZERO, "{bus_type}");
+    }
+}
+
+/// Checks that the random values removed by the `DeadRandomValues` pass are not allocated in the
+/// layout of the circuit, which still evaluates to zero on a valid quotient.
+#[test]
+fn test_dead_random_values_shrink_layout() {
+    use std::sync::Arc;
+
+    use air_ir::passes::{DeadRandomValues, Simplify};
+    use air_pass::Pass;
+    use miden_diagnostics::{
+        CodeMap, DefaultEmitter, DiagnosticsHandler, term::termcolor::ColorChoice,
+    };
+
+    let source = "
+    def DeadRandomValues
+    trace_columns {
+        main: [a, b, s],
+    }
+    buses {
+        multiset p,
+    }
+    public_inputs {
+        stack_inputs: [1],
+    }
+    boundary_constraints {
+        enf a.first = 0;
+        enf p.first = null;
+        enf p.last = null;
+    }
+    integrity_constraints {
+        enf s^2 = s;
+        p.insert(0, a, 0) when s;
+        p.remove(0, b, 0) when s;
+    }";
+    let code_map = Arc::new(CodeMap::new());
+    let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), code_map.clone(), emitter);
+    let air = lower(&diagnostics, code_map, source);
+    let (_, circuit, _) = build_ace_circuit(&air).expect("codegen failed");
+    assert_eq!(circuit.layout.random_values.width, 4);
+
+    let air = Simplify::new(&diagnostics)
+        .chain(DeadRandomValues::new(&diagnostics))
+        .run(air)
+        .expect("pass failed");
+    assert_eq!(air.num_random_values, 2);
+
+    let (root, circuit, _) = build_ace_circuit(&air).expect("codegen failed");
+    assert_eq!(circuit.layout.random_values.width, 2);
+    let ace_vars = AceVars::random_with_valid_quotient(&air, 10);
+    let mem_inputs = ace_vars.to_memory_vec(&circuit.layout);
+    assert_eq!(circuit.eval(root, &mem_inputs), QuadFelt::ZERO);
+}
@@ -0,0 +1,74 @@
+use air_ir::{Air, ConstraintDomain};
+
+use crate::{
+    AceCircuit, AceNode, StarkVar,
+    builder::{CircuitBuilder, LinearCombination},
+    tests::{generate_circuit, load_air_files},
+};
+
+/// Builds the ACE circuit with the vanishing polynomials hard-coded for each group of roots, as
+/// was done before they were derived from `Air::divisor_for`.
+fn build_hard_coded_circuit(air: &Air) -> (AceNode, AceCircuit) {
+    let mut cb = CircuitBuilder::new(air);
+    let segments = [0, 1];
+    let integrity = segments.map(|seg| {
+        air.integrity_constraints(seg)
+            .iter()
+            .map(|constraint| cb.node_from_index(air, constraint.node_index()))
+            .collect::<Vec<_>>()
+    });
+    let [boundary_first, boundary_last] = [ConstraintDomain::FirstRow, ConstraintDomain::LastRow]
+        .map(|domain| {
+            segments
+                .iter()
+                .flat_map(|&seg| air.boundary_constraints(seg))
+                .filter(|constraint| constraint.domain() == domain)
+                .map(|constraint| cb.node_from_index(air, constraint.node_index()))
+                .collect::<Vec<_>>()
+        });
+
+    let one = cb.constant(1);
+    let alpha = cb.layout.stark_node(StarkVar::Alpha);
+    let z = cb.layout.stark_node(StarkVar::Z);
+    let z_n = cb.layout.stark_node(StarkVar::ZPowN);
+    let gen_last = cb.layout.stark_node(StarkVar::GenLast);
+    let gen_penultimate = cb.layout.stark_node(StarkVar::GenPenultimate);
+
+    let vanish_first = cb.sub(z, one);
+    let vanish_penultimate = cb.sub(z, gen_penultimate);
+    let vanish_last = cb.sub(z, gen_last);
+    let vanish_all = cb.sub(z_n, one);
+
+    let mut lc = LinearCombination::new(alpha);
+    let mut lhs = cb.constant(0);
+
+    let int = lc.next_linear_combination(&mut cb, integrity.concat());
+    let res = cb.prod([vanish_first, vanish_penultimate, vanish_last, vanish_penultimate, int]);
+    lhs = cb.add(lhs, res);
+
+    let bf = lc.next_linear_combination(&mut cb, boundary_first);
+    let res = cb.prod([vanish_penultimate, vanish_all, bf]);
+    lhs = cb.add(lhs, res);
+
+    let bl = lc.next_linear_combination(&mut cb, boundary_last);
+    let res = cb.prod([vanish_first, vanish_all, bl]);
+    lhs = cb.add(lhs, res);
+
+    let q = cb.layout.quotient_nodes();
+    let qz = cb.poly_eval(z_n, &q);
+    let rhs = cb.prod([vanish_first, vanish_penultimate, vanish_all, qz]);
+
+    let root = cb.sub(lhs, rhs);
+    (root, cb.into_ace_circuit())
+}
+
+#[test]
+fn test_divisors_match_hard_coded_circuit() {
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, circuit, root) = generate_circuit(&air_string);
+        let (expected_root, expected_circuit) = build_hard_coded_circuit(&air);
+        assert_eq!(root, expected_root);
+        assert_eq!(circuit, expected_circuit);
+    }
+}
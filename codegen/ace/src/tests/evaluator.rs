@@ -0,0 +1,294 @@
+use std::sync::Arc;
+
+use air_ir::{
+    ConstraintEvaluator, ConstraintRoot, Constraints, NodeIndex, Operation, RootKind,
+    passes::{Canonicalize, Simplify},
+};
+use air_pass::Pass;
+use miden_diagnostics::{
+    CodeMap, DefaultEmitter, DiagnosticsHandler, term::termcolor::ColorChoice,
+};
+
+use miden_core::Felt;
+use winter_math::FieldElement;
+
+use crate::{
+    AceEvaluator, QuadFelt,
+    tests::{
+        generate_circuit, load_air_files,
+        quotient::{coeffs, poly_eval},
+        random::random_eval_context,
+    },
+};
+
+/// Checks that interpreting the Air graph and evaluating the ACE circuit agree on every constraint
+/// root of every testing Air, for several random contexts.
+#[test]
+fn test_evaluators_agree() {
+    let log_trace_len = 10u32;
+    let kinds = [
+        RootKind::Integrity(0),
+        RootKind::Integrity(1),
+        RootKind::BoundaryFirst,
+        RootKind::BoundaryLast,
+    ];
+
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, ..) = generate_circuit(&air_string);
+        let ace = AceEvaluator::new(&air).expect("codegen failed");
+
+        for kind in kinds {
+            assert_eq!(air.num_roots(kind), ace.num_roots(kind), "{}: {kind:?}", air.name());
+        }
+
+        for _ in 0..3 {
+            let ctx = random_eval_context(&air, log_trace_len);
+            for kind in kinds {
+                for i in 0..air.num_roots(kind) {
+                    assert_eq!(
+                        air.evaluate_root(kind, i, &ctx),
+                        ace.evaluate_root(kind, i, &ctx),
+                        "{}: root {i} of {kind:?}",
+                        air.name()
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Checks that the roots given to an Air as integrity constraints, but applied to the first or last
+/// row, are counted and evaluated as boundary roots by the Air graph and by the ACE circuit alike.
+#[test]
+fn test_boundary_domain_integrity_roots_agree() {
+    let log_trace_len = 10u32;
+    let kinds = [RootKind::Integrity(0), RootKind::BoundaryFirst, RootKind::BoundaryLast];
+    let source = "
+    def test
+    trace_columns {
+        main: [a, b, c],
+    }
+    public_inputs {
+        inputs: [2],
+    }
+    boundary_constraints {
+        enf a.first = inputs[0];
+        enf b.first = 1;
+        enf c.last = inputs[1];
+    }
+    integrity_constraints {
+        enf a' = a + b;
+        enf b' = b * c;
+    }";
+    let (mut air, ..) = generate_circuit(source);
+    let (expected, ..) = generate_circuit(source);
+
+    // Every root is given as an integrity constraint, boundary roots first
+    let roots: Vec<ConstraintRoot> = air
+        .boundary_constraints(0)
+        .iter()
+        .chain(air.integrity_constraints(0))
+        .cloned()
+        .collect();
+    let graph = std::mem::take(air.constraint_graph_mut());
+    air.constraints = Constraints::new(graph, vec![], vec![roots]);
+
+    assert_eq!(air.num_roots(RootKind::Integrity(0)), 2);
+    assert_eq!(air.num_roots(RootKind::BoundaryFirst), 2);
+    assert_eq!(air.num_roots(RootKind::BoundaryLast), 1);
+    assert_eq!(air.boundary_constraints(0), expected.boundary_constraints(0));
+    assert_eq!(air.integrity_constraints(0), expected.integrity_constraints(0));
+
+    let ace = AceEvaluator::new(&air).expect("codegen failed");
+    for kind in kinds {
+        assert_eq!(air.num_roots(kind), ace.num_roots(kind), "{kind:?}");
+    }
+    for _ in 0..3 {
+        let ctx = random_eval_context(&air, log_trace_len);
+        for kind in kinds {
+            for i in 0..air.num_roots(kind) {
+                let eval = air.evaluate_root(kind, i, &ctx);
+                assert_eq!(eval, ace.evaluate_root(kind, i, &ctx), "root {i} of {kind:?}");
+                assert_eq!(eval, expected.evaluate_root(kind, i, &ctx), "root {i} of {kind:?}");
+            }
+        }
+    }
+}
+
+/// Checks that canonicalizing the constraint graph of every testing Air does not change the
+/// evaluation of any constraint root, for several random contexts, and that the canonical graph
+/// no longer contains any subtraction, except at the root of boundary constraints.
+#[test]
+fn test_canonicalize_preserves_evaluation() {
+    let log_trace_len = 10u32;
+    let kinds = [
+        RootKind::Integrity(0),
+        RootKind::Integrity(1),
+        RootKind::BoundaryFirst,
+        RootKind::BoundaryLast,
+    ];
+
+    let code_map = Arc::new(CodeMap::new());
+    let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), code_map, emitter);
+
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, ..) = generate_circuit(&air_string);
+        let (canonical, ..) = generate_circuit(&air_string);
+        let canonical = Canonicalize::new(&diagnostics).run(canonical).expect("pass failed");
+
+        let graph = canonical.constraint_graph();
+        let boundary: Vec<_> = [RootKind::BoundaryFirst, RootKind::BoundaryLast]
+            .into_iter()
+            .flat_map(|kind| kind.roots(&canonical).iter().map(|root| *root.node_index()))
+            .collect();
+        for i in (0..graph.num_nodes()).map(NodeIndex::from) {
+            assert!(
+                boundary.contains(&i) || !matches!(graph.node(&i).op(), Operation::Sub(..)),
+                "{}: unexpected subtraction in canonical graph",
+                air.name()
+            );
+        }
+
+        for kind in kinds {
+            assert_eq!(air.num_roots(kind), canonical.num_roots(kind), "{}: {kind:?}", air.name());
+        }
+
+        for _ in 0..3 {
+            let ctx = random_eval_context(&air, log_trace_len);
+            for kind in kinds {
+                for i in 0..air.num_roots(kind) {
+                    assert_eq!(
+                        air.evaluate_root(kind, i, &ctx),
+                        canonical.evaluate_root(kind, i, &ctx),
+                        "{}: root {i} of {kind:?}",
+                        air.name()
+                    );
+                }
+            }
+        }
+
+        // The canonical graph must also be accepted by the ACE backend
+        let ace = AceEvaluator::new(&canonical).expect("codegen failed");
+        let ctx = random_eval_context(&air, log_trace_len);
+        for kind in kinds {
+            for i in 0..air.num_roots(kind) {
+                assert_eq!(
+                    air.evaluate_root(kind, i, &ctx),
+                    ace.evaluate_root(kind, i, &ctx),
+                    "{}: root {i} of {kind:?}",
+                    air.name()
+                );
+            }
+        }
+    }
+}
+
+/// Checks that simplifying the constraint graph of every testing Air does not change the
+/// evaluation of any constraint root, for several random contexts, and that the result is still
+/// accepted by the ACE backend.
+#[test]
+fn test_simplify_preserves_evaluation() {
+    let log_trace_len = 10u32;
+    let kinds = [
+        RootKind::Integrity(0),
+        RootKind::Integrity(1),
+        RootKind::BoundaryFirst,
+        RootKind::BoundaryLast,
+    ];
+
+    let code_map = Arc::new(CodeMap::new());
+    let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), code_map, emitter);
+
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, ..) = generate_circuit(&air_string);
+        let (simplified, ..) = generate_circuit(&air_string);
+        let simplified = Simplify::new(&diagnostics).run(simplified).expect("pass failed");
+        assert!(
+            simplified.constraint_graph().num_nodes() <= air.constraint_graph().num_nodes(),
+            "{}: simplification added nodes",
+            air.name()
+        );
+
+        for kind in kinds {
+            assert_eq!(air.num_roots(kind), simplified.num_roots(kind), "{}: {kind:?}", air.name());
+        }
+
+        let ace = AceEvaluator::new(&simplified).expect("codegen failed");
+        for _ in 0..3 {
+            let ctx = random_eval_context(&air, log_trace_len);
+            for kind in kinds {
+                for i in 0..air.num_roots(kind) {
+                    let expected = air.evaluate_root(kind, i, &ctx);
+                    assert_eq!(
+                        expected,
+                        simplified.evaluate_root(kind, i, &ctx),
+                        "{}: root {i} of {kind:?}",
+                        air.name()
+                    );
+                    assert_eq!(
+                        expected,
+                        ace.evaluate_root(kind, i, &ctx),
+                        "{}: root {i} of {kind:?}",
+                        air.name()
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Checks that reading periodic columns in the next row agrees with evaluating, at the point of
+/// the unshifted column, the copy of the column rotated by one row declared by the Winterfell
+/// backend, and that the ACE circuit agrees with the direct evaluation at `z·g`.
+#[test]
+fn test_shifted_periodic_columns() {
+    let log_trace_len = 10u32;
+    let airs = load_air_files().expect("unable to read airs");
+    let air_string = airs
+        .iter()
+        .find(|air| air.starts_with("def PeriodicNext"))
+        .expect("missing PeriodicNext air");
+    let (air, ..) = generate_circuit(air_string);
+    let shifted = air.shifted_periodic_columns();
+    assert_eq!(shifted.len(), air.periodic_columns.len());
+
+    let ctx = random_eval_context(&air, log_trace_len);
+    for access in shifted {
+        let mut values = air.periodic_columns[&access.name].values.clone();
+        values.rotate_left(access.row_offset);
+        let z_col = ctx.z.exp_vartime((1u64 << log_trace_len) / values.len() as u64);
+        let poly = coeffs(&values);
+
+        assert_eq!(ctx.periodic[&(access.name, access.row_offset)], poly_eval(&poly, z_col));
+    }
+
+    let ace = AceEvaluator::new(&air).expect("codegen failed");
+    let kind = RootKind::Integrity(0);
+    for i in 0..air.num_roots(kind) {
+        assert_eq!(air.evaluate_root(kind, i, &ctx), ace.evaluate_root(kind, i, &ctx));
+    }
+}
+
+/// Checks that the coefficients of the periodic column polynomials exposed by the [Air], which the
+/// ACE circuit embeds as constants, match an independent interpolation of the column values.
+#[test]
+fn test_periodic_column_polynomials() {
+    let airs = load_air_files().expect("unable to read airs");
+    let mut num_columns = 0;
+    for air_string in airs.iter() {
+        let (air, ..) = generate_circuit(air_string);
+        let polys = air.periodic_column_polynomials();
+        assert_eq!(polys.len(), air.periodic_columns.len());
+        for (name, column) in air.periodic_columns.iter() {
+            let poly: Vec<_> = polys[name].iter().map(|c| QuadFelt::from(Felt::new(*c))).collect();
+            assert_eq!(poly, coeffs(&column.values));
+            num_columns += 1;
+        }
+    }
+    assert!(num_columns > 0);
+}
@@ -1,43 +1,57 @@
 use std::sync::Arc;
 
-use air_ir::Air;
+use air_ir::{Air, Provenance, WitnessInput};
+use miden_core::{Felt, crypto::hash::RpoDigest};
 use miden_diagnostics::{
     CodeMap, DefaultEmitter, DiagnosticsHandler, term::termcolor::ColorChoice,
 };
 use winter_math::FieldElement;
 
 use crate::{
-    AceVars, QuadFelt, build_ace_circuit,
+    AceVars, AirLayout, ConstantLayout, DEFAULT_NUM_QUOTIENT_PARTS, EncodingOptions, Endianness,
+    QuadFelt, StarkVar, StarkVarLayout, build_ace_circuit, build_ace_circuit_with_layout,
+    build_ace_circuit_with_quotient_parts,
     circuit::{Circuit, Node},
 };
 
+mod buses;
+mod divisor;
+mod evaluator;
+mod properties;
 mod quotient;
 mod random;
+mod schedule;
 
 /// Generates an ACE circuit and its root index from an AirScript program.
 pub fn generate_circuit(source: &str) -> (Air, Circuit, Node) {
-    use air_pass::Pass;
-
     let code_map = Arc::new(CodeMap::new());
     let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
     let diagnostics = DiagnosticsHandler::new(Default::default(), code_map.clone(), emitter);
 
-    let air = air_parser::parse(&diagnostics, code_map, source)
+    let air = lower(&diagnostics, code_map, source);
+    let (root, circuit, _schedule) = build_ace_circuit(&air).expect("codegen failed");
+
+    (air, circuit, root)
+}
+
+/// Lowers an AirScript program to an [Air], reporting diagnostics to `diagnostics`, whose code
+/// map must be `code_map`.
+pub fn lower(diagnostics: &DiagnosticsHandler, code_map: Arc<CodeMap>, source: &str) -> Air {
+    use air_pass::Pass;
+
+    air_parser::parse(diagnostics, code_map, source)
         .map_err(air_ir::CompileError::Parse)
         .and_then(|ast| {
-            let mut pipeline = air_parser::transforms::ConstantPropagation::new(&diagnostics)
-                .chain(mir::passes::AstToMir::new(&diagnostics))
-                .chain(mir::passes::Inlining::new(&diagnostics))
-                .chain(mir::passes::Unrolling::new(&diagnostics))
-                .chain(air_ir::passes::MirToAir::new(&diagnostics))
-                .chain(air_ir::passes::BusOpExpand::new(&diagnostics));
+            let mut pipeline = air_parser::transforms::ConstantPropagation::new(diagnostics)
+                .chain(mir::passes::AstToMir::new(diagnostics))
+                .chain(mir::passes::Inlining::new(diagnostics))
+                .chain(mir::passes::Unrolling::new(diagnostics))
+                .chain(air_ir::passes::MirToAir::new(diagnostics))
+                .chain(air_ir::passes::TrivialConstraints::new(diagnostics))
+                .chain(air_ir::passes::BusOpExpand::new(diagnostics));
             pipeline.run(ast)
         })
-        .expect("lowering failed");
-
-    let (root, circuit) = build_ace_circuit(&air).expect("codegen failed");
-
-    (air, circuit, root)
+        .expect("lowering failed")
 }
 
 /// Loads all Airs in `tests/airs`.
@@ -92,3 +106,597 @@ fn test_regressions() -> Result<(), std::fmt::Error> {
     }
     Ok(())
 }
+
+/// Checks that the STARK variables can be stored in a different order, by evaluating each circuit
+/// with its inputs laid out according to a permuted layout.
+#[test]
+fn test_permuted_stark_var_layout() {
+    let log_trace_len = 16u32;
+    let stark_var_layout = StarkVarLayout::new([
+        StarkVar::Z,
+        StarkVar::Alpha,
+        StarkVar::ZMaxCycle,
+        StarkVar::GenLast,
+        StarkVar::ZPowN,
+        StarkVar::GenPenultimate,
+    ])
+    .unwrap();
+
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, circuit, root_node) = generate_circuit(&air_string);
+        let (permuted_root_node, permuted_circuit, _) =
+            build_ace_circuit_with_layout(&air, stark_var_layout).expect("codegen failed");
+        assert_eq!(permuted_circuit.layout.stark_var_layout, stark_var_layout);
+
+        let ace_vars = AceVars::random_with_valid_quotient(&air, log_trace_len);
+        let mem_inputs = ace_vars.to_memory_vec(&circuit.layout);
+        let permuted_mem_inputs = ace_vars.to_memory_vec(&permuted_circuit.layout);
+        assert_ne!(mem_inputs, permuted_mem_inputs);
+
+        let eval = permuted_circuit.eval(permuted_root_node, &permuted_mem_inputs);
+        assert_eq!(eval, QuadFelt::ZERO);
+
+        // The inputs of the default layout are not valid for the permuted circuit
+        let eval = circuit.eval(root_node, &permuted_mem_inputs);
+        assert_ne!(eval, QuadFelt::ZERO);
+    }
+}
+
+/// Checks that the quotient can be decomposed in fewer parts than the default, by evaluating each
+/// circuit with inputs containing as many quotient evaluations.
+#[test]
+fn test_num_quotient_parts() {
+    let log_trace_len = 16u32;
+    let num_quotient_parts = 4;
+
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, circuit, root_node) = generate_circuit(&air_string);
+        assert_eq!(circuit.layout.quotient_nodes().len(), DEFAULT_NUM_QUOTIENT_PARTS);
+
+        let (root_node_4, circuit_4, _) = build_ace_circuit_with_quotient_parts(
+            &air,
+            StarkVarLayout::default(),
+            num_quotient_parts,
+        )
+        .expect("codegen failed");
+        assert_eq!(circuit_4.layout.quotient_nodes().len(), num_quotient_parts);
+        assert_eq!(
+            circuit_4.layout,
+            AirLayout::with_num_quotient_parts(&air, StarkVarLayout::default(), num_quotient_parts)
+        );
+
+        let ace_vars =
+            AceVars::random_with_valid_quotient_and_layout(&air, &circuit_4.layout, log_trace_len);
+        let mem_inputs = ace_vars.to_memory_vec(&circuit_4.layout);
+        assert!(mem_inputs.len() < circuit.layout.num_inputs);
+        assert_eq!(circuit_4.eval(root_node_4, &mem_inputs), QuadFelt::ZERO);
+
+        // The default circuit still expects all the parts
+        let ace_vars = AceVars::random_with_valid_quotient(&air, log_trace_len);
+        let mem_inputs = ace_vars.to_memory_vec(&circuit.layout);
+        assert_eq!(circuit.eval(root_node, &mem_inputs), QuadFelt::ZERO);
+    }
+}
+
+/// Checks the number of quotient parts derived from the degree of the constraints.
+#[test]
+fn test_derived_num_quotient_parts() {
+    let source = |constraint: &str| {
+        format!(
+            "
+def QuotientParts
+
+trace_columns {{
+    main: [a, b],
+}}
+
+public_inputs {{
+    stack_inputs: [2],
+}}
+
+periodic_columns {{
+    k: [1, 0],
+}}
+
+boundary_constraints {{
+    enf a.first = 0;
+}}
+
+integrity_constraints {{
+    {constraint}
+}}"
+        )
+    };
+
+    for (constraint, expected) in [
+        ("enf a' = a + b;", 1),
+        ("enf a' = a * b;", 1),
+        ("enf a' = a * b * b;", 2),
+        ("enf a' = k * a * b * b;", 4),
+        ("enf a' = a^5;", 4),
+        ("enf a' = a^9;", 8),
+    ] {
+        let (air, ..) = generate_circuit(&source(constraint));
+        let num_parts = crate::num_quotient_parts(&air, 8).expect("blowup factor is too small");
+        assert_eq!(num_parts, expected, "{constraint}");
+
+        let (root, circuit, _) =
+            build_ace_circuit_with_quotient_parts(&air, StarkVarLayout::default(), num_parts)
+                .expect("codegen failed");
+        let ace_vars = AceVars::random_with_valid_quotient_and_layout(&air, &circuit.layout, 10);
+        let mem_inputs = ace_vars.to_memory_vec(&circuit.layout);
+        assert_eq!(circuit.eval(root, &mem_inputs), QuadFelt::ZERO);
+    }
+
+    // The quotient of a constraint of degree 9 does not fit in a blowup factor of 4
+    let (air, ..) = generate_circuit(&source("enf a' = a^9;"));
+    assert!(crate::num_quotient_parts(&air, 4).is_err());
+    assert!(build_ace_circuit_with_quotient_parts(&air, StarkVarLayout::default(), 0).is_err());
+}
+
+#[test]
+fn test_stark_var_layout_must_be_permutation() {
+    assert!(
+        StarkVarLayout::new([
+            StarkVar::Z,
+            StarkVar::Z,
+            StarkVar::Alpha,
+            StarkVar::GenLast,
+            StarkVar::ZPowN,
+            StarkVar::GenPenultimate,
+        ])
+        .is_none()
+    );
+    let default = StarkVarLayout::default();
+    assert_eq!(StarkVarLayout::new(*default.order()), Some(default));
+    assert_eq!(default.position(StarkVar::Alpha), usize::from(StarkVar::Alpha));
+}
+
+#[test]
+fn test_encoded_circuit_provenance() {
+    let airs = load_air_files().expect("unable to read airs");
+    let (_, circuit, _) = generate_circuit(&airs[0]);
+    let provenance = Provenance {
+        compiler_version: "0.5.0".to_string(),
+        source_hash: "abcd".to_string(),
+        options: vec!["pipeline=WithMIR".to_string()],
+    };
+
+    let encoded = circuit.to_ace();
+    assert_eq!(encoded.provenance(), None);
+    let circuit_hash = encoded.circuit_hash();
+
+    let encoded = encoded.with_provenance(provenance.clone());
+    assert_eq!(encoded.provenance(), Some(&provenance));
+    assert_eq!(encoded.circuit_hash(), circuit_hash);
+}
+
+/// Checks that the evaluation point of the longest periodic column agrees with the exponent
+/// defined by the [Air], and that the other columns are evaluated at the right powers of it, when
+/// periodic columns are used in both main and aux constraints.
+#[test]
+fn test_periodic_columns_evaluation_points() {
+    let log_trace_len = 10u32;
+    let trace_len = 1u64 << log_trace_len;
+    let cycle_sets: [&[usize]; 2] = [&[2, 8], &[2, 4, 8]];
+    for cycles in cycle_sets {
+        let periodic_columns: String = cycles
+            .iter()
+            .enumerate()
+            .map(|(i, len)| {
+                let values = (0..*len).map(|j| (j % 2).to_string()).collect::<Vec<_>>();
+                format!("k{i}: [{}],\n", values.join(", "))
+            })
+            .collect();
+        let selectors = (0..cycles.len()).map(|i| format!("k{i}")).collect::<Vec<_>>().join(" * ");
+        let source = format!(
+            "
+        def PeriodicAux
+        trace_columns {{
+            main: [a, b],
+        }}
+        periodic_columns {{
+            {periodic_columns}
+        }}
+        buses {{
+            multiset p,
+        }}
+        public_inputs {{
+            stack_inputs: [1],
+        }}
+        boundary_constraints {{
+            enf a.first = 0;
+            enf p.first = null;
+            enf p.last = null;
+        }}
+        integrity_constraints {{
+            enf a' = a + {selectors};
+            p.insert(b) when k0;
+        }}"
+        );
+        let (air, circuit, root_node) = generate_circuit(&source);
+
+        let z_exponent = air.z_exponent_for_periodic(trace_len).unwrap();
+        assert_eq!(z_exponent, trace_len / 8);
+
+        let ace_vars = AceVars::random_with_valid_quotient(&air, log_trace_len);
+        assert_eq!(ace_vars.stark.z_max_cycle, ace_vars.stark.z.exp_vartime(z_exponent));
+
+        let mem_inputs = ace_vars.to_memory_vec(&circuit.layout);
+        assert_eq!(circuit.eval(root_node, &mem_inputs), QuadFelt::ZERO);
+    }
+}
+
+/// Checks that the inputs built from a trace frame are laid out in the same way as the randomized
+/// ones, by rebuilding each set of valid random inputs from its parts.
+#[test]
+fn test_ace_vars_from_frame() {
+    let log_trace_len = 16u32;
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, circuit, root_node) = generate_circuit(&air_string);
+
+        let ace_vars = AceVars::random_with_valid_quotient(&air, log_trace_len);
+        let [[main_curr, aux_curr, quotient_curr], [main_next, aux_next, quotient_next]] =
+            ace_vars.segments.clone();
+        let stark_vars: [QuadFelt; StarkVar::num_vars()] =
+            ace_vars.stark.to_vec().try_into().unwrap();
+        let from_frame = AceVars::from_frame(
+            &circuit.layout,
+            ace_vars.public.clone(),
+            ace_vars.rand.clone(),
+            main_curr,
+            aux_curr,
+            main_next,
+            aux_next,
+            [quotient_curr, quotient_next],
+            stark_vars,
+        )
+        .expect("invalid frame");
+
+        let mem_inputs = from_frame.to_memory_vec(&circuit.layout);
+        assert_eq!(mem_inputs, ace_vars.to_memory_vec(&circuit.layout));
+        assert_eq!(circuit.eval(root_node, &mem_inputs), QuadFelt::ZERO);
+    }
+}
+
+#[test]
+fn test_ace_vars_from_frame_checks_counts() {
+    let airs = load_air_files().expect("unable to read airs");
+    let (air, circuit, _) = generate_circuit(&airs[0]);
+    let ace_vars = AceVars::random(&air, 10);
+    let [[main_curr, aux_curr, quotient_curr], [main_next, aux_next, quotient_next]] =
+        ace_vars.segments.clone();
+    let stark_vars: [QuadFelt; StarkVar::num_vars()] = ace_vars.stark.to_vec().try_into().unwrap();
+
+    let mut short_main_next = main_next.clone();
+    short_main_next.pop();
+    let err = AceVars::from_frame(
+        &circuit.layout,
+        ace_vars.public.clone(),
+        ace_vars.rand.clone(),
+        main_curr.clone(),
+        aux_curr.clone(),
+        short_main_next,
+        aux_next.clone(),
+        [quotient_curr.clone(), quotient_next.clone()],
+        stark_vars,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "expected {} values for the main trace in the next row, but got {}",
+            main_next.len(),
+            main_next.len() - 1
+        )
+    );
+
+    let err = AceVars::from_frame(
+        &circuit.layout,
+        vec![],
+        ace_vars.rand.clone(),
+        main_curr,
+        aux_curr,
+        main_next,
+        aux_next,
+        [quotient_curr, quotient_next],
+        stark_vars,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("expected {} public inputs, but got 0", ace_vars.public.len())
+    );
+}
+
+/// Pins the default encoding of a fixture, which must match the layout read by the ACE chiplet.
+#[test]
+fn test_default_encoding_regression() {
+    let ace_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let source = std::fs::read_to_string(format!("{ace_dir}/src/tests/airs/SimpleBoundary.air"))
+        .expect("unable to read air");
+    let (_, circuit, _) = generate_circuit(&source);
+
+    let encoded = circuit.to_ace();
+    assert_eq!(encoded.options(), EncodingOptions::default());
+    assert_eq!(encoded.instructions().len(), 56);
+    assert_eq!(&encoded.instructions()[..4], [0, 0, 1, 0].map(Felt::new).as_slice());
+    let expected = RpoDigest::new(
+        [
+            2488911730404263570,
+            15706029287990249412,
+            582903399883935442,
+            16788665191450082391,
+        ]
+        .map(Felt::new),
+    );
+    assert_eq!(encoded.circuit_hash(), expected);
+}
+
+/// Checks that the constants are read back from every encoding, and that all encodings evaluate
+/// to the same value.
+#[test]
+fn test_encoding_options_round_trip() {
+    let log_trace_len = 16u32;
+    let all_options = [ConstantLayout::ExtensionPadded, ConstantLayout::Packed]
+        .into_iter()
+        .flat_map(|constant_layout| {
+            [Endianness::Little, Endianness::Big]
+                .map(|endianness| EncodingOptions { constant_layout, endianness })
+        });
+
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, circuit, _) = generate_circuit(&air_string);
+        let valid_inputs =
+            AceVars::random_with_valid_quotient(&air, log_trace_len).to_memory_vec(&circuit.layout);
+        let random_inputs = AceVars::random(&air, log_trace_len).to_memory_vec(&circuit.layout);
+
+        let default = circuit.to_ace();
+        let expected = default.eval(&random_inputs);
+        assert_ne!(expected, QuadFelt::ZERO);
+        for options in all_options.clone() {
+            let encoded = circuit.to_ace_with_options(options);
+            assert_eq!(encoded.options(), options);
+            assert_eq!(encoded.constants(), circuit.constants, "{}: {options:?}", air.name);
+            assert_eq!(encoded.eval(&valid_inputs), QuadFelt::ZERO, "{}: {options:?}", air.name);
+            assert_eq!(encoded.eval(&random_inputs), expected, "{}: {options:?}", air.name);
+        }
+    }
+}
+
+/// Checks that the layout of a main trace wider than 255 columns is not truncated, by evaluating
+/// a circuit reading its last column.
+#[test]
+fn test_wide_main_trace() {
+    let source = "
+def WideTrace
+
+trace_columns {
+    main: [a, b[299]],
+}
+
+public_inputs {
+    stack_inputs: [2],
+}
+
+boundary_constraints {
+    enf b[298].first = 0;
+}
+
+integrity_constraints {
+    enf a' = a + b[298];
+}";
+    let (air, circuit, root) = generate_circuit(source);
+    assert_eq!(air.trace_segment_widths[0], 300);
+    for row in circuit.layout.trace_segments.iter() {
+        assert_eq!(row[0].width, 300);
+    }
+
+    let ace_vars = AceVars::random_with_valid_quotient(&air, 10);
+    let mem_inputs = ace_vars.to_memory_vec(&circuit.layout);
+    assert_eq!(circuit.eval(root, &mem_inputs), QuadFelt::ZERO);
+}
+
+const RANDOM_WITHOUT_AUX: &str = "
+def RandomWithoutAux
+
+trace_columns {
+    main: [a, b],
+}
+
+public_inputs {
+    stack_inputs: [2],
+}
+
+boundary_constraints {
+    enf a.first = stack_inputs[0];
+}
+
+integrity_constraints {
+    enf a' = a + b;
+}";
+
+/// Checks that an [Air] drawing random values without any aux column is laid out as if it had no
+/// aux segment, whether or not an empty one is materialized, and that its circuit evaluates to zero.
+#[test]
+fn test_random_values_without_aux_segment() {
+    let (mut air, _, _) = generate_circuit(RANDOM_WITHOUT_AUX);
+    air.num_random_values = 2;
+    assert_eq!(air.trace_segment_widths, [2]);
+
+    let layout = AirLayout::new(&air);
+    assert_eq!(layout.random_values.width, 2);
+    for row in layout.trace_segments.iter() {
+        assert_eq!(row[1].width, 0);
+    }
+    air.trace_segment_widths.push(0);
+    assert_eq!(AirLayout::new(&air), layout);
+    air.trace_segment_widths.pop();
+
+    let (root, circuit, _) = build_ace_circuit(&air).expect("codegen failed");
+    assert_eq!(circuit.layout, layout);
+    let ace_vars = AceVars::random_with_valid_quotient(&air, 10);
+    let mem_inputs = ace_vars.to_memory_vec(&circuit.layout);
+    assert_eq!(mem_inputs.len(), layout.num_inputs);
+    assert_eq!(circuit.eval(root, &mem_inputs), QuadFelt::ZERO);
+}
+
+/// Checks that inputs which do not match the layout are rejected, rather than shifting the inputs
+/// which follow them.
+#[test]
+#[should_panic(expected = "expected 0 values for the aux trace in the current row, but got 1")]
+fn test_mismatched_inputs() {
+    let (air, circuit, _) = generate_circuit(RANDOM_WITHOUT_AUX);
+    let mut ace_vars = AceVars::random(&air, 10);
+    ace_vars.segments[0][1].push(QuadFelt::ONE);
+    ace_vars.to_memory_vec(&circuit.layout);
+}
+
+/// Checks that negative constants are stored as their canonical field elements, and that they
+/// produce the same circuit as the equivalent reduced literals.
+#[test]
+fn test_negative_constants() {
+    let source = |values: [&str; 3]| {
+        format!(
+            "
+def NegativeConstants
+
+trace_columns {{
+    main: [a, b],
+}}
+
+public_inputs {{
+    stack_inputs: [2],
+}}
+
+boundary_constraints {{
+    enf a.first = {};
+    enf b.first = {};
+}}
+
+integrity_constraints {{
+    enf a' = a * {};
+}}",
+            values[0], values[1], values[2]
+        )
+    };
+    let (_, circuit, _) = generate_circuit(&source(["-1", "-0", "(-2)^3"]));
+    let (_, reduced_circuit, _) =
+        generate_circuit(&source(["18446744069414584320", "0", "18446744069414584313"]));
+    assert_eq!(circuit, reduced_circuit);
+    assert!(circuit.constants.contains(&-Felt::ONE));
+    assert!(circuit.constants.contains(&-Felt::new(8)));
+}
+
+/// Checks that the inputs of each circuit are laid out in the order of the witness layout of its
+/// Air, by reading the value of each witness input at its position in the memory of the circuit.
+#[test]
+fn test_witness_layout_matches_inputs() {
+    let log_trace_len = 16u32;
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, circuit, _) = generate_circuit(&air_string);
+        let layout = &circuit.layout;
+        let ace_vars = AceVars::random(&air, log_trace_len);
+        let mem_inputs = ace_vars.to_memory_vec(layout);
+
+        let mut last_index = None;
+        for input in air.witness_layout().inputs() {
+            let (node, value) = match input {
+                WitnessInput::PublicInput(access) => {
+                    let position =
+                        air.public_inputs.keys().position(|name| *name == access.name).unwrap();
+                    (layout.public_input_node(&access), ace_vars.public[position][access.index])
+                },
+                WitnessInput::RandomValue(index) => {
+                    (layout.random_value_node(index), ace_vars.rand[index])
+                },
+                WitnessInput::Trace(access) => (
+                    layout.trace_access_node(&access),
+                    ace_vars.segments[access.row_offset][access.segment][access.column],
+                ),
+            };
+            let Some(Node::Input(index)) = node else {
+                panic!("{input:?} is not an input of the circuit");
+            };
+            assert!(last_index < Some(index), "{input:?} is out of order");
+            assert_eq!(mem_inputs[index], value);
+            last_index = Some(index);
+        }
+    }
+}
+
+/// Checks that the circuit of a program does not depend on the order in which its names were
+/// interned, by compiling it once with fresh names, and once with names interned before in reverse
+/// order.
+#[test]
+fn test_circuit_does_not_depend_on_interning_order() {
+    let source = "
+    def {p}Interning
+
+    trace_columns {
+        main: [{p}s, {p}a, {p}b],
+    }
+
+    buses {
+        multiset {p}zeta_bus,
+        logup {p}alpha_bus,
+    }
+
+    public_inputs {
+        {p}zeta_inputs: [2],
+        {p}alpha_inputs: [4],
+    }
+
+    periodic_columns {
+        {p}zeta_k: [1, 0],
+        {p}alpha_k: [1, 1, 0, 0],
+    }
+
+    boundary_constraints {
+        enf {p}a.first = {p}zeta_inputs[0];
+        enf {p}b.first = {p}alpha_inputs[3];
+        enf {p}zeta_bus.first = null;
+        enf {p}zeta_bus.last = null;
+        enf {p}alpha_bus.first = null;
+        enf {p}alpha_bus.last = null;
+    }
+
+    integrity_constraints {
+        enf {p}s^2 = {p}s;
+        enf {p}b' = {p}b * {p}zeta_k + {p}alpha_k;
+        {p}zeta_bus.insert({p}a) when {p}s;
+        {p}zeta_bus.remove({p}a) when {p}s;
+        {p}alpha_bus.insert({p}b) with {p}a;
+        {p}alpha_bus.remove({p}b) with {p}a;
+    }";
+
+    let (_, cold, cold_root) = generate_circuit(&source.replace("{p}", "ace_cold_"));
+    let decoys = [
+        "s",
+        "a",
+        "b",
+        "zeta_bus",
+        "alpha_bus",
+        "zeta_inputs",
+        "alpha_inputs",
+        "zeta_k",
+        "alpha_k",
+        "Interning",
+    ];
+    for decoy in decoys.iter().rev() {
+        air_parser::Symbol::intern(format!("ace_warm_{decoy}"));
+    }
+    let (_, warm, warm_root) = generate_circuit(&source.replace("{p}", "ace_warm_"));
+
+    assert_eq!(warm_root, cold_root);
+    assert_eq!(warm.constants, cold.constants);
+    assert_eq!(warm.operations, cold.operations);
+    assert!(warm.layout.public_inputs.values().eq(cold.layout.public_inputs.values()));
+    assert_eq!(warm.to_ace().instructions(), cold.to_ace().instructions());
+    assert_eq!(warm.to_ace().circuit_hash(), cold.to_ace().circuit_hash());
+}
@@ -0,0 +1,458 @@
+use std::{
+    collections::BTreeMap,
+    panic::{AssertUnwindSafe, catch_unwind},
+};
+
+use air_ir::{Identifier, Symbol};
+use miden_core::Felt;
+use miden_diagnostics::SourceSpan;
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+use winter_math::{FieldElement, StarkField};
+
+use crate::{
+    ConstantLayout, EncodingOptions, Endianness, QuadFelt, StarkVar, StarkVarLayout,
+    builder::CircuitBuilder,
+    circuit::{ArithmeticOp, Circuit, Node, OperationNode},
+    layout::{InputRegion, Layout},
+};
+
+/// Number of random circuits against which each property is checked.
+const NUM_CASES: u64 = 256;
+
+/// A randomly generated circuit, described independently of the [`CircuitBuilder`] so that it can
+/// be shrunk when a property fails.
+#[derive(Clone, Debug)]
+struct CircuitCase {
+    layout: Layout,
+    inputs: Vec<QuadFelt>,
+    /// Expressions may only reference previous ones, and the last one is the root of the circuit.
+    exprs: Vec<Expr>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Expr {
+    Input(usize),
+    Constant(u64),
+    Op(ArithmeticOp, usize, usize),
+}
+
+impl CircuitCase {
+    /// Samples a circuit whose root is an operation, mixing in the constants `0` and `1` and
+    /// operations with identical operands to exercise the simplifications of the builder.
+    fn random(rng: &mut StdRng) -> Self {
+        let layout = random_layout(rng);
+        let inputs = (0..layout.num_inputs).map(|_| random_quad(rng)).collect();
+
+        let mut exprs = Vec::new();
+        for _ in 0..rng.random_range(1..=8) {
+            let leaf = if rng.random_bool(0.5) {
+                Expr::Input(rng.random_range(0..layout.num_inputs))
+            } else {
+                let c = match rng.random_range(0..4) {
+                    0 => 0,
+                    1 => 1,
+                    2 => Felt::MODULUS - 1,
+                    _ => rng.random_range(0..Felt::MODULUS),
+                };
+                Expr::Constant(c)
+            };
+            exprs.push(leaf);
+        }
+        for _ in 0..rng.random_range(1..=48) {
+            let op =
+                [ArithmeticOp::Sub, ArithmeticOp::Mul, ArithmeticOp::Add][rng.random_range(0..3)];
+            let l = rng.random_range(0..exprs.len());
+            let r = if rng.random_bool(0.125) {
+                l
+            } else {
+                rng.random_range(0..exprs.len())
+            };
+            exprs.push(Expr::Op(op, l, r));
+        }
+
+        Self { layout, inputs, exprs }.pruned()
+    }
+
+    /// Evaluates the root of the circuit directly from its description.
+    fn eval(&self) -> QuadFelt {
+        let mut evals: Vec<QuadFelt> = Vec::with_capacity(self.exprs.len());
+        for expr in &self.exprs {
+            let eval = match *expr {
+                Expr::Input(idx) => self.inputs[idx],
+                Expr::Constant(c) => QuadFelt::from(Felt::new(c)),
+                Expr::Op(op, l, r) => apply(op, evals[l], evals[r]),
+            };
+            evals.push(eval);
+        }
+        *evals.last().unwrap()
+    }
+
+    /// Builds the circuit with `builder`, returning it along with the node of its root.
+    fn build(&self, mut builder: CircuitBuilder) -> (Circuit, Node) {
+        let mut nodes = Vec::with_capacity(self.exprs.len());
+        for expr in &self.exprs {
+            let node = match *expr {
+                Expr::Input(idx) => Node::Input(idx),
+                Expr::Constant(c) => builder.constant(c),
+                Expr::Op(ArithmeticOp::Sub, l, r) => builder.sub(nodes[l], nodes[r]),
+                Expr::Op(ArithmeticOp::Mul, l, r) => builder.mul(nodes[l], nodes[r]),
+                Expr::Op(ArithmeticOp::Add, l, r) => builder.add(nodes[l], nodes[r]),
+            };
+            nodes.push(node);
+        }
+        (builder.into_ace_circuit(), *nodes.last().unwrap())
+    }
+
+    /// Builds the circuit with all simplifications of the [`CircuitBuilder`] enabled.
+    fn build_simplified(&self) -> (Circuit, Node) {
+        self.build(CircuitBuilder::with_layout(self.layout.clone()))
+    }
+
+    /// Builds the circuit with one operation node per operation of the description.
+    fn build_unsimplified(&self) -> (Circuit, Node) {
+        self.build(CircuitBuilder::with_layout(self.layout.clone()).without_simplifications())
+    }
+
+    /// Removes the expressions which do not contribute to the root.
+    fn pruned(mut self) -> Self {
+        let mut used = vec![false; self.exprs.len()];
+        *used.last_mut().unwrap() = true;
+        for idx in (0..self.exprs.len()).rev() {
+            if let (true, Expr::Op(_, l, r)) = (used[idx], self.exprs[idx]) {
+                used[l] = true;
+                used[r] = true;
+            }
+        }
+
+        let mut new_index = Vec::with_capacity(self.exprs.len());
+        let mut exprs = Vec::new();
+        for (expr, used) in self.exprs.into_iter().zip(used) {
+            new_index.push(exprs.len());
+            if used {
+                exprs.push(match expr {
+                    Expr::Op(op, l, r) => Expr::Op(op, new_index[l], new_index[r]),
+                    leaf => leaf,
+                });
+            }
+        }
+        self.exprs = exprs;
+        self
+    }
+
+    /// Returns smaller variants of this circuit, whose root is still an operation.
+    fn shrink_candidates(&self) -> Vec<Self> {
+        let mut candidates = Vec::new();
+        let with_exprs = |exprs: Vec<Expr>| Self { exprs, ..self.clone() }.pruned();
+        let root = self.exprs.len() - 1;
+
+        for (idx, expr) in self.exprs.iter().enumerate() {
+            match *expr {
+                Expr::Op(op, l, r) => {
+                    if idx != root {
+                        // Make a sub-circuit the root.
+                        candidates.push(with_exprs(self.exprs[..=idx].to_vec()));
+                        // Replace the operation by one of its operands.
+                        for operand in [l, r] {
+                            let exprs = self
+                                .exprs
+                                .iter()
+                                .map(|expr| match *expr {
+                                    Expr::Op(op, l, r) => Expr::Op(
+                                        op,
+                                        if l == idx { operand } else { l },
+                                        if r == idx { operand } else { r },
+                                    ),
+                                    leaf => leaf,
+                                })
+                                .collect();
+                            candidates.push(with_exprs(exprs));
+                        }
+                    }
+                    // Use a simpler operation.
+                    if op != ArithmeticOp::Sub {
+                        let mut exprs = self.exprs.clone();
+                        exprs[idx] = Expr::Op(ArithmeticOp::Sub, l, r);
+                        candidates.push(with_exprs(exprs));
+                    }
+                },
+                Expr::Constant(c) => {
+                    for simpler in [0, 1].into_iter().filter(|&simpler| simpler < c) {
+                        let mut exprs = self.exprs.clone();
+                        exprs[idx] = Expr::Constant(simpler);
+                        candidates.push(with_exprs(exprs));
+                    }
+                },
+                Expr::Input(input) => {
+                    if input != 0 {
+                        let mut exprs = self.exprs.clone();
+                        exprs[idx] = Expr::Input(0);
+                        candidates.push(with_exprs(exprs));
+                    }
+                },
+            }
+        }
+        candidates
+    }
+}
+
+/// Samples a layout made of contiguous regions of random widths, covering all inputs.
+fn random_layout(rng: &mut StdRng) -> Layout {
+    let mut num_inputs = 0;
+    let mut next_region = |width: usize| {
+        let region = InputRegion { offset: num_inputs, width };
+        num_inputs += width;
+        region
+    };
+
+    let public_inputs = (0..rng.random_range(0..=2))
+        .map(|idx| {
+            let name = Identifier::new(SourceSpan::UNKNOWN, Symbol::intern(format!("pi{idx}")));
+            (name, next_region(rng.random_range(1..=4)))
+        })
+        .collect();
+    let random_values = next_region(rng.random_range(0..=4));
+    let trace_segments = [0, 1].map(|_| [0, 1, 2].map(|_| next_region(rng.random_range(0..=4))));
+    let stark_vars = next_region(StarkVar::num_vars());
+
+    let mut order = StarkVarLayout::default().order().to_owned();
+    order.shuffle(rng);
+
+    Layout {
+        public_inputs,
+        random_values,
+        trace_segments,
+        stark_vars,
+        stark_var_layout: StarkVarLayout::new(order).unwrap(),
+        num_inputs,
+    }
+}
+
+/// Samples an extension field element from `rng`, so that failing cases can be reproduced.
+fn random_quad(rng: &mut StdRng) -> QuadFelt {
+    let [a, b] = [0; 2].map(|_| Felt::new(rng.random_range(0..Felt::MODULUS)));
+    QuadFelt::new(a, b)
+}
+
+fn apply(op: ArithmeticOp, l: QuadFelt, r: QuadFelt) -> QuadFelt {
+    match op {
+        ArithmeticOp::Sub => l - r,
+        ArithmeticOp::Mul => l * r,
+        ArithmeticOp::Add => l + r,
+    }
+}
+
+/// Reference evaluator, recursively evaluating the operands of `node` in `circuit`.
+fn eval_recursive(
+    circuit: &Circuit,
+    node: Node,
+    inputs: &[QuadFelt],
+    cache: &mut BTreeMap<Node, QuadFelt>,
+) -> QuadFelt {
+    if let Some(eval) = cache.get(&node) {
+        return *eval;
+    }
+    let eval = match node {
+        Node::Input(idx) => inputs[idx],
+        Node::Constant(idx) => QuadFelt::from(circuit.constants[idx]),
+        Node::Operation(idx) => {
+            let OperationNode { op, node_l, node_r } = circuit.operations[idx];
+            let eval_l = eval_recursive(circuit, node_l, inputs, cache);
+            let eval_r = eval_recursive(circuit, node_r, inputs, cache);
+            apply(op, eval_l, eval_r)
+        },
+    };
+    cache.insert(node, eval);
+    eval
+}
+
+/// Runs `property` against a property, returning a description of the failure if it returns an
+/// error or panics.
+fn failure(
+    property: &impl Fn(&CircuitCase) -> Result<(), String>,
+    case: &CircuitCase,
+) -> Option<String> {
+    match catch_unwind(AssertUnwindSafe(|| property(case))) {
+        Ok(result) => result.err(),
+        Err(panic) => Some(
+            panic
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| panic.downcast_ref::<&str>().map(|msg| msg.to_string()))
+                .unwrap_or_else(|| "panicked".to_string()),
+        ),
+    }
+}
+
+/// Checks `property` against [`NUM_CASES`] random circuits. A failing circuit is greedily shrunk
+/// to a minimal one which still fails, and reported along with the seed it was generated from.
+fn check_property(property: impl Fn(&CircuitCase) -> Result<(), String>) {
+    for seed in 0..NUM_CASES {
+        let mut case = CircuitCase::random(&mut StdRng::seed_from_u64(seed));
+        let Some(mut error) = failure(&property, &case) else {
+            continue;
+        };
+
+        'shrink: loop {
+            for candidate in case.shrink_candidates() {
+                if let Some(candidate_error) = failure(&property, &candidate) {
+                    (case, error) = (candidate, candidate_error);
+                    continue 'shrink;
+                }
+            }
+            break;
+        }
+        panic!(
+            "property failed for seed {seed}: {error}\nminimal failing circuit over {} inputs: {:?}",
+            case.layout.num_inputs, case.exprs
+        );
+    }
+}
+
+/// Checks that [`Circuit::eval`] agrees with a reference recursive evaluator, and with a direct
+/// evaluation of the generated circuit.
+#[test]
+fn prop_eval_matches_reference() {
+    check_property(|case| {
+        let expected = case.eval();
+        for (circuit, root) in [case.build_simplified(), case.build_unsimplified()] {
+            let eval = circuit.eval(root, &case.inputs);
+            let reference = eval_recursive(&circuit, root, &case.inputs, &mut BTreeMap::new());
+            if eval != reference || eval != expected {
+                return Err(format!("eval {eval}, reference {reference}, expected {expected}"));
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Checks that the simplifications performed by the [`CircuitBuilder`] never change the value of
+/// the root, nor increase the number of operations.
+#[test]
+fn prop_simplifications_preserve_eval() {
+    check_property(|case| {
+        let (simplified, simplified_root) = case.build_simplified();
+        let (unsimplified, unsimplified_root) = case.build_unsimplified();
+        let simplified_eval = simplified.eval(simplified_root, &case.inputs);
+        let unsimplified_eval = unsimplified.eval(unsimplified_root, &case.inputs);
+        if simplified_eval != unsimplified_eval {
+            return Err(format!("simplified {simplified_eval}, unsimplified {unsimplified_eval}"));
+        }
+        if simplified.operations.len() > unsimplified.operations.len() {
+            return Err("simplifications added operations".to_string());
+        }
+        Ok(())
+    });
+}
+
+/// Checks that decoding an encoded circuit yields the original circuit followed by the padding
+/// instructions, for every [`EncodingOptions`].
+#[test]
+fn prop_encoding_round_trip() {
+    let all_options = [ConstantLayout::ExtensionPadded, ConstantLayout::Packed]
+        .into_iter()
+        .flat_map(|constant_layout| {
+            [Endianness::Little, Endianness::Big]
+                .map(|endianness| EncodingOptions { constant_layout, endianness })
+        });
+
+    check_property(|case| {
+        for (circuit, _) in [case.build_simplified(), case.build_unsimplified()] {
+            // The simplified root may be a leaf, in which case there is nothing to encode.
+            let Some(last) = circuit.operations.len().checked_sub(1) else {
+                continue;
+            };
+            let last_eval = circuit.eval(Node::Operation(last), &case.inputs);
+
+            for options in all_options.clone() {
+                let encoded = circuit.to_ace_with_options(options);
+                let decoded = encoded.decode(circuit.layout.clone());
+                let (operations, padding) = decoded.operations.split_at(circuit.operations.len());
+                if decoded.constants != circuit.constants || operations != circuit.operations {
+                    return Err(format!("{options:?}: decoded circuit differs"));
+                }
+                if decoded.operations.len() % 4 != 0 {
+                    return Err(format!("{options:?}: operations are not padded"));
+                }
+                for (idx, op) in padding.iter().enumerate() {
+                    let node = Node::Operation(last + idx);
+                    let square = OperationNode {
+                        op: ArithmeticOp::Mul,
+                        node_l: node,
+                        node_r: node,
+                    };
+                    if *op != square {
+                        return Err(format!("{options:?}: invalid padding {op:?}"));
+                    }
+                }
+
+                let expected = last_eval.exp(1 << padding.len());
+                let encoded_eval = encoded.eval(&case.inputs);
+                let decoded_eval =
+                    decoded.eval(Node::Operation(decoded.operations.len() - 1), &case.inputs);
+                if encoded_eval != expected || decoded_eval != expected {
+                    return Err(format!(
+                        "{options:?}: encoded {encoded_eval}, decoded {decoded_eval}, expected {expected}"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Checks that [`Circuit::to_dot`] succeeds and produces a well-formed graph, in which every node
+/// is labelled exactly once and edges only connect labelled nodes.
+#[test]
+fn prop_dot_is_well_formed() {
+    check_property(|case| {
+        for (circuit, _) in [case.build_simplified(), case.build_unsimplified()] {
+            let dot = circuit.to_dot().map_err(|err| err.to_string())?;
+            check_dot(&circuit, &dot)?;
+        }
+        Ok(())
+    });
+}
+
+/// Minimal syntax check of the DOT output of `circuit`, restricted to the statements it emits.
+fn check_dot(circuit: &Circuit, dot: &str) -> Result<(), String> {
+    let body = dot
+        .strip_prefix("digraph G {\n")
+        .and_then(|body| body.strip_suffix("}\n"))
+        .ok_or("missing graph delimiters")?;
+
+    let parse_node = |id: &str| -> Result<Node, String> {
+        let parse = |prefix: &str, len: usize| {
+            let idx = id.strip_prefix(prefix)?.parse::<usize>().ok()?;
+            (idx < len).then_some(idx)
+        };
+        parse("input", circuit.layout.num_inputs)
+            .map(Node::Input)
+            .or_else(|| parse("const", circuit.constants.len()).map(Node::Constant))
+            .or_else(|| parse("op", circuit.operations.len()).map(Node::Operation))
+            .ok_or_else(|| format!("invalid node `{id}`"))
+    };
+
+    let mut labels: BTreeMap<Node, usize> = BTreeMap::new();
+    for line in body.lines() {
+        if let Some((id, label)) = line.split_once(" [label=\"") {
+            match label.strip_suffix("\"]") {
+                Some(label) if !label.contains('"') => {},
+                _ => return Err(format!("invalid label `{line}`")),
+            }
+            *labels.entry(parse_node(id)?).or_default() += 1;
+        } else if let Some((from, to)) = line.split_once(" -> ") {
+            let (from, to) = (parse_node(from)?, parse_node(to)?);
+            if !labels.contains_key(&from) || !labels.contains_key(&to) {
+                return Err(format!("edge `{line}` between unlabelled nodes"));
+            }
+        } else {
+            return Err(format!("invalid statement `{line}`"));
+        }
+    }
+
+    // Every input belongs to a region of the generated layouts.
+    if labels.len() != circuit.num_nodes() || labels.values().any(|&count| count != 1) {
+        return Err(format!("nodes are not labelled exactly once: {labels:?}"));
+    }
+    Ok(())
+}
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use air_ir::{Air, ConstraintDomain, NodeIndex, Operation, Value};
 use miden_core::Felt;
-use winter_math::FieldElement;
+use winter_math::{FieldElement, StarkField};
 
 use crate::{
     QuadFelt,
@@ -22,22 +22,22 @@ pub fn eval_quotient(air: &Air, ace_vars: &AceVars, log_trace_len: u32) -> QuadF
         z,
     } = ace_vars.stark;
 
-    // Evaluate all periodic columns at appropriate z power
+    // Evaluate all periodic columns at appropriate power of z, or of z⋅gᵒ for those read `o` rows
+    // ahead
+    let g = QuadFelt::from(Felt::get_root_of_unity(log_trace_len));
     let periodic: BTreeMap<_, _> = air
         .periodic_columns
-        .iter()
-        .map(|(ident, col)| {
+        .keys()
+        .map(|ident| (*ident, 0))
+        .chain(air.shifted_periodic_columns().into_iter().map(|pc| (pc.name, pc.row_offset)))
+        .map(|(ident, row_offset)| {
+            let col = &air.periodic_columns[&ident];
             let trace_len = 1 << log_trace_len;
             let z_col_pow = trace_len / col.values.len();
-            let z_col = z.exp_vartime(z_col_pow as u64);
-
-            let mut poly: Vec<_> =
-                col.values.iter().copied().map(Felt::new).map(QuadFelt::from).collect();
-            let twiddles = winter_math::fft::get_inv_twiddles::<Felt>(poly.len());
-            winter_math::fft::interpolate_poly(&mut poly, &twiddles);
+            let z_col = (z * g.exp_vartime(row_offset as u64)).exp_vartime(z_col_pow as u64);
 
-            let eval = poly_eval(&poly, z_col);
-            (*ident, QuadFelt::from(eval))
+            let eval = poly_eval(&coeffs(&col.values), z_col);
+            ((ident, row_offset), QuadFelt::from(eval))
         })
         .collect();
 
@@ -61,12 +61,13 @@ pub fn eval_quotient(air: &Air, ace_vars: &AceVars, log_trace_len: u32) -> QuadF
                 Value::TraceAccess(access) => {
                     ace_vars.segments[access.row_offset][access.segment][access.column]
                 },
-                Value::PeriodicColumn(access) => periodic[&access.name],
+                Value::PeriodicColumn(access) => periodic[&(access.name, access.row_offset)],
                 Value::PublicInput(access) => {
                     let idx = public[&access.name];
                     ace_vars.public[idx][access.index]
                 },
-                Value::RandomValue(idx) => ace_vars.rand[idx],
+                // Random values removed by `DeadRandomValues` are only read by dead nodes
+                Value::RandomValue(idx) => ace_vars.rand.get(idx).copied().unwrap_or_default(),
             },
             Operation::Add(l, r) => evals[usize::from(l)] + evals[usize::from(r)],
             Operation::Sub(l, r) => evals[usize::from(l)] - evals[usize::from(r)],
@@ -153,6 +154,15 @@ pub fn eval_quotient(air: &Air, ace_vars: &AceVars, log_trace_len: u32) -> QuadF
     composition / vanishing_all
 }
 
+/// Interpolates the `values` of a periodic column over its cycle, returning the coefficients of
+/// the resulting polynomial
+pub fn coeffs(values: &[u64]) -> Vec<QuadFelt> {
+    let mut poly: Vec<_> = values.iter().copied().map(Felt::new).map(QuadFelt::from).collect();
+    let twiddles = winter_math::fft::get_inv_twiddles::<Felt>(poly.len());
+    winter_math::fft::interpolate_poly(&mut poly, &twiddles);
+    poly
+}
+
 /// Evaluates a polynomial given by `coeffs` at `point`
 pub fn poly_eval(coeffs: &[QuadFelt], point: QuadFelt) -> QuadFelt {
     coeffs
@@ -1,4 +1,4 @@
-use air_ir::Air;
+use air_ir::{Air, EvalContext};
 use rand::Rng;
 use winter_utils::Randomizable;
 
@@ -19,7 +19,11 @@ impl InputRegion {
 impl AceVars {
     /// Samples fully random inputs for the ACE circuit.
     pub fn random(air: &Air, log_trace_len: u32) -> Self {
-        let layout = Layout::new(air);
+        Self::random_with_layout(air, &Layout::new(air), log_trace_len)
+    }
+
+    /// Samples fully random inputs for an ACE circuit whose inputs are described by `layout`.
+    pub fn random_with_layout(air: &Air, layout: &Layout, log_trace_len: u32) -> Self {
         let public = layout.public_inputs.values().map(|pi| pi.random()).collect();
         let segments = layout
             .trace_segments
@@ -32,7 +36,17 @@ impl AceVars {
     /// Samples a random set of inputs to the ACE circuit, correcting the
     /// quotient to ensure the final evaluation of the circuit is 0.
     pub fn random_with_valid_quotient(air: &Air, log_trace_len: u32) -> Self {
-        let mut random_vars = Self::random(air, log_trace_len);
+        Self::random_with_valid_quotient_and_layout(air, &Layout::new(air), log_trace_len)
+    }
+
+    /// Samples a random set of inputs as in [`Self::random_with_valid_quotient`], for an ACE
+    /// circuit whose inputs are described by `layout`.
+    pub fn random_with_valid_quotient_and_layout(
+        air: &Air,
+        layout: &Layout,
+        log_trace_len: u32,
+    ) -> Self {
+        let mut random_vars = Self::random_with_layout(air, layout, log_trace_len);
 
         // The target evaluation of the quotient at z should be r, denoted as q'(z),
         // where q' is the corrected quotient.
@@ -64,6 +78,24 @@ impl StarkInputs {
     }
 }
 
+/// Samples a random [`EvalContext`] for the Air, whose periodic column evaluations are derived
+/// from the random evaluation point.
+pub fn random_eval_context(air: &Air, log_trace_len: u32) -> EvalContext {
+    let layout = Layout::new(air);
+    let [main, aux] = [0, 1]
+        .map(|segment| layout.trace_segments.map(|segment_row| segment_row[segment].random()));
+    EvalContext {
+        log_trace_len,
+        main,
+        aux,
+        public: layout.public_inputs.values().map(|pi| pi.random()).collect(),
+        rand: layout.random_values.random(),
+        periodic: Default::default(),
+        z: rand_quad(),
+    }
+    .with_periodic_evals(air)
+}
+
 /// Generates a random extension field element.
 pub fn rand_quad() -> QuadFelt {
     for _ in 0..1000 {
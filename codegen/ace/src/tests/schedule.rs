@@ -0,0 +1,134 @@
+use air_ir::{ConstraintDomain, ConstraintEvaluator, RootKind};
+use winter_math::FieldElement;
+
+use crate::{
+    AceVars, ConstraintGroup, QuadFelt, build_ace_circuit, build_ace_circuit_with_roots,
+    tests::{generate_circuit, load_air_files},
+};
+
+/// Checks that the roots of the schedule are partitioned in groups in combination order, with
+/// contiguous powers of `α`, and that they match the roots of the Air and of the circuit.
+#[test]
+fn test_schedule_partition() {
+    let airs = load_air_files().expect("unable to read airs");
+    for air_string in airs {
+        let (air, ..) = generate_circuit(&air_string);
+        let (_, _, schedule) = build_ace_circuit(&air).expect("codegen failed");
+        let (_, _, roots) = build_ace_circuit_with_roots(&air).expect("codegen failed");
+
+        // The i-th root is multiplied by αⁱ
+        let alpha_powers: Vec<_> =
+            schedule.iter().map(|constraint| constraint.alpha_power).collect();
+        assert_eq!(alpha_powers, (0..schedule.len()).collect::<Vec<_>>(), "{}", air.name());
+
+        // Groups are contiguous, in combination order
+        let groups: Vec<_> = schedule.iter().map(|constraint| constraint.group).collect();
+        assert!(groups.is_sorted(), "{}: {groups:?}", air.name());
+
+        // Each group holds the roots of the Air, in segment then declaration order
+        let integrity_roots: Vec<_> = [0, 1]
+            .into_iter()
+            .flat_map(|segment| air.integrity_constraints(segment))
+            .map(|constraint| *constraint.node_index())
+            .collect();
+        let [boundary_first_roots, boundary_last_roots] =
+            [ConstraintDomain::FirstRow, ConstraintDomain::LastRow].map(|domain| {
+                [0, 1]
+                    .into_iter()
+                    .flat_map(|segment| air.boundary_constraints(segment))
+                    .filter(|constraint| constraint.domain() == domain)
+                    .map(|constraint| *constraint.node_index())
+                    .collect::<Vec<_>>()
+            });
+        let integrity_nodes = roots.integrity.concat();
+        for (group, air_roots, ace_nodes) in [
+            (ConstraintGroup::Integrity, integrity_roots, integrity_nodes.as_slice()),
+            (
+                ConstraintGroup::BoundaryFirst,
+                boundary_first_roots,
+                roots.get(RootKind::BoundaryFirst),
+            ),
+            (
+                ConstraintGroup::BoundaryLast,
+                boundary_last_roots,
+                roots.get(RootKind::BoundaryLast),
+            ),
+        ] {
+            let scheduled = schedule.group(group);
+            let (scheduled_air_roots, scheduled_ace_nodes): (Vec<_>, Vec<_>) =
+                scheduled.map(|constraint| (constraint.air_root, constraint.ace_node)).unzip();
+            assert_eq!(scheduled_air_roots, air_roots, "{}: {group:?}", air.name());
+            assert_eq!(scheduled_ace_nodes, ace_nodes, "{}: {group:?}", air.name());
+        }
+        assert_eq!(
+            schedule.group(ConstraintGroup::Integrity).count(),
+            air.num_roots(RootKind::Integrity(0)) + air.num_roots(RootKind::Integrity(1)),
+            "{}",
+            air.name()
+        );
+    }
+}
+
+/// Checks that reordering the declaration of constraints reorders the schedule, and that the
+/// circuit combines the roots in the new order.
+#[test]
+fn test_schedule_follows_declaration_order() {
+    let source = |boundary: [&str; 2], integrity: [&str; 2]| {
+        format!(
+            "
+    def test
+    trace_columns {{
+        main: [a, b],
+    }}
+    public_inputs {{
+        stack_inputs: [2],
+    }}
+    boundary_constraints {{
+        {}
+        {}
+        enf a.last = 2;
+    }}
+    integrity_constraints {{
+        {}
+        {}
+    }}",
+            boundary[0], boundary[1], integrity[0], integrity[1]
+        )
+    };
+    let boundary = ["enf a.first = 0;", "enf b.first = 1;"];
+    let integrity = ["enf a' = a + b;", "enf b' = a * b;"];
+    let reordered_boundary = [boundary[1], boundary[0]];
+    let reordered_integrity = [integrity[1], integrity[0]];
+
+    let (air, circuit, root) = generate_circuit(&source(boundary, integrity));
+    let (reordered_air, reordered_circuit, reordered_root) =
+        generate_circuit(&source(reordered_boundary, reordered_integrity));
+    let (_, _, schedule) = build_ace_circuit(&air).expect("codegen failed");
+    let (_, _, reordered_schedule) = build_ace_circuit(&reordered_air).expect("codegen failed");
+
+    // Both Airs have the same inputs, so each root can be identified by its evaluation
+    let log_trace_len = 10;
+    let inputs = AceVars::random(&air, log_trace_len).to_memory_vec(&circuit.layout);
+    let evals: Vec<_> = schedule
+        .iter()
+        .map(|constraint| circuit.eval(constraint.ace_node, &inputs))
+        .collect();
+    let reordered_evals: Vec<_> = reordered_schedule
+        .iter()
+        .map(|constraint| reordered_circuit.eval(constraint.ace_node, &inputs))
+        .collect();
+
+    // Integrity roots come first, then the 2 boundary roots on the first row, then the last one
+    assert_eq!(reordered_evals, [evals[1], evals[0], evals[3], evals[2], evals[4]]);
+    let groups: Vec<_> = reordered_schedule.iter().map(|constraint| constraint.group).collect();
+    assert_eq!(groups, schedule.iter().map(|constraint| constraint.group).collect::<Vec<_>>());
+
+    // Each circuit combines the roots in the order of its own schedule
+    for (air, circuit, root) in
+        [(&air, &circuit, root), (&reordered_air, &reordered_circuit, reordered_root)]
+    {
+        let inputs =
+            AceVars::random_with_valid_quotient(air, log_trace_len).to_memory_vec(&circuit.layout);
+        assert_eq!(circuit.eval(root, &inputs), QuadFelt::ZERO);
+    }
+}
@@ -0,0 +1,55 @@
+use air_ir::{Air, Provenance};
+
+mod spec;
+
+// GENERATE MARKDOWN SPECIFICATION
+// ================================================================================================
+
+/// CodeGenerator is used to generate a Markdown specification of the constraints of the [Air] used
+/// to build the CodeGenerator, for auditing them without reading the code of another backend.
+///
+/// The output is deterministic, and lists the constraints in the order in which they are combined
+/// by the ACE backend.
+#[derive(Default)]
+pub struct CodeGenerator {
+    provenance: Option<Provenance>,
+}
+impl CodeGenerator {
+    /// Creates a new [CodeGenerator].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emits the given [Provenance] as a comment header at the top of the generated document.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+}
+impl air_ir::CodeGenerator for CodeGenerator {
+    type Output = String;
+
+    fn generate(&self, ir: &Air) -> anyhow::Result<Self::Output> {
+        let mut doc = String::new();
+
+        if let Some(provenance) = &self.provenance {
+            doc.push_str("<!--\n");
+            for line in provenance.header_lines() {
+                doc.push_str(&format!("{line}\n"));
+            }
+            doc.push_str("-->\n\n");
+        }
+
+        spec::add_title(&mut doc, ir);
+        spec::add_trace_layout(&mut doc, ir)?;
+        spec::add_public_inputs(&mut doc, ir);
+        spec::add_periodic_columns(&mut doc, ir);
+        spec::add_buses(&mut doc, ir);
+        spec::add_constraints(&mut doc, ir)?;
+
+        // Each section ends with a blank line, of which only the newline is kept at the end
+        doc.truncate(doc.trim_end().len());
+        doc.push('\n');
+        Ok(doc)
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt::Write;
+
+use air_ir::{
+    AUX_SEGMENT, Air, BusBoundary, BusOpKind, BusType, DEFAULT_SEGMENT, DivisorSpec,
+    IntegrityConstraintDegree, PublicInput, RootKind, TraceSegmentId, VanishingFactor,
+};
+
+// SECTIONS
+// ================================================================================================
+
+/// Adds the title of the specification.
+pub(super) fn add_title(doc: &mut String, ir: &Air) {
+    writeln!(doc, "# `{}` constraint specification\n", ir.name()).unwrap();
+}
+
+/// Adds a table of the segments of the trace, followed by a table of the columns which are known
+/// by name, i.e. the bus columns of the aux segment and the virtual columns of the main segment.
+pub(super) fn add_trace_layout(doc: &mut String, ir: &Air) -> anyhow::Result<()> {
+    doc.push_str("## Trace layout\n\n");
+    doc.push_str("| Segment | Name | Width |\n| --- | --- | --- |\n");
+    for (segment, width) in ir.trace_segment_widths.iter().enumerate() {
+        writeln!(doc, "| {segment} | `{}` | {width} |", ir.trace_segment_name(segment)).unwrap();
+    }
+    doc.push('\n');
+
+    let mut columns = vec![];
+    for (column, name, definition) in ir.virtual_columns.iter() {
+        let definition = ir.virtual_columns.graph().to_infix(&definition)?;
+        columns.push((
+            DEFAULT_SEGMENT,
+            column,
+            name.to_string(),
+            format!("virtual, defined as `{definition}`"),
+        ));
+    }
+    for (bus, column) in ir.bus_column_layout() {
+        let bus_type = ir.buses.get(&bus).map(|bus| bus_type(bus.bus_type)).unwrap_or("bus");
+        columns.push((AUX_SEGMENT, column, bus.to_string(), format!("{bus_type} bus")));
+    }
+    if !columns.is_empty() {
+        columns.sort();
+        doc.push_str("| Column | Name | Description |\n| --- | --- | --- |\n");
+        for (segment, column, name, description) in columns {
+            let column = column_label(segment, column);
+            writeln!(doc, "| `{column}` | `{name}` | {description} |").unwrap();
+        }
+        doc.push('\n');
+    }
+
+    if ir.uses_random_values() {
+        writeln!(
+            doc,
+            "The aux trace is built from {} random values, `R[0]` to `R[{}]`.\n",
+            ir.num_random_values,
+            ir.num_random_values - 1
+        )
+        .unwrap();
+    }
+    Ok(())
+}
+
+/// Adds a table of the public inputs, in the canonical order of [Air::public_inputs].
+pub(super) fn add_public_inputs(doc: &mut String, ir: &Air) {
+    doc.push_str("## Public inputs\n\n");
+    if ir.public_inputs.is_empty() {
+        doc.push_str("None.\n\n");
+        return;
+    }
+    doc.push_str("| Name | Kind | Size |\n| --- | --- | --- |\n");
+    for input in ir.public_inputs() {
+        let (kind, size) = match input {
+            PublicInput::Vector { size, .. } => ("vector", size.to_string()),
+            PublicInput::Table { size, .. } => ("table", format!("{size} per row")),
+        };
+        writeln!(doc, "| `{}` | {kind} | {size} |", input.name()).unwrap();
+    }
+    doc.push('\n');
+}
+
+/// Adds a table of the periodic columns, with their cycle length and values.
+pub(super) fn add_periodic_columns(doc: &mut String, ir: &Air) {
+    doc.push_str("## Periodic columns\n\n");
+    if ir.periodic_columns.is_empty() {
+        doc.push_str("None.\n\n");
+        return;
+    }
+    doc.push_str("| Name | Cycle | Values |\n| --- | --- | --- |\n");
+    for (name, column) in ir.periodic_columns.iter() {
+        let values: Vec<_> = column.values.iter().map(u64::to_string).collect();
+        writeln!(doc, "| `{name}` | {} | {} |", column.period(), values.join(", ")).unwrap();
+    }
+    doc.push('\n');
+}
+
+/// Adds a section for each bus, with its type, column, boundaries and operations.
+pub(super) fn add_buses(doc: &mut String, ir: &Air) {
+    doc.push_str("## Buses\n\n");
+    if ir.buses.is_empty() {
+        doc.push_str("None.\n\n");
+        return;
+    }
+    let bus_columns = ir.bus_column_layout();
+    for (name, bus) in ir.buses.iter() {
+        writeln!(doc, "### Bus `{name}`\n").unwrap();
+        writeln!(doc, "- Type: {}", bus_type(bus.bus_type)).unwrap();
+        if let Some(column) = bus_columns.get(name) {
+            writeln!(doc, "- Column: `{}`", column_label(AUX_SEGMENT, *column)).unwrap();
+        }
+        if bus.is_extern {
+            doc.push_str("- Declared `extern`\n");
+        }
+        writeln!(doc, "- First row: {}", bus_boundary(&bus.first)).unwrap();
+        writeln!(doc, "- Last row: {}", bus_boundary(&bus.last)).unwrap();
+        if let Some(range) = &bus.multiplicity_range {
+            let bits: Vec<_> = range
+                .bits
+                .iter()
+                .map(|column| format!("`{}`", column_label(DEFAULT_SEGMENT, *column)))
+                .collect();
+            writeln!(
+                doc,
+                "- The multiplicity of operation {} is decomposed into the bits {}",
+                range.bus_op,
+                bits.join(", ")
+            )
+            .unwrap();
+        }
+        doc.push('\n');
+
+        if bus.bus_ops.is_empty() {
+            continue;
+        }
+        doc.push_str("| # | Operation | Tuple size |\n| --- | --- | --- |\n");
+        for (i, op) in bus.bus_ops.iter().enumerate() {
+            let kind = match op.op_kind {
+                BusOpKind::Insert => "insert",
+                BusOpKind::Remove => "remove",
+            };
+            writeln!(doc, "| {i} | {kind} | {} |", op.columns.len()).unwrap();
+        }
+        doc.push('\n');
+    }
+}
+
+/// Adds a table of the constraints of each group, in the order in which they are combined by the
+/// ACE backend, i.e. the integrity constraints of each segment, then the boundary constraints
+/// over the first row and over the last row. The doc comments of the constraints, if any, are
+/// added as their description.
+pub(super) fn add_constraints(doc: &mut String, ir: &Air) -> anyhow::Result<()> {
+    doc.push_str("## Constraints\n\n");
+    let mut kinds: Vec<_> = (0..ir.trace_segment_widths.len()).map(RootKind::Integrity).collect();
+    kinds.extend([RootKind::BoundaryFirst, RootKind::BoundaryLast]);
+
+    let graph = ir.constraint_graph();
+    for kind in kinds {
+        writeln!(doc, "### {}\n", group_title(ir, kind)).unwrap();
+        let roots = kind.roots(ir);
+        if roots.is_empty() {
+            doc.push_str("None.\n\n");
+            continue;
+        }
+        // The doc comments of the constraints are only described if any of them is documented
+        let documented = roots.iter().any(|root| !ir.constraint_docs(root).is_empty());
+        if documented {
+            doc.push_str("| # | Domain | Degree | Divisor | Constraint | Description |\n");
+            doc.push_str("| --- | --- | --- | --- | --- | --- |\n");
+        } else {
+            doc.push_str("| # | Domain | Degree | Divisor | Constraint |\n");
+            doc.push_str("| --- | --- | --- | --- | --- |\n");
+        }
+        for (i, root) in roots.iter().enumerate() {
+            let degree = degree(&graph.degree(root.node_index()));
+            let divisor = divisor(ir.divisor_for(root.domain()));
+            let expr = graph.to_infix(root.node_index())?;
+            write!(doc, "| {i} | {} | {degree} | `{divisor}` | `{expr} = 0` |", root.domain())
+                .unwrap();
+            if documented {
+                write!(doc, " {} |", description(ir.constraint_docs(root))).unwrap();
+            }
+            doc.push('\n');
+        }
+        doc.push('\n');
+    }
+    Ok(())
+}
+
+// HELPERS
+// ================================================================================================
+
+/// Returns the doc comments of a constraint as the text of a table cell, in which the lines are
+/// joined, and the paragraphs separated by line breaks.
+fn description(docs: &[String]) -> String {
+    let paragraphs = docs.split(|line| line.trim().is_empty()).filter(|lines| !lines.is_empty());
+    paragraphs
+        .map(|lines| lines.join(" ").replace('|', "\\|"))
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// Returns the label of a column, matching the labels of trace accesses in constraints.
+fn column_label(segment: TraceSegmentId, column: usize) -> String {
+    match segment {
+        DEFAULT_SEGMENT => format!("M[{column}]"),
+        AUX_SEGMENT => format!("A[{column}]"),
+        segment => format!("S{segment}[{column}]"),
+    }
+}
+
+fn bus_type(bus_type: BusType) -> &'static str {
+    match bus_type {
+        BusType::Multiset => "multiset",
+        BusType::Logup => "logup",
+    }
+}
+
+fn bus_boundary(boundary: &BusBoundary) -> String {
+    match boundary {
+        BusBoundary::PublicInputTable(access) => {
+            format!("the rows of the public input table `{}`", access.table_name)
+        },
+        BusBoundary::Null => "empty (`null`)".to_string(),
+        BusBoundary::Unconstrained => "unconstrained".to_string(),
+    }
+}
+
+fn group_title(ir: &Air, kind: RootKind) -> String {
+    match kind {
+        RootKind::Integrity(segment) => {
+            format!("Integrity constraints of `{}`", ir.trace_segment_name(segment))
+        },
+        RootKind::BoundaryFirst => "Boundary constraints over the first row".to_string(),
+        RootKind::BoundaryLast => "Boundary constraints over the last row".to_string(),
+    }
+}
+
+fn degree(degree: &IntegrityConstraintDegree) -> String {
+    if degree.cycles().is_empty() {
+        degree.base().to_string()
+    } else {
+        let cycles: Vec<_> = degree.cycles().iter().map(usize::to_string).collect();
+        format!("{} (cycles: {})", degree.base(), cycles.join(", "))
+    }
+}
+
+/// Renders a [DivisorSpec] as a fraction of products of [VanishingFactor]s, e.g.
+/// `(z^n - 1) / ((z - g^-1)(z - g^-2))`.
+fn divisor(divisor: DivisorSpec) -> String {
+    let factor = |factor: VanishingFactor| match factor {
+        VanishingFactor::FirstRow => "z - 1".to_string(),
+        VanishingFactor::FromEnd(i) => format!("z - g^-{i}"),
+        VanishingFactor::AllRows => "z^n - 1".to_string(),
+    };
+    let product = |factors: Vec<VanishingFactor>| match factors.as_slice() {
+        [single] => factor(*single),
+        factors => factors.iter().map(|f| format!("({})", factor(*f))).collect(),
+    };
+
+    let numerator = divisor.numerator();
+    let denominator = divisor.denominator();
+    if denominator.is_empty() {
+        return product(numerator);
+    }
+    format!("({}) / ({})", product(numerator), product(denominator))
+}
@@ -0,0 +1,84 @@
+use air_ir::{Air, NodeIndex, Operation, PublicInputAccess, TraceAccess, Value};
+
+// RUST STRING GENERATION FOR THE CONSTRAINT GRAPH
+// ================================================================================================
+
+/// Code generation trait for generating Rust code strings from IR types related to constraints and
+/// the [air_ir::AlgebraicGraph].
+///
+/// Every expression is of type `AB::Expr`, so values read from the trace and the public values are
+/// cloned rather than moved out of the frames declared by the generated `eval` function.
+pub trait Codegen {
+    fn to_string(&self, ir: &Air) -> String;
+}
+
+impl Codegen for TraceAccess {
+    fn to_string(&self, _ir: &Air) -> String {
+        match self.row_offset {
+            0 => format!("main_current[{}].clone()", self.column),
+            1 => format!("main_next[{}].clone()", self.column),
+            _ => panic!("Plonky3 doesn't support row offsets greater than 1."),
+        }
+    }
+}
+
+impl Codegen for NodeIndex {
+    fn to_string(&self, ir: &Air) -> String {
+        let op = ir.constraint_graph().node(self).op();
+        op.to_string(ir)
+    }
+}
+
+impl Codegen for Operation {
+    fn to_string(&self, ir: &Air) -> String {
+        match self {
+            Operation::Value(value) => value.to_string(ir),
+            Operation::Add(..) | Operation::Sub(..) | Operation::Mul(..) => {
+                binary_op_to_string(ir, self)
+            },
+        }
+    }
+}
+
+impl Codegen for Value {
+    fn to_string(&self, ir: &Air) -> String {
+        match self {
+            Value::Constant(0) => "AB::Expr::ZERO".to_string(),
+            Value::Constant(1) => "AB::Expr::ONE".to_string(),
+            Value::Constant(value) => format!("AB::Expr::from_u64({value})"),
+            Value::TraceAccess(trace_access) => trace_access.to_string(ir),
+            Value::PublicInput(PublicInputAccess { name, index }) => {
+                format!("{name}[{index}].clone()")
+            },
+            Value::PeriodicColumn(_) => panic!("Plonky3 doesn't support periodic columns."),
+            Value::RandomValue(_) => panic!("Plonky3 backend doesn't support random values yet."),
+        }
+    }
+}
+
+/// Returns a string representation of a binary operation.
+fn binary_op_to_string(ir: &Air, op: &Operation) -> String {
+    let operand = |index: &NodeIndex, parenthesize: bool| {
+        if parenthesize {
+            format!("({})", index.to_string(ir))
+        } else {
+            index.to_string(ir)
+        }
+    };
+    let precedence = |index: &NodeIndex| ir.constraint_graph().node(index).op().precedence();
+    match op {
+        Operation::Add(l_idx, r_idx) => {
+            format!("{} + {}", operand(l_idx, false), operand(r_idx, false))
+        },
+        Operation::Sub(l_idx, r_idx) => {
+            let rhs = operand(r_idx, precedence(r_idx) <= op.precedence());
+            format!("{} - {rhs}", operand(l_idx, false))
+        },
+        Operation::Mul(l_idx, r_idx) => {
+            let lhs = operand(l_idx, precedence(l_idx) < op.precedence());
+            let rhs = operand(r_idx, precedence(r_idx) < op.precedence());
+            format!("{lhs} * {rhs}")
+        },
+        _ => panic!("unsupported operation"),
+    }
+}
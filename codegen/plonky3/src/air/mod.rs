@@ -0,0 +1,129 @@
+mod graph;
+use air_ir::{Air, ConstraintDomain, PublicInput, TraceSegmentId};
+use anyhow::bail;
+use graph::Codegen;
+
+use super::Scope;
+
+/// The trace segment of the constraints supported by this backend.
+const MAIN_SEGMENT: TraceSegmentId = 0;
+
+// HELPERS TO GENERATE AN IMPLEMENTATION OF THE PLONKY3 AIR TRAIT
+// ================================================================================================
+
+/// Returns an error if the provided AirIR uses a feature which cannot be expressed through a
+/// Plonky3 `AirBuilder` by this backend.
+pub(super) fn validate(ir: &Air) -> anyhow::Result<()> {
+    if let Some(column) = ir.periodic_columns.keys().next() {
+        bail!(
+            "periodic column '{column}' is not supported by the Plonky3 backend, as Plonky3 has no native periodic columns"
+        );
+    }
+    if ir.has_aux_segment() || ir.uses_random_values() || !ir.buses.is_empty() {
+        bail!("buses and auxiliary trace segments are not yet supported by the Plonky3 backend");
+    }
+    if let Some(input) = ir.public_inputs().find(|input| matches!(input, PublicInput::Table { .. }))
+    {
+        bail!(
+            "public input table '{}' is not supported by the Plonky3 backend, only fixed-size public inputs are",
+            input.name()
+        );
+    }
+    for root in ir.integrity_constraints(MAIN_SEGMENT) {
+        if let ConstraintDomain::EveryFrame(size @ 3..) = root.domain() {
+            bail!(
+                "constraints over a frame of {size} rows are not supported by the Plonky3 backend, which only exposes the current and next rows"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Updates the provided scope with a unit struct for the provided AirIR, and its implementations
+/// of the `BaseAir` and `BaseAirWithPublicValues` traits.
+pub(super) fn add_air_struct(scope: &mut Scope, ir: &Air) {
+    let name = ir.name();
+    scope.new_struct(name).vis("pub");
+
+    let width = ir.trace_segment_widths[MAIN_SEGMENT];
+    scope
+        .new_impl(name)
+        .generic("F")
+        .impl_trait("BaseAir<F>")
+        .new_fn("width")
+        .arg_ref_self()
+        .ret("usize")
+        .line(width.to_string());
+
+    let num_public_values: usize = ir.public_inputs().map(PublicInput::size).sum();
+    scope
+        .new_impl(name)
+        .generic("F")
+        .impl_trait("BaseAirWithPublicValues<F>")
+        .new_fn("num_public_values")
+        .arg_ref_self()
+        .ret("usize")
+        .line(num_public_values.to_string());
+}
+
+/// Updates the provided scope with the implementation of the Plonky3 `Air` trait for the provided
+/// AirIR, whose `eval` function enforces all of its constraints.
+///
+/// The public values are flattened in the canonical order of [Air::public_inputs], and each
+/// public input is bound to the slice of the public values holding its elements.
+pub(super) fn add_air_trait(scope: &mut Scope, ir: &Air) {
+    let eval = scope
+        .new_impl(ir.name())
+        .generic("AB: AirBuilderWithPublicValues<F = Goldilocks>")
+        .impl_trait("Air<AB>")
+        .new_fn("eval")
+        .arg_ref_self()
+        .arg("builder", "&mut AB");
+
+    eval.line("let main = builder.main();");
+    eval.line(
+        "let main_current: Vec<AB::Expr> = main.row_slice(0).unwrap().iter().map(|&v| v.into()).collect();",
+    );
+    // the next row is only read by the transition constraints
+    if ir
+        .integrity_constraints(MAIN_SEGMENT)
+        .iter()
+        .any(|root| root.domain() == ConstraintDomain::EveryFrame(2))
+    {
+        eval.line(
+            "let main_next: Vec<AB::Expr> = main.row_slice(1).unwrap().iter().map(|&v| v.into()).collect();",
+        );
+    }
+
+    if ir.public_inputs().next().is_some() {
+        eval.line(
+            "let public_values: Vec<AB::Expr> = builder.public_values().iter().map(|&v| v.into()).collect();",
+        );
+        let mut start = 0;
+        for input in ir.public_inputs() {
+            let end = start + input.size();
+            eval.line(format!("let {} = &public_values[{start}..{end}];", input.name()));
+            start = end;
+        }
+    }
+
+    for root in ir.boundary_constraints(MAIN_SEGMENT) {
+        let filter = match root.domain() {
+            ConstraintDomain::FirstRow => "when_first_row()",
+            ConstraintDomain::LastRow => "when_last_row()",
+            domain => unreachable!("invalid domain for a boundary constraint: {domain}"),
+        };
+        let expr = root.node_index().to_string(ir);
+        eval.line(format!("builder.{filter}.assert_zero({expr});"));
+    }
+
+    for root in ir.integrity_constraints(MAIN_SEGMENT) {
+        let expr = root.node_index().to_string(ir);
+        match root.domain() {
+            ConstraintDomain::EveryFrame(_) => {
+                eval.line(format!("builder.when_transition().assert_zero({expr});"))
+            },
+            _ => eval.line(format!("builder.assert_zero({expr});")),
+        };
+    }
+}
@@ -0,0 +1,13 @@
+use super::Scope;
+
+/// Adds the Plonky3 imports required by the generated code to the provided scope.
+pub(super) fn add_imports(scope: &mut Scope) {
+    scope.import("p3_air", "Air");
+    scope.import("p3_air", "AirBuilder");
+    scope.import("p3_air", "AirBuilderWithPublicValues");
+    scope.import("p3_air", "BaseAir");
+    scope.import("p3_air", "BaseAirWithPublicValues");
+    scope.import("p3_field", "PrimeCharacteristicRing");
+    scope.import("p3_goldilocks", "Goldilocks");
+    scope.import("p3_matrix", "Matrix");
+}
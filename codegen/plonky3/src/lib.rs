@@ -0,0 +1,61 @@
+use air_ir::{Air, Provenance};
+pub use codegen::{self, Scope};
+
+mod air;
+mod imports;
+#[cfg(test)]
+mod tests;
+
+// GENERATE RUST CODE FOR PLONKY3 AIR
+// ================================================================================================
+
+/// CodeGenerator is used to generate a Rust implementation of the `Air` trait of the Plonky3
+/// `p3-air` crate, whose `eval` function enforces the constraints specified by the [Air] used to
+/// build the CodeGenerator through an `AirBuilder`.
+///
+/// The generated code targets Plonky3 0.3 over the Goldilocks field, the field over which
+/// AirScript constants are reduced. Programs using features which Plonky3 cannot express
+/// natively, such as periodic columns, are rejected with an error.
+#[derive(Default)]
+pub struct CodeGenerator {
+    provenance: Option<Provenance>,
+}
+impl CodeGenerator {
+    /// Creates a new [CodeGenerator].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emits the given [Provenance] as a comment header at the top of the generated code.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+}
+impl air_ir::CodeGenerator for CodeGenerator {
+    type Output = String;
+
+    fn generate(&self, ir: &Air) -> anyhow::Result<Self::Output> {
+        air::validate(ir)?;
+
+        let mut scope = Scope::new();
+
+        // add plonky3 imports.
+        imports::add_imports(&mut scope);
+
+        // add the Air struct and the implementations of the base Air traits.
+        air::add_air_struct(&mut scope, ir);
+
+        // add the Plonky3 Air trait implementation, enforcing the constraints of the AirIR.
+        air::add_air_trait(&mut scope, ir);
+
+        match &self.provenance {
+            Some(provenance) => {
+                let header: String =
+                    provenance.header_lines().iter().map(|line| format!("// {line}\n")).collect();
+                Ok(format!("{header}\n{}", scope.to_string()))
+            },
+            None => Ok(scope.to_string()),
+        }
+    }
+}
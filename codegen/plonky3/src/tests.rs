@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use crate::CodeGenerator;
+use air_ir::{Air, CodeGenerator as _, Provenance};
+use air_pass::Pass;
+use miden_diagnostics::{
+    CodeMap, DefaultEmitter, DiagnosticsHandler, term::termcolor::ColorChoice,
+};
+
+const FIBONACCI: &str = "
+def FibonacciAir
+
+trace_columns {
+    main: [a, b],
+}
+
+public_inputs {
+    stack_inputs: [2],
+    result: [1],
+}
+
+boundary_constraints {
+    enf a.first = stack_inputs[0];
+    enf b.first = stack_inputs[1];
+    enf b.last = result[0];
+}
+
+integrity_constraints {
+    enf a' = b;
+    enf b' = a + b;
+}";
+
+/// Compiles an AirScript program to an [Air].
+fn compile(source: &str) -> anyhow::Result<Air> {
+    let code_map = Arc::new(CodeMap::new());
+    let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), code_map.clone(), emitter);
+
+    let air = air_parser::parse(&diagnostics, code_map, source)
+        .map_err(air_ir::CompileError::Parse)
+        .and_then(|ast| {
+            let mut pipeline = air_parser::transforms::ConstantPropagation::new(&diagnostics)
+                .chain(mir::passes::AstToMir::new(&diagnostics))
+                .chain(mir::passes::Inlining::new(&diagnostics))
+                .chain(mir::passes::Unrolling::new(&diagnostics))
+                .chain(air_ir::passes::MirToAir::new(&diagnostics))
+                .chain(air_ir::passes::BusOpExpand::new(&diagnostics));
+            pipeline.run(ast)
+        })?;
+    Ok(air)
+}
+
+#[test]
+fn constraints_are_enforced_through_the_builder() {
+    let air = compile(FIBONACCI).unwrap();
+    let code = CodeGenerator::new().generate(&air).unwrap();
+
+    assert!(code.contains("pub struct FibonacciAir;"));
+    assert!(code.contains(
+        "impl<F> BaseAir<F> for FibonacciAir {\n    fn width(&self) -> usize {\n        2"
+    ));
+    assert!(code.contains("fn num_public_values(&self) -> usize {\n        3"));
+    assert!(code.contains(
+        "impl<AB: AirBuilderWithPublicValues<F = Goldilocks>> Air<AB> for FibonacciAir {"
+    ));
+    // public inputs are sliced from the public values in canonical order
+    assert!(code.contains("let result = &public_values[0..1];"));
+    assert!(code.contains("let stack_inputs = &public_values[1..3];"));
+    assert!(code.contains(
+        "builder.when_first_row().assert_zero(main_current[0].clone() - stack_inputs[0].clone());"
+    ));
+    assert!(code.contains(
+        "builder.when_last_row().assert_zero(main_current[1].clone() - result[0].clone());"
+    ));
+    assert!(code.contains(
+        "builder.when_transition().assert_zero(main_next[1].clone() - (main_current[0].clone() + main_current[1].clone()));"
+    ));
+}
+
+#[test]
+fn validity_constraints_are_enforced_on_every_row() {
+    let source = "
+def ValidityAir
+
+trace_columns {
+    main: [a],
+}
+
+public_inputs {
+    stack_inputs: [1],
+}
+
+boundary_constraints {
+    enf a.first = 0;
+}
+
+integrity_constraints {
+    enf a^2 = a;
+}";
+    let air = compile(source).unwrap();
+    let code = CodeGenerator::new().generate(&air).unwrap();
+
+    assert!(
+        code.contains("builder.assert_zero(main_current[0].clone() * main_current[0].clone() - main_current[0].clone());")
+    );
+    assert!(!code.contains("main_next"));
+}
+
+#[test]
+fn provenance_is_emitted_as_header() {
+    let air = compile(FIBONACCI).unwrap();
+    let provenance = Provenance {
+        compiler_version: "0.5.0".to_string(),
+        source_hash: "abcd".to_string(),
+        options: vec!["pipeline=WithMIR".to_string()],
+    };
+
+    let code = CodeGenerator::new().generate(&air).unwrap();
+    assert_eq!(Provenance::from_header(&code), None);
+
+    let code = CodeGenerator::new().with_provenance(provenance.clone()).generate(&air).unwrap();
+    assert!(code.starts_with("// airscript-version: 0.5.0\n"));
+    assert_eq!(Provenance::from_header(&code), Some(provenance));
+}
+
+#[test]
+fn unsupported_features_are_rejected() {
+    let periodic = "
+def PeriodicAir
+
+trace_columns {
+    main: [a],
+}
+
+periodic_columns {
+    k: [1, 0],
+}
+
+public_inputs {
+    stack_inputs: [1],
+}
+
+boundary_constraints {
+    enf a.first = 0;
+}
+
+integrity_constraints {
+    enf a' = a * k;
+}";
+    let air = compile(periodic).unwrap();
+    let err = CodeGenerator::new().generate(&air).err().unwrap();
+    assert!(err.to_string().contains("periodic column"), "{err}");
+
+    let buses = "
+def BusAir
+
+trace_columns {
+    main: [a],
+}
+
+buses {
+    multiset p,
+}
+
+public_inputs {
+    stack_inputs: [1],
+}
+
+boundary_constraints {
+    enf p.first = null;
+    enf p.last = null;
+}
+
+integrity_constraints {
+    p.insert(a) when a;
+    p.remove(a) when 1 - a;
+}";
+    let air = compile(buses).unwrap();
+    let err = CodeGenerator::new().generate(&air).err().unwrap();
+    assert!(err.to_string().contains("buses"), "{err}");
+
+    let table = "
+def TableAir
+
+trace_columns {
+    main: [a],
+}
+
+public_inputs {
+    inputs: [[2]],
+}
+
+boundary_constraints {
+    enf a.first = 0;
+}
+
+integrity_constraints {
+    enf a' = a;
+}";
+    let air = compile(table).unwrap();
+    let err = CodeGenerator::new().generate(&air).err().unwrap();
+    assert!(err.to_string().contains("public input table 'inputs'"), "{err}");
+}
@@ -2,8 +2,8 @@ use core::panic;
 
 use air_ir::{Air, AlgebraicGraph, ConstraintDomain, NodeIndex, Operation, TraceAccess, Value};
 
-use super::{Codegen, ElemType, Impl};
-use crate::air::call_bus_boundary_varlen_pubinput;
+use super::{BaseField, Codegen, ElemType, Impl};
+use crate::air::{add_constraint_docs, call_bus_boundary_varlen_pubinput};
 
 // HELPERS TO GENERATE THE WINTERFELL BOUNDARY CONSTRAINT METHODS
 // ================================================================================================
@@ -11,13 +11,15 @@ use crate::air::call_bus_boundary_varlen_pubinput;
 /// Adds an implementation of the "get_assertions" method to the referenced Air implementation
 /// based on the data in the provided IR.
 /// TODO: add result types to these functions.
-pub(super) fn add_fn_get_assertions(impl_ref: &mut Impl, ir: &Air) {
+pub(super) fn add_fn_get_assertions(impl_ref: &mut Impl, ir: &Air, base_field: &BaseField) {
     // define the function
-    let get_assertions =
-        impl_ref.new_fn("get_assertions").arg_ref_self().ret("Vec<Assertion<Felt>>");
+    let get_assertions = impl_ref
+        .new_fn("get_assertions")
+        .arg_ref_self()
+        .ret(format!("Vec<Assertion<{base_field}>>"));
 
     // add the boundary constraints
-    add_main_trace_assertions(get_assertions, ir);
+    add_main_trace_assertions(get_assertions, ir, base_field);
 
     // return the result
     get_assertions.line("result");
@@ -25,17 +27,17 @@ pub(super) fn add_fn_get_assertions(impl_ref: &mut Impl, ir: &Air) {
 
 /// Adds an implementation of the "get_aux_assertions" method to the referenced Air implementation
 /// based on the data in the provided IR.
-pub(super) fn add_fn_get_aux_assertions(impl_ref: &mut Impl, ir: &Air) {
+pub(super) fn add_fn_get_aux_assertions(impl_ref: &mut Impl, ir: &Air, base_field: &BaseField) {
     // define the function
     let get_aux_assertions = impl_ref
         .new_fn("get_aux_assertions")
-        .generic("E: FieldElement<BaseField = Felt>")
+        .generic(format!("E: FieldElement<BaseField = {base_field}>"))
         .arg_ref_self()
         .arg("aux_rand_elements", "&AuxRandElements<E>")
         .ret("Vec<Assertion<E>>");
 
     // add the boundary constraints
-    add_aux_trace_assertions(get_aux_assertions, ir);
+    add_aux_trace_assertions(get_aux_assertions, ir, base_field);
 
     // return the result
     get_aux_assertions.line("result");
@@ -43,7 +45,7 @@ pub(super) fn add_fn_get_aux_assertions(impl_ref: &mut Impl, ir: &Air) {
 
 /// Declares a result vector and adds assertions for boundary constraints to it for the main
 /// trace segment
-fn add_main_trace_assertions(func_body: &mut codegen::Function, ir: &Air) {
+fn add_main_trace_assertions(func_body: &mut codegen::Function, ir: &Air, base_field: &BaseField) {
     let elem_type = ElemType::Base;
     let main_trace_segment = 0;
 
@@ -56,7 +58,7 @@ fn add_main_trace_assertions(func_body: &mut codegen::Function, ir: &Air) {
             split_boundary_constraint(ir.constraint_graph(), constraint.node_index());
         debug_assert_eq!(trace_access.segment, main_trace_segment);
 
-        let expr_root_string = expr_root.to_string(ir, elem_type, main_trace_segment);
+        let expr_root_string = expr_root.to_string(ir, base_field, elem_type, main_trace_segment);
 
         let assertion = format!(
             "result.push(Assertion::single({}, {}, {}));",
@@ -65,13 +67,14 @@ fn add_main_trace_assertions(func_body: &mut codegen::Function, ir: &Air) {
             expr_root_string
         );
 
+        add_constraint_docs(func_body, ir, constraint);
         func_body.line(assertion);
     }
 }
 
 /// Declares a result vector and adds assertions for boundary constraints to it for the aux
 /// trace segment (used for buses boundary constraints for variable length public inputs)
-fn add_aux_trace_assertions(func_body: &mut codegen::Function, ir: &Air) {
+fn add_aux_trace_assertions(func_body: &mut codegen::Function, ir: &Air, base_field: &BaseField) {
     let elem_type = ElemType::Ext;
     let aux_trace_segment = 1;
 
@@ -85,7 +88,7 @@ fn add_aux_trace_assertions(func_body: &mut codegen::Function, ir: &Air) {
             split_boundary_constraint(ir.constraint_graph(), constraint.node_index());
         debug_assert_eq!(trace_access.segment, aux_trace_segment);
 
-        let expr_root_string = expr_root.to_string(ir, elem_type, aux_trace_segment);
+        let expr_root_string = expr_root.to_string(ir, base_field, elem_type, aux_trace_segment);
 
         let assertion = format!(
             "result.push(Assertion::single({}, {}, {}));",
@@ -94,6 +97,7 @@ fn add_aux_trace_assertions(func_body: &mut codegen::Function, ir: &Air) {
             expr_root_string
         );
 
+        add_constraint_docs(func_body, ir, constraint);
         func_body.line(assertion);
     }
 
@@ -2,7 +2,7 @@ use air_ir::{
     Air, IntegrityConstraintDegree, NodeIndex, Operation, TraceAccess, TraceSegmentId, Value,
 };
 
-use super::ElemType;
+use super::{BaseField, ElemType};
 
 // RUST STRING GENERATION FOR THE CONSTRAINT GRAPH
 // ================================================================================================
@@ -11,11 +11,23 @@ use super::ElemType;
 /// the [AlgebraicGraph].
 /// TODO: replace panics with errors
 pub trait Codegen {
-    fn to_string(&self, ir: &Air, elem_type: ElemType, trace_segment: TraceSegmentId) -> String;
+    fn to_string(
+        &self,
+        ir: &Air,
+        base_field: &BaseField,
+        elem_type: ElemType,
+        trace_segment: TraceSegmentId,
+    ) -> String;
 }
 
 impl Codegen for IntegrityConstraintDegree {
-    fn to_string(&self, _ir: &Air, _elem_type: ElemType, _trace_segment: TraceSegmentId) -> String {
+    fn to_string(
+        &self,
+        _ir: &Air,
+        _base_field: &BaseField,
+        _elem_type: ElemType,
+        _trace_segment: TraceSegmentId,
+    ) -> String {
         if self.cycles().is_empty() {
             format!("TransitionConstraintDegree::new({})", self.base())
         } else {
@@ -31,7 +43,13 @@ impl Codegen for IntegrityConstraintDegree {
 }
 
 impl Codegen for TraceAccess {
-    fn to_string(&self, _ir: &Air, _elem_type: ElemType, trace_segment: TraceSegmentId) -> String {
+    fn to_string(
+        &self,
+        _ir: &Air,
+        _base_field: &BaseField,
+        _elem_type: ElemType,
+        trace_segment: TraceSegmentId,
+    ) -> String {
         let frame = if self.segment == 0 { "main" } else { "aux" };
         let row_offset = match self.row_offset {
             0 => {
@@ -51,45 +69,64 @@ impl Codegen for TraceAccess {
 }
 
 impl Codegen for NodeIndex {
-    fn to_string(&self, ir: &Air, elem_type: ElemType, trace_segment: TraceSegmentId) -> String {
+    fn to_string(
+        &self,
+        ir: &Air,
+        base_field: &BaseField,
+        elem_type: ElemType,
+        trace_segment: TraceSegmentId,
+    ) -> String {
         let op = ir.constraint_graph().node(self).op();
-        op.to_string(ir, elem_type, trace_segment)
+        op.to_string(ir, base_field, elem_type, trace_segment)
     }
 }
 
 impl Codegen for Operation {
-    fn to_string(&self, ir: &Air, elem_type: ElemType, trace_segment: TraceSegmentId) -> String {
+    fn to_string(
+        &self,
+        ir: &Air,
+        base_field: &BaseField,
+        elem_type: ElemType,
+        trace_segment: TraceSegmentId,
+    ) -> String {
         match self {
-            Operation::Value(value) => value.to_string(ir, elem_type, trace_segment),
-            Operation::Add(..) => binary_op_to_string(ir, self, elem_type, trace_segment),
-            Operation::Sub(..) => binary_op_to_string(ir, self, elem_type, trace_segment),
-            Operation::Mul(..) => binary_op_to_string(ir, self, elem_type, trace_segment),
+            Operation::Value(value) => value.to_string(ir, base_field, elem_type, trace_segment),
+            Operation::Add(..) => {
+                binary_op_to_string(ir, self, base_field, elem_type, trace_segment)
+            },
+            Operation::Sub(..) => {
+                binary_op_to_string(ir, self, base_field, elem_type, trace_segment)
+            },
+            Operation::Mul(..) => {
+                binary_op_to_string(ir, self, base_field, elem_type, trace_segment)
+            },
         }
     }
 }
 
 impl Codegen for Value {
-    fn to_string(&self, ir: &Air, elem_type: ElemType, trace_segment: TraceSegmentId) -> String {
+    fn to_string(
+        &self,
+        ir: &Air,
+        base_field: &BaseField,
+        elem_type: ElemType,
+        trace_segment: TraceSegmentId,
+    ) -> String {
         match self {
-            // TODO: move constant handling to a helper function
-            Value::Constant(0) => match elem_type {
-                ElemType::Base => "Felt::ZERO".to_string(),
-                ElemType::Ext => "E::ZERO".to_string(),
-            },
-            Value::Constant(1) => match elem_type {
-                ElemType::Base => "Felt::ONE".to_string(),
-                ElemType::Ext => "E::ONE".to_string(),
-            },
-            Value::Constant(value) => match elem_type {
-                ElemType::Base => format!("Felt::new({value})"),
-                ElemType::Ext => format!("E::from(Felt::new({value}_u64))"),
-            },
+            Value::Constant(value) => base_field.constant(*value, elem_type),
             Value::TraceAccess(trace_access) => {
-                trace_access.to_string(ir, elem_type, trace_segment)
+                trace_access.to_string(ir, base_field, elem_type, trace_segment)
             },
             Value::PeriodicColumn(pc) => {
-                let index =
-                    ir.periodic_columns.iter().position(|(qid, _)| qid == &pc.name).unwrap();
+                // Shifted copies of the columns are declared after all of the periodic columns,
+                // see `get_periodic_column_values`
+                let index = if pc.row_offset > 0 {
+                    let position =
+                        ir.shifted_periodic_columns().iter().position(|access| access == pc);
+                    ir.periodic_columns.len() + position.unwrap()
+                } else {
+                    ir.periodic_columns.iter().position(|(qid, _)| qid == &pc.name).unwrap()
+                };
                 format!("periodic_values[{index}]")
             },
             Value::PublicInput(air_ir::PublicInputAccess { name, index }) => {
@@ -106,34 +143,35 @@ impl Codegen for Value {
 fn binary_op_to_string(
     ir: &Air,
     op: &Operation,
+    base_field: &BaseField,
     elem_type: ElemType,
     trace_segment: TraceSegmentId,
 ) -> String {
     match op {
         Operation::Add(l_idx, r_idx) => {
-            let lhs = l_idx.to_string(ir, elem_type, trace_segment);
-            let rhs = r_idx.to_string(ir, elem_type, trace_segment);
+            let lhs = l_idx.to_string(ir, base_field, elem_type, trace_segment);
+            let rhs = r_idx.to_string(ir, base_field, elem_type, trace_segment);
             format!("{lhs} + {rhs}")
         },
         Operation::Sub(l_idx, r_idx) => {
-            let lhs = l_idx.to_string(ir, elem_type, trace_segment);
+            let lhs = l_idx.to_string(ir, base_field, elem_type, trace_segment);
             let rhs = if ir.constraint_graph().node(r_idx).op().precedence() <= op.precedence() {
-                format!("({})", r_idx.to_string(ir, elem_type, trace_segment))
+                format!("({})", r_idx.to_string(ir, base_field, elem_type, trace_segment))
             } else {
-                r_idx.to_string(ir, elem_type, trace_segment)
+                r_idx.to_string(ir, base_field, elem_type, trace_segment)
             };
             format!("{lhs} - {rhs}")
         },
         Operation::Mul(l_idx, r_idx) => {
             let lhs = if ir.constraint_graph().node(l_idx).op().precedence() < op.precedence() {
-                format!("({})", l_idx.to_string(ir, elem_type, trace_segment))
+                format!("({})", l_idx.to_string(ir, base_field, elem_type, trace_segment))
             } else {
-                l_idx.to_string(ir, elem_type, trace_segment)
+                l_idx.to_string(ir, base_field, elem_type, trace_segment)
             };
             let rhs = if ir.constraint_graph().node(r_idx).op().precedence() < op.precedence() {
-                format!("({})", r_idx.to_string(ir, elem_type, trace_segment))
+                format!("({})", r_idx.to_string(ir, base_field, elem_type, trace_segment))
             } else {
-                r_idx.to_string(ir, elem_type, trace_segment)
+                r_idx.to_string(ir, base_field, elem_type, trace_segment)
             };
             format!("{lhs} * {rhs}")
         },
@@ -12,14 +12,23 @@ mod boundary_constraints;
 use boundary_constraints::{add_fn_get_assertions, add_fn_get_aux_assertions};
 
 mod transition_constraints;
-use air_ir::{Air, BusBoundary, BusType, ConstraintDomain, Identifier, TraceSegmentId};
+use air_ir::{
+    Air, BusBoundary, BusType, ConstraintDomain, ConstraintRoot, DivisorSpec, Identifier,
+    NUM_TRANSITION_EXEMPTIONS, TraceSegmentId,
+};
 use transition_constraints::{add_fn_evaluate_aux_transition, add_fn_evaluate_transition};
 
-use super::{Impl, Scope};
+use super::{BaseField, Impl, Scope};
 
 // HELPER TYPES
 // ================================================================================================
 
+/// The minimum trace length accepted by Winterfell's `TraceInfo`.
+const MIN_TRACE_LENGTH: u64 = 8;
+
+/// The maximum width of each trace segment accepted by Winterfell's `TraceInfo`.
+const MAX_TRACE_WIDTH: u16 = 255;
+
 #[derive(Debug, Clone, Copy)]
 pub enum ElemType {
     Base,
@@ -29,33 +38,47 @@ pub enum ElemType {
 // HELPERS TO GENERATE AN IMPLEMENTATION OF THE WINTERFELL AIR TRAIT
 // ================================================================================================
 
-/// Updates the provided scope with a new Air struct and Winterfell Air trait implementation
-/// which are equivalent the provided AirIR.
-pub(super) fn add_air(scope: &mut Scope, ir: &Air) {
-    // add the Public Inputs struct and its base implementation.
-    add_public_inputs_struct(scope, ir);
-
-    let name = ir.name();
-
-    // add the Air struct and its base implementation.
-    add_air_struct(scope, ir, name);
+/// Returns an error if a trace segment of the provided AirIR is wider than Winterfell supports,
+/// as the generated Air would otherwise panic when its `TraceInfo` is constructed.
+pub(super) fn validate_trace_widths(ir: &Air) -> anyhow::Result<()> {
+    for (segment, width) in ir.trace_segment_widths.iter().enumerate() {
+        if *width > MAX_TRACE_WIDTH {
+            anyhow::bail!(
+                "trace segment '{}' has width {width}, which exceeds the maximum supported {MAX_TRACE_WIDTH} for the Winterfell backend",
+                ir.trace_segment_name(segment)
+            );
+        }
+    }
+    Ok(())
+}
 
-    // add Winterfell Air trait implementation for the provided AirIR.
-    add_air_trait(scope, ir, name);
+/// Updates the provided scope with the Public Inputs struct of the provided AirIR and its
+/// implementations.
+pub(super) fn add_public_inputs(scope: &mut Scope, ir: &Air, base_field: &BaseField) {
+    add_public_inputs_struct(scope, ir, base_field);
 }
 
 /// Updates the provided scope with a custom Air struct.
-fn add_air_struct(scope: &mut Scope, ir: &Air, name: &str) {
+pub(super) fn add_air_struct(scope: &mut Scope, ir: &Air, base_field: &BaseField) {
+    let name = ir.name();
     // define the custom Air struct.
-    let air_struct = scope.new_struct(name).vis("pub").field("context", "AirContext<Felt>");
+    let air_struct = scope
+        .new_struct(name)
+        .vis("pub")
+        .field("context", format!("AirContext<{base_field}>"));
+    base_field.declare_on_struct(air_struct);
 
     // add public inputs
     for public_input in ir.public_inputs() {
-        air_struct.field(public_input.name().as_str(), public_input_type_to_string(public_input));
+        air_struct.field(
+            public_input.name().as_str(),
+            public_input_type_to_string(public_input, base_field),
+        );
     }
 
     // add the custom Air implementation block
     let base_impl = scope.new_impl(name);
+    base_field.declare_on_impl(base_impl);
     // add a simple method to get the last step.
     base_impl
         .new_fn("last_step")
@@ -82,10 +105,10 @@ fn add_air_struct(scope: &mut Scope, ir: &Air, name: &str) {
         }
     }
     if add_bus_multiset_boundary_varlen {
-        impl_bus_multiset_boundary_varlen(base_impl);
+        impl_bus_multiset_boundary_varlen(base_impl, base_field);
     }
     if add_bus_logup_boundary_varlen {
-        impl_bus_logup_boundary_varlen(base_impl);
+        impl_bus_logup_boundary_varlen(base_impl, base_field);
     }
 }
 
@@ -108,13 +131,13 @@ fn add_air_struct(scope: &mut Scope, ir: &Air, name: &str) {
 /// p = ((r0 + v0_0 * r1 + v0_1 * r2)
 ///    * (r0 + v1_0 * r1 + v1_1 * r2)
 ///    * (r0 + v2_0 * r1 + v2_1 * r2))
-fn impl_bus_multiset_boundary_varlen(base_impl: &mut Impl) {
+fn impl_bus_multiset_boundary_varlen(base_impl: &mut Impl, base_field: &BaseField) {
     base_impl
         .new_fn("bus_multiset_boundary_varlen")
         .generic("'a")
         .generic("const N: usize")
-        .generic("I: IntoIterator<Item = &'a [Felt; N]> + Clone")
-        .generic("E: FieldElement<BaseField = Felt>")
+        .generic(format!("I: IntoIterator<Item = &'a [{base_field}; N]> + Clone"))
+        .generic(format!("E: FieldElement<BaseField = {base_field}>"))
         .arg("aux_rand_elements", "&AuxRandElements<E>")
         .arg("public_inputs", "&I")
         .ret("E")
@@ -152,13 +175,13 @@ fn impl_bus_multiset_boundary_varlen(base_impl: &mut Impl) {
 ///
 /// Because this operation is not part of the Air, and is repeated by the Verifier,
 /// we can divide in this scenario!
-fn impl_bus_logup_boundary_varlen(base_impl: &mut Impl) {
+fn impl_bus_logup_boundary_varlen(base_impl: &mut Impl, base_field: &BaseField) {
     base_impl
         .new_fn("bus_logup_boundary_varlen")
         .generic("'a")
         .generic("const N: usize")
-        .generic("I: IntoIterator<Item = &'a [Felt; N]> + Clone")
-        .generic("E: FieldElement<BaseField = Felt>")
+        .generic(format!("I: IntoIterator<Item = &'a [{base_field}; N]> + Clone"))
+        .generic(format!("E: FieldElement<BaseField = {base_field}>"))
         .arg("aux_rand_elements", "&AuxRandElements<E>")
         .arg("public_inputs", "&I")
         .ret("E")
@@ -178,48 +201,61 @@ fn impl_bus_logup_boundary_varlen(base_impl: &mut Impl) {
 
 /// Updates the provided scope with the custom Air struct and an Air trait implementation based on
 /// the provided AirIR.
-fn add_air_trait(scope: &mut Scope, ir: &Air, name: &str) {
+///
+/// When `split_buses` is set, the integrity constraints of the expanded buses are generated from
+/// their operations, see [add_fn_evaluate_aux_transition].
+pub(super) fn add_air_trait(
+    scope: &mut Scope,
+    ir: &Air,
+    base_field: &BaseField,
+    split_buses: bool,
+) {
+    let name = ir.name();
     // add the implementation block for the Air trait.
     let air_impl = scope
         .new_impl(name)
         .impl_trait("Air")
-        .associate_type("BaseField", "Felt")
-        .associate_type("PublicInputs", "PublicInputs");
+        .associate_type("BaseField", base_field.to_string())
+        .associate_type("PublicInputs", base_field.with_params("PublicInputs"));
+    base_field.declare_on_impl(air_impl);
 
     // add default function "context".
-    let fn_context = air_impl.new_fn("context").arg_ref_self().ret("&AirContext<Felt>");
+    let fn_context = air_impl
+        .new_fn("context")
+        .arg_ref_self()
+        .ret(format!("&AirContext<{base_field}>"));
     fn_context.line("&self.context");
 
     // add the method implementations required by the AIR trait.
-    add_fn_new(air_impl, ir);
+    add_fn_new(air_impl, ir, base_field);
 
-    add_fn_get_periodic_column_values(air_impl, ir);
+    add_fn_get_periodic_column_values(air_impl, ir, base_field);
 
-    add_fn_get_assertions(air_impl, ir);
+    add_fn_get_assertions(air_impl, ir, base_field);
 
-    add_fn_get_aux_assertions(air_impl, ir);
+    add_fn_get_aux_assertions(air_impl, ir, base_field);
 
-    add_fn_evaluate_transition(air_impl, ir);
+    add_fn_evaluate_transition(air_impl, ir, base_field);
 
-    add_fn_evaluate_aux_transition(air_impl, ir);
+    add_fn_evaluate_aux_transition(air_impl, ir, base_field, split_buses);
 }
 
 /// Adds an implementation of the "new" method to the referenced Air implementation based on the
 /// data in the provided AirIR.
-fn add_fn_new(impl_ref: &mut Impl, ir: &Air) {
+fn add_fn_new(impl_ref: &mut Impl, ir: &Air, base_field: &BaseField) {
     // define the function.
     let new = impl_ref
         .new_fn("new")
         .arg("trace_info", "TraceInfo")
-        .arg("public_inputs", "PublicInputs")
+        .arg("public_inputs", base_field.with_params("PublicInputs"))
         .arg("options", "WinterProofOptions")
         .ret("Self");
 
     // define the integrity constraint degrees of the main trace `main_degrees`.
-    add_constraint_degrees(new, ir, 0, "main_degrees");
+    add_constraint_degrees(new, ir, base_field, 0, "main_degrees");
 
     // define the integrity constraint degrees of the aux trace `aux_degrees`.
-    add_constraint_degrees(new, ir, 1, "aux_degrees");
+    add_constraint_degrees(new, ir, base_field, 1, "aux_degrees");
 
     // define the number of main trace boundary constraints `num_main_assertions`.
     new.line(format!("let num_main_assertions = {};", ir.num_boundary_constraints(0)));
@@ -227,8 +263,20 @@ fn add_fn_new(impl_ref: &mut Impl, ir: &Air) {
     // define the number of aux trace boundary constraints `num_aux_assertions`.
     new.line(format!("let num_aux_assertions = {};", num_bus_boundary_constraints(ir)));
 
-    // define the context.
-    let context = "
+    // validate the trace length against the bounds implied by the program.
+    add_trace_length_validation(new, ir);
+
+    // define the context, with the exemptions of the divisor shared by all integrity constraints.
+    let num_transition_exemptions = [0, 1]
+        .into_iter()
+        .flat_map(|segment| ir.integrity_constraints(segment))
+        .map(|constraint| match ir.divisor_for(constraint.domain()) {
+            DivisorSpec::TransitionWithExemptions { exemptions } => exemptions,
+            DivisorSpec::FirstRow | DivisorSpec::LastRow => unreachable!(),
+        })
+        .fold(NUM_TRANSITION_EXEMPTIONS, usize::max);
+    let context = format!(
+        "
 let context = AirContext::new_multi_segment(
     trace_info,
     main_degrees,
@@ -237,7 +285,8 @@ let context = AirContext::new_multi_segment(
     num_aux_assertions,
     options,
 )
-.set_num_transition_exemptions(2);";
+.set_num_transition_exemptions({num_transition_exemptions});"
+    );
 
     new.line(context);
 
@@ -250,23 +299,48 @@ let context = AirContext::new_multi_segment(
     new.line(format!("Self {{ context, {} }}", pub_inputs.join(", ")));
 }
 
+/// Appends an assertion on the trace length to the function body, if the bounds implied by the
+/// program are stricter than those already enforced by Winterfell's `TraceInfo`, which only
+/// accepts power of two lengths of at least [MIN_TRACE_LENGTH] rows.
+fn add_trace_length_validation(func_body: &mut codegen::Function, ir: &Air) {
+    let bounds = ir.trace_length_constraints();
+    if bounds.min_len() <= MIN_TRACE_LENGTH {
+        return;
+    }
+    func_body.line(format!(
+        "assert!(trace_info.length() >= {}, \"the trace length must be at least 2^{}: {}\");",
+        bounds.min_len(),
+        bounds.min_log2,
+        bounds.reasons.join("; ").replace('"', "\\\""),
+    ));
+}
+
 /// Iterates through the degrees of the integrity constraints in the IR, and appends a line of
 /// generated code to the function body that declares all of the constraint degrees.
 fn add_constraint_degrees(
     func_body: &mut codegen::Function,
     ir: &Air,
+    base_field: &BaseField,
     trace_segment: TraceSegmentId,
     decl_name: &str,
 ) {
     let degrees = ir
         .integrity_constraint_degrees(trace_segment)
         .iter()
-        .map(|degree| degree.to_string(ir, ElemType::Ext, trace_segment))
+        .map(|degree| degree.to_string(ir, base_field, ElemType::Ext, trace_segment))
         .collect::<Vec<_>>();
 
     func_body.line(format!("let {decl_name} = vec![{}];", degrees.join(", ")));
 }
 
+/// Appends the doc comments of `constraint` to the function body, as comments above the code
+/// enforcing it.
+fn add_constraint_docs(func_body: &mut codegen::Function, ir: &Air, constraint: &ConstraintRoot) {
+    for doc in ir.constraint_docs(constraint) {
+        func_body.line(format!("// {doc}").trim_end());
+    }
+}
+
 fn call_bus_boundary_varlen_pubinput(
     ir: &Air,
     bus_name: Identifier,
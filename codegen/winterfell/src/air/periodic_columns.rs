@@ -1,46 +1,47 @@
-use std::collections::BTreeMap;
+use air_ir::Air;
 
-use air_ir::{Air, PeriodicColumn, QualifiedIdentifier};
+use super::{BaseField, ElemType, Impl};
 
-use super::Impl;
-
-pub(super) fn add_fn_get_periodic_column_values(impl_ref: &mut Impl, ir: &Air) {
+pub(super) fn add_fn_get_periodic_column_values(
+    impl_ref: &mut Impl,
+    ir: &Air,
+    base_field: &BaseField,
+) {
     // define the function.
     let get_periodic_column_values = impl_ref
         .new_fn("get_periodic_column_values")
         .arg_ref_self()
-        .ret("Vec<Vec<Felt>>");
+        .ret(format!("Vec<Vec<{base_field}>>"));
 
-    // output the periodic columns.
-    let periodic_columns = &ir.periodic_columns;
-    get_periodic_column_values.line(periodic_columns.codegen());
+    // output the periodic columns, followed by the shifted copies of those read in a later row.
+    get_periodic_column_values.line(ir.codegen(base_field));
 }
 
 /// Code generation trait for generating Rust code strings from Periodic Columns.
 trait Codegen {
-    fn codegen(&self) -> String;
+    fn codegen(&self, base_field: &BaseField) -> String;
 }
 
-impl Codegen for &BTreeMap<QualifiedIdentifier, PeriodicColumn> {
-    fn codegen(&self) -> String {
-        let mut columns = vec![];
-        for column in self.values() {
-            let mut rows = vec![];
-            for row in column.values.iter().copied() {
-                match row {
-                    0 => {
-                        rows.push("Felt::ZERO".to_string());
-                    },
-                    1 => {
-                        rows.push("Felt::ONE".to_string());
-                    },
-                    row => {
-                        rows.push(format!("Felt::new({row})"));
-                    },
-                }
-            }
-            columns.push(format!("vec![{}]", rows.join(", ")));
+impl Codegen for Air {
+    fn codegen(&self, base_field: &BaseField) -> String {
+        let mut columns: Vec<_> = self
+            .periodic_columns
+            .values()
+            .map(|column| column_codegen(&column.values, base_field))
+            .collect();
+        // Winterfell only provides the value of each periodic column in the current row, so the
+        // value `o` rows ahead is read from a copy of the column rotated by `o`.
+        for access in self.shifted_periodic_columns() {
+            let mut values = self.periodic_columns[&access.name].values.clone();
+            let shift = access.row_offset % values.len();
+            values.rotate_left(shift);
+            columns.push(column_codegen(&values, base_field));
         }
         format!("vec![{}]", columns.join(", "))
     }
 }
+
+fn column_codegen(values: &[u64], base_field: &BaseField) -> String {
+    let rows: Vec<_> = values.iter().map(|row| base_field.constant(*row, ElemType::Base)).collect();
+    format!("vec![{}]", rows.join(", "))
+}
@@ -1,46 +1,118 @@
 use air_ir::Air;
 
-use super::Scope;
+use super::{BaseField, Scope};
 
-pub(super) fn public_input_type_to_string(public_input: &air_ir::PublicInput) -> String {
+/// The name of the field using the type parameter of a generic base field in a `PublicInputs`
+/// struct without any public input.
+const PHANTOM_FIELD: &str = "_base_field";
+
+pub(super) fn public_input_type_to_string(
+    public_input: &air_ir::PublicInput,
+    base_field: &BaseField,
+) -> String {
     match public_input {
-        air_ir::PublicInput::Vector { size, .. } => format!("[Felt; {size}]"),
-        air_ir::PublicInput::Table { size, .. } => format!("Vec<[Felt; {size}]>"),
+        air_ir::PublicInput::Vector { size, .. } => format!("[{base_field}; {size}]"),
+        air_ir::PublicInput::Table { size, .. } => format!("Vec<[{base_field}; {size}]>"),
     }
 }
 
 /// Updates the provided scope with a public input.
-pub(super) fn add_public_inputs_struct(scope: &mut Scope, ir: &Air) {
+pub(super) fn add_public_inputs_struct(scope: &mut Scope, ir: &Air, base_field: &BaseField) {
     let name = "PublicInputs";
+    // the fields are listed in declaration order, but always flattened in the canonical order
+    let declared_inputs = ir.declared_public_inputs();
+
     // define the PublicInputs struct.
-    let pub_inputs_struct = scope.new_struct(name).vis("pub");
+    let pub_inputs_struct = scope
+        .new_struct(name)
+        .vis("pub")
+        .doc(
+            "The public inputs of the AIR.\n\n\
+             They are serialized and flattened by `to_elements` in the canonical order, sorted by \
+             name,\nwhich is the order in which every verifier absorbs them into the transcript.",
+        )
+        .derive("Clone")
+        .derive("Debug")
+        .derive("PartialEq")
+        .derive("Eq");
+    base_field.declare_on_struct(pub_inputs_struct);
 
-    for public_input in ir.public_inputs() {
-        pub_inputs_struct
-            .field(public_input.name().as_str(), public_input_type_to_string(public_input));
+    for public_input in declared_inputs.iter() {
+        let field = pub_inputs_struct.new_field(
+            public_input.name().as_str(),
+            public_input_type_to_string(public_input, base_field),
+        );
+        // document the names of the columns of a table, if it declares them, and whether its
+        // number of rows is committed to
+        let mut docs = vec![];
+        if !public_input.columns().is_empty() {
+            let columns: Vec<String> =
+                public_input.columns().iter().map(|column| format!("`{column}`")).collect();
+            docs.push(format!("The columns of each row are: {}.", columns.join(", ")));
+        }
+        if public_input.is_commitment() {
+            docs.push(
+                "The rows are prefixed with their number by `to_elements`, as the table is \
+                 annotated\nwith `@commitment`."
+                    .to_string(),
+            );
+        }
+        if !docs.is_empty() {
+            field.doc(docs.join("\n\n"));
+        }
+    }
+
+    // the type parameter of a generic base field must be used by a field of the struct
+    let needs_marker = base_field.is_generic() && declared_inputs.is_empty();
+    if needs_marker {
+        pub_inputs_struct.field(PHANTOM_FIELD, format!("core::marker::PhantomData<{base_field}>"));
     }
 
     // add the public inputs implementation block
     let base_impl = scope.new_impl(name);
+    base_field.declare_on_impl(base_impl);
 
-    let pub_inputs_values: Vec<String> =
-        ir.public_inputs().map(|input| input.name().to_string()).collect();
+    let mut declared_values: Vec<String> =
+        declared_inputs.iter().map(|input| input.name().to_string()).collect();
+    if needs_marker {
+        declared_values.push(format!("{PHANTOM_FIELD}: core::marker::PhantomData"));
+    }
 
-    // add a constructor for public inputs
+    // add a constructor for public inputs, taking them in declaration order
     let new_fn = base_impl
         .new_fn("new")
         .vis("pub")
         .ret("Self")
-        .line(format!("Self {{ {} }}", pub_inputs_values.join(", ")));
-    for public_input in ir.public_inputs() {
-        new_fn.arg(public_input.name().as_str(), public_input_type_to_string(public_input));
+        .line(format!("Self {{ {} }}", declared_values.join(", ")));
+    if declared_inputs
+        .iter()
+        .any(|input| matches!(input, air_ir::PublicInput::Table { .. }))
+    {
+        new_fn.doc(
+            "Each row of a table is one tuple of the bus initialized or finalized by it. The order \
+             of the\nrows does not affect the bus boundaries, but `to_elements` flattens them in \
+             order, so the\nprover and the verifier must be given the rows in the same order.",
+        );
+    }
+    for public_input in declared_inputs.iter() {
+        new_fn.arg(
+            public_input.name().as_str(),
+            public_input_type_to_string(public_input, base_field),
+        );
     }
 
-    add_serializable_impl(scope, pub_inputs_values.clone());
+    let pub_inputs_values: Vec<String> =
+        ir.public_inputs().map(|input| input.name().to_string()).collect();
+    add_serializable_impl(scope, base_field, pub_inputs_values);
 
     // add a to_elements implementation
-    let to_elements_impl = scope.new_impl("PublicInputs").impl_trait("ToElements<Felt>");
-    let to_elements_fn = to_elements_impl.new_fn("to_elements").arg_ref_self().ret("Vec<Felt>");
+    let to_elements_impl =
+        scope.new_impl("PublicInputs").impl_trait(format!("ToElements<{base_field}>"));
+    base_field.declare_on_impl(to_elements_impl);
+    let to_elements_fn = to_elements_impl
+        .new_fn("to_elements")
+        .arg_ref_self()
+        .ret(format!("Vec<{base_field}>"));
     to_elements_fn.line("let mut elements = Vec::new();");
     for public_input in ir.public_inputs() {
         match public_input {
@@ -49,6 +121,12 @@ pub(super) fn add_public_inputs_struct(scope: &mut Scope, ir: &Air) {
                     .line(format!("elements.extend_from_slice(&self.{});", public_input.name()));
             },
             air_ir::PublicInput::Table { .. } => {
+                if public_input.is_commitment() {
+                    to_elements_fn.line(format!(
+                        "elements.push({base_field}::from(self.{}.len() as u32));",
+                        public_input.name()
+                    ));
+                }
                 to_elements_fn.line(format!(
                     "self.{}.iter().for_each(|row| elements.extend_from_slice(row));",
                     public_input.name()
@@ -60,8 +138,9 @@ pub(super) fn add_public_inputs_struct(scope: &mut Scope, ir: &Air) {
 }
 
 /// Adds Serialization implementation for PublicInputs to the scope
-fn add_serializable_impl(scope: &mut Scope, pub_input_values: Vec<String>) {
+fn add_serializable_impl(scope: &mut Scope, base_field: &BaseField, pub_input_values: Vec<String>) {
     let serializable_impl = scope.new_impl("PublicInputs").impl_trait("Serializable");
+    base_field.declare_on_impl(serializable_impl);
     let write_into_fn = serializable_impl
         .new_fn("write_into")
         .generic("W: ByteWriter")
@@ -1,18 +1,18 @@
-use air_ir::{Air, TraceSegmentId};
+use air_ir::{Air, BusOpKind, BusType, ExpandedBus, NodeIndex, Operation, TraceSegmentId};
 
-use super::{Codegen, ElemType, Impl};
+use super::{BaseField, Codegen, ElemType, Impl, add_constraint_docs};
 
 // HELPERS TO GENERATE THE WINTERFELL TRANSITION CONSTRAINT METHODS
 // ================================================================================================
 
 /// Adds an implementation of the "evaluate_transition" method to the referenced Air implementation
 /// based on the data in the provided IR.
-pub(super) fn add_fn_evaluate_transition(impl_ref: &mut Impl, ir: &Air) {
+pub(super) fn add_fn_evaluate_transition(impl_ref: &mut Impl, ir: &Air, base_field: &BaseField) {
     // define the function.
     let evaluate_transition = impl_ref
         .new_fn("evaluate_transition")
         .arg_ref_self()
-        .generic("E: FieldElement<BaseField = Felt>")
+        .generic(format!("E: FieldElement<BaseField = {base_field}>"))
         .arg("frame", "&EvaluationFrame<E>")
         .arg("periodic_values", "&[E]")
         .arg("result", "&mut [E]");
@@ -22,12 +22,20 @@ pub(super) fn add_fn_evaluate_transition(impl_ref: &mut Impl, ir: &Air) {
     evaluate_transition.line("let main_next = frame.next();");
 
     // output the constraints.
-    add_constraints(evaluate_transition, ir, 0);
+    add_constraints(evaluate_transition, ir, base_field, 0, false);
 }
 
 /// Adds an implementation of the "evaluate_aux_transition" method to the referenced Air
 /// implementation based on the data in the provided IR.
-pub(super) fn add_fn_evaluate_aux_transition(impl_ref: &mut Impl, ir: &Air) {
+///
+/// When `split_buses` is set, the term of each operation of an expanded bus is bound to a `let`
+/// before the constraint of the bus is assigned, see [add_split_bus].
+pub(super) fn add_fn_evaluate_aux_transition(
+    impl_ref: &mut Impl,
+    ir: &Air,
+    base_field: &BaseField,
+    split_buses: bool,
+) {
     // define the function.
     let evaluate_aux_transition = impl_ref
         .new_fn("evaluate_aux_transition")
@@ -38,8 +46,8 @@ pub(super) fn add_fn_evaluate_aux_transition(impl_ref: &mut Impl, ir: &Air) {
         .arg("_periodic_values", "&[F]")
         .arg("aux_rand_elements", "&AuxRandElements<E>")
         .arg("result", "&mut [E]")
-        .bound("F", "FieldElement<BaseField = Felt>")
-        .bound("E", "FieldElement<BaseField = Felt> + ExtensionOf<F>");
+        .bound("F", format!("FieldElement<BaseField = {base_field}>"))
+        .bound("E", format!("FieldElement<BaseField = {base_field}> + ExtensionOf<F>"));
 
     // declare current and next trace row arrays.
     evaluate_aux_transition.line("let main_current = main_frame.current();");
@@ -48,17 +56,97 @@ pub(super) fn add_fn_evaluate_aux_transition(impl_ref: &mut Impl, ir: &Air) {
     evaluate_aux_transition.line("let aux_next = aux_frame.next();");
 
     // output the constraints.
-    add_constraints(evaluate_aux_transition, ir, 1);
+    add_constraints(evaluate_aux_transition, ir, base_field, 1, split_buses);
 }
 
 /// Iterates through the integrity constraints in the IR, and appends a line of generated code to
 /// the provided codegen function body for each constraint.
-fn add_constraints(func_body: &mut codegen::Function, ir: &Air, trace_segment: TraceSegmentId) {
+fn add_constraints(
+    func_body: &mut codegen::Function,
+    ir: &Air,
+    base_field: &BaseField,
+    trace_segment: TraceSegmentId,
+    split_buses: bool,
+) {
     for (idx, constraint) in ir.integrity_constraints(trace_segment).iter().enumerate() {
-        func_body.line(format!(
-            "result[{}] = {};",
-            idx,
-            constraint.node_index().to_string(ir, ElemType::Ext, trace_segment)
-        ));
+        let expr = match ir.expanded_bus(constraint).filter(|_| split_buses) {
+            Some(bus) => add_split_bus(func_body, ir, base_field, bus),
+            None => constraint.node_index().to_string(ir, base_field, ElemType::Ext, trace_segment),
+        };
+        add_constraint_docs(func_body, ir, constraint);
+        func_body.line(format!("result[{idx}] = {expr};"));
+    }
+}
+
+/// Appends a `let` to the function body for each operation of the expanded `bus`, and returns the
+/// expression of its integrity constraint in terms of these bindings.
+///
+/// For a multiset bus `p`, each `p_factor_i` is the factor `args * latch + 1 - latch` of the
+/// operation, and the constraint is `p * (inserted factors) - p' * (removed factors)`. For a
+/// logup bus `q`, each `q_factor_i` is the combined tuple of the operation, and the constraint is
+/// `q * prod + sum(inserted terms) - (q' * prod + sum(removed terms))`, where `prod` is the
+/// product of all factors and the term of each operation is its latch multiplied by all other
+/// factors.
+fn add_split_bus(
+    func_body: &mut codegen::Function,
+    ir: &Air,
+    base_field: &BaseField,
+    bus: &ExpandedBus,
+) -> String {
+    let operand = |index: &NodeIndex| {
+        let expr = index.to_string(ir, base_field, ElemType::Ext, 1);
+        match ir.constraint_graph().node(index).op() {
+            Operation::Value(_) => expr,
+            _ => format!("({expr})"),
+        }
+    };
+    let name = bus.name;
+    let factor = |i: usize| format!("{name}_factor_{i}");
+    let column = operand(&bus.column);
+    let column_next = operand(&bus.column_next);
+
+    match bus.bus_type {
+        BusType::Multiset => {
+            let one = base_field.constant(1, ElemType::Ext);
+            let mut inserted = vec![];
+            let mut removed = vec![];
+            for (i, op) in bus.ops.iter().enumerate() {
+                let (args, latch) = (operand(&op.args_combined), operand(&op.latch));
+                func_body.line(format!("let {} = {args} * {latch} + {one} - {latch};", factor(i)));
+                match op.kind {
+                    BusOpKind::Insert => inserted.push(factor(i)),
+                    BusOpKind::Remove => removed.push(factor(i)),
+                }
+            }
+            let side = |column: &str, factors: Vec<String>| {
+                factors.into_iter().fold(column.to_string(), |acc, f| format!("{acc} * {f}"))
+            };
+            format!("{} - {}", side(&column, inserted), side(&column_next, removed))
+        },
+        BusType::Logup => {
+            for (i, op) in bus.ops.iter().enumerate() {
+                let args = op.args_combined.to_string(ir, base_field, ElemType::Ext, 1);
+                func_body.line(format!("let {} = {args};", factor(i)));
+            }
+            // The product of the factors of all operations but `except`, multiplied by `rhs`
+            let term = |except: Option<usize>, rhs: String| {
+                (0..bus.ops.len())
+                    .filter(|i| Some(*i) != except)
+                    .map(&factor)
+                    .chain([rhs])
+                    .collect::<Vec<_>>()
+                    .join(" * ")
+            };
+            let mut lhs = vec![term(None, column)];
+            let mut rhs = vec![term(None, column_next)];
+            for (i, op) in bus.ops.iter().enumerate() {
+                let term = term(Some(i), operand(&op.latch));
+                match op.kind {
+                    BusOpKind::Insert => lhs.push(term),
+                    BusOpKind::Remove => rhs.push(term),
+                }
+            }
+            format!("{} - ({})", lhs.join(" + "), rhs.join(" + "))
+        },
     }
 }
@@ -0,0 +1,150 @@
+use core::fmt;
+
+use codegen::{Function, Impl, Struct};
+
+use super::{DEFAULT_BASE_FIELD, Scope, air::ElemType};
+
+/// The modulus of the 64-bit field over which constants are reduced by the compiler, i.e.
+/// `2^64 - 2^32 + 1`.
+const FIELD_MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+/// The name of the type parameter of the generated Air when it is generic over its base field.
+const TYPE_PARAM: &str = "B";
+
+/// The bounds required by Winterfell on the base field of an Air.
+const TYPE_PARAM_BOUNDS: &str = "StarkField + ExtensibleField<2> + ExtensibleField<3>";
+
+/// The name of the helper converting the constants which do not fit in a `u32` to elements of a
+/// generic base field.
+const CONSTANT_HELPER: &str = "base_constant";
+
+/// The base field of the generated Air.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BaseField {
+    /// A concrete type, which is the Winterfell 64-bit field imported under the given name unless
+    /// the preamble is overridden
+    Concrete(String),
+    /// The type parameter `B` of the generated structs and implementations
+    Generic,
+}
+impl Default for BaseField {
+    fn default() -> Self {
+        Self::Concrete(DEFAULT_BASE_FIELD.to_string())
+    }
+}
+impl BaseField {
+    /// Returns true if the generated Air is generic over its base field.
+    pub fn is_generic(&self) -> bool {
+        matches!(self, Self::Generic)
+    }
+
+    /// Returns the name of a generated type, with the type parameter of the base field if it is
+    /// generic, e.g. `PublicInputs<B>`.
+    pub fn with_params(&self, name: &str) -> String {
+        match self {
+            Self::Concrete(_) => name.to_string(),
+            Self::Generic => format!("{name}<{TYPE_PARAM}>"),
+        }
+    }
+
+    /// Declares the type parameter of the base field on the given struct, if it is generic.
+    pub fn declare_on_struct(&self, item: &mut Struct) {
+        if let Self::Generic = self {
+            item.generic(TYPE_PARAM).bound(TYPE_PARAM, TYPE_PARAM_BOUNDS);
+        }
+    }
+
+    /// Declares the type parameter of the base field on the given implementation, and applies it
+    /// to the implemented type, if it is generic.
+    pub fn declare_on_impl(&self, item: &mut Impl) {
+        if let Self::Generic = self {
+            item.generic(TYPE_PARAM)
+                .target_generic(TYPE_PARAM)
+                .bound(TYPE_PARAM, TYPE_PARAM_BOUNDS);
+        }
+    }
+
+    /// Returns the Rust expression of the given constant, as an element of the base field or of
+    /// its extension `E`.
+    ///
+    /// The constants are reduced modulo the 64-bit field by the compiler, so when the base field is
+    /// generic, they are emitted in their signed form, e.g. `-B::ONE` rather than `2^64 - 2^32`,
+    /// which is only valid in the 64-bit field. Constants which do not fit in a `u32` are converted
+    /// by [CONSTANT_HELPER], which panics if they are not smaller than the modulus of the field.
+    pub fn constant(&self, value: u64, elem_type: ElemType) -> String {
+        let base_field = match self {
+            Self::Concrete(name) => name,
+            Self::Generic => return self.generic_constant(value, elem_type),
+        };
+        match (value, elem_type) {
+            (0, ElemType::Base) => format!("{base_field}::ZERO"),
+            (1, ElemType::Base) => format!("{base_field}::ONE"),
+            (value, ElemType::Base) => format!("{base_field}::new({value})"),
+            (0, ElemType::Ext) => "E::ZERO".to_string(),
+            (1, ElemType::Ext) => "E::ONE".to_string(),
+            (value, ElemType::Ext) => format!("E::from({base_field}::new({value}_u64))"),
+        }
+    }
+
+    fn generic_constant(&self, value: u64, elem_type: ElemType) -> String {
+        let (sign, magnitude) = if value > FIELD_MODULUS / 2 && value < FIELD_MODULUS {
+            ("-", FIELD_MODULUS - value)
+        } else {
+            ("", value)
+        };
+        let elem = match elem_type {
+            ElemType::Base => TYPE_PARAM,
+            ElemType::Ext => "E",
+        };
+        let base = match u32::try_from(magnitude) {
+            Ok(0) => return format!("{elem}::ZERO"),
+            Ok(1) => return format!("{sign}{elem}::ONE"),
+            Ok(magnitude) => format!("{TYPE_PARAM}::from({magnitude}_u32)"),
+            Err(_) => format!("{CONSTANT_HELPER}::<{TYPE_PARAM}>({magnitude})"),
+        };
+        match elem_type {
+            ElemType::Base => format!("{sign}{base}"),
+            ElemType::Ext => format!("{sign}E::from({base})"),
+        }
+    }
+
+    /// Adds the helper converting constants to elements of the base field, if it is generic.
+    pub fn add_constant_helper(&self, scope: &mut Scope) {
+        if let Self::Concrete(_) = self {
+            return;
+        }
+        let helper: &mut Function = scope
+            .new_fn(CONSTANT_HELPER)
+            .allow("dead_code")
+            .doc(
+                "Converts a constant of the AirScript program to an element of the base field.\n\n\
+                 The constants are not reduced modulo the base field, which is unknown when the \
+                 code is generated,\nso this panics if the constant is not smaller than its \
+                 modulus.",
+            )
+            .generic(format!("{TYPE_PARAM}: StarkField"))
+            .arg("value", "u64")
+            .ret(TYPE_PARAM);
+        helper.line(format!("let modulus = {TYPE_PARAM}::get_modulus_le_bytes();"));
+        helper.line("let (low_bytes, high_bytes) = modulus.split_at(modulus.len().min(8));");
+        helper.line("let mut bytes = [0_u8; 8];");
+        helper.line("bytes[..low_bytes.len()].copy_from_slice(low_bytes);");
+        helper.line(
+            "assert!(\n    high_bytes.iter().any(|byte| *byte != 0) || value < \
+             u64::from_le_bytes(bytes),\n    \"the constant {value} is not an element of the base \
+             field\"\n);",
+        );
+        helper.line(format!(
+            "{TYPE_PARAM}::from((value >> 32) as u32) * ({TYPE_PARAM}::from(u32::MAX) + \
+             {TYPE_PARAM}::ONE) + {TYPE_PARAM}::from(value as u32)"
+        ));
+    }
+}
+impl fmt::Display for BaseField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Concrete(name) => f.write_str(name),
+            Self::Generic => f.write_str(TYPE_PARAM),
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use super::Scope;
+use super::{BaseField, Scope};
 
-/// Adds the required imports to the provided scope.
-pub(super) fn add_imports(scope: &mut Scope) {
+/// Adds the required imports to the provided scope, with the Winterfell base field imported as
+/// `base_field`, or the traits bounding it if it is generic.
+pub(super) fn add_imports(scope: &mut Scope, base_field: &BaseField) {
     // add winterfell imports
     scope.import("winter_air", "Air");
     scope.import("winter_air", "AirContext");
@@ -11,9 +12,17 @@ pub(super) fn add_imports(scope: &mut Scope) {
     scope.import("winter_air", "ProofOptions as WinterProofOptions");
     scope.import("winter_air", "TransitionConstraintDegree");
     scope.import("winter_air", "TraceInfo");
-    scope.import("winter_math::fields::f64", "BaseElement as Felt");
+    if let BaseField::Concrete(name) = base_field {
+        scope.import("winter_math::fields::f64", &format!("BaseElement as {name}"));
+    }
+    if base_field.is_generic() {
+        scope.import("winter_math", "ExtensibleField");
+    }
     scope.import("winter_math", "ExtensionOf");
     scope.import("winter_math", "FieldElement");
+    if base_field.is_generic() {
+        scope.import("winter_math", "StarkField");
+    }
     scope.import("winter_math", "ToElements");
     scope.import("winter_utils", "ByteWriter");
     scope.import("winter_utils", "Serializable");
@@ -1,28 +1,160 @@
-use air_ir::Air;
-use codegen::{Impl, Scope};
+use air_ir::{Air, Provenance};
+use codegen::Impl;
+pub use codegen::{self, Scope};
+
+use self::field::BaseField;
 
 mod air;
+mod field;
 mod imports;
+#[cfg(test)]
+mod tests;
 
 // GENERATE RUST CODE FOR WINTERFELL AIR
 // ================================================================================================
 
+/// The name of the base field type of the generated Air, unless overridden with
+/// [CodeGenerator::with_base_field].
+pub const DEFAULT_BASE_FIELD: &str = "Felt";
+
+/// A callback which is given the [Scope] of the generated code, along with the [Air] it is
+/// generated from, in order to append custom items to the generated code.
+pub type Hook = Box<dyn Fn(&mut Scope, &Air)>;
+
+/// The stages of the code generation after which [Hook]s can be run.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stage {
+    /// After the `PublicInputs` struct and its implementations have been emitted
+    PublicInputs,
+    /// After the Air struct and its base implementation have been emitted
+    AirStruct,
+    /// After the implementation of the Winterfell `Air` trait has been emitted
+    AirTrait,
+}
+
 /// CodeGenerator is used to generate a Rust implementation of the Winterfell STARK prover library's
 /// Air trait. The generated Air expresses the constraints specified by the AirIR used to build the
 /// CodeGenerator.
-pub struct CodeGenerator;
+///
+/// The generated code can be extended by registering [Hook]s, which are run after each [Stage] of
+/// the code generation, and the `use` preamble of the generated code can be overridden.
+#[derive(Default)]
+pub struct CodeGenerator {
+    preamble: Option<Hook>,
+    hooks: Vec<(Stage, Hook)>,
+    provenance: Option<Provenance>,
+    base_field: BaseField,
+    split_buses: bool,
+}
+impl CodeGenerator {
+    /// Creates a new [CodeGenerator] without any hooks, emitting the default imports.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the base field type of the generated Air, which is `Felt` by default.
+    ///
+    /// The default imports bring the Winterfell 64-bit field into scope under this name, so a
+    /// custom preamble must import or define a type with this name.
+    pub fn with_base_field(mut self, name: impl Into<String>) -> Self {
+        self.base_field = BaseField::Concrete(name.into());
+        self
+    }
+
+    /// Makes the generated Air generic over its base field, which becomes the type parameter `B`
+    /// of the `PublicInputs` and Air structs and of their implementations.
+    ///
+    /// As the modulus of the base field is unknown when the code is generated, the constants of
+    /// the program are emitted unreduced, and those which do not fit in a `u32` are converted by a
+    /// generated `base_constant` helper, which checks that they are elements of the base field.
+    pub fn with_generic_base_field(mut self) -> Self {
+        self.base_field = BaseField::Generic;
+        self
+    }
+
+    /// Generates the integrity constraint of each expanded bus from its operations, binding the
+    /// term of each operation to a `let` before combining them, rather than as a single expression
+    /// whose size grows with the number of operations of the bus.
+    pub fn with_split_buses(mut self) -> Self {
+        self.split_buses = true;
+        self
+    }
+
+    /// Replaces the default Winterfell imports with the ones added to the [Scope] by `preamble`.
+    pub fn with_preamble(mut self, preamble: impl Fn(&mut Scope, &Air) + 'static) -> Self {
+        self.preamble = Some(Box::new(preamble));
+        self
+    }
+
+    /// Emits the given [Provenance] as a comment header at the top of the generated code.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Registers a hook to be run after the given [Stage]. Hooks registered for the same stage are
+    /// run in the order in which they were registered.
+    pub fn with_hook(mut self, stage: Stage, hook: impl Fn(&mut Scope, &Air) + 'static) -> Self {
+        self.hooks.push((stage, Box::new(hook)));
+        self
+    }
+
+    /// Registers a hook to be run after the `PublicInputs` struct has been emitted.
+    pub fn after_public_inputs(self, hook: impl Fn(&mut Scope, &Air) + 'static) -> Self {
+        self.with_hook(Stage::PublicInputs, hook)
+    }
+
+    /// Registers a hook to be run after the Air struct has been emitted.
+    pub fn after_air_struct(self, hook: impl Fn(&mut Scope, &Air) + 'static) -> Self {
+        self.with_hook(Stage::AirStruct, hook)
+    }
+
+    /// Registers a hook to be run after the Winterfell `Air` trait implementation has been
+    /// emitted.
+    pub fn after_air_trait(self, hook: impl Fn(&mut Scope, &Air) + 'static) -> Self {
+        self.with_hook(Stage::AirTrait, hook)
+    }
+
+    fn run_hooks(&self, stage: Stage, scope: &mut Scope, ir: &Air) {
+        for (_, hook) in self.hooks.iter().filter(|(s, _)| *s == stage) {
+            hook(scope, ir);
+        }
+    }
+}
 impl air_ir::CodeGenerator for CodeGenerator {
     type Output = String;
 
     fn generate(&self, ir: &Air) -> anyhow::Result<Self::Output> {
+        air::validate_trace_widths(ir)?;
+
         let mut scope = Scope::new();
 
-        // add winterfell imports.
-        imports::add_imports(&mut scope);
+        // add winterfell imports, unless overridden.
+        match &self.preamble {
+            Some(preamble) => preamble(&mut scope, ir),
+            None => imports::add_imports(&mut scope, &self.base_field),
+        }
+        self.base_field.add_constant_helper(&mut scope);
+
+        // add the Public Inputs struct and its base implementation.
+        air::add_public_inputs(&mut scope, ir, &self.base_field);
+        self.run_hooks(Stage::PublicInputs, &mut scope, ir);
+
+        // add the Air struct and its base implementation.
+        air::add_air_struct(&mut scope, ir, &self.base_field);
+        self.run_hooks(Stage::AirStruct, &mut scope, ir);
 
-        // add an Air struct and Winterfell Air trait implementation for the provided AirIR.
-        air::add_air(&mut scope, ir);
+        // add Winterfell Air trait implementation for the provided AirIR.
+        air::add_air_trait(&mut scope, ir, &self.base_field, self.split_buses);
+        self.run_hooks(Stage::AirTrait, &mut scope, ir);
 
-        Ok(scope.to_string())
+        match &self.provenance {
+            Some(provenance) => {
+                let header: String =
+                    provenance.header_lines().iter().map(|line| format!("// {line}\n")).collect();
+                Ok(format!("{header}\n{}", scope.to_string()))
+            },
+            None => Ok(scope.to_string()),
+        }
     }
 }
@@ -0,0 +1,534 @@
+use std::sync::Arc;
+
+use air_ir::{Air, CodeGenerator as _, Provenance};
+use air_pass::Pass;
+use miden_diagnostics::{
+    CodeMap, DefaultEmitter, DiagnosticsHandler, term::termcolor::ColorChoice,
+};
+
+use crate::{CodeGenerator, Scope, Stage};
+
+const SOURCE: &str = "
+def PubInputsAir
+
+trace_columns {
+    main: [a, b],
+}
+
+public_inputs {
+    stack_inputs: [2],
+}
+
+boundary_constraints {
+    enf a.first = stack_inputs[0];
+}
+
+integrity_constraints {
+    enf a' = a + b;
+}";
+
+/// Compiles an AirScript program to an [Air].
+fn compile(source: &str) -> Air {
+    let code_map = Arc::new(CodeMap::new());
+    let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), code_map.clone(), emitter);
+
+    air_parser::parse(&diagnostics, code_map, source)
+        .map_err(air_ir::CompileError::Parse)
+        .and_then(|ast| {
+            let mut pipeline = air_parser::transforms::ConstantPropagation::new(&diagnostics)
+                .chain(mir::passes::AstToMir::new(&diagnostics))
+                .chain(mir::passes::Inlining::new(&diagnostics))
+                .chain(mir::passes::Unrolling::new(&diagnostics))
+                .chain(air_ir::passes::MirToAir::new(&diagnostics))
+                .chain(air_ir::passes::BusOpExpand::new(&diagnostics));
+            pipeline.run(ast)
+        })
+        .expect("lowering failed")
+}
+
+/// Adds an implementation of `From<&MyTrace>` for the `PublicInputs` struct, reading the public
+/// inputs from the first row of the main trace.
+fn add_from_trace(scope: &mut Scope, ir: &Air) {
+    let main_width = ir.trace_segment_widths[0];
+    let from_fn = scope
+        .new_impl("PublicInputs")
+        .impl_trait("From<&MyTrace>")
+        .new_fn("from")
+        .arg("trace", "&MyTrace")
+        .ret("Self");
+    let args: Vec<String> = ir
+        .public_inputs()
+        .map(|_| format!("core::array::from_fn(|i| trace.get(i % {main_width}, 0))"))
+        .collect();
+    from_fn.line(format!("Self::new({})", args.join(", ")));
+}
+
+#[test]
+fn hooks_are_run_after_each_stage() {
+    let air = compile(SOURCE);
+    let code = CodeGenerator::new()
+        .after_public_inputs(add_from_trace)
+        .after_air_struct(|scope, ir| {
+            scope.raw(format!("// after {} struct", ir.name()));
+        })
+        .with_hook(Stage::AirTrait, |scope, _| {
+            scope.raw("// after air trait");
+        })
+        .generate(&air)
+        .unwrap();
+
+    let public_inputs_struct = code.find("pub struct PublicInputs").unwrap();
+    let from_impl = code.find("impl From<&MyTrace> for PublicInputs {").unwrap();
+    let air_struct = code.find("pub struct PubInputsAir").unwrap();
+    let after_air_struct = code.find("// after PubInputsAir struct").unwrap();
+    let air_trait = code.find("impl Air for PubInputsAir").unwrap();
+    let after_air_trait = code.find("// after air trait").unwrap();
+
+    assert!(public_inputs_struct < from_impl);
+    assert!(from_impl < air_struct);
+    assert!(air_struct < after_air_struct);
+    assert!(after_air_struct < air_trait);
+    assert!(air_trait < after_air_trait);
+    assert!(code.contains("Self::new(core::array::from_fn(|i| trace.get(i % 2, 0)))"));
+}
+
+#[test]
+fn preamble_can_be_overridden() {
+    let air = compile(SOURCE);
+
+    let code = CodeGenerator::new().generate(&air).unwrap();
+    assert!(code.contains("use winter_air::"));
+
+    let code = CodeGenerator::new()
+        .with_preamble(|scope, _| {
+            scope.import("crate::winter_air", "Air");
+        })
+        .generate(&air)
+        .unwrap();
+    assert!(code.contains("use crate::winter_air::Air;"));
+    assert!(!code.contains("use winter_air::"));
+}
+
+#[test]
+fn provenance_is_emitted_as_header() {
+    let air = compile(SOURCE);
+    let provenance = Provenance {
+        compiler_version: "0.5.0".to_string(),
+        source_hash: "abcd".to_string(),
+        options: vec!["pipeline=WithMIR".to_string()],
+    };
+
+    let code = CodeGenerator::new().generate(&air).unwrap();
+    assert_eq!(Provenance::from_header(&code), None);
+
+    let code = CodeGenerator::new().with_provenance(provenance.clone()).generate(&air).unwrap();
+    assert!(code.starts_with("// airscript-version: 0.5.0\n"));
+    assert_eq!(Provenance::from_header(&code), Some(provenance));
+}
+
+#[test]
+fn simplified_constraints_omit_identities() {
+    let source = SOURCE.replace("enf a' = a + b;", "enf a' = a * 1 + b - 0;");
+    let air = compile(&source);
+    let code = CodeGenerator::new().generate(&air).unwrap();
+    assert!(code.contains("E::ONE"));
+    assert!(code.contains("E::ZERO"));
+
+    let code_map = Arc::new(CodeMap::new());
+    let emitter = Arc::new(DefaultEmitter::new(ColorChoice::Auto));
+    let diagnostics = DiagnosticsHandler::new(Default::default(), code_map, emitter);
+    let air = air_ir::passes::Simplify::new(&diagnostics).run(air).unwrap();
+    let code = CodeGenerator::new().generate(&air).unwrap();
+    assert!(!code.contains("E::ONE"));
+    assert!(!code.contains("E::ZERO"));
+    assert_eq!(code, CodeGenerator::new().generate(&compile(SOURCE)).unwrap());
+}
+
+#[test]
+fn shifted_periodic_columns_are_declared_after_periodic_columns() {
+    let source = "
+def PeriodicNext
+
+trace_columns {
+    main: [a, b],
+}
+
+periodic_columns {
+    k: [1, 0, 0, 0],
+    m: [1, 0],
+}
+
+public_inputs {
+    stack_inputs: [2],
+}
+
+boundary_constraints {
+    enf a.first = 0;
+}
+
+integrity_constraints {
+    enf a' = a + k' - k;
+    enf b * m = 0;
+}";
+    let code = CodeGenerator::new().generate(&compile(source)).unwrap();
+    // The copy of `k` is rotated by one row, and declared after `k` and `m`
+    assert!(code.contains(
+        "vec![vec![Felt::ONE, Felt::ZERO, Felt::ZERO, Felt::ZERO], vec![Felt::ONE, Felt::ZERO], \
+         vec![Felt::ZERO, Felt::ZERO, Felt::ZERO, Felt::ONE]]"
+    ));
+    assert!(code.contains("periodic_values[2] - periodic_values[0]"));
+}
+
+#[test]
+fn trace_length_is_validated_against_periodic_columns() {
+    // Short cycles are already covered by the minimum trace length of Winterfell
+    let code = CodeGenerator::new().generate(&compile(SOURCE)).unwrap();
+    assert!(!code.contains("trace_info.length()"));
+
+    let cycle = vec!["1"; 1024].join(", ");
+    let source = SOURCE
+        .replace(
+            "public_inputs {",
+            &format!("periodic_columns {{\n    k: [{cycle}],\n}}\n\npublic_inputs {{"),
+        )
+        .replace("enf a' = a + b;", "enf a' = a + b * k;");
+    let code = CodeGenerator::new().generate(&compile(&source)).unwrap();
+    assert!(code.contains(
+        "assert!(trace_info.length() >= 1024, \"the trace length must be at least 2^10: "
+    ));
+    assert!(code.contains("periodic column 'k' has a cycle of length 1024\");"));
+}
+
+#[test]
+fn trace_widths_are_validated_against_winterfell_limits() {
+    let with_width =
+        |width: usize| SOURCE.replace("main: [a, b],", &format!("main: [a, b, c[{}]],", width - 2));
+
+    let air = compile(&with_width(255));
+    assert_eq!(air.trace_segment_widths[0], 255);
+    assert!(CodeGenerator::new().generate(&air).is_ok());
+
+    let air = compile(&with_width(256));
+    assert_eq!(air.trace_segment_widths[0], 256);
+    let err = CodeGenerator::new().generate(&air).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "trace segment '$main' has width 256, which exceeds the maximum supported 255 for the \
+         Winterfell backend"
+    );
+}
+
+#[test]
+fn base_field_can_be_renamed() {
+    let source = "
+def BaseFieldAir
+
+trace_columns {
+    main: [a, b],
+}
+
+periodic_columns {
+    k: [1, 0, 0, 0],
+}
+
+public_inputs {
+    stack_inputs: [2],
+}
+
+boundary_constraints {
+    enf a.first = stack_inputs[0];
+    enf b.last = 3;
+}
+
+integrity_constraints {
+    enf a' = a + b * k + 2;
+}";
+    let air = compile(source);
+    let default = CodeGenerator::new().generate(&air).unwrap();
+    let code = CodeGenerator::new().with_base_field("Goldilocks").generate(&air).unwrap();
+
+    assert!(code.contains("use winter_math::fields::f64::BaseElement as Goldilocks;"));
+    assert!(code.contains("type BaseField = Goldilocks;"));
+    assert!(!code.contains("Felt"));
+    assert_eq!(code, default.replace("Felt", "Goldilocks"));
+}
+
+#[test]
+fn aux_constraints_read_main_next_row_from_main_frame() {
+    let source = "
+def BusesNextRowAir
+
+trace_columns {
+    main: [s, a],
+}
+
+buses {
+    multiset p,
+}
+
+public_inputs {
+    stack_inputs: [2],
+}
+
+boundary_constraints {
+    enf p.first = null;
+    enf p.last = null;
+}
+
+integrity_constraints {
+    p.insert(a') when s;
+}";
+    let code = CodeGenerator::new().generate(&compile(source)).unwrap();
+    let aux_transition = &code[code.find("fn evaluate_aux_transition").unwrap()..];
+    assert!(aux_transition.contains("E::from(main_next[1])"));
+    assert!(aux_transition.contains("aux_next[0]"));
+    assert!(!aux_transition.contains("aux_next[1]"));
+}
+
+#[test]
+fn negative_constants_are_reduced() {
+    let source = "
+def NegativeConstants
+
+trace_columns {
+    main: [a, b],
+}
+
+public_inputs {
+    stack_inputs: [2],
+}
+
+boundary_constraints {
+    enf a.first = -1;
+    enf b.first = -0;
+}
+
+integrity_constraints {
+    enf a' = a * (-2)^3;
+}";
+    let code = CodeGenerator::new().generate(&compile(source)).unwrap();
+    assert!(code.contains("Assertion::single(0, 0, Felt::new(18446744069414584320))"));
+    assert!(code.contains("Assertion::single(1, 0, Felt::ZERO)"));
+    assert!(code.contains("main_current[0] * E::from(Felt::new(18446744069414584313_u64))"));
+    assert!(!code.contains("-1"));
+}
+
+#[test]
+fn renamed_public_inputs_are_used_in_struct() {
+    let mut air = compile(SOURCE);
+    let old = air_ir::Identifier::new(Default::default(), air_ir::Symbol::intern("stack_inputs"));
+    let new = air_ir::Identifier::new(Default::default(), air_ir::Symbol::intern("inputs"));
+    air.rename_public_input(old, new).unwrap();
+
+    let code = CodeGenerator::new().generate(&air).unwrap();
+    assert!(code.contains("pub struct PublicInputs {\n    inputs: [Felt; 2],\n}"));
+    assert!(code.contains("pub fn new(inputs: [Felt; 2]) -> Self {\n        Self { inputs }"));
+    assert!(code.contains("Assertion::single(0, 0, self.inputs[0])"));
+    assert!(!code.contains("stack_inputs"));
+}
+
+#[test]
+fn public_inputs_are_declared_in_order_and_flattened_by_name() {
+    let source = "
+def test
+trace_columns {
+    main: [a],
+}
+public_inputs {
+    zeta: [2],
+    alpha: [3],
+}
+boundary_constraints {
+    enf a.first = zeta[0];
+    enf a.last = alpha[0];
+}
+integrity_constraints {
+    enf a' = a;
+}";
+    let code = CodeGenerator::new().generate(&compile(source)).unwrap();
+    assert!(code.contains(
+        "#[derive(Clone, Debug, PartialEq, Eq)]\npub struct PublicInputs {\n    zeta: [Felt; 2],\n    alpha: [Felt; 3],\n}"
+    ));
+    assert!(code.contains("pub fn new(zeta: [Felt; 2], alpha: [Felt; 3]) -> Self"));
+    assert!(code.contains(
+        "elements.extend_from_slice(&self.alpha);\n        elements.extend_from_slice(&self.zeta);"
+    ));
+}
+
+#[test]
+fn base_field_can_be_generic() {
+    let source = "
+def GenericAir
+
+trace_columns {
+    main: [a, b],
+}
+
+periodic_columns {
+    k: [1, 0, 2, 0],
+}
+
+public_inputs {
+    stack_inputs: [2],
+}
+
+boundary_constraints {
+    enf a.first = -1;
+    enf b.last = 4294967296;
+}
+
+integrity_constraints {
+    enf a' = a * (-2)^3 + b * k;
+}";
+    let code = CodeGenerator::new()
+        .with_generic_base_field()
+        .generate(&compile(source))
+        .unwrap();
+
+    assert!(!code.contains("Felt"));
+    assert!(
+        code.contains("use winter_math::{ExtensibleField, ExtensionOf, FieldElement, StarkField")
+    );
+    assert!(code.contains("fn base_constant<B: StarkField>(value: u64) -> B {"));
+    assert!(code.contains(
+        "pub struct PublicInputs<B>\nwhere B: StarkField + ExtensibleField<2> + ExtensibleField<3>,"
+    ));
+    assert!(code.contains("impl<B> ToElements<B> for PublicInputs<B>\nwhere B:"));
+    assert!(code.contains("impl<B> Air for GenericAir<B>\nwhere B:"));
+    assert!(code.contains("type PublicInputs = PublicInputs<B>;"));
+
+    // constants are emitted in their signed form, without assuming the modulus of the field
+    assert!(code.contains("vec![vec![B::ONE, B::ZERO, B::from(2_u32), B::ZERO]]"));
+    assert!(code.contains("Assertion::single(0, 0, -B::ONE)"));
+    assert!(
+        code.contains("Assertion::single(1, self.last_step(), base_constant::<B>(4294967296))")
+    );
+    assert!(code.contains("main_current[0] * -E::from(B::from(8_u32))"));
+}
+
+#[test]
+fn split_buses_bind_each_operation() {
+    for bus_type in ["multiset", "logup"] {
+        let ops: String = (0..32)
+            .map(|i| match i % 2 {
+                0 => format!("p.insert(a, {i}) when s;\n"),
+                _ => format!("p.remove(b, {i}) when s;\n"),
+            })
+            .collect();
+        let source = format!(
+            "
+def LargeBusAir
+
+trace_columns {{
+    main: [a, b, s],
+}}
+
+buses {{
+    {bus_type} p,
+}}
+
+public_inputs {{
+    stack_inputs: [2],
+}}
+
+boundary_constraints {{
+    enf p.first = null;
+    enf p.last = null;
+}}
+
+integrity_constraints {{
+    enf s^2 = s;
+    {ops}
+}}"
+        );
+        let air = compile(&source);
+        let monolithic = CodeGenerator::new().generate(&air).unwrap();
+        let code = CodeGenerator::new().with_split_buses().generate(&air).unwrap();
+        assert!(!monolithic.contains("let p_factor_0"));
+        for i in 0..32 {
+            assert!(code.contains(&format!("let p_factor_{i} = ")), "{bus_type}: {code}");
+        }
+    }
+}
+
+/// A program declaring several items of each kind out of order, whose names all start with `{p}`
+const INTERNING_SOURCE: &str = "
+def {p}Interning
+
+ev {p}zeta_check([x]) {
+    enf x^2 = x;
+}
+
+ev {p}alpha_check([x, y]) {
+    enf x' = x + y;
+}
+
+trace_columns {
+    main: [{p}s, {p}a, {p}b],
+}
+
+buses {
+    multiset {p}zeta_bus,
+    logup {p}alpha_bus,
+}
+
+public_inputs {
+    {p}zeta_inputs: [2],
+    {p}alpha_inputs: [2],
+}
+
+periodic_columns {
+    {p}zeta_k: [1, 0],
+    {p}alpha_k: [1, 1, 0, 0],
+}
+
+boundary_constraints {
+    enf {p}a.first = {p}zeta_inputs[0];
+    enf {p}b.first = {p}alpha_inputs[1];
+    enf {p}zeta_bus.first = null;
+    enf {p}zeta_bus.last = null;
+    enf {p}alpha_bus.first = null;
+    enf {p}alpha_bus.last = null;
+}
+
+integrity_constraints {
+    enf {p}zeta_check([{p}s]);
+    enf {p}alpha_check([{p}a, {p}b]);
+    enf {p}b' = {p}b * {p}zeta_k + {p}alpha_k;
+    {p}zeta_bus.insert({p}a) when {p}s;
+    {p}zeta_bus.remove({p}a) when {p}s;
+    {p}alpha_bus.insert({p}b) with {p}a;
+    {p}alpha_bus.remove({p}b) with {p}a;
+}";
+
+#[test]
+fn generated_code_does_not_depend_on_interning_order() {
+    // The names of each program are interned for the first time when it is compiled, in the order
+    // in which they appear, unless decoys with the same names are interned before in reverse order
+    let cold = CodeGenerator::new()
+        .generate(&compile(&INTERNING_SOURCE.replace("{p}", "wf_cold_")))
+        .unwrap();
+    let decoys = [
+        "zeta_check",
+        "alpha_check",
+        "s",
+        "a",
+        "b",
+        "zeta_bus",
+        "alpha_bus",
+        "zeta_inputs",
+        "alpha_inputs",
+        "zeta_k",
+        "alpha_k",
+        "Interning",
+    ];
+    for decoy in decoys.iter().rev() {
+        air_parser::Symbol::intern(format!("wf_warm_{decoy}"));
+    }
+    let warm = CodeGenerator::new()
+        .generate(&compile(&INTERNING_SOURCE.replace("{p}", "wf_warm_")))
+        .unwrap();
+
+    assert_eq!(warm.replace("wf_warm_", "wf_cold_"), cold);
+}
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use air_parser::ast::{self, Identifier};
+use air_parser::ast::{self, Identifier, TraceColumnIndex};
 use miden_diagnostics::{SourceSpan, Spanned};
 
 use crate::{
@@ -67,6 +67,15 @@ pub struct Bus {
     pub columns: Vec<Link<Op>>,
     /// selectors denoting when a value is present
     pub latches: Vec<Link<Op>>,
+    /// main trace columns into which the multiplicity of the bus is decomposed, from the least
+    /// significant bit, or empty if the multiplicity is not range checked
+    pub multiplicity_bits: Vec<TraceColumnIndex>,
+    /// index in `columns` and `latches` of the operation whose multiplicity is range checked,
+    /// i.e. the operation declared with `with`
+    pub range_checked_op: Option<usize>,
+    /// whether the bus is declared `extern`, i.e. defined by another program, in which case its
+    /// boundaries are left unconstrained
+    pub is_extern: bool,
     first: Link<Op>,
     last: Link<Op>,
     #[span]
@@ -79,6 +88,9 @@ impl std::hash::Hash for Bus {
         self.bus_type.hash(state);
         self.columns.hash(state);
         self.latches.hash(state);
+        self.multiplicity_bits.hash(state);
+        self.range_checked_op.hash(state);
+        self.is_extern.hash(state);
     }
 }
 
@@ -88,6 +100,9 @@ impl PartialEq for Bus {
             && self.bus_type == other.bus_type
             && self.columns == other.columns
             && self.latches == other.latches
+            && self.multiplicity_bits == other.multiplicity_bits
+            && self.range_checked_op == other.range_checked_op
+            && self.is_extern == other.is_extern
     }
 }
 
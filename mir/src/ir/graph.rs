@@ -1,6 +1,6 @@
 use std::{
     cell::{Ref, RefMut},
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap, btree_map::Entry},
 };
 
 use air_parser::ast::QualifiedIdentifier;
@@ -17,6 +17,12 @@ pub struct Graph {
     pub boundary_constraints_roots: ir::Link<Vec<ir::Link<ir::Op>>>,
     pub integrity_constraints_roots: ir::Link<Vec<ir::Link<ir::Op>>>,
     pub buses: BTreeMap<QualifiedIdentifier, ir::Link<ir::Bus>>,
+    // HashMap<EnfPtr, (Enf node, Call sites, innermost first)>, where nodes are held weakly so
+    // that recording their provenance does not keep them in the graph
+    provenance: HashMap<usize, (ir::BackLink<ir::Op>, Vec<ir::CallSiteInfo>)>,
+    // HashMap<ConstraintPtr, (Constraint node, Selectors, outermost first)>, for the constraints
+    // enforced under a `when` or `match` selector
+    selectors: HashMap<usize, (ir::BackLink<ir::Op>, Vec<ir::Link<ir::Op>>)>,
 }
 
 impl Graph {
@@ -25,7 +31,8 @@ impl Graph {
     }
 
     /// Inserts a function into the graph, returning an error if the root is not a [ir::Function],
-    /// or if the function already exists (declaration conflict).
+    /// or if the function already exists (declaration conflict), in which case the existing
+    /// function is kept.
     pub fn insert_function(
         &mut self,
         ident: QualifiedIdentifier,
@@ -34,10 +41,15 @@ impl Graph {
         if node.as_function().is_none() {
             return Err(CompileError::Failed);
         }
-        match self.functions.insert(ident, node) {
-            None => Ok(()),
-            Some(link) => {
-                if let ir::Root::None(_) = *link.borrow() {
+        match self.functions.entry(ident) {
+            Entry::Vacant(entry) => {
+                entry.insert(node);
+                Ok(())
+            },
+            Entry::Occupied(mut entry) => {
+                // Only a placeholder may be replaced, the existing declaration is left untouched
+                if matches!(*entry.get().borrow(), ir::Root::None(_)) {
+                    entry.insert(node);
                     Ok(())
                 } else {
                     Err(CompileError::Failed)
@@ -71,8 +83,14 @@ impl Graph {
         self.functions.values().cloned().collect()
     }
 
+    /// Queries all functions, by name
+    pub fn get_functions(&self) -> &BTreeMap<QualifiedIdentifier, ir::Link<ir::Root>> {
+        &self.functions
+    }
+
     /// Inserts an evaluator into the graph, returning an error if the root is not an
-    /// [ir::Evaluator], or if the evaluator already exists (declaration conflict).
+    /// [ir::Evaluator], or if the evaluator already exists (declaration conflict), in which case
+    /// the existing evaluator is kept.
     pub fn insert_evaluator(
         &mut self,
         ident: QualifiedIdentifier,
@@ -81,10 +99,15 @@ impl Graph {
         if node.as_evaluator().is_none() {
             return Err(CompileError::Failed);
         }
-        match self.evaluators.insert(ident, node) {
-            None => Ok(()),
-            Some(link) => {
-                if let ir::Root::None(_) = *link.borrow() {
+        match self.evaluators.entry(ident) {
+            Entry::Vacant(entry) => {
+                entry.insert(node);
+                Ok(())
+            },
+            Entry::Occupied(mut entry) => {
+                // Only a placeholder may be replaced, the existing declaration is left untouched
+                if matches!(*entry.get().borrow(), ir::Root::None(_)) {
+                    entry.insert(node);
                     Ok(())
                 } else {
                     Err(CompileError::Failed)
@@ -118,6 +141,11 @@ impl Graph {
         self.evaluators.values().cloned().collect()
     }
 
+    /// Queries all evaluators, by name
+    pub fn get_evaluators(&self) -> &BTreeMap<QualifiedIdentifier, ir::Link<ir::Root>> {
+        &self.evaluators
+    }
+
     /// Inserts a boundary constraint into the graph, if it does not already exist.
     pub fn insert_boundary_constraints_root(&mut self, root: ir::Link<ir::Op>) {
         if !self.boundary_constraints_roots.borrow().contains(&root) {
@@ -143,13 +171,19 @@ impl Graph {
     }
 
     /// Inserts a bus into the graph, returning an error
-    /// if the bus already exists (declaration conflict).
+    /// if the bus already exists (declaration conflict), in which case the existing bus is kept.
     pub fn insert_bus(
         &mut self,
         ident: QualifiedIdentifier,
         bus: ir::Link<ir::Bus>,
     ) -> Result<(), CompileError> {
-        self.buses.insert(ident, bus).map_or(Ok(()), |_| Err(CompileError::Failed))
+        match self.buses.entry(ident) {
+            Entry::Vacant(entry) => {
+                entry.insert(bus);
+                Ok(())
+            },
+            Entry::Occupied(_) => Err(CompileError::Failed),
+        }
     }
 
     /// Queries a given bus, returning a [ir::Link<ir::Bus>] if it exists.
@@ -170,4 +204,75 @@ impl Graph {
     pub fn get_bus_nodes(&self) -> Vec<ir::Link<ir::Bus>> {
         self.buses.values().cloned().collect()
     }
+
+    /// Queries the name of the given evaluator root, if it belongs to this graph.
+    pub fn get_evaluator_name(&self, root: &ir::Link<ir::Root>) -> Option<QualifiedIdentifier> {
+        self.evaluators
+            .iter()
+            .find(|(_, evaluator)| evaluator.get_ptr() == root.get_ptr())
+            .map(|(ident, _)| *ident)
+    }
+
+    /// Queries the calls through which the given constraint was inlined, innermost first, which
+    /// is empty if it was written directly in the constraints sections of the program.
+    pub fn get_provenance(&self, node: &ir::Link<ir::Op>) -> &[ir::CallSiteInfo] {
+        self.provenance
+            .get(&node.get_ptr())
+            .map(|(_, call_sites)| call_sites.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Queries all constraints which were inlined through calls, with these calls, innermost
+    /// first.
+    pub fn get_provenances(&self) -> Vec<(ir::Link<ir::Op>, Vec<ir::CallSiteInfo>)> {
+        self.provenance
+            .values()
+            .filter_map(|(node, call_sites)| Some((node.to_link()?, call_sites.clone())))
+            .collect()
+    }
+
+    /// Records the calls through which the given constraint was inlined, innermost first.
+    pub fn set_provenance(&mut self, node: ir::Link<ir::Op>, call_sites: Vec<ir::CallSiteInfo>) {
+        if call_sites.is_empty() {
+            self.provenance.remove(&node.get_ptr());
+        } else {
+            self.provenance.insert(node.get_ptr(), (node.into(), call_sites));
+        }
+    }
+
+    /// Queries the selectors under which the given constraint is enforced, outermost first, which
+    /// is empty if it is not enforced under a `when` or `match` selector.
+    pub fn get_selectors(&self, node: &ir::Link<ir::Op>) -> &[ir::Link<ir::Op>] {
+        self.selectors
+            .get(&node.get_ptr())
+            .map(|(_, selectors)| selectors.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Queries all constraints enforced under selectors, with these selectors, outermost first.
+    pub fn get_all_selectors(&self) -> Vec<(ir::Link<ir::Op>, Vec<ir::Link<ir::Op>>)> {
+        self.selectors
+            .values()
+            .filter_map(|(node, selectors)| Some((node.to_link()?, selectors.clone())))
+            .collect()
+    }
+
+    /// Records the selectors under which the given constraint is enforced, outermost first, i.e.
+    /// the factors it was multiplied by when its comprehension was unrolled.
+    pub fn set_selectors(&mut self, node: ir::Link<ir::Op>, selectors: Vec<ir::Link<ir::Op>>) {
+        if selectors.is_empty() {
+            self.selectors.remove(&node.get_ptr());
+        } else {
+            self.selectors.insert(node.get_ptr(), (node.into(), selectors));
+        }
+    }
+
+    /// Records that the constraint `to` was inlined through the same calls as `from`, e.g. when
+    /// `to` is a duplicate of `from`.
+    pub fn copy_provenance(&mut self, from: &ir::Link<ir::Op>, to: ir::Link<ir::Op>) {
+        let call_sites = self.get_provenance(from).to_vec();
+        if !call_sites.is_empty() {
+            self.set_provenance(to, call_sites);
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use air_parser::ast::TraceSegment;
+use air_parser::ast::{DocComments, TraceSegment, VirtualColumn};
 pub use air_parser::{
     Symbol,
     ast::{Identifier, PeriodicColumn, PublicInput, QualifiedIdentifier},
@@ -25,6 +25,11 @@ pub struct Mir {
     ///
     /// These are taken straight from the [air_parser::ast::Program] without modification.
     pub trace_columns: Vec<TraceSegment>,
+    /// The columns of the main trace declared virtual, with their definition.
+    ///
+    /// These are taken straight from the [air_parser::ast::Program] without modification, as
+    /// they are only lowered to compute their degree, see `air_ir::Air::virtual_columns`.
+    pub virtual_columns: Vec<VirtualColumn>,
     /// The periodic columns referenced by this program.
     ///
     /// These are taken straight from the [air_parser::ast::Program] without modification.
@@ -35,6 +40,15 @@ pub struct Mir {
     pub public_inputs: BTreeMap<Identifier, PublicInput>,
     /// The total number of elements in the random values array
     pub num_random_values: u16,
+    /// The span of the `buses` section of the program, if any.
+    ///
+    /// This is taken straight from the [air_parser::ast::Program] without modification.
+    pub buses_span: Option<SourceSpan>,
+    /// The doc comments of the program, by the span of the items they document.
+    ///
+    /// These are taken straight from the [air_parser::ast::Program] without modification, as
+    /// they are only looked up to document the constraints, see `air_ir::Air::constraint_docs`.
+    pub docs: DocComments,
     /// The constraints of the program, represented as MIR Nodes
     graph: Graph,
 }
@@ -54,9 +68,12 @@ impl Mir {
         Self {
             name,
             trace_columns: vec![],
+            virtual_columns: vec![],
             periodic_columns: Default::default(),
             public_inputs: Default::default(),
             num_random_values: 0,
+            buses_span: None,
+            docs: Default::default(),
             graph: Default::default(),
         }
     }
@@ -5,6 +5,8 @@ mod mir;
 mod node;
 mod nodes;
 mod owner;
+mod provenance;
+mod serializable;
 mod utils;
 pub extern crate derive_ir;
 
@@ -16,6 +18,8 @@ pub use mir::Mir;
 pub use node::Node;
 pub use nodes::*;
 pub use owner::Owner;
+pub use provenance::CallSiteInfo;
+pub use serializable::*;
 pub use utils::*;
 /// A trait for nodes that can have children
 /// This is used with the Child trait to allow for easy traversal and manipulation of the graph
@@ -1,10 +1,11 @@
 use std::hash::Hash;
 
 use miden_diagnostics::{SourceSpan, Spanned};
+use serde::{Deserialize, Serialize};
 
 use crate::ir::{BackLink, Builder, Bus, Child, Link, Node, Op, Owner, Parent, Singleton};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash, Serialize, Deserialize)]
 pub enum BusOpKind {
     #[default]
     Insert,
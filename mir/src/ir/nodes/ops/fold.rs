@@ -1,4 +1,5 @@
 use miden_diagnostics::{SourceSpan, Spanned};
+use serde::{Deserialize, Serialize};
 
 use crate::ir::{BackLink, Builder, Child, Link, Node, Op, Owner, Parent, Singleton};
 
@@ -23,7 +24,7 @@ pub struct Fold {
     pub span: SourceSpan,
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Default, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum FoldOperator {
     Add,
     Mul,
@@ -1,5 +1,6 @@
 use air_parser::ast::{self, Identifier, QualifiedIdentifier, TraceColumnIndex, TraceSegmentId};
 use miden_diagnostics::{SourceSpan, Spanned};
+use serde::{Deserialize, Serialize};
 
 use crate::ir::{BackLink, Builder, Bus, Child, Link, Node, Op, Owner, Singleton};
 
@@ -100,7 +101,7 @@ impl BusAccess {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub enum ConstantValue {
     Felt(u64),
     Vector(Vec<u64>),
@@ -109,7 +110,7 @@ pub enum ConstantValue {
 
 /// [TraceAccess] is like SymbolAccess, but is used to describe an access to a specific trace
 /// column or columns.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TraceAccess {
     /// The trace segment being accessed
     pub segment: TraceSegmentId,
@@ -130,7 +131,7 @@ impl TraceAccess {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub struct TraceAccessBinding {
     pub segment: TraceSegmentId,
     /// The offset to the first column of the segment which is bound by this binding
@@ -147,7 +148,7 @@ pub struct SpannedMirValue {
     pub value: MirValue,
 }
 
-#[derive(Debug, Default, Eq, PartialEq, Clone, Hash)]
+#[derive(Debug, Default, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub enum MirType {
     #[default]
     Felt,
@@ -170,10 +171,12 @@ impl From<ast::Type> for MirType {
 pub struct PeriodicColumnAccess {
     pub name: QualifiedIdentifier,
     pub cycle: usize,
+    /// The number of rows after the current row at which the column is read, e.g. 1 for `k'`
+    pub row_offset: usize,
 }
 impl PeriodicColumnAccess {
-    pub const fn new(name: QualifiedIdentifier, cycle: usize) -> Self {
-        Self { name, cycle }
+    pub const fn new(name: QualifiedIdentifier, cycle: usize, row_offset: usize) -> Self {
+        Self { name, cycle, row_offset }
     }
 }
 
@@ -216,6 +219,9 @@ impl PublicInputTableAccess {
     pub fn bus_name(&self) -> Identifier {
         self.bus_name.expect("Bus name should have already been set")
     }
+    pub fn try_bus_name(&self) -> Option<Identifier> {
+        self.bus_name
+    }
 }
 
 impl Default for SpannedMirValue {
@@ -11,6 +11,8 @@ pub struct Evaluator {
     pub parameters: Vec<Vec<Link<Op>>>,
     // Operations contained in the Evaluator
     pub body: Link<Vec<Link<Op>>>,
+    // Whether the evaluator was declared with `@inline(never)`, see the Inlining pass
+    pub inline_never: bool,
     pub _node: Singleton<Node>,
     pub _owner: Singleton<Owner>,
     #[span]
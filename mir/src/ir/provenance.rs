@@ -0,0 +1,23 @@
+use air_parser::ast::QualifiedIdentifier;
+use miden_diagnostics::SourceSpan;
+
+/// A call to an evaluator through which a constraint was inlined by the
+/// [crate::passes::Inlining] pass.
+///
+/// The call sites of a constraint are recorded in a side table of the [super::Graph], innermost
+/// first, so that diagnostics about the constraint can point both at the expression written in
+/// the body of the evaluator, and at each call which led to it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CallSiteInfo {
+    /// The name of the evaluator which was called
+    pub callee: QualifiedIdentifier,
+    /// The span of the call
+    pub call_span: SourceSpan,
+    /// The number of calls between the constraint and this one, i.e. 0 for the call to the
+    /// evaluator in which the constraint is written, 1 for the call to the evaluator containing
+    /// that call, and so on.
+    pub depth: usize,
+    /// The doc comments of the evaluator which was called, which are kept through inlining so
+    /// that they can document the constraints inlined from it.
+    pub docs: Vec<String>,
+}
@@ -0,0 +1,1064 @@
+use std::collections::HashMap;
+
+use air_parser::{
+    Symbol,
+    ast::{
+        self, AccessType, ColumnBoundaryFlags, Identifier, NamespacedIdentifier, PeriodicColumn,
+        PublicInput, QualifiedIdentifier, TraceBinding, TraceSegment,
+    },
+};
+use miden_diagnostics::{SourceSpan, Span};
+use serde::{Deserialize, Serialize};
+
+use crate::ir::{
+    Accessor, Add, BackLink, Boundary, Bus, BusAccess, BusOp, BusOpKind, Call, CallSiteInfo,
+    ConstantValue, Enf, Evaluator, Exp, Fold, FoldOperator, For, Function, If, Link, Matrix, Mir,
+    MirType, MirValue, Mul, Op, Owner, Parameter, PeriodicColumnAccess, PublicInputAccess,
+    PublicInputTableAccess, Root, SpannedMirValue, Sub, TraceAccess, TraceAccessBinding, Value,
+    Vector,
+};
+
+/// An index-based representation of a [Mir], which can be serialized, e.g. to JSON.
+///
+/// It is produced by [Mir::to_serializable], e.g. to inspect the MIR between two passes with
+/// external tools, and can be imported back with [SerializableMir::into_mir], e.g. to replay a
+/// dumped MIR through the passes which follow.
+///
+/// The graph is flattened into tables of operations, roots and buses, which refer to each other
+/// by their index in these tables. A node shared by several operations is only exported once, so
+/// that it is still shared once imported.
+///
+/// Source spans are not exported, nor are the virtual columns, whose definitions are AST
+/// expressions. Importing a [SerializableMir] therefore produces a [Mir] without them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableMir {
+    pub name: String,
+    pub trace_columns: Vec<SerializableTraceSegment>,
+    pub periodic_columns: Vec<SerializablePeriodicColumn>,
+    pub public_inputs: Vec<SerializablePublicInput>,
+    pub num_random_values: u16,
+    /// The operations of the graph, referred to by their index in this table
+    pub ops: Vec<SerializableOp>,
+    /// The functions and evaluators of the graph, along with any other root which is called
+    pub roots: Vec<SerializableRoot>,
+    /// The buses of the graph, along with any other bus which is referred to
+    pub buses: Vec<SerializableBus>,
+    /// The indices of the boundary constraints in [Self::ops]
+    pub boundary_constraints: Vec<usize>,
+    /// The indices of the integrity constraints in [Self::ops]
+    pub integrity_constraints: Vec<usize>,
+    /// The calls through which constraints were inlined, see [crate::ir::Graph::get_provenance]
+    pub provenance: Vec<SerializableProvenance>,
+    /// The selectors under which constraints are enforced, see [crate::ir::Graph::get_selectors]
+    pub selectors: Vec<SerializableSelectors>,
+}
+
+/// A [QualifiedIdentifier], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableQualifiedIdentifier {
+    pub module: String,
+    pub name: String,
+    /// Whether the identifier belongs to the namespace of functions, rather than the one of
+    /// bindings, see [NamespacedIdentifier]
+    pub function: bool,
+}
+
+/// A [TraceSegment], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableTraceSegment {
+    pub id: usize,
+    pub name: String,
+    pub size: usize,
+    pub bindings: Vec<SerializableTraceBinding>,
+}
+
+/// A [TraceBinding], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableTraceBinding {
+    pub name: Option<String>,
+    pub offset: usize,
+    pub size: usize,
+    pub ty: MirType,
+}
+
+/// A [PeriodicColumn], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializablePeriodicColumn {
+    pub name: SerializableQualifiedIdentifier,
+    pub values: Vec<u64>,
+}
+
+/// A [PublicInput], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerializablePublicInput {
+    Vector {
+        name: String,
+        size: usize,
+    },
+    Table {
+        name: String,
+        size: usize,
+        columns: Vec<String>,
+        commitment: bool,
+    },
+}
+
+/// An [Op], as exported in a [SerializableMir].
+///
+/// Operands are referred to by their index in [SerializableMir::ops].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerializableOp {
+    Enf {
+        expr: usize,
+    },
+    Boundary {
+        kind: SerializableBoundaryKind,
+        expr: usize,
+    },
+    Add {
+        lhs: usize,
+        rhs: usize,
+    },
+    Sub {
+        lhs: usize,
+        rhs: usize,
+    },
+    Mul {
+        lhs: usize,
+        rhs: usize,
+    },
+    Exp {
+        lhs: usize,
+        rhs: usize,
+    },
+    If {
+        condition: usize,
+        then_branch: usize,
+        else_branch: usize,
+    },
+    For {
+        iterators: Vec<usize>,
+        expr: usize,
+        selector: usize,
+    },
+    Call {
+        /// The index of the called root in [SerializableMir::roots]
+        function: usize,
+        arguments: Vec<usize>,
+    },
+    Fold {
+        iterator: usize,
+        operator: FoldOperator,
+        initial_value: usize,
+    },
+    Vector {
+        size: usize,
+        elements: Vec<usize>,
+    },
+    Matrix {
+        size: usize,
+        elements: Vec<usize>,
+    },
+    Accessor {
+        indexable: usize,
+        access_type: SerializableAccessType,
+        offset: usize,
+    },
+    BusOp {
+        /// The index of the bus in [SerializableMir::buses], unless it was dropped
+        bus: Option<usize>,
+        kind: BusOpKind,
+        args: Vec<usize>,
+        latch: usize,
+    },
+    Parameter {
+        /// The operation or root declaring the parameter, unless it was dropped
+        ref_node: Option<SerializableOwner>,
+        position: usize,
+        ty: MirType,
+    },
+    Value(SerializableValue),
+    None,
+}
+
+/// The owner of a [Parameter], as exported in a [SerializableMir]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerializableOwner {
+    /// The index of a root in [SerializableMir::roots]
+    Root(usize),
+    /// The index of an operation in [SerializableMir::ops]
+    Op(usize),
+}
+
+/// An [ast::Boundary], as exported in a [SerializableMir]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerializableBoundaryKind {
+    First,
+    Last,
+}
+
+/// An [AccessType], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerializableAccessType {
+    Default,
+    Slice { start: usize, end: usize },
+    Index(usize),
+    Matrix(usize, usize),
+}
+
+/// A [MirValue], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerializableValue {
+    Constant(ConstantValue),
+    TraceAccess(TraceAccess),
+    PeriodicColumn {
+        name: SerializableQualifiedIdentifier,
+        cycle: usize,
+        row_offset: usize,
+    },
+    PublicInput {
+        name: String,
+        index: usize,
+    },
+    PublicInputTable {
+        table_name: String,
+        bus_name: Option<String>,
+        num_cols: usize,
+    },
+    RandomValue(usize),
+    TraceAccessBinding(TraceAccessBinding),
+    BusAccess {
+        /// The index of the bus in [SerializableMir::buses]
+        bus: usize,
+        row_offset: usize,
+    },
+    Null,
+    Unconstrained,
+}
+
+/// A [Root], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableRoot {
+    /// The name under which the root is declared in the graph, if it is
+    pub name: Option<SerializableQualifiedIdentifier>,
+    pub kind: SerializableRootKind,
+}
+
+/// The kind of a [SerializableRoot], with the indices of its operations in [SerializableMir::ops]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerializableRootKind {
+    Function {
+        parameters: Vec<usize>,
+        return_type: usize,
+        body: Vec<usize>,
+    },
+    Evaluator {
+        parameters: Vec<Vec<usize>>,
+        body: Vec<usize>,
+        inline_never: bool,
+    },
+    None,
+}
+
+/// A [Bus], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableBus {
+    /// The name under which the bus is declared in the graph, if it is
+    pub key: Option<SerializableQualifiedIdentifier>,
+    pub name: String,
+    pub bus_type: SerializableBusType,
+    /// The indices of the bus operations in [SerializableMir::ops]
+    pub columns: Vec<usize>,
+    /// The indices of the latches of the bus operations in [SerializableMir::ops]
+    pub latches: Vec<usize>,
+    pub multiplicity_bits: Vec<usize>,
+    pub range_checked_op: Option<usize>,
+    pub is_extern: bool,
+    /// The index of the value of the bus on the first row in [SerializableMir::ops]
+    pub first: usize,
+    /// The index of the value of the bus on the last row in [SerializableMir::ops]
+    pub last: usize,
+}
+
+/// An [ast::BusType], as exported in a [SerializableMir]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerializableBusType {
+    Multiset,
+    Logup,
+}
+
+/// The calls through which a constraint was inlined, as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableProvenance {
+    /// The index of the constraint in [SerializableMir::ops]
+    pub constraint: usize,
+    /// The calls, innermost first, see [CallSiteInfo]
+    pub call_sites: Vec<SerializableCallSite>,
+}
+
+/// The selectors under which a constraint is enforced, as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableSelectors {
+    /// The index of the constraint in [SerializableMir::ops]
+    pub constraint: usize,
+    /// The indices of the selectors in [SerializableMir::ops], outermost first
+    pub selectors: Vec<usize>,
+}
+
+/// A [CallSiteInfo], as exported in a [SerializableMir]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableCallSite {
+    pub callee: SerializableQualifiedIdentifier,
+    pub depth: usize,
+}
+
+impl Mir {
+    /// Flattens this [Mir] into a [SerializableMir].
+    pub fn to_serializable(&self) -> SerializableMir {
+        let graph = self.constraint_graph();
+        let mut exporter = Exporter::default();
+        // The declared roots and buses are indexed first, so that their names are known when
+        // they are referred to
+        for (ident, root) in graph.get_functions().iter().chain(graph.get_evaluators()) {
+            exporter.root(root, Some(ident));
+        }
+        for (ident, bus) in graph.buses.iter() {
+            exporter.bus(bus, Some(ident));
+        }
+        let boundary_constraints = exporter.ops(&graph.boundary_constraints_roots.borrow());
+        let integrity_constraints = exporter.ops(&graph.integrity_constraints_roots.borrow());
+        exporter.export_pending();
+
+        // Only the provenance of exported constraints is kept, as the others were removed from the
+        // graph
+        let mut provenance = graph
+            .get_provenances()
+            .into_iter()
+            .filter_map(|(constraint, call_sites)| {
+                Some(SerializableProvenance {
+                    constraint: *exporter.op_indices.get(&constraint.get_ptr())?,
+                    call_sites: call_sites
+                        .iter()
+                        .map(|call_site| SerializableCallSite {
+                            callee: (&call_site.callee).into(),
+                            depth: call_site.depth,
+                        })
+                        .collect(),
+                })
+            })
+            .collect::<Vec<_>>();
+        provenance.sort_by_key(|provenance| provenance.constraint);
+        let mut selectors = graph
+            .get_all_selectors()
+            .into_iter()
+            .filter_map(|(constraint, selectors)| {
+                Some(SerializableSelectors {
+                    constraint: *exporter.op_indices.get(&constraint.get_ptr())?,
+                    selectors: selectors
+                        .iter()
+                        .map(|selector| exporter.op_indices.get(&selector.get_ptr()).copied())
+                        .collect::<Option<_>>()?,
+                })
+            })
+            .collect::<Vec<_>>();
+        selectors.sort_by_key(|selectors| selectors.constraint);
+
+        SerializableMir {
+            name: self.name.to_string(),
+            trace_columns: self.trace_columns.iter().map(Into::into).collect(),
+            periodic_columns: self
+                .periodic_columns
+                .iter()
+                .map(|(name, column)| SerializablePeriodicColumn {
+                    name: name.into(),
+                    values: column.values.clone(),
+                })
+                .collect(),
+            public_inputs: self.public_inputs.values().map(Into::into).collect(),
+            num_random_values: self.num_random_values,
+            ops: exporter.ops,
+            roots: exporter.roots,
+            buses: exporter.buses,
+            boundary_constraints,
+            integrity_constraints,
+            provenance,
+            selectors,
+        }
+    }
+}
+
+impl SerializableMir {
+    /// Reconstructs the [Mir] this was exported from, see [Mir::to_serializable].
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index does not refer to an item of the corresponding table, or if a root or
+    /// a bus is declared twice, i.e. if this was not produced by [Mir::to_serializable].
+    pub fn into_mir(self) -> Mir {
+        let mut mir = Mir::new(identifier(&self.name));
+        mir.trace_columns = self.trace_columns.iter().map(Into::into).collect();
+        mir.periodic_columns = self
+            .periodic_columns
+            .iter()
+            .map(|column| {
+                let name = QualifiedIdentifier::from(&column.name);
+                let values = column.values.clone();
+                (name, PeriodicColumn::new(SourceSpan::UNKNOWN, name.item.id(), values))
+            })
+            .collect();
+        mir.public_inputs = self
+            .public_inputs
+            .iter()
+            .map(|input| {
+                let input = PublicInput::from(input);
+                (input.name(), input)
+            })
+            .collect();
+        mir.num_random_values = self.num_random_values;
+
+        // All the nodes are created upfront, and then defined, as they may refer to each other
+        let importer = Importer {
+            ops: self.ops.iter().map(|_| Link::default()).collect(),
+            roots: self.roots.iter().map(|_| Link::default()).collect(),
+            buses: self
+                .buses
+                .iter()
+                .map(|bus| {
+                    Bus::create(identifier(&bus.name), bus.bus_type.into(), SourceSpan::UNKNOWN)
+                })
+                .collect(),
+        };
+        for (link, op) in importer.ops.iter().zip(&self.ops) {
+            *link.borrow_mut() = importer.op(op);
+        }
+        for (link, root) in importer.roots.iter().zip(&self.roots) {
+            *link.borrow_mut() = importer.root(&root.kind);
+        }
+        // The owner of a parameter can only be referred to once it is defined
+        for (link, op) in importer.ops.iter().zip(&self.ops) {
+            if let SerializableOp::Parameter { ref_node: Some(owner), .. } = op {
+                let owner = match *owner {
+                    SerializableOwner::Root(index) => Some(importer.roots[index].as_owner()),
+                    SerializableOwner::Op(index) => importer.ops[index].as_owner(),
+                };
+                if let Some(owner) = owner {
+                    link.as_parameter_mut().unwrap().set_ref_node(owner);
+                }
+            }
+        }
+        for (link, bus) in importer.buses.iter().zip(&self.buses) {
+            importer.define_bus(link, bus);
+        }
+
+        let graph = mir.constraint_graph_mut();
+        for (link, root) in importer.roots.iter().zip(&self.roots) {
+            let Some(name) = &root.name else {
+                continue;
+            };
+            let inserted = match root.kind {
+                SerializableRootKind::Function { .. } => {
+                    graph.insert_function(name.into(), link.clone())
+                },
+                SerializableRootKind::Evaluator { .. } => {
+                    graph.insert_evaluator(name.into(), link.clone())
+                },
+                SerializableRootKind::None => Ok(()),
+            };
+            inserted.unwrap_or_else(|_| panic!("root {} is declared twice", name.name));
+        }
+        for (link, bus) in importer.buses.iter().zip(&self.buses) {
+            if let Some(key) = &bus.key {
+                graph
+                    .insert_bus(key.into(), link.clone())
+                    .unwrap_or_else(|_| panic!("bus {} is declared twice", key.name));
+            }
+        }
+        // The constraints are pushed as is, as inserting them would drop structurally identical
+        // constraints
+        graph
+            .boundary_constraints_roots
+            .borrow_mut()
+            .extend(importer.ops(&self.boundary_constraints));
+        graph
+            .integrity_constraints_roots
+            .borrow_mut()
+            .extend(importer.ops(&self.integrity_constraints));
+        for provenance in self.provenance.iter() {
+            let call_sites = provenance
+                .call_sites
+                .iter()
+                .map(|call_site| CallSiteInfo {
+                    callee: (&call_site.callee).into(),
+                    call_span: SourceSpan::UNKNOWN,
+                    depth: call_site.depth,
+                    docs: vec![],
+                })
+                .collect();
+            graph.set_provenance(importer.ops[provenance.constraint].clone(), call_sites);
+        }
+        for selectors in self.selectors.iter() {
+            graph.set_selectors(
+                importer.ops[selectors.constraint].clone(),
+                importer.ops(&selectors.selectors),
+            );
+        }
+
+        mir
+    }
+}
+
+/// Flattens the graph of a [Mir], see [Mir::to_serializable]
+#[derive(Default)]
+struct Exporter {
+    ops: Vec<SerializableOp>,
+    /// The index of each exported operation, by pointer
+    op_indices: HashMap<usize, usize>,
+    roots: Vec<SerializableRoot>,
+    /// The roots which were indexed, in order, some of which may not be exported yet
+    pending_roots: Vec<(Link<Root>, Option<SerializableQualifiedIdentifier>)>,
+    root_indices: HashMap<usize, usize>,
+    buses: Vec<SerializableBus>,
+    /// The buses which were indexed, in order, some of which may not be exported yet
+    pending_buses: Vec<(Link<Bus>, Option<SerializableQualifiedIdentifier>)>,
+    bus_indices: HashMap<usize, usize>,
+}
+impl Exporter {
+    /// Exports `op` and its operands, if they were not exported yet, returning its index
+    fn op(&mut self, op: &Link<Op>) -> usize {
+        if let Some(index) = self.op_indices.get(&op.get_ptr()) {
+            return *index;
+        }
+        // The index is reserved before exporting the operands, as the parameters declared by an
+        // operation refer back to it
+        let index = self.ops.len();
+        self.op_indices.insert(op.get_ptr(), index);
+        self.ops.push(SerializableOp::None);
+
+        let op = op.borrow().clone();
+        let exported = match op {
+            Op::Enf(enf) => SerializableOp::Enf { expr: self.op(&enf.expr) },
+            Op::Boundary(boundary) => SerializableOp::Boundary {
+                kind: boundary.kind.into(),
+                expr: self.op(&boundary.expr),
+            },
+            Op::Add(add) => SerializableOp::Add {
+                lhs: self.op(&add.lhs),
+                rhs: self.op(&add.rhs),
+            },
+            Op::Sub(sub) => SerializableOp::Sub {
+                lhs: self.op(&sub.lhs),
+                rhs: self.op(&sub.rhs),
+            },
+            Op::Mul(mul) => SerializableOp::Mul {
+                lhs: self.op(&mul.lhs),
+                rhs: self.op(&mul.rhs),
+            },
+            Op::Exp(exp) => SerializableOp::Exp {
+                lhs: self.op(&exp.lhs),
+                rhs: self.op(&exp.rhs),
+            },
+            Op::If(if_op) => SerializableOp::If {
+                condition: self.op(&if_op.condition),
+                then_branch: self.op(&if_op.then_branch),
+                else_branch: self.op(&if_op.else_branch),
+            },
+            Op::For(for_op) => SerializableOp::For {
+                iterators: self.ops(&for_op.iterators.borrow()),
+                expr: self.op(&for_op.expr),
+                selector: self.op(&for_op.selector),
+            },
+            Op::Call(call) => SerializableOp::Call {
+                function: self.root(&call.function, None),
+                arguments: self.ops(&call.arguments.borrow()),
+            },
+            Op::Fold(fold) => SerializableOp::Fold {
+                iterator: self.op(&fold.iterator),
+                operator: fold.operator,
+                initial_value: self.op(&fold.initial_value),
+            },
+            Op::Vector(vector) => SerializableOp::Vector {
+                size: vector.size,
+                elements: self.ops(&vector.elements.borrow()),
+            },
+            Op::Matrix(matrix) => SerializableOp::Matrix {
+                size: matrix.size,
+                elements: self.ops(&matrix.elements.borrow()),
+            },
+            Op::Accessor(accessor) => SerializableOp::Accessor {
+                indexable: self.op(&accessor.indexable),
+                access_type: (&accessor.access_type).into(),
+                offset: accessor.offset,
+            },
+            Op::BusOp(bus_op) => SerializableOp::BusOp {
+                bus: bus_op.bus.to_link().map(|bus| self.bus(&bus, None)),
+                kind: bus_op.kind,
+                args: self.ops(&bus_op.args),
+                latch: self.op(&bus_op.latch),
+            },
+            Op::Parameter(parameter) => SerializableOp::Parameter {
+                ref_node: parameter.ref_node.to_link().and_then(|owner| self.owner(&owner)),
+                position: parameter.position,
+                ty: parameter.ty,
+            },
+            Op::Value(value) => SerializableOp::Value(self.value(&value.value.value)),
+            Op::None(_) => SerializableOp::None,
+        };
+        self.ops[index] = exported;
+        index
+    }
+
+    fn ops(&mut self, ops: &[Link<Op>]) -> Vec<usize> {
+        ops.iter().map(|op| self.op(op)).collect()
+    }
+
+    fn owner(&mut self, owner: &Link<Owner>) -> Option<SerializableOwner> {
+        if let Some(root) = owner.as_root() {
+            return Some(SerializableOwner::Root(self.root(&root, None)));
+        }
+        owner.as_op().map(|op| SerializableOwner::Op(self.op(&op)))
+    }
+
+    fn value(&mut self, value: &MirValue) -> SerializableValue {
+        match value {
+            MirValue::Constant(constant) => SerializableValue::Constant(constant.clone()),
+            MirValue::TraceAccess(access) => SerializableValue::TraceAccess(*access),
+            MirValue::PeriodicColumn(access) => SerializableValue::PeriodicColumn {
+                name: (&access.name).into(),
+                cycle: access.cycle,
+                row_offset: access.row_offset,
+            },
+            MirValue::PublicInput(access) => SerializableValue::PublicInput {
+                name: access.name.to_string(),
+                index: access.index,
+            },
+            MirValue::PublicInputTable(access) => SerializableValue::PublicInputTable {
+                table_name: access.table_name.to_string(),
+                bus_name: access.try_bus_name().map(|name| name.to_string()),
+                num_cols: access.num_cols,
+            },
+            MirValue::RandomValue(index) => SerializableValue::RandomValue(*index),
+            MirValue::TraceAccessBinding(binding) => {
+                SerializableValue::TraceAccessBinding(binding.clone())
+            },
+            MirValue::BusAccess(access) => SerializableValue::BusAccess {
+                bus: self.bus(&access.bus, None),
+                row_offset: access.row_offset,
+            },
+            MirValue::Null => SerializableValue::Null,
+            MirValue::Unconstrained => SerializableValue::Unconstrained,
+        }
+    }
+
+    /// Returns the index of `root`, which is exported by [Self::export_pending]
+    fn root(&mut self, root: &Link<Root>, name: Option<&QualifiedIdentifier>) -> usize {
+        if let Some(index) = self.root_indices.get(&root.get_ptr()) {
+            return *index;
+        }
+        let index = self.pending_roots.len();
+        self.root_indices.insert(root.get_ptr(), index);
+        self.pending_roots.push((root.clone(), name.map(Into::into)));
+        index
+    }
+
+    /// Returns the index of `bus`, which is exported by [Self::export_pending]
+    fn bus(&mut self, bus: &Link<Bus>, key: Option<&QualifiedIdentifier>) -> usize {
+        if let Some(index) = self.bus_indices.get(&bus.get_ptr()) {
+            return *index;
+        }
+        let index = self.pending_buses.len();
+        self.bus_indices.insert(bus.get_ptr(), index);
+        self.pending_buses.push((bus.clone(), key.map(Into::into)));
+        index
+    }
+
+    /// Exports the roots and buses which were indexed, until all of those they refer to are
+    fn export_pending(&mut self) {
+        loop {
+            if let Some((root, name)) = self.pending_roots.get(self.roots.len()).cloned() {
+                let root = root.borrow().clone();
+                let kind = match root {
+                    Root::Function(function) => SerializableRootKind::Function {
+                        parameters: self.ops(&function.parameters),
+                        return_type: self.op(&function.return_type),
+                        body: self.ops(&function.body.borrow()),
+                    },
+                    Root::Evaluator(evaluator) => SerializableRootKind::Evaluator {
+                        parameters: evaluator
+                            .parameters
+                            .iter()
+                            .map(|parameters| self.ops(parameters))
+                            .collect(),
+                        body: self.ops(&evaluator.body.borrow()),
+                        inline_never: evaluator.inline_never,
+                    },
+                    Root::None(_) => SerializableRootKind::None,
+                };
+                self.roots.push(SerializableRoot { name, kind });
+            } else if let Some((bus, key)) = self.pending_buses.get(self.buses.len()).cloned() {
+                let bus = bus.borrow().clone();
+                let exported = SerializableBus {
+                    key,
+                    name: bus.name().to_string(),
+                    bus_type: bus.bus_type.into(),
+                    columns: self.ops(&bus.columns),
+                    latches: self.ops(&bus.latches),
+                    multiplicity_bits: bus.multiplicity_bits.clone(),
+                    range_checked_op: bus.range_checked_op,
+                    is_extern: bus.is_extern,
+                    first: self.op(&bus.get_first()),
+                    last: self.op(&bus.get_last()),
+                };
+                self.buses.push(exported);
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// Reconstructs the graph of a [Mir], see [SerializableMir::into_mir]
+struct Importer {
+    ops: Vec<Link<Op>>,
+    roots: Vec<Link<Root>>,
+    buses: Vec<Link<Bus>>,
+}
+impl Importer {
+    fn op(&self, op: &SerializableOp) -> Op {
+        match op {
+            SerializableOp::Enf { expr } => Op::Enf(Enf {
+                expr: self.ops[*expr].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::Boundary { kind, expr } => Op::Boundary(Boundary {
+                kind: (*kind).into(),
+                expr: self.ops[*expr].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::Add { lhs, rhs } => Op::Add(Add {
+                lhs: self.ops[*lhs].clone(),
+                rhs: self.ops[*rhs].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::Sub { lhs, rhs } => Op::Sub(Sub {
+                lhs: self.ops[*lhs].clone(),
+                rhs: self.ops[*rhs].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::Mul { lhs, rhs } => Op::Mul(Mul {
+                lhs: self.ops[*lhs].clone(),
+                rhs: self.ops[*rhs].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::Exp { lhs, rhs } => Op::Exp(Exp {
+                lhs: self.ops[*lhs].clone(),
+                rhs: self.ops[*rhs].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::If { condition, then_branch, else_branch } => Op::If(If {
+                condition: self.ops[*condition].clone(),
+                then_branch: self.ops[*then_branch].clone(),
+                else_branch: self.ops[*else_branch].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::For { iterators, expr, selector } => Op::For(For {
+                iterators: Link::new(self.ops(iterators)),
+                expr: self.ops[*expr].clone(),
+                selector: self.ops[*selector].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::Call { function, arguments } => Op::Call(Call {
+                function: self.roots[*function].clone(),
+                arguments: Link::new(self.ops(arguments)),
+                ..Default::default()
+            }),
+            SerializableOp::Fold { iterator, operator, initial_value } => Op::Fold(Fold {
+                iterator: self.ops[*iterator].clone(),
+                operator: operator.clone(),
+                initial_value: self.ops[*initial_value].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::Vector { size, elements } => Op::Vector(Vector {
+                size: *size,
+                elements: Link::new(self.ops(elements)),
+                ..Default::default()
+            }),
+            SerializableOp::Matrix { size, elements } => Op::Matrix(Matrix {
+                size: *size,
+                elements: Link::new(self.ops(elements)),
+                ..Default::default()
+            }),
+            SerializableOp::Accessor { indexable, access_type, offset } => Op::Accessor(Accessor {
+                indexable: self.ops[*indexable].clone(),
+                access_type: access_type.into(),
+                offset: *offset,
+                ..Default::default()
+            }),
+            SerializableOp::BusOp { bus, kind, args, latch } => Op::BusOp(BusOp {
+                bus: bus.map_or_else(BackLink::none, |bus| self.buses[bus].clone().into()),
+                kind: *kind,
+                args: self.ops(args),
+                latch: self.ops[*latch].clone(),
+                ..Default::default()
+            }),
+            SerializableOp::Parameter { position, ty, .. } => {
+                // The owner of the parameter is set once it is defined
+                let parameter = Parameter::create(*position, ty.clone(), SourceSpan::UNKNOWN);
+                parameter.borrow().clone()
+            },
+            SerializableOp::Value(value) => Op::Value(Value {
+                value: SpannedMirValue {
+                    span: SourceSpan::UNKNOWN,
+                    value: self.value(value),
+                },
+                ..Default::default()
+            }),
+            SerializableOp::None => Op::None(SourceSpan::UNKNOWN),
+        }
+    }
+
+    fn ops(&self, ops: &[usize]) -> Vec<Link<Op>> {
+        ops.iter().map(|op| self.ops[*op].clone()).collect()
+    }
+
+    fn value(&self, value: &SerializableValue) -> MirValue {
+        match value {
+            SerializableValue::Constant(constant) => MirValue::Constant(constant.clone()),
+            SerializableValue::TraceAccess(access) => MirValue::TraceAccess(*access),
+            SerializableValue::PeriodicColumn { name, cycle, row_offset } => {
+                MirValue::PeriodicColumn(PeriodicColumnAccess::new(
+                    name.into(),
+                    *cycle,
+                    *row_offset,
+                ))
+            },
+            SerializableValue::PublicInput { name, index } => {
+                MirValue::PublicInput(PublicInputAccess::new(identifier(name), *index))
+            },
+            SerializableValue::PublicInputTable { table_name, bus_name, num_cols } => {
+                let mut access = PublicInputTableAccess::new(identifier(table_name), *num_cols);
+                if let Some(bus_name) = bus_name {
+                    access.set_bus_name(identifier(bus_name));
+                }
+                MirValue::PublicInputTable(access)
+            },
+            SerializableValue::RandomValue(index) => MirValue::RandomValue(*index),
+            SerializableValue::TraceAccessBinding(binding) => {
+                MirValue::TraceAccessBinding(binding.clone())
+            },
+            SerializableValue::BusAccess { bus, row_offset } => {
+                MirValue::BusAccess(BusAccess::new(self.buses[*bus].clone(), *row_offset))
+            },
+            SerializableValue::Null => MirValue::Null,
+            SerializableValue::Unconstrained => MirValue::Unconstrained,
+        }
+    }
+
+    fn root(&self, root: &SerializableRootKind) -> Root {
+        match root {
+            SerializableRootKind::Function { parameters, return_type, body } => {
+                Root::Function(Function {
+                    parameters: self.ops(parameters),
+                    return_type: self.ops[*return_type].clone(),
+                    body: Link::new(self.ops(body)),
+                    ..Default::default()
+                })
+            },
+            SerializableRootKind::Evaluator { parameters, body, inline_never } => {
+                Root::Evaluator(Evaluator {
+                    parameters: parameters.iter().map(|parameters| self.ops(parameters)).collect(),
+                    body: Link::new(self.ops(body)),
+                    inline_never: *inline_never,
+                    ..Default::default()
+                })
+            },
+            SerializableRootKind::None => Root::None(SourceSpan::UNKNOWN),
+        }
+    }
+
+    fn define_bus(&self, link: &Link<Bus>, bus: &SerializableBus) {
+        let mut defined = link.borrow_mut();
+        defined.columns = self.ops(&bus.columns);
+        defined.latches = self.ops(&bus.latches);
+        defined.multiplicity_bits = bus.multiplicity_bits.clone();
+        defined.range_checked_op = bus.range_checked_op;
+        defined.is_extern = bus.is_extern;
+        defined
+            .set_first(self.ops[bus.first].clone())
+            .expect("the bus was just created");
+        defined.set_last(self.ops[bus.last].clone()).expect("the bus was just created");
+    }
+}
+
+fn identifier(name: &str) -> Identifier {
+    Identifier::new(SourceSpan::UNKNOWN, Symbol::intern(name))
+}
+
+impl From<&QualifiedIdentifier> for SerializableQualifiedIdentifier {
+    fn from(ident: &QualifiedIdentifier) -> Self {
+        Self {
+            module: ident.module.to_string(),
+            name: ident.item.id().to_string(),
+            function: matches!(ident.item, NamespacedIdentifier::Function(_)),
+        }
+    }
+}
+impl From<&SerializableQualifiedIdentifier> for QualifiedIdentifier {
+    fn from(ident: &SerializableQualifiedIdentifier) -> Self {
+        let name = identifier(&ident.name);
+        let item = if ident.function {
+            NamespacedIdentifier::Function(name)
+        } else {
+            NamespacedIdentifier::Binding(name)
+        };
+        QualifiedIdentifier::new(identifier(&ident.module), item)
+    }
+}
+
+impl From<&TraceSegment> for SerializableTraceSegment {
+    fn from(segment: &TraceSegment) -> Self {
+        Self {
+            id: segment.id,
+            name: segment.name.to_string(),
+            size: segment.size,
+            bindings: segment
+                .bindings
+                .iter()
+                .map(|binding| SerializableTraceBinding {
+                    name: binding.name.map(|name| name.to_string()),
+                    offset: binding.offset,
+                    size: binding.size,
+                    ty: binding.ty.into(),
+                })
+                .collect(),
+        }
+    }
+}
+impl From<&SerializableTraceSegment> for TraceSegment {
+    fn from(segment: &SerializableTraceSegment) -> Self {
+        let bindings = segment
+            .bindings
+            .iter()
+            .map(|binding| TraceBinding {
+                span: SourceSpan::UNKNOWN,
+                name: binding.name.as_deref().map(identifier),
+                segment: segment.id,
+                offset: binding.offset,
+                size: binding.size,
+                ty: match binding.ty {
+                    MirType::Felt => ast::Type::Felt,
+                    MirType::Vector(size) => ast::Type::Vector(size),
+                    MirType::Matrix(rows, cols) => ast::Type::Matrix(rows, cols),
+                },
+            })
+            .collect();
+        TraceSegment {
+            span: SourceSpan::UNKNOWN,
+            id: segment.id,
+            name: identifier(&segment.name),
+            size: segment.size,
+            bindings,
+            // Which columns are constrained on each boundary is only tracked by semantic analysis
+            boundary_constrained: vec![
+                Span::new(SourceSpan::UNKNOWN, ColumnBoundaryFlags::EMPTY);
+                segment.size
+            ],
+        }
+    }
+}
+
+impl From<&PublicInput> for SerializablePublicInput {
+    fn from(input: &PublicInput) -> Self {
+        match input {
+            PublicInput::Vector { name, size, .. } => {
+                Self::Vector { name: name.to_string(), size: *size }
+            },
+            PublicInput::Table { name, size, columns, commitment, .. } => Self::Table {
+                name: name.to_string(),
+                size: *size,
+                columns: columns.iter().map(|column| column.to_string()).collect(),
+                commitment: *commitment,
+            },
+        }
+    }
+}
+impl From<&SerializablePublicInput> for PublicInput {
+    fn from(input: &SerializablePublicInput) -> Self {
+        match input {
+            SerializablePublicInput::Vector { name, size } => PublicInput::Vector {
+                span: SourceSpan::UNKNOWN,
+                name: identifier(name),
+                size: *size,
+            },
+            SerializablePublicInput::Table { name, size, columns, commitment } => {
+                PublicInput::Table {
+                    span: SourceSpan::UNKNOWN,
+                    name: identifier(name),
+                    size: *size,
+                    columns: columns.iter().map(|column| identifier(column)).collect(),
+                    commitment: *commitment,
+                }
+            },
+        }
+    }
+}
+
+impl From<ast::Boundary> for SerializableBoundaryKind {
+    fn from(kind: ast::Boundary) -> Self {
+        match kind {
+            ast::Boundary::First => Self::First,
+            ast::Boundary::Last => Self::Last,
+        }
+    }
+}
+impl From<SerializableBoundaryKind> for ast::Boundary {
+    fn from(kind: SerializableBoundaryKind) -> Self {
+        match kind {
+            SerializableBoundaryKind::First => Self::First,
+            SerializableBoundaryKind::Last => Self::Last,
+        }
+    }
+}
+
+impl From<&AccessType> for SerializableAccessType {
+    fn from(access_type: &AccessType) -> Self {
+        match access_type {
+            AccessType::Default => Self::Default,
+            AccessType::Slice(range) => {
+                let range = range.to_slice_range();
+                Self::Slice { start: range.start, end: range.end }
+            },
+            AccessType::Index(index) => Self::Index(*index),
+            AccessType::Matrix(row, col) => Self::Matrix(*row, *col),
+            AccessType::Bound(_) => {
+                unreachable!("bound accesses are rewritten by semantic analysis")
+            },
+        }
+    }
+}
+impl From<&SerializableAccessType> for AccessType {
+    fn from(access_type: &SerializableAccessType) -> Self {
+        match access_type {
+            SerializableAccessType::Default => Self::Default,
+            SerializableAccessType::Slice { start, end } => Self::Slice((*start..*end).into()),
+            SerializableAccessType::Index(index) => Self::Index(*index),
+            SerializableAccessType::Matrix(row, col) => Self::Matrix(*row, *col),
+        }
+    }
+}
+
+impl From<ast::BusType> for SerializableBusType {
+    fn from(bus_type: ast::BusType) -> Self {
+        match bus_type {
+            ast::BusType::Multiset => Self::Multiset,
+            ast::BusType::Logup => Self::Logup,
+        }
+    }
+}
+impl From<SerializableBusType> for ast::BusType {
+    fn from(bus_type: SerializableBusType) -> Self {
+        match bus_type {
+            SerializableBusType::Multiset => Self::Multiset,
+            SerializableBusType::Logup => Self::Logup,
+        }
+    }
+}
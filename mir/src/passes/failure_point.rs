@@ -0,0 +1,67 @@
+use std::cell::RefCell;
+
+use crate::ir::{Link, Node};
+
+thread_local! {
+    static FAILURE_POINT: RefCell<Option<FailurePoint>> = const { RefCell::new(None) };
+}
+
+/// The point at which a pass of the MIR pipeline failed, recorded so that the IR around it can be
+/// reported, e.g. by `air_ir::compile_with_repro`.
+#[derive(Debug, Default, Clone)]
+pub struct FailurePoint {
+    /// The root of the graph which was being visited by a [super::Visitor] when the pass failed,
+    /// by returning an error or panicking, formatted with [Node::debug].
+    ///
+    /// It is formatted when recorded, as the graph may be dropped before the failure is reported,
+    /// e.g. while unwinding from a panic, leaving the links to its nodes stale.
+    pub root: Option<String>,
+    /// Whether the failure is an internal error of the compiler, reported with a
+    /// [miden_diagnostics::Severity::Bug] diagnostic
+    pub internal: bool,
+}
+
+/// Records that `root` was being visited when a pass failed.
+///
+/// Only the first root is kept, as the visit of a root can fail while visiting another one.
+pub(crate) fn record_failure_root(root: Link<Node>) {
+    FAILURE_POINT.with_borrow_mut(|point| {
+        let point = point.get_or_insert_with(Default::default);
+        if point.root.is_none() {
+            point.root = Some(root.debug());
+        }
+    });
+}
+
+/// Records that a pass failed with an internal error, after reporting it with a
+/// [miden_diagnostics::Severity::Bug] diagnostic.
+pub fn record_internal_error() {
+    FAILURE_POINT.with_borrow_mut(|point| {
+        point.get_or_insert_with(Default::default).internal = true;
+    });
+}
+
+/// Takes the failure point recorded on this thread since the last call, if any.
+pub fn take_failure_point() -> Option<FailurePoint> {
+    FAILURE_POINT.take()
+}
+
+/// Records the root being visited as the failure point if it is dropped before [Self::exit],
+/// i.e. if visiting the root returns early with an error, or panics.
+pub(crate) struct VisitedRoot(Option<Link<Node>>);
+impl VisitedRoot {
+    pub fn enter(root: Link<Node>) -> Self {
+        Self(Some(root))
+    }
+
+    pub fn exit(mut self) {
+        self.0 = None;
+    }
+}
+impl Drop for VisitedRoot {
+    fn drop(&mut self) {
+        if let Some(root) = self.0.take() {
+            record_failure_root(root);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, ops::Deref};
 
+use air_parser::ast::{AccessType, DocComments};
 use air_pass::Pass;
 use miden_diagnostics::{DiagnosticsHandler, Severity, SourceSpan, Spanned};
 
@@ -7,8 +8,8 @@ use super::{duplicate_node_or_replace, visitor::Visitor};
 use crate::{
     CompileError,
     ir::{
-        Accessor, Graph, Link, Mir, MirType, MirValue, Node, Op, Parameter, Parent, Root,
-        SpannedMirValue, TraceAccessBinding, Value, Vector,
+        Accessor, CallSiteInfo, Graph, Link, Mir, MirType, MirValue, Node, Op, Parameter, Parent,
+        Root, SpannedMirValue, TraceAccessBinding, Value, Vector,
     },
 };
 
@@ -21,7 +22,14 @@ use crate::{
 ///   order, this means there is a circular dependency.
 /// * Then, we visit the graph again at each Call nodes, building a duplicate of the body (with
 ///   Parameter replaced by call arguments), and replacing the Call node by this duplicate body.
-///  
+///
+/// Evaluators are pure, so calls to the same evaluator with structurally identical arguments (e.g.
+/// the same trace columns) share the nodes of a single inlined body, instead of each duplicating
+/// it.
+///
+/// Evaluators declared with the `@inline(never)` hint go further: the expressions of their body
+/// which do not depend on any parameter (e.g. combinations of periodic columns, public inputs and
+/// random values) are only duplicated once, and shared between all calls.
 pub struct Inlining<'a> {
     diagnostics: &'a DiagnosticsHandler,
 }
@@ -70,6 +78,38 @@ pub struct CallInliningContext {
 }
 impl CallInliningContext {}
 
+/// The structure of the arguments of a call, ignoring spans, used to recognize calls with identical
+/// arguments.
+///
+/// Only arguments which do not depend on the context of the call (i.e. which contain no
+/// [Parameter]) have a key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum ArgumentKey {
+    Value(MirValue),
+    Vector(Vec<ArgumentKey>),
+    Accessor(Box<ArgumentKey>, AccessType, usize),
+}
+impl ArgumentKey {
+    fn new(op: &Link<Op>) -> Option<Self> {
+        match op.borrow().deref() {
+            Op::Value(value) => Some(Self::Value(value.value.value.clone())),
+            Op::Vector(vector) => vector
+                .children()
+                .borrow()
+                .iter()
+                .map(Self::new)
+                .collect::<Option<Vec<_>>>()
+                .map(Self::Vector),
+            Op::Accessor(accessor) => Some(Self::Accessor(
+                Box::new(Self::new(&accessor.indexable)?),
+                accessor.access_type.clone(),
+                accessor.offset,
+            )),
+            _ => None,
+        }
+    }
+}
+
 pub struct InliningSecondPass<'a> {
     diagnostics: &'a DiagnosticsHandler,
 
@@ -85,6 +125,16 @@ pub struct InliningSecondPass<'a> {
 
     // HashMap<CaleePtr, (Callee, Vec<Call nodes where called>)>
     func_eval_nodes_where_called: HashMap<usize, (Link<Root>, Vec<Link<Op>>)>, // Op is a Call here
+    // HashMap<(EvaluatorPtr, Arguments), Inlined body>
+    inlined_evaluator_calls: HashMap<(usize, Vec<ArgumentKey>), Vec<Link<Op>>>,
+    // HashMap<(EvaluatorPtr, BodyNodePtr), (Body node, Shared duplicate)>, for the nodes of
+    // `@inline(never)` evaluators which do not depend on their parameters
+    shared_evaluator_nodes: HashMap<(usize, usize), (Link<Op>, Link<Op>)>,
+    // HashMap<BodyNodePtr, (Body node, Whether it depends on no parameter)>
+    argument_independent_nodes: HashMap<usize, (Link<Op>, bool)>,
+    // The doc comments of the program, from which those of the inlined evaluators are recorded
+    // in the provenance of their constraints
+    docs: DocComments,
 }
 impl<'a> InliningSecondPass<'a> {
     pub fn new(
@@ -100,6 +150,61 @@ impl<'a> InliningSecondPass<'a> {
             params_for_ref_node: HashMap::new(),
             func_eval_nodes_where_called,
             func_eval_inlining_order,
+            inlined_evaluator_calls: HashMap::new(),
+            shared_evaluator_nodes: HashMap::new(),
+            argument_independent_nodes: HashMap::new(),
+            docs: DocComments::default(),
+        }
+    }
+
+    /// Sets the doc comments of the program, see [CallSiteInfo::docs].
+    pub fn with_docs(mut self, docs: DocComments) -> Self {
+        self.docs = docs;
+        self
+    }
+
+    /// Returns true if the value of `op` does not depend on the parameters of the function or
+    /// evaluator it belongs to.
+    fn is_argument_independent(&mut self, op: &Link<Op>) -> bool {
+        if let Some((_, independent)) = self.argument_independent_nodes.get(&op.get_ptr()) {
+            return *independent;
+        }
+        let is_leaf_value = matches!(op.borrow().deref(), Op::Value(_));
+        let is_expression = matches!(
+            op.borrow().deref(),
+            Op::Add(_)
+                | Op::Sub(_)
+                | Op::Mul(_)
+                | Op::Exp(_)
+                | Op::Vector(_)
+                | Op::Matrix(_)
+                | Op::Accessor(_)
+        );
+        let independent = if is_expression {
+            let children = op.clone().as_node().children().borrow().clone();
+            children.iter().all(|child| self.is_argument_independent(child))
+        } else {
+            is_leaf_value
+        };
+        self.argument_independent_nodes.insert(op.get_ptr(), (op.clone(), independent));
+        independent
+    }
+
+    /// Replaces the duplicate of `op` by the one shared between all calls to `evaluator`, if `op`
+    /// does not depend on the parameters of the evaluator.
+    fn share_evaluator_node(&mut self, evaluator: &Link<Node>, op: &Link<Op>) {
+        if !self.is_argument_independent(op) {
+            return;
+        }
+        let key = (evaluator.get_ptr(), op.get_ptr());
+        match self.shared_evaluator_nodes.get(&key) {
+            Some((_, shared)) => {
+                self.nodes_to_replace.insert(op.get_ptr(), (op.clone(), shared.clone()));
+            },
+            None => {
+                let (_, duplicate) = self.nodes_to_replace.get(&op.get_ptr()).unwrap().clone();
+                self.shared_evaluator_nodes.insert(key, (op.clone(), duplicate));
+            },
         }
     }
 }
@@ -124,7 +229,8 @@ impl Pass for Inlining<'_> {
             self.diagnostics,
             func_eval_inlining_order.clone(),
             first_pass.func_eval_nodes_where_called.clone(),
-        );
+        )
+        .with_docs(ir.docs.clone());
         Visitor::run(&mut second_pass, ir.constraint_graph_mut())?;
 
         Ok(ir)
@@ -292,6 +398,7 @@ impl Visitor for InliningSecondPass<'_> {
 
                 let callee = call_node.function.clone();
                 let arguments = call_node.arguments.clone();
+                let call_span = call_node.span();
                 let (pure_function, body) = if let Some(f) = callee.clone().as_function() {
                     (true, f.body.clone())
                 } else if let Some(ev) = callee.clone().as_evaluator() {
@@ -302,6 +409,27 @@ impl Visitor for InliningSecondPass<'_> {
                     );
                 };
 
+                // If the same evaluator was already inlined with identical arguments, we share the
+                // nodes of its inlined body
+                let call_key = if pure_function {
+                    None
+                } else {
+                    arguments
+                        .borrow()
+                        .iter()
+                        .map(ArgumentKey::new)
+                        .collect::<Option<Vec<_>>>()
+                        .map(|arguments| (callee.get_ptr(), arguments))
+                };
+                if let Some(inlined_body) =
+                    call_key.as_ref().and_then(|key| self.inlined_evaluator_calls.get(key))
+                {
+                    let new_nodes_vector = Vector::create(inlined_body.clone(), call_node.span());
+                    drop(call_node);
+                    root_node.as_op().unwrap().set(&new_nodes_vector);
+                    continue;
+                }
+
                 let context = CallInliningContext {
                     body,
                     arguments,
@@ -327,6 +455,29 @@ impl Visitor for InliningSecondPass<'_> {
                         self.nodes_to_replace.get(&last_child_of_body.get_ptr()).unwrap().clone();
                     updated_op = Some(new_node);
                 } else {
+                    // The constraints of the inlined body were inlined through this call, in
+                    // addition to the calls through which they were inlined in the body
+                    let docs = callee
+                        .clone()
+                        .as_evaluator()
+                        .map(|evaluator| self.docs.get(evaluator.span).to_vec())
+                        .unwrap_or_default();
+                    if let Some(callee) = graph.get_evaluator_name(&callee) {
+                        for (node, new_node) in self.nodes_to_replace.values() {
+                            if !matches!(node.borrow().deref(), Op::Enf(_)) {
+                                continue;
+                            }
+                            let mut call_sites = graph.get_provenance(node).to_vec();
+                            call_sites.push(CallSiteInfo {
+                                callee,
+                                call_span,
+                                depth: call_sites.len(),
+                                docs: docs.clone(),
+                            });
+                            graph.set_provenance(new_node.clone(), call_sites);
+                        }
+                    }
+
                     // We have finished inlining the body, we can now replace the Call node with all
                     // the body
                     let mut new_nodes = Vec::new();
@@ -343,6 +494,9 @@ impl Visitor for InliningSecondPass<'_> {
                         new_nodes.iter().map(|n| n.span()).fold(SourceSpan::UNKNOWN, |acc, s| {
                             acc.merge(s).unwrap_or(SourceSpan::UNKNOWN)
                         });
+                    if let Some(key) = call_key {
+                        self.inlined_evaluator_calls.insert(key, new_nodes.clone());
+                    }
                     let new_nodes_vector = Vector::create(new_nodes, span);
 
                     updated_op = Some(new_nodes_vector);
@@ -463,6 +617,11 @@ impl Visitor for InliningSecondPass<'_> {
                         None,
                         &mut self.params_for_ref_node,
                     );
+
+                    let evaluator = self.call_inlining_context.clone().unwrap().ref_node;
+                    if evaluator.as_root().unwrap().as_evaluator().unwrap().inline_never {
+                        self.share_evaluator_node(&evaluator, &call_op);
+                    }
                 }
             }
         }
@@ -1,3 +1,4 @@
+mod failure_point;
 mod inlining;
 mod translate;
 mod unrolling;
@@ -9,10 +10,11 @@ mod visitor;
 //pub use value_numbering::ValueNumbering;
 use std::{collections::HashMap, ops::Deref};
 
+pub use failure_point::{FailurePoint, record_internal_error, take_failure_point};
 pub use inlining::Inlining;
 use miden_diagnostics::Spanned;
 pub use translate::AstToMir;
-pub use unrolling::Unrolling;
+pub use unrolling::{UnrollLimits, Unrolling};
 pub use visitor::Visitor;
 
 use crate::ir::{
@@ -290,7 +292,6 @@ pub fn duplicate_node_or_replace(
             let new_iterators = iterators
                 .borrow()
                 .iter()
-                .cloned()
                 .map(|iterator| current_replace_map.get(&iterator.get_ptr()).unwrap().1.clone())
                 .collect::<Vec<_>>()
                 .into();
@@ -321,7 +322,6 @@ pub fn duplicate_node_or_replace(
             let new_arguments = arguments
                 .borrow()
                 .iter()
-                .cloned()
                 .map(|argument| current_replace_map.get(&argument.get_ptr()).unwrap().1.clone())
                 .collect::<Vec<_>>();
             let new_node = Call::create(function, new_arguments, call.span());
@@ -343,7 +343,6 @@ pub fn duplicate_node_or_replace(
             let children = children_ref.deref();
             let new_children = children
                 .iter()
-                .cloned()
                 .map(|child| current_replace_map.get(&child.get_ptr()).unwrap().1.clone())
                 .collect();
             let new_node = Vector::create(new_children, vector.span());
@@ -365,7 +364,6 @@ pub fn duplicate_node_or_replace(
                 let row_children = row_children_ref.deref();
                 let new_row_as_vec = row_children
                     .iter()
-                    .cloned()
                     .map(|child| current_replace_map.get(&child.get_ptr()).unwrap().1.clone())
                     .collect::<Vec<_>>();
                 let new_row = Vector::create(new_row_as_vec, row.span());
@@ -427,3 +425,26 @@ pub fn duplicate_node_or_replace(
         Op::None(_) => {},
     }
 }
+
+/// Returns true if `node` is an expression which can be shared rather than duplicated by
+/// [duplicate_node_or_replace], i.e. a value, or an operation whose children were all replaced by
+/// themselves.
+///
+/// Only expressions without side effects qualify: the other nodes are either rewritten in place by
+/// the passes (e.g. calls and comprehensions), or must remain distinct (e.g. constraints).
+fn is_unchanged_expression(
+    current_replace_map: &HashMap<usize, (Link<Op>, Link<Op>)>,
+    node: &Link<Op>,
+) -> bool {
+    let is_expression = match node.borrow().deref() {
+        Op::Value(_) => return true,
+        Op::Add(_) | Op::Sub(_) | Op::Mul(_) | Op::Exp(_) | Op::Vector(_) | Op::Accessor(_) => true,
+        _ => false,
+    };
+    is_expression
+        && node.clone().as_node().children().borrow().iter().all(|child| {
+            current_replace_map
+                .get(&child.get_ptr())
+                .is_some_and(|(_, new_child)| new_child.get_ptr() == child.get_ptr())
+        })
+}
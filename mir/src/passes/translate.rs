@@ -13,7 +13,7 @@ use crate::{
         MirValue, Mul, Op, Owner, Parameter, PublicInputAccess, PublicInputTableAccess, Root,
         SpannedMirValue, Sub, TraceAccess, TraceAccessBinding, Value, Vector,
     },
-    passes::duplicate_node,
+    passes::{duplicate_node, record_internal_error},
 };
 
 /// This pass transforms a given [ast::Program] into a Middle Intermediate Representation ([Mir])
@@ -84,11 +84,17 @@ impl<'a> MirBuilder<'a> {
         let buses = &self.program.buses;
 
         self.mir.trace_columns.clone_from(trace_columns);
+        self.mir.virtual_columns.clone_from(&self.program.virtual_columns);
         self.mir.periodic_columns = self.program.periodic_columns.clone();
         self.mir.public_inputs = self.program.public_inputs.clone();
+        self.mir.docs = self.program.docs.clone();
+        self.mir.buses_span = self.program.buses_span;
         for (qual_ident, ast_bus) in buses.iter() {
             let bus = self.translate_bus_definition(ast_bus)?;
-            self.mir.constraint_graph_mut().insert_bus(*qual_ident, bus)?;
+            if let Err(err) = self.mir.constraint_graph_mut().insert_bus(*qual_ident, bus) {
+                let prev = self.mir.constraint_graph().get_bus_link(qual_ident);
+                return Err(self.conflicting_declaration(err, "bus", prev, qual_ident.span()));
+            }
         }
 
         for (ident, function) in &self.program.functions {
@@ -113,6 +119,22 @@ impl<'a> MirBuilder<'a> {
             self.translate_statement(integrity_constraint)?;
         }
 
+        for bus in self.mir.constraint_graph().buses.values() {
+            let bus = bus.borrow();
+            if !bus.multiplicity_bits.is_empty() && bus.range_checked_op.is_none() {
+                self.diagnostics
+                    .diagnostic(Severity::Error)
+                    .with_message("invalid bus declaration")
+                    .with_primary_label(
+                        bus.span(),
+                        "the multiplicity of this bus is range checked, but none of its operations has a multiplicity",
+                    )
+                    .with_note("Declare the multiplicity of an operation with `with`, e.g. `q.insert(a) with m`.")
+                    .emit();
+                return Err(CompileError::Failed);
+            }
+        }
+
         for bus in self.mir.constraint_graph().buses.values() {
             let bus_name = bus.borrow().name();
             if let Some(ref mut mirvalue) = bus.borrow().get_first().as_value_mut() {
@@ -130,7 +152,57 @@ impl<'a> MirBuilder<'a> {
     }
 
     fn translate_bus_definition(&mut self, bus: &'a ast::Bus) -> Result<Link<Bus>, CompileError> {
-        Ok(Bus::create(bus.name, bus.bus_type, bus.span()))
+        let link = Bus::create(bus.name, bus.bus_type, bus.span());
+        link.borrow_mut().is_extern = bus.is_extern;
+        if let Some(bits) = bus.multiplicity_bits {
+            if bus.bus_type != ast::BusType::Logup {
+                self.diagnostics
+                    .diagnostic(Severity::Error)
+                    .with_message("invalid bus declaration")
+                    .with_primary_label(
+                        bits.span(),
+                        "the multiplicity of a multiset bus cannot be range checked",
+                    )
+                    .with_note(
+                        "Operations on multiset buses only accept a binary selector, declare the bus as a LogUp bus to use multiplicities.",
+                    )
+                    .emit();
+                return Err(CompileError::Failed);
+            }
+            let binding = self.program.trace_columns.first().and_then(|segment| {
+                segment.bindings.iter().find(|binding| binding.name == Some(bits))
+            });
+            let Some(binding) = binding else {
+                self.diagnostics
+                    .diagnostic(Severity::Error)
+                    .with_message("invalid bus declaration")
+                    .with_primary_label(bits.span(), "expected a column binding of the main trace")
+                    .with_note(
+                        "The multiplicity of a bus is decomposed into binary columns of the main trace.",
+                    )
+                    .emit();
+                return Err(CompileError::Failed);
+            };
+            // The range must not wrap around the field modulus, which is just below 2^64
+            if binding.size >= u64::BITS as usize {
+                self.diagnostics
+                    .diagnostic(Severity::Error)
+                    .with_message("invalid bus declaration")
+                    .with_primary_label(
+                        bits.span(),
+                        format!(
+                            "the multiplicity cannot be decomposed into {} columns, the maximum is {}",
+                            binding.size,
+                            u64::BITS - 1
+                        ),
+                    )
+                    .emit();
+                return Err(CompileError::Failed);
+            }
+            link.borrow_mut().multiplicity_bits =
+                (binding.offset..binding.offset + binding.size).collect();
+        }
+        Ok(link)
     }
 
     fn translate_evaluator_signature(
@@ -141,7 +213,7 @@ impl<'a> MirBuilder<'a> {
         let mut all_params_flatten = Vec::new();
 
         self.root_name = Some(ident);
-        let mut ev = Evaluator::builder().span(ast_eval.span);
+        let mut ev = Evaluator::builder().span(ast_eval.span).inline_never(ast_eval.inline_never);
         let mut i = 0;
 
         for trace_segment in &ast_eval.params {
@@ -164,7 +236,10 @@ impl<'a> MirBuilder<'a> {
 
         set_all_ref_nodes(all_params_flatten.clone(), ev.as_owner());
 
-        self.mir.constraint_graph_mut().insert_evaluator(*ident, ev.clone())?;
+        if let Err(err) = self.mir.constraint_graph_mut().insert_evaluator(*ident, ev.clone()) {
+            let prev = self.mir.constraint_graph().get_evaluator_root(ident);
+            return Err(self.conflicting_declaration(err, "evaluator", prev, ident.span()));
+        }
 
         Ok(ev)
     }
@@ -242,7 +317,10 @@ impl<'a> MirBuilder<'a> {
         let func = func.return_type(ret).build();
         set_all_ref_nodes(params.clone(), func.as_owner());
 
-        self.mir.constraint_graph_mut().insert_function(*ident, func.clone())?;
+        if let Err(err) = self.mir.constraint_graph_mut().insert_function(*ident, func.clone()) {
+            let prev = self.mir.constraint_graph().get_function_root(ident);
+            return Err(self.conflicting_declaration(err, "function", prev, ident.span()));
+        }
 
         Ok(func)
     }
@@ -301,6 +379,7 @@ impl<'a> MirBuilder<'a> {
                     .with_message("matrix parameters not supported")
                     .with_primary_label(span, "expected this to be a felt or vector")
                     .emit();
+                record_internal_error();
                 Err(CompileError::Failed)
             },
         }
@@ -335,6 +414,7 @@ impl<'a> MirBuilder<'a> {
                     .with_message("matrix parameters not supported")
                     .with_primary_label(span, "expected this to be a felt or vector")
                     .emit();
+                record_internal_error();
                 Err(CompileError::Failed)
             },
         }
@@ -364,6 +444,26 @@ impl<'a> MirBuilder<'a> {
         Ok(func)
     }
 
+    /// Reports an attempt to register `ty` under a name which is already declared in the graph,
+    /// pointing at both the new declaration and the existing one.
+    fn conflicting_declaration<T: Spanned>(
+        &self,
+        err: CompileError,
+        ty: &str,
+        prev: Option<Link<T>>,
+        current: SourceSpan,
+    ) -> CompileError {
+        if let Some(prev) = prev {
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message(format!("invalid {ty} declaration"))
+                .with_primary_label(current, "this conflicts with a previous declaration")
+                .with_secondary_label(prev.span(), "previously defined here")
+                .emit();
+        }
+        err
+    }
+
     fn translate_type(&mut self, ty: &ast::Type) -> MirType {
         match ty {
             ast::Type::Felt => MirType::Felt,
@@ -382,18 +482,40 @@ impl<'a> MirBuilder<'a> {
             ast::Statement::BusEnforce(list_comp) => self.translate_bus_enforce(list_comp),
         }
     }
+    /// Translates a `let` and the statements it is in scope for.
+    ///
+    /// The parser nests all the statements following a `let` in its block into its body, so that
+    /// the binding remains in scope for the later sibling statements, and goes out of scope at
+    /// the end of the block.
     fn translate_let(&mut self, let_stmt: &'a ast::Let) -> Result<Link<Op>, CompileError> {
         let name = &let_stmt.name;
         let value: Link<Op> = self.translate_expr(&let_stmt.value)?;
         let mut ret_value = value.clone();
         self.bindings.enter();
         self.bindings.insert(name, value.clone());
-        for stmt in let_stmt.body.iter() {
+        for (i, stmt) in let_stmt.body.iter().enumerate() {
+            if i > 0 {
+                // Only the value of the last statement is returned, so the preceding ones, e.g.
+                // constraints enforced in the body of an evaluator, must be kept in the body
+                self.push_to_body(ret_value);
+            }
             ret_value = self.translate_statement(stmt)?;
         }
         self.bindings.exit();
         Ok(ret_value)
     }
+
+    /// Appends `op` to the body of the function or evaluator being translated, if any.
+    ///
+    /// Integrity constraints outside of functions and evaluators are inserted as roots of the
+    /// constraint graph instead, see `insert_enforce`.
+    fn push_to_body(&self, op: Link<Op>) {
+        match self.root.borrow().deref() {
+            Root::Function(f) => f.body.borrow_mut().push(op),
+            Root::Evaluator(e) => e.body.borrow_mut().push(op),
+            Root::None(_) => {},
+        }
+    }
     fn translate_expr(&mut self, expr: &'a ast::Expr) -> Result<Link<Op>, CompileError> {
         match expr {
             ast::Expr::Const(c) => self.translate_spanned_const(c),
@@ -540,9 +662,33 @@ impl<'a> MirBuilder<'a> {
             },
             _ => unimplemented!(),
         };
-        let sel = match list_comp.selector.as_ref() {
-            Some(selector) => self.translate_scalar_expr(selector)?,
-            None => {
+        // When both a selector and a multiplicity are given, the effective multiplicity of the
+        // bus operation is their product. This is only meaningful for LogUp buses, as multiset
+        // buses expect a binary latch.
+        let bus_link = bus_op.as_bus_op().unwrap().bus.to_link().unwrap();
+        let sel = match (list_comp.selector.as_ref(), list_comp.multiplicity.as_ref()) {
+            (Some(selector), None) => self.translate_scalar_expr(selector)?,
+            (None, Some(multiplicity)) => self.translate_scalar_expr(multiplicity)?,
+            (Some(selector), Some(multiplicity)) => {
+                if bus_link.borrow().bus_type != ast::BusType::Logup {
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid bus constraint")
+                        .with_primary_label(
+                            multiplicity.span(),
+                            "a multiplicity cannot be combined with a selector on a multiset bus",
+                        )
+                        .with_note(
+                            "Operations on multiset buses only accept a binary selector, declare the bus as a LogUp bus to use multiplicities.",
+                        )
+                        .emit();
+                    return Err(CompileError::Failed);
+                }
+                let sel = self.translate_scalar_expr(selector)?;
+                let multiplicity_node = self.translate_scalar_expr(multiplicity)?;
+                Mul::builder().lhs(sel).rhs(multiplicity_node).span(list_comp.span()).build()
+            },
+            (None, None) => {
                 self.diagnostics
                     .diagnostic(Severity::Error)
                     .with_message("Bus operations should always have a selector or a multiplicity")
@@ -557,11 +703,28 @@ impl<'a> MirBuilder<'a> {
                 return Err(CompileError::Failed);
             },
         };
+        if let Some(multiplicity) = list_comp.multiplicity.as_ref() {
+            let mut bus = bus_link.borrow_mut();
+            if !bus.multiplicity_bits.is_empty() {
+                if bus.range_checked_op.is_some() {
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid bus constraint")
+                        .with_primary_label(
+                            multiplicity.span(),
+                            "the multiplicity of another operation of this bus is already range checked",
+                        )
+                        .with_note(
+                            "The operations of a bus share the columns its multiplicity is decomposed into, so only one of them can have a multiplicity.",
+                        )
+                        .emit();
+                    return Err(CompileError::Failed);
+                }
+                bus.range_checked_op = Some(bus.columns.len());
+            }
+        }
         // Note: safe to unwrap because we checked that bus_op is a BusOp above
         bus_op.as_bus_op_mut().unwrap().latch.borrow_mut().clone_from(&sel.borrow());
-        let bus_op_clone = bus_op.clone();
-        let bus_op_ref = bus_op_clone.as_bus_op_mut().unwrap();
-        let bus_link = bus_op_ref.bus.to_link().unwrap();
         let mut bus = bus_link.borrow_mut();
         bus.latches.push(sel.clone());
         bus.columns.push(bus_op.clone());
@@ -668,6 +831,7 @@ impl<'a> MirBuilder<'a> {
                             value: MirValue::PeriodicColumn(crate::ir::PeriodicColumnAccess::new(
                                 qual_ident,
                                 pc.period(),
+                                access.offset,
                             )),
                         })
                         .build();
@@ -731,6 +895,21 @@ impl<'a> MirBuilder<'a> {
                 let SpannedMirValue { span: _span, value } = val_ref.value.clone();
                 if let MirValue::BusAccess(bus_access) = value {
                     let bus = bus_access.bus;
+                    if bus.borrow().is_extern {
+                        self.diagnostics
+                            .diagnostic(Severity::Error)
+                            .with_message("invalid bus boundary constraint")
+                            .with_primary_label(
+                                bin_op.span(),
+                                "the boundaries of an extern bus cannot be constrained",
+                            )
+                            .with_secondary_label(bus.borrow().span(), "declared extern here")
+                            .with_note(
+                                "The boundaries of an extern bus are constrained by the program defining it.",
+                            )
+                            .emit();
+                        return Err(CompileError::Failed);
+                    }
                     match kind {
                         ast::Boundary::First => {
                             bus.borrow_mut().set_first(rhs.clone()).map_err(|_| {
@@ -777,6 +956,13 @@ impl<'a> MirBuilder<'a> {
                 let node = Mul::builder().lhs(lhs).rhs(rhs).span(bin_op.span()).build();
                 Ok(node)
             },
+            // Division by a constant is rewritten as a multiplication during constant propagation,
+            // so the denominator of any remaining division is not constant
+            ast::BinaryOp::Div => {
+                self.diagnostics
+                    .emit(ast::InvalidExprError::NonConstantDenominator(bin_op.rhs.span()));
+                Err(CompileError::Failed)
+            },
             ast::BinaryOp::Exp => {
                 let node = Exp::builder().lhs(lhs).rhs(rhs).span(bin_op.span()).build();
                 Ok(node)
@@ -821,6 +1007,31 @@ impl<'a> MirBuilder<'a> {
                         .build();
                     Ok(node)
                 },
+                symbols::InSet => {
+                    // The set has been folded to a vector of constants during constant propagation,
+                    // and `in_set(x, set)` is the product of the differences between `x` and each
+                    // of its elements
+                    let [value, ast::Expr::Const(set)] = call.args.as_slice() else {
+                        unreachable!("invalid arguments to in_set: {:#?}", &call.args);
+                    };
+                    let ast::ConstantExpr::Vector(set) = &set.item else {
+                        unreachable!("expected the set of in_set to be a vector: {set:#?}");
+                    };
+                    let mut product = None;
+                    for elem in set.iter() {
+                        let value_node = self.translate_expr(value)?;
+                        let elem_node = self.translate_scalar_const(*elem, call.span())?;
+                        let diff =
+                            Sub::builder().lhs(value_node).rhs(elem_node).span(call.span()).build();
+                        product = Some(match product {
+                            None => diff,
+                            Some(acc) => {
+                                Mul::builder().lhs(acc).rhs(diff).span(call.span()).build()
+                            },
+                        });
+                    }
+                    Ok(product.expect("expected a non-empty set"))
+                },
                 other => unimplemented!("unhandled builtin: {}", other),
             }
         } else {
@@ -990,6 +1201,9 @@ impl<'a> MirBuilder<'a> {
                 value: MirValue::Unconstrained,
             })),
             ast::ScalarExpr::BusOperation(bo) => self.translate_bus_operation(bo),
+            ast::ScalarExpr::RowComprehension(_) => {
+                unreachable!("row comprehensions are resolved during semantic analysis")
+            },
         }
     }
 
@@ -1,10 +1,10 @@
-use std::{collections::HashMap, ops::Deref, rc::Rc};
+use std::{collections::HashMap, ops::Deref};
 
 use air_parser::ast::AccessType;
 use air_pass::Pass;
-use miden_diagnostics::{DiagnosticsHandler, Spanned};
+use miden_diagnostics::{DiagnosticsHandler, Severity, SourceSpan, Spanned};
 
-use super::{duplicate_node_or_replace, visitor::Visitor};
+use super::{duplicate_node_or_replace, is_unchanged_expression, visitor::Visitor};
 use crate::{CompileError, ir::*};
 
 /// This pass follows a similar approach as the Inlining pass.
@@ -12,17 +12,63 @@ use crate::{CompileError, ir::*};
 ///
 /// * In the first step, we visit the graph, unrolling each node type except For nodes. Instead, for
 ///   these node types we gather the context to inline them in the second pass.
-/// * In the second pass, we inline the bodies of For nodes.
+/// * In the second pass, we inline the bodies of For nodes. The expressions of a body which do not
+///   depend on the iteration (e.g. constants) are copied on write: they are shared by all
+///   iterations, and only the expressions depending on them are duplicated.
+///
+/// The size of the unrolled graph is checked against [UnrollLimits] during the first step, before
+/// any body is duplicated, so that an oversized comprehension is reported rather than exhausting
+/// memory.
 ///
 /// TODO:
 /// - [ ] Implement diagnostics for better error handling
 pub struct Unrolling<'a> {
     diagnostics: &'a DiagnosticsHandler,
+    limits: UnrollLimits,
+    share_expressions: bool,
 }
 
 impl<'a> Unrolling<'a> {
     pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
-        Self { diagnostics }
+        Self {
+            diagnostics,
+            limits: UnrollLimits::default(),
+            share_expressions: true,
+        }
+    }
+
+    /// Sets the limits on the size of the unrolled graph
+    pub fn with_limits(mut self, limits: UnrollLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets whether the expressions of a comprehension body which do not depend on the iteration
+    /// are shared by all iterations, which is the default, rather than duplicated in each one.
+    ///
+    /// Both produce equivalent graphs, duplicating them is only useful to check that sharing them
+    /// does not change the resulting constraints.
+    pub fn with_shared_expressions(mut self, share_expressions: bool) -> Self {
+        self.share_expressions = share_expressions;
+        self
+    }
+}
+
+/// Limits on the number of operations produced by unrolling comprehensions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnrollLimits {
+    /// The maximum number of iterations of a single comprehension
+    pub max_iterations: usize,
+    /// The maximum number of operations produced by unrolling all comprehensions, estimated as
+    /// the number of iterations of each comprehension times the size of its body
+    pub max_ops: usize,
+}
+impl Default for UnrollLimits {
+    fn default() -> Self {
+        Self {
+            max_iterations: 10_000,
+            max_ops: 1_000_000,
+        }
     }
 }
 
@@ -37,8 +83,10 @@ pub struct ForInliningContext {
 impl ForInliningContext {}
 
 pub struct UnrollingFirstPass<'a> {
-    #[allow(unused)]
     diagnostics: &'a DiagnosticsHandler,
+    limits: UnrollLimits,
+    // The estimated number of operations produced by the comprehensions visited so far
+    unrolled_ops: usize,
 
     // general context
     work_stack: Vec<Link<Node>>,
@@ -55,9 +103,11 @@ pub struct UnrollingFirstPass<'a> {
 }
 
 impl<'a> UnrollingFirstPass<'a> {
-    pub fn new(diagnostics: &'a DiagnosticsHandler) -> Self {
+    pub fn new(diagnostics: &'a DiagnosticsHandler, limits: UnrollLimits) -> Self {
         Self {
             diagnostics,
+            limits,
+            unrolled_ops: 0,
             work_stack: vec![],
             bodies_to_inline: vec![],
             params_for_ref_node: HashMap::new(),
@@ -83,15 +133,19 @@ pub struct UnrollingSecondPass<'a> {
     // We keep a reference to For nodes in order to avoid the backlinks stored in Parameters
     // referencing them to be dropped
     all_for_nodes: HashMap<usize, (Link<Op>, Link<Owner>)>,
+    // Whether the expressions which do not depend on the iteration are shared by all iterations
+    share_expressions: bool,
 }
 impl<'a> UnrollingSecondPass<'a> {
     pub fn new(
         diagnostics: &'a DiagnosticsHandler,
         bodies_to_inline: Vec<(Link<Op>, ForInliningContext)>,
         all_for_nodes: HashMap<usize, (Link<Op>, Link<Owner>)>,
+        share_expressions: bool,
     ) -> Self {
         Self {
             diagnostics,
+            share_expressions,
             work_stack: vec![],
             bodies_to_inline,
             for_inlining_context: None,
@@ -109,7 +163,7 @@ impl Pass for Unrolling<'_> {
 
     fn run<'a>(&mut self, mut ir: Self::Input<'a>) -> Result<Self::Output<'a>, Self::Error> {
         // The first pass unrolls all nodes fully, except for For nodes
-        let mut first_pass = UnrollingFirstPass::new(self.diagnostics);
+        let mut first_pass = UnrollingFirstPass::new(self.diagnostics, self.limits);
         Visitor::run(&mut first_pass, ir.constraint_graph_mut())?;
 
         // The second pass actually inlines the For nodes
@@ -117,6 +171,7 @@ impl Pass for Unrolling<'_> {
             self.diagnostics,
             first_pass.bodies_to_inline.clone(),
             first_pass.all_for_nodes.clone(),
+            self.share_expressions,
         );
         Visitor::run(&mut second_pass, ir.constraint_graph_mut())?;
         Ok(ir)
@@ -352,7 +407,7 @@ impl UnrollingFirstPass<'_> {
 
     fn visit_enf_bis(
         &mut self,
-        _graph: &mut Graph,
+        graph: &mut Graph,
         enf: Link<Op>,
     ) -> Result<Option<Link<Op>>, CompileError> {
         let mut updated_enf = None;
@@ -365,6 +420,7 @@ impl UnrollingFirstPass<'_> {
                 let mut new_vec = vec![];
                 for op in ops.iter() {
                     let new_node = Enf::create(op.clone(), enf_ref.span());
+                    graph.copy_provenance(&enf, new_node.clone());
                     new_vec.push(new_node);
                 }
                 updated_enf = Some(Vector::create(new_vec, enf_ref.span()));
@@ -588,113 +644,50 @@ impl UnrollingFirstPass<'_> {
                         if indexable.clone().as_matrix().is_some() {
                             unreachable!(); // raise diag
                         }*/
-                        updated_accessor = Some(indexable.clone());
-
-                        if let Some(value) = indexable.clone().as_value() {
-                            let mir_value = value.value.value.clone();
-
-                            match mir_value {
-                                MirValue::TraceAccess(trace_access) => {
-                                    let new_node = Value::create(SpannedMirValue {
-                                        span: Default::default(),
-                                        value: MirValue::TraceAccess(TraceAccess {
-                                            segment: trace_access.segment,
-                                            column: trace_access.column,
-                                            row_offset: trace_access.row_offset + offset,
-                                        }),
-                                    });
-                                    updated_accessor = Some(new_node);
-                                },
-                                _ => unreachable!(),
-                            }
+                        updated_accessor = Some(Self::offset_element(indexable.clone(), offset));
+
+                        // Only trace accesses depend on the row, other values, e.g. the constant
+                        // an empty fold reduces to, are kept as is
+                        if let Some(value) = indexable.clone().as_value()
+                            && let MirValue::TraceAccess(trace_access) = value.value.value
+                        {
+                            let new_node = Value::create(SpannedMirValue {
+                                span: Default::default(),
+                                value: MirValue::TraceAccess(TraceAccess {
+                                    segment: trace_access.segment,
+                                    column: trace_access.column,
+                                    row_offset: trace_access.row_offset + offset,
+                                }),
+                            });
+                            updated_accessor = Some(new_node);
                         }
                     },
                     AccessType::Index(index) => {
-                        // Check that the child node is a vector, raise diag otherwise
-                        // Replace the current node by the index-th element of the vector
-                        // Raise diag if index is out of bounds
-
-                        if let Op::Vector(indexable_vector) = indexable.borrow().deref() {
-                            let indexable_vec =
-                                indexable_vector.children().borrow().deref().clone();
-                            let child_accessed = match indexable_vec.get(index) {
-                                Some(child_accessed) => child_accessed,
-                                None => unreachable!(), // raise diag
-                            };
-                            if let Some(value) = child_accessed.clone().as_value() {
-                                let mir_value = value.value.value.clone();
-                                match mir_value {
-                                    MirValue::TraceAccess(trace_access) => {
-                                        let new_node = Value::create(SpannedMirValue {
-                                            span: Default::default(),
-                                            value: MirValue::TraceAccess(TraceAccess {
-                                                segment: trace_access.segment,
-                                                column: trace_access.column,
-                                                row_offset: trace_access.row_offset + offset,
-                                            }),
-                                        });
-                                        updated_accessor = Some(new_node);
-                                    },
-                                    _ => {
-                                        updated_accessor = Some(child_accessed.clone());
-                                    },
-                                }
-                            } else {
-                                updated_accessor = Some(child_accessed.clone());
-                            }
-                        } else {
-                            unreachable!("indexable is {:?}", indexable); // raise diag
-                        };
+                        // Replace the current node by the index-th element of the vector, or the
+                        // index-th row of the matrix
+                        let elements = Self::indexable_elements(&indexable);
+                        let element = self.get_element(&elements, index, accessor_ref.span())?;
+                        updated_accessor = Some(Self::offset_element(element, offset));
                     },
                     AccessType::Matrix(row, col) => {
-                        // Check that the child node is a matrix, raise diag otherwise
-                        // Replace the current node by the index-th element of the vector
-                        // Raise diag if index is out of bounds
-
-                        if let Op::Vector(indexable_vector) = indexable.borrow().deref() {
-                            let indexable_vec =
-                                indexable_vector.children().borrow().deref().clone();
-                            let row_accessed = match indexable_vec.get(row) {
-                                Some(row_accessed) => row_accessed,
-                                None => unreachable!(), // raise diag
-                            };
-
-                            if let Op::Vector(row_accessed_vector) = row_accessed.borrow().deref() {
-                                let row_accessed_vec =
-                                    row_accessed_vector.children().borrow().deref().clone();
-                                let child_accessed = match row_accessed_vec.get(col) {
-                                    Some(child_accessed) => child_accessed,
-                                    None => unreachable!(), // raise diag
-                                };
-                                updated_accessor = Some(child_accessed.clone());
-                            } else {
-                                unreachable!(); // raise diag
-                            };
-                        } else if let Op::Matrix(indexable_matrix) = indexable.borrow().deref() {
-                            let indexable_vec =
-                                indexable_matrix.children().borrow().deref().clone();
-                            let row_accessed = match indexable_vec.get(row) {
-                                Some(row_accessed) => row_accessed,
-                                None => unreachable!(), // raise diag
-                            };
-
-                            if let Op::Vector(row_accessed_vector) = row_accessed.borrow().deref() {
-                                let row_accessed_vec =
-                                    row_accessed_vector.children().borrow().deref().clone();
-                                let child_accessed = match row_accessed_vec.get(col) {
-                                    Some(child_accessed) => child_accessed,
-                                    None => unreachable!(), // raise diag
-                                };
-                                updated_accessor = Some(child_accessed.clone());
-                            } else {
-                                unreachable!(); // raise diag
-                            };
+                        // Replace the current node by the element at the given row and column of
+                        // the matrix
+                        let rows = Self::indexable_elements(&indexable);
+                        let row_accessed = self.get_element(&rows, row, accessor_ref.span())?;
+                        let Op::Vector(row_accessed_vector) = row_accessed.borrow().deref().clone()
+                        else {
+                            unreachable!("row accessed is {:?}", row_accessed);
                         };
+                        let row_accessed_vec = row_accessed_vector.children().borrow().clone();
+                        let element =
+                            self.get_element(&row_accessed_vec, col, accessor_ref.span())?;
+                        updated_accessor = Some(Self::offset_element(element, offset));
                     },
 
                     AccessType::Slice(_range_expr) => {
                         unreachable!(); // Slices are not scalar, raise diag
                     },
+                    AccessType::Bound(_) => unreachable!(),
                 }
             }
         }
@@ -702,6 +695,88 @@ impl UnrollingFirstPass<'_> {
         Ok(updated_accessor)
     }
 
+    /// Returns the elements of a vector, or the rows of a matrix
+    fn indexable_elements(indexable: &Link<Op>) -> Vec<Link<Op>> {
+        match indexable.borrow().deref() {
+            Op::Vector(vector) => vector.children().borrow().deref().clone(),
+            Op::Matrix(matrix) => matrix.children().borrow().deref().clone(),
+            _ => unreachable!("indexable is {:?}", indexable),
+        }
+    }
+
+    /// Returns the element at `index`, raising a diagnostic if it is out of bounds
+    fn get_element(
+        &self,
+        elements: &[Link<Op>],
+        index: usize,
+        span: SourceSpan,
+    ) -> Result<Link<Op>, CompileError> {
+        match elements.get(index) {
+            Some(element) => Ok(element.clone()),
+            None => {
+                self.diagnostics
+                    .diagnostic(Severity::Error)
+                    .with_message("invalid access")
+                    .with_primary_label(
+                        span,
+                        format!(
+                            "index {index} is out of bounds, the accessed value has {} elements",
+                            elements.len()
+                        ),
+                    )
+                    .emit();
+                Err(CompileError::Failed)
+            },
+        }
+    }
+
+    /// Applies the row offset of an access to the accessed element.
+    ///
+    /// Trace accesses are shifted by `offset` rows, accessors have `offset` added to their own, and
+    /// vectors (i.e. rows of a matrix) have the offset applied to each of their elements.
+    ///
+    /// Values, accessors and vectors are copied even when `offset` is zero: the element may be
+    /// shared, e.g. an argument used several times in the body of an inlined evaluator, and would
+    /// otherwise be replaced in place when the accessor is replaced by it.
+    fn offset_element(element: Link<Op>, offset: usize) -> Link<Op> {
+        let op = element.borrow().deref().clone();
+        match op {
+            Op::Value(Value {
+                value:
+                    SpannedMirValue {
+                        span,
+                        value: MirValue::TraceAccess(trace_access),
+                    },
+                ..
+            }) => Value::create(SpannedMirValue {
+                span,
+                value: MirValue::TraceAccess(TraceAccess {
+                    segment: trace_access.segment,
+                    column: trace_access.column,
+                    row_offset: trace_access.row_offset + offset,
+                }),
+            }),
+            Op::Value(value) => Value::create(value.value),
+            Op::Accessor(accessor) => Accessor::create(
+                accessor.indexable.clone(),
+                accessor.access_type.clone(),
+                accessor.offset + offset,
+                accessor.span(),
+            ),
+            Op::Vector(vector) => {
+                let elements = vector
+                    .children()
+                    .borrow()
+                    .iter()
+                    .cloned()
+                    .map(|element| Self::offset_element(element, offset))
+                    .collect();
+                Vector::create(elements, vector.span())
+            },
+            _ => element,
+        }
+    }
+
     fn compute_iterator_len(iterator: Link<Op>) -> usize {
         match iterator.borrow().deref() {
             Op::Vector(vector) => vector.size,
@@ -726,6 +801,7 @@ impl UnrollingFirstPass<'_> {
                     _ => unreachable!(), // Raise diag
                 },
                 AccessType::Matrix(..) => 1,
+                AccessType::Bound(_) => unreachable!(),
             },
             Op::Parameter(parameter) => match parameter.ty {
                 MirType::Felt => 1,
@@ -736,6 +812,59 @@ impl UnrollingFirstPass<'_> {
         }
     }
 
+    /// Checks that unrolling a comprehension of `iterations` iterations over `body` stays within
+    /// the [UnrollLimits], raising a diagnostic otherwise.
+    fn check_unroll_limits(
+        &mut self,
+        iterations: usize,
+        body: &Link<Op>,
+        span: SourceSpan,
+    ) -> Result<(), CompileError> {
+        if iterations > self.limits.max_iterations {
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("comprehension is too large to unroll")
+                .with_primary_label(
+                    span,
+                    format!(
+                        "this comprehension has {iterations} iterations, which exceeds the limit \
+                         of {}",
+                        self.limits.max_iterations
+                    ),
+                )
+                .with_note(
+                    "The limit can be raised with `UnrollLimits::max_iterations`, or the \
+                     `--max-unroll` option of `airc transpile`.",
+                )
+                .emit();
+            return Err(CompileError::Failed);
+        }
+
+        // The body only needs to be measured up to the point where the limit is exceeded
+        let budget = self.limits.max_ops.saturating_sub(self.unrolled_ops);
+        let body_size = count_ops(body, budget / iterations.max(1) + 1);
+        self.unrolled_ops = self.unrolled_ops.saturating_add(iterations.saturating_mul(body_size));
+        if self.unrolled_ops > self.limits.max_ops {
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("unrolled constraints are too large")
+                .with_primary_label(
+                    span,
+                    format!(
+                        "unrolling this comprehension exceeds the limit of {} operations",
+                        self.limits.max_ops
+                    ),
+                )
+                .with_note(
+                    "The limit can be raised with `UnrollLimits::max_ops`, or the \
+                     `--max-unrolled-ops` option of `airc transpile`.",
+                )
+                .emit();
+            return Err(CompileError::Failed);
+        }
+        Ok(())
+    }
+
     fn visit_for_bis(
         &mut self,
         _graph: &mut Graph,
@@ -772,6 +901,19 @@ impl UnrollingFirstPass<'_> {
                 }
             }
 
+            self.check_unroll_limits(iterator_expected_len, &expr, for_ref.span())?;
+
+            // The elements of vectors and matrices are only collected once, rather than for each
+            // iteration
+            let iterator_elements = iterators
+                .iter()
+                .map(|op| match op.borrow().deref() {
+                    Op::Vector(vector) => Some(vector.children().borrow().deref().clone()),
+                    Op::Matrix(matrix) => Some(matrix.children().borrow().deref().clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
             let mut new_vec = vec![];
 
             for i in 0..iterator_expected_len {
@@ -781,16 +923,12 @@ impl UnrollingFirstPass<'_> {
 
                 let iterators_i = iterators
                     .iter()
-                    .map(|op| {
+                    .zip(iterator_elements.iter())
+                    .map(|(op, elements)| {
+                        if let Some(elements) = elements {
+                            return elements[i].clone();
+                        }
                         match op.borrow().deref() {
-                            Op::Vector(vector) => {
-                                let children = vector.children().borrow().deref().clone();
-                                children[i].clone()
-                            },
-                            Op::Matrix(matrix) => {
-                                let children = matrix.children().borrow().deref().clone();
-                                children[i].clone()
-                            },
                             Op::Accessor(accessor) => {
                                 match accessor.indexable.borrow().deref() {
                                     // If we access an outer loop parameter in the body of an inner
@@ -941,9 +1079,11 @@ impl Visitor for UnrollingFirstPass<'_> {
             Node::None(_) => Ok(None),
         };
 
-        // We update the node if needed
+        // We update the node if needed, along with its provenance if it becomes a constraint
         if let Some(updated_op) = updated_op? {
-            node.as_op().unwrap().set(&updated_op);
+            let op = node.as_op().unwrap();
+            op.set(&updated_op);
+            graph.copy_provenance(&updated_op, op);
         }
 
         Ok(())
@@ -959,22 +1099,15 @@ impl Visitor for UnrollingSecondPass<'_> {
         self.bodies_to_inline
             .iter()
             .map(|(k, _v)| k)
-            .cloned()
             .map(|op| op.as_node())
             .collect::<Vec<_>>()
     }
     fn run(&mut self, graph: &mut Graph) -> Result<(), CompileError> {
-        for root in self.root_nodes_to_visit(graph).iter() {
+        // The root nodes are the children of For nodes, so each comes with its inlining context
+        for (node, context) in self.bodies_to_inline.clone() {
+            let root = &node.as_node();
             // Set context to inline the body for this index
-            let for_inlining_context = self.bodies_to_inline.iter().find_map(|(node, context)| {
-                if Rc::ptr_eq(&node.clone().as_node().link, &root.link) {
-                    Some(context.clone())
-                } else {
-                    None
-                }
-            });
-
-            self.for_inlining_context = for_inlining_context;
+            self.for_inlining_context = Some(context);
             // We inline a new body, so we clear the nodes to replace and the parameters for the ref
             // node
             self.nodes_to_replace.clear();
@@ -984,27 +1117,28 @@ impl Visitor for UnrollingSecondPass<'_> {
             while let Some(node) = self.work_stack().pop() {
                 self.visit_node(graph, node.clone())?;
             }
+            for (node, new_node) in self.nodes_to_replace.values() {
+                graph.copy_provenance(node, new_node.clone());
+            }
 
             // We have finished inlining the body, we can now replace the Root node with the body
             let body = self.for_inlining_context.clone().unwrap().body;
             let new_node = self.nodes_to_replace.get(&body.get_ptr()).unwrap().1.clone();
 
             // If there is a selector, we need to enforce it on the body
-            let new_node_with_selector_if_needed = if let Some(selector) =
-                self.for_inlining_context.clone().unwrap().selector
-            {
-                let zero_node = Value::create(SpannedMirValue {
-                    span: Default::default(),
-                    value: MirValue::Constant(ConstantValue::Felt(0)),
-                });
-                // FIXME: The Sub here is used to keep the form of Eq(lhs, rhs) -> Enf(Sub(lhs, rhs)
-                // == 0), but it introduces an unnecessary zero node
-                Sub::create(Mul::create(selector, new_node, root.span()), zero_node, root.span())
-            } else {
-                new_node
-            };
+            let new_node_with_selector_if_needed =
+                match self.for_inlining_context.clone().unwrap().selector {
+                    Some(selector) => enforce_with_selector(graph, &selector, new_node, root.span()),
+                    None => new_node,
+                };
 
-            root.as_op().unwrap().set(&new_node_with_selector_if_needed);
+            let root_op = root.as_op().unwrap();
+            root_op.set(&new_node_with_selector_if_needed);
+            graph.copy_provenance(&new_node_with_selector_if_needed, root_op.clone());
+            let selectors = graph.get_selectors(&new_node_with_selector_if_needed).to_vec();
+            if !selectors.is_empty() {
+                graph.set_selectors(root_op, selectors);
+            }
 
             // Reset context to None
             self.for_inlining_context = None;
@@ -1017,6 +1151,11 @@ impl Visitor for UnrollingSecondPass<'_> {
             return Ok(());
         }
         if let Some(op) = node.clone().as_op() {
+            // Expressions which do not depend on the iteration are shared by all iterations
+            if self.share_expressions && is_unchanged_expression(&self.nodes_to_replace, &op) {
+                self.nodes_to_replace.insert(op.get_ptr(), (op.clone(), op));
+                return Ok(());
+            }
             duplicate_node_or_replace(
                 &mut self.nodes_to_replace,
                 op,
@@ -1038,6 +1177,68 @@ impl Visitor for UnrollingSecondPass<'_> {
     }
 }
 
+/// Enforces the constraints of `body` under `selector`, by multiplying each of them by the
+/// selector, and records the selectors under which each resulting constraint is enforced.
+///
+/// A body enforcing several constraints, e.g. a call to an evaluator, is a vector of [Enf] nodes,
+/// possibly nested, over which the selector is distributed.
+fn enforce_with_selector(
+    graph: &mut Graph,
+    selector: &Link<Op>,
+    body: Link<Op>,
+    span: SourceSpan,
+) -> Link<Op> {
+    let elements = body.as_vector().map(|vector| vector.elements.borrow().clone());
+    if let Some(elements) = elements {
+        let elements = elements
+            .into_iter()
+            .map(|element| {
+                let enforced = element.as_enf().map(|enf| (enf.expr.clone(), enf.span()));
+                match enforced {
+                    Some((expr, span)) => {
+                        let expr = enforce_with_selector(graph, selector, expr, span);
+                        let enf = Enf::create(expr, span);
+                        graph.copy_provenance(&element, enf.clone());
+                        enf
+                    },
+                    None => enforce_with_selector(graph, selector, element, span),
+                }
+            })
+            .collect();
+        return Vector::create(elements, span);
+    }
+
+    // The selectors of a nested comprehension enforced in the body follow this one
+    let mut selectors = vec![selector.clone()];
+    selectors.extend(graph.get_selectors(&body).iter().cloned());
+    let zero_node = Value::create(SpannedMirValue {
+        span: Default::default(),
+        value: MirValue::Constant(ConstantValue::Felt(0)),
+    });
+    // FIXME: The Sub here is used to keep the form of Eq(lhs, rhs) -> Enf(Sub(lhs, rhs)
+    // == 0), but it introduces an unnecessary zero node
+    let enforced = Sub::create(Mul::create(selector.clone(), body, span), zero_node, span);
+    graph.set_selectors(enforced.clone(), selectors);
+    enforced
+}
+
+/// Returns the number of operations in the graph rooted at `root`, counting shared operations
+/// once per use, or any number greater than `cap` if there are more than `cap` of them.
+fn count_ops(root: &Link<Op>, cap: usize) -> usize {
+    let mut count = 0;
+    let mut stack = vec![root.clone().as_node()];
+    while let Some(node) = stack.pop() {
+        count += 1;
+        if count > cap {
+            break;
+        }
+        if node.clone().as_owner().is_some() {
+            stack.extend(node.children().borrow().iter().map(|child| child.clone().as_node()));
+        }
+    }
+    count
+}
+
 fn to_link_and<F>(
     back: BackLink<Op>,
     graph: &mut Graph,
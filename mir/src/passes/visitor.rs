@@ -1,5 +1,6 @@
 use std::ops::Deref;
 
+use super::failure_point::VisitedRoot;
 use crate::{
     CompileError,
     ir::{Graph, Link, Node, Op, Parent, Root},
@@ -14,6 +15,9 @@ use crate::{
 /// - Once we have scanned all the children, we visit the nodes in the stack starting from the last
 ///   one
 /// - We then dispatch to the relevant `visit_*` method  based on the variant of the node
+///
+/// If visiting a root fails, by returning an error or panicking, the root is recorded as the
+/// [super::FailurePoint] of the pass.
 pub trait Visitor {
     fn work_stack(&mut self) -> &mut Vec<Link<Node>>;
     /// Entry points for the visitor
@@ -21,11 +25,13 @@ pub trait Visitor {
     /// Run the visitor on the graph
     fn run(&mut self, graph: &mut Graph) -> Result<(), CompileError> {
         for root in self.root_nodes_to_visit(graph) {
+            let visited = VisitedRoot::enter(root.clone());
             self.scan_node(graph, root.clone())?;
 
             while let Some(node) = self.work_stack().pop() {
                 self.visit_node(graph, node)?;
             }
+            visited.exit();
         }
         Ok(())
     }
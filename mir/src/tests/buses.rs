@@ -310,3 +310,20 @@ fn err_trace_columns_constrained_with_bus_access() {
 
     expect_diagnostic(source, "error: invalid expression");
 }
+
+#[test]
+fn duplicate_bus_keeps_first_declaration() {
+    let mut mir = Mir::default();
+    let name = ast::Identifier::new(SourceSpan::default(), Symbol::intern("p"));
+    let ident = ast::QualifiedIdentifier::new(
+        ast::Identifier::new(SourceSpan::default(), Symbol::intern("test")),
+        ast::NamespacedIdentifier::Binding(name),
+    );
+    let first = Bus::create(name, ast::BusType::Multiset, SourceSpan::default());
+    let second = Bus::create(name, ast::BusType::Logup, SourceSpan::default());
+
+    let graph = mir.constraint_graph_mut();
+    assert!(graph.insert_bus(ident, first).is_ok());
+    assert!(graph.insert_bus(ident, second).is_err());
+    assert_eq!(graph.get_bus(&ident).unwrap().bus_type, ast::BusType::Multiset);
+}
@@ -1,4 +1,10 @@
-use super::compile;
+use std::collections::HashSet;
+
+use air_parser::{Symbol, ast};
+use miden_diagnostics::SourceSpan;
+
+use super::{compile, compile_without_sharing, inline};
+use crate::ir::{Builder, Evaluator, Mir, Parent};
 
 #[test]
 fn simple_evaluator() {
@@ -112,3 +118,212 @@ fn ev_fn_call_with_column_group() {
 
     assert!(compile(source).is_ok());
 }
+
+#[test]
+fn identical_evaluator_calls_share_inlined_body() {
+    let source = "
+    def test
+    ev is_binary([x]) {
+        enf x^2 = x;
+    }
+
+    trace_columns {
+        main: [a, b],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf is_binary([a]);
+        enf is_binary([b]);
+        enf is_binary([a]);
+    }";
+
+    let mir = inline(source).unwrap();
+    let roots = mir.constraint_graph().integrity_constraints_roots.borrow().clone();
+    assert_eq!(roots.len(), 3);
+
+    // Each call is replaced by a vector of the nodes of the inlined body
+    let inlined_bodies = roots
+        .iter()
+        .map(|root| {
+            let inlined = root.as_enf().unwrap().expr.clone();
+            let body = inlined.as_vector().unwrap().children().borrow().clone();
+            body.iter().map(|node| node.get_ptr()).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(inlined_bodies[0], inlined_bodies[2]);
+    assert_ne!(inlined_bodies[0], inlined_bodies[1]);
+
+    // The shared nodes are still translated into one constraint per call
+    let mir = compile(source).unwrap();
+    assert_eq!(mir.constraint_graph().integrity_constraints_roots.borrow().len(), 3);
+}
+
+#[test]
+fn inline_never_evaluator_shares_argument_independent_nodes() {
+    let source = |hint: &str| {
+        format!(
+            "
+    def test
+    {hint}
+    ev scaled([x]) {{
+        enf x' = x * (k0 + k1 * k2);
+    }}
+
+    trace_columns {{
+        main: [a, b, c, d],
+    }}
+
+    periodic_columns {{
+        k0: [1, 0],
+        k1: [1, 1, 0, 0],
+        k2: [1, 0, 0, 0],
+    }}
+
+    public_inputs {{
+        stack_inputs: [16],
+    }}
+
+    boundary_constraints {{
+        enf a.first = 0;
+    }}
+
+    integrity_constraints {{
+        enf scaled([a]);
+        enf scaled([b]);
+        enf scaled([c]);
+        enf scaled([d]);
+    }}"
+        )
+    };
+
+    // Counts the distinct nodes reachable from the integrity constraints
+    let count_nodes = |mir: &Mir| {
+        let mut visited = HashSet::new();
+        let mut stack = mir
+            .constraint_graph()
+            .integrity_constraints_roots
+            .borrow()
+            .iter()
+            .map(|root| root.clone().as_node())
+            .collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            if visited.insert(node.get_ptr()) && node.clone().as_owner().is_some() {
+                stack.extend(node.children().borrow().iter().map(|child| child.clone().as_node()));
+            }
+        }
+        visited.len()
+    };
+
+    let inlined = inline(&source("")).unwrap();
+    let shared = inline(&source("@inline(never)")).unwrap();
+    let evaluators = shared.constraint_graph().get_evaluator_nodes();
+    assert!(evaluators.iter().all(|ev| ev.as_evaluator().unwrap().inline_never));
+    // `k0 + k1 * k2` and its operands are duplicated for each call, unless they are shared
+    assert!(count_nodes(&shared) < count_nodes(&inlined));
+
+    // Sharing does not change the constraints
+    let inlined = compile(&source("")).unwrap();
+    let shared = compile(&source("@inline(never)")).unwrap();
+    assert_eq!(
+        inlined.constraint_graph().integrity_constraints_roots.borrow().len(),
+        shared.constraint_graph().integrity_constraints_roots.borrow().len()
+    );
+}
+
+#[test]
+fn duplicate_evaluator_keeps_first_declaration() {
+    let mut mir = Mir::default();
+    let ident = ast::QualifiedIdentifier::new(
+        ast::Identifier::new(SourceSpan::default(), Symbol::intern("test")),
+        ast::NamespacedIdentifier::Function(ast::Identifier::new(
+            SourceSpan::default(),
+            Symbol::intern("advance_clock"),
+        )),
+    );
+    let first = Evaluator::builder()
+        .span(SourceSpan::default())
+        .parameters(vec![])
+        .inline_never(false)
+        .build();
+    let second = Evaluator::builder().span(SourceSpan::default()).inline_never(false).build();
+
+    let graph = mir.constraint_graph_mut();
+    assert!(graph.insert_evaluator(ident, first).is_ok());
+    assert!(graph.insert_evaluator(ident, second).is_err());
+    assert_eq!(graph.get_evaluator(&ident).unwrap().parameters.len(), 1);
+}
+
+/// Returns a program calling an evaluator of `num_constraints` constraints from a comprehension of
+/// `num_iterations` iterations.
+fn evaluator_in_comprehension(num_constraints: usize, num_iterations: usize) -> String {
+    let body: String = (0..num_constraints)
+        .map(|i| format!("        enf x^2 * {i} = x + y * {}", i + 1))
+        .collect::<Vec<_>>()
+        .join(";\n");
+    format!(
+        "
+    def EvaluatorInComprehensionAir
+
+    trace_columns {{
+        main: [c[{num_iterations}], d[{num_iterations}]],
+    }}
+
+    public_inputs {{
+        inputs: [1],
+    }}
+
+    ev constraints([x, y]) {{
+{body};
+    }}
+
+    boundary_constraints {{
+        enf c[0].first = 0;
+    }}
+
+    integrity_constraints {{
+        enf constraints([x, y]) for (x, y) in (c, d);
+    }}"
+    )
+}
+
+/// Returns the number of distinct nodes reachable from the integrity constraints of `mir`.
+fn num_integrity_nodes(mir: &Mir) -> usize {
+    let mut visited = HashSet::new();
+    let mut stack = mir.constraint_graph().integrity_constraints_roots.borrow().clone();
+    while let Some(op) = stack.pop() {
+        if visited.insert(op.get_ptr()) {
+            stack.extend(op.as_node().children().borrow().iter().cloned());
+        }
+    }
+    visited.len()
+}
+
+#[test]
+fn evaluator_in_comprehension_shares_constants() {
+    let source = evaluator_in_comprehension(50, 64);
+    let Ok(shared) = compile(&source) else {
+        panic!("expected compilation to succeed");
+    };
+    let Ok(duplicated) = compile_without_sharing(&source) else {
+        panic!("expected compilation to succeed");
+    };
+
+    // The constants of the body do not depend on the iteration, so sharing them saves a fraction
+    // of the nodes of each iteration, which `air_ir` checks yields the same constraints
+    let num_shared = num_integrity_nodes(&shared);
+    let num_duplicated = num_integrity_nodes(&duplicated);
+    assert!(
+        num_shared <= num_duplicated * 4 / 5,
+        "expected at most {} nodes, got {num_shared}",
+        num_duplicated * 4 / 5
+    );
+}
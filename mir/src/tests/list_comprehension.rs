@@ -1,5 +1,5 @@
-use super::compile;
-use crate::ir::assert_integrity_eq;
+use super::{Compiler, compile, compile_with_limits, expect_diagnostic};
+use crate::{ir::assert_integrity_eq, passes::UnrollLimits};
 
 #[test]
 fn list_comprehension_nested_nobind() {
@@ -72,3 +72,61 @@ fn list_comprehension_nested_nobind() {
     };
     assert_integrity_eq(&mut nested, &mut explicit);
 }
+
+/// Returns a program with a comprehension of `n` constraints.
+fn large_comprehension(n: usize) -> String {
+    format!(
+        "
+    def LargeComprehensionAir
+
+    trace_columns {{
+        main: [a],
+    }}
+
+    public_inputs {{
+        input: [1],
+    }}
+
+    boundary_constraints {{
+        enf a.first = 0;
+    }}
+
+    integrity_constraints {{
+        enf a * (i + 1) = 0 for i in 0..{n};
+    }}"
+    )
+}
+
+#[test]
+fn list_comprehension_exceeding_unroll_limit() {
+    expect_diagnostic(
+        &large_comprehension(100_000),
+        "this comprehension has 100000 iterations, which exceeds the limit of 10000",
+    );
+    expect_diagnostic(&large_comprehension(100_000), "`--max-unroll`");
+}
+
+#[test]
+fn list_comprehension_exceeding_unrolled_ops_limit() {
+    let compiler = Compiler::default();
+    let limits = UnrollLimits { max_iterations: 100_000, max_ops: 1_000 };
+    let err = compiler.compile_with_limits(&large_comprehension(100_000), limits).unwrap_err();
+    compiler.diagnostics.emit(err);
+    assert!(
+        compiler
+            .emitter
+            .captured()
+            .contains("unrolling this comprehension exceeds the limit of 1000 operations")
+    );
+}
+
+#[test]
+fn list_comprehension_within_raised_unroll_limit() {
+    expect_diagnostic(&large_comprehension(20_000), "this comprehension has 20000 iterations");
+
+    let limits = UnrollLimits {
+        max_iterations: 20_000,
+        ..Default::default()
+    };
+    assert!(compile_with_limits(&large_comprehension(20_000), limits).is_ok());
+}
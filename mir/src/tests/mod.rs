@@ -27,7 +27,7 @@ use miden_diagnostics::{CodeMap, DiagnosticsConfig, DiagnosticsHandler, Verbosit
 /// However, instead of removing the following tests, we should ensure the resulting Mir graph
 /// is consistent with what is expected, as well as test each pass.
 pub use crate::CompileError;
-use crate::ir::Mir;
+use crate::{ir::Mir, passes::UnrollLimits};
 
 pub fn compile(source: &str) -> Result<Mir, ()> {
     let compiler = Compiler::default();
@@ -41,6 +41,32 @@ pub fn compile(source: &str) -> Result<Mir, ()> {
     }
 }
 
+pub fn compile_with_limits(source: &str, limits: UnrollLimits) -> Result<Mir, ()> {
+    let compiler = Compiler::default();
+    match compiler.compile_with_limits(source, limits) {
+        Ok(mir) => Ok(mir),
+        Err(err) => {
+            compiler.diagnostics.emit(err);
+            compiler.emitter.print_captured_to_stderr();
+            Err(())
+        },
+    }
+}
+
+/// Compiles `source` with the Unrolling pass duplicating, in each iteration of a comprehension,
+/// the expressions it otherwise shares, see [crate::passes::Unrolling::with_shared_expressions].
+pub fn compile_without_sharing(source: &str) -> Result<Mir, ()> {
+    let compiler = Compiler::default();
+    match compiler.compile_with_unrolling(source, UnrollLimits::default(), false) {
+        Ok(mir) => Ok(mir),
+        Err(err) => {
+            compiler.diagnostics.emit(err);
+            compiler.emitter.print_captured_to_stderr();
+            Err(())
+        },
+    }
+}
+
 pub fn translate(source: &str) -> Result<Mir, ()> {
     let compiler = Compiler::default();
     match compiler.translate(source) {
@@ -53,6 +79,18 @@ pub fn translate(source: &str) -> Result<Mir, ()> {
     }
 }
 
+pub fn inline(source: &str) -> Result<Mir, ()> {
+    let compiler = Compiler::default();
+    match compiler.inline(source) {
+        Ok(mir) => Ok(mir),
+        Err(err) => {
+            compiler.diagnostics.emit(err);
+            compiler.emitter.print_captured_to_stderr();
+            Err(())
+        },
+    }
+}
+
 #[allow(dead_code)]
 pub fn parse(source: &str) -> Result<air_parser::ast::Program, ()> {
     let compiler = Compiler::default();
@@ -109,6 +147,21 @@ impl Compiler {
     }
 
     pub fn compile(&self, source: &str) -> Result<Mir, CompileError> {
+        self.compile_with_limits(source, UnrollLimits::default())
+    }
+    pub fn compile_with_limits(
+        &self,
+        source: &str,
+        limits: UnrollLimits,
+    ) -> Result<Mir, CompileError> {
+        self.compile_with_unrolling(source, limits, true)
+    }
+    pub fn compile_with_unrolling(
+        &self,
+        source: &str,
+        limits: UnrollLimits,
+        share_expressions: bool,
+    ) -> Result<Mir, CompileError> {
         air_parser::parse(&self.diagnostics, self.codemap.clone(), source)
             .map_err(CompileError::Parse)
             .and_then(|ast| {
@@ -116,7 +169,11 @@ impl Compiler {
                     air_parser::transforms::ConstantPropagation::new(&self.diagnostics)
                         .chain(crate::passes::AstToMir::new(&self.diagnostics))
                         .chain(crate::passes::Inlining::new(&self.diagnostics))
-                        .chain(crate::passes::Unrolling::new(&self.diagnostics));
+                        .chain(
+                            crate::passes::Unrolling::new(&self.diagnostics)
+                                .with_limits(limits)
+                                .with_shared_expressions(share_expressions),
+                        );
                 pipeline.run(ast)
             })
     }
@@ -130,6 +187,17 @@ impl Compiler {
                 pipeline.run(ast)
             })
     }
+    pub fn inline(&self, source: &str) -> Result<Mir, CompileError> {
+        air_parser::parse(&self.diagnostics, self.codemap.clone(), source)
+            .map_err(CompileError::Parse)
+            .and_then(|ast| {
+                let mut pipeline =
+                    air_parser::transforms::ConstantPropagation::new(&self.diagnostics)
+                        .chain(crate::passes::AstToMir::new(&self.diagnostics))
+                        .chain(crate::passes::Inlining::new(&self.diagnostics));
+                pipeline.run(ast)
+            })
+    }
     #[allow(dead_code)]
     pub fn parse(&self, source: &str) -> Result<air_parser::ast::Program, CompileError> {
         air_parser::parse(&self.diagnostics, self.codemap.clone(), source)
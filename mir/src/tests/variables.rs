@@ -185,7 +185,7 @@ fn invalid_matrix_literal_with_leading_vector_binding() {
 
     expect_diagnostic(
         source,
-        "expected one of: '\"!\"', '\"(\"', '\"null\"', '\"unconstrained\"', 'decl_ident_ref', 'function_identifier', 'identifier', 'int'",
+        "expected one of: '\"!\"', '\"(\"', '\"-\"', '\"null\"', '\"unconstrained\"', 'decl_ident_ref', 'function_identifier', 'identifier', 'int'",
     );
 }
 
@@ -0,0 +1,425 @@
+//! This module provides information about the symbols declared in an AirScript program, intended
+//! for editor tooling, e.g. autocompletion and go-to-definition.
+//!
+//! The [SymbolTable] of a valid program is built during semantic analysis, see [symbol_table].
+//! For a program with errors, [symbol_table_from_file] falls back to the declarations which could
+//! be parsed, without requiring the program to be valid.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::Path,
+    sync::Arc,
+};
+
+use miden_diagnostics::{CodeMap, DiagnosticsHandler, NullEmitter, SourceSpan, Spanned};
+
+use crate::{
+    ast::{
+        BusType, Identifier, Import, Library, Module, ModuleId, Program, PublicInput, TraceSegment,
+        TraceSegmentId, Type,
+    },
+    parser::ParseError,
+};
+
+/// The symbols declared in the modules of an AirScript program.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolTable {
+    /// The root module of the program
+    pub root: Option<ModuleId>,
+    /// The symbols declared in each module, the root module first
+    pub symbols: Vec<SymbolInfo>,
+}
+
+/// A symbol declared in an AirScript module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolInfo {
+    /// The name of this symbol, whose span is the span of its definition
+    pub name: Identifier,
+    /// The module in which this symbol is declared
+    pub module: ModuleId,
+    pub kind: SymbolKind,
+    /// The places where this symbol can be referred to by its name
+    pub scopes: Vec<SymbolScope>,
+    /// The doc comments of the declaration of this symbol, see [crate::ast::DocComments]
+    pub docs: Vec<String>,
+}
+
+/// The kind of item a [SymbolInfo] refers to, with its shape or signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolKind {
+    Constant {
+        ty: Type,
+    },
+    /// A trace column, or a group of trace columns if `size` is greater than 1
+    TraceColumns {
+        segment: TraceSegmentId,
+        segment_name: Identifier,
+        offset: usize,
+        size: usize,
+    },
+    PublicInput {
+        size: usize,
+    },
+    PeriodicColumn {
+        period: usize,
+    },
+    Bus {
+        bus_type: BusType,
+    },
+    Function {
+        params: Vec<(Identifier, Type)>,
+        result: Type,
+    },
+    Evaluator {
+        params: Vec<TraceSegment>,
+    },
+}
+impl SymbolKind {
+    /// Returns the name of this kind of symbol, as used in the JSON representation
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Constant { .. } => "constant",
+            Self::TraceColumns { .. } => "trace_columns",
+            Self::PublicInput { .. } => "public_input",
+            Self::PeriodicColumn { .. } => "periodic_column",
+            Self::Bus { .. } => "bus",
+            Self::Function { .. } => "function",
+            Self::Evaluator { .. } => "evaluator",
+        }
+    }
+}
+
+/// A place where a symbol is visible.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SymbolScope {
+    /// Anywhere in the given module, which is either the module declaring the symbol or a module
+    /// importing it
+    Module(ModuleId),
+    /// In the boundary and integrity constraints of the given root module
+    Constraints(ModuleId),
+    /// In the boundary constraints of the given root module
+    BoundaryConstraints(ModuleId),
+}
+
+/// Returns the [SymbolTable] of `program`, which was built during semantic analysis.
+///
+/// Unlike the items of the [Program] itself, the table contains the symbols which were eliminated
+/// as dead code.
+pub fn symbol_table(program: &Program) -> SymbolTable {
+    program.symbols.clone()
+}
+
+/// Parses the AirScript program at `path`, and returns its [SymbolTable].
+///
+/// If the program is invalid, the diagnostics are emitted as usual, and the symbols are collected
+/// on a best-effort basis from the declarations of the modules which could be parsed: the table
+/// is only unavailable if the root module itself cannot be parsed.
+pub fn symbol_table_from_file<P: AsRef<Path>>(
+    diagnostics: &DiagnosticsHandler,
+    codemap: Arc<CodeMap>,
+    path: P,
+) -> Result<SymbolTable, ParseError> {
+    let path = path.as_ref();
+    match crate::parse_file(diagnostics, codemap.clone(), path) {
+        Ok(program) => return Ok(symbol_table(&program)),
+        Err(ParseError::Failed) => {},
+        Err(err) => diagnostics.emit(err),
+    }
+
+    // Parse the modules again without semantic analysis, with diagnostics discarded as they have
+    // already been reported above
+    let silent = DiagnosticsHandler::new(
+        Default::default(),
+        codemap.clone(),
+        Arc::new(NullEmitter::default()),
+    );
+    let root = crate::parse_module_from_file(&silent, codemap.clone(), path)
+        .map_err(|_| ParseError::Failed)?;
+    let name = root.name;
+    let root_only =
+        SymbolTable::new(name, [&root], &root.trace_columns, root.public_inputs.values());
+    match Library::new(&silent, codemap, vec![root]) {
+        Ok(library) => {
+            let root = library.get(&name).unwrap();
+            Ok(SymbolTable::new(
+                name,
+                library.modules.values(),
+                &root.trace_columns,
+                root.public_inputs.values(),
+            ))
+        },
+        // The imports could not be resolved, so only the symbols of the root module are known
+        Err(_) => Ok(root_only),
+    }
+}
+
+impl SymbolTable {
+    /// Collects the symbols declared in `modules`, of which `root` is the root module.
+    ///
+    /// The trace columns and public inputs of the root module are given separately, as they are
+    /// moved out of the module when loading a [Program].
+    pub(crate) fn new<'a>(
+        root: ModuleId,
+        modules: impl IntoIterator<Item = &'a Module>,
+        trace_columns: &[TraceSegment],
+        public_inputs: impl IntoIterator<Item = &'a PublicInput>,
+    ) -> Self {
+        let public_inputs = public_inputs.into_iter().collect::<Vec<_>>();
+        let mut modules = modules.into_iter().collect::<Vec<_>>();
+        modules.sort_by_key(|module| (module.name != root, module.name));
+
+        // The modules importing each exported item, which it is visible in
+        let mut importers = BTreeMap::<(ModuleId, Identifier), BTreeSet<_>>::new();
+        for module in modules.iter() {
+            for import in module.imports.values() {
+                let Some(from) = modules.iter().find(|m| m.name == import.module()) else {
+                    continue;
+                };
+                for export in from.exports() {
+                    let imported = match import {
+                        Import::All { .. } => true,
+                        Import::Partial { items, .. } => items.contains(&export.name()),
+                    };
+                    if imported {
+                        importers
+                            .entry((from.name, export.name()))
+                            .or_default()
+                            .insert(SymbolScope::Module(module.name));
+                    }
+                }
+            }
+        }
+        let module_scopes = |module: ModuleId, name: Identifier| {
+            let mut scopes = vec![SymbolScope::Module(module)];
+            scopes.extend(importers.get(&(module, name)).into_iter().flatten().copied());
+            scopes
+        };
+
+        let mut symbols = vec![];
+        for module in modules {
+            // The doc comments of a symbol are those of the line on which its declaration starts
+            let mut add = |name, declaration: SourceSpan, kind, scopes| {
+                let docs = module.docs.get(declaration).to_vec();
+                symbols.push(SymbolInfo {
+                    name,
+                    module: module.name,
+                    kind,
+                    scopes,
+                    docs,
+                });
+            };
+            for constant in module.constants.values() {
+                let kind = SymbolKind::Constant { ty: constant.ty() };
+                add(constant.name, constant.span, kind, module_scopes(module.name, constant.name));
+            }
+            if module.name == root {
+                for segment in trace_columns {
+                    for binding in segment.bindings.iter() {
+                        let Some(name) = binding.name else {
+                            continue;
+                        };
+                        let kind = SymbolKind::TraceColumns {
+                            segment: segment.id,
+                            segment_name: segment.name,
+                            offset: binding.offset,
+                            size: binding.size,
+                        };
+                        add(name, name.span(), kind, vec![SymbolScope::Constraints(root)]);
+                    }
+                }
+                for input in public_inputs.iter() {
+                    let kind = SymbolKind::PublicInput { size: input.size() };
+                    let scopes = vec![SymbolScope::BoundaryConstraints(root)];
+                    add(input.name(), input.span(), kind, scopes);
+                }
+            }
+            for column in module.periodic_columns.values() {
+                let kind = SymbolKind::PeriodicColumn { period: column.period() };
+                add(column.name, column.span, kind, vec![SymbolScope::Module(module.name)]);
+            }
+            for bus in module.buses.values() {
+                let kind = SymbolKind::Bus { bus_type: bus.bus_type };
+                add(bus.name, bus.span, kind, vec![SymbolScope::Constraints(module.name)]);
+            }
+            for function in module.functions.values() {
+                let kind = SymbolKind::Function {
+                    params: function.params.clone(),
+                    result: function.return_type,
+                };
+                add(function.name, function.span, kind, vec![SymbolScope::Module(module.name)]);
+            }
+            for evaluator in module.evaluators.values() {
+                let kind = SymbolKind::Evaluator { params: evaluator.params.clone() };
+                let scopes = module_scopes(module.name, evaluator.name);
+                add(evaluator.name, evaluator.span, kind, scopes);
+            }
+        }
+
+        Self { root: Some(root), symbols }
+    }
+
+    /// Returns an iterator over the symbols with the given name, in any module.
+    pub fn get<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a SymbolInfo> + 'a {
+        self.symbols.iter().filter(move |symbol| symbol.name.as_str() == name)
+    }
+
+    /// Renders this table as JSON, with the spans resolved to file names, byte offsets and
+    /// 1-based line and column numbers using `codemap`.
+    pub fn to_json(&self, codemap: &CodeMap) -> String {
+        let mut json = String::from("{\"root\":");
+        match self.root {
+            Some(root) => json_string(&mut json, root.as_str()),
+            None => json.push_str("null"),
+        }
+        json.push_str(",\"symbols\":[");
+        for (i, symbol) in self.symbols.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            symbol.write_json(&mut json, codemap);
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+impl SymbolInfo {
+    fn write_json(&self, json: &mut String, codemap: &CodeMap) {
+        json.push_str("{\"name\":");
+        json_string(json, self.name.as_str());
+        json.push_str(",\"module\":");
+        json_string(json, self.module.as_str());
+        json.push_str(",\"kind\":");
+        json_string(json, self.kind.name());
+        match &self.kind {
+            SymbolKind::Constant { ty } => {
+                json.push_str(",\"shape\":");
+                json_shape(json, ty);
+            },
+            SymbolKind::TraceColumns { segment, segment_name, offset, size } => {
+                write!(json, ",\"segment\":{segment},\"segment_name\":").unwrap();
+                json_string(json, segment_name.as_str());
+                write!(json, ",\"offset\":{offset},\"size\":{size}").unwrap();
+            },
+            SymbolKind::PublicInput { size } => write!(json, ",\"size\":{size}").unwrap(),
+            SymbolKind::PeriodicColumn { period } => write!(json, ",\"period\":{period}").unwrap(),
+            SymbolKind::Bus { bus_type } => {
+                let bus_type = match bus_type {
+                    BusType::Multiset => "multiset",
+                    BusType::Logup => "logup",
+                };
+                write!(json, ",\"bus_type\":\"{bus_type}\"").unwrap();
+            },
+            SymbolKind::Function { params, result } => {
+                json.push_str(",\"params\":[");
+                for (i, (name, ty)) in params.iter().enumerate() {
+                    if i > 0 {
+                        json.push(',');
+                    }
+                    json.push_str("{\"name\":");
+                    json_string(json, name.as_str());
+                    json.push_str(",\"shape\":");
+                    json_shape(json, ty);
+                    json.push('}');
+                }
+                json.push_str("],\"result\":");
+                json_shape(json, result);
+            },
+            SymbolKind::Evaluator { params } => {
+                json.push_str(",\"params\":[");
+                for (i, segment) in params.iter().enumerate() {
+                    if i > 0 {
+                        json.push(',');
+                    }
+                    write!(json, "{{\"segment\":{},\"bindings\":[", segment.id).unwrap();
+                    for (j, binding) in segment.bindings.iter().enumerate() {
+                        if j > 0 {
+                            json.push(',');
+                        }
+                        json.push_str("{\"name\":");
+                        match binding.name {
+                            Some(name) => json_string(json, name.as_str()),
+                            None => json.push_str("null"),
+                        }
+                        write!(json, ",\"size\":{}}}", binding.size).unwrap();
+                    }
+                    json.push_str("]}");
+                }
+                json.push(']');
+            },
+        }
+        json.push_str(",\"span\":");
+        json_span(json, self.name.span(), codemap);
+        json.push_str(",\"scopes\":[");
+        for (i, scope) in self.scopes.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let (kind, module) = match scope {
+                SymbolScope::Module(module) => ("module", module),
+                SymbolScope::Constraints(module) => ("constraints", module),
+                SymbolScope::BoundaryConstraints(module) => ("boundary_constraints", module),
+            };
+            write!(json, "{{\"kind\":\"{kind}\",\"module\":").unwrap();
+            json_string(json, module.as_str());
+            json.push('}');
+        }
+        json.push(']');
+        if !self.docs.is_empty() {
+            json.push_str(",\"docs\":[");
+            for (i, doc) in self.docs.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                json_string(json, doc);
+            }
+            json.push(']');
+        }
+        json.push('}');
+    }
+}
+
+/// Writes the dimensions of `ty`, i.e. `[]` for a scalar
+fn json_shape(json: &mut String, ty: &Type) {
+    match ty {
+        Type::Felt => json.push_str("[]"),
+        Type::Vector(len) => write!(json, "[{len}]").unwrap(),
+        Type::Matrix(rows, cols) => write!(json, "[{rows},{cols}]").unwrap(),
+    }
+}
+
+/// Writes `span` resolved with `codemap`, or `null` if it cannot be resolved
+fn json_span(json: &mut String, span: SourceSpan, codemap: &CodeMap) {
+    let (Ok(file), Ok(location)) = (codemap.name(span.source_id()), codemap.location(&span)) else {
+        json.push_str("null");
+        return;
+    };
+    json.push_str("{\"file\":");
+    json_string(json, &file.to_string());
+    write!(
+        json,
+        ",\"start\":{},\"end\":{},\"line\":{},\"column\":{}}}",
+        span.start_index().to_usize(),
+        span.end_index().to_usize(),
+        location.line.to_usize() + 1,
+        location.column.to_usize() + 1,
+    )
+    .unwrap();
+}
+
+/// Writes `s` as a JSON string literal
+fn json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
@@ -38,6 +38,8 @@ pub enum Declaration {
     Buses(Span<Vec<Bus>>),
     /// A constant value declaration
     Constant(Constant),
+    /// A constant value computed at compile time, e.g. `const RCS = [rc(i) for i in 0..8];`
+    ComputedConstant(Box<ComputedConstant>),
     /// An evaluator function definition
     ///
     /// Evaluator functions can be defined in any module of the program
@@ -59,7 +61,17 @@ pub enum Declaration {
     ///
     /// There may only be one of these in the entire program, and it must
     /// appear in the root AirScript module, i.e. in a module declared with `def`
-    Trace(Span<Vec<TraceSegment>>),
+    ///
+    /// Columns declared with an initial value, e.g. `main: [clk = 0]`, come with the first-row
+    /// boundary constraints they imply, e.g. `enf clk.first = 0`, columns declared virtual,
+    /// e.g. `main: [@virtual(a * b) c]`, with their definition, and columns declared binary, e.g.
+    /// `main: [@binary s]`, with the integrity constraints they imply, e.g. `enf s * s = s`.
+    Trace(
+        #[span] Span<Vec<TraceSegment>>,
+        Vec<Statement>,
+        Vec<VirtualColumn>,
+        Vec<Statement>,
+    ),
     /// A `boundary_constraints` section declaration
     ///
     /// There may only be one of these in the entire program, and it must
@@ -79,13 +91,61 @@ pub struct Bus {
     pub span: SourceSpan,
     pub name: Identifier,
     pub bus_type: BusType,
+    /// The main trace columns into which the multiplicity of the bus is decomposed, if any, e.g.
+    /// `bits` in `logup q with bits`.
+    ///
+    /// Each column is constrained to be binary, and the effective multiplicity of the operation
+    /// of the bus declared with `with` is constrained to equal `sum(2^i * bits[i])`, so that it
+    /// lies in `[0, 2^n)` for `n` columns.
+    pub multiplicity_bits: Option<Identifier>,
+    /// Whether this bus is declared `extern`, e.g. `extern multiset p`, in which case it must be
+    /// defined by another program, with which this one is linked, see `Air::link`.
+    ///
+    /// Programs declaring a bus `extern` may insert into and remove from it, but may not constrain
+    /// its boundaries.
+    pub is_extern: bool,
 }
 impl Bus {
     /// Creates a new bus declaration
     pub const fn new(span: SourceSpan, name: Identifier, bus_type: BusType) -> Self {
-        Self { span, name, bus_type }
+        Self {
+            span,
+            name,
+            bus_type,
+            multiplicity_bits: None,
+            is_extern: false,
+        }
+    }
+
+    /// Marks this bus as declared `extern`, see [Bus::is_extern].
+    pub fn into_extern(mut self) -> Self {
+        self.is_extern = true;
+        self
+    }
+
+    /// Sets the columns into which the multiplicity of this bus is decomposed, see
+    /// [Bus::multiplicity_bits].
+    pub fn with_multiplicity_bits(mut self, bits: Identifier) -> Self {
+        self.multiplicity_bits = Some(bits);
+        self
     }
 }
+impl fmt::Display for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_extern {
+            f.write_str("extern ")?;
+        }
+        match self.bus_type {
+            BusType::Multiset => write!(f, "multiset {}", self.name)?,
+            BusType::Logup => write!(f, "logup {}", self.name)?,
+        }
+        if let Some(bits) = self.multiplicity_bits {
+            write!(f, " with {bits}")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Default, Copy, Hash, Debug, Clone, PartialEq, Eq)]
 pub enum BusType {
     /// A multiset bus
@@ -114,7 +174,9 @@ impl std::fmt::Display for BusOperator {
 impl Eq for Bus {}
 impl PartialEq for Bus {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name && self.bus_type == other.bus_type
+        self.name == other.name
+            && self.bus_type == other.bus_type
+            && self.multiplicity_bits == other.multiplicity_bits
     }
 }
 
@@ -148,6 +210,31 @@ impl PartialEq for Constant {
     }
 }
 
+/// Declaration of a constant whose value is computed at compile time, from a call to a pure
+/// function, e.g. `const R = rc(3);`, or from a comprehension, e.g. `const RCS = [rc(i) for i in
+/// 0..8];`.
+///
+/// Such constants are evaluated to a [Constant] when their module is loaded into a [Program].
+#[derive(Debug, Clone, Spanned)]
+pub struct ComputedConstant {
+    #[span]
+    pub span: SourceSpan,
+    pub name: Identifier,
+    pub initializer: Expr,
+}
+impl ComputedConstant {
+    /// Returns a new instance of a [ComputedConstant]
+    pub const fn new(span: SourceSpan, name: Identifier, initializer: Expr) -> Self {
+        Self { span, name, initializer }
+    }
+}
+impl Eq for ComputedConstant {}
+impl PartialEq for ComputedConstant {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.initializer == other.initializer
+    }
+}
+
 /// Value of a constant. Constants can be of 3 value types:
 ///
 /// * Scalar: 123
@@ -181,16 +268,20 @@ impl ConstantExpr {
 impl fmt::Display for ConstantExpr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Scalar(value) => write!(f, "{value}"),
+            Self::Scalar(value) => write!(f, "{}", DisplayFelt(*value)),
             Self::Vector(values) => {
-                write!(f, "{}", DisplayList(values.as_slice()))
+                write!(
+                    f,
+                    "{}",
+                    DisplayBracketed(DisplayCsv::new(values.iter().copied().map(DisplayFelt)))
+                )
             },
             Self::Matrix(values) => write!(
                 f,
                 "{}",
-                DisplayBracketed(DisplayCsv::new(
-                    values.iter().map(|vs| DisplayList(vs.as_slice()))
-                ))
+                DisplayBracketed(DisplayCsv::new(values.iter().map(|vs| {
+                    DisplayBracketed(DisplayCsv::new(vs.iter().copied().map(DisplayFelt)))
+                })))
             ),
         }
     }
@@ -277,6 +368,22 @@ impl PeriodicColumn {
         Self { span, name, values }
     }
 
+    /// Synthesizes the periodic column whose value at each row of a cycle of `period` rows is the
+    /// constant of the corresponding round, e.g. `round_const([c0, c1, c2, c3])`, which is zero
+    /// in the rows past the last constant.
+    ///
+    /// Panics if there are more constants than rows in the cycle.
+    pub fn from_round_constants(
+        span: SourceSpan,
+        name: Identifier,
+        mut constants: Vec<u64>,
+        period: usize,
+    ) -> Self {
+        assert!(constants.len() <= period, "round constants do not fit in the cycle");
+        constants.resize(period, 0);
+        Self::new(span, name, constants)
+    }
+
     pub fn period(&self) -> usize {
         self.values.len()
     }
@@ -288,12 +395,56 @@ impl PartialEq for PeriodicColumn {
     }
 }
 
+/// The parity of the rows selected by an `enf on_even` or `enf on_odd` block.
+///
+/// The constraints of such a block are gated by a periodic column of period 2, which is
+/// synthesized in the module declaring the block, and is only 1 on the rows of the given parity.
+/// Rows are selected soundly only if the trace length is even, which always holds for the traces
+/// supported by the backends, whose length is a power of two no smaller than the period.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+impl Parity {
+    /// Returns the name of the periodic column selecting the rows of this parity.
+    pub fn column_name(&self, span: SourceSpan) -> Identifier {
+        let name = match self {
+            Self::Even => "%even",
+            Self::Odd => "%odd",
+        };
+        Identifier::new(span, Symbol::intern(name))
+    }
+
+    /// Returns the parity selected by the periodic column named `name`, if it is one of the
+    /// columns synthesized for `enf on_even` and `enf on_odd` blocks.
+    pub fn from_column_name(name: &Identifier) -> Option<Self> {
+        match name.as_str() {
+            "%even" => Some(Self::Even),
+            "%odd" => Some(Self::Odd),
+            _ => None,
+        }
+    }
+
+    /// Returns the periodic column selecting the rows of this parity.
+    pub fn column(&self, span: SourceSpan) -> PeriodicColumn {
+        let values = match self {
+            Self::Even => vec![1, 0],
+            Self::Odd => vec![0, 1],
+        };
+        PeriodicColumn::new(span, self.column_name(span), values)
+    }
+}
+
 /// Declaration of a public input for an AirScript program.
 ///
 /// This declaration is only permitted in the root module.
 ///
 /// Public inputs are represented by a named identifier which is used to identify a fixed
-/// size array of length `size`.
+/// size array of length `size`, or a table of a variable number of rows with `size` columns.
+///
+/// A table may be annotated with `@commitment`, e.g. `@commitment inputs: [[4]]`, in which case
+/// its number of rows is part of the elements the verifier absorbs, see [PublicInput::is_commitment].
 #[derive(Debug, Clone, Spanned)]
 pub enum PublicInput {
     Vector {
@@ -307,6 +458,10 @@ pub enum PublicInput {
         span: SourceSpan,
         name: Identifier,
         size: usize,
+        /// The names of the columns, if declared as e.g. `[[clk, value]]`, otherwise empty.
+        columns: Vec<Identifier>,
+        /// Set by the `@commitment` annotation, see [PublicInput::is_commitment].
+        commitment: bool,
     },
 }
 impl PublicInput {
@@ -324,7 +479,27 @@ impl PublicInput {
             span,
             name,
             size: size.try_into().unwrap(),
+            columns: vec![],
+            commitment: false,
+        }
+    }
+    #[inline]
+    pub fn new_named_table(span: SourceSpan, name: Identifier, columns: Vec<Identifier>) -> Self {
+        Self::Table {
+            span,
+            name,
+            size: columns.len(),
+            columns,
+            commitment: false,
+        }
+    }
+    /// Sets whether this table has the `@commitment` annotation, which has no effect on a vector.
+    #[inline]
+    pub fn with_commitment(mut self, commitment: bool) -> Self {
+        if let Self::Table { commitment: annotated, .. } = &mut self {
+            *annotated = commitment;
         }
+        self
     }
     #[inline]
     pub fn name(&self) -> Identifier {
@@ -338,6 +513,24 @@ impl PublicInput {
             Self::Vector { size, .. } | Self::Table { size, .. } => *size,
         }
     }
+    /// Returns the names of the columns of a table, which are empty if they were not named.
+    #[inline]
+    pub fn columns(&self) -> &[Identifier] {
+        match self {
+            Self::Vector { .. } => &[],
+            Self::Table { columns, .. } => columns,
+        }
+    }
+    /// Returns true if this is a table annotated with `@commitment`.
+    ///
+    /// The rows of a table are only known at proving time, and the prover and the verifier hash
+    /// them to commit to the public inputs. The rows of such a table are prefixed with their
+    /// number when the public inputs are flattened into field elements, such that the number of
+    /// rows is committed to along with them, see the documentation of the backends for the layout.
+    #[inline]
+    pub fn is_commitment(&self) -> bool {
+        matches!(self, Self::Table { commitment: true, .. })
+    }
 }
 impl Eq for PublicInput {}
 impl PartialEq for PublicInput {
@@ -346,9 +539,22 @@ impl PartialEq for PublicInput {
             (Self::Vector { name: l, size: ls, .. }, Self::Vector { name: r, size: rs, .. }) => {
                 l == r && ls == rs
             },
-            (Self::Table { name: l, size: lc, .. }, Self::Table { name: r, size: rc, .. }) => {
-                l == r && lc == rc
-            },
+            (
+                Self::Table {
+                    name: l,
+                    size: lc,
+                    columns: lcols,
+                    commitment: lcommit,
+                    ..
+                },
+                Self::Table {
+                    name: r,
+                    size: rc,
+                    columns: rcols,
+                    commitment: rcommit,
+                    ..
+                },
+            ) => l == r && lc == rc && lcols == rcols && lcommit == rcommit,
             _ => false,
         }
     }
@@ -364,6 +570,9 @@ pub struct EvaluatorFunction {
     pub name: Identifier,
     pub params: Vec<TraceSegment>,
     pub body: Vec<Statement>,
+    /// Set by the `@inline(never)` hint, which asks the MIR inliner to share the parts of the
+    /// body that do not depend on the arguments between all calls, rather than duplicating them.
+    pub inline_never: bool,
 }
 impl EvaluatorFunction {
     /// Creates a new function.
@@ -373,13 +582,28 @@ impl EvaluatorFunction {
         params: Vec<TraceSegment>,
         body: Vec<Statement>,
     ) -> Self {
-        Self { span, name, params, body }
+        Self {
+            span,
+            name,
+            params,
+            body,
+            inline_never: false,
+        }
+    }
+
+    /// Sets whether this evaluator has the `@inline(never)` hint.
+    pub const fn with_inline_never(mut self, inline_never: bool) -> Self {
+        self.inline_never = inline_never;
+        self
     }
 }
 impl Eq for EvaluatorFunction {}
 impl PartialEq for EvaluatorFunction {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name && self.params == other.params && self.body == other.body
+        self.name == other.name
+            && self.params == other.params
+            && self.body == other.body
+            && self.inline_never == other.inline_never
     }
 }
 
@@ -1,6 +1,6 @@
 use std::{cell::Cell, fmt};
 
-use super::Statement;
+use super::{FIELD_MODULUS, Statement};
 
 /// Displays an item surrounded by brackets, e.g. `[foo]`
 pub struct DisplayBracketed<T>(pub T);
@@ -10,6 +10,24 @@ impl<T: fmt::Display> fmt::Display for DisplayBracketed<T> {
     }
 }
 
+/// Displays a field element, rendering values just below the field modulus as small negative
+/// integers, e.g. `-2` for `p - 2`
+pub struct DisplayFelt(pub u64);
+impl DisplayFelt {
+    /// Values whose additive inverse is smaller than this are displayed as negative integers
+    const NEGATIVE_THRESHOLD: u64 = 1 << 32;
+}
+impl fmt::Display for DisplayFelt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let neg = FIELD_MODULUS.wrapping_sub(self.0);
+        if self.0 < FIELD_MODULUS && neg < Self::NEGATIVE_THRESHOLD {
+            write!(f, "-{neg}")
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
 /// Displays a slice of items surrounded by brackets, e.g. `[foo, bar]`
 pub struct DisplayList<'a, T>(pub &'a [T]);
 impl<T: fmt::Display> fmt::Display for DisplayList<'_, T> {
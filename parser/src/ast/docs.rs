@@ -0,0 +1,84 @@
+use miden_diagnostics::{ByteIndex, CodeMap, SourceIndex, SourceSpan};
+
+/// The doc comments of the items of a module, i.e. the lines starting with `##`, while lines
+/// starting with a single `#` are ordinary comments, and those starting with `###` are section
+/// headers.
+///
+/// A block of doc comments documents the next line of code, and every item starting on that line,
+/// e.g. a constant, a trace column binding, an evaluator, or an `enf` statement. The doc comments
+/// are kept in a side table keyed by the span of the documented line, rather than in the items
+/// themselves, so that they can be looked up by the span of any item, including the constraints
+/// lowered from a statement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocComments {
+    blocks: Vec<DocComment>,
+}
+
+/// A block of doc comments, and the line of code it documents
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DocComment {
+    /// The span of the documented line, without leading and trailing whitespace
+    span: SourceSpan,
+    /// The text of each doc comment, without the `##` prefix and the space following it
+    lines: Vec<String>,
+}
+
+impl DocComments {
+    /// Collects the doc comments of the items within `span` from its source file in `codemap`.
+    pub fn collect(codemap: &CodeMap, span: SourceSpan) -> Self {
+        let Ok(file) = codemap.get(span.source_id()) else {
+            return Self::default();
+        };
+        let within = span.start_index().to_usize()..span.end_index().to_usize();
+        let index = |offset: usize| SourceIndex::new(file.id(), ByteIndex(offset as u32));
+
+        let mut blocks = vec![];
+        let mut lines = vec![];
+        let mut offset = 0;
+        for line in file.source().split_inclusive('\n') {
+            let start = offset + (line.len() - line.trim_start().len());
+            offset += line.len();
+            let text = line.trim();
+            if let Some(doc) = text.strip_prefix("##").filter(|doc| !doc.starts_with('#')) {
+                lines.push(doc.strip_prefix(' ').unwrap_or(doc).to_string());
+                continue;
+            }
+            // Blank lines and ordinary comments do not detach the doc comments from the code
+            if text.is_empty() || text.starts_with('#') || lines.is_empty() {
+                continue;
+            }
+            let lines = core::mem::take(&mut lines);
+            if within.contains(&start) {
+                let span = SourceSpan::new(index(start), index(start + text.len()));
+                blocks.push(DocComment { span, lines });
+            }
+        }
+
+        Self { blocks }
+    }
+
+    /// Returns true if there are no doc comments
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Returns the doc comments of the item at `span`, i.e. those of the line on which it starts,
+    /// which is empty if the item is not documented.
+    pub fn get(&self, span: SourceSpan) -> &[String] {
+        let start = span.start_index();
+        self.blocks
+            .iter()
+            .find(|block| {
+                block.span.source_id() == span.source_id()
+                    && block.span.start_index() <= start
+                    && start < block.span.end_index()
+            })
+            .map(|block| block.lines.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Adds the doc comments of `other` to this table, e.g. to merge those of several modules.
+    pub fn extend(&mut self, other: &DocComments) {
+        self.blocks.extend(other.blocks.iter().cloned());
+    }
+}
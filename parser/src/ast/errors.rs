@@ -7,10 +7,16 @@ pub enum InvalidExprError {
     InvalidExponent(SourceSpan),
     #[error("expected exponent to be a constant")]
     NonConstantExponent(SourceSpan),
+    #[error("expected denominator to be a constant")]
+    NonConstantDenominator(SourceSpan),
+    #[error("division by zero")]
+    DivisionByZero(SourceSpan),
     #[error("expected constant range expression")]
     NonConstantRangeExpr(SourceSpan),
     #[error("accessing column boundaries is not allowed here")]
     BoundedSymbolAccess(SourceSpan),
+    #[error("comprehensions over the rows of a table are only allowed as bus boundaries")]
+    RowComprehension(SourceSpan),
     #[error("expected scalar expression")]
     InvalidScalarExpr(SourceSpan),
     #[error(
@@ -37,6 +43,13 @@ impl ToDiagnostic for InvalidExprError {
                     "Only constant powers are supported with the exponentiation operator currently"
                         .to_string(),
                 ]),
+            Self::NonConstantDenominator(span) => Diagnostic::error()
+                .with_message("invalid expression")
+                .with_labels(vec![Label::primary(span.source_id(), span).with_message(message)])
+                .with_notes(vec![
+                    "Only division by a constant is supported, as it is folded into a multiplication by the inverse of that constant"
+                        .to_string(),
+                ]),
             Self::NonConstantRangeExpr(span) => Diagnostic::error()
                 .with_message("invalid expression")
                 .with_labels(vec![Label::primary(span.source_id(), span).with_message(message)])
@@ -44,7 +57,9 @@ impl ToDiagnostic for InvalidExprError {
                     "Range expression must be a constant to do this operation".to_string(),
                 ]),
             Self::InvalidExponent(span)
+            | Self::DivisionByZero(span)
             | Self::BoundedSymbolAccess(span)
+            | Self::RowComprehension(span)
             | Self::InvalidScalarExpr(span)
             | Self::InvalidLetExpr(span)
             | Self::NotAnExpr(span) => Diagnostic::error()
@@ -20,7 +20,11 @@ use crate::symbols::Symbol;
 pub type Range = std::ops::Range<usize>;
 
 /// Represents any type of identifier in AirScript
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Spanned)]
+///
+/// Identifiers are ordered by name, regardless of their span, and of the order in which their
+/// symbols were interned. The maps keyed by identifiers, e.g. the buses or public inputs of a
+/// [Program], are thus iterated in the same order in every compilation session.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Spanned)]
 pub struct Identifier(pub Span<Symbol>);
 impl Identifier {
     pub fn new(span: SourceSpan, name: Symbol) -> Self {
@@ -64,6 +68,18 @@ impl PartialEq<&Identifier> for Identifier {
         self == *other
     }
 }
+impl PartialOrd for Identifier {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Identifier {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
 impl fmt::Debug for Identifier {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("Identifier").field(&format!("{}", &self.0.item)).finish()
@@ -160,7 +176,10 @@ impl QualifiedIdentifier {
         if self.module.name() == "$builtin" {
             match self.item {
                 NamespacedIdentifier::Function(id) => {
-                    matches!(id.name(), symbols::Sum | symbols::Prod)
+                    matches!(
+                        id.name(),
+                        symbols::Sum | symbols::Prod | symbols::InSet | symbols::Combine
+                    )
                 },
                 _ => false,
             }
@@ -458,6 +477,7 @@ impl TryFrom<ScalarExpr> for Expr {
             ScalarExpr::BusOperation(expr) => Ok(Self::BusOperation(expr)),
             ScalarExpr::Null(spanned) => Ok(Self::Null(spanned)),
             ScalarExpr::Unconstrained(spanned) => Ok(Self::Unconstrained(spanned)),
+            ScalarExpr::RowComprehension(_) => Err(InvalidExprError::RowComprehension(expr.span())),
         }
     }
 }
@@ -513,6 +533,10 @@ pub enum ScalarExpr {
     Null(Span<()>),
     /// An unconstrained bus
     Unconstrained(Span<()>),
+    /// The rows of a public input table
+    ///
+    /// NOTE: This is only a valid expression as the value of a bus boundary constraint
+    RowComprehension(RowComprehension),
 }
 impl ScalarExpr {
     /// Returns true if this is a constant value
@@ -551,6 +575,7 @@ impl ScalarExpr {
             Self::BusOperation(_) | ScalarExpr::Null(_) | ScalarExpr::Unconstrained(_) => {
                 Ok(Some(Type::Felt))
             },
+            Self::RowComprehension(_) => Ok(None),
         }
     }
 }
@@ -610,13 +635,14 @@ impl fmt::Debug for ScalarExpr {
             Self::BusOperation(expr) => f.debug_tuple("BusOp").field(expr).finish(),
             Self::Null(expr) => f.debug_tuple("Null").field(expr).finish(),
             Self::Unconstrained(expr) => f.debug_tuple("Unconstrained").field(expr).finish(),
+            Self::RowComprehension(expr) => f.debug_tuple("RowComprehension").field(expr).finish(),
         }
     }
 }
 impl fmt::Display for ScalarExpr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Const(value) => write!(f, "{value}"),
+            Self::Const(value) => write!(f, "{}", DisplayFelt(value.item)),
             Self::SymbolAccess(expr) => write!(f, "{expr}"),
             Self::BoundedSymbolAccess(expr) => write!(f, "{}.{}", &expr.column, &expr.boundary),
             Self::Binary(expr) => write!(f, "{expr}"),
@@ -632,6 +658,7 @@ impl fmt::Display for ScalarExpr {
             Self::BusOperation(expr) => write!(f, "{expr}"),
             Self::Null(_value) => write!(f, "null"),
             Self::Unconstrained(_value) => write!(f, "unconstrained"),
+            Self::RowComprehension(expr) => write!(f, "{expr}"),
         }
     }
 }
@@ -823,6 +850,11 @@ pub enum BinaryOp {
     Sub,
     /// Multiplication
     Mul,
+    /// Division
+    ///
+    /// NOTE: Only division by a constant is supported, which is folded into a multiplication by
+    /// the inverse of that constant during constant propagation
+    Div,
     /// Exponentiation
     Exp,
     /// Equality
@@ -836,6 +868,7 @@ impl fmt::Display for BinaryOp {
             Self::Add => f.write_str("+"),
             Self::Sub => f.write_str("-"),
             Self::Mul => f.write_str("*"),
+            Self::Div => f.write_str("/"),
             Self::Exp => f.write_str("^"),
             Self::Eq => f.write_str("="),
         }
@@ -872,6 +905,12 @@ pub enum AccessType {
     Index(usize),
     /// Access binds the value at a specific row and column of a matrix value
     Matrix(usize, usize),
+    /// Access binds the value at indices of an aggregate value, at least one of which is a
+    /// binding of an enclosing list comprehension, e.g. `m[i][0] for i in 0..2`
+    ///
+    /// Semantic analysis rewrites these into accesses with constant indices, one per iteration of
+    /// the comprehension, so they never reach later passes.
+    Bound(Vec<AccessIndex>),
 }
 impl fmt::Display for AccessType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -882,6 +921,27 @@ impl fmt::Display for AccessType {
             },
             Self::Index(idx) => write!(f, "reference to element at index {idx}"),
             Self::Matrix(row, col) => write!(f, "reference to value in matrix at [{row}][{col}]"),
+            Self::Bound(indices) => {
+                f.write_str("reference to value at ")?;
+                indices.iter().try_for_each(|index| write!(f, "[{index}]"))
+            },
+        }
+    }
+}
+
+/// An index of an [AccessType::Bound] access
+#[derive(Hash, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AccessIndex {
+    /// A constant index
+    Const(usize),
+    /// An index bound by an enclosing list comprehension
+    Binding(Identifier),
+}
+impl fmt::Display for AccessIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Const(index) => write!(f, "{index}"),
+            Self::Binding(binding) => write!(f, "{binding}"),
         }
     }
 }
@@ -965,6 +1025,7 @@ impl SymbolAccess {
                 AccessType::Default => Ok(self.clone()),
                 _ => Err(InvalidAccessError::IndexIntoScalar),
             },
+            AccessType::Bound(_) => unreachable!(),
         }
     }
 
@@ -1021,6 +1082,7 @@ impl SymbolAccess {
                     ..self.clone()
                 }),
             },
+            AccessType::Bound(_) => unreachable!(),
         }
     }
 
@@ -1089,6 +1151,7 @@ impl SymbolAccess {
                     ..self.clone()
                 }),
             },
+            AccessType::Bound(_) => unreachable!(),
         }
     }
 
@@ -1117,6 +1180,7 @@ impl SymbolAccess {
             },
             AccessType::Slice(_) => Err(InvalidAccessError::SliceOfMatrix),
             AccessType::Matrix(..) => Err(InvalidAccessError::IndexIntoScalar),
+            AccessType::Bound(_) => unreachable!(),
         }
     }
 }
@@ -1147,6 +1211,9 @@ impl fmt::Display for SymbolAccess {
             AccessType::Index(idx) => write!(f, "[{idx}]")?,
             AccessType::Slice(range) => write!(f, "[{}..{}]", range.start, range.end)?,
             AccessType::Matrix(row, col) => write!(f, "[{row}][{col}]")?,
+            AccessType::Bound(indices) => {
+                indices.iter().try_for_each(|index| write!(f, "[{index}]"))?
+            },
         }
         // TODO: When we change the syntax to support arbitrary offsets, we'll need to update this
         for _ in 0..self.offset {
@@ -1216,6 +1283,17 @@ pub struct ListComprehension {
     /// When the comprehension is used as a constraint, this field is only valid for
     /// use in integrity constraints.
     pub selector: Option<ScalarExpr>,
+    /// An optional multiplicity, which is only valid for bus constraints, e.g. `q.insert(a) with
+    /// m`. It can only be combined with a selector on LogUp buses, e.g. `q.insert(a) when s with
+    /// m`.
+    ///
+    /// The effective multiplicity of such a bus operation is `selector * multiplicity`.
+    pub multiplicity: Option<Box<ScalarExpr>>,
+    /// An optional periodic column masking the rows on which this constraint is enforced, e.g.
+    /// `enf a' = a unless k`, which is only enforced on the rows where `k` is zero.
+    ///
+    /// This is folded into the selector as `(1 - k)` during semantic analysis.
+    pub mask: Option<Box<SymbolAccess>>,
     /// The type of the result of this list comprehension, e.g. `vector[5]`
     ///
     /// This is set during semantic analysis
@@ -1237,9 +1315,23 @@ impl ListComprehension {
             iterables,
             body: Box::new(body),
             selector,
+            multiplicity: None,
+            mask: None,
             ty: None,
         }
     }
+
+    /// Sets the multiplicity of this comprehension, see [ListComprehension::multiplicity].
+    pub fn with_multiplicity(mut self, multiplicity: ScalarExpr) -> Self {
+        self.multiplicity = Some(Box::new(multiplicity));
+        self
+    }
+
+    /// Sets the mask of this comprehension, see [ListComprehension::mask].
+    pub fn with_mask(mut self, mask: SymbolAccess) -> Self {
+        self.mask = Some(Box::new(mask));
+        self
+    }
 }
 impl Eq for ListComprehension {}
 impl PartialEq for ListComprehension {
@@ -1248,6 +1340,8 @@ impl PartialEq for ListComprehension {
             && self.iterables == other.iterables
             && self.body == other.body
             && self.selector == other.selector
+            && self.multiplicity == other.multiplicity
+            && self.mask == other.mask
     }
 }
 impl fmt::Debug for ListComprehension {
@@ -1257,6 +1351,8 @@ impl fmt::Debug for ListComprehension {
             .field("iterables", &self.iterables)
             .field("body", self.body.as_ref())
             .field("selector", &self.selector)
+            .field("multiplicity", &self.multiplicity)
+            .field("mask", &self.mask)
             .field("ty", &self.ty)
             .finish()
     }
@@ -1276,9 +1372,68 @@ impl fmt::Display for ListComprehension {
         }
 
         if let Some(selector) = self.selector.as_ref() {
-            write!(f, " when {selector}")
+            write!(f, " when {selector}")?;
+        }
+        if let Some(multiplicity) = self.multiplicity.as_ref() {
+            write!(f, " with {multiplicity}")?;
+        }
+        if let Some(mask) = self.mask.as_ref() {
+            write!(f, " unless {mask}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A comprehension over the rows of a public input table, e.g.
+/// `[(clk, value) for (clk, value) in table]` or `[(row[0], row[1]) for row in table]`.
+///
+/// NOTE: This is only valid as the value of a bus boundary constraint, which is bound to entire rows
+/// of the table. It is resolved to an access of the table during semantic analysis.
+#[derive(Clone, Spanned)]
+pub struct RowComprehension {
+    #[span]
+    pub span: SourceSpan,
+    /// The expressions forming each row
+    pub row: Span<Vec<ScalarExpr>>,
+    /// Either a single name bound to each row, or one name bound to each column of the row
+    pub bindings: Span<Vec<Identifier>>,
+    /// The public input table whose rows are iterated over
+    pub table: Identifier,
+}
+impl RowComprehension {
+    pub fn new(
+        span: SourceSpan,
+        row: Span<Vec<ScalarExpr>>,
+        bindings: Span<Vec<Identifier>>,
+        table: Identifier,
+    ) -> Self {
+        Self { span, row, bindings, table }
+    }
+}
+impl Eq for RowComprehension {}
+impl PartialEq for RowComprehension {
+    fn eq(&self, other: &Self) -> bool {
+        self.row.item == other.row.item
+            && self.bindings.item == other.bindings.item
+            && self.table == other.table
+    }
+}
+impl fmt::Debug for RowComprehension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RowComprehension")
+            .field("row", &self.row.item)
+            .field("bindings", &self.bindings.item)
+            .field("table", &self.table)
+            .finish()
+    }
+}
+impl fmt::Display for RowComprehension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let row = DisplayTuple(self.row.as_slice());
+        if self.bindings.len() == 1 {
+            write!(f, "[{row} for {} in {}]", &self.bindings[0], &self.table)
         } else {
-            Ok(())
+            write!(f, "[{row} for {} in {}]", DisplayTuple(self.bindings.as_slice()), &self.table)
         }
     }
 }
@@ -1365,6 +1520,8 @@ impl Call {
         match callee.name() {
             symbols::Sum => Self::sum(span, args),
             symbols::Prod => Self::prod(span, args),
+            symbols::InSet => Self::in_set(span, args),
+            symbols::Combine => Self::combine(span, args),
             _ => Self {
                 span,
                 callee: ResolvableIdentifier::Unresolved(NamespacedIdentifier::Function(callee)),
@@ -1392,6 +1549,21 @@ impl Call {
         Self::new_builtin(span, "prod", args, Type::Felt)
     }
 
+    /// Constructs a function call for the `in_set` builtin, whose value is the product of the
+    /// differences between a value and each element of a constant set, i.e. zero if and only if
+    /// the value is in the set
+    #[inline]
+    pub fn in_set(span: SourceSpan, args: Vec<Expr>) -> Self {
+        Self::new_builtin(span, "in_set", args, Type::Felt)
+    }
+
+    /// Constructs a function call for the `combine` reducer, which reduces a tuple to a single
+    /// element using a vector of coefficients
+    #[inline]
+    pub fn combine(span: SourceSpan, args: Vec<Expr>) -> Self {
+        Self::new_builtin(span, "combine", args, Type::Felt)
+    }
+
     fn new_builtin(span: SourceSpan, name: &str, args: Vec<Expr>, ty: Type) -> Self {
         let builtin_module = Identifier::new(SourceSpan::UNKNOWN, Symbol::intern("$builtin"));
         let name = Identifier::new(span, Symbol::intern(name));
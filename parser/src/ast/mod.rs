@@ -1,5 +1,6 @@
 mod declarations;
 mod display;
+mod docs;
 mod errors;
 mod expression;
 mod module;
@@ -21,10 +22,11 @@ use miden_diagnostics::{
 
 pub(crate) use self::display::*;
 pub use self::{
-    declarations::*, errors::*, expression::*, module::*, statement::*, trace::*, types::*,
+    declarations::*, docs::*, errors::*, expression::*, module::*, statement::*, trace::*, types::*,
 };
 use crate::{
     Symbol,
+    analysis::SymbolTable,
     parser::ParseError,
     sema::{self, SemanticAnalysisError},
 };
@@ -35,6 +37,7 @@ use crate::{
 /// All of the details described in the documentation for [Program] and [Library]
 /// apply to their respective variants here.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Source {
     /// The source code which was parsed produced a valid [Program],
     /// i.e. it contained a root module, and optionally, one or more
@@ -70,6 +73,8 @@ pub struct Program {
     pub functions: BTreeMap<QualifiedIdentifier, Function>,
     /// The set of used buses referenced in this program.
     pub buses: BTreeMap<QualifiedIdentifier, Bus>,
+    /// The span of the `buses` section of the root module, if any
+    pub buses_span: Option<SourceSpan>,
     /// The set of used periodic columns referenced in this program.
     pub periodic_columns: BTreeMap<QualifiedIdentifier, PeriodicColumn>,
     /// The set of public inputs defined in the root module
@@ -79,6 +84,8 @@ pub struct Program {
     pub public_inputs: BTreeMap<Identifier, PublicInput>,
     /// The set of trace columns of the main trace defined in the root module
     pub trace_columns: Vec<TraceSegment>,
+    /// The columns of the main trace declared virtual in the root module, in declaration order
+    pub virtual_columns: Vec<VirtualColumn>,
     /// The boundary_constraints block defined in the root module
     ///
     /// It is guaranteed that this is non-empty
@@ -87,6 +94,10 @@ pub struct Program {
     ///
     /// It is guaranteed that this is non-empty
     pub integrity_constraints: Vec<Statement>,
+    /// The symbols declared in the modules of this program, see [crate::analysis::symbol_table]
+    pub symbols: SymbolTable,
+    /// The doc comments of the modules of this program, see [DocComments]
+    pub docs: DocComments,
 }
 impl Program {
     /// Creates a new, empty [Program].
@@ -110,11 +121,15 @@ impl Program {
             evaluators: Default::default(),
             functions: Default::default(),
             buses: Default::default(),
+            buses_span: None,
             periodic_columns: Default::default(),
             public_inputs: Default::default(),
             trace_columns: vec![],
+            virtual_columns: vec![],
             boundary_constraints: vec![],
             integrity_constraints: vec![],
+            symbols: Default::default(),
+            docs: Default::default(),
         }
     }
 
@@ -188,6 +203,18 @@ impl Program {
             // during semantic analysis
             let mut module = library.modules.remove(&module_name).unwrap();
 
+            // Evaluate the constants computed at compile time, which semantic analysis expects
+            // to be literals like the others
+            if !module.computed_constants.is_empty() {
+                let evaluation =
+                    crate::transforms::ConstantEvaluation::new(diagnostics, &library, &module);
+                let constants = evaluation.run()?;
+                module.computed_constants.clear();
+                for constant in constants {
+                    module.constants.insert(constant.name, constant);
+                }
+            }
+
             // Resolve imports
             let resolver = sema::ImportResolver::new(diagnostics, &library);
             let imported = resolver.run(&mut module)?;
@@ -202,6 +229,16 @@ impl Program {
             library.modules.insert(module.name, module);
         }
 
+        for module in library.modules.values() {
+            program.docs.extend(&module.docs);
+        }
+        program.symbols = SymbolTable::new(
+            root,
+            library.modules.values(),
+            &program.trace_columns,
+            program.public_inputs.values(),
+        );
+
         // Now that we have a dependency graph for each function/constraint in the root module,
         // we traverse the graph top-down from the root node, to each of it's dependencies,
         // adding them to the program struct as we go. The root node represents items referenced
@@ -217,6 +254,7 @@ impl Program {
         let mut root_nodes = VecDeque::from(vec![root_node]);
         {
             let root_module = library.get(&root).unwrap();
+            program.virtual_columns = root_module.virtual_columns.clone();
             // Make sure we move the boundary_constraints into the program
             if let Some(bc) = root_module.boundary_constraints.as_ref() {
                 program.boundary_constraints = bc.to_vec();
@@ -231,6 +269,7 @@ impl Program {
                     (QualifiedIdentifier::new(root, NamespacedIdentifier::Binding(*k)), v.clone())
                 }));
             }
+            program.buses_span = root_module.buses_span;
             for evaluator in root_module.evaluators.values() {
                 root_nodes.push_back(QualifiedIdentifier::new(
                     root,
@@ -297,6 +336,7 @@ impl PartialEq for Program {
             && self.periodic_columns == other.periodic_columns
             && self.public_inputs == other.public_inputs
             && self.trace_columns == other.trace_columns
+            && self.virtual_columns == other.virtual_columns
             && self.boundary_constraints == other.boundary_constraints
             && self.integrity_constraints == other.integrity_constraints
     }
@@ -319,6 +359,15 @@ impl fmt::Display for Program {
         f.write_str("}}")?;
         f.write_str("\n")?;
 
+        if !self.buses.is_empty() {
+            writeln!(f, "buses {{")?;
+            for bus in self.buses.values() {
+                writeln!(f, "    {bus},")?;
+            }
+            f.write_str("}}")?;
+            f.write_str("\n")?;
+        }
+
         if !self.periodic_columns.is_empty() {
             writeln!(f, "periodic_columns {{")?;
             for (qid, column) in self.periodic_columns.iter() {
@@ -358,6 +407,9 @@ impl fmt::Display for Program {
         f.write_str("\n")?;
 
         for (qid, evaluator) in self.evaluators.iter() {
+            if evaluator.inline_never {
+                f.write_str("@inline(never)\n")?;
+            }
             f.write_str("ev ")?;
             if qid.module == self.name {
                 writeln!(f, "{}{}", &qid.item, DisplayTuple(evaluator.params.as_slice()))?;
@@ -1,8 +1,15 @@
-use std::collections::{BTreeMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashSet},
+    ops::ControlFlow,
+};
 
 use miden_diagnostics::{DiagnosticsHandler, Severity, SourceSpan, Span, Spanned};
 
-use crate::{ast::*, sema::SemanticAnalysisError};
+use crate::{
+    ast::{visit::VisitMut, *},
+    sema::SemanticAnalysisError,
+    symbols::{self, Symbol},
+};
 
 /// This is a type alias used to clarify that an identifier refers to a module
 pub type ModuleId = Identifier;
@@ -52,14 +59,22 @@ pub struct Module {
     pub ty: ModuleType,
     pub imports: BTreeMap<ModuleId, Import>,
     pub constants: BTreeMap<Identifier, Constant>,
+    /// The constants computed at compile time, which are moved to `constants` once evaluated,
+    /// see [ComputedConstant]
+    pub computed_constants: BTreeMap<Identifier, ComputedConstant>,
     pub evaluators: BTreeMap<Identifier, EvaluatorFunction>,
     pub functions: BTreeMap<Identifier, Function>,
     pub periodic_columns: BTreeMap<Identifier, PeriodicColumn>,
     pub public_inputs: BTreeMap<Identifier, PublicInput>,
     pub trace_columns: Vec<TraceSegment>,
+    pub virtual_columns: Vec<VirtualColumn>,
     pub buses: BTreeMap<Identifier, Bus>,
+    /// The span of the `buses` section, if any
+    pub buses_span: Option<SourceSpan>,
     pub boundary_constraints: Option<Span<Vec<Statement>>>,
     pub integrity_constraints: Option<Span<Vec<Statement>>>,
+    /// The doc comments of the items of this module
+    pub docs: DocComments,
 }
 impl Module {
     /// Constructs an empty module of the specified type, with the given span and name.
@@ -78,17 +93,27 @@ impl Module {
             ty,
             imports: Default::default(),
             constants: Default::default(),
+            computed_constants: Default::default(),
             evaluators: Default::default(),
             functions: Default::default(),
             buses: Default::default(),
+            buses_span: None,
             periodic_columns: Default::default(),
             public_inputs: Default::default(),
             trace_columns: vec![],
+            virtual_columns: vec![],
             boundary_constraints: None,
             integrity_constraints: None,
+            docs: Default::default(),
         }
     }
 
+    /// Sets the doc comments of the items of this module
+    pub fn with_docs(mut self, docs: DocComments) -> Self {
+        self.docs = docs;
+        self
+    }
+
     /// Constructs a module of the specified type, with the given span and name, using the
     /// provided declarations.
     ///
@@ -110,6 +135,13 @@ impl Module {
         // including explicitly imported names. Wildcard imports will be
         // checked in later analysis.
         let mut names = HashSet::<NamespacedIdentifier>::default();
+        // Keep track of the sections declared in this module, as they may appear in any order,
+        // but each of them may only be declared once.
+        let mut sections = BTreeMap::<&'static str, SourceSpan>::default();
+        // The boundary constraints implied by the initial values of trace columns
+        let mut initial_values = Vec::new();
+        // The integrity constraints implied by the columns declared binary
+        let mut binary_constraints = Vec::new();
 
         for declaration in declarations.drain(..) {
             match declaration {
@@ -119,6 +151,9 @@ impl Module {
                 Declaration::Constant(constant) => {
                     module.declare_constant(diagnostics, &mut names, constant)?;
                 },
+                Declaration::ComputedConstant(constant) => {
+                    module.declare_computed_constant(diagnostics, &mut names, *constant)?;
+                },
                 Declaration::EvaluatorFunction(evaluator) => {
                     module.declare_evaluator(diagnostics, &mut names, evaluator)?;
                 },
@@ -126,6 +161,13 @@ impl Module {
                     module.declare_function(diagnostics, &mut names, function)?;
                 },
                 Declaration::PeriodicColumns(mut columns) => {
+                    declare_section(
+                        diagnostics,
+                        &mut sections,
+                        "periodic_columns",
+                        columns.span(),
+                        columns.is_empty(),
+                    )?;
                     for column in columns.drain(..) {
                         module.declare_periodic_column(diagnostics, &mut names, column)?;
                     }
@@ -135,11 +177,30 @@ impl Module {
                         invalid_section_in_library(diagnostics, "public_inputs", span);
                         return Err(SemanticAnalysisError::RootSectionInLibrary(span));
                     }
+                    declare_section(
+                        diagnostics,
+                        &mut sections,
+                        "public_inputs",
+                        inputs.span(),
+                        inputs.is_empty(),
+                    )?;
                     for input in inputs.item.drain(..) {
                         module.declare_public_input(diagnostics, &mut names, input)?;
                     }
                 },
-                Declaration::Trace(segments) => {
+                Declaration::Trace(segments, mut initializers, mut virtual_columns, mut binary) => {
+                    initial_values.append(&mut initializers);
+                    binary_constraints.append(&mut binary);
+                    module.virtual_columns.append(&mut virtual_columns);
+                    if module.is_root() {
+                        declare_section(
+                            diagnostics,
+                            &mut sections,
+                            "trace_columns",
+                            segments.span(),
+                            false,
+                        )?;
+                    }
                     module.declare_trace_segments(diagnostics, &mut names, segments)?;
                 },
                 Declaration::BoundaryConstraints(statements) => {
@@ -149,6 +210,16 @@ impl Module {
                     module.declare_integrity_constraints(diagnostics, statements)?;
                 },
                 Declaration::Buses(mut buses) => {
+                    declare_section(
+                        diagnostics,
+                        &mut sections,
+                        "buses",
+                        buses.span(),
+                        buses.is_empty(),
+                    )?;
+                    if !buses.is_empty() {
+                        module.buses_span = Some(buses.span());
+                    }
                     for bus in buses.drain(..) {
                         module.declare_bus(diagnostics, &mut names, bus)?;
                     }
@@ -156,6 +227,8 @@ impl Module {
             }
         }
 
+        module.declare_parity_columns(diagnostics, &mut names)?;
+
         if module.is_root() {
             if module.trace_columns.is_empty() {
                 diagnostics.diagnostic(Severity::Error)
@@ -173,12 +246,74 @@ impl Module {
                 return Err(SemanticAnalysisError::Invalid);
             }
 
-            if module.boundary_constraints.is_none() || module.integrity_constraints.is_none() {
-                return Err(SemanticAnalysisError::MissingConstraints);
+            // Integrity constraints gated by `$first` or `$last` are boundary constraints in
+            // disguise, they are appended to the explicit boundary constraints
+            let mut selected = match module.integrity_constraints.as_mut() {
+                Some(statements) => {
+                    desugar_row_selectors(diagnostics, &module.trace_columns, statements)?
+                },
+                None => vec![],
+            };
+            // Likewise, running products imply a boundary constraint on the first row
+            if let Some(statements) = module.integrity_constraints.as_mut() {
+                selected.append(&mut desugar_running_products(
+                    diagnostics,
+                    &module.trace_columns,
+                    &module.buses,
+                    statements,
+                )?);
+            }
+            // And permutations are arguments over buses of their own, whose boundaries are empty
+            if let Some(statements) = module.integrity_constraints.as_mut() {
+                let (buses, mut boundaries) = desugar_permutations(diagnostics, statements)?;
+                for bus in buses {
+                    module.declare_bus(diagnostics, &mut names, bus)?;
+                }
+                selected.append(&mut boundaries);
+            }
+            if !selected.is_empty() {
+                match module.boundary_constraints.as_mut() {
+                    Some(statements) => statements.item.append(&mut selected),
+                    None => {
+                        let span = selected[0].span();
+                        module.boundary_constraints = Some(Span::new(span, selected));
+                    },
+                }
+            }
+
+            // Initial values are placed before the explicit boundary constraints, so that an
+            // explicit constraint on the first row of an initialized column is the one reported
+            // as overlapping
+            if !initial_values.is_empty() {
+                match module.boundary_constraints.as_mut() {
+                    Some(statements) => {
+                        initial_values.append(&mut statements.item);
+                        statements.item = initial_values;
+                    },
+                    None => {
+                        let span = initial_values[0].span();
+                        module.boundary_constraints = Some(Span::new(span, initial_values));
+                    },
+                }
             }
 
-            if module.public_inputs.is_empty() {
-                return Err(SemanticAnalysisError::MissingPublicInputs);
+            // Likewise, the booleanity of binary columns is enforced before the explicit integrity
+            // constraints
+            if !binary_constraints.is_empty() {
+                match module.integrity_constraints.as_mut() {
+                    Some(statements) => {
+                        binary_constraints.append(&mut statements.item);
+                        statements.item = binary_constraints;
+                    },
+                    None => {
+                        let span = binary_constraints[0].span();
+                        module.integrity_constraints = Some(Span::new(span, binary_constraints));
+                    },
+                }
+            }
+
+            if module.boundary_constraints.is_none() || module.integrity_constraints.is_none() {
+                return Err(SemanticAnalysisError::MissingConstraints);
             }
         }
 
@@ -238,6 +373,7 @@ impl Module {
                 if name == self.name {
                     return Err(SemanticAnalysisError::ImportSelf(name.span()));
                 }
+                let mut conflict = None;
                 match self.imports.entry(name) {
                     Entry::Occupied(mut entry) => match entry.get_mut() {
                         Import::All { module: prev } => {
@@ -265,50 +401,93 @@ impl Module {
                                         .emit();
                                     continue;
                                 }
-                                prev_items.insert(item);
-                                let name = if item.is_uppercase() {
+                                let namespaced_name = if item.is_uppercase() {
                                     NamespacedIdentifier::Binding(item)
                                 } else {
                                     NamespacedIdentifier::Function(item)
                                 };
-                                if let Some(prev) = names.replace(name) {
-                                    conflicting_declaration(
-                                        diagnostics,
-                                        "import",
-                                        prev.span(),
-                                        item.span(),
-                                    );
-                                    return Err(SemanticAnalysisError::NameConflict(item.span()));
+                                if let Some(prev) = names.replace(namespaced_name) {
+                                    conflict = Some((prev, item));
+                                    break;
                                 }
+                                prev_items.insert(item);
                             }
                         },
                     },
                     Entry::Vacant(entry) => {
                         for item in items.iter().copied() {
-                            let name = if item.is_uppercase() {
+                            let namespaced_name = if item.is_uppercase() {
                                 NamespacedIdentifier::Binding(item)
                             } else {
                                 NamespacedIdentifier::Function(item)
                             };
-                            if let Some(prev) = names.replace(name) {
-                                conflicting_declaration(
-                                    diagnostics,
-                                    "import",
-                                    prev.span(),
-                                    item.span(),
-                                );
-                                return Err(SemanticAnalysisError::NameConflict(item.span()));
+                            if let Some(prev) = names.replace(namespaced_name) {
+                                conflict = Some((prev, item));
+                                break;
                             }
                         }
-                        entry.insert(Import::Partial { module: name, items });
+                        if conflict.is_none() {
+                            entry.insert(Import::Partial { module: name, items });
+                        }
                     },
                 }
 
+                // Conflicts are reported once the import map is no longer borrowed, as we need it
+                // to determine where the conflicting item came from
+                if let Some((prev, item)) = conflict {
+                    self.conflicting_name(diagnostics, "import", prev, item, Some(name));
+                    return Err(SemanticAnalysisError::NameConflict(item.span()));
+                }
+
                 Ok(())
             },
         }
     }
 
+    /// Returns the module from which `item` was explicitly imported into this module, if any.
+    fn imported_from(&self, item: Identifier) -> Option<ModuleId> {
+        self.imports.iter().find_map(|(module, import)| match import {
+            Import::Partial { items, .. } if items.contains(&item) => Some(*module),
+            _ => None,
+        })
+    }
+
+    /// Reports a `ty` declaration named `current`, which conflicts with the previously declared
+    /// `prev`. `from` is the module `current` is imported from, if it is an import.
+    ///
+    /// When either of the conflicting items is imported, the diagnostic names its module.
+    fn conflicting_name(
+        &self,
+        diagnostics: &DiagnosticsHandler,
+        ty: &str,
+        prev: NamespacedIdentifier,
+        current: Identifier,
+        from: Option<ModuleId>,
+    ) {
+        let message = match (self.imported_from(prev.id()), from) {
+            (None, None) => {
+                return conflicting_declaration(diagnostics, ty, prev.span(), current.span());
+            },
+            (Some(prev_from), Some(from)) => {
+                format!("'{current}' is imported from both '{prev_from}' and '{from}'")
+            },
+            (Some(prev_from), None) => {
+                format!(
+                    "invalid {ty} declaration, '{current}' is already imported from '{prev_from}'"
+                )
+            },
+            (None, Some(from)) => {
+                format!("invalid import, '{current}' from '{from}' conflicts with a declaration")
+            },
+        };
+        diagnostics
+            .diagnostic(Severity::Error)
+            .with_message(message)
+            .with_primary_label(current.span(), "this conflicts with a previous declaration")
+            .with_secondary_label(prev.span(), "previously defined here")
+            .emit();
+    }
+
     fn declare_constant(
         &mut self,
         diagnostics: &DiagnosticsHandler,
@@ -325,7 +504,7 @@ impl Module {
         }
 
         if let Some(prev) = names.replace(NamespacedIdentifier::Binding(constant.name)) {
-            conflicting_declaration(diagnostics, "constant", prev.span(), constant.name.span());
+            self.conflicting_name(diagnostics, "constant", prev, constant.name, None);
             return Err(SemanticAnalysisError::NameConflict(constant.name.span()));
         }
 
@@ -355,6 +534,31 @@ impl Module {
         Ok(())
     }
 
+    fn declare_computed_constant(
+        &mut self,
+        diagnostics: &DiagnosticsHandler,
+        names: &mut HashSet<NamespacedIdentifier>,
+        constant: ComputedConstant,
+    ) -> Result<(), SemanticAnalysisError> {
+        if !constant.name.is_uppercase() {
+            diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("constant identifiers must be uppercase ASCII characters, e.g. FOO")
+                .with_primary_label(constant.name.span(), "this is an invalid constant identifier")
+                .emit();
+            return Err(SemanticAnalysisError::Invalid);
+        }
+
+        if let Some(prev) = names.replace(NamespacedIdentifier::Binding(constant.name)) {
+            self.conflicting_name(diagnostics, "constant", prev, constant.name, None);
+            return Err(SemanticAnalysisError::NameConflict(constant.name.span()));
+        }
+
+        assert_eq!(self.computed_constants.insert(constant.name, constant), None);
+
+        Ok(())
+    }
+
     fn declare_evaluator(
         &mut self,
         diagnostics: &DiagnosticsHandler,
@@ -362,7 +566,7 @@ impl Module {
         evaluator: EvaluatorFunction,
     ) -> Result<(), SemanticAnalysisError> {
         if let Some(prev) = names.replace(NamespacedIdentifier::Function(evaluator.name)) {
-            conflicting_declaration(diagnostics, "evaluator", prev.span(), evaluator.name.span());
+            self.conflicting_name(diagnostics, "evaluator", prev, evaluator.name, None);
             return Err(SemanticAnalysisError::NameConflict(evaluator.name.span()));
         }
 
@@ -378,7 +582,7 @@ impl Module {
         function: Function,
     ) -> Result<(), SemanticAnalysisError> {
         if let Some(prev) = names.replace(NamespacedIdentifier::Function(function.name)) {
-            conflicting_declaration(diagnostics, "function", prev.span(), function.name.span());
+            self.conflicting_name(diagnostics, "function", prev, function.name, None);
             return Err(SemanticAnalysisError::NameConflict(function.name.span()));
         }
 
@@ -394,7 +598,7 @@ impl Module {
         bus: Bus,
     ) -> Result<(), SemanticAnalysisError> {
         if let Some(prev) = names.replace(NamespacedIdentifier::Binding(bus.name)) {
-            conflicting_declaration(diagnostics, "bus", prev.span(), bus.name.span());
+            self.conflicting_name(diagnostics, "bus", prev, bus.name, None);
             return Err(SemanticAnalysisError::NameConflict(bus.name.span()));
         }
 
@@ -410,12 +614,7 @@ impl Module {
         column: PeriodicColumn,
     ) -> Result<(), SemanticAnalysisError> {
         if let Some(prev) = names.replace(NamespacedIdentifier::Binding(column.name)) {
-            conflicting_declaration(
-                diagnostics,
-                "periodic column",
-                prev.span(),
-                column.name.span(),
-            );
+            self.conflicting_name(diagnostics, "periodic column", prev, column.name, None);
             return Err(SemanticAnalysisError::NameConflict(column.name.span()));
         }
 
@@ -435,6 +634,37 @@ impl Module {
         }
     }
 
+    /// Declares the periodic columns selecting rows by parity, for each parity selected by an
+    /// `enf on_even` or `enf on_odd` block of this module.
+    ///
+    /// All blocks of the same parity share a single column.
+    fn declare_parity_columns(
+        &mut self,
+        diagnostics: &DiagnosticsHandler,
+        names: &mut HashSet<NamespacedIdentifier>,
+    ) -> Result<(), SemanticAnalysisError> {
+        struct ParitySelectors(BTreeMap<Parity, SourceSpan>);
+        impl VisitMut<()> for ParitySelectors {
+            fn visit_mut_symbol_access(&mut self, expr: &mut SymbolAccess) -> ControlFlow<()> {
+                if let ResolvableIdentifier::Unresolved(NamespacedIdentifier::Binding(name)) =
+                    expr.name
+                    && let Some(parity) = Parity::from_column_name(&name)
+                {
+                    self.0.entry(parity).or_insert(name.span());
+                }
+                ControlFlow::Continue(())
+            }
+        }
+
+        let mut selectors = ParitySelectors(BTreeMap::new());
+        let _ = selectors.visit_mut_module(self);
+        for (parity, span) in selectors.0 {
+            self.declare_periodic_column(diagnostics, names, parity.column(span))?;
+        }
+
+        Ok(())
+    }
+
     fn declare_public_input(
         &mut self,
         diagnostics: &DiagnosticsHandler,
@@ -445,8 +675,17 @@ impl Module {
             return Err(SemanticAnalysisError::RootSectionInLibrary(input.span()));
         }
 
+        // The names of the columns of a table are not bound, but must be unique within the table
+        let mut columns = HashSet::new();
+        for column in input.columns() {
+            if let Some(prev) = columns.replace(*column) {
+                conflicting_declaration(diagnostics, "table column", prev.span(), column.span());
+                return Err(SemanticAnalysisError::NameConflict(column.span()));
+            }
+        }
+
         if let Some(prev) = names.replace(NamespacedIdentifier::Binding(input.name())) {
-            conflicting_declaration(diagnostics, "public input", prev.span(), input.name().span());
+            self.conflicting_name(diagnostics, "public input", prev, input.name(), None);
             Err(SemanticAnalysisError::NameConflict(input.name().span()))
         } else {
             assert_eq!(self.public_inputs.insert(input.name(), input), None);
@@ -468,23 +707,13 @@ impl Module {
 
         for segment in segments.iter() {
             if let Some(prev) = names.replace(NamespacedIdentifier::Binding(segment.name)) {
-                conflicting_declaration(
-                    diagnostics,
-                    "trace segment",
-                    prev.span(),
-                    segment.name.span(),
-                );
+                self.conflicting_name(diagnostics, "trace segment", prev, segment.name, None);
                 return Err(SemanticAnalysisError::NameConflict(segment.name.span()));
             }
             for binding in segment.bindings.iter() {
                 let binding_name = binding.name.expect("expected binding name");
                 if let Some(prev) = names.replace(NamespacedIdentifier::Binding(binding_name)) {
-                    conflicting_declaration(
-                        diagnostics,
-                        "trace binding",
-                        prev.span(),
-                        binding_name.span(),
-                    );
+                    self.conflicting_name(diagnostics, "trace binding", prev, binding_name, None);
                     return Err(SemanticAnalysisError::NameConflict(binding_name.span()));
                 }
             }
@@ -589,16 +818,414 @@ impl PartialEq for Module {
             && self.ty == other.ty
             && self.imports == other.imports
             && self.constants == other.constants
+            && self.computed_constants == other.computed_constants
             && self.evaluators == other.evaluators
             && self.functions == other.functions
             && self.periodic_columns == other.periodic_columns
             && self.public_inputs == other.public_inputs
             && self.trace_columns == other.trace_columns
+            && self.virtual_columns == other.virtual_columns
             && self.boundary_constraints == other.boundary_constraints
             && self.integrity_constraints == other.integrity_constraints
     }
 }
 
+/// Rewrites the top-level integrity constraints gated by a builtin row selector, i.e. of the form
+/// `enf S * E = 0`, returning the boundary constraints they are equivalent to:
+///
+/// * `S = 1 - $last` exempts the last row, which is already the case of every integrity
+///   constraint, so the constraint is rewritten to `enf E = 0`
+/// * `S = $first` (resp. `$last`) only enforces `E` on the first (resp. last) row, which is
+///   expressible as a boundary constraint if `E` only constrains a column, see
+///   [split_boundary_column], so the constraint is removed and `enf col.first = value` (resp.
+///   `col.last`) is returned
+///
+/// Any other use of a row selector is left untouched, and rejected during semantic analysis.
+fn desugar_row_selectors(
+    diagnostics: &DiagnosticsHandler,
+    trace_columns: &[TraceSegment],
+    statements: &mut Span<Vec<Statement>>,
+) -> Result<Vec<Statement>, SemanticAnalysisError> {
+    let mut boundary_constraints = vec![];
+    let mut invalid = false;
+    for statement in core::mem::take(&mut statements.item) {
+        let Some((span, selector, expr)) = split_row_selector(&statement) else {
+            statements.item.push(statement);
+            continue;
+        };
+        let zero = ScalarExpr::Const(Span::new(span, 0));
+        match selector {
+            None => {
+                let constraint = BinaryExpr::new(span, BinaryOp::Eq, expr, zero);
+                statements.item.push(Statement::Enforce(ScalarExpr::Binary(constraint)));
+            },
+            Some(boundary) => {
+                let Some((column, rhs)) = split_boundary_column(trace_columns, &expr) else {
+                    diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid use of builtin row selector")
+                        .with_primary_label(
+                            expr.span(),
+                            "this expression cannot be enforced on a single row",
+                        )
+                        .with_note(
+                            "Constraints gated by `$first` or `$last` are boundary \
+                             constraints, and must be of the form `col - value`, \
+                             `value - col`, `col + value`, `value + col` or `col`",
+                        )
+                        .emit();
+                    invalid = true;
+                    continue;
+                };
+                let lhs = ScalarExpr::BoundedSymbolAccess(BoundedSymbolAccess::new(
+                    column.span(),
+                    column,
+                    boundary,
+                ));
+                let constraint = BinaryExpr::new(span, BinaryOp::Eq, lhs, rhs);
+                boundary_constraints.push(Statement::Enforce(ScalarExpr::Binary(constraint)));
+            },
+        }
+    }
+
+    if invalid {
+        return Err(SemanticAnalysisError::Invalid);
+    }
+
+    Ok(boundary_constraints)
+}
+
+/// Splits the expression `E` of a constraint gated by `$first` or `$last` into the column it
+/// constrains and the value the column must take on that row, i.e. `col - value` and
+/// `value - col` into `col` and `value`, `col + value` and `value + col` into `col` and
+/// `0 - value`, and `col` into `col` and `0`. Returns `None` if `E` has none of these forms.
+///
+/// The constrained column is the operand which is a single column of `trace_columns`, the left one
+/// if both are. If neither is, the left operand is assumed to be the column, such that semantic
+/// analysis reports how it is misused.
+fn split_boundary_column(
+    trace_columns: &[TraceSegment],
+    expr: &ScalarExpr,
+) -> Option<(SymbolAccess, ScalarExpr)> {
+    let span = expr.span();
+    match expr {
+        ScalarExpr::SymbolAccess(column) => {
+            Some((column.clone(), ScalarExpr::Const(Span::new(span, 0))))
+        },
+        ScalarExpr::Binary(BinaryExpr {
+            op: op @ (BinaryOp::Sub | BinaryOp::Add),
+            lhs,
+            rhs,
+            ..
+        }) => {
+            let (column, value) = match (lhs.as_ref(), rhs.as_ref()) {
+                (ScalarExpr::SymbolAccess(column), value)
+                    if is_single_column(trace_columns, column) =>
+                {
+                    (column, value)
+                },
+                (value, ScalarExpr::SymbolAccess(column))
+                    if is_single_column(trace_columns, column) =>
+                {
+                    (column, value)
+                },
+                (ScalarExpr::SymbolAccess(column), value) => (column, value),
+                _ => return None,
+            };
+            let value = match op {
+                BinaryOp::Sub => value.clone(),
+                _ => {
+                    let zero = ScalarExpr::Const(Span::new(span, 0));
+                    ScalarExpr::Binary(BinaryExpr::new(span, BinaryOp::Sub, zero, value.clone()))
+                },
+            };
+            Some((column.clone(), value))
+        },
+        _ => None,
+    }
+}
+
+/// If `statement` is of the form `enf S * E = 0` or `enf E * S = 0`, where `S` is a builtin row
+/// selector, returns the span of the constraint, the boundary selected by `S` (or `None` for
+/// `1 - $last`), and `E`.
+fn split_row_selector(statement: &Statement) -> Option<(SourceSpan, Option<Boundary>, ScalarExpr)> {
+    let Statement::Enforce(ScalarExpr::Binary(BinaryExpr { span, op: BinaryOp::Eq, lhs, rhs })) =
+        statement
+    else {
+        return None;
+    };
+    if !matches!(rhs.as_ref(), ScalarExpr::Const(value) if value.item == 0) {
+        return None;
+    }
+    let ScalarExpr::Binary(BinaryExpr { op: BinaryOp::Mul, lhs, rhs, .. }) = lhs.as_ref() else {
+        return None;
+    };
+
+    fn selector(expr: &ScalarExpr) -> Option<Option<Boundary>> {
+        match expr {
+            ScalarExpr::SymbolAccess(access) if is_row_selector(access, symbols::First) => {
+                Some(Some(Boundary::First))
+            },
+            ScalarExpr::SymbolAccess(access) if is_row_selector(access, symbols::Last) => {
+                Some(Some(Boundary::Last))
+            },
+            ScalarExpr::Binary(BinaryExpr { op: BinaryOp::Sub, lhs, rhs, .. }) => {
+                match (lhs.as_ref(), rhs.as_ref()) {
+                    (ScalarExpr::Const(one), ScalarExpr::SymbolAccess(access))
+                        if one.item == 1 && is_row_selector(access, symbols::Last) =>
+                    {
+                        Some(None)
+                    },
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
+
+    if let Some(boundary) = selector(lhs) {
+        Some((*span, boundary, rhs.as_ref().clone()))
+    } else {
+        selector(rhs).map(|boundary| (*span, boundary, lhs.as_ref().clone()))
+    }
+}
+
+/// Expands each top-level `enf running_product(p, e)` in `statements` to the integrity constraint
+/// `enf p' = p * e`, and returns the boundary constraints `enf p.first = 1` they imply.
+fn desugar_running_products(
+    diagnostics: &DiagnosticsHandler,
+    trace_columns: &[TraceSegment],
+    buses: &BTreeMap<Identifier, Bus>,
+    statements: &mut Span<Vec<Statement>>,
+) -> Result<Vec<Statement>, SemanticAnalysisError> {
+    let mut boundary_constraints = vec![];
+    let mut invalid = false;
+    for statement in core::mem::take(&mut statements.item) {
+        let call = match statement {
+            Statement::Enforce(ScalarExpr::Call(call))
+                if call.callee.as_ref().name() == symbols::RunningProduct =>
+            {
+                call
+            },
+            statement => {
+                statements.item.push(statement);
+                continue;
+            },
+        };
+        let span = call.span();
+        let Some((column, factor)) = running_product_args(diagnostics, trace_columns, buses, call)
+        else {
+            invalid = true;
+            continue;
+        };
+
+        let next = ScalarExpr::SymbolAccess(SymbolAccess { offset: 1, ..column.clone() });
+        let product =
+            BinaryExpr::new(span, BinaryOp::Mul, ScalarExpr::SymbolAccess(column.clone()), factor);
+        let constraint = BinaryExpr::new(span, BinaryOp::Eq, next, ScalarExpr::Binary(product));
+        statements.item.push(Statement::Enforce(ScalarExpr::Binary(constraint)));
+
+        let first = ScalarExpr::BoundedSymbolAccess(BoundedSymbolAccess::new(
+            span,
+            column,
+            Boundary::First,
+        ));
+        let one = ScalarExpr::Const(Span::new(span, 1));
+        let constraint = BinaryExpr::new(span, BinaryOp::Eq, first, one);
+        boundary_constraints.push(Statement::Enforce(ScalarExpr::Binary(constraint)));
+    }
+
+    if invalid {
+        return Err(SemanticAnalysisError::Invalid);
+    }
+
+    Ok(boundary_constraints)
+}
+
+/// Returns the column and the factor of a call to `running_product`, or `None` after raising an
+/// error if the column is not a single trace column, or the factor is not a scalar expression.
+fn running_product_args(
+    diagnostics: &DiagnosticsHandler,
+    trace_columns: &[TraceSegment],
+    buses: &BTreeMap<Identifier, Bus>,
+    call: Call,
+) -> Option<(SymbolAccess, ScalarExpr)> {
+    let span = call.span();
+    let Ok([column, factor]) = <[Expr; 2]>::try_from(call.args) else {
+        diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid call to builtin `running_product`")
+            .with_primary_label(span, "expected exactly two arguments")
+            .with_note(
+                "Running products are declared as `enf running_product(p, e)`, which enforces \
+                 `p' = p * e` and `p.first = 1`",
+            )
+            .emit();
+        return None;
+    };
+
+    let column = match column {
+        Expr::SymbolAccess(access) if is_single_column(trace_columns, &access) => access,
+        column => {
+            let note = match column {
+                Expr::SymbolAccess(ref access)
+                    if buses.contains_key::<Identifier>(access.name.as_ref()) =>
+                {
+                    "Buses maintain their running product themselves, use their `insert` and \
+                     `remove` operations instead"
+                },
+                _ => {
+                    "The column must be a single column of the trace, accessed in the current \
+                     row, e.g. `p` or `p[1]`"
+                },
+            };
+            diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("invalid call to builtin `running_product`")
+                .with_primary_label(column.span(), "expected a single trace column")
+                .with_note(note)
+                .emit();
+            return None;
+        },
+    };
+
+    match ScalarExpr::try_from(factor) {
+        Ok(factor) => Some((column, factor)),
+        Err(err) => {
+            diagnostics.emit(err);
+            None
+        },
+    }
+}
+
+/// Expands each top-level `enf permutation(a, b)` in `statements` to the operations
+/// `p.insert(a) when 1` and `p.remove(b) when 1` on a fresh multiset bus `p`, and returns these
+/// buses along with the boundary constraints `enf p.first = null` and `enf p.last = null` they
+/// imply.
+///
+/// The buses are named `%permutation0`, `%permutation1`, etc., which cannot conflict with a
+/// declared name.
+fn desugar_permutations(
+    diagnostics: &DiagnosticsHandler,
+    statements: &mut Span<Vec<Statement>>,
+) -> Result<(Vec<Bus>, Vec<Statement>), SemanticAnalysisError> {
+    let mut buses = vec![];
+    let mut boundary_constraints = vec![];
+    let mut invalid = false;
+    for statement in core::mem::take(&mut statements.item) {
+        let call = match statement {
+            Statement::Enforce(ScalarExpr::Call(call))
+                if call.callee.as_ref().name() == symbols::Permutation =>
+            {
+                call
+            },
+            statement => {
+                statements.item.push(statement);
+                continue;
+            },
+        };
+        let span = call.span();
+        let Some((inserted, removed)) = permutation_args(diagnostics, call) else {
+            invalid = true;
+            continue;
+        };
+
+        let name = format!("%permutation{}", buses.len());
+        let name = Identifier::new(span, Symbol::intern(name));
+        for (op, args) in [(BusOperator::Insert, inserted), (BusOperator::Remove, removed)] {
+            let op = ScalarExpr::BusOperation(BusOperation::new(span, name, op, args));
+            let binding = Identifier::new(SourceSpan::UNKNOWN, Symbol::intern("%row"));
+            let context = vec![(binding, Expr::Range(RangeExpr::from(0..1)))];
+            let selector = ScalarExpr::Const(Span::new(span, 1));
+            let op = ListComprehension::new(span, op, context, Some(selector));
+            statements.item.push(Statement::BusEnforce(op));
+        }
+
+        for boundary in [Boundary::First, Boundary::Last] {
+            let access = SymbolAccess::new(span, name, AccessType::Default, 0);
+            let bus =
+                ScalarExpr::BoundedSymbolAccess(BoundedSymbolAccess::new(span, access, boundary));
+            let null = ScalarExpr::Null(Span::new(span, ()));
+            let constraint = BinaryExpr::new(span, BinaryOp::Eq, bus, null);
+            boundary_constraints.push(Statement::Enforce(ScalarExpr::Binary(constraint)));
+        }
+        buses.push(Bus::new(span, name, BusType::Multiset));
+    }
+
+    if invalid {
+        return Err(SemanticAnalysisError::Invalid);
+    }
+
+    Ok((buses, boundary_constraints))
+}
+
+/// Returns the tuples inserted and removed by a call to `permutation`, or `None` after raising an
+/// error if it does not have two arguments of the same width.
+///
+/// Each argument is either a scalar expression, e.g. `a`, or a vector of them, e.g. `[a, b]`, for
+/// a permutation of rows of several columns.
+fn permutation_args(
+    diagnostics: &DiagnosticsHandler,
+    call: Call,
+) -> Option<(Vec<Expr>, Vec<Expr>)> {
+    let span = call.span();
+    let Ok([inserted, removed]) = <[Expr; 2]>::try_from(call.args) else {
+        diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid call to builtin `permutation`")
+            .with_primary_label(span, "expected exactly two arguments")
+            .with_note(
+                "Permutations are declared as `enf permutation(a, b)`, which enforces that the \
+                 values of `a` are a permutation of those of `b`",
+            )
+            .emit();
+        return None;
+    };
+
+    let tuple = |expr: Expr| match expr {
+        Expr::Vector(elements) => elements.item,
+        expr => vec![expr],
+    };
+    let (inserted_span, removed_span) = (inserted.span(), removed.span());
+    let (inserted, removed) = (tuple(inserted), tuple(removed));
+    if inserted.len() != removed.len() {
+        diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid call to builtin `permutation`")
+            .with_primary_label(removed_span, format!("expected {} values", inserted.len()))
+            .with_secondary_label(inserted_span, format!("{} values here", inserted.len()))
+            .with_note("Both arguments of `permutation` must have the same number of values")
+            .emit();
+        return None;
+    }
+
+    Some((inserted, removed))
+}
+
+/// Returns true if `access` refers to a single column of one of `trace_columns` in the current row
+fn is_single_column(trace_columns: &[TraceSegment], access: &SymbolAccess) -> bool {
+    let name: &Identifier = access.name.as_ref();
+    let Some(binding) = trace_columns
+        .iter()
+        .flat_map(|segment| segment.bindings.iter())
+        .find(|binding| binding.name.as_ref() == Some(name))
+    else {
+        return false;
+    };
+    access.offset == 0
+        && match access.access_type {
+            AccessType::Default => binding.is_scalar(),
+            AccessType::Index(index) => !binding.is_scalar() && index < binding.size,
+            _ => false,
+        }
+}
+
+fn is_row_selector(access: &SymbolAccess, selector: Symbol) -> bool {
+    access.name.as_ref().name() == selector
+        && access.offset == 0
+        && matches!(access.access_type, AccessType::Default)
+}
+
 fn invalid_section_in_library(diagnostics: &DiagnosticsHandler, ty: &str, span: SourceSpan) {
     diagnostics
         .diagnostic(Severity::Error)
@@ -607,6 +1234,35 @@ fn invalid_section_in_library(diagnostics: &DiagnosticsHandler, ty: &str, span:
         .emit();
 }
 
+/// Records the declaration of the section `ty` at `span`, raising an error if the section was
+/// already declared in this module, and a warning if the section is empty.
+fn declare_section(
+    diagnostics: &DiagnosticsHandler,
+    sections: &mut BTreeMap<&'static str, SourceSpan>,
+    ty: &'static str,
+    span: SourceSpan,
+    is_empty: bool,
+) -> Result<(), SemanticAnalysisError> {
+    if let Some(prev) = sections.insert(ty, span) {
+        conflicting_declaration(diagnostics, ty, prev, span);
+        return Err(SemanticAnalysisError::Invalid);
+    }
+
+    if is_empty {
+        diagnostics
+            .diagnostic(Severity::Warning)
+            .with_message(format!("empty {ty} section"))
+            .with_primary_label(
+                span,
+                "this section is empty, and will be treated as if it was omitted",
+            )
+            .with_note("Consider removing the section entirely")
+            .emit();
+    }
+
+    Ok(())
+}
+
 fn conflicting_declaration(
     diagnostics: &DiagnosticsHandler,
     ty: &str,
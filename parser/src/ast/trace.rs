@@ -266,6 +266,7 @@ impl TraceBinding {
                 Ok(Self { offset, size: 1, ty: Type::Felt, ..*self })
             },
             AccessType::Matrix(..) => Err(InvalidAccessError::IndexIntoScalar),
+            AccessType::Bound(_) => unreachable!(),
         }
     }
 }
@@ -299,3 +300,30 @@ impl fmt::Display for TraceBinding {
         }
     }
 }
+
+/// A column of the main trace declared with the `@virtual` attribute, e.g. `@virtual(a * b) c`,
+/// whose value in every row is a pure function of other columns.
+///
+/// The definition is not enforced, it is only an aid for degree analysis, which can substitute it
+/// for the column to report the degrees the constraints would have without the column.
+#[derive(Debug, Clone, Spanned)]
+pub struct VirtualColumn {
+    #[span]
+    pub span: SourceSpan,
+    /// The name of the column, which is declared as a single column of the main trace
+    pub name: Identifier,
+    /// The expression the value of the column is a function of
+    pub definition: ScalarExpr,
+}
+impl VirtualColumn {
+    /// Creates a new virtual column declaration
+    pub const fn new(span: SourceSpan, name: Identifier, definition: ScalarExpr) -> Self {
+        Self { span, name, definition }
+    }
+}
+impl Eq for VirtualColumn {}
+impl PartialEq for VirtualColumn {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.definition == other.definition
+    }
+}
@@ -1,5 +1,46 @@
 use super::*;
 
+/// The modulus of the field over which constraints are evaluated, i.e. `2^64 - 2^32 + 1`
+pub const FIELD_MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+/// Returns the additive inverse of `value` in the field, e.g. `p - 2` for `2`, and `0` for `0`
+pub fn field_neg(value: u64) -> u64 {
+    (FIELD_MODULUS - value % FIELD_MODULUS) % FIELD_MODULUS
+}
+
+/// Returns the sum of `a` and `b` in the field
+pub fn field_add(a: u64, b: u64) -> u64 {
+    ((a as u128 + b as u128) % FIELD_MODULUS as u128) as u64
+}
+
+/// Returns the product of `a` and `b` in the field
+pub fn field_mul(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % FIELD_MODULUS as u128) as u64
+}
+
+/// Returns the multiplicative inverse of `a` in the field, or `None` if `a` is zero in the field
+pub fn field_inv(a: u64) -> Option<u64> {
+    if a.is_multiple_of(FIELD_MODULUS) {
+        return None;
+    }
+    // By Fermat's little theorem, a^(p - 2) is the inverse of a
+    Some(field_pow(a, FIELD_MODULUS - 2))
+}
+
+/// Returns `base` raised to the power `exp` in the field
+pub fn field_pow(base: u64, mut exp: u64) -> u64 {
+    let mut result = 1;
+    let mut base = base % FIELD_MODULUS;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = field_mul(result, base);
+        }
+        base = field_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
 /// The types of values which can be represented in an AirScript program
 #[derive(Hash, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Type {
@@ -38,6 +79,17 @@ impl Type {
         matches!(self, Self::Vector(_))
     }
 
+    /// Returns the type of the elements of an empty vector, which cannot be obtained by accessing
+    /// its first element.
+    ///
+    /// Returns `None` if this type is not an empty vector.
+    pub fn empty_element(&self) -> Option<Self> {
+        match self {
+            Self::Vector(0) => Some(Self::Felt),
+            _ => None,
+        }
+    }
+
     /// Return a new [Type] representing the type of the value produced by the given [AccessType]
     pub fn access(&self, access_type: AccessType) -> Result<Self, InvalidAccessError> {
         match *self {
@@ -210,6 +210,9 @@ pub trait VisitMut<T> {
     fn visit_mut_bus_operation(&mut self, expr: &mut ast::BusOperation) -> ControlFlow<T> {
         visit_mut_bus_operation(self, expr)
     }
+    fn visit_mut_row_comprehension(&mut self, expr: &mut ast::RowComprehension) -> ControlFlow<T> {
+        visit_mut_row_comprehension(self, expr)
+    }
     fn visit_mut_range_bound(&mut self, expr: &mut ast::RangeBound) -> ControlFlow<T> {
         visit_mut_range_bound(self, expr)
     }
@@ -355,6 +358,9 @@ where
     fn visit_mut_bus_operation(&mut self, expr: &mut ast::BusOperation) -> ControlFlow<T> {
         (**self).visit_mut_bus_operation(expr)
     }
+    fn visit_mut_row_comprehension(&mut self, expr: &mut ast::RowComprehension) -> ControlFlow<T> {
+        (**self).visit_mut_row_comprehension(expr)
+    }
     fn visit_mut_range_bound(&mut self, expr: &mut ast::RangeBound) -> ControlFlow<T> {
         (**self).visit_mut_range_bound(expr)
     }
@@ -638,6 +644,7 @@ where
         ast::ScalarExpr::Call(expr) => visitor.visit_mut_call(expr),
         ast::ScalarExpr::Let(expr) => visitor.visit_mut_let(expr),
         ast::ScalarExpr::BusOperation(expr) => visitor.visit_mut_bus_operation(expr),
+        ast::ScalarExpr::RowComprehension(expr) => visitor.visit_mut_row_comprehension(expr),
     }
 }
 
@@ -665,6 +672,12 @@ where
     if let Some(selector) = expr.selector.as_mut() {
         visitor.visit_mut_scalar_expr(selector)?;
     }
+    if let Some(multiplicity) = expr.multiplicity.as_mut() {
+        visitor.visit_mut_scalar_expr(multiplicity)?;
+    }
+    if let Some(mask) = expr.mask.as_mut() {
+        visitor.visit_mut_symbol_access(mask.as_mut())?;
+    }
     visitor.visit_mut_scalar_expr(expr.body.as_mut())
 }
 
@@ -693,6 +706,23 @@ where
     ControlFlow::Continue(())
 }
 
+pub fn visit_mut_row_comprehension<V, T>(
+    visitor: &mut V,
+    expr: &mut ast::RowComprehension,
+) -> ControlFlow<T>
+where
+    V: ?Sized + VisitMut<T>,
+{
+    for binding in expr.bindings.iter_mut() {
+        visitor.visit_mut_identifier(binding)?;
+    }
+    visitor.visit_mut_identifier(&mut expr.table)?;
+    for expr in expr.row.iter_mut() {
+        visitor.visit_mut_scalar_expr(expr)?;
+    }
+    ControlFlow::Continue(())
+}
+
 pub fn visit_mut_range_bound<V, T>(visitor: &mut V, expr: &mut ast::RangeBound) -> ControlFlow<T>
 where
     V: ?Sized + VisitMut<T>,
@@ -708,9 +738,10 @@ where
     V: ?Sized + VisitMut<T>,
 {
     match expr {
-        ast::AccessType::Default | ast::AccessType::Index(_) | ast::AccessType::Matrix(..) => {
-            ControlFlow::Continue(())
-        },
+        ast::AccessType::Default
+        | ast::AccessType::Index(_)
+        | ast::AccessType::Matrix(..)
+        | ast::AccessType::Bound(_) => ControlFlow::Continue(()),
         ast::AccessType::Slice(range) => {
             visitor.visit_mut_range_bound(&mut range.start)?;
             visitor.visit_mut_range_bound(&mut range.end)
@@ -0,0 +1,100 @@
+//! Stable codes identifying common diagnostics, along with a longer explanation of each.
+//!
+//! A diagnostic emitted with an [ErrorCode] is rendered as e.g. `error[E0001]: ...`, and its
+//! explanation can be printed with `airc --explain E0001`.
+use miden_diagnostics::{Diagnostic, InFlightDiagnostic};
+
+/// A stable code identifying a class of diagnostics, and the explanation registered for it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// The code, e.g. `E0001`
+    pub code: &'static str,
+    /// A longer explanation of the diagnostic, with a minimal example and how to fix it
+    pub explanation: &'static str,
+}
+
+/// A column is constrained more than once at the same boundary.
+pub const OVERLAPPING_BOUNDARY_CONSTRAINTS: ErrorCode = ErrorCode {
+    code: "E0001",
+    explanation: "\
+A trace column was constrained more than once at the same boundary.
+
+Each column may be constrained at most once in the first row, and at most once in the last row.
+A second constraint against the same boundary is either redundant, or contradicts the first one.
+
+Erroneous code example:
+
+    boundary_constraints {
+        enf a.first = 0;
+        enf a.first = 1;
+    }
+
+Remove all but one of the constraints against the boundary, or constrain the other boundary if
+that was intended:
+
+    boundary_constraints {
+        enf a.first = 0;
+        enf a.last = 1;
+    }
+",
+};
+
+/// A boundary constraint reads a value outside of the row it applies to.
+pub const BOUNDARY_CONSTRAINT_DOMAIN_MISMATCH: ErrorCode = ErrorCode {
+    code: "E0002",
+    explanation: "\
+A boundary constraint reads a value outside of the row it applies to.
+
+A boundary constraint applies to the first or the last row of the trace, as selected by `.first` or
+`.last` on its left-hand side. Both of its sides are evaluated in that same row, so they may not
+read a value whose domain is another row, such as a column accessed in the next row.
+
+Erroneous code example:
+
+    boundary_constraints {
+        enf a.first = b';
+    }
+
+Constrain the boundary against a value available in that row, e.g. a public input or a constant,
+and relate the rows with an integrity constraint instead:
+
+    boundary_constraints {
+        enf a.first = stack_inputs[0];
+    }
+
+    integrity_constraints {
+        enf a' = b;
+    }
+",
+};
+
+/// All of the registered error codes, in increasing order.
+pub const ERROR_CODES: &[ErrorCode] =
+    &[OVERLAPPING_BOUNDARY_CONSTRAINTS, BOUNDARY_CONSTRAINT_DOMAIN_MISMATCH];
+
+/// Returns the explanation registered for `code`, which is matched case-insensitively.
+pub fn explain(code: &str) -> Option<&'static str> {
+    ERROR_CODES
+        .iter()
+        .find(|error| error.code.eq_ignore_ascii_case(code))
+        .map(|error| error.explanation)
+}
+
+/// Extends [InFlightDiagnostic] with the ability to attach an [ErrorCode].
+pub trait WithErrorCode {
+    /// Attaches `code` to this diagnostic, with a note pointing at its explanation, and returns the
+    /// resulting [Diagnostic], ready to be emitted.
+    fn with_code(self, code: ErrorCode) -> Diagnostic;
+}
+impl WithErrorCode for InFlightDiagnostic<'_> {
+    fn with_code(self, code: ErrorCode) -> Diagnostic {
+        let mut diagnostic = self
+            .with_note(format!(
+                "For more information about this error, try `airc --explain {}`.",
+                code.code
+            ))
+            .take();
+        diagnostic.code = Some(code.code.to_string());
+        diagnostic
+    }
+}
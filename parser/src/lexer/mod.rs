@@ -18,6 +18,8 @@ pub enum LexicalError {
     InvalidInt { span: SourceSpan, reason: IntErrorKind },
     #[error("encountered unexpected character '{found}'")]
     UnexpectedCharacter { start: SourceIndex, found: char },
+    #[error("unterminated string literal")]
+    UnterminatedString { span: SourceSpan },
 }
 impl PartialEq for LexicalError {
     fn eq(&self, other: &Self) -> bool {
@@ -29,6 +31,7 @@ impl PartialEq for LexicalError {
                 Self::UnexpectedCharacter { found: lhs, .. },
                 Self::UnexpectedCharacter { found: rhs, .. },
             ) => lhs == rhs,
+            (Self::UnterminatedString { .. }, Self::UnterminatedString { .. }) => true,
             _ => false,
         }
     }
@@ -49,6 +52,12 @@ impl ToDiagnostic for LexicalError {
                     Label::primary(start.source_id(), SourceSpan::new(start, start)),
                 ])
             },
+            Self::UnterminatedString { span } => Diagnostic::error()
+                .with_message("unterminated string literal")
+                .with_labels(vec![
+                    Label::primary(span.source_id(), span)
+                        .with_message("expected a closing '\"' on this line"),
+                ]),
         }
     }
 }
@@ -83,6 +92,9 @@ pub enum Token {
     FunctionIdent(Symbol),
     /// Integers should only contain numeric characters.
     Num(u64),
+    /// A string delimited by double quotes, which cannot span multiple lines, e.g. the path of a
+    /// table in `include_table("sbox.csv")`.
+    Str(Symbol),
 
     // DECLARATION KEYWORDS
     // --------------------------------------------------------------------------------------------
@@ -117,6 +129,8 @@ pub enum Token {
     Multiset,
     /// Used to represent a logup bus declaration.
     Logup,
+    /// Used to declare a bus which is defined by another program, see `Air::link`.
+    Extern,
     /// Used to represent an empty bus
     Null,
     /// Used to represent an unconstrained bus
@@ -153,6 +167,12 @@ pub enum Token {
     Match,
     Case,
     When,
+    /// Used to mask a constraint by a periodic column, e.g. `enf a' = a unless k`
+    Unless,
+    /// Used to name the selector of a bus operation, e.g. `p.insert(a) when s as g`
+    As,
+    OnEven,
+    OnOdd,
     Felt,
     With,
 
@@ -174,10 +194,12 @@ pub enum Token {
     Plus,
     Minus,
     Star,
+    Slash,
     Caret,
     Ampersand,
     Bar,
     Bang,
+    At,
     Arrow,
     SemiColon,
 }
@@ -199,6 +221,7 @@ impl Token {
             "buses" => Self::Buses,
             "multiset" => Self::Multiset,
             "logup" => Self::Logup,
+            "extern" => Self::Extern,
             "null" => Self::Null,
             "unconstrained" => Self::Unconstrained,
             "insert" => Self::Insert,
@@ -214,6 +237,10 @@ impl Token {
             "match" => Self::Match,
             "case" => Self::Case,
             "when" => Self::When,
+            "unless" => Self::Unless,
+            "as" => Self::As,
+            "on_even" => Self::OnEven,
+            "on_odd" => Self::OnOdd,
             "with" => Self::With,
             other => Self::Ident(Symbol::intern(other)),
         }
@@ -248,6 +275,11 @@ impl PartialEq for Token {
                     return i == i2;
                 }
             },
+            Self::Str(s) => {
+                if let Self::Str(s2) = other {
+                    return s == s2;
+                }
+            },
             _ => return mem::discriminant(self) == mem::discriminant(other),
         }
         false
@@ -263,6 +295,7 @@ impl fmt::Display for Token {
             Self::DeclIdentRef(id) => write!(f, "{id}"),
             Self::FunctionIdent(id) => write!(f, "{id}"),
             Self::Num(i) => write!(f, "{i}"),
+            Self::Str(s) => write!(f, "\"{s}\""),
             Self::Def => write!(f, "def"),
             Self::Mod => write!(f, "mod"),
             Self::Use => write!(f, "use"),
@@ -278,6 +311,7 @@ impl fmt::Display for Token {
             Self::Buses => write!(f, "buses"),
             Self::Multiset => write!(f, "multiset"),
             Self::Logup => write!(f, "logup"),
+            Self::Extern => write!(f, "extern"),
             Self::Null => write!(f, "null"),
             Self::Unconstrained => write!(f, "unconstrained"),
             Self::Insert => write!(f, "insert"),
@@ -293,6 +327,10 @@ impl fmt::Display for Token {
             Self::Match => write!(f, "match"),
             Self::Case => write!(f, "case"),
             Self::When => write!(f, "when"),
+            Self::Unless => write!(f, "unless"),
+            Self::As => write!(f, "as"),
+            Self::OnEven => write!(f, "on_even"),
+            Self::OnOdd => write!(f, "on_odd"),
             Self::With => write!(f, "with"),
             Self::Quote => write!(f, "'"),
             Self::Colon => write!(f, ":"),
@@ -310,10 +348,12 @@ impl fmt::Display for Token {
             Self::Plus => write!(f, "+"),
             Self::Minus => write!(f, "-"),
             Self::Star => write!(f, "*"),
+            Self::Slash => write!(f, "/"),
             Self::Caret => write!(f, "^"),
             Self::Ampersand => write!(f, "&"),
             Self::Bar => write!(f, "|"),
             Self::Bang => write!(f, "!"),
+            Self::At => write!(f, "@"),
             Self::Arrow => write!(f, "->"),
             Self::SemiColon => write!(f, ";"),
         }
@@ -535,11 +575,14 @@ where
                 _ => pop!(self, Token::Minus),
             },
             '*' => pop!(self, Token::Star),
+            '/' => pop!(self, Token::Slash),
             '^' => pop!(self, Token::Caret),
             '&' => pop!(self, Token::Ampersand),
             '|' => pop!(self, Token::Bar),
             '!' => pop!(self, Token::Bang),
+            '@' => pop!(self, Token::At),
             ';' => pop!(self, Token::SemiColon),
+            '"' => self.lex_string(),
             '$' => self.lex_special_identifier(),
             '0'..='9' => self.lex_number(),
             'a'..='z' => self.lex_keyword_or_ident(),
@@ -631,6 +674,26 @@ where
         }
     }
 
+    fn lex_string(&mut self) -> Token {
+        let c = self.pop();
+        debug_assert!(c == '"');
+
+        loop {
+            match self.read() {
+                '"' => break,
+                '\n' | '\0' => {
+                    return Token::Error(LexicalError::UnterminatedString { span: self.span() });
+                },
+                _ => self.skip(),
+            }
+        }
+        self.skip();
+
+        // Strip the quotes from the token
+        let slice = self.slice();
+        Token::Str(Symbol::intern(&slice[1..slice.len() - 1]))
+    }
+
     #[inline]
     fn lex_number(&mut self) -> Token {
         let mut num = String::new();
@@ -646,7 +709,7 @@ where
             Ok(i) => Token::Num(i),
             Err(err) => Token::Error(LexicalError::InvalidInt {
                 span: self.span(),
-                reason: err.kind().clone(),
+                reason: *err.kind(),
             }),
         }
     }
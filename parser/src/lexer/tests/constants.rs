@@ -1,4 +1,6 @@
-use super::{Symbol, Token, expect_valid_tokenization};
+use miden_diagnostics::SourceSpan;
+
+use super::{LexicalError, Symbol, Token, expect_error_at_location, expect_valid_tokenization};
 
 #[test]
 fn constants_scalar() {
@@ -238,3 +240,30 @@ fn constants_access_inside_integrity_expr_invalid() {
     ];
     expect_valid_tokenization(source, tokens);
 }
+
+#[test]
+fn constants_include_table() {
+    let source = r#"const SBOX = include_table("tables/sbox.csv");"#;
+    let tokens = vec![
+        Token::Const,
+        Token::Ident(Symbol::intern("SBOX")),
+        Token::Equal,
+        Token::FunctionIdent(Symbol::intern("include_table")),
+        Token::LParen,
+        Token::Str(Symbol::intern("tables/sbox.csv")),
+        Token::RParen,
+        Token::SemiColon,
+    ];
+    expect_valid_tokenization(source, tokens);
+}
+
+#[test]
+fn error_unterminated_string() {
+    let source = "const SBOX = include_table(\"sbox.csv);\nconst A = 1;";
+    expect_error_at_location(
+        source,
+        LexicalError::UnterminatedString { span: SourceSpan::UNKNOWN },
+        0,
+        27,
+    );
+}
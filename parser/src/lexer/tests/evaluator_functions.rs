@@ -65,6 +65,40 @@ fn ev_fn_with_main_cols() {
     expect_valid_tokenization(source, tokens.to_vec());
 }
 
+#[test]
+fn ev_fn_with_inline_never() {
+    let source = "
+    @inline(never)
+    ev ev_fn([clk]) {
+        enf clk' = clk + 1
+    }";
+
+    let tokens = [
+        Token::At,
+        Token::FunctionIdent(Symbol::intern("inline")),
+        Token::LParen,
+        Token::Ident(Symbol::intern("never")),
+        Token::RParen,
+        Token::Ev,
+        Token::FunctionIdent(Symbol::intern("ev_fn")),
+        Token::LParen,
+        Token::LBracket,
+        Token::Ident(Symbol::intern("clk")),
+        Token::RBracket,
+        Token::RParen,
+        Token::LBrace,
+        Token::Enf,
+        Token::Ident(Symbol::intern("clk")),
+        Token::Quote,
+        Token::Equal,
+        Token::Ident(Symbol::intern("clk")),
+        Token::Plus,
+        Token::Num(1),
+        Token::RBrace,
+    ];
+    expect_valid_tokenization(source, tokens.to_vec());
+}
+
 #[test]
 fn ev_fn_call() {
     let source = "
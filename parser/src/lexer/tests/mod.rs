@@ -52,7 +52,9 @@ fn expect_error_at_location(source: &str, expected: LexicalError, line: u32, col
         .expect("expected lexical error, but lexing completed successfully");
 
     let loc = match &err {
-        LexicalError::InvalidInt { span, .. } => codemap.location(span).unwrap(),
+        LexicalError::InvalidInt { span, .. } | LexicalError::UnterminatedString { span } => {
+            codemap.location(span).unwrap()
+        },
         LexicalError::UnexpectedCharacter { start, .. } => {
             let span = miden_diagnostics::SourceSpan::new(*start, *start);
             codemap.location(&span).unwrap()
@@ -1,7 +1,9 @@
 #[macro_use]
 extern crate lalrpop_util;
 
+pub mod analysis;
 pub mod ast;
+pub mod error_codes;
 mod lexer;
 mod parser;
 mod sema;
@@ -0,0 +1,107 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use miden_diagnostics::{
+    ByteIndex, CodeMap, DiagnosticsHandler, FileName, Severity, SourceIndex, SourceSpan,
+};
+
+use super::ParseError;
+use crate::{Symbol, ast::field_neg};
+
+/// Loads the table of constants at `path` for the `include_table(path)` directive at `span`.
+///
+/// The path is relative to the file containing the directive, or to the current working
+/// directory if it was not parsed from disk. Each non-empty line of the table is a row, whose
+/// values are integers separated by commas and/or whitespace, and `#` starts a comment which
+/// extends to the end of the line. As for constants, a negative value denotes its additive inverse
+/// in the field.
+///
+/// The table is added to the [CodeMap], so that errors can be reported in the table itself. All
+/// rows must have the same number of values, and there must be at least one.
+pub(crate) fn include_table(
+    diagnostics: &DiagnosticsHandler,
+    codemap: &Arc<CodeMap>,
+    span: SourceSpan,
+    path: Symbol,
+) -> Result<Vec<Vec<u64>>, ParseError> {
+    let source_dir = match codemap.name(span.source_id()) {
+        Ok(FileName::Real(file)) => file.parent().unwrap_or_else(|| Path::new(".")).to_path_buf(),
+        _ => std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+    };
+    let filename = source_dir.join(path.as_str());
+    let file = match codemap.add_file(&filename).map(|id| codemap.get(id)) {
+        Ok(Ok(file)) => file,
+        Ok(Err(_)) => unreachable!("files are retrievable once added to the codemap"),
+        Err(err) => {
+            diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("invalid table")
+                .with_primary_label(span, format!("unable to read `{path}`: {err}"))
+                .emit();
+            return Err(ParseError::Failed);
+        },
+    };
+
+    let source = file.source();
+    let span_of = |value: &str| {
+        let start = value.as_ptr() as usize - source.as_ptr() as usize;
+        SourceSpan::new(
+            SourceIndex::new(file.id(), ByteIndex(start as u32)),
+            SourceIndex::new(file.id(), ByteIndex((start + value.len()) as u32)),
+        )
+    };
+
+    let mut rows: Vec<(SourceSpan, Vec<u64>)> = vec![];
+    for line in source.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut row = vec![];
+        for value in line.split([',', ' ', '\t']).filter(|value| !value.is_empty()) {
+            let parsed = match value.strip_prefix('-') {
+                Some(magnitude) => magnitude.parse::<u64>().map(field_neg),
+                None => value.parse::<u64>(),
+            };
+            match parsed {
+                Ok(value) => row.push(value),
+                Err(err) => {
+                    diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid table")
+                        .with_primary_label(span_of(value), format!("invalid integer: {err}"))
+                        .with_secondary_label(span, "the table is included here")
+                        .emit();
+                    return Err(ParseError::Failed);
+                },
+            }
+        }
+        rows.push((span_of(line), row));
+    }
+
+    let Some((first_span, first_row)) = rows.first() else {
+        diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid table")
+            .with_primary_label(span, format!("`{path}` has no rows"))
+            .emit();
+        return Err(ParseError::Failed);
+    };
+    if let Some((row_span, row)) = rows.iter().find(|(_, row)| row.len() != first_row.len()) {
+        diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid table")
+            .with_primary_label(*row_span, format!("this row has {} values", row.len()))
+            .with_secondary_label(
+                *first_span,
+                format!("but the first row has {} values", first_row.len()),
+            )
+            .with_secondary_label(span, "the table is included here")
+            .emit();
+        return Err(ParseError::Failed);
+    }
+
+    Ok(rows.into_iter().map(|(_, row)| row).collect())
+}
@@ -8,6 +8,8 @@ macro_rules! span {
     };
 }
 
+mod include;
+
 lalrpop_mod!(
     #[allow(clippy::all)]
     grammar,
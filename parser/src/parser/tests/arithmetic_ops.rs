@@ -144,6 +144,32 @@ fn multi_multiplication() {
     ParseTest::new().expect_module_ast(source, expected);
 }
 
+#[test]
+fn single_division() {
+    // the operation must be put into a source section, or parsing will fail
+    let source = "
+    mod test
+
+    ev test([clk]) {
+        enf clk' * (1 / 3) = clk / 2;
+    }";
+
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected.evaluators.insert(
+        ident!(test),
+        EvaluatorFunction::new(
+            SourceSpan::UNKNOWN,
+            ident!(test),
+            vec![trace_segment!(0, "%0", [(clk, 1)])],
+            vec![enforce!(eq!(
+                mul!(access!(clk, 1), div!(int!(1), int!(3))),
+                div!(access!(clk), int!(2))
+            ))],
+        ),
+    );
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
 #[test]
 fn unit_with_parens() {
     // the operation must be put into a source section, or parsing will fail
@@ -23,6 +23,45 @@ fn buses() {
     ParseTest::new().expect_module_ast(source, expected);
 }
 
+#[test]
+fn buses_with_multiplicity_bits() {
+    let source = "
+    mod test
+
+    buses {
+        logup q with bits,
+    }";
+
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected.buses.insert(
+        ident!(q),
+        Bus::new(SourceSpan::UNKNOWN, ident!(q), BusType::Logup)
+            .with_multiplicity_bits(ident!(bits)),
+    );
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn extern_buses() {
+    let source = "
+    mod test
+
+    buses {
+        extern multiset p,
+        logup q,
+    }";
+
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected.buses.insert(
+        ident!(p),
+        Bus::new(SourceSpan::UNKNOWN, ident!(p), BusType::Multiset).into_extern(),
+    );
+    expected
+        .buses
+        .insert(ident!(q), Bus::new(SourceSpan::UNKNOWN, ident!(q), BusType::Logup));
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
 #[test]
 fn boundary_constraints_buses() {
     let _source = "
@@ -80,6 +119,40 @@ fn integrity_constraints_buses() {
     ParseTest::new().expect_module_ast(source, expected);*/
 }
 
+#[test]
+fn named_bus_selector() {
+    let source = "
+    mod test
+
+    buses {
+        multiset p,
+    }
+
+    ev test([a, s, b]) {
+        p.insert(a) when s as g;
+        enf b = 0 when g;
+    }";
+
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected
+        .buses
+        .insert(ident!(p), Bus::new(SourceSpan::UNKNOWN, ident!(p), BusType::Multiset));
+    // The bus operation binds its selector for the rest of the block, and is gated by the binding
+    let body = vec![let_!(g = expr!(access!(s)) =>
+        bus_enforce!(lc!((("%2", range!(0..1))) => bus_insert!(p, vec![expr!(access!(a))]), when access!(g))),
+        enforce_all!(lc!((("%1", range!(0..1))) => eq!(access!(b), int!(0)), when access!(g))))];
+    expected.evaluators.insert(
+        ident!(test),
+        EvaluatorFunction::new(
+            SourceSpan::UNKNOWN,
+            ident!(test),
+            vec![trace_segment!(0, "%0", [(a, 1), (s, 1), (b, 1)])],
+            body,
+        ),
+    );
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
 #[test]
 fn err_empty_buses() {
     let source = "
@@ -87,6 +160,31 @@ fn err_empty_buses() {
 
     buses{}";
 
-    ParseTest::new()
-        .expect_module_diagnostic(source, "expected one of: '\"logup\"', '\"multiset\"'");
+    ParseTest::new().expect_module_diagnostic(source, "empty buses section");
+}
+
+#[test]
+fn err_duplicate_bus_declaration() {
+    let source = "
+    mod test
+
+    buses {
+        multiset p,
+        logup p,
+    }";
+
+    ParseTest::new().expect_module_diagnostic(source, "invalid bus declaration");
+}
+
+#[test]
+fn err_extern_bus_with_multiplicity_bits() {
+    // The range of the multiplicity is enforced by the program defining the bus
+    let source = "
+    mod test
+
+    buses {
+        extern logup q with bits,
+    }";
+
+    ParseTest::new().expect_module_diagnostic(source, "unexpected token");
 }
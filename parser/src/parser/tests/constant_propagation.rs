@@ -105,3 +105,82 @@ fn test_constant_propagation() {
 
     assert_eq!(program, expected);
 }
+
+#[test]
+fn test_constant_propagation_negative_values() {
+    let root = r#"
+    def root
+
+    trace_columns {
+        main: [a, b, c, d],
+    }
+
+    public_inputs {
+        inputs: [0],
+    }
+
+    const NEG = -1;
+
+    integrity_constraints {
+        enf a = -b;
+    }
+
+    boundary_constraints {
+        enf a.first = NEG;
+        enf b.first = -0;
+        enf c.first = (-2)^3;
+        enf d.first = 0 - 2^3;
+    }
+    "#;
+
+    let test = ParseTest::new();
+    let program = match test.parse_program(root) {
+        Err(err) => {
+            test.diagnostics.emit(err);
+            panic!("expected parsing to succeed, see diagnostics for details");
+        },
+        Ok(ast) => ast,
+    };
+
+    let mut pass = ConstantPropagation::new(&test.diagnostics);
+    let program = pass.run(program).unwrap();
+
+    let mut expected = Program::new(ident!(root));
+    expected
+        .trace_columns
+        .push(trace_segment!(0, "$main", [(a, 1), (b, 1), (c, 1), (d, 1)]));
+    expected
+        .public_inputs
+        .insert(ident!(inputs), PublicInput::new_vector(SourceSpan::UNKNOWN, ident!(inputs), 0));
+    expected
+        .constants
+        .insert(ident!(root, NEG), constant!(NEG = 18446744069414584320));
+    // Negative values are reduced modulo the field, so that the boundary constraints should look
+    // like:
+    //     enf a.first = p - 1
+    //     enf b.first = 0
+    //     enf c.first = p - 8
+    //     enf d.first = p - 8
+    expected.boundary_constraints.push(enforce!(eq!(
+        bounded_access!(a, Boundary::First, Type::Felt),
+        int!(FIELD_MODULUS - 1)
+    )));
+    expected
+        .boundary_constraints
+        .push(enforce!(eq!(bounded_access!(b, Boundary::First, Type::Felt), int!(0))));
+    expected.boundary_constraints.push(enforce!(eq!(
+        bounded_access!(c, Boundary::First, Type::Felt),
+        int!(FIELD_MODULUS - 8)
+    )));
+    expected.boundary_constraints.push(enforce!(eq!(
+        bounded_access!(d, Boundary::First, Type::Felt),
+        int!(FIELD_MODULUS - 8)
+    )));
+    // Unary minus is lowered to a subtraction from zero:
+    //     enf a = 0 - b
+    expected
+        .integrity_constraints
+        .push(enforce!(eq!(access!(a, Type::Felt), sub!(int!(0), access!(b, Type::Felt)))));
+
+    assert_eq!(program, expected);
+}
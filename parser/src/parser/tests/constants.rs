@@ -74,6 +74,136 @@ fn constants_matrices() {
     ParseTest::new().expect_module_ast(source, expected);
 }
 
+#[test]
+fn constants_negative() {
+    let source = "
+    mod test
+
+    const A = -2;
+    const B = [-1, 0, -0];";
+
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected.constants.insert(
+        ident!(A),
+        Constant::new(SourceSpan::UNKNOWN, ident!(A), ConstantExpr::Scalar(FIELD_MODULUS - 2)),
+    );
+    expected.constants.insert(
+        ident!(B),
+        Constant::new(
+            SourceSpan::UNKNOWN,
+            ident!(B),
+            ConstantExpr::Vector(vec![FIELD_MODULUS - 1, 0, 0]),
+        ),
+    );
+    ParseTest::new().expect_module_ast(source, expected);
+
+    // Values close to the modulus are displayed as negative integers
+    assert_eq!(ConstantExpr::Scalar(FIELD_MODULUS - 2).to_string(), "-2");
+    assert_eq!(ConstantExpr::Vector(vec![FIELD_MODULUS - 1, 0, 1]).to_string(), "[-1, 0, 1]");
+}
+
+#[test]
+fn constants_include_table() {
+    let source = r#"
+    mod test
+
+    const SBOX = include_table("src/parser/tests/input/sbox.csv");"#;
+
+    let sbox = [12, 5, 6, 11, 9, 0, 10, 13, 3, 14, 15, 8, 4, 7, 1, 2];
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected.constants.insert(
+        ident!(SBOX),
+        Constant::new(
+            SourceSpan::UNKNOWN,
+            ident!(SBOX),
+            ConstantExpr::Matrix((0..16).map(|i| vec![i, sbox[i as usize]]).collect()),
+        ),
+    );
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn constants_include_table_whitespace_separated() {
+    let source = r#"
+    mod test
+
+    const A = include_table("table.txt");"#;
+
+    let test = ParseTest::new();
+    let path = std::env::current_dir().unwrap().join("table.txt");
+    test.add_virtual_file(path, "1 2\t-3\n\n4  5,6 # comment\n".to_string());
+
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected.constants.insert(
+        ident!(A),
+        Constant::new(
+            SourceSpan::UNKNOWN,
+            ident!(A),
+            ConstantExpr::Matrix(vec![vec![1, 2, FIELD_MODULUS - 3], vec![4, 5, 6]]),
+        ),
+    );
+    test.expect_module_ast(source, expected);
+}
+
+#[test]
+fn err_include_table_not_rectangular() {
+    let source = r#"
+    mod test
+
+    const A = include_table("table.csv");"#;
+
+    let test = ParseTest::new();
+    let path = std::env::current_dir().unwrap().join("table.csv");
+    test.add_virtual_file(path, "1, 2\n3, 4, 5\n".to_string());
+    test.expect_module_diagnostic(source, "this row has 3 values");
+}
+
+#[test]
+fn err_include_table_invalid_value() {
+    let source = r#"
+    mod test
+
+    const A = include_table("table.csv");"#;
+
+    let test = ParseTest::new();
+    let path = std::env::current_dir().unwrap().join("table.csv");
+    test.add_virtual_file(path, "1, 2\n3, x\n".to_string());
+    test.expect_module_diagnostic(source, "invalid integer");
+}
+
+#[test]
+fn err_include_table_missing_file() {
+    let source = r#"
+    mod test
+
+    const A = include_table("missing.csv");"#;
+
+    ParseTest::new().expect_module_diagnostic(source, "unable to read");
+}
+
+#[test]
+fn err_include_table_empty() {
+    let source = r#"
+    mod test
+
+    const A = include_table("table.csv");"#;
+
+    let test = ParseTest::new();
+    let path = std::env::current_dir().unwrap().join("table.csv");
+    test.add_virtual_file(path, "# no rows\n".to_string());
+    test.expect_module_diagnostic(source, "has no rows");
+}
+
+#[test]
+fn err_unknown_constant_directive() {
+    let source = r#"
+    mod test
+
+    const A = include_matrix("table.csv");"#;
+
+    ParseTest::new().expect_module_diagnostic(source, "invalid constant declaration");
+}
+
 #[test]
 fn err_const_matrix_unequal_number_of_cols() {
     // This is invalid since the number of columns for the two rows are unequal. However this
@@ -172,3 +302,137 @@ fn err_invalid_matrix_element() {
     const A = [[1, 2], [3, [4, 5]]];";
     ParseTest::new().expect_unrecognized_token(source);
 }
+
+// COMPUTED CONSTANTS
+// ================================================================================================
+
+#[test]
+fn computed_constants() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    const fn rc(i: felt) -> felt {
+        return (i * 7 + 3)^5;
+    }
+
+    const RCS = [rc(i) for i in 0..8];
+    const R = rc(2);
+
+    boundary_constraints {
+        enf a.first = R;
+    }
+
+    integrity_constraints {
+        enf a' = a + RCS[3];
+    }";
+
+    let program = ParseTest::new().parse_program(source).expect("expected parsing to succeed");
+    let value = |name| {
+        program
+            .constants
+            .values()
+            .find(|constant| constant.name == name)
+            .map(|constant| constant.value.clone())
+    };
+    assert_eq!(
+        value(ident!(RCS)),
+        Some(ConstantExpr::Vector(vec![
+            243, 100000, 1419857, 7962624, 28629151, 79235168, 184528125, 380204032
+        ]))
+    );
+    assert_eq!(value(ident!(R)), Some(ConstantExpr::Scalar(1419857)));
+}
+
+#[test]
+fn err_computed_constant_trace_access() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    fn rc(i: felt) -> felt {
+        return i + a;
+    }
+
+    const RCS = [rc(i) for i in 0..8];
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a' = a + RCS[3];
+    }";
+
+    ParseTest::new().expect_program_diagnostic(source, "this value is not known at compile time");
+}
+
+#[test]
+fn err_computed_constant_call_depth() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    fn f(i: felt) -> felt {
+        return f(i + 1);
+    }
+
+    const X = f(0);
+
+    boundary_constraints {
+        enf a.first = X;
+    }
+
+    integrity_constraints {
+        enf a' = a;
+    }";
+
+    ParseTest::new().expect_program_diagnostic(source, "exceeds the limit of 64 nested calls");
+}
+
+#[test]
+fn err_computed_constant_iterations() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a],
+    }
+
+    public_inputs {
+        stack_inputs: [16],
+    }
+
+    const X = [i * i for i in 0..100000];
+
+    boundary_constraints {
+        enf a.first = X[0];
+    }
+
+    integrity_constraints {
+        enf a' = a;
+    }";
+
+    ParseTest::new().expect_program_diagnostic(source, "exceeds the limit of 65536 iterations");
+}
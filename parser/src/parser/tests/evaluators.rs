@@ -28,6 +28,30 @@ fn ev_fn_main_cols() {
     ParseTest::new().expect_module_ast(source, expected);
 }
 
+#[test]
+fn ev_fn_inline_never() {
+    let source = "
+    mod test
+
+    @inline(never)
+    ev advance_clock([clk]) {
+        enf clk' = clk + 1;
+    }";
+
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected.evaluators.insert(
+        ident!(advance_clock),
+        EvaluatorFunction::new(
+            SourceSpan::UNKNOWN,
+            ident!(advance_clock),
+            vec![trace_segment!(0, "%0", [(clk, 1)])],
+            vec![enforce!(eq!(access!(clk, 1), add!(access!(clk), int!(1))))],
+        )
+        .with_inline_never(true),
+    );
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
 #[test]
 fn ev_fn_call_simple() {
     let source = "
@@ -237,3 +261,30 @@ fn ev_fn_with_invalid_params() {
     }";
     ParseTest::new().expect_unrecognized_token(source);
 }
+
+#[test]
+fn err_ev_fn_invalid_attribute() {
+    let source = "
+    mod test
+
+    @inline(always)
+    ev advance_clock([clk]) {
+        enf clk' = clk + 1;
+    }";
+    ParseTest::new().expect_module_diagnostic(source, "invalid attribute");
+}
+
+#[test]
+fn err_ev_fn_duplicate_declaration() {
+    let source = "
+    mod test
+
+    ev advance_clock([clk]) {
+        enf clk' = clk + 1;
+    }
+
+    ev advance_clock([clk]) {
+        enf clk' = clk + 2;
+    }";
+    ParseTest::new().expect_module_diagnostic(source, "invalid evaluator declaration");
+}
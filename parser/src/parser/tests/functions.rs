@@ -490,3 +490,18 @@ fn fn_call_in_vector_def() {
 
     ParseTest::new().expect_module_ast(source, expected);
 }
+
+#[test]
+fn err_fn_duplicate_declaration() {
+    let source = "
+    mod test
+
+    fn double(a: felt) -> felt {
+        return a + a;
+    }
+
+    fn double(a: felt) -> felt {
+        return 2 * a;
+    }";
+    ParseTest::new().expect_module_diagnostic(source, "invalid function declaration");
+}
@@ -748,9 +748,9 @@ fn error_invalid_next_usage() {
     }
 
     integrity_constraints {
-        enf clk'' = clk + 1
+        enf clk'' = clk + 1;
     }";
-    ParseTest::new().expect_unrecognized_token(source);
+    ParseTest::new().expect_module_diagnostic(source, "invalid row offset");
 }
 
 #[test]
@@ -769,3 +769,31 @@ fn err_empty_integrity_constraints() {
     }";
     ParseTest::new().expect_unrecognized_token(source);
 }
+
+#[test]
+fn err_ic_windowed_offset_in_fold() {
+    // Frames only span two rows, so a windowed sum over more rows must be rejected with a
+    // diagnostic pointing towards auxiliary columns.
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a],
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a''' = sum([a, a', a'']);
+    }";
+
+    let test = ParseTest::new();
+    test.expect_module_diagnostic(source, "invalid row offset");
+    test.expect_module_diagnostic(source, "introduce auxiliary columns");
+}
@@ -444,6 +444,49 @@ fn ic_multiple_iterables_lc() {
     ParseTest::new().expect_module_ast(source, expected);
 }
 
+#[test]
+fn ic_index_by_binding_lc() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [a, b, c[4]],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        let x = [c[i] * i for i in 0..3];
+        enf a = x[0] + x[1] + x[2];
+    }";
+
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected
+        .trace_columns
+        .push(trace_segment!(0, "$main", [(a, 1), (b, 1), (c, 4)]));
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(bounded_access!(a, Boundary::First), int!(0)))],
+    ));
+    let indexed = ScalarExpr::SymbolAccess(SymbolAccess::new(
+        SourceSpan::UNKNOWN,
+        ident!(c),
+        AccessType::Bound(vec![AccessIndex::Binding(ident!(i))]),
+        0,
+    ));
+    expected.integrity_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![
+            let_!(x = lc!(((i, range!(0..3))) => mul!(indexed, access!(i))).into() =>
+                  enforce!(eq!(access!(a), add!(add!(access!(x[0]), access!(x[1])), access!(x[2]))))),
+        ],
+    ));
+
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
 // INVALID LIST COMPREHENSION
 // ================================================================================================
 
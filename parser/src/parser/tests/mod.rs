@@ -517,7 +517,8 @@ macro_rules! lc {
                 (ident!($binding), $iterable)
             ),+
         ];
-        ListComprehension::new(miden_diagnostics::SourceSpan::UNKNOWN, $body, context, Some($multiplicity))
+        ListComprehension::new(miden_diagnostics::SourceSpan::UNKNOWN, $body, context, None)
+            .with_multiplicity($multiplicity)
     }};
 
     (($(($binding:literal, $iterable:expr)),*) => $body:expr, with $multiplicity:expr) => {{
@@ -526,7 +527,8 @@ macro_rules! lc {
                 (ident!($binding), $iterable)
             ),+
         ];
-        ListComprehension::new(miden_diagnostics::SourceSpan::UNKNOWN, $body, context, Some($multiplicity))
+        ListComprehension::new(miden_diagnostics::SourceSpan::UNKNOWN, $body, context, None)
+            .with_multiplicity($multiplicity)
     }};
 }
 
@@ -625,6 +627,17 @@ macro_rules! mul {
     };
 }
 
+macro_rules! div {
+    ($lhs:expr, $rhs:expr) => {
+        ScalarExpr::Binary(BinaryExpr::new(
+            miden_diagnostics::SourceSpan::UNKNOWN,
+            BinaryOp::Div,
+            $lhs,
+            $rhs,
+        ))
+    };
+}
+
 macro_rules! exp {
     ($lhs:expr, $rhs:expr) => {
         ScalarExpr::Binary(BinaryExpr::new(
@@ -667,6 +680,7 @@ mod periodic_columns;
 mod pub_inputs;
 mod sections;
 mod selectors;
+mod symbol_table;
 mod trace_columns;
 mod variables;
 
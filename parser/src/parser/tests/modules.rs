@@ -100,3 +100,133 @@ fn modules_integration_test() {
     ParseTest::new()
         .expect_program_ast_from_file("src/parser/tests/input/import_example.air", expected);
 }
+
+#[test]
+fn err_import_conflicts_with_declaration() {
+    let root = "
+    def root
+
+    use lib1::advance_clock;
+
+    trace_columns {
+        main: [clk],
+    }
+
+    public_inputs {
+        inputs: [0],
+    }
+
+    ev advance_clock([clk]) {
+        enf clk' = clk + 2;
+    }
+
+    integrity_constraints {
+        enf advance_clock([clk]);
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }";
+    let lib1 = "
+    mod lib1
+
+    ev advance_clock([clk]) {
+        enf clk' = clk + 1;
+    }";
+
+    let test = ParseTest::new();
+    let path = std::env::current_dir().unwrap().join("lib1.air");
+    test.add_virtual_file(path, lib1.to_string());
+    test.expect_program_diagnostic(
+        root,
+        "invalid evaluator declaration, 'advance_clock' is already imported from 'lib1'",
+    );
+}
+
+#[test]
+fn err_import_conflicts_with_import() {
+    let root = "
+    def root
+
+    use lib1::advance_clock;
+    use lib2::advance_clock;
+
+    trace_columns {
+        main: [clk],
+    }
+
+    public_inputs {
+        inputs: [0],
+    }
+
+    integrity_constraints {
+        enf advance_clock([clk]);
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }";
+    let lib1 = "
+    mod lib1
+
+    ev advance_clock([clk]) {
+        enf clk' = clk + 1;
+    }";
+    let lib2 = "
+    mod lib2
+
+    ev advance_clock([clk]) {
+        enf clk' = clk + 2;
+    }";
+
+    let test = ParseTest::new();
+    let path = std::env::current_dir().unwrap().join("lib1.air");
+    test.add_virtual_file(path, lib1.to_string());
+    let path = std::env::current_dir().unwrap().join("lib2.air");
+    test.add_virtual_file(path, lib2.to_string());
+    test.expect_program_diagnostic(root, "'advance_clock' is imported from both 'lib1' and 'lib2'");
+}
+
+#[test]
+fn err_wildcard_import_conflicts_with_import() {
+    let root = "
+    def root
+
+    use lib1::*;
+    use lib2::*;
+
+    trace_columns {
+        main: [clk],
+    }
+
+    public_inputs {
+        inputs: [0],
+    }
+
+    integrity_constraints {
+        enf advance_clock([clk]);
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }";
+    let lib1 = "
+    mod lib1
+
+    ev advance_clock([clk]) {
+        enf clk' = clk + 1;
+    }";
+    let lib2 = "
+    mod lib2
+
+    ev advance_clock([clk]) {
+        enf clk' = clk + 2;
+    }";
+
+    let test = ParseTest::new();
+    let path = std::env::current_dir().unwrap().join("lib1.air");
+    test.add_virtual_file(path, lib1.to_string());
+    let path = std::env::current_dir().unwrap().join("lib2.air");
+    test.add_virtual_file(path, lib2.to_string());
+    test.expect_program_diagnostic(root, "the item 'advance_clock' is imported from 'lib2' here");
+}
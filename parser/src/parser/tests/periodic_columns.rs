@@ -32,8 +32,7 @@ fn empty_periodic_columns() {
 
     periodic_columns{}";
 
-    let expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
-    ParseTest::new().expect_module_ast(source, expected);
+    ParseTest::new().expect_module_diagnostic(source, "empty periodic_columns section");
 }
 
 #[test]
@@ -50,3 +49,53 @@ fn err_periodic_columns_length() {
         "periodic columns must have a non-zero cycle length which is a power of two",
     );
 }
+
+#[test]
+fn periodic_columns_from_round_constants() {
+    let source = "
+    mod test
+
+    periodic_columns {
+        k0: round_const([3, 5, 7, 11]),
+        k1: round_const([3, -1], 4),
+    }";
+
+    let mut expected = Module::new(ModuleType::Library, SourceSpan::UNKNOWN, ident!(test));
+    expected.periodic_columns.insert(
+        ident!(k0),
+        PeriodicColumn::new(SourceSpan::UNKNOWN, ident!(k0), vec![3, 5, 7, 11]),
+    );
+    expected.periodic_columns.insert(
+        ident!(k1),
+        PeriodicColumn::new(SourceSpan::UNKNOWN, ident!(k1), vec![3, 0xffff_ffff_0000_0000, 0, 0]),
+    );
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn err_round_constants_exceed_period() {
+    let source = "
+    mod test
+
+    periodic_columns {
+        k0: round_const([1, 2, 3], 2),
+    }";
+
+    ParseTest::new()
+        .expect_module_diagnostic(source, "3 round constants do not fit in a cycle of length 2");
+}
+
+#[test]
+fn err_round_constants_period_length() {
+    let source = "
+    mod test
+
+    periodic_columns {
+        k0: round_const([1, 2, 3]),
+    }";
+
+    ParseTest::new().expect_module_diagnostic(
+        source,
+        "periodic columns must have a non-zero cycle length which is a power of two",
+    );
+}
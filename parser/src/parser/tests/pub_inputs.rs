@@ -1,7 +1,7 @@
 use miden_diagnostics::{SourceSpan, Span};
 
 use super::ParseTest;
-use crate::{ast::*, parser::ParseError};
+use crate::ast::*;
 
 // PUBLIC INPUTS
 // ================================================================================================
@@ -87,15 +87,255 @@ fn public_inputs_table() {
 }
 
 #[test]
-fn error_no_public_input() {
+fn public_inputs_named_table() {
     let source = "
     def test
 
     trace_columns {
-        main: [clk]
+        main: [clk],
+    }
+
+    buses {
+        multiset p,
+    }
+
+    public_inputs {
+        overflow: [[clk, value, prev]],
+    }
+
+    boundary_constraints {
+        enf p.first = [(clk, value, prev) for (clk, value, prev) in overflow];
+        enf p.last = [(row[0], row[1], row[2]) for row in overflow];
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected.trace_columns.push(trace_segment!(0, "$main", [(clk, 1)]));
+    expected
+        .buses
+        .insert(ident!(p), Bus::new(SourceSpan::UNKNOWN, ident!(p), BusType::Multiset));
+    expected.public_inputs.insert(
+        ident!(overflow),
+        PublicInput::new_named_table(
+            SourceSpan::UNKNOWN,
+            ident!(overflow),
+            vec![ident!(clk), ident!(value), ident!(prev)],
+        ),
+    );
+    let rows = |row, bindings| {
+        ScalarExpr::RowComprehension(RowComprehension::new(
+            SourceSpan::UNKNOWN,
+            Span::new(SourceSpan::UNKNOWN, row),
+            Span::new(SourceSpan::UNKNOWN, bindings),
+            ident!(overflow),
+        ))
+    };
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![
+            enforce!(eq!(
+                bounded_access!(p, Boundary::First),
+                rows(
+                    vec![access!(clk), access!(value), access!(prev)],
+                    vec![ident!(clk), ident!(value), ident!(prev)]
+                )
+            )),
+            enforce!(eq!(
+                bounded_access!(p, Boundary::Last),
+                rows(vec![access!(row[0]), access!(row[1]), access!(row[2])], vec![ident!(row)])
+            )),
+        ],
+    ));
+    expected.integrity_constraints =
+        Some(Span::new(SourceSpan::UNKNOWN, vec![enforce!(eq!(access!(clk), int!(0)))]));
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn err_public_inputs_table_duplicate_column() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [clk],
+    }
+
+    public_inputs {
+        overflow: [[clk, value, clk]],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    ParseTest::new().expect_module_diagnostic(source, "invalid table column declaration");
+}
+
+#[test]
+fn public_inputs_commitment_table() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [clk],
+    }
+
+    public_inputs {
+        @commitment a: [[4]],
+        b: [[32]],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected.trace_columns.push(trace_segment!(0, "$main", [(clk, 1)]));
+    expected.public_inputs.insert(
+        ident!(a),
+        PublicInput::new_table(SourceSpan::UNKNOWN, ident!(a), 4).with_commitment(true),
+    );
+    expected
+        .public_inputs
+        .insert(ident!(b), PublicInput::new_table(SourceSpan::UNKNOWN, ident!(b), 32));
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(bounded_access!(clk, Boundary::First), int!(0)))],
+    ));
+    expected.integrity_constraints =
+        Some(Span::new(SourceSpan::UNKNOWN, vec![enforce!(eq!(access!(clk), int!(0)))]));
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn err_public_inputs_commitment_vector() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [clk],
+    }
+
+    public_inputs {
+        @commitment stack_inputs: [16],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    ParseTest::new()
+        .expect_module_diagnostic(source, "only tables may be annotated with `@commitment`");
+}
+
+#[test]
+fn err_public_inputs_unknown_attribute() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [clk],
+    }
+
+    public_inputs {
+        @hash a: [[4]],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    ParseTest::new().expect_module_diagnostic(source, "invalid attribute");
+}
+
+#[test]
+fn public_inputs_omitted() {
+    // Public inputs are optional, as long as none are referenced.
+    let source = "
+    def test
+
+    trace_columns {
+        main: [clk],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected.trace_columns.push(trace_segment!(0, "$main", [(clk, 1)]));
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(bounded_access!(clk, Boundary::First), int!(0)))],
+    ));
+    expected.integrity_constraints =
+        Some(Span::new(SourceSpan::UNKNOWN, vec![enforce!(eq!(access!(clk), int!(0)))]));
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn err_empty_public_inputs() {
+    // An empty section is treated as omitted, but produces a warning.
+    let source = "
+    def test
+
+    trace_columns {
+        main: [clk],
     }
 
     public_inputs { }
-    ";
-    assert_module_error!(source, ParseError::UnrecognizedToken { .. });
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    ParseTest::new().expect_module_diagnostic(source, "empty public_inputs section");
+}
+
+#[test]
+fn err_public_input_undefined() {
+    // Referencing a public input without declaring any is an error.
+    let source = "
+    def test
+
+    trace_columns {
+        main: [clk],
+    }
+
+    boundary_constraints {
+        enf clk.first = stack_inputs[0];
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    ParseTest::new().expect_program_diagnostic(source, "reference to undefined variable / bus");
 }
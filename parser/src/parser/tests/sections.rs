@@ -1,4 +1,7 @@
+use miden_diagnostics::{SourceSpan, Span};
+
 use super::ParseTest;
+use crate::ast::*;
 
 // SECTIONS
 // ================================================================================================
@@ -13,3 +16,94 @@ fn error_constraint_without_section() {
     "#;
     ParseTest::new().expect_unrecognized_token(source);
 }
+
+#[test]
+fn sections_in_any_order() {
+    // Sections may be declared in any order.
+    let source = "
+    def test
+
+    integrity_constraints {
+        enf clk' = clk + k0;
+    }
+
+    periodic_columns {
+        k0: [1, 0],
+    }
+
+    boundary_constraints {
+        enf clk.first = inputs[0];
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    trace_columns {
+        main: [clk],
+    }";
+
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected.trace_columns.push(trace_segment!(0, "$main", [(clk, 1)]));
+    expected
+        .public_inputs
+        .insert(ident!(inputs), PublicInput::new_vector(SourceSpan::UNKNOWN, ident!(inputs), 2));
+    expected
+        .periodic_columns
+        .insert(ident!(k0), PeriodicColumn::new(SourceSpan::UNKNOWN, ident!(k0), vec![1, 0]));
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(bounded_access!(clk, Boundary::First), access!(inputs[0])))],
+    ));
+    expected.integrity_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(access!(clk, 1), add!(access!(clk), access!(k0))))],
+    ));
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn err_duplicate_public_inputs_section() {
+    let source = "
+    def test
+
+    trace_columns {
+        main: [clk],
+    }
+
+    public_inputs {
+        a: [2],
+    }
+
+    public_inputs {
+        b: [2],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk = 0;
+    }";
+
+    let test = ParseTest::new();
+    test.expect_module_diagnostic(source, "invalid public_inputs declaration");
+    test.expect_module_diagnostic(source, "previously defined here");
+}
+
+#[test]
+fn err_duplicate_periodic_columns_section() {
+    let source = "
+    mod test
+
+    periodic_columns {
+        k0: [1, 0],
+    }
+
+    periodic_columns {
+        k1: [0, 1],
+    }";
+
+    ParseTest::new().expect_module_diagnostic(source, "invalid periodic_columns declaration");
+}
@@ -84,3 +84,134 @@ fn chained_selectors() {
 
     ParseTest::new().expect_module_ast(source, expected);
 }
+
+#[test]
+fn parity_selectors() {
+    let source = r#"
+    def test
+
+    trace_columns {
+        main: [clk, n1],
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf on_even {
+            clk' = clk;
+            n1' = n1 when clk;
+        };
+        enf on_odd {
+            clk' = clk + 1;
+        };
+        enf on_even {
+            n1' = 0;
+        };
+    }"#;
+    let even = || {
+        ScalarExpr::SymbolAccess(SymbolAccess::new(
+            SourceSpan::UNKNOWN,
+            ident!("%even"),
+            AccessType::Default,
+            0,
+        ))
+    };
+    let odd = || {
+        ScalarExpr::SymbolAccess(SymbolAccess::new(
+            SourceSpan::UNKNOWN,
+            ident!("%odd"),
+            AccessType::Default,
+            0,
+        ))
+    };
+
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected.trace_columns.push(trace_segment!(0, "$main", [(clk, 1), (n1, 1)]));
+    expected
+        .public_inputs
+        .insert(ident!(inputs), PublicInput::new_vector(SourceSpan::UNKNOWN, ident!(inputs), 2));
+    // A single period-2 column is synthesized for each parity
+    expected.periodic_columns.insert(
+        ident!("%even"),
+        PeriodicColumn::new(SourceSpan::UNKNOWN, ident!("%even"), vec![1, 0]),
+    );
+    expected.periodic_columns.insert(
+        ident!("%odd"),
+        PeriodicColumn::new(SourceSpan::UNKNOWN, ident!("%odd"), vec![0, 1]),
+    );
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(bounded_access!(clk, Boundary::First), int!(0)))],
+    ));
+    // Each constraint of a block is gated by the column, in addition to its own selector
+    expected.integrity_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![
+            enforce_all!(
+                lc!((("%1", range!(0..1))) => eq!(access!(clk, 1), access!(clk)), when even())
+            ),
+            enforce_all!(
+                lc!((("%0", range!(0..1))) => eq!(access!(n1, 1), access!(n1)), when mul!(even(), access!(clk)))
+            ),
+            enforce_all!(
+                lc!((("%2", range!(0..1))) => eq!(access!(clk, 1), add!(access!(clk), int!(1))), when odd())
+            ),
+            enforce_all!(lc!((("%3", range!(0..1))) => eq!(access!(n1, 1), int!(0)), when even())),
+        ],
+    ));
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn masked_constraint() {
+    let source = r#"
+    def test
+
+    trace_columns {
+        main: [clk, n1],
+    }
+
+    periodic_columns {
+        k: [1, 0],
+    }
+
+    boundary_constraints {
+        enf clk.first = 0;
+    }
+
+    integrity_constraints {
+        enf clk' = clk unless k;
+        enf n1' = n1 when clk unless k;
+    }"#;
+    let mask = || SymbolAccess::new(SourceSpan::UNKNOWN, ident!(k), AccessType::Default, 0);
+
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected.trace_columns.push(trace_segment!(0, "$main", [(clk, 1), (n1, 1)]));
+    expected
+        .periodic_columns
+        .insert(ident!(k), PeriodicColumn::new(SourceSpan::UNKNOWN, ident!(k), vec![1, 0]));
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(bounded_access!(clk, Boundary::First), int!(0)))],
+    ));
+    // The mask is kept apart from the selector until semantic analysis
+    expected.integrity_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![
+            enforce_all!(
+                lc!((("%0", range!(0..1))) => eq!(access!(clk, 1), access!(clk))).with_mask(mask())
+            ),
+            enforce_all!(
+                lc!((("%1", range!(0..1))) => eq!(access!(n1, 1), access!(n1)), when access!(clk))
+                    .with_mask(mask())
+            ),
+        ],
+    ));
+    ParseTest::new().expect_module_ast(source, expected);
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use miden_diagnostics::{CaptureEmitter, CodeMap, DiagnosticsHandler, Spanned};
+
+use crate::{
+    analysis::{SymbolInfo, SymbolKind, SymbolScope, SymbolTable, symbol_table_from_file},
+    ast::*,
+    parser::ParseError,
+};
+
+fn load(path: &str) -> (Arc<CodeMap>, Arc<CaptureEmitter>, Result<SymbolTable, ParseError>) {
+    let codemap = Arc::new(CodeMap::new());
+    let emitter = Arc::new(CaptureEmitter::default());
+    let diagnostics = DiagnosticsHandler::new(Default::default(), codemap.clone(), emitter.clone());
+    let symbols = symbol_table_from_file(&diagnostics, codemap.clone(), path);
+    (codemap, emitter, symbols)
+}
+
+/// Returns the only symbol named `name` declared in `module`
+#[track_caller]
+fn symbol<'a>(symbols: &'a SymbolTable, module: &str, name: &'a str) -> &'a SymbolInfo {
+    let mut found = symbols.get(name).filter(|symbol| symbol.module.as_str() == module);
+    let symbol = found.next().unwrap_or_else(|| panic!("missing symbol {module}::{name}"));
+    assert!(found.next().is_none(), "duplicate symbol {module}::{name}");
+    symbol
+}
+
+#[test]
+fn symbol_table_with_imports() {
+    let (codemap, _, symbols) = load("src/parser/tests/input/import_example.air");
+    let symbols = symbols.expect("parsing failed");
+    assert_eq!(symbols.root.unwrap().as_str(), "import_example");
+
+    let root = ident!(import_example);
+    let clk = symbol(&symbols, "import_example", "clk");
+    assert!(matches!(
+        clk.kind,
+        SymbolKind::TraceColumns { segment: 0, offset: 0, size: 1, .. }
+    ));
+    assert_eq!(clk.scopes, [SymbolScope::Constraints(root)]);
+    assert_eq!(codemap.source_slice(&clk.name.span()).unwrap(), "clk");
+
+    let inputs = symbol(&symbols, "import_example", "inputs");
+    assert_eq!(inputs.kind, SymbolKind::PublicInput { size: 2 });
+    assert_eq!(inputs.scopes, [SymbolScope::BoundaryConstraints(root)]);
+
+    // Everything exported by `foo` is imported, even if unused
+    for name in ["foo_constraint", "other_constraint"] {
+        let evaluator = symbol(&symbols, "foo", name);
+        assert!(matches!(evaluator.kind, SymbolKind::Evaluator { .. }));
+        assert_eq!(evaluator.scopes, [SymbolScope::Module(ident!(foo)), SymbolScope::Module(root)]);
+        assert_eq!(codemap.source_slice(&evaluator.name.span()).unwrap(), name);
+        let file = codemap.name(evaluator.name.span().source_id()).unwrap().to_string();
+        assert!(file.ends_with("foo.air"), "unexpected file {file}");
+    }
+    let location = codemap.location(&symbol(&symbols, "foo", "foo_constraint").name.span());
+    assert_eq!(location.unwrap().line.to_usize(), 7);
+
+    // Periodic columns are not exported, so they do not conflict
+    let k0 = symbol(&symbols, "bar", "k0");
+    assert_eq!(k0.kind, SymbolKind::PeriodicColumn { period: 2 });
+    assert_eq!(k0.scopes, [SymbolScope::Module(ident!(bar))]);
+    assert_eq!(symbol(&symbols, "foo", "k0").kind, SymbolKind::PeriodicColumn { period: 4 });
+
+    let json = symbols.to_json(&codemap);
+    assert!(json.starts_with(r#"{"root":"import_example","symbols":[{"name":"clk","#));
+    assert!(json.contains(
+        r#"{"name":"bar_constraint","module":"bar","kind":"evaluator","params":[{"segment":0,"bindings":[{"name":"clk","size":1}]}],"span":{"file":"#
+    ));
+}
+
+#[test]
+fn symbol_table_with_semantic_errors() {
+    let (codemap, emitter, symbols) = load("src/parser/tests/input/symbols_with_errors.air");
+    let symbols = symbols.expect("the root module should be parsed");
+    assert!(emitter.captured().contains("undefined"));
+
+    let root = ident!(symbols_with_errors);
+    let width = symbol(&symbols, "symbols_with_errors", "WIDTH");
+    assert_eq!(width.kind, SymbolKind::Constant { ty: Type::Vector(2) });
+    assert_eq!(codemap.source_slice(&width.name.span()).unwrap(), "WIDTH");
+
+    let limbs = symbol(&symbols, "symbols_with_errors", "limbs");
+    assert!(matches!(limbs.kind, SymbolKind::TraceColumns { offset: 1, size: 2, .. }));
+
+    let double = symbol(&symbols, "symbols_with_errors", "double");
+    assert_eq!(
+        double.kind,
+        SymbolKind::Function {
+            params: vec![(ident!(x), Type::Felt)],
+            result: Type::Felt
+        }
+    );
+
+    // Only the imported evaluator is visible from the root module
+    let foo_constraint = symbol(&symbols, "foo", "foo_constraint");
+    assert_eq!(
+        foo_constraint.scopes,
+        [SymbolScope::Module(ident!(foo)), SymbolScope::Module(root)]
+    );
+    let other_constraint = symbol(&symbols, "foo", "other_constraint");
+    assert_eq!(other_constraint.scopes, [SymbolScope::Module(ident!(foo))]);
+}
+
+#[test]
+fn symbol_table_with_doc_comments() {
+    let (codemap, _, symbols) = load("src/parser/tests/input/doc_comments.air");
+    let symbols = symbols.expect("parsing failed");
+
+    let module = "doc_comments";
+    assert_eq!(symbol(&symbols, module, "LIMBS").docs, ["The number of limbs of a word"]);
+    // A doc comment documents every item declared on the next line
+    for name in ["clk", "limbs"] {
+        assert_eq!(symbol(&symbols, module, name).docs, ["The clock and the limbs of the word"]);
+    }
+    assert!(symbol(&symbols, module, "inputs").docs.is_empty());
+    assert_eq!(
+        symbol(&symbols, module, "is_binary").docs,
+        ["Enforces that the column is binary.", "", "Used by every selector."]
+    );
+
+    let json = symbols.to_json(&codemap);
+    assert!(json.contains(r#""docs":["The number of limbs of a word"]}"#));
+    assert!(json.contains(
+        r#""docs":["Enforces that the column is binary.","","Used by every selector."]}"#
+    ));
+    assert_eq!(json.matches(r#""docs""#).count(), 4);
+}
@@ -84,6 +84,171 @@ fn trace_columns_groups() {
     ParseTest::new().expect_module_ast(source, expected);
 }
 
+#[test]
+fn trace_columns_virtual() {
+    let source = r#"
+    def test
+
+    trace_columns {
+        main: [a, b, @virtual(a * b) c],
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf c' = c + a;
+    }"#;
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected
+        .trace_columns
+        .push(trace_segment!(0, "$main", [(a, 1), (b, 1), (c, 1)]));
+    expected.virtual_columns.push(VirtualColumn::new(
+        SourceSpan::UNKNOWN,
+        ident!(c),
+        mul!(access!(a), access!(b)),
+    ));
+    expected
+        .public_inputs
+        .insert(ident!(inputs), PublicInput::new_vector(SourceSpan::UNKNOWN, ident!(inputs), 2));
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(bounded_access!(a, Boundary::First), int!(0)))],
+    ));
+    expected.integrity_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(access!(c, 1), add!(access!(c), access!(a))))],
+    ));
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn trace_columns_binary() {
+    let source = r#"
+    def test
+
+    trace_columns {
+        main: [a, @binary s[2]],
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf a' = a + s[0];
+    }"#;
+    let mut expected = Module::new(ModuleType::Root, SourceSpan::UNKNOWN, ident!(test));
+    expected.trace_columns.push(trace_segment!(0, "$main", [(a, 1), (s, 2)]));
+    expected
+        .public_inputs
+        .insert(ident!(inputs), PublicInput::new_vector(SourceSpan::UNKNOWN, ident!(inputs), 2));
+    expected.boundary_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![enforce!(eq!(bounded_access!(a, Boundary::First), int!(0)))],
+    ));
+    // The booleanity of the columns is enforced before the constraints of the section
+    expected.integrity_constraints = Some(Span::new(
+        SourceSpan::UNKNOWN,
+        vec![
+            enforce!(eq!(mul!(access!(s[0]), access!(s[0])), access!(s[0]))),
+            enforce!(eq!(mul!(access!(s[1]), access!(s[1])), access!(s[1]))),
+            enforce!(eq!(access!(a, 1), add!(access!(a), access!(s[0])))),
+        ],
+    ));
+    ParseTest::new().expect_module_ast(source, expected);
+}
+
+#[test]
+fn err_trace_columns_invalid_attribute() {
+    let source = r#"
+    def test
+
+    trace_columns {
+        main: [a, b, @inline(a) c],
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf a.first = 0;
+    }
+
+    integrity_constraints {
+        enf c = a;
+    }"#;
+
+    ParseTest::new().expect_module_diagnostic(
+        source,
+        "the only supported attributes on trace columns are `@virtual(..)` and `@binary`",
+    );
+}
+
+#[test]
+fn err_trace_columns_virtual_vector_definition() {
+    let source = r#"
+    def test
+
+    trace_columns {
+        main: [a[2], @virtual(a) c],
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf c.first = 0;
+    }
+
+    integrity_constraints {
+        enf c = a[0];
+    }"#;
+
+    ParseTest::new()
+        .expect_program_diagnostic(source, "expected a scalar value here, but got felt[2]");
+}
+
+#[test]
+fn err_trace_columns_virtual_call_definition() {
+    let source = r#"
+    def test
+
+    trace_columns {
+        main: [a, @virtual(double(a)) c],
+    }
+
+    public_inputs {
+        inputs: [2],
+    }
+
+    boundary_constraints {
+        enf c.first = 0;
+    }
+
+    integrity_constraints {
+        enf c = a;
+    }
+
+    fn double(x: felt) -> felt {
+        return x + x;
+    }"#;
+
+    ParseTest::new()
+        .expect_program_diagnostic(source, "only arithmetic on scalar values is supported here");
+}
+
 #[test]
 fn err_empty_trace_columns() {
     let source = r#"
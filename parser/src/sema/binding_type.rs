@@ -177,6 +177,29 @@ impl BindingType {
         }
     }
 
+    /// Produce the [BindingType] of the elements of an empty vector, e.g. `a[0..0]`, which cannot be
+    /// obtained by accessing its first element.
+    ///
+    /// Returns `None` if this binding is not an empty vector.
+    pub fn empty_element(&self) -> Option<Self> {
+        match self {
+            Self::Alias(aliased) => aliased.empty_element(),
+            Self::Local(ty) => ty.empty_element().map(Self::Local),
+            Self::Constant(ty) => {
+                ty.empty_element().map(|t| Self::Alias(Box::new(Self::Constant(t))))
+            },
+            Self::PublicInput(ty) => ty.empty_element().map(Self::PublicInput),
+            Self::TraceColumn(tb) if tb.ty == Type::Vector(0) => {
+                Some(Self::TraceColumn(TraceBinding { size: 1, ty: Type::Felt, ..*tb }))
+            },
+            Self::TraceParam(tb) if tb.ty == Type::Vector(0) => {
+                Some(Self::TraceParam(TraceBinding { size: 1, ty: Type::Felt, ..*tb }))
+            },
+            Self::Vector(elems) if elems.is_empty() => Some(Self::Local(Type::Felt)),
+            _ => None,
+        }
+    }
+
     /// Produce a new [BindingType] which represents accessing the current binding via `access_type`
     pub fn access(&self, access_type: AccessType) -> Result<Self, InvalidAccessError> {
         match self {
@@ -205,6 +228,7 @@ impl BindingType {
                     Err(InvalidAccessError::IndexOutOfBounds)
                 },
                 AccessType::Matrix(row, col) => elems[row].access(AccessType::Index(col)),
+                AccessType::Bound(_) => unreachable!(),
             },
             Self::PublicInput(ty) => ty.access(access_type).map(Self::PublicInput),
             Self::PeriodicColumn(period) => match access_type {
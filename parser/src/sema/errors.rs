@@ -11,8 +11,6 @@ pub enum SemanticAnalysisError {
         "root module must contain at both boundary_constraints and integrity_constraints sections"
     )]
     MissingConstraints,
-    #[error("root module must contain a public_inputs section")]
-    MissingPublicInputs,
     #[error("reference to unknown module '{0}'")]
     MissingModule(ModuleId),
     #[error("invalid use of restricted section type in library module")]
@@ -26,7 +24,11 @@ pub enum SemanticAnalysisError {
     #[error("cannot import from self")]
     ImportSelf(SourceSpan),
     #[error("import conflict")]
-    ImportConflict { item: Identifier, prev: SourceSpan },
+    ImportConflict {
+        item: Identifier,
+        module: ModuleId,
+        prev: SourceSpan,
+    },
     #[error("import failed")]
     ImportFailed(SourceSpan),
     #[error(transparent)]
@@ -55,7 +57,6 @@ impl ToDiagnostic for SemanticAnalysisError {
         match self {
             Self::MissingRoot => Diagnostic::error().with_message("no root module found"),
             Self::MissingConstraints => Diagnostic::error().with_message("root module must contain both boundary_constraints and integrity_constraints sections"),
-            Self::MissingPublicInputs => Diagnostic::error().with_message("root module must contain a public_inputs section"),
             Self::MissingModule(id) => Diagnostic::error()
                 .with_message("found reference to module which does not exist")
                 .with_labels(vec![Label::primary(id.span().source_id(), id.span()).with_message("this module could not be found")]),
@@ -80,10 +81,10 @@ impl ToDiagnostic for SemanticAnalysisError {
                 .with_message("invalid import")
                 .with_labels(vec![Label::primary(span.source_id(), span)
                     .with_message("cannot import a module from within itself")]),
-            Self::ImportConflict { item, prev } => Diagnostic::error()
+            Self::ImportConflict { item, module, prev } => Diagnostic::error()
                 .with_message("conflicting import")
                 .with_labels(vec![Label::primary(item.span().source_id(), item.span())
-                    .with_message(format!("the item '{item}' is imported here")),
+                    .with_message(format!("the item '{item}' is imported from '{module}' here")),
                                   Label::secondary(prev.source_id(), prev)
                     .with_message("but it conflicts with an item of the same name here")]),
             Self::ImportFailed(span) => Diagnostic::error()
@@ -118,6 +118,7 @@ impl ImportResolver<'_> {
         match module.constants.get(&item) {
             Some(exists) => ControlFlow::Break(SemanticAnalysisError::ImportConflict {
                 item,
+                module: from,
                 prev: exists.name.span(),
             }),
             None => {
@@ -141,6 +142,7 @@ impl ImportResolver<'_> {
                             // Conflict is with another imported name, raise an error
                             ControlFlow::Break(SemanticAnalysisError::ImportConflict {
                                 item,
+                                module: from,
                                 prev: id.span(),
                             })
                         }
@@ -167,6 +169,7 @@ impl ImportResolver<'_> {
         match module.evaluators.get(&item) {
             Some(exists) => ControlFlow::Break(SemanticAnalysisError::ImportConflict {
                 item,
+                module: from,
                 prev: exists.name.span(),
             }),
             None => {
@@ -190,6 +193,7 @@ impl ImportResolver<'_> {
                             // Conflict is with another import, raise an error
                             ControlFlow::Break(SemanticAnalysisError::ImportConflict {
                                 item,
+                                module: from,
                                 prev: id.span(),
                             })
                         }
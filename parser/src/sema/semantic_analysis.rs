@@ -9,10 +9,15 @@ use miden_diagnostics::{DiagnosticsHandler, Severity, SourceSpan, Span, Spanned}
 use super::*;
 use crate::{
     ast::{visit::VisitMut, *},
+    error_codes::{BOUNDARY_CONSTRAINT_DOMAIN_MISMATCH, WithErrorCode},
     sema::SemanticAnalysisError,
     symbols::{self, Symbol},
 };
 
+/// The size of the sets above which a warning is emitted for calls to `in_set`, as the degree of
+/// the resulting constraint is the size of the set
+const MAX_IN_SET_SIZE: usize = 8;
+
 /// A helper enum for representing what constraint mode is active
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum ConstraintMode {
@@ -75,6 +80,9 @@ pub struct SemanticAnalysis<'a> {
     has_undefined_variables: bool,
     has_type_errors: bool,
     in_constraint_comprehension: bool,
+    /// Used to generate unique names for the bindings introduced when expanding constraints on
+    /// calls to functions returning vectors
+    next_generated_binding: usize,
 }
 impl<'a> SemanticAnalysis<'a> {
     /// Create a new instance of the semantic analyzer
@@ -100,6 +108,7 @@ impl<'a> SemanticAnalysis<'a> {
             has_undefined_variables: false,
             has_type_errors: false,
             in_constraint_comprehension: false,
+            next_generated_binding: 0,
         }
     }
 
@@ -139,6 +148,32 @@ impl<'a> SemanticAnalysis<'a> {
 }
 
 impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
+    fn visit_mut_statement_block(
+        &mut self,
+        block: &mut Vec<Statement>,
+    ) -> ControlFlow<SemanticAnalysisError> {
+        for i in 0..block.len() {
+            self.visit_mut_statement(&mut block[i])?;
+
+            // Constraints on calls to functions returning vectors are expanded once visited, the
+            // statements following them are visited as part of the expansion
+            if let Statement::Enforce(ScalarExpr::Call(Call {
+                ty: Some(Type::Vector(_)), ..
+            })) = &block[i]
+            {
+                let rest = block.split_off(i + 1);
+                let Some(Statement::Enforce(ScalarExpr::Call(call))) = block.pop() else {
+                    unreachable!()
+                };
+                let expanded = self.expand_enforced_vector_call(call, rest)?;
+                block.push(expanded);
+                break;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
     fn visit_mut_module(&mut self, module: &mut Module) -> ControlFlow<SemanticAnalysisError> {
         self.current_module = Some(module.name);
 
@@ -203,8 +238,8 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
         for constant in module.constants.values() {
             let namespaced_name = NamespacedIdentifier::Binding(constant.name);
             // See if a constant with the same name was previously imported
-            if let Some((prev, _)) = self.imported.get_key_value(&namespaced_name) {
-                self.declaration_import_conflict(constant.span(), prev.span())?;
+            if let Some((prev, from)) = self.imported.get_key_value(&namespaced_name) {
+                self.declaration_import_conflict(constant.span(), prev.span(), *from)?;
             }
             // It should be impossible for there to be a local by this name at this point
             assert_eq!(
@@ -218,8 +253,8 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
         // Functions are in their own namespace, but may conflict with imported items
         for (function_name, function) in module.evaluators.iter() {
             let namespaced_name = NamespacedIdentifier::Function(*function_name);
-            if let Some((prev, _)) = self.imported.get_key_value(&namespaced_name) {
-                self.declaration_import_conflict(namespaced_name.span(), prev.span())?;
+            if let Some((prev, from)) = self.imported.get_key_value(&namespaced_name) {
+                self.declaration_import_conflict(namespaced_name.span(), prev.span(), *from)?;
             }
             assert_eq!(
                 self.locals.insert(
@@ -232,8 +267,8 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
 
         for (function_name, function) in module.functions.iter() {
             let namespaced_name = NamespacedIdentifier::Function(*function_name);
-            if let Some((prev, _)) = self.imported.get_key_value(&namespaced_name) {
-                self.declaration_import_conflict(namespaced_name.span(), prev.span())?;
+            if let Some((prev, from)) = self.imported.get_key_value(&namespaced_name) {
+                self.declaration_import_conflict(namespaced_name.span(), prev.span(), *from)?;
             }
             assert_eq!(
                 self.locals.insert(
@@ -251,8 +286,8 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
         // Buses are in their own namespace, but may conflict with imported items
         for (bus_name, bus) in module.buses.iter() {
             let namespaced_name = NamespacedIdentifier::Binding(*bus_name);
-            if let Some((prev, _)) = self.imported.get_key_value(&namespaced_name) {
-                self.declaration_import_conflict(namespaced_name.span(), prev.span())?;
+            if let Some((prev, from)) = self.imported.get_key_value(&namespaced_name) {
+                self.declaration_import_conflict(namespaced_name.span(), prev.span(), *from)?;
             }
             assert_eq!(self.locals.insert(namespaced_name, BindingType::Bus(bus.bus_type)), None);
         }
@@ -310,6 +345,10 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
             }
         }
 
+        for column in module.virtual_columns.iter_mut() {
+            self.visit_mut_virtual_column(column)?;
+        }
+
         self.current_module = None;
 
         // We're done
@@ -486,6 +525,19 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
         let result = self.visit_mut_list_comprehension(expr);
         self.in_constraint_comprehension = false;
 
+        // All iterables have the same type, so the comprehension enforces nothing if the first
+        // one is empty, e.g. when its range is bounded by a constant set to zero
+        if result.is_continue() && expr.iterables[0].ty() == Some(Type::Vector(0)) {
+            self.diagnostics
+                .diagnostic(Severity::Note)
+                .with_message("empty constraint comprehension")
+                .with_primary_label(
+                    expr.iterables[0].span(),
+                    "this iterable is empty, so no constraints are enforced",
+                )
+                .emit();
+        }
+
         result
     }
 
@@ -534,6 +586,9 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
             self.visit_mut_expr(iterable)?;
         }
 
+        // Bind the accesses of the body which are indexed by the bindings, e.g. `m[i][0]`
+        self.bind_indexed_accesses(expr)?;
+
         // Start a new lexical scope
         self.locals.enter();
 
@@ -578,8 +633,12 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
             }
             match self.expr_binding_type(iterable) {
                 Ok(iterable_binding_ty) => {
+                    // The bindings of an empty iterable are never bound to a value, but they are
+                    // typed as the elements of the iterable so that the body can be checked
                     let binding_ty = iterable_binding_ty
                         .access(AccessType::Index(0))
+                        .ok()
+                        .or_else(|| iterable_binding_ty.empty_element())
                         .expect("unexpected scalar iterable");
                     binding_tys.push((binding, iterable.span(), Some(binding_ty)));
                 },
@@ -628,6 +687,52 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
             self.visit_mut_scalar_expr(selector)?;
         }
 
+        // Visit the multiplicity
+        if let Some(multiplicity) = expr.multiplicity.as_mut() {
+            self.visit_mut_scalar_expr(multiplicity)?;
+        }
+
+        // Validate the mask, and fold its complement into the selector, i.e. `when s unless k` is
+        // treated as `when s * (1 - k)`
+        if let Some(mut mask) = expr.mask.take() {
+            self.visit_mut_symbol_access(mask.as_mut())?;
+            match self.access_binding_type(&mask) {
+                Ok(BindingType::PeriodicColumn(_)) => (),
+                Ok(BindingType::Alias(aliased))
+                    if matches!(*aliased, BindingType::PeriodicColumn(_)) => {},
+                Ok(ty) => {
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid mask")
+                        .with_primary_label(
+                            mask.span(),
+                            format!("expected a periodic column here, but got {ty}"),
+                        )
+                        .with_note("Only periodic columns can be used as the mask of a constraint.")
+                        .emit();
+                    return ControlFlow::Break(SemanticAnalysisError::Invalid);
+                },
+                // The access was already validated when visiting it
+                Err(_) => return ControlFlow::Break(SemanticAnalysisError::Invalid),
+            }
+            let span = mask.span();
+            let complement = ScalarExpr::Binary(BinaryExpr::new(
+                span,
+                BinaryOp::Sub,
+                ScalarExpr::Const(Span::new(span, 1)),
+                ScalarExpr::SymbolAccess(*mask),
+            ));
+            expr.selector = Some(match expr.selector.take() {
+                Some(selector) => ScalarExpr::Binary(BinaryExpr::new(
+                    selector.span(),
+                    BinaryOp::Mul,
+                    selector,
+                    complement,
+                )),
+                None => complement,
+            });
+        }
+
         // Visit the comprehension body
         if self.in_constraint_comprehension {
             self.visit_mut_enforce(expr.body.as_mut())?;
@@ -701,8 +806,8 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
             self.visit_mut_expr(expr)?;
         }
 
-        // Validate arguments for builtin functions, which currently consist only of the sum/prod
-        // reducers
+        // Validate arguments for builtin functions, which currently consist of the sum/prod/combine
+        // reducers, and `in_set`
         if expr.is_builtin() {
             self.validate_call_to_builtin(expr)?;
         }
@@ -711,7 +816,7 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
         //
         // * Must be trace bindings or aliases of same
         // * Must match the type signature of the callee
-        if let Ok(ty) = callee_binding_ty {
+        if let Ok(ref ty) = callee_binding_ty {
             if let BindingType::Function(FunctionType::Evaluator(ref params)) = ty.item {
                 for (arg, param) in expr.args.iter().zip(params.iter()) {
                     self.validate_evaluator_argument(expr.span(), arg, param)?;
@@ -719,6 +824,33 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
             }
         }
 
+        // Validate arguments for pure functions, which must have the type of the parameter, e.g.
+        // an empty vector cannot be given for a parameter of type `felt[2]`. Builtins are validated
+        // above, as they accept vectors of any size
+        if !expr.is_builtin()
+            && let Ok(ref ty) = callee_binding_ty
+            && let BindingType::Function(FunctionType::Function(ref params, _)) = ty.item
+        {
+            for (arg, param) in expr.args.iter().zip(params.iter()) {
+                if let Some(arg_ty) = arg.ty()
+                    && arg_ty != *param
+                {
+                    self.has_type_errors = true;
+                    // Note: We don't break here but at the end of the module's compilation, as we
+                    // want to continue to gather as many errors as possible
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("type mismatch")
+                        .with_primary_label(arg.span(), format!("this argument has type {arg_ty}"))
+                        .with_secondary_label(
+                            expr.callee.span(),
+                            format!("but this function expects it to have type {param}"),
+                        )
+                        .emit();
+                }
+            }
+        }
+
         ControlFlow::Continue(())
     }
 
@@ -860,10 +992,46 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
         ControlFlow::Break(SemanticAnalysisError::Invalid)
     }
 
+    fn visit_mut_row_comprehension(
+        &mut self,
+        expr: &mut RowComprehension,
+    ) -> ControlFlow<SemanticAnalysisError> {
+        // Comprehensions over the rows of a table are only valid as bus boundaries, which are
+        // explicitly checked in the handling of `visit_mut_boundary_constraint`
+        self.diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid expression")
+            .with_primary_label(
+                expr.span(),
+                "comprehensions over the rows of a table are only permitted as bus boundaries",
+            )
+            .emit();
+        ControlFlow::Break(SemanticAnalysisError::Invalid)
+    }
+
     fn visit_mut_symbol_access(
         &mut self,
         expr: &mut SymbolAccess,
     ) -> ControlFlow<SemanticAnalysisError> {
+        // Accesses indexed by the bindings of a comprehension are rewritten when visiting it, so
+        // any remaining binding is not bound by an enclosing comprehension
+        if let AccessType::Bound(indices) = &expr.access_type {
+            let index = indices.iter().find_map(|index| match index {
+                AccessIndex::Binding(binding) => Some(*binding),
+                AccessIndex::Const(_) => None,
+            });
+            self.has_type_errors = true;
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("invalid index")
+                .with_primary_label(
+                    index.map_or(expr.span(), |index| index.span()),
+                    "indices must be constant integers, or bindings of an enclosing list comprehension",
+                )
+                .emit();
+            return ControlFlow::Break(SemanticAnalysisError::Invalid);
+        }
+
         self.visit_mut_resolvable_identifier(&mut expr.name)?;
         self.visit_mut_access_type(&mut expr.access_type)?;
 
@@ -883,15 +1051,16 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
         // * This is an invalid periodic column access in a boundary constraint
         // * This is an invalid public input access in an integrity constraint
         match &resolved_binding_ty.item {
-            BindingType::TraceColumn(_) | BindingType::TraceParam(_) => {
-                if self.constraint_mode.is_boundary() && expr.offset > 0 {
-                    self.has_type_errors = true;
-                    self.diagnostics.diagnostic(Severity::Error)
-                        .with_message("invalid expression")
-                        .with_primary_label(expr.span(), "invalid access of a trace column with offset")
-                        .with_note("It is not allowed to access trace columns with an offset in boundary constraints.")
-                        .emit();
-                }
+            BindingType::TraceColumn(_) | BindingType::TraceParam(_)
+                if self.constraint_mode.is_boundary() && expr.offset > 0 =>
+            {
+                self.has_type_errors = true;
+                let diagnostic = self.diagnostics.diagnostic(Severity::Error)
+                    .with_message("invalid expression")
+                    .with_primary_label(expr.span(), "invalid access of a trace column with offset")
+                    .with_note("It is not allowed to access trace columns with an offset in boundary constraints.")
+                    .with_code(BOUNDARY_CONSTRAINT_DOMAIN_MISMATCH);
+                self.diagnostics.emit(diagnostic);
             },
             ty @ BindingType::PeriodicColumn(_) if self.constraint_mode.is_boundary() => {
                 self.invalid_access_in_constraint(expr.span(), ty);
@@ -1044,6 +1213,23 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
                             )
                             .emit();
                     },
+                    NamespacedIdentifier::Binding(id)
+                        if matches!(id.name(), symbols::First | symbols::Last) =>
+                    {
+                        self.diagnostics
+                            .diagnostic(Severity::Error)
+                            .with_message("invalid use of builtin row selector")
+                            .with_primary_label(
+                                namespaced_id.span(),
+                                "row selectors are not supported in this position",
+                            )
+                            .with_note(
+                                "Row selectors may only gate a top-level integrity constraint, \
+                                 as in `enf $first * (a - 5) = 0`, `enf $last * (a - 5) = 0`, or \
+                                 `enf (1 - $last) * (a' - a) = 0`",
+                            )
+                            .emit();
+                    },
                     NamespacedIdentifier::Binding(_) => {
                         self.diagnostics
                             .diagnostic(Severity::Error)
@@ -1063,8 +1249,8 @@ impl VisitMut<SemanticAnalysisError> for SemanticAnalysis<'_> {
 }
 
 impl SemanticAnalysis<'_> {
-    /// Validate arguments for builtin functions, which currently consist only of the sum/prod
-    /// reducers
+    /// Validate arguments for builtin functions, which currently consist of the sum/prod/combine
+    /// reducers, and `in_set`
     fn validate_call_to_builtin(&mut self, call: &Call) -> ControlFlow<SemanticAnalysisError> {
         match call.callee.as_ref().name() {
             // The known reducers - each takes a single argument, which must be an aggregate or
@@ -1113,11 +1299,167 @@ impl SemanticAnalysis<'_> {
                     },
                 }
             },
+            // `in_set` takes a field element, and the vector of constants it must be one of
+            symbols::InSet => match call.args.as_slice() {
+                [value, set] => {
+                    if let Ok(binding_ty) = self.expr_binding_type(value)
+                        && binding_ty.ty() != Some(Type::Felt)
+                    {
+                        self.has_type_errors = true;
+                        self.diagnostics
+                            .diagnostic(Severity::Error)
+                            .with_message("invalid call")
+                            .with_primary_label(
+                                call.span(),
+                                "this function expects a field element as its first argument",
+                            )
+                            .with_secondary_label(value.span(), "but this argument is not")
+                            .emit();
+                    }
+                    match self.expr_binding_type(set).map(|binding_ty| binding_ty.ty()) {
+                        Ok(Some(Type::Vector(len))) if len > MAX_IN_SET_SIZE => {
+                            self.diagnostics
+                                .diagnostic(Severity::Warning)
+                                .with_message("constraint of high degree")
+                                .with_primary_label(
+                                    call.span(),
+                                    format!(
+                                        "this constraint has degree {len}, the size of the set"
+                                    ),
+                                )
+                                .with_note(
+                                    "Consider decomposing the value into smaller parts, e.g. \
+                                     binary columns, to lower the degree of the constraint.",
+                                )
+                                .emit();
+                        },
+                        Ok(Some(Type::Vector(len))) if len > 0 => (),
+                        Ok(_) => {
+                            self.has_type_errors = true;
+                            self.diagnostics
+                                .diagnostic(Severity::Error)
+                                .with_message("invalid call")
+                                .with_primary_label(
+                                    call.span(),
+                                    "this function expects a non-empty vector of constants as \
+                                     its second argument",
+                                )
+                                .with_secondary_label(set.span(), "but this argument is not")
+                                .emit();
+                        },
+                        Err(_) => {
+                            // We've already raised a diagnostic for this when visiting the access
+                            // expression
+                            assert!(self.has_undefined_variables || self.has_type_errors);
+                        },
+                    }
+                },
+                _ => {
+                    self.has_type_errors = true;
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid call")
+                        .with_primary_label(
+                            call.span(),
+                            format!(
+                                "the callee expects two arguments, but got {}",
+                                call.args.len()
+                            ),
+                        )
+                        .emit();
+                },
+            },
+            // `combine(t, alphas)` takes a tuple `t` of `n` elements, and a vector of at least
+            // `n + 1` coefficients
+            symbols::Combine => match call.args.as_slice() {
+                [tuple, alphas] => {
+                    let tuple_len = self.combine_argument_len(call, tuple);
+                    let alphas_len = self.combine_argument_len(call, alphas);
+                    if let (Some(tuple_len), Some(alphas_len)) = (tuple_len, alphas_len)
+                        && alphas_len <= tuple_len
+                    {
+                        self.has_type_errors = true;
+                        self.diagnostics
+                            .diagnostic(Severity::Error)
+                            .with_message("invalid call")
+                            .with_primary_label(
+                                alphas.span(),
+                                format!(
+                                    "expected at least {} coefficients, but got {alphas_len}",
+                                    tuple_len + 1
+                                ),
+                            )
+                            .with_secondary_label(
+                                tuple.span(),
+                                format!("this tuple has {tuple_len} elements"),
+                            )
+                            .with_note(
+                                "`combine(t, alphas)` evaluates to `alphas[0] + t[0] * \
+                                 alphas[1] + .. + t[n-1] * alphas[n]` for a tuple `t` of `n` \
+                                 elements",
+                            )
+                            .emit();
+                    }
+                },
+                _ => {
+                    self.has_type_errors = true;
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid call")
+                        .with_primary_label(
+                            call.span(),
+                            format!(
+                                "the callee expects two arguments, but got {}",
+                                call.args.len()
+                            ),
+                        )
+                        .emit();
+                },
+            },
             other => unimplemented!("unrecognized builtin function: {}", other),
         }
         ControlFlow::Continue(())
     }
 
+    /// Returns the number of elements of an argument to `combine`, or `None` after raising an
+    /// error if it is not a vector literal, or a reference to a vector.
+    ///
+    /// Calls to `combine` are expanded element-wise during constant propagation, so the argument
+    /// must be one whose elements can be accessed individually.
+    fn combine_argument_len(&mut self, call: &Call, arg: &Expr) -> Option<usize> {
+        let is_expandable = match arg {
+            Expr::Const(_) | Expr::SymbolAccess(_) => true,
+            Expr::Vector(elems) => elems.iter().all(|elem| elem.ty() == Some(Type::Felt)),
+            _ => false,
+        };
+        match self.expr_binding_type(arg) {
+            Ok(binding_ty) => match binding_ty.ty() {
+                Some(Type::Vector(len)) if is_expandable => Some(len),
+                _ => {
+                    self.has_type_errors = true;
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid call")
+                        .with_primary_label(
+                            call.span(),
+                            "this function expects vectors of field elements as arguments",
+                        )
+                        .with_secondary_label(
+                            arg.span(),
+                            "but this argument is not a vector literal, or a reference to a vector",
+                        )
+                        .emit();
+                    None
+                },
+            },
+            Err(_) => {
+                // We've already raised a diagnostic for this when visiting the access expression
+                assert!(self.has_undefined_variables || self.has_type_errors);
+                None
+            },
+        }
+    }
+
     fn validate_evaluator_argument(
         &mut self,
         span: SourceSpan,
@@ -1390,6 +1732,14 @@ impl SemanticAnalysis<'_> {
                                 },
                             };
 
+                        // A bus boundary bound to the rows of a table is resolved to an access of
+                        // the table
+                        if let (BindingType::Bus(_), ScalarExpr::RowComprehension(rows)) =
+                            (&found.item, expr.rhs.as_ref())
+                        {
+                            *expr.rhs = self.resolve_row_comprehension(rows)?;
+                        }
+
                         match (found.clone().item, expr.rhs.as_mut()) {
                             // Buses boundaries can be constrained by null or set to be
                             // unconstrained
@@ -1548,9 +1898,15 @@ impl SemanticAnalysis<'_> {
         //
         match expr {
             ScalarExpr::Binary(expr) if expr.op == BinaryOp::Eq => self.visit_mut_binary_expr(expr),
-            ScalarExpr::Call(expr) => {
+            ScalarExpr::Call(call) => {
                 // Visit the call normally, so we can resolve the callee identifier
-                self.visit_mut_call(expr)?;
+                self.visit_mut_call(call)?;
+
+                // A call to a function producing a value enforces that value to be zero
+                if let Some(ty) = call.ty {
+                    return self.visit_mut_enforced_function_call(expr, ty);
+                }
+                let expr = call;
 
                 // Check that the call references an evaluator
                 //
@@ -1659,10 +2015,11 @@ impl SemanticAnalysis<'_> {
         &self,
         decl: SourceSpan,
         import: SourceSpan,
+        from: ModuleId,
     ) -> ControlFlow<SemanticAnalysisError> {
         self.diagnostics
             .diagnostic(Severity::Error)
-            .with_message("declaration conflicts with an imported item")
+            .with_message(format!("declaration conflicts with an item imported from '{from}'"))
             .with_primary_label(decl, "this name is already in use")
             .with_secondary_label(import, "it was declared via this import")
             .emit();
@@ -1678,6 +2035,87 @@ impl SemanticAnalysis<'_> {
             .emit();
     }
 
+    /// Validates a constraint of the form `enf f(x)`, where `f` is a function producing a value of
+    /// type `ty`, which enforces that value to be zero.
+    ///
+    /// If `f` returns a scalar, the constraint is rewritten as `enf f(x) = 0`. If it returns a
+    /// vector, the constraint is left as is, and expanded into one constraint per element by
+    /// `visit_mut_statement_block`.
+    fn visit_mut_enforced_function_call(
+        &mut self,
+        expr: &mut ScalarExpr,
+        ty: Type,
+    ) -> ControlFlow<SemanticAnalysisError> {
+        let span = expr.span();
+        match ty {
+            Type::Felt => {
+                let call = mem::replace(expr, ScalarExpr::Const(Span::new(span, 0)));
+                *expr = ScalarExpr::Binary(BinaryExpr::new(
+                    span,
+                    BinaryOp::Eq,
+                    call,
+                    ScalarExpr::Const(Span::new(span, 0)),
+                ));
+                ControlFlow::Continue(())
+            },
+            Type::Vector(_) if !self.in_constraint_comprehension => ControlFlow::Continue(()),
+            Type::Vector(_) => {
+                self.invalid_constraint(span, "this function returns a vector")
+                    .with_note("Only calls to functions returning a felt can be enforced in a comprehension or with a selector, as each of their elements would otherwise be a constraint of its own.")
+                    .emit();
+                ControlFlow::Break(SemanticAnalysisError::Invalid)
+            },
+            Type::Matrix(..) => {
+                self.invalid_constraint(span, "this function returns a matrix")
+                    .with_note("Only calls to functions returning a felt or a vector can be enforced, e.g. `enf f(x)` where `f` returns a felt is equivalent to `enf f(x) = 0`.")
+                    .emit();
+                ControlFlow::Break(SemanticAnalysisError::Invalid)
+            },
+        }
+    }
+
+    /// Expands a constraint `enf f(x)` where `f` returns a vector into one constraint per element
+    /// of that vector, i.e. `let %r = f(x); enf %e = 0 for %e in %r`, followed by `rest`, the
+    /// statements which followed the constraint in its block.
+    ///
+    /// The call has already been visited, but the expanded statements have not.
+    fn expand_enforced_vector_call(
+        &mut self,
+        call: Call,
+        rest: Vec<Statement>,
+    ) -> ControlFlow<SemanticAnalysisError, Statement> {
+        let span = call.span();
+        let ty = call.ty.unwrap();
+        let mut generated_binding = || {
+            let name = format!("%{}", self.next_generated_binding);
+            self.next_generated_binding += 1;
+            Identifier::new(span, Symbol::intern(name))
+        };
+        let result = generated_binding();
+        let element = generated_binding();
+
+        let constraint = ScalarExpr::Binary(BinaryExpr::new(
+            span,
+            BinaryOp::Eq,
+            ScalarExpr::SymbolAccess(SymbolAccess::new(span, element, AccessType::Default, 0)),
+            ScalarExpr::Const(Span::new(span, 0)),
+        ));
+        let iterable = Expr::SymbolAccess(SymbolAccess::new(span, result, AccessType::Default, 0));
+        let constraints = ListComprehension::new(span, constraint, vec![(element, iterable)], None);
+
+        let mut body = vec![Statement::EnforceAll(constraints)];
+        body.extend(rest);
+        let mut expr = Let::new(span, result, Expr::Call(call), body);
+
+        self.locals.enter();
+        self.locals
+            .insert(NamespacedIdentifier::Binding(result), BindingType::Local(ty));
+        self.visit_mut_statement_block(&mut expr.body)?;
+        self.locals.exit();
+
+        ControlFlow::Continue(Statement::Let(expr))
+    }
+
     fn invalid_callee(
         &self,
         span: SourceSpan,
@@ -1693,6 +2131,239 @@ impl SemanticAnalysis<'_> {
         ControlFlow::Break(SemanticAnalysisError::Invalid)
     }
 
+    /// Resolves a comprehension over the rows of a public input table to an access of the table.
+    ///
+    /// Bus boundaries are bound to entire rows, so the comprehension must produce the columns of
+    /// each row in order, either as `[(a, b) for (a, b) in table]` by destructuring the row, or as
+    /// `[(row[0], row[1]) for row in table]`.
+    fn resolve_row_comprehension(
+        &mut self,
+        rows: &RowComprehension,
+    ) -> ControlFlow<SemanticAnalysisError, ScalarExpr> {
+        let (declaration, num_cols) = match self.program.public_inputs.get(&rows.table) {
+            Some(PublicInput::Table { span, size, .. }) => (*span, *size),
+            _ => {
+                self.has_type_errors = true;
+                self.invalid_constraint(rows.table.span(), "expected a public input table")
+                    .with_note("Only the rows of a public input table can be iterated over in a bus boundary.")
+                    .emit();
+                return ControlFlow::Break(SemanticAnalysisError::Invalid);
+            },
+        };
+
+        let bindings = &rows.bindings;
+        let mut names = HashSet::new();
+        if let Some(duplicate) = bindings.iter().find(|binding| !names.insert(**binding)) {
+            self.has_type_errors = true;
+            self.invalid_constraint(duplicate.span(), "this name is bound more than once")
+                .emit();
+            return ControlFlow::Break(SemanticAnalysisError::Invalid);
+        }
+
+        // The accesses producing each column of the row, in order
+        let columns: Vec<_> = match bindings.len() {
+            1 => (0..num_cols).map(|idx| (bindings[0], AccessType::Index(idx))).collect(),
+            n if n == num_cols => {
+                bindings.iter().map(|binding| (*binding, AccessType::Default)).collect()
+            },
+            n => {
+                self.has_type_errors = true;
+                self.invalid_constraint(
+                    bindings.span(),
+                    format!("this destructures rows into {n} columns"),
+                )
+                .with_secondary_label(
+                    declaration,
+                    format!("but `{}` is declared with {num_cols} columns here", rows.table),
+                )
+                .emit();
+                return ControlFlow::Break(SemanticAnalysisError::Invalid);
+            },
+        };
+
+        let is_column = |expr: &ScalarExpr, (binding, access_type): &(Identifier, AccessType)| {
+            matches!(expr, ScalarExpr::SymbolAccess(access)
+                if access.offset == 0
+                    && access.access_type == *access_type
+                    && access.name == ResolvableIdentifier::Unresolved(NamespacedIdentifier::Binding(*binding)))
+        };
+        if rows.row.len() != columns.len()
+            || !rows
+                .row
+                .iter()
+                .zip(columns.iter())
+                .all(|(expr, column)| is_column(expr, column))
+        {
+            self.has_type_errors = true;
+            self.invalid_constraint(
+                rows.row.span(),
+                format!("expected the columns of `{}` in order", rows.table),
+            )
+            .with_note("Bus boundaries are bound to entire rows of a public input table, so each row must list all of its columns in declaration order.")
+            .emit();
+            return ControlFlow::Break(SemanticAnalysisError::Invalid);
+        }
+
+        ControlFlow::Continue(ScalarExpr::SymbolAccess(SymbolAccess::new(
+            rows.table.span(),
+            rows.table,
+            AccessType::Default,
+            0,
+        )))
+    }
+
+    /// Rewrites the accesses of a comprehension which are indexed by its bindings into bindings of
+    /// new iterables, which hold the accessed value at each iteration.
+    ///
+    /// For example, `enf m[i][0] = 0 for i in 0..2` is rewritten into
+    /// `enf %m[i][0] = 0 for (i, %m[i][0]) in (0..2, [m[0][0], m[1][0]])`, so that the
+    /// comprehension is expanded like any other. The bindings used as indices must be bound to a
+    /// range.
+    fn bind_indexed_accesses(
+        &mut self,
+        expr: &mut ListComprehension,
+    ) -> ControlFlow<SemanticAnalysisError> {
+        let mut accesses = IndexedAccesses {
+            bindings: expr.bindings.iter().copied().collect(),
+            accesses: vec![],
+        };
+        accesses.visit_mut_scalar_expr(expr.body.as_mut())?;
+        if let Some(selector) = expr.selector.as_mut() {
+            accesses.visit_mut_scalar_expr(selector)?;
+        }
+        if let Some(multiplicity) = expr.multiplicity.as_mut() {
+            accesses.visit_mut_scalar_expr(multiplicity)?;
+        }
+
+        for (binding, access) in accesses.accesses {
+            let AccessType::Bound(indices) = &access.access_type else {
+                unreachable!()
+            };
+
+            // The value of each index at each iteration of the comprehension
+            let mut values = vec![];
+            for index in indices {
+                match index {
+                    AccessIndex::Const(_) => values.push(None),
+                    AccessIndex::Binding(index) => {
+                        let i = expr.bindings.iter().position(|binding| binding == index).unwrap();
+                        let Expr::Range(range) = &expr.iterables[i] else {
+                            self.has_type_errors = true;
+                            self.diagnostics
+                                .diagnostic(Severity::Error)
+                                .with_message("invalid index")
+                                .with_primary_label(
+                                    index.span(),
+                                    "indices must be bound to a range",
+                                )
+                                .with_secondary_label(expr.iterables[i].span(), "bound here")
+                                .emit();
+                            return ControlFlow::Break(SemanticAnalysisError::Invalid);
+                        };
+                        values.push(Some(range.to_slice_range()));
+                    },
+                }
+            }
+            let num_iterations =
+                values.iter().flatten().map(ExactSizeIterator::len).min().unwrap_or_default();
+
+            let mut name = access.name;
+            self.visit_mut_resolvable_identifier(&mut name)?;
+            let indexed_ty = self.resolvable_binding_type(&name).ok();
+
+            let mut elements = Vec::with_capacity(num_iterations);
+            for iteration in 0..num_iterations {
+                let resolved: Vec<usize> = indices
+                    .iter()
+                    .zip(values.iter())
+                    .map(|(index, values)| match (index, values) {
+                        (AccessIndex::Const(index), _) => *index,
+                        (AccessIndex::Binding(_), values) => {
+                            values.as_ref().unwrap().start + iteration
+                        },
+                    })
+                    .collect();
+                let access_type = match resolved.as_slice() {
+                    [index] => AccessType::Index(*index),
+                    [row, col] => AccessType::Matrix(*row, *col),
+                    _ => unreachable!(),
+                };
+
+                if let Some(ty) = indexed_ty.as_ref()
+                    && let Err(InvalidAccessError::IndexOutOfBounds) =
+                        ty.item.access(access_type.clone())
+                {
+                    let element =
+                        SymbolAccess::new(access.span(), access.name.into(), access_type, 0);
+                    self.has_type_errors = true;
+                    self.diagnostics
+                        .diagnostic(Severity::Error)
+                        .with_message("invalid access")
+                        .with_primary_label(
+                            expr.span(),
+                            format!("at iteration {iteration} of this comprehension, `{access}` accesses `{element}`, which is out of bounds"),
+                        )
+                        .with_secondary_label(ty.span(), "references this declaration")
+                        .emit();
+                    return ControlFlow::Break(SemanticAnalysisError::Invalid);
+                }
+
+                elements.push(Expr::SymbolAccess(SymbolAccess {
+                    name,
+                    ..SymbolAccess::new(access.span(), access.name.into(), access_type, 0)
+                }));
+            }
+
+            let mut iterable = Expr::Vector(Span::new(access.span(), elements));
+            self.visit_mut_expr(&mut iterable)?;
+            expr.bindings.push(binding);
+            expr.iterables.push(iterable);
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Visits the definition of a virtual column, which is not a constraint, and is lowered without
+    /// inlining, so it may only consist of arithmetic on scalar values.
+    fn visit_mut_virtual_column(
+        &mut self,
+        column: &mut VirtualColumn,
+    ) -> ControlFlow<SemanticAnalysisError> {
+        self.visit_mut_scalar_expr(&mut column.definition)?;
+        self.validate_virtual_column_definition(&column.definition)
+    }
+
+    fn validate_virtual_column_definition(
+        &mut self,
+        expr: &ScalarExpr,
+    ) -> ControlFlow<SemanticAnalysisError> {
+        let label = match expr {
+            ScalarExpr::Const(_) => return ControlFlow::Continue(()),
+            ScalarExpr::Binary(expr) if expr.op != BinaryOp::Eq => {
+                self.validate_virtual_column_definition(&expr.lhs)?;
+                return self.validate_virtual_column_definition(&expr.rhs);
+            },
+            ScalarExpr::SymbolAccess(access) => match self.access_binding_type(access) {
+                // Undefined variables and invalid accesses have already been reported
+                Err(_) => return ControlFlow::Continue(()),
+                Ok(BindingType::Bus(_)) => "buses cannot be accessed here".to_string(),
+                Ok(binding_ty) if binding_ty.ty() != Some(Type::Felt) => match binding_ty.ty() {
+                    Some(ty) => format!("expected a scalar value here, but got {ty}"),
+                    None => "expected a scalar value here".to_string(),
+                },
+                Ok(_) => return ControlFlow::Continue(()),
+            },
+            _ => "only arithmetic on scalar values is supported here".to_string(),
+        };
+        self.has_type_errors = true;
+        self.diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid virtual column definition")
+            .with_primary_label(expr.span(), label)
+            .emit();
+        ControlFlow::Continue(())
+    }
+
     fn invalid_constraint(
         &self,
         span: SourceSpan,
@@ -1872,6 +2543,20 @@ impl SemanticAnalysis<'_> {
                         FunctionType::Function(vec![Type::Vector(usize::MAX)], Type::Felt);
                     Ok(Span::new(qid.span(), BindingType::Function(folder_ty)))
                 },
+                symbols::InSet => {
+                    let in_set_ty = FunctionType::Function(
+                        vec![Type::Felt, Type::Vector(usize::MAX)],
+                        Type::Felt,
+                    );
+                    Ok(Span::new(qid.span(), BindingType::Function(in_set_ty)))
+                },
+                symbols::Combine => {
+                    let combine_ty = FunctionType::Function(
+                        vec![Type::Vector(usize::MAX), Type::Vector(usize::MAX)],
+                        Type::Felt,
+                    );
+                    Ok(Span::new(qid.span(), BindingType::Function(combine_ty)))
+                },
                 name => unimplemented!("unsupported builtin: {}", name),
             }
         } else {
@@ -1896,6 +2581,69 @@ impl SemanticAnalysis<'_> {
     }
 }
 
+/// Collects the accesses indexed by the bindings of a list comprehension, e.g. `m[i][0]` in
+/// `enf m[i][0] = 0 for i in 0..2`, and replaces them with accesses of a new binding per distinct
+/// access, see [SemanticAnalysis::bind_indexed_accesses].
+struct IndexedAccesses {
+    /// The bindings of the comprehension which are in scope
+    bindings: HashSet<Identifier>,
+    /// The new bindings, and the accesses they are bound to, without their row offset
+    accesses: Vec<(Identifier, SymbolAccess)>,
+}
+impl VisitMut<SemanticAnalysisError> for IndexedAccesses {
+    fn visit_mut_list_comprehension(
+        &mut self,
+        expr: &mut ListComprehension,
+    ) -> ControlFlow<SemanticAnalysisError> {
+        for iterable in expr.iterables.iter_mut() {
+            self.visit_mut_expr(iterable)?;
+        }
+
+        // Bindings shadowed by a nested comprehension are not in scope in its body
+        let shadowed: Vec<_> = expr
+            .bindings
+            .iter()
+            .filter(|binding| self.bindings.remove(*binding))
+            .copied()
+            .collect();
+        self.visit_mut_scalar_expr(expr.body.as_mut())?;
+        if let Some(selector) = expr.selector.as_mut() {
+            self.visit_mut_scalar_expr(selector)?;
+        }
+        if let Some(multiplicity) = expr.multiplicity.as_mut() {
+            self.visit_mut_scalar_expr(multiplicity)?;
+        }
+        self.bindings.extend(shadowed);
+
+        ControlFlow::Continue(())
+    }
+
+    fn visit_mut_symbol_access(
+        &mut self,
+        expr: &mut SymbolAccess,
+    ) -> ControlFlow<SemanticAnalysisError> {
+        let AccessType::Bound(indices) = &expr.access_type else {
+            return ControlFlow::Continue(());
+        };
+        let is_bound = |index: &AccessIndex| match index {
+            AccessIndex::Const(_) => true,
+            AccessIndex::Binding(binding) => self.bindings.contains(binding),
+        };
+        if !indices.iter().all(is_bound) {
+            return ControlFlow::Continue(());
+        }
+
+        let access = SymbolAccess { offset: 0, ..expr.clone() };
+        let name = Identifier::new(expr.span(), Symbol::intern(format!("%{access}")));
+        if !self.accesses.iter().any(|(binding, _)| *binding == name) {
+            self.accesses.push((name, access));
+        }
+        *expr = SymbolAccess::new(expr.span(), name, AccessType::Default, expr.offset);
+
+        ControlFlow::Continue(())
+    }
+}
+
 fn segment_id_to_name(id: TraceSegmentId) -> Symbol {
     match id {
         0 => symbols::Main,
@@ -17,9 +17,34 @@ pub mod predefined {
     pub const Sum: Symbol = Symbol::new(2);
     /// The symbol `prod`
     pub const Prod: Symbol = Symbol::new(3);
-
-    pub(super) const __SYMBOLS: &[(Symbol, &str)] =
-        &[(Main, "$main"), (Builtin, "$builtin"), (Sum, "sum"), (Prod, "prod")];
+    /// The symbol `$aux`
+    pub const Aux: Symbol = Symbol::new(4);
+    /// The symbol `$first`
+    pub const First: Symbol = Symbol::new(5);
+    /// The symbol `$last`
+    pub const Last: Symbol = Symbol::new(6);
+    /// The symbol `in_set`
+    pub const InSet: Symbol = Symbol::new(7);
+    /// The symbol `running_product`
+    pub const RunningProduct: Symbol = Symbol::new(8);
+    /// The symbol `combine`
+    pub const Combine: Symbol = Symbol::new(9);
+    /// The symbol `permutation`
+    pub const Permutation: Symbol = Symbol::new(10);
+
+    pub(super) const __SYMBOLS: &[(Symbol, &str)] = &[
+        (Main, "$main"),
+        (Builtin, "$builtin"),
+        (Sum, "sum"),
+        (Prod, "prod"),
+        (Aux, "$aux"),
+        (First, "$first"),
+        (Last, "$last"),
+        (InSet, "in_set"),
+        (RunningProduct, "running_product"),
+        (Combine, "combine"),
+        (Permutation, "permutation"),
+    ];
 }
 
 pub use self::predefined::*;
@@ -83,6 +108,8 @@ impl PartialOrd for Symbol {
         Some(self.cmp(other))
     }
 }
+/// Symbols are ordered by their string, rather than by the order in which they were interned, which
+/// depends on everything compiled before in the same process.
 impl Ord for Symbol {
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.as_str().cmp(other.as_str())
@@ -0,0 +1,565 @@
+use std::{collections::BTreeMap, mem};
+
+use miden_diagnostics::{DiagnosticsHandler, Severity, SourceSpan, Span, Spanned};
+
+use super::constant_propagation::try_fold_binary_expr;
+use crate::{
+    ast::*,
+    sema::{LexicalScope, SemanticAnalysisError},
+};
+
+/// The maximum number of nested function calls when evaluating a constant
+const MAX_CALL_DEPTH: usize = 64;
+/// The maximum number of comprehension iterations when evaluating the constants of a module
+const MAX_ITERATIONS: usize = 1 << 16;
+
+/// This evaluates the [ComputedConstant]s of a [Module] to literal [Constant]s, by interpreting
+/// the calls to pure functions and the comprehensions of their initializers.
+///
+/// It is run when the module is loaded into a [Program], before its imports are resolved, as
+/// semantic analysis needs the types and values of all constants, e.g. to check the bounds of
+/// ranges. The modules it imports have already been loaded, so their constants are literals.
+///
+/// The functions called may only refer to their parameters, their `let` bindings and constants.
+/// Any other value, e.g. a trace column, is not known at compile time, and is reported as an
+/// error, as are the evaluations which exceed [MAX_CALL_DEPTH] or [MAX_ITERATIONS].
+pub(crate) struct ConstantEvaluation<'a> {
+    diagnostics: &'a DiagnosticsHandler,
+    library: &'a Library,
+    module: &'a Module,
+    /// The values of the computed constants evaluated so far
+    values: BTreeMap<Identifier, ConstantExpr>,
+    /// The computed constants being evaluated, innermost last, used to detect cycles
+    pending: Vec<Identifier>,
+    /// The module of the function being evaluated, in which its names are resolved
+    current: ModuleId,
+    locals: LexicalScope<Identifier, ConstantExpr>,
+    depth: usize,
+    iterations: usize,
+}
+impl<'a> ConstantEvaluation<'a> {
+    pub fn new(
+        diagnostics: &'a DiagnosticsHandler,
+        library: &'a Library,
+        module: &'a Module,
+    ) -> Self {
+        Self {
+            diagnostics,
+            library,
+            module,
+            values: Default::default(),
+            pending: vec![],
+            current: module.name,
+            locals: Default::default(),
+            depth: 0,
+            iterations: 0,
+        }
+    }
+
+    /// Evaluates the computed constants of the module, returning them as literal constants
+    pub fn run(mut self) -> Result<Vec<Constant>, SemanticAnalysisError> {
+        let module = self.module;
+        for name in module.computed_constants.keys() {
+            self.eval_constant(*name)?;
+        }
+
+        Ok(self
+            .values
+            .into_iter()
+            .map(|(name, value)| {
+                Constant::new(module.computed_constants[&name].span(), name, value)
+            })
+            .collect())
+    }
+
+    fn eval_constant(&mut self, name: Identifier) -> Result<ConstantExpr, SemanticAnalysisError> {
+        if let Some(value) = self.values.get(&name) {
+            return Ok(value.clone());
+        }
+
+        let module = self.module;
+        let constant = &module.computed_constants[&name];
+        if self.pending.contains(&name) {
+            self.diagnostics
+                .diagnostic(Severity::Error)
+                .with_message("invalid constant declaration")
+                .with_primary_label(
+                    constant.name.span(),
+                    "the value of this constant depends on itself",
+                )
+                .emit();
+            return Err(SemanticAnalysisError::Invalid);
+        }
+
+        // The initializer of a constant is evaluated in the module it is declared in, and does not
+        // see the bindings of the function referring to it, if any
+        self.pending.push(name);
+        let current = mem::replace(&mut self.current, module.name);
+        let locals = mem::take(&mut self.locals);
+        let value = self.eval_expr(&constant.initializer);
+        self.locals = locals;
+        self.current = current;
+        self.pending.pop();
+        let value = value?;
+
+        if let ConstantExpr::Matrix(rows) = &value {
+            if rows.iter().any(|row| row.len() != rows[0].len()) {
+                return Err(self.error(
+                    constant.initializer.span(),
+                    "invalid matrix constant: the rows of this matrix have different lengths",
+                ));
+            }
+        }
+        self.values.insert(name, value.clone());
+        Ok(value)
+    }
+
+    fn eval_expr(&mut self, expr: &Expr) -> Result<ConstantExpr, SemanticAnalysisError> {
+        match expr {
+            Expr::Const(value) => Ok(value.item.clone()),
+            Expr::Range(range) => {
+                let (start, end) = self.eval_range(range)?;
+                Ok(ConstantExpr::Vector((start..end).map(|i| i as u64).collect()))
+            },
+            Expr::Vector(elems) => {
+                let values =
+                    elems.iter().map(|elem| self.eval_expr(elem)).collect::<Result<Vec<_>, _>>()?;
+                self.aggregate(elems.span(), values)
+            },
+            Expr::Matrix(rows) => rows
+                .iter()
+                .map(|row| {
+                    row.iter().map(|elem| self.eval_scalar(elem)).collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<_, _>>()
+                .map(ConstantExpr::Matrix),
+            Expr::SymbolAccess(access) => self.eval_access(access),
+            Expr::Binary(expr) => self.eval_binary(expr).map(ConstantExpr::Scalar),
+            Expr::Call(call) => self.eval_call(call),
+            Expr::ListComprehension(lc) => self.eval_comprehension(lc),
+            Expr::Let(expr) => self.eval_let(expr),
+            expr => Err(self.non_constant(expr.span())),
+        }
+    }
+
+    fn eval_scalar(&mut self, expr: &ScalarExpr) -> Result<u64, SemanticAnalysisError> {
+        let value = match expr {
+            ScalarExpr::Const(value) => return Ok(value.item),
+            ScalarExpr::Binary(expr) => return self.eval_binary(expr),
+            ScalarExpr::SymbolAccess(access) => self.eval_access(access)?,
+            ScalarExpr::Call(call) => self.eval_call(call)?,
+            ScalarExpr::Let(expr) => self.eval_let(expr)?,
+            expr => return Err(self.non_constant(expr.span())),
+        };
+        match value {
+            ConstantExpr::Scalar(value) => Ok(value),
+            value => Err(self.error(
+                expr.span(),
+                format!("expected a value of type felt, got a value of type {}", value.ty()),
+            )),
+        }
+    }
+
+    fn eval_binary(&mut self, expr: &BinaryExpr) -> Result<u64, SemanticAnalysisError> {
+        if expr.op == BinaryOp::Eq {
+            return Err(self.error(expr.span(), "constraints cannot be evaluated at compile time"));
+        }
+        let lhs = self.eval_scalar(&expr.lhs)?;
+        let rhs = self.eval_scalar(&expr.rhs)?;
+        self.fold(
+            expr.span(),
+            expr.op,
+            Span::new(expr.lhs.span(), lhs),
+            Span::new(expr.rhs.span(), rhs),
+        )
+    }
+
+    /// Applies `op` to constant operands, using the folding of [super::ConstantPropagation]
+    fn fold(
+        &self,
+        span: SourceSpan,
+        op: BinaryOp,
+        lhs: Span<u64>,
+        rhs: Span<u64>,
+    ) -> Result<u64, SemanticAnalysisError> {
+        let expr = BinaryExpr::new(span, op, ScalarExpr::Const(lhs), ScalarExpr::Const(rhs));
+        match try_fold_binary_expr(&expr) {
+            Ok(Some(value)) => Ok(value.item),
+            Ok(None) => unreachable!("constant operands of {op} are always folded"),
+            Err(err) => Err(SemanticAnalysisError::InvalidExpr(err)),
+        }
+    }
+
+    fn eval_access(
+        &mut self,
+        access: &SymbolAccess,
+    ) -> Result<ConstantExpr, SemanticAnalysisError> {
+        if access.offset > 0 {
+            return Err(self.non_constant(access.span()));
+        }
+        let value = self.lookup(access.name)?.ok_or_else(|| self.non_constant(access.span()))?;
+        let ty = value.ty();
+        let value = match (&access.access_type, value) {
+            (AccessType::Default, value) => Some(value),
+            (AccessType::Index(idx), ConstantExpr::Vector(elems)) => {
+                elems.get(*idx).copied().map(ConstantExpr::Scalar)
+            },
+            (AccessType::Index(idx), ConstantExpr::Matrix(rows)) => {
+                rows.get(*idx).cloned().map(ConstantExpr::Vector)
+            },
+            (AccessType::Matrix(row, col), ConstantExpr::Matrix(rows)) => {
+                rows.get(*row).and_then(|row| row.get(*col)).copied().map(ConstantExpr::Scalar)
+            },
+            (AccessType::Slice(range), ConstantExpr::Vector(elems)) => {
+                let (start, end) = self.eval_range(range)?;
+                elems.get(start..end).map(|elems| ConstantExpr::Vector(elems.to_vec()))
+            },
+            (AccessType::Slice(range), ConstantExpr::Matrix(rows)) => {
+                let (start, end) = self.eval_range(range)?;
+                rows.get(start..end)
+                    .filter(|rows| !rows.is_empty())
+                    .map(|rows| ConstantExpr::Matrix(rows.to_vec()))
+            },
+            _ => None,
+        };
+        value.ok_or_else(|| {
+            self.error(
+                access.span(),
+                format!("invalid {} of a value of type {ty}", access.access_type),
+            )
+        })
+    }
+
+    /// Returns the value of the constant or local binding `name`, if there is one
+    fn lookup(
+        &mut self,
+        name: ResolvableIdentifier,
+    ) -> Result<Option<ConstantExpr>, SemanticAnalysisError> {
+        let id = name.namespaced().id();
+        if name.module().is_none() {
+            if let Some(value) = self.locals.get(&id) {
+                return Ok(Some(value.clone()));
+            }
+        }
+
+        let module = self.module_of(name);
+        if module.name == self.module.name && module.computed_constants.contains_key(&id) {
+            return self.eval_constant(id).map(Some);
+        }
+        let constant = module
+            .constants
+            .get(&id)
+            .or_else(|| self.imported(module, id, |m| m.constants.get(&id)));
+        Ok(constant.map(|constant| constant.value.clone()))
+    }
+
+    /// Returns the module in which `name` is resolved
+    fn module_of(&self, name: ResolvableIdentifier) -> &'a Module {
+        let module = name.module().unwrap_or(self.current);
+        if module == self.module.name {
+            self.module
+        } else {
+            self.library.get(&module).expect("modules are loaded after their imports")
+        }
+    }
+
+    /// Looks up `name` among the items `module` imports from other modules
+    fn imported<T: 'a>(
+        &self,
+        module: &'a Module,
+        name: Identifier,
+        get: impl Fn(&'a Module) -> Option<&'a T>,
+    ) -> Option<&'a T> {
+        module
+            .imports
+            .values()
+            .filter(|import| match import {
+                Import::All { .. } => true,
+                Import::Partial { items, .. } => items.contains(&name),
+            })
+            .filter_map(|import| self.library.get(&import.module()))
+            .find_map(get)
+    }
+
+    fn eval_range(&mut self, range: &RangeExpr) -> Result<(usize, usize), SemanticAnalysisError> {
+        let start = self.eval_bound(&range.start)?;
+        let end = self.eval_bound(&range.end)?;
+        if start > end {
+            return Err(self.error(range.span(), "the start of this range is after its end"));
+        }
+        Ok((start, end))
+    }
+
+    fn eval_bound(&mut self, bound: &RangeBound) -> Result<usize, SemanticAnalysisError> {
+        match bound {
+            RangeBound::Const(value) => Ok(value.item),
+            RangeBound::SymbolAccess(access) => match self.lookup(access.name)? {
+                Some(ConstantExpr::Scalar(value)) => usize::try_from(value)
+                    .map_err(|_| self.error(access.span(), "this range bound is too large")),
+                Some(value) => Err(self.error(
+                    access.span(),
+                    format!("expected a value of type felt, got a value of type {}", value.ty()),
+                )),
+                None => Err(self.non_constant(access.span())),
+            },
+        }
+    }
+
+    fn eval_call(&mut self, call: &Call) -> Result<ConstantExpr, SemanticAnalysisError> {
+        if call.is_builtin() {
+            return self.eval_builtin(call).map(ConstantExpr::Scalar);
+        }
+
+        let name = call.callee.namespaced().id();
+        let module = self.module_of(call.callee);
+        let function = module
+            .functions
+            .get(&name)
+            .or_else(|| self.imported(module, name, |m| m.functions.get(&name)));
+        let Some(function) = function else {
+            return Err(self.error(call.span(), format!("`{name}` is not a pure function")));
+        };
+        if call.args.len() != function.params.len() {
+            return Err(self.error(
+                call.span(),
+                format!("expected {} arguments, got {}", function.params.len(), call.args.len()),
+            ));
+        }
+        if self.depth == MAX_CALL_DEPTH {
+            return Err(self.error(
+                call.span(),
+                format!("this call exceeds the limit of {MAX_CALL_DEPTH} nested calls"),
+            ));
+        }
+
+        let args =
+            call.args.iter().map(|arg| self.eval_expr(arg)).collect::<Result<Vec<_>, _>>()?;
+        for ((_, ty), (arg, value)) in function.params.iter().zip(call.args.iter().zip(&args)) {
+            if value.ty() != *ty {
+                return Err(self.error(
+                    arg.span(),
+                    format!("expected a value of type {ty}, got a value of type {}", value.ty()),
+                ));
+            }
+        }
+
+        // The body of the function only sees its parameters, and resolves other names in the
+        // module it is defined in
+        let current = mem::replace(&mut self.current, module.name);
+        let locals = mem::take(&mut self.locals);
+        self.locals.enter();
+        for ((param, _), value) in function.params.iter().zip(args) {
+            self.locals.insert(*param, value);
+        }
+        self.depth += 1;
+        let value = self.eval_block(&function.body);
+        self.depth -= 1;
+        self.locals = locals;
+        self.current = current;
+        value
+    }
+
+    /// Evaluates a call to `sum`, `prod` or `combine`
+    fn eval_builtin(&mut self, call: &Call) -> Result<u64, SemanticAnalysisError> {
+        let (op, init) = match call.callee.namespaced().id().name() {
+            crate::symbols::Sum => (BinaryOp::Add, 0),
+            crate::symbols::Prod => (BinaryOp::Mul, 1),
+            crate::symbols::Combine => return self.eval_combine(call),
+            name => unreachable!("unknown builtin function {name}"),
+        };
+        let [arg] = call.args.as_slice() else {
+            return Err(self.error(call.span(), "expected a single argument"));
+        };
+        let elems = self.eval_vector(arg)?;
+        elems.into_iter().try_fold(init, |acc, elem| {
+            self.fold(call.span(), op, Span::new(call.span(), acc), Span::new(arg.span(), elem))
+        })
+    }
+
+    /// Evaluates a call to `combine(t, alphas)`, i.e. `alphas[0] + Σ t[j] * alphas[j + 1]`
+    fn eval_combine(&mut self, call: &Call) -> Result<u64, SemanticAnalysisError> {
+        let [tuple, alphas] = call.args.as_slice() else {
+            return Err(self.error(call.span(), "expected two arguments"));
+        };
+        let elems = self.eval_vector(tuple)?;
+        let coefficients = self.eval_vector(alphas)?;
+        if coefficients.len() <= elems.len() {
+            return Err(self.error(
+                alphas.span(),
+                format!(
+                    "expected at least {} coefficients, got {}",
+                    elems.len() + 1,
+                    coefficients.len()
+                ),
+            ));
+        }
+        let span = call.span();
+        elems
+            .into_iter()
+            .zip(&coefficients[1..])
+            .try_fold(coefficients[0], |acc, (elem, alpha)| {
+                let term = self.fold(
+                    span,
+                    BinaryOp::Mul,
+                    Span::new(tuple.span(), elem),
+                    Span::new(alphas.span(), *alpha),
+                )?;
+                self.fold(span, BinaryOp::Add, Span::new(span, acc), Span::new(span, term))
+            })
+    }
+
+    /// Evaluates `expr`, which must produce a vector
+    fn eval_vector(&mut self, expr: &Expr) -> Result<Vec<u64>, SemanticAnalysisError> {
+        match self.eval_expr(expr)? {
+            ConstantExpr::Vector(elems) => Ok(elems),
+            value => Err(self.error(
+                expr.span(),
+                format!("expected a vector, got a value of type {}", value.ty()),
+            )),
+        }
+    }
+
+    fn eval_block(&mut self, block: &[Statement]) -> Result<ConstantExpr, SemanticAnalysisError> {
+        match block.first() {
+            Some(Statement::Let(expr)) => self.eval_let(expr),
+            Some(Statement::Expr(expr)) => self.eval_expr(expr),
+            Some(stmt) => {
+                Err(self.error(stmt.span(), "constraints cannot be evaluated at compile time"))
+            },
+            None => unreachable!("function bodies end with a return statement"),
+        }
+    }
+
+    fn eval_let(&mut self, expr: &Let) -> Result<ConstantExpr, SemanticAnalysisError> {
+        let value = self.eval_expr(&expr.value)?;
+        self.locals.enter();
+        self.locals.insert(expr.name, value);
+        let value = self.eval_block(&expr.body);
+        self.locals.exit();
+        value
+    }
+
+    fn eval_comprehension(
+        &mut self,
+        lc: &ListComprehension,
+    ) -> Result<ConstantExpr, SemanticAnalysisError> {
+        if let Some(selector) = lc.selector.as_ref() {
+            return Err(self.error(
+                selector.span(),
+                "selectors are not supported in comprehensions evaluated at compile time",
+            ));
+        }
+
+        let mut iterables = Vec::with_capacity(lc.iterables.len());
+        for iterable in lc.iterables.iter() {
+            let elems = match self.eval_expr(iterable)? {
+                ConstantExpr::Vector(elems) => {
+                    elems.into_iter().map(ConstantExpr::Scalar).collect()
+                },
+                ConstantExpr::Matrix(rows) => rows.into_iter().map(ConstantExpr::Vector).collect(),
+                value => {
+                    return Err(self.error(
+                        iterable.span(),
+                        format!("expected an iterable, got a value of type {}", value.ty()),
+                    ));
+                },
+            };
+            iterables.push(elems);
+        }
+        let len = iterables.first().map(Vec::len).unwrap_or_default();
+        if let Some(iterable) = iterables.iter().position(|elems| elems.len() != len) {
+            return Err(self.error(
+                lc.iterables[iterable].span(),
+                format!(
+                    "expected an iterable of length {len}, as the first one of this comprehension"
+                ),
+            ));
+        }
+
+        self.iterations += len;
+        if self.iterations > MAX_ITERATIONS {
+            return Err(self.error(
+                lc.span(),
+                format!(
+                    "this comprehension exceeds the limit of {MAX_ITERATIONS} iterations in the \
+                     evaluation of constants"
+                ),
+            ));
+        }
+
+        let mut values = Vec::with_capacity(len);
+        for i in 0..len {
+            self.locals.enter();
+            for (binding, elems) in lc.bindings.iter().zip(iterables.iter()) {
+                self.locals.insert(*binding, elems[i].clone());
+            }
+            let value = self.eval_scalar(&lc.body);
+            self.locals.exit();
+            values.push(value?);
+        }
+        Ok(ConstantExpr::Vector(values))
+    }
+
+    /// Builds a vector from scalars, or a matrix from vectors
+    fn aggregate(
+        &self,
+        span: SourceSpan,
+        values: Vec<ConstantExpr>,
+    ) -> Result<ConstantExpr, SemanticAnalysisError> {
+        if values.iter().all(|value| matches!(value, ConstantExpr::Scalar(_))) {
+            let elems = values
+                .into_iter()
+                .map(|value| match value {
+                    ConstantExpr::Scalar(value) => value,
+                    _ => unreachable!(),
+                })
+                .collect();
+            return Ok(ConstantExpr::Vector(elems));
+        }
+
+        let rows = values
+            .into_iter()
+            .map(|value| match value {
+                ConstantExpr::Vector(row) => Some(row),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        match rows {
+            Some(rows) if rows.iter().all(|row| row.len() == rows[0].len()) => {
+                Ok(ConstantExpr::Matrix(rows))
+            },
+            _ => Err(self.error(span, "the elements of this vector are not all of the same type")),
+        }
+    }
+
+    /// Reports a value which is not known at compile time
+    fn non_constant(&self, span: SourceSpan) -> SemanticAnalysisError {
+        self.diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid constant declaration")
+            .with_primary_label(span, "this value is not known at compile time")
+            .with_secondary_label(self.pending_span(), "when evaluating this constant")
+            .with_note(
+                "The initializer of a constant may only refer to constants, and to the parameters \
+                 and local bindings of the functions it calls.",
+            )
+            .emit();
+        SemanticAnalysisError::Invalid
+    }
+
+    fn error(&self, span: SourceSpan, label: impl ToString) -> SemanticAnalysisError {
+        self.diagnostics
+            .diagnostic(Severity::Error)
+            .with_message("invalid constant declaration")
+            .with_primary_label(span, label)
+            .with_secondary_label(self.pending_span(), "when evaluating this constant")
+            .emit();
+        SemanticAnalysisError::Invalid
+    }
+
+    /// Returns the span of the innermost constant being evaluated
+    fn pending_span(&self) -> SourceSpan {
+        let name = self.pending.last().expect("errors are raised while evaluating a constant");
+        self.module.computed_constants[name].span()
+    }
+}
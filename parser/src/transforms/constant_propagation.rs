@@ -60,6 +60,25 @@ impl<'a> ConstantPropagation<'a> {
         }
     }
 
+    /// Checks that the set of a call to `in_set`, whose arguments have been folded, is a vector of
+    /// constants, as it is expanded to the product of the differences with each of its elements.
+    fn check_in_set(&self, call: &Call) -> ControlFlow<SemanticAnalysisError> {
+        match call.args.get(1) {
+            Some(Expr::Const(set)) if matches!(set.item, ConstantExpr::Vector(_)) => {
+                ControlFlow::Continue(())
+            },
+            set => {
+                let span = set.map(|set| set.span()).unwrap_or_else(|| call.span());
+                self.diagnostics
+                    .diagnostic(miden_diagnostics::Severity::Error)
+                    .with_message("invalid call")
+                    .with_primary_label(span, "the set of `in_set` must be a vector of constants")
+                    .emit();
+                ControlFlow::Break(SemanticAnalysisError::Invalid)
+            },
+        }
+    }
+
     fn run_visitor(&mut self, program: &mut Program) -> ControlFlow<SemanticAnalysisError> {
         // Record all of the constant declarations
         for (name, constant) in program.constants.iter() {
@@ -86,7 +105,14 @@ impl<'a> ConstantPropagation<'a> {
 
         // Visit all of the constraints
         self.visit_mut_boundary_constraints(&mut program.boundary_constraints)?;
-        self.visit_mut_integrity_constraints(&mut program.integrity_constraints)
+        self.visit_mut_integrity_constraints(&mut program.integrity_constraints)?;
+
+        // Visit the definitions of the virtual columns
+        for column in program.virtual_columns.iter_mut() {
+            self.visit_mut_scalar_expr(&mut column.definition)?;
+        }
+
+        ControlFlow::Continue(())
     }
 
     fn try_fold_binary_expr(
@@ -100,6 +126,17 @@ impl<'a> ConstantPropagation<'a> {
         if let ControlFlow::Break(err) = self.visit_mut_scalar_expr(expr.rhs.as_mut()) {
             return Err(err);
         }
+        // Division by a constant is rewritten as multiplication by the inverse of that constant
+        if expr.op == BinaryOp::Div && !expr.lhs.is_constant() {
+            if let ScalarExpr::Const(denominator) = expr.rhs.as_ref() {
+                let span = denominator.span();
+                let inverse = field_inv(denominator.item).ok_or(
+                    SemanticAnalysisError::InvalidExpr(InvalidExprError::DivisionByZero(span)),
+                )?;
+                expr.op = BinaryOp::Mul;
+                *expr.rhs = ScalarExpr::Const(Span::new(span, inverse));
+            }
+        }
         // If both operands are constant, fold
         try_fold_binary_expr(expr).map_err(SemanticAnalysisError::InvalidExpr)
     }
@@ -184,9 +221,10 @@ impl VisitMut<SemanticAnalysisError> for ConstantPropagation<'_> {
     ) -> ControlFlow<SemanticAnalysisError> {
         match expr {
             // Expression is already folded
-            ScalarExpr::Const(_) | ScalarExpr::Null(_) | ScalarExpr::Unconstrained(_) => {
-                ControlFlow::Continue(())
-            },
+            ScalarExpr::Const(_)
+            | ScalarExpr::Null(_)
+            | ScalarExpr::Unconstrained(_)
+            | ScalarExpr::RowComprehension(_) => ControlFlow::Continue(()),
             // Need to check if this access is to a constant value, and transform to a constant if
             // so
             ScalarExpr::SymbolAccess(sym) => {
@@ -256,6 +294,26 @@ impl VisitMut<SemanticAnalysisError> for ConstantPropagation<'_> {
                     Err(err) => ControlFlow::Break(err),
                 }
             },
+            // The set of `in_set` must be folded to a constant vector
+            ScalarExpr::Call(call)
+                if call.is_builtin() && call.callee.as_ref().name() == symbols::InSet =>
+            {
+                self.visit_mut_call(call)?;
+                self.check_in_set(call)
+            },
+            // Calls to `combine` are expanded, and the expansion folded
+            ScalarExpr::Call(call)
+                if call.is_builtin() && call.callee.as_ref().name() == symbols::Combine =>
+            {
+                self.visit_mut_call(call)?;
+                let mut combined = match expand_combine(call) {
+                    Ok(combined) => combined,
+                    Err(err) => return ControlFlow::Break(SemanticAnalysisError::InvalidExpr(err)),
+                };
+                self.visit_mut_scalar_expr(&mut combined)?;
+                *expr = combined;
+                ControlFlow::Continue(())
+            },
             // While calls cannot be constant folded, arguments can be
             ScalarExpr::Call(call) => self.visit_mut_call(call),
             // This cannot be constant folded
@@ -371,6 +429,7 @@ impl VisitMut<SemanticAnalysisError> for ConstantPropagation<'_> {
                                     ConstantExpr::Scalar(value[row][col]),
                                 ));
                             },
+                            AccessType::Bound(_) => unreachable!(),
                         },
                     }
                 } else {
@@ -402,6 +461,22 @@ impl VisitMut<SemanticAnalysisError> for ConstantPropagation<'_> {
                             }
                         }
                     },
+                    symbols::InSet => self.check_in_set(call)?,
+                    symbols::Combine => {
+                        let mut combined = match expand_combine(call) {
+                            Ok(combined) => combined,
+                            Err(err) => {
+                                return ControlFlow::Break(SemanticAnalysisError::InvalidExpr(err));
+                            },
+                        };
+                        self.visit_mut_scalar_expr(&mut combined)?;
+                        *expr = match Expr::try_from(combined) {
+                            Ok(combined) => combined,
+                            Err(err) => {
+                                return ControlFlow::Break(SemanticAnalysisError::InvalidExpr(err));
+                            },
+                        };
+                    },
                     invalid => unimplemented!("unknown builtin function: {invalid}"),
                 }
                 ControlFlow::Continue(())
@@ -711,11 +786,10 @@ impl VisitMut<SemanticAnalysisError> for ConstantPropagation<'_> {
 
 /// This function attempts to folds a binary operator expression into a constant value.
 ///
-/// If the operands are both constant, the operator is applied, and if the result does not
-/// overflow/underflow, then `Ok(Some)` is returned with the result of the evaluation.
+/// If the operands are both constant, the operator is applied in the field, and `Ok(Some)` is
+/// returned with the reduced result of the evaluation, e.g. `0 - 2` folds to `p - 2`.
 ///
-/// If the operands are not both constant, or the operation would overflow/underflow, then
-/// `Ok(None)` is returned.
+/// If the operands are not both constant, then `Ok(None)` is returned.
 ///
 /// If the operands are constant, or there is some validation error with the expression,
 /// `Err(InvalidExprError)` will be returned.
@@ -725,23 +799,77 @@ pub(crate) fn try_fold_binary_expr(
     // If both operands are constant, fold
     if let (ScalarExpr::Const(l), ScalarExpr::Const(r)) = (expr.lhs.as_ref(), expr.rhs.as_ref()) {
         let folded = match expr.op {
-            BinaryOp::Add => l.item.checked_add(r.item),
-            BinaryOp::Sub => l.item.checked_sub(r.item),
-            BinaryOp::Mul => l.item.checked_mul(r.item),
-            BinaryOp::Exp => match r.item.try_into() {
-                Ok(exp) => l.item.checked_pow(exp),
+            BinaryOp::Add => field_add(l.item, r.item),
+            BinaryOp::Sub => field_add(l.item, field_neg(r.item)),
+            BinaryOp::Mul => field_mul(l.item, r.item),
+            BinaryOp::Div => match field_inv(r.item) {
+                Some(inverse) => field_mul(l.item, inverse),
+                None => return Err(InvalidExprError::DivisionByZero(expr.rhs.span())),
+            },
+            BinaryOp::Exp => match u32::try_from(r.item) {
+                Ok(exp) => field_pow(l.item, exp.into()),
                 Err(_) => return Err(InvalidExprError::InvalidExponent(expr.span())),
             },
             // This op cannot be folded
             BinaryOp::Eq => return Ok(None),
         };
-        Ok(folded.map(|v| Span::new(expr.span(), v)))
+        Ok(Some(Span::new(expr.span(), folded)))
     } else {
-        // If we observe a non-constant power in an exponentiation operation, raise an error
-        if expr.op == BinaryOp::Exp && !expr.rhs.is_constant() {
-            Err(InvalidExprError::NonConstantExponent(expr.rhs.span()))
-        } else {
-            Ok(None)
+        // If we observe a non-constant power in an exponentiation operation, or a non-constant
+        // denominator in a division, raise an error
+        match expr.op {
+            BinaryOp::Exp if !expr.rhs.is_constant() => {
+                Err(InvalidExprError::NonConstantExponent(expr.rhs.span()))
+            },
+            BinaryOp::Div if !expr.rhs.is_constant() => {
+                Err(InvalidExprError::NonConstantDenominator(expr.rhs.span()))
+            },
+            _ => Ok(None),
         }
     }
 }
+
+/// Expands a call to `combine(t, alphas)`, whose arguments have already been validated during
+/// semantic analysis, to `alphas[0] + t[0] * alphas[1] + .. + t[n-1] * alphas[n]`.
+///
+/// This is the reduction of a tuple to a single element which is used by the expansion of buses,
+/// and which can be used to write permutation arguments by hand.
+fn expand_combine(call: &Call) -> Result<ScalarExpr, InvalidExprError> {
+    let span = call.span();
+    let [tuple, alphas] = call.args.as_slice() else {
+        panic!("invalid arguments to builtin `combine`: {:#?}", &call.args);
+    };
+    let tuple = vector_elements(tuple)?;
+    let mut alphas = vector_elements(alphas)?.into_iter();
+    let alpha = alphas.next().expect("expected at least one coefficient");
+    Ok(tuple.into_iter().zip(alphas).fold(alpha, |acc, (elem, alpha)| {
+        let term = BinaryExpr::new(span, BinaryOp::Mul, elem, alpha);
+        ScalarExpr::Binary(BinaryExpr::new(span, BinaryOp::Add, acc, ScalarExpr::Binary(term)))
+    }))
+}
+
+/// Returns the elements of `expr`, which must be a vector literal, or a reference to a vector.
+fn vector_elements(expr: &Expr) -> Result<Vec<ScalarExpr>, InvalidExprError> {
+    match expr {
+        Expr::Const(value) => match &value.item {
+            ConstantExpr::Vector(elems) => Ok(elems
+                .iter()
+                .map(|elem| ScalarExpr::Const(Span::new(value.span(), *elem)))
+                .collect()),
+            _ => Err(InvalidExprError::InvalidScalarExpr(value.span())),
+        },
+        Expr::Vector(elems) => elems.iter().cloned().map(ScalarExpr::try_from).collect(),
+        Expr::SymbolAccess(access) => match access.ty {
+            Some(Type::Vector(len)) => (0..len)
+                .map(|idx| {
+                    let elem = access
+                        .access(AccessType::Index(idx))
+                        .map_err(|_| InvalidExprError::InvalidScalarExpr(access.span()))?;
+                    Ok(ScalarExpr::SymbolAccess(elem))
+                })
+                .collect(),
+            _ => Err(InvalidExprError::InvalidScalarExpr(access.span())),
+        },
+        invalid => Err(InvalidExprError::InvalidScalarExpr(invalid.span())),
+    }
+}
@@ -453,6 +453,12 @@ impl<'a> Inlining<'a> {
                     assert_eq!(call.args.len(), 1);
                     self.expand_fold(BinaryOp::Mul, call.args.pop().unwrap())
                 },
+                symbols::InSet => {
+                    assert_eq!(call.args.len(), 2);
+                    let set = call.args.pop().unwrap();
+                    let value = call.args.pop().unwrap();
+                    self.expand_in_set(call.span(), value, set)
+                },
                 other => unimplemented!("unhandled builtin: {}", other),
             }
         } else {
@@ -494,6 +500,30 @@ impl<'a> Inlining<'a> {
         }))
     }
 
+    /// Expand a call to `in_set` into the product of the differences between `value` and each
+    /// element of `set`, which has been folded to a vector of constants during constant propagation
+    fn expand_in_set(
+        &mut self,
+        span: SourceSpan,
+        value: Expr,
+        set: Expr,
+    ) -> Result<Expr, SemanticAnalysisError> {
+        let Expr::Const(Span { item: ConstantExpr::Vector(set), .. }) = set else {
+            unreachable!("expected the set of `in_set` to have been folded, got {set:#?}")
+        };
+        let value = ScalarExpr::try_from(value).map_err(SemanticAnalysisError::InvalidExpr)?;
+        let product = set
+            .into_iter()
+            .map(|elem| {
+                let elem = ScalarExpr::Const(Span::new(span, elem));
+                ScalarExpr::Binary(BinaryExpr::new(span, BinaryOp::Sub, value.clone(), elem))
+            })
+            .reduce(|acc, diff| ScalarExpr::Binary(BinaryExpr::new(span, BinaryOp::Mul, acc, diff)))
+            .expect("expected a non-empty set");
+        let product = self.expand_scalar_expr(product)?;
+        Expr::try_from(product).map_err(SemanticAnalysisError::InvalidExpr)
+    }
+
     /// Expand a list folding operation (e.g. sum/prod) over an expression of aggregate type into an
     /// equivalent expression tree
     fn expand_fold(&mut self, op: BinaryOp, list: Expr) -> Result<Expr, SemanticAnalysisError> {
@@ -545,9 +575,19 @@ impl<'a> Inlining<'a> {
                     },
                 }
             },
-            // Constant propagation will have already folded calls to list-folding builtins
-            // with constant arguments, so we should panic if we ever see one here
-            Expr::Const(_) => panic!("expected constant to have been folded"),
+            // Constant propagation folds the calls to list-folding builtins with constant
+            // arguments in expressions, but only folds the argument of those in the scalar
+            // expressions of constraints, e.g. `enf a = sum(RCS[6..8])`
+            Expr::Const(constant) => match constant.item {
+                ConstantExpr::Vector(elems) => {
+                    let mut vector = elems
+                        .into_iter()
+                        .map(|elem| Expr::Const(Span::new(span, ConstantExpr::Scalar(elem))))
+                        .collect();
+                    self.expand_vector_fold(span, op, &mut vector)
+                },
+                invalid => panic!("invalid argument to list folding builtin: {invalid:#?}"),
+            },
             // All other invalid expressions should have been caught by now
             invalid => panic!("invalid argument to list folding builtin: {invalid:#?}"),
         }
@@ -561,9 +601,13 @@ impl<'a> Inlining<'a> {
         op: BinaryOp,
         vector: &mut Vec<Expr>,
     ) -> Result<Expr, SemanticAnalysisError> {
-        // To expand this fold, we simply produce a nested sequence of BinaryExpr
+        // To expand this fold, we simply produce a nested sequence of BinaryExpr, starting from
+        // the first element. The fold of an empty vector is the identity of the operator.
         let mut elems = vector.drain(..);
-        let mut acc = elems.next().unwrap();
+        let Some(mut acc) = elems.next() else {
+            let identity = if op == BinaryOp::Mul { 1 } else { 0 };
+            return Ok(Expr::Const(Span::new(span, ConstantExpr::Scalar(identity))));
+        };
         self.rewrite_expr(&mut acc)?;
         let mut acc: ScalarExpr = acc.try_into().map_err(SemanticAnalysisError::InvalidExpr)?;
         for mut elem in elems {
@@ -683,6 +727,9 @@ impl<'a> Inlining<'a> {
                     BinaryOp::Exp if !rhs.is_constant() => Err(SemanticAnalysisError::InvalidExpr(
                         InvalidExprError::NonConstantExponent(rhs.span()),
                     )),
+                    BinaryOp::Div if !rhs.is_constant() => Err(SemanticAnalysisError::InvalidExpr(
+                        InvalidExprError::NonConstantDenominator(rhs.span()),
+                    )),
                     _ => Ok(()),
                 }
             },
@@ -711,7 +758,10 @@ impl<'a> Inlining<'a> {
                 }
                 Ok(())
             },
-            ScalarExpr::BusOperation(_) | ScalarExpr::Null(_) | ScalarExpr::Unconstrained(_) => {
+            ScalarExpr::BusOperation(_)
+            | ScalarExpr::Null(_)
+            | ScalarExpr::Unconstrained(_)
+            | ScalarExpr::RowComprehension(_) => {
                 self.diagnostics
                     .diagnostic(Severity::Error)
                     .with_message("buses are not implemented for this Pipeline")
@@ -1559,7 +1609,7 @@ fn eval_expr_binding_type(
     match expr {
         Expr::Const(constant) => Ok(BindingType::Local(constant.ty())),
         Expr::Range(range) => Ok(BindingType::Local(Type::Vector(range.to_slice_range().len()))),
-        Expr::Vector(elems) => match elems[0].ty() {
+        Expr::Vector(elems) => match elems.first().and_then(|elem| elem.ty()) {
             None | Some(Type::Felt) => {
                 let mut binding_tys = Vec::with_capacity(elems.len());
                 for elem in elems.iter() {
@@ -1634,6 +1684,19 @@ fn eval_let_binding_ty(
     Ok(binding_ty)
 }
 
+/// Applies the row offset of an access to the element it was resolved to.
+///
+/// Only trace accesses can be offset, the element is returned unchanged otherwise.
+fn apply_offset(expr: ScalarExpr, offset: usize) -> ScalarExpr {
+    match expr {
+        ScalarExpr::SymbolAccess(mut access) if offset > 0 => {
+            access.offset = offset;
+            ScalarExpr::SymbolAccess(access)
+        },
+        expr => expr,
+    }
+}
+
 /// This visitor is used to rewrite uses of iterable bindings within a comprehension body,
 /// including expansion of constant accesses.
 struct RewriteIterableBindingsVisitor<'a> {
@@ -1686,13 +1749,16 @@ impl RewriteIterableBindingsVisitor<'_> {
             },
             Some(Expr::Vector(elems)) => {
                 match access.access_type {
-                    AccessType::Index(idx) => Some(elems[idx].clone().try_into().unwrap()),
+                    AccessType::Index(idx) => {
+                        Some(apply_offset(elems[idx].clone().try_into().unwrap(), access.offset))
+                    },
                     // This implies that the vector contains an element which is vector-like,
                     // if the value at `idx` is not, this is an invalid access
                     AccessType::Matrix(idx, nested_idx) => match &elems[idx] {
                         Expr::SymbolAccess(saccess) => {
-                            let access = saccess.access(AccessType::Index(nested_idx)).unwrap();
-                            self.rewrite_scalar_access(access)?
+                            let mut nested = saccess.access(AccessType::Index(nested_idx)).unwrap();
+                            nested.offset = access.offset;
+                            self.rewrite_scalar_access(nested)?
                         },
                         invalid => panic!(
                             "expected vector-like value at {}[{idx}], got: {invalid:#?}",
@@ -1705,7 +1771,9 @@ impl RewriteIterableBindingsVisitor<'_> {
                 }
             },
             Some(Expr::Matrix(elems)) => match access.access_type {
-                AccessType::Matrix(row, col) => Some(elems[row][col].clone()),
+                AccessType::Matrix(row, col) => {
+                    Some(apply_offset(elems[row][col].clone(), access.offset))
+                },
                 invalid => {
                     panic!("expected matrix to be reduced to scalar by access, got {invalid:#?}")
                 },
@@ -1782,9 +1850,10 @@ impl VisitMut<SemanticAnalysisError> for RewriteIterableBindingsVisitor<'_> {
             // the case that we encounter a let here, as they can only be introduced in scalar
             // expression position as a result of inlining/expansion
             ScalarExpr::Let(_) => unreachable!(),
-            ScalarExpr::BusOperation(_) | ScalarExpr::Null(_) | ScalarExpr::Unconstrained(_) => {
-                ControlFlow::Break(SemanticAnalysisError::Invalid)
-            },
+            ScalarExpr::BusOperation(_)
+            | ScalarExpr::Null(_)
+            | ScalarExpr::Unconstrained(_)
+            | ScalarExpr::RowComprehension(_) => ControlFlow::Break(SemanticAnalysisError::Invalid),
         }
     }
 }
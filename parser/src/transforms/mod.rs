@@ -1,4 +1,6 @@
+mod constant_evaluation;
 mod constant_propagation;
 mod inlining;
 
+pub(crate) use self::constant_evaluation::ConstantEvaluation;
 pub use self::{constant_propagation::ConstantPropagation, inlining::Inlining};
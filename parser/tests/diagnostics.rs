@@ -0,0 +1,70 @@
+//! Snapshot tests of the diagnostics rendered for invalid programs.
+//!
+//! Each `.air` file in `tests/diagnostics` is parsed and analyzed, and the rendered diagnostics are
+//! compared against the `.stderr` file of the same name. Run the tests with `UPDATE_EXPECT=1` to
+//! update the snapshots after an intentional change to the diagnostics.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use miden_diagnostics::{
+    CaptureEmitter, CodeMap, DiagnosticsConfig, DiagnosticsHandler, Verbosity,
+};
+
+/// Parses the program at `path`, and returns the rendered diagnostics.
+///
+/// The source is parsed from a string rather than from its file, so that the rendered diagnostics
+/// do not depend on the location of the repository.
+fn render_diagnostics(path: &Path) -> String {
+    let source = fs::read_to_string(path).unwrap();
+    let codemap = Arc::new(CodeMap::new());
+    let emitter = Arc::new(CaptureEmitter::new());
+    let config = DiagnosticsConfig {
+        verbosity: Verbosity::Warning,
+        warnings_as_errors: true,
+        no_warn: false,
+        display: Default::default(),
+    };
+    let diagnostics = DiagnosticsHandler::new(config, codemap.clone(), emitter.clone());
+
+    match air_parser::parse(&diagnostics, codemap, &source) {
+        Ok(_) => panic!("expected {} to be invalid", path.display()),
+        Err(err) => diagnostics.emit(err),
+    }
+    emitter.captured()
+}
+
+#[test]
+fn diagnostics_snapshots() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/diagnostics");
+    let update = std::env::var_os("UPDATE_EXPECT").is_some();
+
+    let mut sources: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "air"))
+        .collect();
+    sources.sort();
+    assert!(!sources.is_empty(), "no programs found in {}", dir.display());
+
+    let mut mismatches = vec![];
+    for source in sources {
+        let rendered = render_diagnostics(&source);
+        let snapshot = source.with_extension("stderr");
+        if update {
+            fs::write(&snapshot, &rendered).unwrap();
+        } else if fs::read_to_string(&snapshot).ok().as_deref() != Some(rendered.as_str()) {
+            eprintln!("--- {}\n{rendered}", snapshot.display());
+            mismatches.push(snapshot);
+        }
+    }
+    assert!(
+        mismatches.is_empty(),
+        "the diagnostics of {} programs do not match their snapshot, run with UPDATE_EXPECT=1 to \
+         update them: {mismatches:#?}",
+        mismatches.len()
+    );
+}